@@ -19,6 +19,7 @@ const PATCHES: &[&str] = &[
     include_str!("../patches/fire-and-smoke.yml"),
     include_str!("../patches/instance.yml"),
     include_str!("../patches/joint-instance.yml"),
+    include_str!("../patches/lighting.yml"),
     include_str!("../patches/localization-table.yml"),
     include_str!("../patches/parts.yml"),
     include_str!("../patches/players.yml"),
@@ -34,6 +35,11 @@ pub struct PropertyPatches {
 
     #[serde(default)]
     pub add: HashMap<String, HashMap<String, PropertyAdd>>,
+
+    /// Enum values from older files mapped to the values that replaced them,
+    /// keyed by enum name.
+    #[serde(default)]
+    pub migrate_enum_values: HashMap<String, HashMap<u32, u32>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -123,6 +129,9 @@ impl PropertyPatches {
 
             all_patches.change.extend(parsed.change);
             all_patches.add.extend(parsed.add);
+            all_patches
+                .migrate_enum_values
+                .extend(parsed.migrate_enum_values);
         }
 
         Ok(all_patches)
@@ -197,6 +206,35 @@ impl PropertyPatches {
             }
         }
 
+        for (enum_name, migrations) in &self.migrate_enum_values {
+            let descriptor = database.enums.get_mut(enum_name.as_str()).ok_or_else(|| {
+                anyhow!(
+                    "Enum {} migrated in patch file did not exist in database",
+                    enum_name
+                )
+            })?;
+
+            for (&old_value, &new_value) in migrations {
+                if !descriptor.items.values().any(|&value| value == new_value) {
+                    bail!(
+                        "Enum {} value {} is migrated to {}, which isn't one of its items",
+                        enum_name,
+                        old_value,
+                        new_value
+                    );
+                }
+
+                log::debug!(
+                    "Enum {} value {} migrated to {}",
+                    enum_name,
+                    old_value,
+                    new_value
+                );
+
+                descriptor.value_migrations.insert(old_value, new_value);
+            }
+        }
+
         Ok(())
     }
 }
@@ -1,52 +1,248 @@
 use std::{
+    borrow::Cow,
+    convert::TryFrom,
     fmt,
     io::{self, Read, Write},
     str,
+    sync::Arc,
 };
 
-use crate::core::{RbxReadExt, RbxWriteExt};
+use rbx_dom_weak::types::{Blob, BlobStore};
+
+use crate::core::{preallocation, RbxReadExt, RbxWriteExt};
 
 /// Represents one chunk from a binary model file.
 #[derive(Debug)]
 pub struct Chunk {
     pub name: [u8; 4],
     pub data: Vec<u8>,
+
+    /// The chunk's contents, if they were copied into a blob store instead of
+    /// being read into `data`.
+    pub stored: Option<Blob>,
+
+    /// The number of bytes the chunk takes up in the file, including its
+    /// header.
+    pub encoded_len: u64,
 }
 
 impl Chunk {
     /// Reads and decodes a `Chunk` from the given reader.
-    pub fn decode<R: Read>(mut reader: R) -> io::Result<Chunk> {
+    pub fn decode<R: Read>(reader: R) -> io::Result<Chunk> {
+        Self::decode_inner(reader, None, &[])
+    }
+
+    /// Reads and decodes a `Chunk` like `decode`, except that a chunk named in
+    /// `skip` is read past without being decompressed, and comes back with no
+    /// data.
+    pub fn decode_skipping<R: Read>(reader: R, skip: &[&[u8; 4]]) -> io::Result<Chunk> {
+        Self::decode_inner(reader, None, skip)
+    }
+
+    /// Reads and decodes a `Chunk` like `decode`, except that an uncompressed
+    /// PROP or SSTR chunk longer than `threshold` is copied into `store`
+    /// instead of into memory. Those are the only chunks that can hold values
+    /// large enough to be kept in a store.
+    pub fn decode_to_store<R: Read>(
+        reader: R,
+        store: &Arc<dyn BlobStore>,
+        threshold: usize,
+    ) -> io::Result<Chunk> {
+        Self::decode_inner(reader, Some((store, threshold)), &[])
+    }
+
+    fn decode_inner<R: Read>(
+        mut reader: R,
+        store: Option<(&Arc<dyn BlobStore>, usize)>,
+        skip: &[&[u8; 4]],
+    ) -> io::Result<Chunk> {
+        detail_span!("read_chunk");
+
         let header = decode_chunk_header(&mut reader)?;
 
         log::trace!("{}", header);
+        debug_event!(
+            chunk = %String::from_utf8_lossy(&header.name),
+            compressed_len = header.compressed_len,
+            len = header.len,
+            "read chunk"
+        );
+
+        let stored_len = if header.compressed_len == 0 {
+            header.len
+        } else {
+            header.compressed_len
+        };
+
+        let encoded_len = CHUNK_HEADER_LEN as u64 + u64::from(stored_len);
+
+        if skip.contains(&&header.name) {
+            let skipped = io::copy(&mut reader.take(u64::from(stored_len)), &mut io::sink())?;
+
+            if skipped != u64::from(stored_len) {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "{} had {} bytes of data, but should have had {}",
+                        header, skipped, stored_len
+                    ),
+                ));
+            }
+
+            return Ok(Chunk {
+                name: header.name,
+                data: Vec::new(),
+                stored: None,
+                encoded_len,
+            });
+        }
+
+        if let Some((store, threshold)) = store {
+            let storable = &header.name == b"PROP" || &header.name == b"SSTR";
+
+            if storable && header.compressed_len == 0 && header.len as usize > threshold {
+                let stored = Blob::store(store, reader.take(header.len as u64))?;
+
+                if stored.len() != header.len as u64 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "{} had {} bytes of data, but should have had {}",
+                            header,
+                            stored.len(),
+                            header.len
+                        ),
+                    ));
+                }
+
+                return Ok(Chunk {
+                    name: header.name,
+                    data: Vec::new(),
+                    stored: Some(stored),
+                    encoded_len,
+                });
+            }
+        }
 
         let data = if header.compressed_len == 0 {
-            let mut data = Vec::with_capacity(header.len as usize);
+            let mut data = Vec::with_capacity(preallocation(header.len as usize));
             reader.take(header.len as u64).read_to_end(&mut data)?;
             data
         } else {
-            let mut compressed_data = Vec::with_capacity(header.compressed_len as usize);
+            let mut compressed_data =
+                Vec::with_capacity(preallocation(header.compressed_len as usize));
             reader
                 .take(header.compressed_len as u64)
                 .read_to_end(&mut compressed_data)?;
 
+            if compressed_data.starts_with(&ZSTD_MAGIC) {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!(
+                        "{} is compressed with ZSTD, which rbx_binary can't read",
+                        header
+                    ),
+                ));
+            }
+
+            // LZ4 sets aside all of the space it's told the data will take, so
+            // a damaged header could otherwise ask it for gigabytes.
+            let max_len = compressed_data.len() as u64 * MAX_COMPRESSION_RATIO;
+            if header.len as u64 > max_len || header.len > i32::MAX as u32 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{} claims to decompress to {} bytes, which is more than {} bytes of \
+                         LZ4 data can hold",
+                        header,
+                        header.len,
+                        compressed_data.len()
+                    ),
+                ));
+            }
+
             lz4::block::decompress(&compressed_data, Some(header.len as i32))?
         };
 
-        assert_eq!(data.len(), header.len as usize);
+        if data.len() != header.len as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} had {} bytes of data, but should have had {}",
+                    header,
+                    data.len(),
+                    header.len
+                ),
+            ));
+        }
 
         Ok(Chunk {
             name: header.name,
             data,
+            stored: None,
+            encoded_len,
         })
     }
 }
 
+/// The size of a chunk's header: its name, compressed and uncompressed
+/// lengths, and four reserved bytes.
+const CHUNK_HEADER_LEN: usize = 16;
+
+/// The names of all of the chunks that rbx_binary understands.
+const KNOWN_CHUNK_NAMES: &[&[u8; 4]] = &[b"META", b"SSTR", b"INST", b"PROP", b"PRNT", b"END\0"];
+
+/// LZ4 can't compress data by more than a factor of 255, which lets us reject
+/// chunk headers that can't possibly be real.
+const MAX_COMPRESSION_RATIO: u64 = 255;
+
+/// Finds the offset of the first position in the given data that looks like
+/// the start of a chunk, used to get back on track after reading a damaged
+/// chunk.
+///
+/// Only chunks that rbx_binary knows about and whose header is consistent with
+/// the amount of data left are considered.
+pub fn find_next_chunk(data: &[u8]) -> Option<usize> {
+    (0..data.len().saturating_sub(CHUNK_HEADER_LEN - 1)).find(|&offset| {
+        let mut header = &data[offset..offset + CHUNK_HEADER_LEN];
+
+        let name = &header[..4];
+        if !KNOWN_CHUNK_NAMES.iter().any(|known| known[..] == *name) {
+            return false;
+        }
+
+        header = &header[4..];
+        let compressed_len = header.read_le_u32().unwrap() as u64;
+        let len = header.read_le_u32().unwrap() as u64;
+        let reserved = header.read_le_u32().unwrap();
+
+        let remaining = (data.len() - offset - CHUNK_HEADER_LEN) as u64;
+
+        if compressed_len == 0 {
+            reserved == 0 && len <= remaining
+        } else {
+            reserved == 0
+                && compressed_len <= remaining
+                && len <= compressed_len * MAX_COMPRESSION_RATIO
+        }
+    })
+}
+
+/// The bytes that ZSTD-compressed data starts with. Newer versions of Roblox
+/// can write chunks compressed this way.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The highest level that LZ4's high compression mode accepts. Higher levels
+/// are treated as this one.
+const MAX_LZ4_LEVEL: u32 = 12;
+
 /// The compression format of a chunk in the binary model format.
 #[derive(Debug, Clone, Copy)]
 pub enum ChunkCompression {
-    /// The contents of the chunk should be LZ4 compressed.
-    Compressed,
+    /// The contents of the chunk should be LZ4 compressed. Level zero uses
+    /// LZ4's default compressor, and higher levels use its high compression
+    /// mode, which is slower but writes smaller chunks.
+    Lz4 { level: u32 },
 
     /// The contents of the chunk should be uncompressed.
     Uncompressed,
@@ -57,11 +253,19 @@ pub enum ChunkCompression {
 /// This type intended to be written into via io::Write and then dumped into the
 /// output stream all at once. It handles compression and chunk header output
 /// automatically.
+///
+/// Values kept in a blob store can be written with `write_blob_string`, which
+/// leaves them in the store until the chunk is dumped. A chunk holding any of
+/// them is written uncompressed, so that it never has to be in memory at once.
 #[must_use]
 pub struct ChunkBuilder {
     chunk_name: &'static [u8],
     compression: ChunkCompression,
     buffer: Vec<u8>,
+
+    /// Blobs to be written into the chunk, each paired with the position in
+    /// `buffer` that it goes before.
+    blobs: Vec<(usize, Blob)>,
 }
 
 impl ChunkBuilder {
@@ -72,16 +276,64 @@ impl ChunkBuilder {
             chunk_name,
             compression,
             buffer: Vec::new(),
+            blobs: Vec::new(),
         }
     }
 
+    /// The name of the chunk, like `PROP`.
+    pub fn name(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.chunk_name)
+    }
+
+    /// The number of bytes written to the chunk so far, before compression.
+    pub fn len(&self) -> usize {
+        let blobs_len: u64 = self.blobs.iter().map(|(_, blob)| blob.len()).sum();
+        self.buffer.len() + blobs_len as usize
+    }
+
+    /// Writes a length-prefixed binary string whose contents are kept in a
+    /// blob, without reading them into memory.
+    pub fn write_blob_string(&mut self, blob: &Blob) -> io::Result<()> {
+        let len = u32::try_from(blob.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "a {} byte value is too long for a binary string",
+                    blob.len()
+                ),
+            )
+        })?;
+
+        self.buffer.write_le_u32(len)?;
+        self.blobs.push((self.buffer.len(), blob.clone()));
+
+        Ok(())
+    }
+
     /// Consume the chunk and write it to the given writer.
     pub fn dump<W: Write>(self, mut writer: W) -> io::Result<()> {
+        if !self.blobs.is_empty() {
+            return self.dump_with_blobs(writer);
+        }
+
         writer.write_all(self.chunk_name)?;
 
         match self.compression {
-            ChunkCompression::Compressed => {
-                let compressed = lz4::block::compress(&self.buffer, None, false)?;
+            ChunkCompression::Lz4 { level } => {
+                let mode = match level {
+                    0 => None,
+                    level => Some(lz4::block::CompressionMode::HIGHCOMPRESSION(
+                        level.min(MAX_LZ4_LEVEL) as i32,
+                    )),
+                };
+                let compressed = lz4::block::compress(&self.buffer, mode, false)?;
+
+                debug_event!(
+                    chunk = %String::from_utf8_lossy(self.chunk_name),
+                    compressed_len = compressed.len(),
+                    len = self.buffer.len(),
+                    "wrote chunk"
+                );
 
                 writer.write_le_u32(compressed.len() as u32)?;
                 writer.write_le_u32(self.buffer.len() as u32)?;
@@ -90,6 +342,13 @@ impl ChunkBuilder {
                 writer.write_all(&compressed)?;
             }
             ChunkCompression::Uncompressed => {
+                debug_event!(
+                    chunk = %String::from_utf8_lossy(self.chunk_name),
+                    compressed_len = 0,
+                    len = self.buffer.len(),
+                    "wrote chunk"
+                );
+
                 writer.write_le_u32(0)?;
                 writer.write_le_u32(self.buffer.len() as u32)?;
                 writer.write_le_u32(0)?;
@@ -102,6 +361,53 @@ impl ChunkBuilder {
     }
 }
 
+impl ChunkBuilder {
+    /// Writes the chunk uncompressed, copying each blob from its store
+    /// straight into the writer.
+    fn dump_with_blobs<W: Write>(self, mut writer: W) -> io::Result<()> {
+        let len = u32::try_from(self.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} chunk is too long to write", self.name()),
+            )
+        })?;
+
+        debug_event!(
+            chunk = %String::from_utf8_lossy(self.chunk_name),
+            compressed_len = 0,
+            len = len,
+            blobs = self.blobs.len(),
+            "wrote chunk"
+        );
+
+        writer.write_all(self.chunk_name)?;
+        writer.write_le_u32(0)?;
+        writer.write_le_u32(len)?;
+        writer.write_le_u32(0)?;
+
+        let mut written = 0;
+
+        for (position, blob) in &self.blobs {
+            writer.write_all(&self.buffer[written..*position])?;
+            written = *position;
+
+            let copied = io::copy(&mut blob.open()?, &mut writer)?;
+            if copied != blob.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "blob should have had {} bytes, but the store gave back {}",
+                        blob.len(),
+                        copied
+                    ),
+                ));
+            }
+        }
+
+        writer.write_all(&self.buffer[written..])
+    }
+}
+
 impl Write for ChunkBuilder {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.buffer.write(buf)
@@ -154,10 +460,13 @@ fn decode_chunk_header<R: Read>(source: &mut R) -> io::Result<ChunkHeader> {
     let reserved = source.read_le_u32()?;
 
     if reserved != 0 {
-        panic!(
-            "Chunk reserved space was not zero, it was {}. This chunk may be malformed.",
-            reserved
-        );
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Chunk reserved space was not zero, it was {}. This chunk may be malformed.",
+                reserved
+            ),
+        ));
     }
 
     Ok(ChunkHeader {
@@ -1,4 +1,5 @@
 use std::{
+    fmt,
     io::{self, Read, Write},
     mem,
 };
@@ -9,6 +10,54 @@ pub static FILE_MAGIC_HEADER: &[u8] = b"<roblox!";
 pub static FILE_SIGNATURE: &[u8] = b"\x89\xff\x0d\x0a\x1a\x0a";
 pub const FILE_VERSION: u16 = 0;
 
+/// Properties that the reflection database says don't serialize, but that old
+/// files still have. These are decoded and encoded anyway, so that converting
+/// an old model doesn't unlock it.
+pub const PRESERVED_LEGACY_PROPERTIES: &[&str] = &["RobloxLocked"];
+
+/// The most space that's set aside up front for something whose length comes
+/// from a file. Damaged files can claim lengths of several gigabytes, so longer
+/// buffers grow as their contents are actually read instead.
+pub const MAX_PREALLOCATION: usize = 64 * 1024;
+
+/// The capacity to start a buffer of `len` items with, when `len` was read
+/// from a file that might be damaged.
+#[inline]
+pub fn preallocation(len: usize) -> usize {
+    len.min(MAX_PREALLOCATION)
+}
+
+/// An instance's ID within a binary file, as written in its INST, PROP, and
+/// PRNT chunks. These only mean something within the file they're from, and
+/// are kept apart from other integers so that a count or index can't be used
+/// where a referent belongs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BinaryReferent(i32);
+
+impl BinaryReferent {
+    /// The referent written for a missing instance, like the parent of an
+    /// instance at the top of the file or a Ref property that's null.
+    pub const NONE: BinaryReferent = BinaryReferent(-1);
+
+    pub fn new(referent: i32) -> Self {
+        BinaryReferent(referent)
+    }
+
+    pub fn get(self) -> i32 {
+        self.0
+    }
+
+    pub fn is_none(self) -> bool {
+        self == Self::NONE
+    }
+}
+
+impl fmt::Display for BinaryReferent {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(output, "{}", self.get())
+    }
+}
+
 pub trait RbxReadExt: Read {
     fn read_le_u32(&mut self) -> io::Result<u32> {
         let mut buffer = [0; 4];
@@ -58,10 +107,18 @@ pub trait RbxReadExt: Read {
     /// no guarantees about encoding of things it calls strings. rbx_binary
     /// makes a semantic differentiation between strings and binary buffers,
     /// which makes it more strict than Roblox but more likely to be correct.
+    ///
+    /// `BinaryString` values are read by the deserializer itself, so that long
+    /// values can be sent to a blob store. This is used for `String` values
+    /// that need to be checked for valid UTF-8 before they're kept.
     fn read_binary_string(&mut self) -> io::Result<Vec<u8>> {
         let length = self.read_le_u32()?;
+        self.read_bytes(length)
+    }
 
-        let mut value = Vec::with_capacity(length as usize);
+    /// Read up to `length` bytes into a new buffer.
+    fn read_bytes(&mut self, length: u32) -> io::Result<Vec<u8>> {
+        let mut value = Vec::with_capacity(preallocation(length as usize));
         self.take(length as u64).read_to_end(&mut value)?;
 
         Ok(value)
@@ -72,7 +129,7 @@ pub trait RbxReadExt: Read {
     /// generally aren't dilligent about data being valid Unicode.
     fn read_string(&mut self) -> io::Result<String> {
         let length = self.read_le_u32()?;
-        let mut value = String::with_capacity(length as usize);
+        let mut value = String::with_capacity(preallocation(length as usize));
         self.take(length as u64).read_to_string(&mut value)?;
 
         Ok(value)
@@ -131,14 +188,17 @@ pub trait RbxReadExt: Read {
         Ok(())
     }
 
-    fn read_referent_array(&mut self, output: &mut [i32]) -> io::Result<()> {
-        self.read_interleaved_i32_array(output)?;
+    fn read_referent_array(&mut self, output: &mut [BinaryReferent]) -> io::Result<()> {
+        let mut deltas = vec![0; output.len()];
+        self.read_interleaved_i32_array(&mut deltas)?;
 
         let mut last = 0;
 
-        for referent in output.iter_mut() {
-            *referent += last;
-            last = *referent;
+        for (referent, delta) in output.iter_mut().zip(deltas) {
+            // Real files never get anywhere near overflowing, but damaged ones
+            // can, and Roblox wraps around in that case too.
+            last = delta.wrapping_add(last);
+            *referent = BinaryReferent(last);
         }
 
         Ok(())
@@ -273,10 +333,11 @@ pub trait RbxWriteExt: Write {
 
     fn write_referent_array<I>(&mut self, values: I) -> io::Result<()>
     where
-        I: Iterator<Item = i32>,
+        I: Iterator<Item = BinaryReferent>,
     {
         let mut last_value = 0;
-        let delta_encoded = values.map(|value| {
+        let delta_encoded = values.map(|referent| {
+            let value = referent.get();
             let encoded = value - last_value;
             last_value = value;
             encoded
@@ -449,8 +510,14 @@ fn find_serialized_from_canonical(
         }
 
         // If this property does not serialize, there is no serialized
-        // descriptor to use.
-        PropertySerialization::DoesNotSerialize => None,
+        // descriptor to use, unless it's one we keep for old files.
+        PropertySerialization::DoesNotSerialize => {
+            if PRESERVED_LEGACY_PROPERTIES.contains(&canonical.name.as_ref()) {
+                Some(canonical)
+            } else {
+                None
+            }
+        }
 
         // This case will be hit if a new form of property serialization is
         // introduced to the reflection database. This might happen if the
@@ -1,28 +1,31 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     convert::TryInto,
     io::{self, Read},
     str,
+    sync::Arc,
 };
 
 use rbx_dom_weak::{
     types::{
-        Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
-        ColorSequenceKeypoint, Content, CustomPhysicalProperties, Enum, Faces, Matrix3,
-        NumberRange, NumberSequence, NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref,
-        SharedString, UDim, UDim2, Variant, VariantType, Vector2, Vector3, Vector3int16,
+        Axes, BinaryString, Blob, BlobStore, BrickColor, CFrame, Color3, Color3uint8,
+        ColorSequence, ColorSequenceKeypoint, Content, ContentNormalizer, CountingReader,
+        CustomPhysicalProperties, Enum, Faces, Matrix3, NumberRange, NumberSequence,
+        NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref, RefProvider, SharedString,
+        StringInterner, UDim, UDim2, Variant, VariantType, Vector2, Vector3, Vector3int16,
     },
     InstanceBuilder, WeakDom,
 };
-use rbx_reflection::DataType;
+use rbx_reflection::{ClassFilter, DataType};
 use thiserror::Error;
 
 use crate::{
-    chunk::Chunk,
+    chunk::{find_next_chunk, Chunk},
     core::{
-        find_canonical_property_descriptor, RbxReadExt, FILE_MAGIC_HEADER, FILE_SIGNATURE,
-        FILE_VERSION,
+        find_canonical_property_descriptor, preallocation, BinaryReferent, RbxReadExt,
+        FILE_MAGIC_HEADER, FILE_SIGNATURE, FILE_VERSION,
     },
+    summary::{DecodeSummary, Diagnostic, DiagnosticCode, SourceLocation},
     types::{InvalidTypeError, Type},
 };
 
@@ -33,6 +36,102 @@ pub struct Error {
     source: Box<InnerError>,
 }
 
+impl Error {
+    /// The category of problem that caused this error.
+    pub fn kind(&self) -> DecodeErrorKind {
+        match &*self.source {
+            InnerError::Io { source } => match source.kind() {
+                // Chunks that are cut off or can't be decompressed surface
+                // as IO errors, but are really problems with the file.
+                io::ErrorKind::UnexpectedEof | io::ErrorKind::InvalidData => {
+                    DecodeErrorKind::MalformedFile
+                }
+                // Chunks compressed in a format we can't read, like ZSTD.
+                io::ErrorKind::Unsupported => DecodeErrorKind::UnsupportedVersion,
+                _ => DecodeErrorKind::Io,
+            },
+            InnerError::BadHeader
+            | InnerError::InvalidTypeId { .. }
+            | InnerError::InvalidReferent { .. }
+            | InnerError::TruncatedProp { .. }
+            | InnerError::TruncatedArrayProp { .. }
+            | InnerError::CountTooLarge { .. } => DecodeErrorKind::MalformedFile,
+            InnerError::InvalidPropData { .. }
+            | InnerError::BadCFrameOrientationId { .. }
+            | InnerError::InvalidString { .. } => DecodeErrorKind::MalformedValue,
+            InnerError::UnknownFileVersion { .. } | InnerError::UnknownChunkVersion { .. } => {
+                DecodeErrorKind::UnsupportedVersion
+            }
+            InnerError::InvalidTypeError { .. } | InnerError::PropTypeMismatch { .. } => {
+                DecodeErrorKind::UnsupportedType
+            }
+            InnerError::DuplicateRef { .. } => DecodeErrorKind::DuplicateRef,
+        }
+    }
+
+    /// Tells whether this error came from reading the file's source.
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        self.kind() == DecodeErrorKind::Io
+    }
+
+    /// Tells whether this error was caused by a file that isn't valid.
+    /// Decoding the same file again will fail the same way.
+    #[inline]
+    pub fn is_malformed(&self) -> bool {
+        matches!(
+            self.kind(),
+            DecodeErrorKind::MalformedFile | DecodeErrorKind::MalformedValue
+        )
+    }
+
+    /// Tells whether this error was caused by a file that might be valid, but
+    /// uses a version or type that this version of rbx_binary doesn't
+    /// understand.
+    #[inline]
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self.kind(),
+            DecodeErrorKind::UnsupportedVersion | DecodeErrorKind::UnsupportedType
+        )
+    }
+}
+
+/// The category of problem that caused a
+/// [`DecodeError`][crate::DecodeError].
+///
+/// New categories may be added in minor releases, and errors may be moved
+/// into a new category that describes them better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The source couldn't be read, like when a file can't be read from disk.
+    Io,
+
+    /// The file's structure is invalid. This includes files with a bad header,
+    /// chunks that are cut off or can't be decompressed, and references to
+    /// types or instances that were never declared.
+    MalformedFile,
+
+    /// A property's value is invalid, like a CFrame with an unknown rotation
+    /// ID.
+    MalformedValue,
+
+    /// The file or one of its chunks has a version that rbx_binary doesn't
+    /// know how to read, or a chunk is compressed in a format that it can't
+    /// decompress.
+    UnsupportedVersion,
+
+    /// A property has a type that rbx_binary doesn't know about, or that it
+    /// can't convert to the type the reflection database expects.
+    UnsupportedType,
+
+    /// The [`ref_provider`][crate::DecodeOptions::ref_provider] gave out a
+    /// Ref that was already taken, either by an instance in the dom being
+    /// decoded into or by another instance in the file.
+    DuplicateRef,
+}
+
 impl From<InnerError> for Error {
     fn from(inner: InnerError) -> Self {
         Self {
@@ -85,15 +184,78 @@ pub(crate) enum InnerError {
         actual_value: String,
     },
 
+    #[error(
+        "The {chunk_name} chunk said it had {count} {items}, but it's too short to hold that many"
+    )]
+    CountTooLarge {
+        chunk_name: &'static str,
+        items: &'static str,
+        count: u32,
+    },
+
     #[error("File referred to type ID {type_id}, which was not declared")]
     InvalidTypeId { type_id: u32 },
 
+    #[error("File referred to instance {referent}, which was not declared")]
+    InvalidReferent { referent: BinaryReferent },
+
+    #[error(
+        "The PROP chunk for {type_name}.{prop_name} ended before all {expected} of its values, \
+         which can't be partly read"
+    )]
+    TruncatedArrayProp {
+        type_name: String,
+        prop_name: String,
+        expected: usize,
+    },
+
+    #[error(
+        "The PROP chunk for {type_name}.{prop_name} ended after {decoded} of its {expected} values"
+    )]
+    TruncatedProp {
+        type_name: String,
+        prop_name: String,
+        decoded: usize,
+        expected: usize,
+    },
+
     #[error("Invalid property data: CFrame property {type_name}.{prop_name} had an invalid orientation ID {id:02x}")]
     BadCFrameOrientationId {
         type_name: String,
         prop_name: String,
         id: u8,
     },
+
+    #[error("Invalid property data: String property {type_name}.{prop_name} of instance {index} of its class was not valid UTF-8")]
+    InvalidString {
+        type_name: String,
+        prop_name: String,
+        index: usize,
+    },
+
+    #[error("The RefProvider gave out the Ref {referent}, which was already taken")]
+    DuplicateRef { referent: Ref },
+}
+
+/// Makes sure that a chunk has room for the number of items it says it has,
+/// each of which takes up at least `item_len` bytes, before space is set aside
+/// for them.
+pub(crate) fn check_count(
+    chunk: &[u8],
+    chunk_name: &'static str,
+    items: &'static str,
+    count: u32,
+    item_len: usize,
+) -> Result<(), InnerError> {
+    if (count as usize).saturating_mul(item_len) > chunk.len() {
+        Err(InnerError::CountTooLarge {
+            chunk_name,
+            items,
+            count,
+        })
+    } else {
+        Ok(())
+    }
 }
 
 // TODO potentially move this to a different file if/when we do the inverse for serializing
@@ -223,43 +385,581 @@ pub(crate) fn special_case_to_rotation(id: u8) -> Option<Matrix3> {
     }
 }
 
-pub(crate) fn decode<R: Read>(reader: R) -> Result<WeakDom, Error> {
-    Ok(decode_inner(reader)?)
+/// Removes instances left out by the `class_filter` option, which have no
+/// children by now, and clears any Ref property under `roots` that points to
+/// one of them.
+fn remove_filtered_out(tree: &mut WeakDom, roots: &[Ref], filtered_out: &HashSet<Ref>) {
+    let mut to_visit = roots.to_vec();
+
+    while let Some(referent) = to_visit.pop() {
+        let instance = tree.get_by_ref_mut(referent).unwrap();
+
+        for value in instance.properties.values_mut() {
+            if let Variant::Ref(target) = value {
+                if filtered_out.contains(target) {
+                    *target = Ref::none();
+                }
+            }
+        }
+
+        to_visit.extend_from_slice(instance.children());
+    }
+
+    for &referent in filtered_out {
+        tree.destroy(referent);
+    }
+}
+
+/// Reads a length-prefixed BinaryString, keeping it in the blob store if it's
+/// too long to keep in memory.
+fn read_binary_string_value<R: Read>(
+    options: &DecodeOptions,
+    mut reader: R,
+) -> io::Result<BinaryString> {
+    let len = reader.read_le_u32()?;
+
+    match options.store_for_len(len) {
+        Some(store) => Ok(BinaryString::from_blob(read_blob(store, reader, len)?)),
+        None => Ok(reader.read_bytes(len)?.into()),
+    }
 }
 
-pub(crate) fn decode_inner<R: Read>(reader: R) -> Result<WeakDom, InnerError> {
-    let mut deserializer = BinaryDeserializer::new(reader)?;
+/// Reads a length-prefixed SharedString, keeping it in the blob store if it's
+/// too long to keep in memory.
+fn read_shared_string_value<R: Read>(
+    options: &DecodeOptions,
+    mut reader: R,
+) -> io::Result<SharedString> {
+    let len = reader.read_le_u32()?;
+
+    match options.store_for_len(len) {
+        Some(store) => SharedString::from_blob(read_blob(store, reader, len)?),
+        None => Ok(SharedString::new(reader.read_bytes(len)?)),
+    }
+}
 
-    loop {
-        let chunk = Chunk::decode(&mut deserializer.input)?;
+fn read_blob<R: Read>(store: &Arc<dyn BlobStore>, reader: R, len: u32) -> io::Result<Blob> {
+    let blob = Blob::store(store, reader.take(len as u64))?;
+
+    if blob.len() != len as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "expected {} bytes of string data, found {}",
+                len,
+                blob.len()
+            ),
+        ));
+    }
 
-        match &chunk.name {
-            b"META" => deserializer.decode_meta_chunk(&chunk.data)?,
-            b"SSTR" => deserializer.decode_sstr_chunk(&chunk.data)?,
-            b"INST" => deserializer.decode_inst_chunk(&chunk.data)?,
-            b"PROP" => deserializer.decode_prop_chunk(&chunk.data)?,
-            b"PRNT" => deserializer.decode_prnt_chunk(&chunk.data)?,
-            b"END\0" => {
-                deserializer.decode_end_chunk(&chunk.data)?;
-                break;
+    Ok(blob)
+}
+
+/// Describes what rbx_binary does with `String` and `Content` property values
+/// that aren't valid UTF-8. Used with
+/// [`DecodeOptions::invalid_strings`][DecodeOptions::invalid_strings].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum InvalidStringBehavior {
+    /// Return an error naming the property and the instance that had the
+    /// value. This is the default.
+    #[default]
+    Error,
+
+    /// Replace every invalid sequence of bytes with U+FFFD REPLACEMENT
+    /// CHARACTER, and record a
+    /// [`ReplacedInvalidString`][crate::DiagnosticCode::ReplacedInvalidString]
+    /// diagnostic.
+    ReplaceLossy,
+
+    /// Keep the exact bytes as a `BinaryString` value under the same property
+    /// name, and record a
+    /// [`PreservedInvalidString`][crate::DiagnosticCode::PreservedInvalidString]
+    /// diagnostic. Names can only be strings, so invalid names are replaced as
+    /// they are with `ReplaceLossy`.
+    PreserveAsBinaryString,
+}
+
+/// A `String` property value that was checked against the `invalid_strings`
+/// option.
+enum StringValue {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// Checks the `String` values of one property against the `invalid_strings`
+/// option, keeping track of the ones that weren't valid UTF-8.
+struct StringChecker {
+    behavior: InvalidStringBehavior,
+    type_name: String,
+    prop_name: String,
+    replaced: Vec<usize>,
+    preserved: Vec<usize>,
+}
+
+impl StringChecker {
+    fn new(behavior: InvalidStringBehavior, type_name: &str, prop_name: &str) -> Self {
+        StringChecker {
+            behavior,
+            type_name: type_name.to_owned(),
+            prop_name: prop_name.to_owned(),
+            replaced: Vec::new(),
+            preserved: Vec::new(),
+        }
+    }
+
+    /// Reads the value for the instance at `index` within its class.
+    /// `can_preserve` is false for values that can only be stored as text.
+    fn read<R: Read>(
+        &mut self,
+        mut reader: R,
+        index: usize,
+        can_preserve: bool,
+    ) -> Result<StringValue, InnerError> {
+        let error = match String::from_utf8(reader.read_binary_string()?) {
+            Ok(value) => return Ok(StringValue::Text(value)),
+            Err(error) => error,
+        };
+
+        match self.behavior {
+            InvalidStringBehavior::PreserveAsBinaryString if can_preserve => {
+                self.preserved.push(index);
+                Ok(StringValue::Bytes(error.into_bytes()))
             }
-            _ => match str::from_utf8(&chunk.name) {
-                Ok(name) => log::info!("Unknown binary chunk name {}", name),
-                Err(_) => log::info!("Unknown binary chunk name {:?}", chunk.name),
-            },
+            InvalidStringBehavior::ReplaceLossy | InvalidStringBehavior::PreserveAsBinaryString => {
+                self.replaced.push(index);
+                let value = String::from_utf8_lossy(error.as_bytes()).into_owned();
+                Ok(StringValue::Text(value))
+            }
+            InvalidStringBehavior::Error => Err(InnerError::InvalidString {
+                type_name: self.type_name.clone(),
+                prop_name: self.prop_name.clone(),
+                index,
+            }),
+        }
+    }
+
+    /// Turns the values that weren't valid UTF-8 into diagnostics.
+    fn finish(self, chunk_offset: u64) -> Vec<Diagnostic> {
+        let property = format!("{}.{}", self.type_name, self.prop_name);
+        let mut diagnostics = Vec::new();
+
+        let groups = [
+            (
+                DiagnosticCode::ReplacedInvalidString,
+                self.replaced,
+                "replaced with U+FFFD",
+            ),
+            (
+                DiagnosticCode::PreservedInvalidString,
+                self.preserved,
+                "kept as BinaryString values",
+            ),
+        ];
+
+        for (code, indices, outcome) in groups {
+            if indices.is_empty() {
+                continue;
+            }
+
+            let message = format!(
+                "{} had invalid UTF-8 in {} {}, {}; the first was instance {} of its class",
+                property,
+                indices.len(),
+                if indices.len() == 1 {
+                    "value"
+                } else {
+                    "values"
+                },
+                outcome,
+                indices[0]
+            );
+            log::warn!("{}", message);
+
+            let mut diagnostic = Diagnostic::new(code, message, chunk_offset);
+            diagnostic.chunk_name = Some("PROP".to_owned());
+            diagnostic.property = Some(property.clone());
+            diagnostics.push(diagnostic);
         }
+
+        diagnostics
+    }
+}
+
+/// Options available for deserializing a binary model or place.
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    recover: bool,
+    intern_strings: bool,
+    intern_max_len: usize,
+    normalize_content: bool,
+    class_filter: Option<ClassFilter>,
+    source_locations: bool,
+    blob_store: Option<Arc<dyn BlobStore>>,
+    blob_threshold: usize,
+    ref_provider: Option<Box<dyn RefProvider>>,
+    invalid_strings: InvalidStringBehavior,
+    migrate_enum_values: bool,
+    preserve_missing_names: bool,
+}
+
+impl DecodeOptions {
+    /// Constructs a `DecodeOptions` with all values set to their defaults.
+    #[inline]
+    pub fn new() -> Self {
+        DecodeOptions {
+            recover: false,
+            intern_strings: false,
+            intern_max_len: 1024,
+            normalize_content: false,
+            class_filter: None,
+            source_locations: false,
+            blob_store: None,
+            blob_threshold: 16 * 1024 * 1024,
+            ref_provider: None,
+            invalid_strings: InvalidStringBehavior::Error,
+            migrate_enum_values: true,
+            preserve_missing_names: false,
+        }
+    }
+
+    /// Determines whether rbx_binary will try to salvage as much of a damaged
+    /// file as it can instead of returning an error.
+    ///
+    /// With this option set, a chunk that can't be read is skipped and
+    /// decoding continues with the next chunk, searching ahead for one if the
+    /// damaged chunk's header can't be trusted. Instances whose parent was
+    /// lost are dropped along with their descendants, and instances that were
+    /// never given a parent are placed at the top level of the dom. Every
+    /// problem is recorded as a diagnostic in the
+    /// [`DecodeSummary`][crate::DecodeSummary].
+    ///
+    /// This requires reading the entire file into memory before decoding it.
+    /// This is off by default.
+    #[inline]
+    pub fn recover(self, recover: bool) -> Self {
+        DecodeOptions { recover, ..self }
+    }
+
+    /// Determines whether rbx_binary will deduplicate `String` and `Content`
+    /// property values as it decodes them.
+    ///
+    /// Places often use the same strings and asset URLs thousands of times.
+    /// With this option set, every instance with the same value shares a
+    /// single copy of it. Mutating a `Content` value gives that instance its
+    /// own copy, and a `String` value can only be replaced, so other instances
+    /// are never affected.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn intern_strings(self, intern_strings: bool) -> Self {
+        DecodeOptions {
+            intern_strings,
+            ..self
+        }
+    }
+
+    /// Sets the length in bytes of the longest value that will be
+    /// deduplicated when [`intern_strings`][DecodeOptions::intern_strings] is
+    /// enabled. Longer values are rarely repeated, so checking them just costs
+    /// time.
+    ///
+    /// This is 1024 bytes by default.
+    #[inline]
+    pub fn intern_max_len(self, intern_max_len: usize) -> Self {
+        DecodeOptions {
+            intern_max_len,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_binary will rewrite `Content` property values
+    /// that refer to Roblox assets into the canonical `rbxassetid://<id>` form,
+    /// using [`Content::normalize`][rbx_dom_weak::types::Content::normalize].
+    ///
+    /// URLs like `http://www.roblox.com/asset/?id=1818` and
+    /// `rbxassetid://1818` both become `rbxassetid://1818`. Other URLs, like
+    /// `rbxasset://` paths, are left alone. Every value that was rewritten is
+    /// listed in [`DecodeSummary::normalized_content`][crate::DecodeSummary::normalized_content].
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn normalize_content(self, normalize_content: bool) -> Self {
+        DecodeOptions {
+            normalize_content,
+            ..self
+        }
+    }
+
+    /// Makes rbx_binary leave out every instance whose class isn't in the
+    /// given filter. The children of an instance that's left out take its
+    /// place under its parent, so a filter of only scripts still finds the
+    /// scripts inside of models. Ref properties that point to an instance that
+    /// was left out are set to none.
+    ///
+    /// ```
+    /// use rbx_binary::DecodeOptions;
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    /// use rbx_reflection::ClassFilter;
+    ///
+    /// let model = WeakDom::new(
+    ///     InstanceBuilder::new("Model")
+    ///         .with_child(InstanceBuilder::new("Part"))
+    ///         .with_child(InstanceBuilder::new("LocalScript")),
+    /// );
+    ///
+    /// let mut file = Vec::new();
+    /// rbx_binary::to_writer_default(&mut file, &model, &[model.root_ref()]).unwrap();
+    ///
+    /// let options = DecodeOptions::new().class_filter(ClassFilter::scripts());
+    /// let dom = rbx_binary::from_reader(file.as_slice(), options).unwrap();
+    ///
+    /// let script = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    /// assert_eq!(dom.root().children().len(), 1);
+    /// assert_eq!(script.class, "LocalScript");
+    /// ```
+    ///
+    /// By default, every instance is kept.
+    #[inline]
+    pub fn class_filter(self, class_filter: ClassFilter) -> Self {
+        DecodeOptions {
+            class_filter: Some(class_filter),
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_binary will record which INST chunk created each
+    /// instance, and where in that chunk it was, so that tools can point back
+    /// to the source of a problem they find in the dom.
+    ///
+    /// With this option set, every decoded instance has a location in
+    /// [`DecodeSummary::source_locations`][crate::DecodeSummary::source_locations].
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn source_locations(self, source_locations: bool) -> Self {
+        DecodeOptions {
+            source_locations,
+            ..self
+        }
+    }
+
+    /// Makes rbx_binary keep `BinaryString` and `SharedString` values longer
+    /// than [`blob_threshold`][DecodeOptions::blob_threshold] in the given
+    /// store instead of in memory. Their contents are only read back when
+    /// something needs them, and both rbx_binary and rbx_xml encode them
+    /// straight from the store.
+    ///
+    /// Uncompressed PROP and SSTR chunks longer than the threshold are copied
+    /// into the store as they're read too, so files made of them can be
+    /// decoded without ever holding their largest values in memory.
+    /// Compressed chunks still need to be decompressed in memory first.
+    ///
+    /// By default, every value is kept in memory.
+    #[inline]
+    pub fn blob_store(self, blob_store: Arc<dyn BlobStore>) -> Self {
+        DecodeOptions {
+            blob_store: Some(blob_store),
+            ..self
+        }
+    }
+
+    /// Sets the length in bytes above which values are kept in the
+    /// [`blob_store`][DecodeOptions::blob_store], if there is one.
+    ///
+    /// This is 16 MiB by default.
+    #[inline]
+    pub fn blob_threshold(self, blob_threshold: usize) -> Self {
+        DecodeOptions {
+            blob_threshold,
+            ..self
+        }
+    }
+
+    /// The store that a value of the given length should be kept in, if it's
+    /// too long to keep in memory.
+    fn store_for_len(&self, len: u32) -> Option<&Arc<dyn BlobStore>> {
+        self.blob_store
+            .as_ref()
+            .filter(|_| len as usize > self.blob_threshold)
+    }
+
+    /// Sets where rbx_binary gets the `Ref` of each instance it creates, including
+    /// the `DataModel` at the root of the dom.
+    ///
+    /// Decoding the same file with a
+    /// [`SeededRefProvider`][rbx_dom_weak::types::SeededRefProvider] always
+    /// gives its instances the same Refs. The provider is cloned along with
+    /// these options, so every decode that uses a clone of the same options
+    /// starts from the same place in the provider's sequence.
+    ///
+    /// That also means decoding twice into the same dom with clones of the
+    /// same options gives the second set of instances Refs that are already
+    /// taken, which makes [`decode_into`][crate::decode_into] return a
+    /// [`DuplicateRef`][crate::DecodeErrorKind::DuplicateRef] error without
+    /// changing the dom. Use a provider with a different seed for each decode
+    /// into one dom.
+    ///
+    /// By default, Refs are random.
+    #[inline]
+    pub fn ref_provider<P: RefProvider + 'static>(self, ref_provider: P) -> Self {
+        DecodeOptions {
+            ref_provider: Some(Box::new(ref_provider)),
+            ..self
+        }
+    }
+
+    /// Decides what rbx_binary does with `String` property values that aren't
+    /// valid UTF-8, which turn up in files saved by old or modified clients.
+    ///
+    /// By default, they're an error.
+    #[inline]
+    pub fn invalid_strings(self, invalid_strings: InvalidStringBehavior) -> Self {
+        DecodeOptions {
+            invalid_strings,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_binary will replace enum values that the
+    /// reflection database lists as coming from older versions of Roblox with
+    /// the values that took their place. For example, `Lighting.Technology`
+    /// values of `Legacy` become `Compatibility`.
+    ///
+    /// Each property with replaced values gets a
+    /// [`MigratedEnumValues`][crate::DiagnosticCode::MigratedEnumValues]
+    /// diagnostic. Turning this off keeps every value the way the file has
+    /// it, which matters for tools that archive places.
+    ///
+    /// This is on by default.
+    #[inline]
+    pub fn migrate_enum_values(self, migrate_enum_values: bool) -> Self {
+        DecodeOptions {
+            migrate_enum_values,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_binary will remember which instances had no
+    /// `Name` in the file, like instances of classes that were encoded with
+    /// `EncodeOptions::omit_default_names`.
+    ///
+    /// Those instances are always named after their ClassName. With this on,
+    /// [`Instance::explicit_name`][rbx_dom_weak::Instance::explicit_name] also
+    /// returns `None` for them, and rbx_binary and rbx_xml leave their Name
+    /// out again when they're encoded.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn preserve_missing_names(self, preserve_missing_names: bool) -> Self {
+        DecodeOptions {
+            preserve_missing_names,
+            ..self
+        }
+    }
+
+    /// Creates a builder for a new instance, taking its referent from the
+    /// configured `RefProvider` if there is one.
+    pub(crate) fn new_builder(&mut self, class: &str) -> InstanceBuilder {
+        let builder = InstanceBuilder::new(class);
+
+        match &mut self.ref_provider {
+            Some(provider) => builder.with_referent(provider.next_ref()),
+            None => builder,
+        }
+    }
+
+    /// Tells whether instances of the given class should be kept, according to
+    /// the `class_filter` option.
+    fn keeps_class(&self, class: &str) -> bool {
+        match &self.class_filter {
+            Some(filter) => filter.contains(class),
+            None => true,
+        }
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> DecodeOptions {
+        DecodeOptions::new()
     }
+}
+
+pub(crate) fn decode<R: Read>(reader: R, options: DecodeOptions) -> Result<WeakDom, Error> {
+    let (dom, _summary) = decode_with_summary(reader, options)?;
+    Ok(dom)
+}
 
-    Ok(deserializer.finish())
+pub(crate) fn decode_with_summary<R: Read>(
+    reader: R,
+    options: DecodeOptions,
+) -> Result<(WeakDom, DecodeSummary), Error> {
+    Ok(decode_inner(reader, options)?)
+}
+
+pub(crate) fn decode_inner<R: Read>(
+    reader: R,
+    mut options: DecodeOptions,
+) -> Result<(WeakDom, DecodeSummary), InnerError> {
+    let mut tree = WeakDom::new(options.new_builder("DataModel"));
+    let root_ref = tree.root_ref();
+
+    let (_roots, summary) = decode_into_inner(&mut tree, root_ref, reader, options)?;
+
+    Ok((tree, summary))
+}
+
+/// Decodes a file into an existing tree, inserting its top-level instances
+/// under `parent` and returning their referents. Nothing is inserted until the
+/// whole file has been decoded, so the tree is left alone if decoding fails.
+pub(crate) fn decode_into_inner<R: Read>(
+    tree: &mut WeakDom,
+    parent: Ref,
+    reader: R,
+    options: DecodeOptions,
+) -> Result<(Vec<Ref>, DecodeSummary), InnerError> {
+    phase_span!("decode", recover = options.recover);
+
+    assert!(
+        tree.get_by_ref(parent).is_some(),
+        "cannot decode into parent that does not exist"
+    );
+
+    let mut input = CountingReader::new(reader);
+
+    let (roots, mut summary) = if options.recover {
+        // Getting back on track after a damaged chunk can mean searching ahead
+        // for the next chunk, so we need the whole file available at once.
+        let mut file = Vec::new();
+        input.read_to_end(&mut file)?;
+
+        let mut deserializer = BinaryDeserializer::new(file.as_slice(), options)?;
+        deserializer.decode_chunks_recovering(&file);
+        deserializer.finish(tree, parent)?
+    } else {
+        let mut deserializer = BinaryDeserializer::new(&mut input, options)?;
+        deserializer.decode_chunks()?;
+        deserializer.finish(tree, parent)?
+    };
+
+    summary.bytes_read = input.bytes_read();
+
+    debug_event!(
+        bytes_read = summary.bytes_read,
+        instances = summary.instance_count,
+        properties = summary.property_count,
+        "decoded file"
+    );
+
+    Ok((roots, summary))
 }
 
 struct BinaryDeserializer<R> {
     /// The input data encoded as a binary model.
     input: R,
 
-    /// The tree that instances should be written into. Eventually returned to
-    /// the user.
-    tree: WeakDom,
+    /// The options that this file is being decoded with.
+    options: DecodeOptions,
 
     /// The metadata contained in the file, which affects how some constructs
     /// are interpreted by Roblox.
@@ -273,11 +973,39 @@ struct BinaryDeserializer<R> {
     type_infos: HashMap<u32, TypeInfo>,
 
     /// All of the instances known by the deserializer.
-    instances_by_ref: HashMap<i32, Instance>,
+    instances_by_ref: HashMap<BinaryReferent, Instance>,
 
     /// Referents for all of the instances with no parent, in order they appear
     /// in the file.
-    root_instance_refs: Vec<i32>,
+    root_instance_refs: Vec<BinaryReferent>,
+
+    /// The number of property values decoded so far, not counting names.
+    property_count: usize,
+
+    /// Referents for all of the instances that have been given a parent (or
+    /// been placed at the top level) by a PRNT chunk. Only tracked when
+    /// recovering from errors.
+    parented_refs: HashSet<BinaryReferent>,
+
+    /// The offset in the file of the chunk currently being decoded.
+    chunk_offset: u64,
+
+    /// Where each instance was created, keyed by the Ref it'll be given, if
+    /// the `source_locations` option is set.
+    source_locations: HashMap<Ref, SourceLocation>,
+
+    /// Whether any part of the file was skipped because of an error.
+    recovered: bool,
+
+    /// Problems found and worked around while decoding.
+    diagnostics: Vec<Diagnostic>,
+
+    /// Deduplicates String and Content values, if the `intern_strings` option
+    /// is set.
+    string_interner: Option<StringInterner>,
+
+    /// Rewrites Content values, if the `normalize_content` option is set.
+    content_normalizer: Option<ContentNormalizer>,
 }
 
 /// All the information contained in the header before any chunks are read from
@@ -303,7 +1031,7 @@ struct TypeInfo {
     type_name: String,
 
     /// A list of the instances described by this file that are this type.
-    referents: Vec<i32>,
+    referents: Vec<BinaryReferent>,
 }
 
 /// Contains all the information we need to gather in order to construct an
@@ -314,32 +1042,124 @@ struct Instance {
     builder: InstanceBuilder,
 
     /// Document-defined IDs for the children of this instance.
-    children: Vec<i32>,
+    children: Vec<BinaryReferent>,
 }
 
 impl<R: Read> BinaryDeserializer<R> {
-    fn new(mut input: R) -> Result<Self, InnerError> {
-        let tree = WeakDom::new(InstanceBuilder::new("DataModel"));
-
-        let header = FileHeader::decode(&mut input)?;
-
-        let type_infos = HashMap::with_capacity(header.num_types as usize);
-        let instances_by_ref = HashMap::with_capacity(1 + header.num_instances as usize);
+    fn new(mut input: R, options: DecodeOptions) -> Result<Self, InnerError> {
+        let header = {
+            phase_span!("read_header");
+            FileHeader::decode(&mut input)?
+        };
+
+        let type_infos = HashMap::with_capacity(preallocation(header.num_types as usize));
+        let instances_by_ref =
+            HashMap::with_capacity(preallocation(1 + header.num_instances as usize));
+
+        let string_interner = if options.intern_strings {
+            Some(StringInterner::new(options.intern_max_len))
+        } else {
+            None
+        };
+
+        let content_normalizer = if options.normalize_content {
+            Some(ContentNormalizer::new())
+        } else {
+            None
+        };
 
         Ok(BinaryDeserializer {
             input,
-            tree,
+            options,
             metadata: HashMap::new(),
             shared_strings: Vec::new(),
             type_infos,
             instances_by_ref,
             root_instance_refs: Vec::new(),
+            property_count: 0,
+            parented_refs: HashSet::new(),
+            chunk_offset: FileHeader::LEN,
+            source_locations: HashMap::new(),
+            recovered: false,
+            diagnostics: Vec::new(),
+            string_interner,
+            content_normalizer,
         })
     }
 
+    /// Decodes every chunk in the file, stopping at the first error.
+    fn decode_chunks(&mut self) -> Result<(), InnerError> {
+        loop {
+            let chunk = match self.options.blob_store.as_ref() {
+                Some(store) => {
+                    Chunk::decode_to_store(&mut self.input, store, self.options.blob_threshold)?
+                }
+                None => Chunk::decode(&mut self.input)?,
+            };
+
+            if self.decode_chunk(&chunk)? {
+                return Ok(());
+            }
+
+            self.chunk_offset += chunk.encoded_len;
+        }
+    }
+
+    /// Decodes a single chunk, returning whether it was the last chunk in the
+    /// file.
+    fn decode_chunk(&mut self, chunk: &Chunk) -> Result<bool, InnerError> {
+        detail_span!(
+            "decode_chunk",
+            chunk = %String::from_utf8_lossy(&chunk.name),
+            len = chunk.data.len()
+        );
+
+        match &chunk.name {
+            b"META" => self.decode_meta_chunk(&chunk.data)?,
+            b"SSTR" => match &chunk.stored {
+                Some(stored) => self.decode_sstr_chunk(stored.open()?)?,
+                None => self.decode_sstr_chunk(chunk.data.as_slice())?,
+            },
+            b"INST" => self.decode_inst_chunk(&chunk.data)?,
+            b"PROP" => match &chunk.stored {
+                Some(stored) => self.decode_stored_prop_chunk(stored)?,
+                None => self.decode_prop_chunk(&chunk.data)?,
+            },
+            b"PRNT" => self.decode_prnt_chunk(&chunk.data)?,
+            b"END\0" => {
+                self.decode_end_chunk(&chunk.data)?;
+                return Ok(true);
+            }
+            _ => match str::from_utf8(&chunk.name) {
+                Ok(name) => log::info!("Unknown binary chunk name {}", name),
+                Err(_) => log::info!("Unknown binary chunk name {:?}", chunk.name),
+            },
+        }
+
+        Ok(false)
+    }
+
+    /// Records a problem that was worked around while recovering from errors.
+    fn add_diagnostic<M: ToString>(
+        &mut self,
+        code: DiagnosticCode,
+        chunk_name: Option<&[u8; 4]>,
+        message: M,
+    ) -> &mut Diagnostic {
+        let message = message.to_string();
+        log::warn!("Recovering from damaged file: {}", message);
+
+        let mut diagnostic = Diagnostic::new(code, message, self.chunk_offset);
+        diagnostic.chunk_name = chunk_name.map(|name| String::from_utf8_lossy(name).into_owned());
+
+        self.recovered = true;
+        self.diagnostics.push(diagnostic);
+        self.diagnostics.last_mut().unwrap()
+    }
+
     fn decode_meta_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
         let len = chunk.read_le_u32()?;
-        self.metadata.reserve(len as usize);
+        self.metadata.reserve(preallocation(len as usize));
 
         for _ in 0..len {
             let key = chunk.read_string()?;
@@ -351,7 +1171,7 @@ impl<R: Read> BinaryDeserializer<R> {
         Ok(())
     }
 
-    fn decode_sstr_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+    fn decode_sstr_chunk<C: Read>(&mut self, mut chunk: C) -> Result<(), InnerError> {
         let version = chunk.read_le_u32()?;
 
         if version != 0 {
@@ -365,8 +1185,8 @@ impl<R: Read> BinaryDeserializer<R> {
 
         for _ in 0..num_entries {
             chunk.read_exact(&mut [0; 16])?; // We don't do anything with the hash.
-            let data = chunk.read_binary_string()?;
-            self.shared_strings.push(SharedString::new(data));
+            let shared_string = read_shared_string_value(&self.options, &mut chunk)?;
+            self.shared_strings.push(shared_string);
         }
 
         Ok(())
@@ -385,17 +1205,38 @@ impl<R: Read> BinaryDeserializer<R> {
             object_format,
             number_instances,
         );
+        debug_event!(class = %type_name, instances = number_instances, "INST chunk");
 
-        let mut referents = vec![0; number_instances as usize];
+        check_count(chunk, "INST", "instances", number_instances, 4)?;
+
+        let mut referents = vec![BinaryReferent::NONE; number_instances as usize];
         chunk.read_referent_array(&mut referents)?;
 
         // TODO: Check object_format and check for service markers if it's 1?
 
-        for &referent in &referents {
+        for (index, &referent) in referents.iter().enumerate() {
+            let mut builder = self.options.new_builder(&type_name);
+
+            // A Name PROP chunk gives the instance its name again, so this
+            // only sticks for instances whose class doesn't have one.
+            if self.options.preserve_missing_names {
+                builder.clear_name();
+            }
+
+            if self.options.source_locations {
+                self.source_locations.insert(
+                    builder.referent(),
+                    SourceLocation {
+                        chunk_offset: self.chunk_offset,
+                        index,
+                    },
+                );
+            }
+
             self.instances_by_ref.insert(
                 referent,
                 Instance {
-                    builder: InstanceBuilder::new(&type_name),
+                    builder,
                     children: Vec::new(),
                 },
             );
@@ -418,47 +1259,246 @@ impl<R: Read> BinaryDeserializer<R> {
         let prop_name = chunk.read_string()?;
         let binary_type: Type = chunk.read_u8()?.try_into()?;
 
+        let len = chunk.len() as u64;
+        self.decode_prop_chunk_values(type_id, prop_name, binary_type, len, || Ok(chunk))
+    }
+
+    /// Decodes a PROP chunk whose contents were copied into the blob store
+    /// instead of being read into memory.
+    fn decode_stored_prop_chunk(&mut self, chunk: &Blob) -> Result<(), InnerError> {
+        let mut header = chunk.open()?;
+        let type_id = header.read_le_u32()?;
+        let prop_name = header.read_string()?;
+        let binary_type: Type = header.read_u8()?.try_into()?;
+        drop(header);
+
+        // The type ID, the name and its length, and the type.
+        let header_len = 4 + 4 + prop_name.len() as u64 + 1;
+
+        self.decode_prop_chunk_values(
+            type_id,
+            prop_name,
+            binary_type,
+            chunk.len().saturating_sub(header_len),
+            || {
+                let mut values = chunk.open()?;
+                io::copy(&mut (&mut values).take(header_len), &mut io::sink())?;
+                Ok(values)
+            },
+        )
+    }
+
+    /// Decodes the values in a PROP chunk, which are read from the start of
+    /// each reader returned by `open_values`. A damaged chunk may need to be
+    /// read more than once.
+    fn decode_prop_chunk_values<C, F>(
+        &mut self,
+        type_id: u32,
+        prop_name: String,
+        binary_type: Type,
+        len: u64,
+        mut open_values: F,
+    ) -> Result<(), InnerError>
+    where
+        C: Read,
+        F: FnMut() -> io::Result<C>,
+    {
         let type_info = self
             .type_infos
             .get(&type_id)
             .ok_or(InnerError::InvalidTypeId { type_id })?;
 
         log::trace!(
-            "PROP chunk ({}.{}, instance type {}, prop type {}",
+            "PROP chunk ({}.{}, instance type {}, prop type {}, {} bytes",
             type_info.type_name,
             prop_name,
             type_info.type_id,
-            type_id
+            type_id,
+            len
+        );
+        debug_event!(
+            class = %type_info.type_name,
+            property = %prop_name,
+            instances = type_info.referents.len(),
+            bytes = len,
+            "PROP chunk"
         );
 
+        let count = type_info.referents.len();
+
+        // Values can be decoded more than once below, so diagnostics about
+        // them from a failed attempt are thrown away before the next one.
+        let diagnostic_count = self.diagnostics.len();
+
+        match self.decode_prop_values(
+            type_id,
+            prop_name.clone(),
+            binary_type,
+            open_values()?,
+            count,
+        ) {
+            Ok(value_count) => {
+                self.property_count += value_count;
+                Ok(())
+            }
+            Err(InnerError::Io { source })
+                if source.kind() == io::ErrorKind::UnexpectedEof
+                    && !binary_type.is_sequential() =>
+            {
+                // Arrays like these are laid out according to how many values
+                // they hold, so reading fewer values from one that was cut off
+                // gives values that look fine but aren't the ones written.
+                // Every instance is left without the property instead.
+                self.diagnostics.truncate(diagnostic_count);
+
+                let type_name = self.type_infos[&type_id].type_name.clone();
+                let property = format!("{}.{}", type_name, prop_name);
+                let error = InnerError::TruncatedArrayProp {
+                    type_name,
+                    prop_name,
+                    expected: count,
+                };
+
+                if self.options.recover {
+                    self.add_diagnostic(
+                        DiagnosticCode::TruncatedProperty,
+                        Some(b"PROP"),
+                        format!("{}; every instance was left without it", error),
+                    )
+                    .property = Some(property);
+                    Ok(())
+                } else {
+                    Err(error)
+                }
+            }
+            Err(InnerError::Io { source }) if source.kind() == io::ErrorKind::UnexpectedEof => {
+                // The chunk ended before every instance got a value. Values of
+                // this type are written one after another, and reading fewer
+                // of them only ever needs less data, so we can search for the
+                // most values that the chunk actually holds.
+                let mut decoded = 0;
+                let mut too_many = count;
+
+                while too_many - decoded > 1 {
+                    let middle = decoded + (too_many - decoded) / 2;
+                    self.diagnostics.truncate(diagnostic_count);
+
+                    match self.decode_prop_values(
+                        type_id,
+                        prop_name.clone(),
+                        binary_type,
+                        open_values()?,
+                        middle,
+                    ) {
+                        Ok(_) => decoded = middle,
+                        Err(_) => too_many = middle,
+                    }
+                }
+
+                // The last attempt might have been one that failed partway,
+                // so we decode the values we're keeping once more. This also
+                // tells us how many of them count as properties.
+                self.diagnostics.truncate(diagnostic_count);
+                let value_count = self.decode_prop_values(
+                    type_id,
+                    prop_name.clone(),
+                    binary_type,
+                    open_values()?,
+                    decoded,
+                )?;
+
+                let type_name = self.type_infos[&type_id].type_name.clone();
+                let property = format!("{}.{}", type_name, prop_name);
+                let error = InnerError::TruncatedProp {
+                    type_name,
+                    prop_name,
+                    decoded,
+                    expected: count,
+                };
+
+                if self.options.recover {
+                    self.add_diagnostic(
+                        DiagnosticCode::TruncatedProperty,
+                        Some(b"PROP"),
+                        format!(
+                            "{}; {} instances were left without it",
+                            error,
+                            count - decoded
+                        ),
+                    )
+                    .property = Some(property);
+                    self.property_count += value_count;
+                    Ok(())
+                } else {
+                    Err(error)
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Decodes the values of a PROP chunk's property for the first `count`
+    /// instances of its type, reading from the part of the chunk after its
+    /// header.
+    fn decode_prop_values<C: Read>(
+        &mut self,
+        type_id: u32,
+        prop_name: String,
+        binary_type: Type,
+        mut chunk: C,
+        count: usize,
+    ) -> Result<usize, InnerError> {
+        let type_info = &self.type_infos[&type_id];
+        let referents = &type_info.referents[..count];
+
         // The `Name` prop is special and is routed to a different spot for
         // rbx_dom_weak, so we handle it specially here.
         if prop_name == "Name" {
-            // TODO: If an instance is never assigned a name through this code
-            // path, we should use the reflection database to figure out its
-            // default name. This should be rare: effectively never!
+            // Instances that are never assigned a name through this code path
+            // keep the name given to them by `InstanceBuilder::new`, which is
+            // their ClassName. rbx_xml does the same for items with no Name.
+            // With `preserve_missing_names`, they're also marked as having no
+            // Name of their own.
+
+            let mut checker = StringChecker::new(
+                self.options.invalid_strings,
+                &type_info.type_name,
+                &prop_name,
+            );
 
-            for referent in &type_info.referents {
+            for (index, referent) in referents.iter().enumerate() {
                 let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                let value = chunk.read_string()?;
-                instance.builder.set_name(value);
+
+                if let StringValue::Text(value) = checker.read(&mut chunk, index, false)? {
+                    instance.builder.set_name(value);
+                }
             }
 
-            return Ok(());
+            let diagnostics = checker.finish(self.chunk_offset);
+            self.diagnostics.extend(diagnostics);
+
+            return Ok(0);
         }
 
         let canonical_name;
         let canonical_type;
+        let mut enum_descriptor = None;
 
         match find_canonical_property_descriptor(&type_info.type_name, &prop_name) {
             Some(descriptor) => {
                 canonical_name = descriptor.name.clone().into_owned();
                 canonical_type = match &descriptor.data_type {
                     DataType::Value(ty) => *ty,
-                    DataType::Enum(_) => VariantType::Enum,
+                    DataType::Enum(enum_name) => {
+                        enum_descriptor = rbx_reflection_database::get()
+                            .enums
+                            .get(enum_name.as_ref())
+                            .filter(|_| self.options.migrate_enum_values);
+                        VariantType::Enum
+                    }
                     _ => {
                         // TODO: Configurable handling of unknown types?
-                        return Ok(());
+                        return Ok(0);
                     }
                 };
 
@@ -476,7 +1516,7 @@ impl<R: Read> BinaryDeserializer<R> {
                     None => {
                         log::warn!("Unsupported prop type {:?}, skipping property", binary_type);
 
-                        return Ok(());
+                        return Ok(0);
                     }
                 }
 
@@ -487,23 +1527,74 @@ impl<R: Read> BinaryDeserializer<R> {
         match binary_type {
             Type::String => match canonical_type {
                 VariantType::String => {
-                    for referent in &type_info.referents {
+                    let mut checker = StringChecker::new(
+                        self.options.invalid_strings,
+                        &type_info.type_name,
+                        &prop_name,
+                    );
+
+                    for (index, referent) in referents.iter().enumerate() {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                        let value = chunk.read_string()?;
-                        instance.builder.add_property(&canonical_name, value);
+
+                        match checker.read(&mut chunk, index, true)? {
+                            StringValue::Text(value) => {
+                                let mut value = Arc::from(value);
+
+                                if let Some(interner) = &mut self.string_interner {
+                                    value = interner.intern_str(value);
+                                }
+
+                                instance
+                                    .builder
+                                    .add_property(&canonical_name, Variant::String(value))
+                            }
+                            StringValue::Bytes(value) => instance
+                                .builder
+                                .add_property(&canonical_name, BinaryString::from(value)),
+                        }
                     }
+
+                    let diagnostics = checker.finish(self.chunk_offset);
+                    self.diagnostics.extend(diagnostics);
                 }
                 VariantType::Content => {
-                    for referent in &type_info.referents {
+                    let mut checker = StringChecker::new(
+                        self.options.invalid_strings,
+                        &type_info.type_name,
+                        &prop_name,
+                    );
+
+                    for (index, referent) in referents.iter().enumerate() {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                        let value: Content = chunk.read_string()?.into();
+
+                        let mut value: Content = match checker.read(&mut chunk, index, true)? {
+                            StringValue::Text(value) => value.into(),
+                            StringValue::Bytes(value) => {
+                                instance
+                                    .builder
+                                    .add_property(&canonical_name, BinaryString::from(value));
+                                continue;
+                            }
+                        };
+
+                        if let Some(normalizer) = &mut self.content_normalizer {
+                            value = normalizer.normalize(value);
+                        }
+
+                        if let Some(interner) = &mut self.string_interner {
+                            value = interner.intern_content(value);
+                        }
+
                         instance.builder.add_property(&canonical_name, value);
                     }
+
+                    let diagnostics = checker.finish(self.chunk_offset);
+                    self.diagnostics.extend(diagnostics);
                 }
                 VariantType::BinaryString => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
+                        let value = read_binary_string_value(&self.options, &mut chunk)?;
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                        let value: BinaryString = chunk.read_binary_string()?.into();
                         instance.builder.add_property(&canonical_name, value);
                     }
                 }
@@ -518,7 +1609,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Bool => match canonical_type {
                 VariantType::Bool => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let value = chunk.read_bool()?;
                         instance.builder.add_property(&canonical_name, value);
@@ -535,10 +1626,10 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Int32 => match canonical_type {
                 VariantType::Int32 => {
-                    let mut values = vec![0; type_info.referents.len()];
+                    let mut values = vec![0; referents.len()];
                     chunk.read_interleaved_i32_array(&mut values)?;
 
-                    for (value, referent) in values.into_iter().zip(&type_info.referents) {
+                    for (value, referent) in values.into_iter().zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -554,10 +1645,10 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Float32 => match canonical_type {
                 VariantType::Float32 => {
-                    let mut values = vec![0.0; type_info.referents.len()];
+                    let mut values = vec![0.0; referents.len()];
                     chunk.read_interleaved_f32_array(&mut values)?;
 
-                    for (value, referent) in values.into_iter().zip(&type_info.referents) {
+                    for (value, referent) in values.into_iter().zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -573,7 +1664,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Float64 => match canonical_type {
                 VariantType::Float64 => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let value = chunk.read_le_f64()?;
                         instance.builder.add_property(&canonical_name, value);
@@ -590,8 +1681,8 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::UDim => match canonical_type {
                 VariantType::UDim => {
-                    let mut scales = vec![0.0; type_info.referents.len()];
-                    let mut offsets = vec![0; type_info.referents.len()];
+                    let mut scales = vec![0.0; referents.len()];
+                    let mut offsets = vec![0; referents.len()];
 
                     chunk.read_interleaved_f32_array(&mut scales)?;
                     chunk.read_interleaved_i32_array(&mut offsets)?;
@@ -601,7 +1692,7 @@ impl<R: Read> BinaryDeserializer<R> {
                         .zip(offsets)
                         .map(|(scale, offset)| UDim::new(scale, offset));
 
-                    for (value, referent) in values.zip(&type_info.referents) {
+                    for (value, referent) in values.zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -617,7 +1708,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::UDim2 => match canonical_type {
                 VariantType::UDim2 => {
-                    let prop_count = type_info.referents.len();
+                    let prop_count = referents.len();
                     let mut scale_x = vec![0.0; prop_count];
                     let mut scale_y = vec![0.0; prop_count];
                     let mut offset_x = vec![0; prop_count];
@@ -640,7 +1731,7 @@ impl<R: Read> BinaryDeserializer<R> {
 
                     let values = x.zip(y).map(|(x, y)| UDim2::new(x, y));
 
-                    for (value, referent) in values.zip(&type_info.referents) {
+                    for (value, referent) in values.zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -656,7 +1747,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Ray => match canonical_type {
                 VariantType::Ray => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let origin_x = chunk.read_le_f32()?;
                         let origin_y = chunk.read_le_f32()?;
                         let origin_z = chunk.read_le_f32()?;
@@ -686,7 +1777,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Faces => match canonical_type {
                 VariantType::Faces => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let value = chunk.read_u8()?;
                         let faces =
@@ -711,7 +1802,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Axes => match canonical_type {
                 VariantType::Axes => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let value = chunk.read_u8()?;
 
@@ -737,10 +1828,10 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::BrickColor => match canonical_type {
                 VariantType::BrickColor => {
-                    let mut values = vec![0; type_info.referents.len()];
+                    let mut values = vec![0; referents.len()];
                     chunk.read_interleaved_u32_array(&mut values)?;
 
-                    for (value, referent) in values.into_iter().zip(&type_info.referents) {
+                    for (value, referent) in values.into_iter().zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let color = value
                             .try_into()
@@ -756,20 +1847,58 @@ impl<R: Read> BinaryDeserializer<R> {
                         instance.builder.add_property(&canonical_name, color);
                     }
                 }
+                // Old files store some colors, like `BasePart.BrickColor`, as
+                // BrickColors. Those properties are now aliases for Color3
+                // properties, so we swap in the color each BrickColor stood for.
+                VariantType::Color3 | VariantType::Color3uint8 => {
+                    let mut values = vec![0; referents.len()];
+                    chunk.read_interleaved_u32_array(&mut values)?;
+
+                    for (value, referent) in values.into_iter().zip(referents) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+
+                        // Files can contain both the legacy property and the
+                        // one that replaced it, in which case the newer one
+                        // wins no matter which comes first.
+                        if instance.builder.has_property(&canonical_name) {
+                            continue;
+                        }
+
+                        // Roblox treats BrickColor numbers it doesn't recognize
+                        // as Medium stone grey, so we do too.
+                        let color = value
+                            .try_into()
+                            .ok()
+                            .and_then(BrickColor::from_number)
+                            .unwrap_or(BrickColor::MediumStoneGrey)
+                            .to_color3uint8();
+
+                        // Newer files store these colors as Color3uint8, which
+                        // we widen to match the reflection database, so we
+                        // produce the same type here.
+                        if canonical_type == VariantType::Color3 {
+                            instance
+                                .builder
+                                .add_property(&canonical_name, Color3::from(color));
+                        } else {
+                            instance.builder.add_property(&canonical_name, color);
+                        }
+                    }
+                }
                 invalid_type => {
                     return Err(InnerError::PropTypeMismatch {
                         type_name: type_info.type_name.clone(),
                         prop_name,
-                        valid_type_names: "BrickColor",
+                        valid_type_names: "BrickColor, Color3, or Color3uint8",
                         actual_type_name: format!("{:?}", invalid_type),
                     });
                 }
             },
             Type::Color3 => match canonical_type {
                 VariantType::Color3 => {
-                    let mut r = vec![0.0; type_info.referents.len()];
-                    let mut g = vec![0.0; type_info.referents.len()];
-                    let mut b = vec![0.0; type_info.referents.len()];
+                    let mut r = vec![0.0; referents.len()];
+                    let mut g = vec![0.0; referents.len()];
+                    let mut b = vec![0.0; referents.len()];
 
                     chunk.read_interleaved_f32_array(&mut r)?;
                     chunk.read_interleaved_f32_array(&mut g)?;
@@ -781,7 +1910,7 @@ impl<R: Read> BinaryDeserializer<R> {
                         .zip(b)
                         .map(|((r, g), b)| Color3::new(r, g, b));
 
-                    for (color, referent) in colors.zip(&type_info.referents) {
+                    for (color, referent) in colors.zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, color);
                     }
@@ -797,15 +1926,15 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Vector2 => match canonical_type {
                 VariantType::Vector2 => {
-                    let mut x = vec![0.0; type_info.referents.len()];
-                    let mut y = vec![0.0; type_info.referents.len()];
+                    let mut x = vec![0.0; referents.len()];
+                    let mut y = vec![0.0; referents.len()];
 
                     chunk.read_interleaved_f32_array(&mut x)?;
                     chunk.read_interleaved_f32_array(&mut y)?;
 
                     let values = x.into_iter().zip(y).map(|(x, y)| Vector2::new(x, y));
 
-                    for (value, referent) in values.zip(&type_info.referents) {
+                    for (value, referent) in values.zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -821,9 +1950,9 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Vector3 => match canonical_type {
                 VariantType::Vector3 => {
-                    let mut x = vec![0.0; type_info.referents.len()];
-                    let mut y = vec![0.0; type_info.referents.len()];
-                    let mut z = vec![0.0; type_info.referents.len()];
+                    let mut x = vec![0.0; referents.len()];
+                    let mut y = vec![0.0; referents.len()];
+                    let mut z = vec![0.0; referents.len()];
 
                     chunk.read_interleaved_f32_array(&mut x)?;
                     chunk.read_interleaved_f32_array(&mut y)?;
@@ -835,7 +1964,7 @@ impl<R: Read> BinaryDeserializer<R> {
                         .zip(z)
                         .map(|((x, y), z)| Vector3::new(x, y, z));
 
-                    for (value, referent) in values.zip(&type_info.referents) {
+                    for (value, referent) in values.zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -851,29 +1980,17 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::CFrame => match canonical_type {
                 VariantType::CFrame => {
-                    let referents = &type_info.referents;
                     let mut rotations = Vec::with_capacity(referents.len());
 
                     for _ in 0..referents.len() {
                         let id = chunk.read_u8()?;
                         if id == 0 {
-                            rotations.push(Matrix3::new(
-                                Vector3::new(
-                                    chunk.read_le_f32()?,
-                                    chunk.read_le_f32()?,
-                                    chunk.read_le_f32()?,
-                                ),
-                                Vector3::new(
-                                    chunk.read_le_f32()?,
-                                    chunk.read_le_f32()?,
-                                    chunk.read_le_f32()?,
-                                ),
-                                Vector3::new(
-                                    chunk.read_le_f32()?,
-                                    chunk.read_le_f32()?,
-                                    chunk.read_le_f32()?,
-                                ),
-                            ));
+                            let mut components = [0.0; 9];
+                            for component in &mut components {
+                                *component = chunk.read_le_f32()?;
+                            }
+
+                            rotations.push(Matrix3::from_components(&components));
                         } else {
                             let special_case = special_case_to_rotation(id).ok_or_else(|| {
                                 InnerError::BadCFrameOrientationId {
@@ -919,15 +2036,51 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Enum => match canonical_type {
                 VariantType::Enum => {
-                    let mut values = vec![0; type_info.referents.len()];
+                    let mut values = vec![0; referents.len()];
                     chunk.read_interleaved_u32_array(&mut values)?;
 
-                    for (value, referent) in values.into_iter().zip(&type_info.referents) {
+                    let mut migrated = Vec::new();
+
+                    for (index, (value, referent)) in values.into_iter().zip(referents).enumerate()
+                    {
+                        let value = match enum_descriptor.and_then(|e| e.migrate_value(value)) {
+                            Some(new_value) => {
+                                migrated.push(index);
+                                new_value
+                            }
+                            None => value,
+                        };
+
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance
                             .builder
                             .add_property(&canonical_name, Enum::from_u32(value));
                     }
+
+                    if let (Some(descriptor), Some(first)) = (enum_descriptor, migrated.first()) {
+                        let property = format!("{}.{}", type_info.type_name, canonical_name);
+                        let message = format!(
+                            "{} had {} {} value{} from an older version of Roblox, which {} \
+                             migrated to the values that replaced them; the first was instance \
+                             {} of its class",
+                            property,
+                            migrated.len(),
+                            descriptor.name,
+                            if migrated.len() == 1 { "" } else { "s" },
+                            if migrated.len() == 1 { "was" } else { "were" },
+                            first
+                        );
+                        log::debug!("{}", message);
+
+                        let mut diagnostic = Diagnostic::new(
+                            DiagnosticCode::MigratedEnumValues,
+                            message,
+                            self.chunk_offset,
+                        );
+                        diagnostic.chunk_name = Some("PROP".to_owned());
+                        diagnostic.property = Some(property);
+                        self.diagnostics.push(diagnostic);
+                    }
                 }
                 invalid_type => {
                     return Err(InnerError::PropTypeMismatch {
@@ -940,10 +2093,10 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Ref => match canonical_type {
                 VariantType::Ref => {
-                    let mut refs = vec![0; type_info.referents.len()];
+                    let mut refs = vec![BinaryReferent::NONE; referents.len()];
                     chunk.read_referent_array(&mut refs)?;
 
-                    for (value, referent) in refs.into_iter().zip(&type_info.referents) {
+                    for (value, referent) in refs.into_iter().zip(referents) {
                         let rbx_value = if let Some(instance) = self.instances_by_ref.get(&value) {
                             instance.builder.referent()
                         } else {
@@ -965,7 +2118,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Vector3int16 => match canonical_type {
                 VariantType::Vector3int16 => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(
                             &canonical_name,
@@ -977,21 +2130,38 @@ impl<R: Read> BinaryDeserializer<R> {
                         )
                     }
                 }
+                // Every Vector3int16 fits in a Vector3, so older files that
+                // store a property as the narrow type are widened to match
+                // the reflection database, the same way rbx_xml does it.
+                VariantType::Vector3 => {
+                    for referent in referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        instance.builder.add_property(
+                            &canonical_name,
+                            Vector3::new(
+                                chunk.read_le_i16()?.into(),
+                                chunk.read_le_i16()?.into(),
+                                chunk.read_le_i16()?.into(),
+                            ),
+                        )
+                    }
+                }
                 invalid_type => {
                     return Err(InnerError::PropTypeMismatch {
                         type_name: type_info.type_name.clone(),
                         prop_name,
-                        valid_type_names: "Vector3int16",
+                        valid_type_names: "Vector3int16 or Vector3",
                         actual_type_name: format!("{:?}", invalid_type),
                     });
                 }
             },
             Type::NumberSequence => match canonical_type {
                 VariantType::NumberSequence => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let keypoint_count = chunk.read_le_u32()?;
-                        let mut keypoints = Vec::with_capacity(keypoint_count as usize);
+                        let mut keypoints =
+                            Vec::with_capacity(preallocation(keypoint_count as usize));
 
                         for _ in 0..keypoint_count {
                             keypoints.push(NumberSequenceKeypoint::new(
@@ -1017,10 +2187,10 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::ColorSequence => match canonical_type {
                 VariantType::ColorSequence => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let keypoint_count = chunk.read_le_u32()? as usize;
-                        let mut keypoints = Vec::with_capacity(keypoint_count);
+                        let mut keypoints = Vec::with_capacity(preallocation(keypoint_count));
 
                         for _ in 0..keypoint_count {
                             keypoints.push(ColorSequenceKeypoint::new(
@@ -1052,7 +2222,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::NumberRange => match canonical_type {
                 VariantType::NumberRange => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(
                             &canonical_name,
@@ -1071,7 +2241,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::Rect => match canonical_type {
                 VariantType::Rect => {
-                    let len = type_info.referents.len();
+                    let len = referents.len();
                     let mut x_min = vec![0.0; len];
                     let mut y_min = vec![0.0; len];
                     let mut x_max = vec![0.0; len];
@@ -1088,7 +2258,7 @@ impl<R: Read> BinaryDeserializer<R> {
                         },
                     );
 
-                    for (value, referent) in values.zip(&type_info.referents) {
+                    for (value, referent) in values.zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value)
                     }
@@ -1104,7 +2274,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::PhysicalProperties => match canonical_type {
                 VariantType::PhysicalProperties => {
-                    for referent in &type_info.referents {
+                    for referent in referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let value = if chunk.read_u8()? == 1 {
                             Variant::PhysicalProperties(PhysicalProperties::Custom(
@@ -1133,8 +2303,8 @@ impl<R: Read> BinaryDeserializer<R> {
                 }
             },
             Type::Color3uint8 => match canonical_type {
-                VariantType::Color3 => {
-                    let len = type_info.referents.len();
+                VariantType::Color3 | VariantType::Color3uint8 => {
+                    let len = referents.len();
                     let mut r = vec![0; len];
                     let mut g = vec![0; len];
                     let mut b = vec![0; len];
@@ -1149,26 +2319,36 @@ impl<R: Read> BinaryDeserializer<R> {
                         .zip(b)
                         .map(|((r, g), b)| Color3uint8::new(r, g, b));
 
-                    for (color, referent) in colors.into_iter().zip(&type_info.referents) {
+                    for (color, referent) in colors.into_iter().zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
-                        instance.builder.add_property(&canonical_name, color);
+
+                        // Properties like `BasePart.Color` are Color3 in the
+                        // reflection database but serialized as Color3uint8.
+                        // Widening them here keeps us in step with rbx_xml.
+                        if canonical_type == VariantType::Color3 {
+                            instance
+                                .builder
+                                .add_property(&canonical_name, Color3::from(color));
+                        } else {
+                            instance.builder.add_property(&canonical_name, color);
+                        }
                     }
                 }
                 invalid_type => {
                     return Err(InnerError::PropTypeMismatch {
                         type_name: type_info.type_name.clone(),
                         prop_name,
-                        valid_type_names: "Color3",
+                        valid_type_names: "Color3 or Color3uint8",
                         actual_type_name: format!("{:?}", invalid_type),
                     });
                 }
             },
             Type::Int64 => match canonical_type {
                 VariantType::Int64 => {
-                    let mut values = vec![0; type_info.referents.len()];
+                    let mut values = vec![0; referents.len()];
                     chunk.read_interleaved_i64_array(&mut values)?;
 
-                    for (value, referent) in values.into_iter().zip(&type_info.referents) {
+                    for (value, referent) in values.into_iter().zip(referents) {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         instance.builder.add_property(&canonical_name, value);
                     }
@@ -1184,10 +2364,10 @@ impl<R: Read> BinaryDeserializer<R> {
             },
             Type::SharedString => match canonical_type {
                 VariantType::SharedString => {
-                    let mut values = vec![0; type_info.referents.len()];
+                    let mut values = vec![0; referents.len()];
                     chunk.read_interleaved_u32_array(&mut values)?;
 
-                    for (value, referent) in values.into_iter().zip(&type_info.referents) {
+                    for (value, referent) in values.into_iter().zip(referents) {
                         let shared_string =
                             self.shared_strings.get(value as usize).ok_or_else(|| {
                                 InnerError::InvalidPropData {
@@ -1216,7 +2396,7 @@ impl<R: Read> BinaryDeserializer<R> {
             },
         }
 
-        Ok(())
+        Ok(count)
     }
 
     fn decode_prnt_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
@@ -1233,14 +2413,42 @@ impl<R: Read> BinaryDeserializer<R> {
 
         log::trace!("PRNT chunk ({} instances)", number_objects);
 
-        let mut subjects = vec![0; number_objects as usize];
-        let mut parents = vec![0; number_objects as usize];
+        check_count(chunk, "PRNT", "links", number_objects, 8)?;
+
+        let mut subjects = vec![BinaryReferent::NONE; number_objects as usize];
+        let mut parents = vec![BinaryReferent::NONE; number_objects as usize];
 
         chunk.read_referent_array(&mut subjects)?;
         chunk.read_referent_array(&mut parents)?;
 
         for (id, parent_ref) in subjects.iter().copied().zip(parents.iter().copied()) {
-            if parent_ref == -1 {
+            if self.options.recover {
+                self.parented_refs.insert(id);
+            }
+
+            // If either side of this link refers to an instance we don't know
+            // about, its INST chunk was either missing or lost to damage.
+            let unknown_ref = [id, parent_ref].iter().copied().find(|referent| {
+                !referent.is_none() && !self.instances_by_ref.contains_key(referent)
+            });
+
+            if let Some(referent) = unknown_ref {
+                if self.options.recover {
+                    self.add_diagnostic(
+                        DiagnosticCode::UndeclaredInstance,
+                        Some(b"PRNT"),
+                        format!(
+                            "Dropped instance {} with parent {} because instance {} is not declared",
+                            id, parent_ref, referent
+                        ),
+                    );
+                    continue;
+                } else {
+                    return Err(InnerError::InvalidReferent { referent });
+                }
+            }
+
+            if parent_ref.is_none() {
                 self.root_instance_refs.push(id);
             } else {
                 let instance = self.instances_by_ref.get_mut(&parent_ref).unwrap();
@@ -1262,10 +2470,58 @@ impl<R: Read> BinaryDeserializer<R> {
     }
 
     /// Combines together all the decoded information to build and emplace
-    /// instances in our tree.
-    fn finish(mut self) -> WeakDom {
+    /// instances in the given tree under `parent`, returning the referents of
+    /// the top-level instances.
+    ///
+    /// The returned summary is filled out with everything except the number of
+    /// bytes read, which the deserializer doesn't track.
+    fn finish(
+        mut self,
+        tree: &mut WeakDom,
+        parent: Ref,
+    ) -> Result<(Vec<Ref>, DecodeSummary), InnerError> {
+        phase_span!("build_dom");
         log::trace!("Constructing tree from deserialized data");
 
+        // A RefProvider can hand out Refs that are already in the tree, like
+        // when a clone of a seeded provider is used to decode into the same
+        // tree twice. We check for that before inserting anything so that the
+        // tree is left alone.
+        let mut referents = HashSet::with_capacity(self.instances_by_ref.len());
+        for instance in self.instances_by_ref.values() {
+            let referent = instance.builder.referent();
+
+            if tree.get_by_ref(referent).is_some() || !referents.insert(referent) {
+                return Err(InnerError::DuplicateRef { referent });
+            }
+        }
+
+        if self.options.recover {
+            // Instances that were never mentioned by a PRNT chunk are most
+            // likely there because the PRNT chunk was lost. Rather than lose
+            // these instances too, we put them at the top level of the tree.
+            let mut orphaned_refs: Vec<BinaryReferent> = self
+                .instances_by_ref
+                .keys()
+                .copied()
+                .filter(|referent| !self.parented_refs.contains(referent))
+                .collect();
+            orphaned_refs.sort_unstable();
+
+            if !orphaned_refs.is_empty() {
+                self.add_diagnostic(
+                    DiagnosticCode::OrphanedInstances,
+                    None,
+                    format!(
+                        "Placed {} instances with no known parent at the top level",
+                        orphaned_refs.len()
+                    ),
+                );
+            }
+
+            self.root_instance_refs.extend(orphaned_refs);
+        }
+
         // Track all the instances we need to construct. Order of construction
         // is important to preserve for both determinism and sometimes
         // functionality of models we handle.
@@ -1274,25 +2530,124 @@ impl<R: Read> BinaryDeserializer<R> {
         // Any instance with a parent of -1 will be at the top level of the
         // tree. Because of the way rbx_dom_weak generally works, we need to
         // start at the top of the tree to begin construction.
-        let root_ref = self.tree.root_ref();
         for &referent in &self.root_instance_refs {
-            instances_to_construct.push_back((referent, root_ref));
+            instances_to_construct.push_back((referent, parent));
         }
 
+        let mut roots = Vec::with_capacity(self.root_instance_refs.len());
+
+        let mut instance_count = 0;
+        let mut filtered_out = HashSet::new();
+
         while let Some((referent, parent_ref)) = instances_to_construct.pop_front() {
-            let instance = self.instances_by_ref.remove(&referent).unwrap();
-            let id = self.tree.insert(parent_ref, instance.builder);
+            // A damaged file can mention the same instance more than once,
+            // which we can only honor the first time.
+            let instance = match self.instances_by_ref.remove(&referent) {
+                Some(instance) => instance,
+                None => continue,
+            };
+            let id = tree.insert(parent_ref, instance.builder);
+
+            if !self
+                .options
+                .keeps_class(&tree.get_by_ref(id).unwrap().class)
+            {
+                // The children of a filtered out instance are constructed
+                // next so that they end up where it was among its siblings.
+                filtered_out.insert(id);
+                for referent in instance.children.into_iter().rev() {
+                    instances_to_construct.push_front((referent, parent_ref));
+                }
+
+                continue;
+            }
+
+            instance_count += 1;
+
+            if parent_ref == parent {
+                roots.push(id);
+            }
 
             for referent in instance.children {
                 instances_to_construct.push_back((referent, id));
             }
         }
 
-        self.tree
+        if !filtered_out.is_empty() {
+            remove_filtered_out(tree, &roots, &filtered_out);
+        }
+
+        // Instances that were filtered out or never given a place in the tree
+        // don't have a location worth reporting.
+        self.source_locations
+            .retain(|referent, _| tree.get_by_ref(*referent).is_some());
+
+        let summary = DecodeSummary {
+            bytes_read: 0,
+            instance_count,
+            property_count: self.property_count,
+            recovered: self.recovered,
+            diagnostics: self.diagnostics,
+            normalized_content: self
+                .content_normalizer
+                .map(ContentNormalizer::into_changes)
+                .unwrap_or_default(),
+            source_locations: self.source_locations,
+        };
+
+        Ok((roots, summary))
+    }
+}
+
+impl<'a> BinaryDeserializer<&'a [u8]> {
+    /// Decodes every chunk in the file that can be decoded, skipping over any
+    /// damaged chunks.
+    ///
+    /// `file` must be the entire file that this deserializer is reading from.
+    fn decode_chunks_recovering(&mut self, file: &'a [u8]) {
+        loop {
+            let offset = file.len() - self.input.len();
+            self.chunk_offset = offset as u64;
+
+            if self.input.is_empty() {
+                self.add_diagnostic(
+                    DiagnosticCode::MissingEndChunk,
+                    None,
+                    "The file ended without an END chunk",
+                );
+                return;
+            }
+
+            match Chunk::decode(&mut self.input) {
+                Ok(chunk) => match self.decode_chunk(&chunk) {
+                    Ok(true) => return,
+                    Ok(false) => {}
+                    Err(err) => {
+                        self.add_diagnostic(DiagnosticCode::DamagedChunk, Some(&chunk.name), err);
+                    }
+                },
+                Err(err) => {
+                    self.add_diagnostic(DiagnosticCode::UnreadableChunkHeader, None, err);
+
+                    // We can't trust the header of this chunk, so we don't know
+                    // where it ends. Instead, we look for the next thing that
+                    // looks like the start of a chunk.
+                    let search_start = offset + 1;
+                    match find_next_chunk(&file[search_start..]) {
+                        Some(next_offset) => self.input = &file[search_start + next_offset..],
+                        None => return,
+                    }
+                }
+            }
+        }
     }
 }
 
 impl FileHeader {
+    /// The size of the header in bytes, which is also where the first chunk
+    /// starts.
+    const LEN: u64 = 32;
+
     pub(crate) fn decode<R: Read>(mut source: R) -> Result<Self, InnerError> {
         let mut magic_header = [0; 8];
         source.read_exact(&mut magic_header)?;
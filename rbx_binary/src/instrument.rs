@@ -0,0 +1,44 @@
+//! Optional instrumentation through the `tracing` crate, enabled with the
+//! `tracing` feature. Without that feature, these macros expand to nothing and
+//! their arguments are never evaluated.
+
+/// Enters an info-level span covering one phase of decoding or encoding. The
+/// span lasts until the end of the enclosing block.
+#[cfg(feature = "tracing")]
+macro_rules! phase_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        let _span = tracing::info_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! phase_span {
+    ($($args:tt)*) => {};
+}
+
+/// Like `phase_span`, but at debug level, for spans that are created once per
+/// chunk.
+#[cfg(feature = "tracing")]
+macro_rules! detail_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        let _span = tracing::debug_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! detail_span {
+    ($($args:tt)*) => {};
+}
+
+/// Emits a debug-level event, used to report counts and sizes.
+#[cfg(feature = "tracing")]
+macro_rules! debug_event {
+    ($($args:tt)*) => {
+        tracing::debug!($($args)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug_event {
+    ($($args:tt)*) => {};
+}
@@ -1,12 +1,26 @@
 //! Implementation of Roblox's binary model (rbxm) and place (rbxl) file
 //! formats.
+//!
+//! ## Malformed files
+//! Decoding a file never panics, no matter how it's damaged: a bad file is a
+//! [`DecodeError`], or a diagnostic when decoding with
+//! [`DecodeOptions::recover`]. The counts and lengths that a file gives are
+//! checked against how much of the file is left before space is set aside for
+//! them, so a small file can't make rbx_binary run out of memory either. The
+//! test suite checks this by decoding files that have been cut short or
+//! damaged in every position.
 
 #![deny(missing_docs)]
 
+#[macro_use]
+mod instrument;
+
 mod chunk;
 mod core;
 mod deserializer;
+mod peek;
 mod serializer;
+mod summary;
 mod types;
 
 #[cfg(any(test, feature = "unstable_text_format"))]
@@ -17,9 +31,16 @@ mod tests;
 
 use std::io::{Read, Write};
 
-use rbx_dom_weak::{types::Ref, WeakDom};
+use rbx_dom_weak::{
+    types::{Ref, VariantType},
+    WeakDom,
+};
 
-use crate::{deserializer::decode, serializer::encode};
+use crate::{
+    deserializer::{decode, decode_into_inner, decode_with_summary},
+    serializer::{encode, encode_with_summary},
+    types::SUPPORTED_TYPES,
+};
 
 /// An unstable textual format that can be used to debug binary models.
 #[cfg(feature = "unstable_text_format")]
@@ -27,16 +48,91 @@ pub mod text_format {
     pub use crate::text_deserializer::*;
 }
 
-pub use crate::{deserializer::Error as DecodeError, serializer::Error as EncodeError};
+pub use crate::{
+    deserializer::{DecodeErrorKind, DecodeOptions, Error as DecodeError, InvalidStringBehavior},
+    peek::PeekInfo,
+    serializer::{CompressionBackend, EncodeOptions, Error as EncodeError},
+    summary::{DecodeSummary, Diagnostic, DiagnosticCode, EncodeSummary, Severity, SourceLocation},
+};
 
 /// Decodes an binary format model or place from something that implements the
 /// `std::io::Read` trait.
+pub fn from_reader<R: Read>(reader: R, options: DecodeOptions) -> Result<WeakDom, DecodeError> {
+    decode(reader, options)
+}
+
+/// Decodes an binary format model or place from something that implements the
+/// `std::io::Read` trait using the default decoder options.
 pub fn from_reader_default<R: Read>(reader: R) -> Result<WeakDom, DecodeError> {
-    decode(reader)
+    decode(reader, DecodeOptions::default())
+}
+
+/// Decodes an binary format model or place from something that implements the
+/// `std::io::Read` trait, also returning a summary of what was read.
+pub fn from_reader_with_summary<R: Read>(
+    reader: R,
+    options: DecodeOptions,
+) -> Result<(WeakDom, DecodeSummary), DecodeError> {
+    decode_with_summary(reader, options)
+}
+
+/// Decodes a binary format model or place into an existing DOM, inserting
+/// each of its top-level instances under `parent` and returning their
+/// referents in order.
+///
+/// This is useful for decoding many files into one DOM without building a new
+/// DOM for each one. Ref properties only ever point to instances from the same
+/// file. Nothing is inserted if decoding fails.
+///
+/// ## Panics
+/// Panics if `parent` does not refer to an instance in the DOM, or if a
+/// decoded instance is given a `Ref` that's already in it, which can only
+/// happen with a [`DecodeOptions::ref_provider`] that repeats Refs.
+pub fn decode_into<R: Read>(
+    dom: &mut WeakDom,
+    parent: Ref,
+    reader: R,
+    options: DecodeOptions,
+) -> Result<Vec<Ref>, DecodeError> {
+    let (roots, _summary) = decode_into_inner(dom, parent, reader, options)?;
+    Ok(roots)
+}
+
+/// Reads the metadata and top-level instances of a binary format model or
+/// place, without decoding the rest of the file.
+///
+/// Only the chunks that say which instances there are and where they go are
+/// read in full, so this is much faster than decoding the whole file. Shared
+/// strings aren't decompressed at all.
+pub fn peek<R: Read>(reader: R) -> Result<PeekInfo, DecodeError> {
+    peek::peek(reader)
+}
+
+/// Lists every type of property value that rbx_binary can encode, taken from
+/// the table that maps each one to its binary type. Encoding a property of
+/// any other type is an error.
+///
+/// Passing this to `ReflectionDatabase::unsupported_properties` lists the
+/// properties that rbx_binary can't write.
+pub fn supported_types() -> &'static [VariantType] {
+    SUPPORTED_TYPES
 }
 
 /// Serializes a subset of the given DOM to a binary format model or place,
 /// writing to something that implements the `std::io::Write` trait.
+pub fn to_writer<W: Write>(
+    writer: W,
+    dom: &WeakDom,
+    refs: &[Ref],
+    options: EncodeOptions,
+) -> Result<(), EncodeError> {
+    encode_with_summary(dom, refs, writer, options)?;
+    Ok(())
+}
+
+/// Serializes a subset of the given DOM to a binary format model or place,
+/// writing to something that implements the `std::io::Write` trait using the
+/// default encoder options.
 pub fn to_writer_default<W: Write>(
     writer: W,
     dom: &WeakDom,
@@ -44,3 +140,15 @@ pub fn to_writer_default<W: Write>(
 ) -> Result<(), EncodeError> {
     encode(dom, refs, writer)
 }
+
+/// Serializes a subset of the given DOM to a binary format model or place,
+/// writing to something that implements the `std::io::Write` trait and
+/// returning a summary of what was written.
+pub fn to_writer_with_summary<W: Write>(
+    writer: W,
+    dom: &WeakDom,
+    refs: &[Ref],
+    options: EncodeOptions,
+) -> Result<EncodeSummary, EncodeError> {
+    encode_with_summary(dom, refs, writer, options)
+}
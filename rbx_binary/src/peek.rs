@@ -0,0 +1,203 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::Read,
+};
+
+use crate::{
+    chunk::Chunk,
+    core::{preallocation, BinaryReferent, RbxReadExt},
+    deserializer::{check_count, Error, FileHeader, InnerError},
+    types::Type,
+};
+
+/// What a binary model or place says about itself as a whole, read without
+/// decoding any of its instances' properties.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PeekInfo {
+    pub(crate) metadata: HashMap<String, String>,
+    pub(crate) class_count: u32,
+    pub(crate) instance_count: u32,
+    pub(crate) top_level: Vec<(String, String)>,
+}
+
+impl PeekInfo {
+    /// The file's metadata, like `ExplicitAutoJoints`, from its META chunk.
+    #[inline]
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.metadata
+    }
+
+    /// The number of classes that the file's header says it has instances
+    /// of.
+    #[inline]
+    pub fn class_count(&self) -> u32 {
+        self.class_count
+    }
+
+    /// The number of instances that the file's header says it describes.
+    #[inline]
+    pub fn instance_count(&self) -> u32 {
+        self.instance_count
+    }
+
+    /// The ClassName and Name of each of the file's top-level instances, in
+    /// the order they would be decoded in. For places, these are the
+    /// services.
+    #[inline]
+    pub fn top_level(&self) -> &[(String, String)] {
+        &self.top_level
+    }
+}
+
+/// An INST chunk, kept until the PRNT chunk says which of its instances are at
+/// the top level.
+struct Class {
+    type_name: String,
+    referents: Vec<BinaryReferent>,
+
+    /// The contents of the class's Name PROP chunk after its header, which are
+    /// only read for the classes that have top-level instances.
+    names: Option<Vec<u8>>,
+}
+
+pub(crate) fn peek<R: Read>(reader: R) -> Result<PeekInfo, Error> {
+    Ok(peek_inner(reader)?)
+}
+
+fn peek_inner<R: Read>(mut reader: R) -> Result<PeekInfo, InnerError> {
+    let header = FileHeader::decode(&mut reader)?;
+
+    let mut info = PeekInfo {
+        class_count: header.num_types,
+        instance_count: header.num_instances,
+        ..Default::default()
+    };
+
+    let mut classes = HashMap::with_capacity(preallocation(header.num_types as usize));
+    let mut top_level_refs = Vec::new();
+
+    loop {
+        // Shared strings are only ever used by properties, so there's no
+        // reason to decompress them.
+        let chunk = Chunk::decode_skipping(&mut reader, &[b"SSTR"])?;
+        let mut data = chunk.data.as_slice();
+
+        match &chunk.name {
+            b"META" => {
+                let len = data.read_le_u32()?;
+
+                for _ in 0..len {
+                    let key = data.read_string()?;
+                    let value = data.read_string()?;
+                    info.metadata.insert(key, value);
+                }
+            }
+            b"INST" => {
+                let type_id = data.read_le_u32()?;
+                let type_name = data.read_string()?;
+                let _object_format = data.read_u8()?;
+                let number_instances = data.read_le_u32()?;
+
+                check_count(data, "INST", "instances", number_instances, 4)?;
+
+                let mut referents = vec![BinaryReferent::NONE; number_instances as usize];
+                data.read_referent_array(&mut referents)?;
+
+                classes.insert(
+                    type_id,
+                    Class {
+                        type_name,
+                        referents,
+                        names: None,
+                    },
+                );
+            }
+            b"PROP" => {
+                let type_id = data.read_le_u32()?;
+                let prop_name = data.read_string()?;
+                let binary_type = data.read_u8()?;
+
+                if prop_name == "Name" && binary_type == Type::String as u8 {
+                    let class = classes
+                        .get_mut(&type_id)
+                        .ok_or(InnerError::InvalidTypeId { type_id })?;
+                    class.names = Some(data.to_vec());
+                }
+            }
+            b"PRNT" => {
+                let version = data.read_u8()?;
+
+                if version != 0 {
+                    return Err(InnerError::UnknownChunkVersion {
+                        chunk_name: "PRNT",
+                        version: version as u32,
+                    });
+                }
+
+                let number_objects = data.read_le_u32()?;
+
+                check_count(data, "PRNT", "links", number_objects, 8)?;
+
+                let mut subjects = vec![BinaryReferent::NONE; number_objects as usize];
+                let mut parents = vec![BinaryReferent::NONE; number_objects as usize];
+
+                data.read_referent_array(&mut subjects)?;
+                data.read_referent_array(&mut parents)?;
+
+                for (referent, parent) in subjects.into_iter().zip(parents) {
+                    if parent.is_none() {
+                        top_level_refs.push(referent);
+                    }
+                }
+            }
+            b"END\0" => break,
+            _ => {}
+        }
+    }
+
+    // Where each top-level instance is in its class's list of instances, which
+    // is also where its name is in the class's Name chunk.
+    let mut positions = HashMap::with_capacity(top_level_refs.len());
+    for (type_id, class) in &classes {
+        for (index, referent) in class.referents.iter().enumerate() {
+            positions.insert(*referent, (*type_id, index));
+        }
+    }
+
+    let mut names_by_type: HashMap<u32, Vec<String>> = HashMap::new();
+
+    for referent in top_level_refs {
+        let (type_id, index) = *positions
+            .get(&referent)
+            .ok_or(InnerError::InvalidReferent { referent })?;
+        let class = &classes[&type_id];
+
+        let names = match names_by_type.entry(type_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut names = Vec::new();
+
+                if let Some(mut data) = class.names.as_deref() {
+                    for _ in 0..class.referents.len() {
+                        let name = data.read_binary_string()?;
+                        names.push(String::from_utf8_lossy(&name).into_owned());
+                    }
+                }
+
+                entry.insert(names)
+            }
+        };
+
+        // Like the deserializer, instances without a name are named after
+        // their class.
+        let name = match names.get(index) {
+            Some(name) => name.clone(),
+            None => class.type_name.clone(),
+        };
+
+        info.top_level.push((class.type_name.clone(), name));
+    }
+
+    Ok(info)
+}
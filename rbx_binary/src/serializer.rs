@@ -1,15 +1,16 @@
 use std::{
     borrow::{Borrow, Cow},
     collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
-    convert::TryInto,
+    convert::TryFrom,
     io::{self, Write},
+    ops::Range,
     u32,
 };
 
 use rbx_dom_weak::{
     types::{
         Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
-        ColorSequenceKeypoint, Enum, Faces, Matrix3, NumberRange, NumberSequence,
+        ColorSequenceKeypoint, CountingWriter, Enum, Faces, Matrix3, NumberRange, NumberSequence,
         NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref, SharedString, UDim, UDim2,
         Variant, VariantType, Vector2, Vector3, Vector3int16,
     },
@@ -21,8 +22,10 @@ use thiserror::Error;
 use crate::{
     chunk::{ChunkBuilder, ChunkCompression},
     core::{
-        find_property_descriptors, RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE, FILE_VERSION,
+        find_property_descriptors, BinaryReferent, RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE,
+        FILE_VERSION,
     },
+    summary::{Diagnostic, DiagnosticCode, EncodeSummary},
     types::Type,
 };
 
@@ -72,21 +75,216 @@ enum InnerError {
 
     #[error("The instance with referent {referent:?} was not present in the dom.")]
     InvalidInstanceId { referent: Ref },
+
+    #[error(
+        "Binary files can hold at most {} instances, but {count} were given",
+        i32::MAX
+    )]
+    TooManyInstances { count: usize },
+
+    #[error(
+        "Property {type_name}.{prop_name} on instance {instance_full_name} takes {len} bytes \
+        to write, which doesn't fit in a chunk of at most {max_len} bytes"
+    )]
+    PropTooLarge {
+        type_name: String,
+        prop_name: String,
+        instance_full_name: String,
+        len: usize,
+        max_len: usize,
+    },
+
+    #[error(
+        "The {chunk} is {len} bytes long, which is more than the maximum of {max_len} bytes, \
+        and it can't be split"
+    )]
+    ChunkTooLarge {
+        chunk: String,
+        len: usize,
+        max_len: usize,
+    },
+}
+
+/// Options available for serializing a binary model or place.
+#[derive(Debug, Clone)]
+pub struct EncodeOptions {
+    omit_default_names: bool,
+    max_chunk_len: Option<usize>,
+    class_count_warning: Option<usize>,
+    compression: CompressionBackend,
+    compression_level: u32,
+}
+
+impl EncodeOptions {
+    /// Constructs a `EncodeOptions` with all values set to their defaults.
+    #[inline]
+    pub fn new() -> Self {
+        EncodeOptions {
+            omit_default_names: false,
+            max_chunk_len: None,
+            class_count_warning: None,
+            compression: CompressionBackend::Lz4,
+            compression_level: 0,
+        }
+    }
+
+    /// Determines whether rbx_binary will leave out the `Name` property for
+    /// classes where every instance is named after its ClassName.
+    ///
+    /// Instances without a `Name` are given their ClassName as a name when
+    /// they're decoded by rbx_binary or rbx_xml, so this doesn't lose any
+    /// information.
+    ///
+    /// Classes whose instances have no
+    /// [`explicit_name`][rbx_dom_weak::Instance::explicit_name], like ones
+    /// decoded with `DecodeOptions::preserve_missing_names`, never get a Name,
+    /// whether this is on or not.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn omit_default_names(self, omit_default_names: bool) -> Self {
+        EncodeOptions {
+            omit_default_names,
+            ..self
+        }
+    }
+
+    /// Sets the largest size, in bytes, that any chunk may have before it's
+    /// compressed.
+    ///
+    /// When a class has too many instances for its INST or PROP chunks to fit,
+    /// its instances are split across several class groups, each with their
+    /// own chunks. Chunks that can't be split, like a PROP chunk holding a
+    /// single value that is too large by itself, cause encoding to fail with
+    /// an error naming the class and property.
+    ///
+    /// By default, chunks can be any size.
+    #[inline]
+    pub fn max_chunk_len(self, max_chunk_len: Option<usize>) -> Self {
+        EncodeOptions {
+            max_chunk_len,
+            ..self
+        }
+    }
+
+    /// Sets how many class groups a file may have before rbx_binary adds a
+    /// warning to the [`EncodeSummary`]'s diagnostics. Each class gets at
+    /// least one group, and more if it's split by
+    /// [`max_chunk_len`][EncodeOptions::max_chunk_len].
+    ///
+    /// By default, no warning is given.
+    #[inline]
+    pub fn class_count_warning(self, class_count_warning: Option<usize>) -> Self {
+        EncodeOptions {
+            class_count_warning,
+            ..self
+        }
+    }
+
+    /// Sets how the chunks of the file are compressed.
+    ///
+    /// By default, chunks are compressed with LZ4.
+    #[inline]
+    pub fn compression(self, compression: CompressionBackend) -> Self {
+        EncodeOptions {
+            compression,
+            ..self
+        }
+    }
+
+    /// Sets how hard the compression backend tries to make chunks smaller.
+    /// Higher levels write smaller files, but take longer to do it.
+    ///
+    /// For LZ4, level 0 uses the same fast compressor as Roblox, and levels 1
+    /// through 12 use LZ4's high compression mode. Levels above 12 are treated
+    /// as 12. Every level writes LZ4 data that any version of Roblox can read.
+    /// The level has no effect on uncompressed files.
+    ///
+    /// This is 0 by default.
+    #[inline]
+    pub fn compression_level(self, compression_level: u32) -> Self {
+        EncodeOptions {
+            compression_level,
+            ..self
+        }
+    }
+
+    fn chunk_compression(&self) -> ChunkCompression {
+        match self.compression {
+            CompressionBackend::Lz4 => ChunkCompression::Lz4 {
+                level: self.compression_level,
+            },
+            CompressionBackend::Uncompressed => ChunkCompression::Uncompressed,
+        }
+    }
+}
+
+/// The ways that rbx_binary can compress the chunks of a file, set with
+/// [`EncodeOptions::compression`].
+///
+/// Newer versions of Roblox also write chunks compressed with ZSTD, but older
+/// versions can only read LZ4, so rbx_binary sticks to the formats that every
+/// version can read. rbx_binary can't decode ZSTD chunks either, and reports
+/// an error when it finds one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompressionBackend {
+    /// Compress chunks with LZ4, like Roblox does.
+    ///
+    /// This is the default.
+    Lz4,
+
+    /// Leave chunks uncompressed. Files are larger, but are the fastest to
+    /// write and read.
+    Uncompressed,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> EncodeOptions {
+        EncodeOptions::new()
+    }
 }
 
 /// Serializes instances from an `WeakDom` into a writer in Roblox's binary
-/// model format.
+/// model format using the default encoder options.
 pub fn encode<W: Write>(dom: &WeakDom, refs: &[Ref], writer: W) -> Result<(), Error> {
-    let mut serializer = BinarySerializer::new(dom, writer);
+    encode_with_summary(dom, refs, writer, EncodeOptions::default())?;
+    Ok(())
+}
+
+/// Serializes instances from an `WeakDom` into a writer in Roblox's binary
+/// model format, returning information about what was written.
+pub fn encode_with_summary<W: Write>(
+    dom: &WeakDom,
+    refs: &[Ref],
+    writer: W,
+    options: EncodeOptions,
+) -> Result<EncodeSummary, Error> {
+    phase_span!("encode");
+
+    let mut serializer = BinarySerializer::new(
+        dom,
+        CountingWriter::new(writer),
+        options.max_chunk_len,
+        options.chunk_compression(),
+    );
 
     serializer.add_instances(refs)?;
 
+    serializer.omit_default_names(options.omit_default_names);
+
     log::debug!("Type info discovered: {:#?}", serializer.type_infos);
 
-    serializer.generate_referents();
+    serializer.generate_referents()?;
 
     log::trace!("Referents constructed: {:#?}", serializer.id_to_referent);
 
+    serializer.plan_type_groups()?;
+
+    if let Some(max_count) = options.class_count_warning {
+        serializer.check_class_count(max_count);
+    }
+
     serializer.write_header()?;
     serializer.serialize_metadata()?;
     serializer.serialize_shared_strings()?;
@@ -95,7 +293,16 @@ pub fn encode<W: Write>(dom: &WeakDom, refs: &[Ref], writer: W) -> Result<(), Er
     serializer.serialize_parents()?;
     serializer.serialize_end()?;
 
-    Ok(())
+    let summary = serializer.summary();
+
+    debug_event!(
+        bytes_written = summary.bytes_written,
+        instances = summary.instance_count,
+        properties = summary.property_count,
+        "encoded file"
+    );
+
+    Ok(summary)
 }
 
 /// Represents all of the state during a single serialization session. A new
@@ -114,7 +321,7 @@ struct BinarySerializer<'a, W> {
 
     /// A map from rbx-dom's unique instance ID (Ref) to the ID space used in
     /// the binary model format, signed integers.
-    id_to_referent: HashMap<Ref, i32>,
+    id_to_referent: HashMap<Ref, BinaryReferent>,
 
     /// All of the types of instance discovered by our serializer that we'll be
     /// writing into the output.
@@ -127,6 +334,23 @@ struct BinarySerializer<'a, W> {
     /// A map of SharedStrings to where it is in the SSTR chunk. This is used
     /// for writing PROP chunks.
     shared_string_ids: HashMap<SharedString, u32>,
+
+    /// The largest number of bytes that any chunk is allowed to have before
+    /// compression, if there's a limit.
+    max_chunk_len: Option<usize>,
+
+    /// How the chunks that hold instances and their properties are
+    /// compressed.
+    compression: ChunkCompression,
+
+    /// The class groups that will be written, in order. Every type gets at
+    /// least one group, and types with too many instances to fit in
+    /// `max_chunk_len` get more.
+    type_groups: Vec<TypeGroup>,
+
+    /// Problems found while encoding that didn't stop the file from being
+    /// written.
+    diagnostics: Vec<Diagnostic>,
 }
 
 /// An instance class that our serializer knows about. We should have one struct
@@ -157,6 +381,23 @@ struct TypeInfo {
     class_descriptor: Option<&'static ClassDescriptor<'static>>,
 }
 
+/// A run of instances of one type that are written together with their own
+/// INST chunk and type ID.
+#[derive(Debug)]
+struct TypeGroup {
+    /// The ClassName of the instances in this group, which is also the key of
+    /// its `TypeInfo`.
+    type_name: String,
+
+    /// The type ID written for this group. The first group of each type uses
+    /// the type's own ID.
+    type_id: u32,
+
+    /// The instances in this group, as a range within the type's
+    /// `object_refs`.
+    range: Range<usize>,
+}
+
 /// A property on a specific class that our serializer knows about.
 ///
 /// We should have one `PropInfo` per logical property per class that is used in
@@ -256,7 +497,7 @@ impl TypeInfos {
                     prop_type: Type::String,
                     serialized_name: Cow::Borrowed("Name"),
                     aliases: BTreeSet::new(),
-                    default_value: Cow::Owned(Variant::String(String::new())),
+                    default_value: Cow::Owned(Variant::String("".into())),
                 },
             );
 
@@ -279,21 +520,32 @@ impl TypeInfos {
 }
 
 impl<'a, W: Write> BinarySerializer<'a, W> {
-    fn new(dom: &'a WeakDom, output: W) -> Self {
+    fn new(
+        dom: &'a WeakDom,
+        output: W,
+        max_chunk_len: Option<usize>,
+        compression: ChunkCompression,
+    ) -> Self {
         BinarySerializer {
             dom,
             output,
+            compression,
             relevant_instances: Vec::new(),
             id_to_referent: HashMap::new(),
             type_infos: TypeInfos::new(),
             shared_strings: Vec::new(),
             shared_string_ids: HashMap::new(),
+            max_chunk_len,
+            type_groups: Vec::new(),
+            diagnostics: Vec::new(),
         }
     }
 
     /// Mark the given instance IDs and all of their descendants as intended for
     /// serialization with this serializer.
     fn add_instances(&mut self, referents: &[Ref]) -> Result<(), InnerError> {
+        phase_span!("add_instances");
+
         let mut to_visit = VecDeque::new();
         to_visit.extend(referents);
 
@@ -301,8 +553,10 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
             self.relevant_instances.push(referent);
             self.collect_type_info(referent)?;
 
-            // TODO: Turn into error
-            let instance = self.dom.get_by_ref(referent).unwrap();
+            let instance = self
+                .dom
+                .get_by_ref(referent)
+                .ok_or(InnerError::InvalidInstanceId { referent })?;
             to_visit.extend(instance.children());
         }
 
@@ -437,26 +691,213 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
         Ok(())
     }
 
+    /// Removes the Name property from every type whose instances all have no
+    /// Name of their own, or, with `all_defaults`, are all named after their
+    /// ClassName. Decoders fill in that name on their own.
+    fn omit_default_names(&mut self, all_defaults: bool) {
+        let dom = self.dom;
+
+        for (type_name, type_info) in &mut self.type_infos.values {
+            let all_default = type_info.object_refs.iter().all(|id| {
+                // This unwrap will not panic because any ID in object_refs
+                // must be part of this dom.
+                let instance = dom.get_by_ref(*id).unwrap();
+
+                instance.explicit_name().is_none() || (all_defaults && instance.name == *type_name)
+            });
+
+            if all_default {
+                type_info.properties.remove("Name");
+            }
+        }
+    }
+
     /// Populate the map from rbx-dom's instance ID space to the IDs that we'll
     /// be serializing to the model.
-    fn generate_referents(&mut self) {
-        self.id_to_referent.reserve(self.relevant_instances.len());
+    fn generate_referents(&mut self) -> Result<(), InnerError> {
+        phase_span!("generate_referents");
+
+        let count = self.relevant_instances.len();
+        if i32::try_from(count).is_err() {
+            return Err(InnerError::TooManyInstances { count });
+        }
+
+        self.id_to_referent.reserve(count);
 
         for (next_referent, id) in self.relevant_instances.iter().enumerate() {
             self.id_to_referent
-                .insert(*id, next_referent.try_into().unwrap());
+                .insert(*id, BinaryReferent::new(next_referent as i32));
+        }
+
+        Ok(())
+    }
+
+    /// Decide which class groups to write. Without a chunk size limit, every
+    /// type is written as one group. With one, types are split into as many
+    /// groups as it takes for each of their INST and PROP chunks to fit.
+    fn plan_type_groups(&mut self) -> Result<(), InnerError> {
+        phase_span!("plan_type_groups");
+
+        let mut next_type_id = self.type_infos.next_type_id;
+        let mut type_groups = Vec::new();
+
+        for (type_name, type_info) in &self.type_infos.values {
+            let max_len = match self.max_chunk_len {
+                Some(max_len) => max_len,
+                None => {
+                    type_groups.push(TypeGroup {
+                        type_name: type_name.clone(),
+                        type_id: type_info.type_id,
+                        range: 0..type_info.object_refs.len(),
+                    });
+                    continue;
+                }
+            };
+
+            let inst_len = |count: usize| {
+                let service_len = if type_info.is_service { count } else { 0 };
+                4 + 4 + type_name.len() + 1 + 4 + 4 * count + service_len
+            };
+
+            // Every value takes up a fixed number of bytes in a PROP chunk no
+            // matter which other values are written next to it, so we can
+            // measure each value by writing it on its own.
+            let mut columns = Vec::with_capacity(type_info.properties.len());
+
+            for (prop_name, prop_info) in &type_info.properties {
+                let header_len = self
+                    .prop_chunk(type_name, 0, prop_name, prop_info, &[])?
+                    .len();
+
+                let mut value_lens = Vec::with_capacity(type_info.object_refs.len());
+                for referent in &type_info.object_refs {
+                    let chunk = self.prop_chunk(
+                        type_name,
+                        0,
+                        prop_name,
+                        prop_info,
+                        std::slice::from_ref(referent),
+                    )?;
+                    value_lens.push(chunk.len() - header_len);
+                }
+
+                columns.push((prop_name, header_len, value_lens));
+            }
+
+            // The first group of each type keeps the type's ID so that files
+            // that don't need splitting come out the same as they would
+            // without a limit.
+            let mut group_type_id = |start: usize| {
+                if start == 0 {
+                    type_info.type_id
+                } else {
+                    next_type_id += 1;
+                    next_type_id - 1
+                }
+            };
+
+            let mut start = 0;
+            let mut chunk_lens: Vec<usize> = columns.iter().map(|column| column.1).collect();
+
+            for i in 0..type_info.object_refs.len() {
+                let fits = inst_len(i - start + 1) <= max_len
+                    && columns
+                        .iter()
+                        .zip(&chunk_lens)
+                        .all(|((_, _, value_lens), len)| len + value_lens[i] <= max_len);
+
+                if !fits {
+                    if inst_len(1) > max_len {
+                        return Err(InnerError::ChunkTooLarge {
+                            chunk: format!("INST chunk for class {}", type_name),
+                            len: inst_len(1),
+                            max_len,
+                        });
+                    }
+
+                    for (prop_name, header_len, value_lens) in &columns {
+                        if header_len + value_lens[i] > max_len {
+                            return Err(InnerError::PropTooLarge {
+                                type_name: type_name.clone(),
+                                prop_name: prop_name.to_string(),
+                                instance_full_name: self
+                                    .dom
+                                    .full_name(type_info.object_refs[i])
+                                    .unwrap(),
+                                len: header_len + value_lens[i],
+                                max_len,
+                            });
+                        }
+                    }
+
+                    type_groups.push(TypeGroup {
+                        type_name: type_name.clone(),
+                        type_id: group_type_id(start),
+                        range: start..i,
+                    });
+
+                    start = i;
+                    for (len, (_, header_len, _)) in chunk_lens.iter_mut().zip(&columns) {
+                        *len = *header_len;
+                    }
+                }
+
+                for (len, (_, _, value_lens)) in chunk_lens.iter_mut().zip(&columns) {
+                    *len += value_lens[i];
+                }
+            }
+
+            type_groups.push(TypeGroup {
+                type_name: type_name.clone(),
+                type_id: group_type_id(start),
+                range: start..type_info.object_refs.len(),
+            });
+        }
+
+        if type_groups.len() > self.type_infos.values.len() {
+            log::debug!(
+                "Split {} types into {} class groups to fit chunks within {:?} bytes",
+                self.type_infos.values.len(),
+                type_groups.len(),
+                self.max_chunk_len
+            );
+        }
+
+        self.type_groups = type_groups;
+
+        Ok(())
+    }
+
+    /// Adds a warning to this serializer's diagnostics if it's going to write
+    /// more than `max_count` class groups.
+    fn check_class_count(&mut self, max_count: usize) {
+        let count = self.type_groups.len();
+
+        if count > max_count {
+            let message = format!(
+                "This file has {} class groups, which is more than the {} allowed by \
+                EncodeOptions::class_count_warning",
+                count, max_count
+            );
+            log::warn!("{}", message);
+
+            self.diagnostics.push(Diagnostic::new(
+                DiagnosticCode::ClassCountExceeded,
+                message,
+                0,
+            ));
         }
     }
 
     fn write_header(&mut self) -> Result<(), InnerError> {
+        phase_span!("write_header");
         log::trace!("Writing header");
 
         self.output.write_all(FILE_MAGIC_HEADER)?;
         self.output.write_all(FILE_SIGNATURE)?;
         self.output.write_le_u16(FILE_VERSION)?;
 
-        self.output
-            .write_le_u32(self.type_infos.values.len() as u32)?;
+        self.output.write_le_u32(self.type_groups.len() as u32)?;
         self.output
             .write_le_u32(self.relevant_instances.len() as u32)?;
         self.output.write_all(&[0; 8])?;
@@ -466,6 +907,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
 
     /// Write out any metadata about this file, stored in a chunk named META.
     fn serialize_metadata(&mut self) -> Result<(), InnerError> {
+        phase_span!("serialize_metadata");
         log::trace!("Writing metadata (currently no-op)");
         // TODO: There is no concept of metadata in a dom yet.
         Ok(())
@@ -474,13 +916,14 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
     /// Write out all of the SharedStrings in this file, if any exist,
     /// stored in a chunk named SSTR.
     fn serialize_shared_strings(&mut self) -> Result<(), InnerError> {
+        phase_span!("serialize_shared_strings");
         log::trace!("Writing shared string chunk");
 
         if self.shared_strings.is_empty() {
             return Ok(());
         }
 
-        let mut chunk = ChunkBuilder::new(b"SSTR", ChunkCompression::Compressed);
+        let mut chunk = ChunkBuilder::new(b"SSTR", self.compression);
 
         chunk.write_le_u32(0)?; // SSTR version number
         chunk.write_le_u32(self.shared_strings.len() as u32)?;
@@ -488,10 +931,14 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
         for shared_string in &self.shared_strings {
             // Better to write nothing than write half a hash
             chunk.write_all(&[0; 16])?;
-            chunk.write_binary_string(shared_string.data())?;
+
+            match shared_string.blob() {
+                Some(blob) => chunk.write_blob_string(blob)?,
+                None => chunk.write_binary_string(shared_string.data())?,
+            }
         }
 
-        chunk.dump(&mut self.output)?;
+        write_chunk(&mut self.output, chunk, self.max_chunk_len)?;
 
         Ok(())
     }
@@ -499,18 +946,24 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
     /// Write out the declarations of all instances, stored in a series of
     /// chunks named INST.
     fn serialize_instances(&mut self) -> Result<(), InnerError> {
+        phase_span!("serialize_instances");
         log::trace!("Writing instance chunks");
 
-        for (type_name, type_info) in &self.type_infos.values {
-            log::trace!(
-                "Writing chunk for {} ({} instances)",
-                type_name,
-                type_info.object_refs.len()
+        for group in &self.type_groups {
+            let type_name = &group.type_name;
+            let type_info = &self.type_infos.values[type_name];
+            let refs = &type_info.object_refs[group.range.clone()];
+
+            log::trace!("Writing chunk for {} ({} instances)", type_name, refs.len());
+            debug_event!(
+                class = %type_name,
+                instances = refs.len(),
+                "INST chunk"
             );
 
-            let mut chunk = ChunkBuilder::new(b"INST", ChunkCompression::Compressed);
+            let mut chunk = ChunkBuilder::new(b"INST", self.compression);
 
-            chunk.write_le_u32(type_info.type_id)?;
+            chunk.write_le_u32(group.type_id)?;
             chunk.write_string(type_name)?;
 
             // It's possible that this integer will be expanded in the future to
@@ -521,14 +974,9 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
             // instead of a bool.
             chunk.write_bool(type_info.is_service)?;
 
-            chunk.write_le_u32(type_info.object_refs.len() as u32)?;
+            chunk.write_le_u32(refs.len() as u32)?;
 
-            chunk.write_referent_array(
-                type_info
-                    .object_refs
-                    .iter()
-                    .map(|id| self.id_to_referent[id]),
-            )?;
+            chunk.write_referent_array(refs.iter().map(|id| self.id_to_referent[id]))?;
 
             if type_info.is_service {
                 // It's unclear what this byte is used for, but when the type is
@@ -538,12 +986,12 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                 // In 99.9% of cases, there's only going to be one copy of a
                 // given service, so we're not worried about doing this super
                 // efficiently.
-                for _ in 0..type_info.object_refs.len() {
+                for _ in 0..refs.len() {
                     chunk.write_u8(1)?;
                 }
             }
 
-            chunk.dump(&mut self.output)?;
+            write_chunk(&mut self.output, chunk, self.max_chunk_len)?;
         }
 
         Ok(())
@@ -553,9 +1001,14 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
     /// previously defined in the INST chunks. Property data is contained in
     /// chunks named PROP.
     fn serialize_properties(&mut self) -> Result<(), InnerError> {
+        phase_span!("serialize_properties");
         log::trace!("Writing properties");
 
-        for (type_name, type_info) in &self.type_infos.values {
+        for group in &self.type_groups {
+            let type_name = &group.type_name;
+            let type_info = &self.type_infos.values[type_name];
+            let refs = &type_info.object_refs[group.range.clone()];
+
             for (prop_name, prop_info) in &type_info.properties {
                 log::trace!(
                     "Writing property {}.{} (type {:?})",
@@ -563,504 +1016,517 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                     prop_name,
                     prop_info.prop_type
                 );
+                debug_event!(
+                    class = %type_name,
+                    property = %prop_name,
+                    instances = refs.len(),
+                    "PROP chunk"
+                );
 
-                let mut chunk = ChunkBuilder::new(b"PROP", ChunkCompression::Compressed);
-
-                chunk.write_le_u32(type_info.type_id)?;
-                chunk.write_string(&prop_info.serialized_name)?;
-                chunk.write_u8(prop_info.prop_type as u8)?;
-
-                let dom = &self.dom;
-                let values = type_info
-                    .object_refs
-                    .iter()
-                    .map(|id| {
-                        // This unwrap will not panic because we uphold the
-                        // invariant that any ID in object_refs must be part of
-                        // this dom.
-                        let instance = dom.get_by_ref(*id).unwrap();
-
-                        // We store the Name property in a different field for
-                        // convenience, but when serializing to the binary model
-                        // format we need to handle it just like other properties.
-                        if prop_name == "Name" {
-                            return Cow::Owned(Variant::String(instance.name.clone()));
-                        }
+                let chunk =
+                    self.prop_chunk(type_name, group.type_id, prop_name, prop_info, refs)?;
+                write_chunk(&mut self.output, chunk, self.max_chunk_len)?;
+            }
+        }
 
-                        // Most properties will be stored on instances using the
-                        // property's canonical name, so we'll try that first.
-                        if let Some(property) = instance.properties.get(prop_name.as_ref()) {
-                            return Cow::Borrowed(property);
-                        }
+        Ok(())
+    }
 
-                        // If there were any known aliases for this property
-                        // used as part of this file, we can check those next.
-                        for alias in &prop_info.aliases {
-                            if let Some(property) = instance.properties.get(alias) {
-                                return Cow::Borrowed(property);
-                            }
-                        }
+    /// Builds a PROP chunk holding the values of one property for the given
+    /// instances, which must all be of the class described by `type_name`.
+    fn prop_chunk(
+        &self,
+        type_name: &str,
+        type_id: u32,
+        prop_name: &str,
+        prop_info: &PropInfo,
+        refs: &[Ref],
+    ) -> Result<ChunkBuilder, InnerError> {
+        let mut chunk = ChunkBuilder::new(b"PROP", self.compression);
+
+        chunk.write_le_u32(type_id)?;
+        chunk.write_string(&prop_info.serialized_name)?;
+        chunk.write_u8(prop_info.prop_type as u8)?;
+
+        let dom = &self.dom;
+        let values = refs
+            .iter()
+            .map(|id| {
+                // This unwrap will not panic because we uphold the
+                // invariant that any ID in object_refs must be part of
+                // this dom.
+                let instance = dom.get_by_ref(*id).unwrap();
+
+                // We store the Name property in a different field for
+                // convenience, but when serializing to the binary model
+                // format we need to handle it just like other properties.
+                if prop_name == "Name" {
+                    return Cow::Owned(Variant::from(instance.name.as_str()));
+                }
 
-                        // Finally, we can fall back to the default value we
-                        // computed for this PropInfo. This is sourced from the
-                        // reflection database if available, or falls back to a
-                        // reasonable default.
-                        Cow::Borrowed(prop_info.default_value.borrow())
-                    })
-                    .enumerate();
-
-                // Helper to generate a type mismatch error with context from
-                // this chunk.
-                let type_mismatch =
-                    |i: usize, bad_value: &Variant, valid_type_names: &'static str| {
-                        Err(InnerError::PropTypeMismatch {
-                            type_name: type_name.clone(),
-                            prop_name: prop_name.to_string(),
-                            valid_type_names,
-                            actual_type_name: format!("{:?}", bad_value.ty()),
-                            instance_full_name: self.full_name_for(type_info.object_refs[i]),
-                        })
-                    };
+                // Most properties will be stored on instances using the
+                // property's canonical name, so we'll try that first.
+                if let Some(property) = instance.properties.get(prop_name) {
+                    return Cow::Borrowed(property);
+                }
 
-                match prop_info.prop_type {
-                    Type::String => {
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::String(value) => {
-                                    chunk.write_string(&value)?;
-                                }
-                                Variant::Content(value) => {
-                                    chunk.write_string(value.as_ref())?;
-                                }
-                                Variant::BinaryString(value) => {
-                                    chunk.write_binary_string(value.as_ref())?;
-                                }
-                                _ => {
-                                    return type_mismatch(
-                                        i,
-                                        &rbx_value,
-                                        "String, Content, or BinaryString",
-                                    );
-                                }
-                            }
-                        }
+                // If there were any known aliases for this property
+                // used as part of this file, we can check those next.
+                for alias in &prop_info.aliases {
+                    if let Some(property) = instance.properties.get(alias) {
+                        return Cow::Borrowed(property);
                     }
-                    Type::Bool => {
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::Bool(value) => {
-                                    chunk.write_bool(*value)?;
-                                }
-                                _ => {
-                                    return type_mismatch(i, &rbx_value, "Bool");
-                                }
-                            }
+                }
+
+                // Finally, we can fall back to the default value we
+                // computed for this PropInfo. This is sourced from the
+                // reflection database if available, or falls back to a
+                // reasonable default.
+                Cow::Borrowed(prop_info.default_value.borrow())
+            })
+            .enumerate();
+
+        // Helper to generate a type mismatch error with context from
+        // this chunk.
+        let type_mismatch = |i: usize, bad_value: &Variant, valid_type_names: &'static str| {
+            Err(InnerError::PropTypeMismatch {
+                type_name: type_name.to_owned(),
+                prop_name: prop_name.to_string(),
+                valid_type_names,
+                actual_type_name: format!("{:?}", bad_value.ty()),
+                instance_full_name: self.dom.full_name(refs[i]).unwrap(),
+            })
+        };
+
+        match prop_info.prop_type {
+            Type::String => {
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::String(value) => {
+                            chunk.write_string(&value)?;
+                        }
+                        Variant::Content(value) => {
+                            chunk.write_string(value.as_ref())?;
+                        }
+                        Variant::BinaryString(value) => match value.blob() {
+                            Some(blob) => chunk.write_blob_string(blob)?,
+                            None => chunk.write_binary_string(value.as_ref())?,
+                        },
+                        _ => {
+                            return type_mismatch(
+                                i,
+                                &rbx_value,
+                                "String, Content, or BinaryString",
+                            );
                         }
                     }
-                    Type::Int32 => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Int32(value) = rbx_value.as_ref() {
-                                buf.push(*value);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Int32");
-                            }
+                }
+            }
+            Type::Bool => {
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::Bool(value) => {
+                            chunk.write_bool(*value)?;
+                        }
+                        _ => {
+                            return type_mismatch(i, &rbx_value, "Bool");
                         }
+                    }
+                }
+            }
+            Type::Int32 => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Int32(value) = rbx_value.as_ref() {
+                        buf.push(*value);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Int32");
+                    }
+                }
 
-                        chunk.write_interleaved_i32_array(buf.into_iter())?;
+                chunk.write_interleaved_i32_array(buf.into_iter())?;
+            }
+            Type::Float32 => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Float32(value) = rbx_value.as_ref() {
+                        buf.push(*value);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Float32");
                     }
-                    Type::Float32 => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Float32(value) = rbx_value.as_ref() {
-                                buf.push(*value);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Float32");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(buf.into_iter())?;
+                chunk.write_interleaved_f32_array(buf.into_iter())?;
+            }
+            Type::Float64 => {
+                for (i, rbx_value) in values {
+                    if let Variant::Float64(value) = rbx_value.as_ref() {
+                        chunk.write_le_f64(*value)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Float64");
                     }
-                    Type::Float64 => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Float64(value) = rbx_value.as_ref() {
-                                chunk.write_le_f64(*value)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Float64");
-                            }
-                        }
+                }
+            }
+            Type::UDim => {
+                let mut scale = Vec::with_capacity(values.len());
+                let mut offset = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::UDim(value) = rbx_value.as_ref() {
+                        scale.push(value.scale);
+                        offset.push(value.offset);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "UDim");
                     }
-                    Type::UDim => {
-                        let mut scale = Vec::with_capacity(values.len());
-                        let mut offset = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::UDim(value) = rbx_value.as_ref() {
-                                scale.push(value.scale);
-                                offset.push(value.offset);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "UDim");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(scale.into_iter())?;
-                        chunk.write_interleaved_i32_array(offset.into_iter())?;
+                chunk.write_interleaved_f32_array(scale.into_iter())?;
+                chunk.write_interleaved_i32_array(offset.into_iter())?;
+            }
+            Type::UDim2 => {
+                let mut scale_x = Vec::with_capacity(values.len());
+                let mut scale_y = Vec::with_capacity(values.len());
+                let mut offset_x = Vec::with_capacity(values.len());
+                let mut offset_y = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::UDim2(value) = rbx_value.as_ref() {
+                        scale_x.push(value.x.scale);
+                        scale_y.push(value.y.scale);
+                        offset_x.push(value.x.offset);
+                        offset_y.push(value.y.offset);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "UDim2");
                     }
-                    Type::UDim2 => {
-                        let mut scale_x = Vec::with_capacity(values.len());
-                        let mut scale_y = Vec::with_capacity(values.len());
-                        let mut offset_x = Vec::with_capacity(values.len());
-                        let mut offset_y = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::UDim2(value) = rbx_value.as_ref() {
-                                scale_x.push(value.x.scale);
-                                scale_y.push(value.y.scale);
-                                offset_x.push(value.x.offset);
-                                offset_y.push(value.y.offset);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "UDim2");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(scale_x.into_iter())?;
-                        chunk.write_interleaved_f32_array(scale_y.into_iter())?;
-                        chunk.write_interleaved_i32_array(offset_x.into_iter())?;
-                        chunk.write_interleaved_i32_array(offset_y.into_iter())?;
+                chunk.write_interleaved_f32_array(scale_x.into_iter())?;
+                chunk.write_interleaved_f32_array(scale_y.into_iter())?;
+                chunk.write_interleaved_i32_array(offset_x.into_iter())?;
+                chunk.write_interleaved_i32_array(offset_y.into_iter())?;
+            }
+            Type::Ray => {
+                for (i, rbx_value) in values {
+                    if let Variant::Ray(value) = rbx_value.as_ref() {
+                        chunk.write_le_f32(value.origin.x)?;
+                        chunk.write_le_f32(value.origin.y)?;
+                        chunk.write_le_f32(value.origin.z)?;
+                        chunk.write_le_f32(value.direction.x)?;
+                        chunk.write_le_f32(value.direction.y)?;
+                        chunk.write_le_f32(value.direction.x)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Ray");
                     }
-                    Type::Ray => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Ray(value) = rbx_value.as_ref() {
-                                chunk.write_le_f32(value.origin.x)?;
-                                chunk.write_le_f32(value.origin.y)?;
-                                chunk.write_le_f32(value.origin.z)?;
-                                chunk.write_le_f32(value.direction.x)?;
-                                chunk.write_le_f32(value.direction.y)?;
-                                chunk.write_le_f32(value.direction.x)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Ray");
-                            }
-                        }
+                }
+            }
+            Type::Faces => {
+                for (i, rbx_value) in values {
+                    if let Variant::Faces(value) = rbx_value.as_ref() {
+                        chunk.write_u8(value.bits())?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Faces");
                     }
-                    Type::Faces => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Faces(value) = rbx_value.as_ref() {
-                                chunk.write_u8(value.bits())?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Faces");
-                            }
-                        }
+                }
+            }
+            Type::Axes => {
+                for (i, rbx_value) in values {
+                    if let Variant::Axes(value) = rbx_value.as_ref() {
+                        chunk.write_u8(value.bits())?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Axes");
                     }
-                    Type::Axes => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Axes(value) = rbx_value.as_ref() {
-                                chunk.write_u8(value.bits())?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Axes");
-                            }
-                        }
+                }
+            }
+            Type::BrickColor => {
+                let mut numbers = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::BrickColor(value) = rbx_value.as_ref() {
+                        numbers.push(*value as u32)
+                    } else {
+                        return type_mismatch(i, &rbx_value, "BrickColor");
                     }
-                    Type::BrickColor => {
-                        let mut numbers = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::BrickColor(value) = rbx_value.as_ref() {
-                                numbers.push(*value as u32)
-                            } else {
-                                return type_mismatch(i, &rbx_value, "BrickColor");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_u32_array(&numbers)?;
+                chunk.write_interleaved_u32_array(&numbers)?;
+            }
+            Type::Color3 => {
+                let mut r = Vec::with_capacity(values.len());
+                let mut g = Vec::with_capacity(values.len());
+                let mut b = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Color3(value) = rbx_value.as_ref() {
+                        r.push(value.r);
+                        g.push(value.g);
+                        b.push(value.b);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Color3");
                     }
-                    Type::Color3 => {
-                        let mut r = Vec::with_capacity(values.len());
-                        let mut g = Vec::with_capacity(values.len());
-                        let mut b = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Color3(value) = rbx_value.as_ref() {
-                                r.push(value.r);
-                                g.push(value.g);
-                                b.push(value.b);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Color3");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(r.into_iter())?;
-                        chunk.write_interleaved_f32_array(g.into_iter())?;
-                        chunk.write_interleaved_f32_array(b.into_iter())?;
+                chunk.write_interleaved_f32_array(r.into_iter())?;
+                chunk.write_interleaved_f32_array(g.into_iter())?;
+                chunk.write_interleaved_f32_array(b.into_iter())?;
+            }
+            Type::Vector2 => {
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Vector2(value) = rbx_value.as_ref() {
+                        x.push(value.x);
+                        y.push(value.y)
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Vector2");
                     }
-                    Type::Vector2 => {
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Vector2(value) = rbx_value.as_ref() {
-                                x.push(value.x);
-                                y.push(value.y)
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Vector2");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+            }
+            Type::Vector3 => {
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+                let mut z = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Vector3(value) = rbx_value.as_ref() {
+                        x.push(value.x);
+                        y.push(value.y);
+                        z.push(value.z)
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Vector3");
                     }
-                    Type::Vector3 => {
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-                        let mut z = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Vector3(value) = rbx_value.as_ref() {
-                                x.push(value.x);
-                                y.push(value.y);
-                                z.push(value.z)
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Vector3");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
-                        chunk.write_interleaved_f32_array(z.into_iter())?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(z.into_iter())?;
+            }
+            Type::CFrame => {
+                let mut rotations = Vec::with_capacity(values.len());
+                let mut x = Vec::with_capacity(values.len());
+                let mut y = Vec::with_capacity(values.len());
+                let mut z = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::CFrame(value) = rbx_value.as_ref() {
+                        rotations.push(value.orientation);
+                        x.push(value.position.x);
+                        y.push(value.position.y);
+                        z.push(value.position.z);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "CFrame");
                     }
-                    Type::CFrame => {
-                        let mut rotations = Vec::with_capacity(values.len());
-                        let mut x = Vec::with_capacity(values.len());
-                        let mut y = Vec::with_capacity(values.len());
-                        let mut z = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::CFrame(value) = rbx_value.as_ref() {
-                                rotations.push(value.orientation);
-                                x.push(value.position.x);
-                                y.push(value.position.y);
-                                z.push(value.position.z);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "CFrame");
-                            }
-                        }
-
-                        for matrix in rotations {
-                            // TODO write special cases; see: https://github.com/rojo-rbx/rbx-dom/issues/129
-                            // Right now all CFrames are written as `0x00`,
-                            // which means that their rotation matrix is written fully.
-                            chunk.write_u8(0x00)?;
-
-                            chunk.write_le_f32(matrix.x.x)?;
-                            chunk.write_le_f32(matrix.x.y)?;
-                            chunk.write_le_f32(matrix.x.z)?;
-
-                            chunk.write_le_f32(matrix.y.x)?;
-                            chunk.write_le_f32(matrix.y.y)?;
-                            chunk.write_le_f32(matrix.y.z)?;
+                }
 
-                            chunk.write_le_f32(matrix.z.x)?;
-                            chunk.write_le_f32(matrix.z.y)?;
-                            chunk.write_le_f32(matrix.z.z)?;
-                        }
+                for matrix in rotations {
+                    // TODO write special cases; see: https://github.com/rojo-rbx/rbx-dom/issues/129
+                    // Right now all CFrames are written as `0x00`,
+                    // which means that their rotation matrix is written fully.
+                    chunk.write_u8(0x00)?;
 
-                        chunk.write_interleaved_f32_array(x.into_iter())?;
-                        chunk.write_interleaved_f32_array(y.into_iter())?;
-                        chunk.write_interleaved_f32_array(z.into_iter())?;
+                    for component in &matrix.to_components() {
+                        chunk.write_le_f32(*component)?;
                     }
-                    Type::Enum => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Enum(value) = rbx_value.as_ref() {
-                                buf.push(value.to_u32());
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Enum");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_u32_array(&buf)?;
+                chunk.write_interleaved_f32_array(x.into_iter())?;
+                chunk.write_interleaved_f32_array(y.into_iter())?;
+                chunk.write_interleaved_f32_array(z.into_iter())?;
+            }
+            Type::Enum => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Enum(value) = rbx_value.as_ref() {
+                        buf.push(value.to_u32());
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Enum");
                     }
-                    Type::Ref => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Ref(value) = rbx_value.as_ref() {
-                                if value.is_none() {
-                                    buf.push(-1);
-                                } else if let Some(id) = self.id_to_referent.get(value) {
-                                    buf.push(*id);
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Ref");
-                            }
+                }
+
+                chunk.write_interleaved_u32_array(&buf)?;
+            }
+            Type::Ref => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Ref(value) = rbx_value.as_ref() {
+                        if value.is_none() {
+                            buf.push(BinaryReferent::NONE);
+                        } else if let Some(id) = self.id_to_referent.get(value) {
+                            buf.push(*id);
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Ref");
+                    }
+                }
 
-                        chunk.write_referent_array(buf.into_iter())?;
+                chunk.write_referent_array(buf.into_iter())?;
+            }
+            Type::Vector3int16 => {
+                for (i, rbx_value) in values {
+                    if let Variant::Vector3int16(value) = rbx_value.as_ref() {
+                        chunk.write_le_i16(value.x)?;
+                        chunk.write_le_i16(value.y)?;
+                        chunk.write_le_i16(value.z)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Vector3int16");
                     }
-                    Type::Vector3int16 => {
-                        for (i, rbx_value) in values {
-                            if let Variant::Vector3int16(value) = rbx_value.as_ref() {
-                                chunk.write_le_i16(value.x)?;
-                                chunk.write_le_i16(value.y)?;
-                                chunk.write_le_i16(value.z)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Vector3int16");
-                            }
+                }
+            }
+            Type::NumberSequence => {
+                for (i, rbx_value) in values {
+                    if let Variant::NumberSequence(value) = rbx_value.as_ref() {
+                        chunk.write_le_u32(value.keypoints.len() as u32)?;
+
+                        for keypoint in &value.keypoints {
+                            chunk.write_le_f32(keypoint.time)?;
+                            chunk.write_le_f32(keypoint.value)?;
+                            chunk.write_le_f32(keypoint.envelope)?;
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "NumberSequence");
                     }
-                    Type::NumberSequence => {
-                        for (i, rbx_value) in values {
-                            if let Variant::NumberSequence(value) = rbx_value.as_ref() {
-                                chunk.write_le_u32(value.keypoints.len() as u32)?;
-
-                                for keypoint in &value.keypoints {
-                                    chunk.write_le_f32(keypoint.time)?;
-                                    chunk.write_le_f32(keypoint.value)?;
-                                    chunk.write_le_f32(keypoint.envelope)?;
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "NumberSequence");
-                            }
+                }
+            }
+            Type::ColorSequence => {
+                for (i, rbx_value) in values {
+                    if let Variant::ColorSequence(value) = rbx_value.as_ref() {
+                        chunk.write_le_u32(value.keypoints.len() as u32)?;
+
+                        for keypoint in &value.keypoints {
+                            chunk.write_le_f32(keypoint.time)?;
+                            chunk.write_le_f32(keypoint.color.r)?;
+                            chunk.write_le_f32(keypoint.color.g)?;
+                            chunk.write_le_f32(keypoint.color.b)?;
+
+                            // write out a dummy value for envelope, which is serialized but doesn't do anything
+                            chunk.write_le_f32(0.0)?;
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "ColorSequence");
                     }
-                    Type::ColorSequence => {
-                        for (i, rbx_value) in values {
-                            if let Variant::ColorSequence(value) = rbx_value.as_ref() {
-                                chunk.write_le_u32(value.keypoints.len() as u32)?;
-
-                                for keypoint in &value.keypoints {
-                                    chunk.write_le_f32(keypoint.time)?;
-                                    chunk.write_le_f32(keypoint.color.r)?;
-                                    chunk.write_le_f32(keypoint.color.g)?;
-                                    chunk.write_le_f32(keypoint.color.b)?;
-
-                                    // write out a dummy value for envelope, which is serialized but doesn't do anything
-                                    chunk.write_le_f32(0.0)?;
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "ColorSequence");
-                            }
-                        }
+                }
+            }
+            Type::NumberRange => {
+                for (i, rbx_value) in values {
+                    if let Variant::NumberRange(value) = rbx_value.as_ref() {
+                        chunk.write_le_f32(value.min)?;
+                        chunk.write_le_f32(value.max)?;
+                    } else {
+                        return type_mismatch(i, &rbx_value, "NumberRange");
                     }
-                    Type::NumberRange => {
-                        for (i, rbx_value) in values {
-                            if let Variant::NumberRange(value) = rbx_value.as_ref() {
-                                chunk.write_le_f32(value.min)?;
-                                chunk.write_le_f32(value.max)?;
-                            } else {
-                                return type_mismatch(i, &rbx_value, "NumberRange");
-                            }
-                        }
+                }
+            }
+            Type::Rect => {
+                let mut x_min = Vec::with_capacity(values.len());
+                let mut y_min = Vec::with_capacity(values.len());
+                let mut x_max = Vec::with_capacity(values.len());
+                let mut y_max = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Rect(value) = rbx_value.as_ref() {
+                        x_min.push(value.min.x);
+                        y_min.push(value.min.y);
+                        x_max.push(value.max.x);
+                        y_max.push(value.max.y);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Rect");
                     }
-                    Type::Rect => {
-                        let mut x_min = Vec::with_capacity(values.len());
-                        let mut y_min = Vec::with_capacity(values.len());
-                        let mut x_max = Vec::with_capacity(values.len());
-                        let mut y_max = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Rect(value) = rbx_value.as_ref() {
-                                x_min.push(value.min.x);
-                                y_min.push(value.min.y);
-                                x_max.push(value.max.x);
-                                y_max.push(value.max.y);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Rect");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_f32_array(x_min.into_iter())?;
-                        chunk.write_interleaved_f32_array(y_min.into_iter())?;
-                        chunk.write_interleaved_f32_array(x_max.into_iter())?;
-                        chunk.write_interleaved_f32_array(y_max.into_iter())?;
-                    }
-                    Type::PhysicalProperties => {
-                        for (i, rbx_value) in values {
-                            if let Variant::PhysicalProperties(value) = rbx_value.as_ref() {
-                                if let PhysicalProperties::Custom(props) = value {
-                                    chunk.write_u8(1)?;
-                                    chunk.write_le_f32(props.density)?;
-                                    chunk.write_le_f32(props.friction)?;
-                                    chunk.write_le_f32(props.elasticity)?;
-                                    chunk.write_le_f32(props.friction_weight)?;
-                                    chunk.write_le_f32(props.elasticity_weight)?;
-                                } else {
-                                    chunk.write_u8(0)?;
-                                }
-                            } else {
-                                return type_mismatch(i, &rbx_value, "PhysicalProperties");
-                            }
+                chunk.write_interleaved_f32_array(x_min.into_iter())?;
+                chunk.write_interleaved_f32_array(y_min.into_iter())?;
+                chunk.write_interleaved_f32_array(x_max.into_iter())?;
+                chunk.write_interleaved_f32_array(y_max.into_iter())?;
+            }
+            Type::PhysicalProperties => {
+                for (i, rbx_value) in values {
+                    if let Variant::PhysicalProperties(value) = rbx_value.as_ref() {
+                        if let PhysicalProperties::Custom(props) = value {
+                            chunk.write_u8(1)?;
+                            chunk.write_le_f32(props.density)?;
+                            chunk.write_le_f32(props.friction)?;
+                            chunk.write_le_f32(props.elasticity)?;
+                            chunk.write_le_f32(props.friction_weight)?;
+                            chunk.write_le_f32(props.elasticity_weight)?;
+                        } else {
+                            chunk.write_u8(0)?;
                         }
+                    } else {
+                        return type_mismatch(i, &rbx_value, "PhysicalProperties");
                     }
-                    Type::Color3uint8 => {
-                        let mut r = Vec::with_capacity(values.len());
-                        let mut g = Vec::with_capacity(values.len());
-                        let mut b = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            match rbx_value.as_ref() {
-                                Variant::Color3uint8(value) => {
-                                    r.push(value.r);
-                                    g.push(value.g);
-                                    b.push(value.b);
-                                }
-                                Variant::Color3(value) => {
-                                    r.push((value.r.max(0.0).min(1.0) * 255.0).round() as u8);
-                                    g.push((value.g.max(0.0).min(1.0) * 255.0).round() as u8);
-                                    b.push((value.b.max(0.0).min(1.0) * 255.0).round() as u8);
-                                }
-                                _ => return type_mismatch(i, &rbx_value, "Color3uint8 or Color3"),
-                            }
+                }
+            }
+            Type::Color3uint8 => {
+                let mut r = Vec::with_capacity(values.len());
+                let mut g = Vec::with_capacity(values.len());
+                let mut b = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    match rbx_value.as_ref() {
+                        Variant::Color3uint8(value) => {
+                            r.push(value.r);
+                            g.push(value.g);
+                            b.push(value.b);
                         }
-
-                        chunk.write_all(r.as_slice())?;
-                        chunk.write_all(g.as_slice())?;
-                        chunk.write_all(b.as_slice())?;
-                    }
-                    Type::Int64 => {
-                        let mut buf = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::Int64(value) = rbx_value.as_ref() {
-                                buf.push(*value);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "Int64");
-                            }
+                        Variant::Color3(value) => {
+                            r.push((value.r.max(0.0).min(1.0) * 255.0).round() as u8);
+                            g.push((value.g.max(0.0).min(1.0) * 255.0).round() as u8);
+                            b.push((value.b.max(0.0).min(1.0) * 255.0).round() as u8);
                         }
+                        _ => return type_mismatch(i, &rbx_value, "Color3uint8 or Color3"),
+                    }
+                }
 
-                        chunk.write_interleaved_i64_array(buf.into_iter())?;
+                chunk.write_all(r.as_slice())?;
+                chunk.write_all(g.as_slice())?;
+                chunk.write_all(b.as_slice())?;
+            }
+            Type::Int64 => {
+                let mut buf = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::Int64(value) = rbx_value.as_ref() {
+                        buf.push(*value);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "Int64");
                     }
-                    Type::SharedString => {
-                        let mut entries = Vec::with_capacity(values.len());
-
-                        for (i, rbx_value) in values {
-                            if let Variant::SharedString(value) = rbx_value.as_ref() {
-                                let id = &self.shared_string_ids[value];
-                                entries.push(*id);
-                            } else {
-                                return type_mismatch(i, &rbx_value, "SharedString");
-                            }
-                        }
+                }
 
-                        chunk.write_interleaved_u32_array(&entries)?;
+                chunk.write_interleaved_i64_array(buf.into_iter())?;
+            }
+            Type::SharedString => {
+                let mut entries = Vec::with_capacity(values.len());
+
+                for (i, rbx_value) in values {
+                    if let Variant::SharedString(value) = rbx_value.as_ref() {
+                        let id = &self.shared_string_ids[value];
+                        entries.push(*id);
+                    } else {
+                        return type_mismatch(i, &rbx_value, "SharedString");
                     }
                 }
 
-                chunk.dump(&mut self.output)?;
+                chunk.write_interleaved_u32_array(&entries)?;
             }
         }
 
-        Ok(())
+        Ok(chunk)
     }
 
     /// Write out the hierarchical relations between instances, stored in a
     /// chunk named PRNT.
     fn serialize_parents(&mut self) -> Result<(), InnerError> {
+        phase_span!("serialize_parents");
         log::trace!("Writing parent relationships");
 
-        let mut chunk = ChunkBuilder::new(b"PRNT", ChunkCompression::Compressed);
+        let mut chunk = ChunkBuilder::new(b"PRNT", self.compression);
 
         chunk.write_u8(0)?; // PRNT version 0
         chunk.write_le_u32(self.relevant_instances.len() as u32)?;
@@ -1080,16 +1546,16 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                 self.id_to_referent
                     .get(&instance.parent())
                     .cloned()
-                    .unwrap_or(-1)
+                    .unwrap_or(BinaryReferent::NONE)
             } else {
-                -1
+                BinaryReferent::NONE
             }
         });
 
         chunk.write_referent_array(object_referents)?;
         chunk.write_referent_array(parent_referents)?;
 
-        chunk.dump(&mut self.output)?;
+        write_chunk(&mut self.output, chunk, self.max_chunk_len)?;
 
         Ok(())
     }
@@ -1098,6 +1564,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
     /// hasn't been truncated mistakenly. This chunk is named END\0, with a zero
     /// byte at the end.
     fn serialize_end(&mut self) -> Result<(), InnerError> {
+        phase_span!("serialize_end");
         log::trace!("Writing file end");
 
         let mut end = ChunkBuilder::new(b"END\0", ChunkCompression::Uncompressed);
@@ -1107,30 +1574,9 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
         Ok(())
     }
 
-    /// Equivalent to Instance:GetFullName() from Roblox.
-    fn full_name_for(&self, subject_ref: Ref) -> String {
-        let mut components = Vec::new();
-        let mut current_id = subject_ref;
-
-        while current_id.is_some() {
-            let instance = self.dom.get_by_ref(current_id).unwrap();
-            components.push(instance.name.as_str());
-            current_id = instance.parent();
-        }
-
-        let mut name = String::new();
-        for component in components.iter().rev() {
-            name.push_str(component);
-            name.push('.');
-        }
-        name.pop();
-
-        name
-    }
-
     fn fallback_default_value(rbx_type: VariantType) -> Option<Variant> {
         Some(match rbx_type {
-            VariantType::String => Variant::String(String::new()),
+            VariantType::String => Variant::String("".into()),
             VariantType::BinaryString => Variant::BinaryString(BinaryString::new()),
             VariantType::Bool => Variant::Bool(false),
             VariantType::Int32 => Variant::Int32(0),
@@ -1183,3 +1629,54 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
         })
     }
 }
+
+/// Writes a finished chunk to the output, failing instead if it's larger than
+/// `max_chunk_len`.
+fn write_chunk<W: Write>(
+    output: &mut W,
+    chunk: ChunkBuilder,
+    max_chunk_len: Option<usize>,
+) -> Result<(), InnerError> {
+    if let Some(max_len) = max_chunk_len {
+        if chunk.len() > max_len {
+            return Err(InnerError::ChunkTooLarge {
+                chunk: format!("{} chunk", chunk.name()),
+                len: chunk.len(),
+                max_len,
+            });
+        }
+    }
+
+    chunk.dump(output)?;
+
+    Ok(())
+}
+
+impl<'a, W: Write> BinarySerializer<'a, CountingWriter<W>> {
+    /// Describes everything that this serializer has written so far.
+    fn summary(&self) -> EncodeSummary {
+        // Most types have a Name property, which we don't count as a property
+        // value since rbx_dom_weak doesn't store it as one.
+        let property_count = self
+            .type_infos
+            .values
+            .values()
+            .map(|type_info| {
+                let value_count = type_info
+                    .properties
+                    .keys()
+                    .filter(|name| *name != "Name")
+                    .count();
+
+                value_count * type_info.object_refs.len()
+            })
+            .sum();
+
+        EncodeSummary {
+            bytes_written: self.output.bytes_written(),
+            instance_count: self.relevant_instances.len(),
+            property_count,
+            diagnostics: self.diagnostics.clone(),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::{collections::HashMap, fmt};
+
+use rbx_dom_weak::types::{Content, Ref};
+
+/// Information about a model or place that was decoded by rbx_binary.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct DecodeSummary {
+    pub(crate) bytes_read: u64,
+    pub(crate) instance_count: usize,
+    pub(crate) property_count: usize,
+    pub(crate) recovered: bool,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) normalized_content: Vec<(Content, Content)>,
+    pub(crate) source_locations: HashMap<Ref, SourceLocation>,
+}
+
+impl DecodeSummary {
+    /// The total number of bytes that were read from the reader.
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// The number of instances that were decoded, not including the
+    /// `DataModel` instance that rbx_binary creates as the root of the dom.
+    #[inline]
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// The number of property values that were decoded, not including each
+    /// instance's name.
+    #[inline]
+    pub fn property_count(&self) -> usize {
+        self.property_count
+    }
+
+    /// Tells whether any part of the file was skipped because it could not be
+    /// read. This can only happen when decoding with
+    /// [`DecodeOptions::recover`][crate::DecodeOptions::recover] enabled.
+    #[inline]
+    pub fn recovered(&self) -> bool {
+        self.recovered
+    }
+
+    /// All of the problems that rbx_binary worked around while decoding, in
+    /// the order that they were found.
+    #[inline]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Every distinct `Content` value that was rewritten because of
+    /// [`DecodeOptions::normalize_content`][crate::DecodeOptions::normalize_content],
+    /// paired with the value it was rewritten to, in the order they were first
+    /// found.
+    #[inline]
+    pub fn normalized_content(&self) -> &[(Content, Content)] {
+        &self.normalized_content
+    }
+
+    /// Where each decoded instance was created in the file, keyed by the
+    /// instance's Ref. This is only filled in when decoding with
+    /// [`DecodeOptions::source_locations`][crate::DecodeOptions::source_locations]
+    /// enabled.
+    #[inline]
+    pub fn source_locations(&self) -> &HashMap<Ref, SourceLocation> {
+        &self.source_locations
+    }
+}
+
+/// Where an instance was created in a binary file.
+///
+/// Binary files describe instances a whole class at a time, so the closest we
+/// can get to an instance is the INST chunk that created it and its position
+/// among the instances that chunk created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SourceLocation {
+    pub(crate) chunk_offset: u64,
+    pub(crate) index: usize,
+}
+
+impl SourceLocation {
+    /// The offset from the start of the file of the INST chunk that created
+    /// the instance.
+    #[inline]
+    pub fn chunk_offset(&self) -> u64 {
+        self.chunk_offset
+    }
+
+    /// The 0-based position of the instance in the INST chunk's list of
+    /// referents.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// Describes a problem that rbx_binary found and worked around while
+/// decoding, or a warning about a file that it encoded.
+///
+/// With the `serde` feature enabled, diagnostics can be serialized so that
+/// other tools can read them. Each one is written as an object with its
+/// `code`, `severity`, `message`, `byteOffset`, and, when they're known, its
+/// `chunkName` and `property`.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub(crate) code: DiagnosticCode,
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    pub(crate) byte_offset: u64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) chunk_name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub(crate) property: Option<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(code: DiagnosticCode, message: String, byte_offset: u64) -> Self {
+        Diagnostic {
+            code,
+            severity: code.severity(),
+            message,
+            byte_offset,
+            chunk_name: None,
+            property: None,
+        }
+    }
+
+    /// The code identifying what kind of problem this is.
+    #[inline]
+    pub fn code(&self) -> DiagnosticCode {
+        self.code
+    }
+
+    /// How serious the problem is.
+    #[inline]
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// A human-readable description of the problem.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The offset from the start of the file of the chunk that the problem was
+    /// found in. Warnings about a whole file have an offset of zero.
+    #[inline]
+    pub fn byte_offset(&self) -> u64 {
+        self.byte_offset
+    }
+
+    /// The name of the chunk that the problem was found in, like `PROP`, if
+    /// the chunk's header could be read.
+    #[inline]
+    pub fn chunk_name(&self) -> Option<&str> {
+        self.chunk_name.as_deref()
+    }
+
+    /// The property that the problem was found in, written like
+    /// `Part.Anchored`, if the problem was with a single property.
+    #[inline]
+    pub fn property(&self) -> Option<&str> {
+        self.property.as_deref()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match &self.chunk_name {
+            Some(name) => write!(
+                output,
+                "{} chunk at byte {}: {}",
+                name, self.byte_offset, self.message
+            ),
+            None => write!(output, "byte {}: {}", self.byte_offset, self.message),
+        }
+    }
+}
+
+/// A stable identifier for a kind of [`Diagnostic`], like `RBXD0001`.
+///
+/// Codes are meant for tools that need to react to particular problems, like
+/// a build that fails whenever a file loses data. Once a code has been
+/// released, its string never changes and it's never given to a different
+/// kind of problem, even if the diagnostic it belonged to is removed. New
+/// codes may be added in minor releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticCode {
+    /// A chunk couldn't be decoded, so everything in it was skipped.
+    DamagedChunk,
+
+    /// A chunk's header couldn't be read, so rbx_binary searched ahead for the
+    /// next chunk.
+    UnreadableChunkHeader,
+
+    /// The file ended without an `END` chunk, which usually means that it was
+    /// cut off.
+    MissingEndChunk,
+
+    /// An instance was dropped because a `PRNT` chunk linked it to an instance
+    /// that was never declared.
+    UndeclaredInstance,
+
+    /// Instances that were never given a parent were placed at the top level.
+    OrphanedInstances,
+
+    /// A `PROP` chunk ended before giving every instance a value.
+    TruncatedProperty,
+
+    /// An encoded file has more class groups than
+    /// [`EncodeOptions::class_count_warning`][crate::EncodeOptions::class_count_warning]
+    /// allows.
+    ClassCountExceeded,
+
+    /// `String` values that weren't valid UTF-8 had their invalid bytes
+    /// replaced, because of
+    /// [`InvalidStringBehavior::ReplaceLossy`][crate::InvalidStringBehavior::ReplaceLossy].
+    ReplacedInvalidString,
+
+    /// `String` values that weren't valid UTF-8 were kept as `BinaryString`
+    /// values, because of
+    /// [`InvalidStringBehavior::PreserveAsBinaryString`][crate::InvalidStringBehavior::PreserveAsBinaryString].
+    PreservedInvalidString,
+
+    /// Enum values from an older version of Roblox were replaced with the
+    /// values that took their place, because of
+    /// [`DecodeOptions::migrate_enum_values`][crate::DecodeOptions::migrate_enum_values].
+    MigratedEnumValues,
+}
+
+impl DiagnosticCode {
+    /// The code as a string, like `RBXD0001`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticCode::DamagedChunk => "RBXD0001",
+            DiagnosticCode::UnreadableChunkHeader => "RBXD0002",
+            DiagnosticCode::MissingEndChunk => "RBXD0003",
+            DiagnosticCode::UndeclaredInstance => "RBXD0004",
+            DiagnosticCode::OrphanedInstances => "RBXD0005",
+            DiagnosticCode::TruncatedProperty => "RBXD0006",
+            DiagnosticCode::ClassCountExceeded => "RBXD0007",
+            DiagnosticCode::ReplacedInvalidString => "RBXD0008",
+            DiagnosticCode::PreservedInvalidString => "RBXD0009",
+            DiagnosticCode::MigratedEnumValues => "RBXD0010",
+        }
+    }
+
+    /// How serious problems with this code are.
+    pub fn severity(self) -> Severity {
+        match self {
+            DiagnosticCode::DamagedChunk
+            | DiagnosticCode::UnreadableChunkHeader
+            | DiagnosticCode::UndeclaredInstance
+            | DiagnosticCode::TruncatedProperty
+            | DiagnosticCode::ReplacedInvalidString => Severity::Error,
+            DiagnosticCode::MissingEndChunk
+            | DiagnosticCode::OrphanedInstances
+            | DiagnosticCode::ClassCountExceeded
+            | DiagnosticCode::PreservedInvalidString
+            | DiagnosticCode::MigratedEnumValues => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        output.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DiagnosticCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum Severity {
+    /// Something that might be a problem, but didn't lose any data.
+    Warning,
+
+    /// Part of the file was lost.
+    Error,
+}
+
+/// Information about a model or place that was encoded by rbx_binary.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct EncodeSummary {
+    pub(crate) bytes_written: u64,
+    pub(crate) instance_count: usize,
+    pub(crate) property_count: usize,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+impl EncodeSummary {
+    /// The total number of bytes that were written to the writer.
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// The number of instances that were encoded.
+    #[inline]
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// The number of property values that were encoded, not including each
+    /// instance's name.
+    ///
+    /// Every instance of a class is written with a value for every property
+    /// used by any instance of that class, so this number can be larger than
+    /// the number of properties present in the dom.
+    #[inline]
+    pub fn property_count(&self) -> usize {
+        self.property_count
+    }
+
+    /// All of the warnings that rbx_binary gave while encoding, in the order
+    /// that they were found.
+    #[inline]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+}
@@ -0,0 +1,170 @@
+use rbx_dom_weak::{
+    types::{BinaryString, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{from_reader_default, tests::util::split_chunks, EncodeError, EncodeOptions};
+
+/// Builds a dom with `count` folders, each holding a 10 KB blob filled with
+/// its own index.
+fn blob_tree(count: u8) -> WeakDom {
+    let mut root = InstanceBuilder::new("DataModel");
+
+    for i in 0..count {
+        root.add_child(
+            InstanceBuilder::new("Folder")
+                .with_name(format!("Blob{}", i))
+                .with_property("Data", BinaryString::from(vec![i; 10_000])),
+        );
+    }
+
+    WeakDom::new(root)
+}
+
+fn encode(tree: &WeakDom, options: EncodeOptions) -> Result<Vec<u8>, EncodeError> {
+    let mut file = Vec::new();
+    crate::to_writer(&mut file, tree, tree.root().children(), options)?;
+    Ok(file)
+}
+
+#[test]
+fn splits_large_columns() {
+    let _ = env_logger::try_init();
+
+    let tree = blob_tree(10);
+    let file = encode(&tree, EncodeOptions::new().max_chunk_len(Some(25_000))).unwrap();
+
+    let chunks = split_chunks(&file);
+    for chunk in &chunks {
+        assert!(
+            chunk.data.len() <= 25_000,
+            "chunk was {} bytes",
+            chunk.data.len()
+        );
+    }
+
+    // Only two blobs fit in each PROP chunk, so the ten folders should be
+    // split across five class groups.
+    let inst_count = chunks.iter().filter(|chunk| &chunk.name == b"INST").count();
+    assert_eq!(inst_count, 5);
+
+    let decoded = from_reader_default(file.as_slice()).unwrap();
+    let folders = decoded.root().children();
+    assert_eq!(folders.len(), 10);
+
+    for (i, referent) in folders.iter().enumerate() {
+        let folder = decoded.get_by_ref(*referent).unwrap();
+        assert_eq!(folder.class, "Folder");
+        assert_eq!(folder.name, format!("Blob{}", i));
+        assert_eq!(
+            folder.properties.get("Data"),
+            Some(&Variant::BinaryString(vec![i as u8; 10_000].into()))
+        );
+    }
+}
+
+#[test]
+fn files_that_fit_are_unchanged() {
+    let _ = env_logger::try_init();
+
+    let tree = blob_tree(3);
+    let unlimited = encode(&tree, EncodeOptions::new()).unwrap();
+    let limited = encode(&tree, EncodeOptions::new().max_chunk_len(Some(1_000_000))).unwrap();
+
+    assert_eq!(unlimited, limited);
+}
+
+#[test]
+fn value_too_large() {
+    let _ = env_logger::try_init();
+
+    let tree = blob_tree(2);
+    let error = encode(&tree, EncodeOptions::new().max_chunk_len(Some(5_000))).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Property Folder.Data on instance DataModel.Blob0 takes 10017 bytes to write, \
+        which doesn't fit in a chunk of at most 5000 bytes"
+    );
+}
+
+#[test]
+fn unsplittable_chunk_too_large() {
+    let _ = env_logger::try_init();
+
+    // Every instance is listed in the PRNT chunk, so it can't be split.
+    let mut root = InstanceBuilder::new("DataModel");
+    for _ in 0..100 {
+        root.add_child(InstanceBuilder::new("Folder"));
+    }
+    let tree = WeakDom::new(root);
+
+    let error = encode(&tree, EncodeOptions::new().max_chunk_len(Some(500))).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "The PRNT chunk is 805 bytes long, which is more than the maximum of 500 bytes, \
+        and it can't be split"
+    );
+}
+
+#[test]
+fn class_count_warning() {
+    let _ = env_logger::try_init();
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Folder"))
+            .with_child(InstanceBuilder::new("Model"))
+            .with_child(InstanceBuilder::new("Part")),
+    );
+
+    let mut file = Vec::new();
+    let summary = crate::to_writer_with_summary(
+        &mut file,
+        &tree,
+        tree.root().children(),
+        EncodeOptions::new().class_count_warning(Some(3)),
+    )
+    .unwrap();
+    assert!(summary.diagnostics().is_empty());
+
+    let summary = crate::to_writer_with_summary(
+        &mut file,
+        &tree,
+        tree.root().children(),
+        EncodeOptions::new().class_count_warning(Some(2)),
+    )
+    .unwrap();
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].chunk_name(), None);
+    assert_eq!(
+        diagnostics[0].message(),
+        "This file has 3 class groups, which is more than the 2 allowed by \
+        EncodeOptions::class_count_warning"
+    );
+}
+
+#[test]
+fn class_count_includes_split_groups() {
+    let _ = env_logger::try_init();
+
+    let tree = blob_tree(4);
+    let mut file = Vec::new();
+    let summary = crate::to_writer_with_summary(
+        &mut file,
+        &tree,
+        tree.root().children(),
+        EncodeOptions::new()
+            .max_chunk_len(Some(15_000))
+            .class_count_warning(Some(3)),
+    )
+    .unwrap();
+
+    assert_eq!(summary.diagnostics().len(), 1);
+    assert!(summary.diagnostics()[0]
+        .message()
+        .starts_with("This file has 4 class groups"));
+}
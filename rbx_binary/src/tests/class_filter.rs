@@ -0,0 +1,75 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    WeakDom,
+};
+use rbx_reflection::ClassFilter;
+
+use crate::{from_reader, to_writer_default, DecodeOptions};
+
+static PLACE: &str = include_str!("../../../rbx_xml/test-files/extraction-place.rbxlx");
+
+fn place_file() -> Vec<u8> {
+    let dom = rbx_xml::from_str_default(PLACE).unwrap();
+
+    let mut buffer = Vec::new();
+    to_writer_default(&mut buffer, &dom, dom.root().children()).unwrap();
+    buffer
+}
+
+fn full_names(dom: &WeakDom) -> Vec<String> {
+    dom.descendants(dom.root_ref())
+        .skip(1)
+        .map(|instance| dom.full_name(instance.referent()).unwrap())
+        .collect()
+}
+
+/// Each preset should leave the same instances in the same places as it does
+/// when decoding the place with rbx_xml.
+#[test]
+fn presets_match_xml() {
+    let _ = env_logger::try_init();
+
+    let database = rbx_reflection_database::get();
+    let file = place_file();
+
+    let filters = [
+        ClassFilter::scripts(),
+        ClassFilter::gui(database),
+        ClassFilter::geometry(database),
+        ClassFilter::scripts().union(&ClassFilter::gui(database)),
+    ];
+
+    for filter in &filters {
+        let from_binary = from_reader(
+            file.as_slice(),
+            DecodeOptions::new().class_filter(filter.clone()),
+        )
+        .unwrap();
+        let from_xml = rbx_xml::from_str(
+            PLACE,
+            rbx_xml::DecodeOptions::new().class_filter(filter.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(full_names(&from_binary), full_names(&from_xml));
+    }
+}
+
+#[test]
+fn refs_to_filtered_instances() {
+    let _ = env_logger::try_init();
+
+    let dom = from_reader(
+        place_file().as_slice(),
+        DecodeOptions::new().class_filter(ClassFilter::new(Some("ObjectValue"))),
+    )
+    .unwrap();
+
+    assert_eq!(dom.len(), 2);
+
+    let seat = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(
+        seat.properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+}
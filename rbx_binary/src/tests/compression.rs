@@ -0,0 +1,113 @@
+use rbx_dom_weak::{content_hash, ContentHashOptions, InstanceBuilder, WeakDom};
+
+use crate::{
+    core::RbxWriteExt, from_reader_default, tests::util::split_file, CompressionBackend,
+    DecodeErrorKind, EncodeOptions,
+};
+
+/// A model with enough repeated names and script sources for the compression
+/// levels to make a difference.
+fn fixture() -> WeakDom {
+    let mut root = InstanceBuilder::new("DataModel");
+
+    for i in 0..50 {
+        root.add_child(
+            InstanceBuilder::new("Model")
+                .with_name(format!("Tree{}", i))
+                .with_child(InstanceBuilder::new("Part").with_name("Trunk"))
+                .with_child(InstanceBuilder::new("Part").with_name("Leaves"))
+                .with_child(InstanceBuilder::new("Script").with_property(
+                    "Source",
+                    format!("-- Tree {}\n{}", i, "print('sway')\n".repeat(i % 7 + 1)),
+                )),
+        );
+    }
+
+    WeakDom::new(root)
+}
+
+fn encode(tree: &WeakDom, options: EncodeOptions) -> Vec<u8> {
+    let mut file = Vec::new();
+    crate::to_writer(&mut file, tree, tree.root().children(), options).unwrap();
+    file
+}
+
+fn hash(tree: &WeakDom) -> [u8; 32] {
+    content_hash(tree, tree.root_ref(), ContentHashOptions::new())
+}
+
+#[test]
+fn every_backend_and_level() {
+    let _ = env_logger::try_init();
+
+    let tree = fixture();
+    let expected = hash(&tree);
+
+    let backends = [CompressionBackend::Uncompressed, CompressionBackend::Lz4];
+    let levels = [0, 1, 6, 12, 20];
+
+    let mut sizes = Vec::new();
+
+    for &backend in &backends {
+        for &level in &levels {
+            let options = EncodeOptions::new()
+                .compression(backend)
+                .compression_level(level);
+            let file = encode(&tree, options);
+
+            let decoded = from_reader_default(file.as_slice()).unwrap();
+            assert_eq!(
+                hash(&decoded),
+                expected,
+                "{:?} at level {} did not round-trip",
+                backend,
+                level
+            );
+
+            log::info!("{:?} at level {}: {} bytes", backend, level, file.len());
+            sizes.push(((backend, level), file.len()));
+        }
+    }
+
+    let size = |backend, level| {
+        sizes
+            .iter()
+            .find(|(key, _)| *key == (backend, level))
+            .unwrap()
+            .1
+    };
+
+    // The level doesn't matter for uncompressed files.
+    let uncompressed = size(CompressionBackend::Uncompressed, 0);
+    for &level in &levels {
+        assert_eq!(size(CompressionBackend::Uncompressed, level), uncompressed);
+    }
+
+    let fast = size(CompressionBackend::Lz4, 0);
+    let best = size(CompressionBackend::Lz4, 12);
+    assert!(fast < uncompressed);
+    assert!(best <= fast);
+    assert_eq!(size(CompressionBackend::Lz4, 20), best);
+
+    // The default is LZ4's fast compressor, like Roblox uses.
+    assert_eq!(encode(&tree, EncodeOptions::new()).len(), fast);
+}
+
+#[test]
+fn zstd_chunks_are_unsupported() {
+    let _ = env_logger::try_init();
+
+    let (header, _) = split_file(&encode(&fixture(), EncodeOptions::new()));
+
+    // A chunk whose compressed data starts with ZSTD's magic number.
+    let mut file = header;
+    file.extend_from_slice(b"INST");
+    file.write_le_u32(8).unwrap();
+    file.write_le_u32(100).unwrap();
+    file.write_le_u32(0).unwrap();
+    file.extend_from_slice(&[0x28, 0xb5, 0x2f, 0xfd, 0, 0, 0, 0]);
+
+    let error = from_reader_default(file.as_slice()).unwrap_err();
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedVersion);
+    assert!(error.to_string().contains("ZSTD"), "{}", error);
+}
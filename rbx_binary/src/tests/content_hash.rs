@@ -0,0 +1,80 @@
+use rbx_dom_weak::{content_hash, ContentHashOptions, WeakDom};
+
+static MODEL: &str = r#"
+<roblox version="4">
+    <Item class="Model" referent="RBX0">
+        <Properties>
+            <string name="Name">Cart</string>
+        </Properties>
+        <Item class="Part" referent="RBX1">
+            <Properties>
+                <bool name="Anchored">true</bool>
+                <CoordinateFrame name="CFrame">
+                    <X>0</X><Y>5</Y><Z>0</Z>
+                    <R00>1</R00><R01>0</R01><R02>0</R02>
+                    <R10>0</R10><R11>1</R11><R12>0</R12>
+                    <R20>0</R20><R21>0</R21><R22>1</R22>
+                </CoordinateFrame>
+                <string name="Name">Body</string>
+                <Vector3 name="size"><X>4</X><Y>1</Y><Z>2</Z></Vector3>
+                <float name="Transparency">0.25</float>
+            </Properties>
+        </Item>
+        <Item class="Part" referent="RBX2">
+            <Properties>
+                <bool name="Anchored">false</bool>
+                <CoordinateFrame name="CFrame">
+                    <X>2</X><Y>4</Y><Z>1</Z>
+                    <R00>0</R00><R01>-1</R01><R02>0</R02>
+                    <R10>1</R10><R11>0</R11><R12>0</R12>
+                    <R20>0</R20><R21>0</R21><R22>1</R22>
+                </CoordinateFrame>
+                <string name="Name">Wheel</string>
+                <Vector3 name="size"><X>1</X><Y>1</Y><Z>1</Z></Vector3>
+                <float name="Transparency">0</float>
+            </Properties>
+        </Item>
+        <Item class="Weld" referent="RBX3">
+            <Properties>
+                <Ref name="Part0">RBX1</Ref>
+                <Ref name="Part1">RBX2</Ref>
+            </Properties>
+        </Item>
+        <Item class="StringValue" referent="RBX4">
+            <Properties>
+                <string name="Value">Made for testing</string>
+            </Properties>
+        </Item>
+    </Item>
+</roblox>
+"#;
+
+fn hash(dom: &WeakDom) -> [u8; 32] {
+    content_hash(dom, dom.root().children()[0], ContentHashOptions::new())
+}
+
+/// A model should hash the same after being converted between formats.
+#[test]
+fn xml_binary_xml_round_trip() {
+    let from_xml = rbx_xml::from_str_default(MODEL).unwrap();
+
+    let mut binary = Vec::new();
+    crate::to_writer_default(&mut binary, &from_xml, from_xml.root().children()).unwrap();
+    let from_binary = crate::from_reader_default(binary.as_slice()).unwrap();
+
+    let mut xml = Vec::new();
+    rbx_xml::to_writer_default(&mut xml, &from_binary, from_binary.root().children()).unwrap();
+    let from_xml_again = rbx_xml::from_reader_default(xml.as_slice()).unwrap();
+
+    assert_eq!(hash(&from_xml), hash(&from_binary));
+    assert_eq!(hash(&from_xml), hash(&from_xml_again));
+}
+
+#[test]
+fn one_changed_property() {
+    let original = rbx_xml::from_str_default(MODEL).unwrap();
+    let changed =
+        rbx_xml::from_str_default(MODEL.replace("Made for testing", "Made for testing!")).unwrap();
+
+    assert_ne!(hash(&original), hash(&changed));
+}
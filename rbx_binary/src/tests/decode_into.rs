@@ -0,0 +1,92 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{decode_into, to_writer_default, DecodeOptions};
+
+/// Encodes a model holding an ObjectValue that points to a sibling with the
+/// given name.
+fn encode_model(name: &str) -> Vec<u8> {
+    let target = InstanceBuilder::new("Part").with_name(format!("{}Target", name));
+    let value = InstanceBuilder::new("ObjectValue")
+        .with_name(format!("{}Value", name))
+        .with_property("Value", target.referent());
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_name(name)
+            .with_child(target)
+            .with_child(value),
+    );
+
+    let mut buffer = Vec::new();
+    to_writer_default(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    buffer
+}
+
+/// Finds the target of the ObjectValue in the given decoded model.
+fn value_target(dom: &WeakDom, model: Ref) -> Ref {
+    let model = dom.get_by_ref(model).unwrap();
+    let value = dom.get_by_ref(model.children()[1]).unwrap();
+
+    match value.properties.get("Value") {
+        Some(Variant::Ref(target)) => *target,
+        other => panic!("expected a Ref, got {:?}", other),
+    }
+}
+
+#[test]
+fn two_files_into_one_dom() {
+    let _ = env_logger::try_init();
+
+    let mut dom =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+    let folder = dom.root().children()[0];
+
+    let first = encode_model("First");
+    let second = encode_model("Second");
+
+    let first_roots =
+        decode_into(&mut dom, folder, first.as_slice(), DecodeOptions::new()).unwrap();
+    let second_roots =
+        decode_into(&mut dom, folder, second.as_slice(), DecodeOptions::new()).unwrap();
+    let again_roots =
+        decode_into(&mut dom, folder, first.as_slice(), DecodeOptions::new()).unwrap();
+
+    assert_eq!(first_roots.len(), 1);
+    assert_eq!(second_roots.len(), 1);
+    assert_eq!(again_roots.len(), 1);
+    assert_eq!(
+        dom.get_by_ref(folder).unwrap().children(),
+        [first_roots[0], second_roots[0], again_roots[0]]
+    );
+
+    // Each ObjectValue should point to the Part decoded alongside it, even
+    // when the same file is decoded twice.
+    for &model in &[first_roots[0], second_roots[0], again_roots[0]] {
+        let target = value_target(&dom, model);
+        assert_eq!(dom.get_by_ref(model).unwrap().children()[0], target);
+    }
+
+    assert_eq!(
+        dom.full_name(value_target(&dom, second_roots[0])),
+        Some("DataModel.Folder.Second.SecondTarget".to_owned())
+    );
+    assert_eq!(dom.len(), 11);
+}
+
+#[test]
+fn failure_leaves_dom_alone() {
+    let _ = env_logger::try_init();
+
+    let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+    let root = dom.root_ref();
+
+    let file = encode_model("Broken");
+    let truncated = &file[..file.len() - 20];
+
+    decode_into(&mut dom, root, truncated, DecodeOptions::new()).unwrap_err();
+    assert!(dom.root().children().is_empty());
+    assert_eq!(dom.len(), 1);
+}
@@ -0,0 +1,71 @@
+use rbx_dom_weak::{
+    types::{Enum, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{from_reader_with_summary, DecodeOptions, DiagnosticCode, Severity};
+
+/// Encodes three Lighting instances, two of which use the retired `Legacy`
+/// lighting technology.
+fn lighting_file() -> Vec<u8> {
+    let lighting = |technology: u32| {
+        InstanceBuilder::new("Lighting").with_property("Technology", Enum::from_u32(technology))
+    };
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_children(vec![
+        lighting(0),
+        lighting(4),
+        lighting(0),
+    ]));
+
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, &tree, tree.root().children()).unwrap();
+    file
+}
+
+fn technologies(tree: &WeakDom) -> Vec<&Variant> {
+    tree.root()
+        .children()
+        .iter()
+        .map(|referent| &tree.get_by_ref(*referent).unwrap().properties["Technology"])
+        .collect()
+}
+
+#[test]
+fn migrated_by_default() {
+    let file = lighting_file();
+    let (tree, summary) = from_reader_with_summary(file.as_slice(), DecodeOptions::new()).unwrap();
+
+    let compatibility = Variant::Enum(Enum::from_u32(2));
+    let future = Variant::Enum(Enum::from_u32(4));
+    assert_eq!(
+        technologies(&tree),
+        [&compatibility, &future, &compatibility]
+    );
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code(), DiagnosticCode::MigratedEnumValues);
+    assert_eq!(diagnostics[0].severity(), Severity::Warning);
+    assert_eq!(diagnostics[0].property(), Some("Lighting.Technology"));
+    assert_eq!(
+        diagnostics[0].message(),
+        "Lighting.Technology had 2 Technology values from an older version of Roblox, which \
+         were migrated to the values that replaced them; the first was instance 0 of its class"
+    );
+
+    // Migrating values isn't recovering from damage.
+    assert!(!summary.recovered());
+}
+
+#[test]
+fn values_kept_when_disabled() {
+    let file = lighting_file();
+    let options = DecodeOptions::new().migrate_enum_values(false);
+    let (tree, summary) = from_reader_with_summary(file.as_slice(), options).unwrap();
+
+    let legacy = Variant::Enum(Enum::from_u32(0));
+    let future = Variant::Enum(Enum::from_u32(4));
+    assert_eq!(technologies(&tree), [&legacy, &future, &legacy]);
+    assert!(summary.diagnostics().is_empty());
+}
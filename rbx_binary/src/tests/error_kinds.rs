@@ -0,0 +1,132 @@
+use std::io::{self, Read};
+
+use rbx_dom_weak::{types::Faces, InstanceBuilder, WeakDom};
+
+use crate::{
+    from_reader_default,
+    tests::util::{build_file, find_prop_chunk, split_file},
+    DecodeError, DecodeErrorKind,
+};
+
+fn encode(tree: &WeakDom) -> Vec<u8> {
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, tree, tree.root().children()).unwrap();
+    file
+}
+
+fn encode_sample() -> Vec<u8> {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "Hello")),
+    );
+
+    encode(&tree)
+}
+
+fn decode_err(file: &[u8]) -> DecodeError {
+    from_reader_default(file).unwrap_err()
+}
+
+struct FailingReader;
+
+impl Read for FailingReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "access denied",
+        ))
+    }
+}
+
+#[test]
+fn io() {
+    let error = from_reader_default(FailingReader).unwrap_err();
+
+    assert_eq!(error.kind(), DecodeErrorKind::Io);
+    assert!(error.is_io());
+    assert!(!error.is_malformed());
+    assert!(!error.is_unsupported());
+}
+
+#[test]
+fn malformed_header() {
+    let mut file = encode_sample();
+    file[0] = b'!';
+
+    let error = decode_err(&file);
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedFile);
+    assert!(error.is_malformed());
+    assert!(!error.is_io());
+}
+
+#[test]
+fn truncated_file() {
+    let (header, chunks) = split_file(&encode_sample());
+    let mut file = build_file(&header, &chunks);
+    file.truncate(file.len() - 12);
+
+    let error = decode_err(&file);
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedFile);
+    assert!(error.is_malformed());
+}
+
+#[test]
+fn malformed_value() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Handles").with_property("Faces", Faces::all())),
+    );
+    let (header, mut chunks) = split_file(&encode(&tree));
+
+    let (chunk, _) = find_prop_chunk(&mut chunks, "Faces");
+    *chunk.data.last_mut().unwrap() = 0xff;
+
+    let error = decode_err(&build_file(&header, &chunks));
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedValue);
+    assert!(error.is_malformed());
+    assert!(!error.is_unsupported());
+}
+
+#[test]
+fn unsupported_version() {
+    let mut file = encode_sample();
+    // The file version comes right after the magic header and signature.
+    file[14..16].copy_from_slice(&3u16.to_le_bytes());
+
+    let error = decode_err(&file);
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedVersion);
+    assert!(error.is_unsupported());
+    assert!(!error.is_malformed());
+    assert_eq!(
+        error.to_string(),
+        "Unknown file version 3. Known versions are: 0"
+    );
+}
+
+#[test]
+fn unsupported_type() {
+    let (header, mut chunks) = split_file(&encode_sample());
+
+    let (chunk, type_offset) = find_prop_chunk(&mut chunks, "Value");
+    chunk.data[type_offset] = 0xee;
+
+    let error = decode_err(&build_file(&header, &chunks));
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedType);
+    assert!(error.is_unsupported());
+}
+
+#[test]
+fn mismatched_type() {
+    let (header, mut chunks) = split_file(&encode_sample());
+
+    // Claim that StringValue.Value holds a bool, which the reflection
+    // database won't accept.
+    let (chunk, type_offset) = find_prop_chunk(&mut chunks, "Value");
+    chunk.data.truncate(type_offset);
+    chunk.data.push(0x02);
+    chunk.data.push(1);
+
+    let error = decode_err(&build_file(&header, &chunks));
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedType);
+    assert!(error.is_unsupported());
+}
@@ -0,0 +1,167 @@
+use std::sync::{Arc, Mutex};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, Layer},
+    prelude::*,
+    registry::LookupSpan,
+};
+
+use crate::{from_reader, to_writer_default, DecodeOptions};
+
+/// Records the name of every span and event message along with the names of
+/// the spans that were open around it.
+#[derive(Clone, Default)]
+struct Recorder {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl Recorder {
+    fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+}
+
+fn scope_path<S>(span: Option<tracing_subscriber::registry::SpanRef<'_, S>>) -> Vec<&'static str>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    match span {
+        Some(span) => span.scope().from_root().map(|span| span.name()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Pulls the message out of an event.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let path = scope_path(ctx.span(id));
+        let line = path.join(" > ");
+
+        let mut lines = self.lines.lock().unwrap();
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let path = scope_path(ctx.event_span(event));
+        let line = format!("event: {} > {}", path.join(" > "), visitor.message);
+
+        let mut lines = self.lines.lock().unwrap();
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+}
+
+fn record<F: FnOnce()>(body: F) -> Vec<String> {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+    tracing::subscriber::with_default(subscriber, body);
+
+    recorder.lines()
+}
+
+fn file() -> Vec<u8> {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "Hello")),
+    );
+
+    let mut file = Vec::new();
+    to_writer_default(&mut file, &tree, &[tree.root_ref()]).unwrap();
+    file
+}
+
+#[test]
+fn decode_spans() {
+    let file = file();
+
+    let lines = record(|| {
+        from_reader(file.as_slice(), DecodeOptions::new()).unwrap();
+    });
+
+    let expected = [
+        "decode",
+        "decode > read_header",
+        "decode > read_chunk",
+        "decode > decode_chunk",
+        "decode > build_dom",
+    ];
+    let spans: Vec<_> = lines
+        .iter()
+        .filter(|line| !line.starts_with("event"))
+        .collect();
+    assert_eq!(spans, expected);
+
+    for event in &[
+        "event: decode > read_chunk > read chunk",
+        "event: decode > decode_chunk > INST chunk",
+        "event: decode > decode_chunk > PROP chunk",
+        "event: decode > decoded file",
+    ] {
+        assert!(lines.iter().any(|line| line == event), "{}", event);
+    }
+}
+
+#[test]
+fn encode_spans() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder"));
+
+    let lines = record(|| {
+        to_writer_default(Vec::new(), &tree, &[tree.root_ref()]).unwrap();
+    });
+
+    let expected = [
+        "encode",
+        "encode > add_instances",
+        "encode > generate_referents",
+        "encode > plan_type_groups",
+        "encode > write_header",
+        "encode > serialize_metadata",
+        "encode > serialize_shared_strings",
+        "encode > serialize_instances",
+        "encode > serialize_properties",
+        "encode > serialize_parents",
+        "encode > serialize_end",
+    ];
+    let spans: Vec<_> = lines
+        .iter()
+        .filter(|line| !line.starts_with("event"))
+        .collect();
+    assert_eq!(spans, expected);
+
+    for event in &[
+        "event: encode > serialize_instances > INST chunk",
+        "event: encode > serialize_properties > PROP chunk",
+        "event: encode > serialize_end > wrote chunk",
+        "event: encode > encoded file",
+    ] {
+        assert!(lines.iter().any(|line| line == event), "{}", event);
+    }
+}
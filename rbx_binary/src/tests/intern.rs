@@ -0,0 +1,155 @@
+use std::{collections::HashSet, sync::Arc};
+
+use rbx_dom_weak::{
+    types::{Content, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_test_util::textures;
+
+use crate::{from_reader, DecodeOptions};
+
+static URLS: &[&str] = &[
+    "http://www.roblox.com/asset/?id=1818",
+    "http://www.roblox.com/asset/?id=262470873",
+    "rbxassetid://1818",
+];
+
+/// A model with lots of decals that share a handful of textures, which is
+/// what most places look like.
+fn encode_decals(count: usize) -> Vec<u8> {
+    let decals = (0..count).map(|i| {
+        InstanceBuilder::new("Decal").with_property("Texture", Content::from(URLS[i % URLS.len()]))
+    });
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(decals));
+
+    let mut buffer = Vec::new();
+    crate::to_writer_default(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    buffer
+}
+
+/// The number of bytes used to hold the URLs themselves, counting each
+/// allocation only once.
+fn url_bytes(contents: &[&Content]) -> usize {
+    let mut seen = HashSet::new();
+
+    contents
+        .iter()
+        .map(|content| AsRef::<str>::as_ref(*content))
+        .filter(|url| seen.insert(url.as_ptr()))
+        .map(str::len)
+        .sum()
+}
+
+#[test]
+fn memory_use() {
+    let file = encode_decals(10_000);
+
+    let plain = from_reader(file.as_slice(), DecodeOptions::new()).unwrap();
+    let interned = from_reader(file.as_slice(), DecodeOptions::new().intern_strings(true)).unwrap();
+
+    let plain_bytes = url_bytes(&textures(&plain));
+    let interned_bytes = url_bytes(&textures(&interned));
+    let distinct_bytes: usize = URLS.iter().map(|url| url.len()).sum();
+
+    assert!(plain_bytes > 3000 * distinct_bytes);
+    assert_eq!(interned_bytes, distinct_bytes);
+}
+
+#[test]
+fn mutation_is_copy_on_write() {
+    let file = encode_decals(6);
+    let mut dom = from_reader(file.as_slice(), DecodeOptions::new().intern_strings(true)).unwrap();
+
+    let first_ref = dom.get_by_ref(dom.root().children()[0]).unwrap().children()[0];
+
+    match dom
+        .get_by_ref_mut(first_ref)
+        .unwrap()
+        .properties
+        .get_mut("Texture")
+    {
+        Some(Variant::Content(content)) => {
+            let url: &mut String = content.as_mut();
+            url.push_str("&version=2");
+        }
+        other => panic!("expected a Content texture, got {:?}", other),
+    }
+
+    let textures = textures(&dom);
+    assert_eq!(
+        AsRef::<str>::as_ref(textures[0]),
+        "http://www.roblox.com/asset/?id=1818&version=2"
+    );
+
+    // The other decal with this texture still has the original URL.
+    assert_eq!(AsRef::<str>::as_ref(textures[3]), URLS[0]);
+    assert!(!textures[0].ptr_eq(textures[3]));
+
+    // Untouched values are still shared.
+    assert!(textures[1].ptr_eq(textures[4]));
+}
+
+#[test]
+fn long_values_are_not_interned() {
+    let file = encode_decals(6);
+
+    let options = DecodeOptions::new()
+        .intern_strings(true)
+        .intern_max_len(URLS[2].len());
+    let dom = from_reader(file.as_slice(), options).unwrap();
+    let textures = textures(&dom);
+
+    assert!(!textures[0].ptr_eq(textures[3]));
+    assert!(textures[2].ptr_eq(textures[5]));
+}
+
+#[test]
+fn off_by_default() {
+    let file = encode_decals(6);
+    let dom = from_reader(file.as_slice(), DecodeOptions::default()).unwrap();
+    let textures = textures(&dom);
+
+    assert!(!textures[0].ptr_eq(textures[3]));
+}
+
+#[test]
+fn strings_are_interned() {
+    let values = (0..4).map(|i| {
+        InstanceBuilder::new("StringValue").with_property("Value", ["Plastic", "Neon"][i % 2])
+    });
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(values));
+
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, &tree, &[tree.root_ref()]).unwrap();
+
+    let mut dom = from_reader(file.as_slice(), DecodeOptions::new().intern_strings(true)).unwrap();
+    let children = dom
+        .get_by_ref(dom.root().children()[0])
+        .unwrap()
+        .children()
+        .to_vec();
+    let value = |dom: &WeakDom, index: usize| -> Arc<str> {
+        match dom
+            .get_by_ref(children[index])
+            .unwrap()
+            .properties
+            .get("Value")
+        {
+            Some(Variant::String(value)) => Arc::clone(value),
+            other => panic!("expected a String value, got {:?}", other),
+        }
+    };
+
+    assert!(Arc::ptr_eq(&value(&dom, 0), &value(&dom, 2)));
+    assert!(Arc::ptr_eq(&value(&dom, 1), &value(&dom, 3)));
+
+    // Replacing one instance's value leaves the others alone.
+    dom.get_by_ref_mut(children[0])
+        .unwrap()
+        .properties
+        .insert("Value".to_owned(), Variant::from("Glass"));
+
+    assert_eq!(&*value(&dom, 0), "Glass");
+    assert_eq!(&*value(&dom, 2), "Plastic");
+}
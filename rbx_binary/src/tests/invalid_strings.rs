@@ -0,0 +1,141 @@
+use rbx_dom_weak::{
+    types::{BinaryString, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{
+    from_reader, from_reader_with_summary,
+    tests::util::{build_file, find_prop_chunk, split_file},
+    DecodeErrorKind, DecodeOptions, DiagnosticCode, InvalidStringBehavior, Severity,
+};
+
+/// Encodes three StringValues, then turns every `!` in their names and values
+/// into a byte that's never valid in UTF-8.
+fn invalid_file() -> Vec<u8> {
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_children(vec![
+        InstanceBuilder::new("StringValue")
+            .with_name("Fine")
+            .with_property("Value", "ok"),
+        InstanceBuilder::new("StringValue")
+            .with_name("Loud!")
+            .with_property("Value", "bad!"),
+        InstanceBuilder::new("StringValue")
+            .with_name("Fine")
+            .with_property("Value", "worse!!"),
+    ]));
+
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, &tree, tree.root().children()).unwrap();
+
+    let (header, mut chunks) = split_file(&file);
+
+    for prop_name in &["Name", "Value"] {
+        let (chunk, type_offset) = find_prop_chunk(&mut chunks, prop_name);
+
+        for byte in &mut chunk.data[type_offset + 1..] {
+            if *byte == b'!' {
+                *byte = 0xff;
+            }
+        }
+    }
+
+    build_file(&header, &chunks)
+}
+
+fn children(tree: &WeakDom) -> Vec<&rbx_dom_weak::Instance> {
+    tree.root()
+        .children()
+        .iter()
+        .map(|referent| tree.get_by_ref(*referent).unwrap())
+        .collect()
+}
+
+#[test]
+fn error_by_default() {
+    let error = from_reader(invalid_file().as_slice(), DecodeOptions::new()).unwrap_err();
+
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedValue);
+    assert_eq!(
+        error.to_string(),
+        "Invalid property data: String property StringValue.Name of instance 1 of its class \
+         was not valid UTF-8"
+    );
+}
+
+#[test]
+fn replace_lossy() {
+    let options = DecodeOptions::new().invalid_strings(InvalidStringBehavior::ReplaceLossy);
+    let (tree, summary) = from_reader_with_summary(invalid_file().as_slice(), options).unwrap();
+
+    let values = children(&tree);
+    assert_eq!(values[1].name, "Loud\u{fffd}");
+    assert_eq!(
+        values[0].properties.get("Value"),
+        Some(&Variant::from("ok"))
+    );
+    assert_eq!(
+        values[1].properties.get("Value"),
+        Some(&Variant::from("bad\u{fffd}"))
+    );
+    assert_eq!(
+        values[2].properties.get("Value"),
+        Some(&Variant::from("worse\u{fffd}\u{fffd}"))
+    );
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(!summary.recovered());
+
+    assert_eq!(diagnostics[0].code(), DiagnosticCode::ReplacedInvalidString);
+    assert_eq!(diagnostics[0].severity(), Severity::Error);
+    assert_eq!(diagnostics[0].property(), Some("StringValue.Name"));
+
+    assert_eq!(diagnostics[1].code(), DiagnosticCode::ReplacedInvalidString);
+    assert_eq!(diagnostics[1].property(), Some("StringValue.Value"));
+    assert_eq!(
+        diagnostics[1].message(),
+        "StringValue.Value had invalid UTF-8 in 2 values, replaced with U+FFFD; \
+         the first was instance 1 of its class"
+    );
+}
+
+#[test]
+fn preserve_as_binary_string() {
+    let options =
+        DecodeOptions::new().invalid_strings(InvalidStringBehavior::PreserveAsBinaryString);
+    let (tree, summary) = from_reader_with_summary(invalid_file().as_slice(), options).unwrap();
+
+    let values = children(&tree);
+
+    // Names can't be BinaryStrings, so they're replaced instead.
+    assert_eq!(values[1].name, "Loud\u{fffd}");
+    assert_eq!(
+        values[0].properties.get("Value"),
+        Some(&Variant::from("ok"))
+    );
+    assert_eq!(
+        values[1].properties.get("Value"),
+        Some(&Variant::BinaryString(BinaryString::from(
+            b"bad\xff".to_vec()
+        )))
+    );
+    assert_eq!(
+        values[2].properties.get("Value"),
+        Some(&Variant::BinaryString(BinaryString::from(
+            b"worse\xff\xff".to_vec()
+        )))
+    );
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+
+    assert_eq!(diagnostics[0].code(), DiagnosticCode::ReplacedInvalidString);
+    assert_eq!(diagnostics[0].property(), Some("StringValue.Name"));
+
+    assert_eq!(
+        diagnostics[1].code(),
+        DiagnosticCode::PreservedInvalidString
+    );
+    assert_eq!(diagnostics[1].severity(), Severity::Warning);
+    assert_eq!(diagnostics[1].property(), Some("StringValue.Value"));
+}
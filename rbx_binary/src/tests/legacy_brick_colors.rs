@@ -0,0 +1,93 @@
+use rbx_dom_weak::{
+    types::{Color3, Color3uint8, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{
+    core::RbxWriteExt,
+    from_reader_default,
+    tests::util::{build_file, split_file, RawChunk},
+};
+
+/// Builds a file containing a single Part with a legacy BrickColor property,
+/// like files saved by Roblox Studio before parts had a Color3 color.
+///
+/// If `color` is given, the Part is also given a Color3uint8 color, which is
+/// written before the BrickColor.
+fn part_with_brick_color(brick_color: u32, color: Option<Color3uint8>) -> Vec<u8> {
+    let mut part = InstanceBuilder::new("Part");
+    if let Some(color) = color {
+        part.add_property("Color", color);
+    }
+
+    let tree = WeakDom::new(part);
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, &tree, &[tree.root_ref()]).unwrap();
+
+    let (header, mut chunks) = split_file(&file);
+
+    // There's only one type in this file, so its type ID is 0.
+    let mut data = Vec::new();
+    data.write_le_u32(0).unwrap();
+    data.write_string("BrickColor").unwrap();
+    data.write_u8(0x0B).unwrap();
+
+    // With only one value, the interleaved array is just the value's bytes in
+    // big-endian order.
+    data.extend_from_slice(&brick_color.to_be_bytes());
+
+    let prnt = chunks
+        .iter()
+        .position(|chunk| &chunk.name == b"PRNT")
+        .unwrap();
+    chunks.insert(
+        prnt,
+        RawChunk {
+            name: *b"PROP",
+            reserved: 0,
+            data,
+        },
+    );
+
+    build_file(&header, &chunks)
+}
+
+fn decode_color(file: &[u8]) -> Option<Variant> {
+    let dom = from_reader_default(file).expect("failed to decode model");
+    let part = dom.get_by_ref(dom.root().children()[0]).unwrap();
+
+    assert!(!part.properties.contains_key("BrickColor"));
+    part.properties.get("Color").cloned()
+}
+
+#[test]
+fn brick_color_becomes_color() {
+    // BrickColor 21 is Bright red.
+    let file = part_with_brick_color(21, None);
+
+    assert_eq!(
+        decode_color(&file),
+        Some(Variant::Color3(Color3uint8::new(196, 40, 28).into()))
+    );
+}
+
+#[test]
+fn unknown_brick_color_is_medium_stone_grey() {
+    let file = part_with_brick_color(9999, None);
+
+    assert_eq!(
+        decode_color(&file),
+        Some(Variant::Color3(Color3uint8::new(163, 162, 165).into()))
+    );
+}
+
+#[test]
+fn color3uint8_wins_over_brick_color() {
+    let color = Color3uint8::new(0, 0, 255);
+    let file = part_with_brick_color(21, Some(color));
+
+    assert_eq!(
+        decode_color(&file),
+        Some(Variant::Color3(Color3::from(color)))
+    );
+}
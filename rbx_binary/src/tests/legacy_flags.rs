@@ -0,0 +1,79 @@
+use rbx_dom_weak::{types::Variant, WeakDom};
+
+use crate::{from_reader_default, to_writer_default};
+
+/// A model saved by an old version of Studio, with its script locked and its
+/// archivable flag written under the old lowercase name.
+static LOCKED_MODEL: &str = r#"
+<roblox version="4">
+    <Item class="Model" referent="RBX0">
+        <Properties>
+            <string name="Name">Locked</string>
+            <bool name="RobloxLocked">true</bool>
+            <bool name="archivable">true</bool>
+        </Properties>
+        <Item class="Script" referent="RBX1">
+            <Properties>
+                <string name="Name">Guard</string>
+                <bool name="RobloxLocked">true</bool>
+                <bool name="archivable">false</bool>
+            </Properties>
+        </Item>
+    </Item>
+</roblox>
+"#;
+
+fn flags(dom: &WeakDom) -> Vec<(String, Option<&Variant>, Option<&Variant>)> {
+    let model = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    let script = dom.get_by_ref(model.children()[0]).unwrap();
+
+    [model, script]
+        .iter()
+        .map(|instance| {
+            (
+                instance.name.clone(),
+                instance.properties.get("Archivable"),
+                instance.properties.get("RobloxLocked"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn xml_to_binary_to_xml() {
+    let _ = env_logger::try_init();
+
+    let original = rbx_xml::from_str_default(LOCKED_MODEL).unwrap();
+    assert_eq!(
+        flags(&original),
+        [
+            (
+                "Locked".to_owned(),
+                Some(&Variant::Bool(true)),
+                Some(&Variant::Bool(true))
+            ),
+            (
+                "Guard".to_owned(),
+                Some(&Variant::Bool(false)),
+                Some(&Variant::Bool(true))
+            ),
+        ]
+    );
+
+    let mut binary = Vec::new();
+    to_writer_default(&mut binary, &original, original.root().children()).unwrap();
+    let from_binary = from_reader_default(binary.as_slice()).unwrap();
+    assert_eq!(flags(&from_binary), flags(&original));
+
+    let mut xml = Vec::new();
+    rbx_xml::to_writer_default(&mut xml, &from_binary, from_binary.root().children()).unwrap();
+    let xml = String::from_utf8(xml).unwrap();
+    assert!(
+        xml.contains(r#"<bool name="RobloxLocked">true</bool>"#),
+        "{}",
+        xml
+    );
+
+    let from_xml = rbx_xml::from_str_default(&xml).unwrap();
+    assert_eq!(flags(&from_xml), flags(&original));
+}
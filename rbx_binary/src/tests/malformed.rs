@@ -0,0 +1,190 @@
+use std::panic;
+
+use rbx_dom_weak::{
+    types::{
+        CFrame, Color3, ColorSequence, ColorSequenceKeypoint, Matrix3, NumberSequence,
+        NumberSequenceKeypoint, Vector3,
+    },
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{
+    core::RbxWriteExt,
+    from_reader, peek,
+    tests::util::{build_file, split_file, RawChunk},
+    to_writer, CompressionBackend, DecodeErrorKind, DecodeOptions, EncodeOptions,
+};
+
+fn sample_tree() -> WeakDom {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Part")
+            .with_property("Size", Vector3::new(4.0, 1.0, 2.0))
+            .with_property(
+                "CFrame",
+                CFrame::new(Vector3::new(1.0, 2.0, 3.0), Matrix3::identity()),
+            ),
+        InstanceBuilder::new("ParticleEmitter")
+            .with_property(
+                "Size",
+                NumberSequence {
+                    keypoints: vec![
+                        NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+                        NumberSequenceKeypoint::new(1.0, 2.0, 0.0),
+                    ],
+                },
+            )
+            .with_property(
+                "Color",
+                ColorSequence {
+                    keypoints: vec![
+                        ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 0.0, 0.0)),
+                        ColorSequenceKeypoint::new(1.0, Color3::new(0.0, 0.0, 1.0)),
+                    ],
+                },
+            ),
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+        InstanceBuilder::new("ObjectValue"),
+    ]));
+
+    let folder = tree.get_by_ref(tree.root_ref()).unwrap();
+    let (part, object_value) = (folder.children()[0], folder.children()[3]);
+    tree.get_by_ref_mut(object_value)
+        .unwrap()
+        .properties
+        .insert("Value".to_owned(), part.into());
+
+    tree
+}
+
+fn encode_sample(compression: CompressionBackend) -> Vec<u8> {
+    let tree = sample_tree();
+    let mut file = Vec::new();
+    let options = EncodeOptions::new().compression(compression);
+    to_writer(&mut file, &tree, &[tree.root_ref()], options).unwrap();
+
+    file
+}
+
+/// Decodes the file every way that rbx_binary can, making sure that none of
+/// them panic. Returns the error from decoding it strictly, if there was one.
+fn decode_every_way(file: &[u8], description: &str) -> Option<DecodeErrorKind> {
+    let result = panic::catch_unwind(|| {
+        let _ = from_reader(file, DecodeOptions::new().recover(true));
+        let _ = peek(file);
+        from_reader(file, DecodeOptions::new())
+            .err()
+            .map(|e| e.kind())
+    });
+
+    match result {
+        Ok(kind) => kind,
+        Err(_) => panic!("decoding a file with {} panicked", description),
+    }
+}
+
+/// Each of these files made rbx_binary panic or abort before, from asking for
+/// gigabytes of memory or overflowing an integer.
+#[test]
+fn fuzz_regressions() {
+    let (header, chunks) = split_file(&encode_sample(CompressionBackend::Uncompressed));
+
+    // An INST chunk that claims to declare about four billion instances.
+    let mut huge_inst = chunks.clone();
+    let inst = huge_inst.iter_mut().find(|c| &c.name == b"INST").unwrap();
+    let name_len = u32::from_le_bytes([inst.data[4], inst.data[5], inst.data[6], inst.data[7]]);
+    let count_offset = 8 + name_len as usize + 1;
+    inst.data[count_offset..count_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    // A PRNT chunk that claims to have about four billion links.
+    let mut huge_prnt = chunks.clone();
+    let prnt = huge_prnt.iter_mut().find(|c| &c.name == b"PRNT").unwrap();
+    prnt.data[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    // An INST chunk whose referents add up to more than an i32 can hold.
+    let mut overflowing_refs = chunks.clone();
+    let inst = overflowing_refs
+        .iter_mut()
+        .find(|c| &c.name == b"INST")
+        .unwrap();
+    let mut data = inst.data[..count_offset].to_vec();
+    data.write_le_u32(2).unwrap();
+    data.write_interleaved_i32_array([i32::MAX, i32::MAX].iter().copied())
+        .unwrap();
+    inst.data = data;
+
+    // A META chunk that claims to have about four billion entries. rbx_binary
+    // never writes one, so it's added here.
+    let mut huge_meta = chunks.clone();
+    let mut meta = RawChunk {
+        name: *b"META",
+        reserved: 0,
+        data: Vec::new(),
+    };
+    meta.data.write_le_u32(u32::MAX).unwrap();
+    meta.data.write_string("ExplicitAutoJoints").unwrap();
+    meta.data.write_string("true").unwrap();
+    huge_meta.insert(0, meta);
+
+    for (chunks, description) in &[
+        (huge_inst, "an INST chunk with too many instances"),
+        (huge_prnt, "a PRNT chunk with too many links"),
+        (overflowing_refs, "referents that overflow"),
+        (huge_meta, "a META chunk with too many entries"),
+    ] {
+        let file = build_file(&header, chunks);
+        assert_eq!(
+            decode_every_way(&file, description),
+            Some(DecodeErrorKind::MalformedFile),
+            "{}",
+            description
+        );
+    }
+
+    // A file header that claims there are about four billion classes and
+    // instances, which is only used to decide how much space to set aside.
+    let mut file = build_file(&header, &chunks);
+    file[16..24].copy_from_slice(&[0xff; 8]);
+    decode_every_way(&file, "huge counts in the file header");
+
+    // A compressed chunk that claims to decompress to about four gigabytes.
+    let mut file = encode_sample(CompressionBackend::Lz4);
+    file[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        decode_every_way(&file, "a chunk with a huge decompressed length"),
+        Some(DecodeErrorKind::MalformedFile)
+    );
+}
+
+/// Checks every way of cutting a file short and many ways of damaging each of
+/// its bytes, so that the guarantee doesn't depend on running a fuzzer.
+///
+/// [`decode_every_way`] turns any panic into a failure that says which change
+/// to the file caused it.
+#[test]
+fn damaged_files_never_panic() {
+    for compression in &[CompressionBackend::Uncompressed, CompressionBackend::Lz4] {
+        let file = encode_sample(*compression);
+
+        for len in 0..file.len() {
+            decode_every_way(
+                &file[..len],
+                &format!("{:?} compression cut off after {} bytes", compression, len),
+            );
+        }
+
+        for offset in 0..file.len() {
+            for &byte in &[0x00, 0x01, 0x7f, 0x80, 0xff] {
+                let mut damaged = file.clone();
+                damaged[offset] = byte;
+
+                decode_every_way(
+                    &damaged,
+                    &format!(
+                        "{:?} compression and byte {} set to {:#04x}",
+                        compression, offset, byte
+                    ),
+                );
+            }
+        }
+    }
+}
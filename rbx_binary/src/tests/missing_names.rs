@@ -0,0 +1,129 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{
+    from_reader, from_reader_default, tests::util::split_chunks, to_writer, to_writer_default,
+    DecodeOptions, EncodeOptions,
+};
+
+/// The same model as `binary_fixture`: the Folders have no Name, but the
+/// StringValue does.
+const XML_FIXTURE: &str = r#"
+    <roblox version="4">
+        <Item class="Folder" referent="RBX1">
+            <Properties />
+            <Item class="Folder" referent="RBX2">
+                <Properties />
+            </Item>
+            <Item class="StringValue" referent="RBX3">
+                <Properties>
+                    <string name="Name">Custom</string>
+                </Properties>
+            </Item>
+        </Item>
+    </roblox>
+"#;
+
+/// Returns a binary model with no Name PROP chunk for its Folders.
+fn binary_fixture() -> Vec<u8> {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Folder"),
+        InstanceBuilder::new("StringValue").with_name("Custom"),
+    ]));
+
+    let mut buffer = Vec::new();
+    let options = EncodeOptions::new().omit_default_names(true);
+    to_writer(&mut buffer, &tree, &[tree.root_ref()], options).unwrap();
+    assert_eq!(name_chunk_count(&buffer), 1);
+
+    buffer
+}
+
+/// Returns the names and explicit names of the root instance and its
+/// children.
+fn names(dom: &WeakDom) -> Vec<(&str, Option<&str>)> {
+    let root = dom.get_by_ref(dom.root().children()[0]).unwrap();
+
+    std::iter::once(root)
+        .chain(
+            root.children()
+                .iter()
+                .map(|id| dom.get_by_ref(*id).unwrap()),
+        )
+        .map(|instance| (instance.name.as_str(), instance.explicit_name()))
+        .collect()
+}
+
+fn name_chunk_count(file: &[u8]) -> usize {
+    split_chunks(file)
+        .into_iter()
+        .filter(|chunk| &chunk.name == b"PROP")
+        .filter(|chunk| {
+            // PROP chunks start with a u32 type ID, then the property name.
+            let data = &chunk.data;
+            data[4..8] == 4u32.to_le_bytes() && &data[8..12] == b"Name"
+        })
+        .count()
+}
+
+#[test]
+fn missing_names_use_class_name() {
+    let from_binary = from_reader_default(binary_fixture().as_slice()).unwrap();
+    let from_xml = rbx_xml::from_str_default(XML_FIXTURE).unwrap();
+
+    let expected = [
+        ("Folder", Some("Folder")),
+        ("Folder", Some("Folder")),
+        ("Custom", Some("Custom")),
+    ];
+    assert_eq!(names(&from_binary), expected);
+    assert_eq!(names(&from_xml), expected);
+}
+
+#[test]
+fn preserve_missing_names() {
+    let from_binary = from_reader(
+        binary_fixture().as_slice(),
+        DecodeOptions::new().preserve_missing_names(true),
+    )
+    .unwrap();
+    let from_xml = rbx_xml::from_str(
+        XML_FIXTURE,
+        rbx_xml::DecodeOptions::new().preserve_missing_names(true),
+    )
+    .unwrap();
+
+    let expected = [
+        ("Folder", None),
+        ("Folder", None),
+        ("Custom", Some("Custom")),
+    ];
+    assert_eq!(names(&from_binary), expected);
+    assert_eq!(names(&from_xml), expected);
+}
+
+/// Missing names should stay missing when a model decoded with
+/// `preserve_missing_names` is encoded again in either format, even without
+/// `omit_default_names`.
+#[test]
+fn missing_names_round_trip() {
+    let options = DecodeOptions::new().preserve_missing_names(true);
+    let dom = from_reader(binary_fixture().as_slice(), options).unwrap();
+
+    let mut binary = Vec::new();
+    to_writer_default(&mut binary, &dom, dom.root().children()).unwrap();
+    assert_eq!(name_chunk_count(&binary), 1);
+
+    let mut xml = Vec::new();
+    rbx_xml::to_writer_default(&mut xml, &dom, dom.root().children()).unwrap();
+    let document = String::from_utf8(xml).unwrap();
+    assert_eq!(document.matches(r#"name="Name""#).count(), 1);
+
+    // Renaming an instance gives it a Name again.
+    let mut dom = dom;
+    let root_ref = dom.root().children()[0];
+    dom.get_by_ref_mut(root_ref).unwrap().name = "Renamed".to_owned();
+
+    let mut binary = Vec::new();
+    to_writer_default(&mut binary, &dom, dom.root().children()).unwrap();
+    assert_eq!(name_chunk_count(&binary), 2);
+}
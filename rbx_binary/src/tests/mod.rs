@@ -1,3 +1,29 @@
+mod chunk_limits;
+mod class_filter;
+mod compression;
+mod content_hash;
+mod decode_into;
+mod enum_migrations;
+mod error_kinds;
+#[cfg(feature = "tracing")]
+mod instrument;
+mod intern;
+mod invalid_strings;
+mod legacy_brick_colors;
+mod legacy_flags;
+mod malformed;
+mod missing_names;
 mod models;
+mod narrow_types;
+mod normalize_content;
+mod peek;
+mod recover;
+mod ref_provider;
+mod referents;
 mod serializer;
+mod source_locations;
+mod summary;
+mod supported_types;
+mod threads;
 mod util;
+mod whitespace_strings;
@@ -1,6 +1,5 @@
-use std::path::PathBuf;
-
 use heck::KebabCase;
+use rbx_test_util::{Fixture, Format};
 
 use super::util::run_model_base_suite;
 
@@ -11,14 +10,8 @@ macro_rules! binary_tests {
             fn $test_name() {
                 let _ = env_logger::try_init();
 
-                let mut test_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-                assert!(test_path.pop());
-                test_path.push("test-files");
-                test_path.push("models");
-                test_path.push(stringify!($test_name).to_kebab_case());
-                test_path.push("binary.rbxm");
-
-                run_model_base_suite(test_path);
+                let fixture = Fixture::model(&stringify!($test_name).to_kebab_case());
+                run_model_base_suite(fixture.path(Format::Binary));
             }
         )*
     };
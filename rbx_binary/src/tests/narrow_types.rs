@@ -0,0 +1,92 @@
+use rbx_dom_weak::{
+    types::{Color3uint8, Variant, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{
+    core::RbxWriteExt,
+    from_reader_default,
+    tests::util::{build_file, split_file, RawChunk},
+    to_writer_default,
+};
+
+fn part_color(dom: &WeakDom) -> Option<&Variant> {
+    let part = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    part.properties.get("Color")
+}
+
+#[test]
+fn color3uint8_is_widened() {
+    let dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+        InstanceBuilder::new("Part").with_property("Color", Color3uint8::new(1, 2, 3)),
+    ));
+
+    let mut file = Vec::new();
+    to_writer_default(&mut file, &dom, dom.root().children()).unwrap();
+    let decoded = from_reader_default(file.as_slice()).unwrap();
+
+    assert_eq!(
+        part_color(&decoded),
+        Some(&Variant::Color3(Color3uint8::new(1, 2, 3).into()))
+    );
+}
+
+/// rbx_binary and rbx_xml should agree on the type of every property they
+/// decode, even for properties that are serialized as narrower types.
+#[test]
+fn widening_matches_rbx_xml() {
+    let dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+        InstanceBuilder::new("Part").with_property("Color", Color3uint8::new(1, 2, 3)),
+    ));
+
+    let mut binary = Vec::new();
+    to_writer_default(&mut binary, &dom, dom.root().children()).unwrap();
+    let from_binary = from_reader_default(binary.as_slice()).unwrap();
+
+    let mut xml = Vec::new();
+    rbx_xml::to_writer_default(&mut xml, &dom, dom.root().children()).unwrap();
+    let from_xml = rbx_xml::from_reader_default(xml.as_slice()).unwrap();
+
+    assert!(part_color(&from_binary).is_some());
+    assert_eq!(part_color(&from_binary), part_color(&from_xml));
+}
+
+#[test]
+fn vector3int16_is_widened() {
+    let tree = WeakDom::new(InstanceBuilder::new("Part"));
+    let mut file = Vec::new();
+    to_writer_default(&mut file, &tree, &[tree.root_ref()]).unwrap();
+
+    let (header, mut chunks) = split_file(&file);
+
+    // Old files store a Part's size in the legacy "size" property as a
+    // Vector3int16. There's only one type in this file, so its type ID is 0.
+    let mut data = Vec::new();
+    data.write_le_u32(0).unwrap();
+    data.write_string("size").unwrap();
+    data.write_u8(0x14).unwrap();
+    data.write_le_i16(4).unwrap();
+    data.write_le_i16(1).unwrap();
+    data.write_le_i16(2).unwrap();
+
+    let prnt = chunks
+        .iter()
+        .position(|chunk| &chunk.name == b"PRNT")
+        .unwrap();
+    chunks.insert(
+        prnt,
+        RawChunk {
+            name: *b"PROP",
+            reserved: 0,
+            data,
+        },
+    );
+
+    let dom = from_reader_default(build_file(&header, &chunks).as_slice()).unwrap();
+    let part = dom.get_by_ref(dom.root().children()[0]).unwrap();
+
+    assert_eq!(
+        part.properties.get("Size"),
+        Some(&Variant::Vector3(Vector3::new(4.0, 1.0, 2.0)))
+    );
+}
@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+use rbx_dom_weak::{
+    types::{CFrame, Color3, Matrix3, SharedString, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+use super::util::{build_file, split_file, RawChunk};
+use crate::{core::RbxWriteExt, encode, from_reader_default, peek};
+
+/// A place with a few services, one of which is full of parts.
+fn place(parts: usize) -> WeakDom {
+    let workspace = InstanceBuilder::new("Workspace").with_children((0..parts).map(|i| {
+        InstanceBuilder::new("Part")
+            .with_name(format!("Part{}", i))
+            .with_property(
+                "CFrame",
+                CFrame::new(Vector3::new(i as f32, 0.0, 0.0), Matrix3::identity()),
+            )
+            .with_property("Color", Color3::new(0.5, 0.25, i as f32 / parts as f32))
+            .with_property("Size", Vector3::new(4.0, 1.0, 2.0))
+    }));
+
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(workspace)
+            .with_child(InstanceBuilder::new("Lighting"))
+            .with_child(
+                InstanceBuilder::new("ReplicatedStorage")
+                    .with_child(InstanceBuilder::new("Folder").with_name("Assets")),
+            )
+            .with_child(
+                InstanceBuilder::new("Model")
+                    .with_name("Meshes")
+                    .with_property("Data", SharedString::new(vec![7; 4096])),
+            ),
+    )
+}
+
+fn encode_place(dom: &WeakDom) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    encode(dom, dom.root().children(), &mut buffer).unwrap();
+    buffer
+}
+
+/// The fastest of a few runs of `f`, to keep one slow run from failing the
+/// test.
+fn fastest<F: FnMut()>(mut f: F) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn peek_matches_decode() {
+    let _ = env_logger::try_init();
+
+    let dom = place(10);
+    let (header, mut chunks) = split_file(&encode_place(&dom));
+
+    // rbx_binary never writes metadata, so it's added to the file by hand.
+    let mut meta = Vec::new();
+    meta.write_le_u32(1).unwrap();
+    meta.write_string("ExplicitAutoJoints").unwrap();
+    meta.write_string("true").unwrap();
+    chunks.insert(
+        0,
+        RawChunk {
+            name: *b"META",
+            reserved: 0,
+            data: meta,
+        },
+    );
+    let file = build_file(&header, &chunks);
+
+    let info = peek(file.as_slice()).unwrap();
+    let decoded = from_reader_default(file.as_slice()).unwrap();
+
+    let top_level: Vec<_> = decoded
+        .root()
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = decoded.get_by_ref(*referent).unwrap();
+            (instance.class.clone(), instance.name.clone())
+        })
+        .collect();
+
+    assert_eq!(info.top_level(), top_level.as_slice());
+    assert_eq!(
+        info.top_level()[3],
+        ("Model".to_owned(), "Meshes".to_owned())
+    );
+    assert_eq!(
+        info.metadata()
+            .get("ExplicitAutoJoints")
+            .map(String::as_str),
+        Some("true")
+    );
+    assert_eq!(info.class_count(), 6);
+    assert_eq!(info.instance_count(), 15);
+}
+
+#[test]
+fn peek_is_faster_than_decode() {
+    let _ = env_logger::try_init();
+
+    let file = encode_place(&place(20_000));
+
+    let peek_time = fastest(|| {
+        peek(file.as_slice()).unwrap();
+    });
+    let decode_time = fastest(|| {
+        from_reader_default(file.as_slice()).unwrap();
+    });
+
+    assert!(
+        peek_time * 10 < decode_time,
+        "peeking took {:?}, but decoding took {:?}",
+        peek_time,
+        decode_time
+    );
+}
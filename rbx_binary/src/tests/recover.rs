@@ -0,0 +1,399 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{
+    from_reader, from_reader_with_summary,
+    tests::util::{build_file, find_prop_chunk, split_file, RawChunk},
+    DecodeOptions, Diagnostic, DiagnosticCode, Severity,
+};
+
+fn sample_tree() -> WeakDom {
+    WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+        InstanceBuilder::new("StringValue"),
+        InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("IntValue")),
+    ]))
+}
+
+/// Encodes the sample tree and splits it into its file header and chunks.
+fn encode_sample() -> (Vec<u8>, Vec<RawChunk>) {
+    let tree = sample_tree();
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, &tree, &[tree.root_ref()]).unwrap();
+
+    split_file(&file)
+}
+
+/// Finds the INST chunk that declares the given class.
+fn find_inst_chunk<'a>(chunks: &'a mut [RawChunk], class_name: &str) -> &'a mut RawChunk {
+    chunks
+        .iter_mut()
+        .filter(|chunk| &chunk.name == b"INST")
+        .find(|chunk| {
+            // INST chunks start with a u32 type ID, then the class name.
+            let len =
+                u32::from_le_bytes([chunk.data[4], chunk.data[5], chunk.data[6], chunk.data[7]]);
+            &chunk.data[8..8 + len as usize] == class_name.as_bytes()
+        })
+        .unwrap()
+}
+
+fn strict_options() -> DecodeOptions {
+    DecodeOptions::new()
+}
+
+fn recover_options() -> DecodeOptions {
+    DecodeOptions::new().recover(true)
+}
+
+/// A PROP chunk referring to a type that doesn't exist should only cost us
+/// that property.
+#[test]
+fn prop_chunk_with_bad_type_id() {
+    let (header, mut chunks) = encode_sample();
+
+    let prop = chunks
+        .iter_mut()
+        .find(|chunk| &chunk.name == b"PROP")
+        .unwrap();
+    prop.data[..4].copy_from_slice(&9999u32.to_le_bytes());
+
+    let file = build_file(&header, &chunks);
+
+    assert!(from_reader(file.as_slice(), strict_options()).is_err());
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+
+    assert!(summary.recovered());
+    assert_eq!(summary.diagnostics().len(), 1);
+    assert_eq!(summary.diagnostics()[0].chunk_name(), Some("PROP"));
+    assert_eq!(summary.instance_count(), 5);
+    assert_eq!(dom.root().children().len(), 1);
+}
+
+/// A chunk with an unreadable header forces the decoder to search for the next
+/// chunk. Everything that chunk declared is lost, but the rest of the file
+/// should still come through.
+#[test]
+fn inst_chunk_with_bad_header() {
+    let (header, mut chunks) = encode_sample();
+
+    find_inst_chunk(&mut chunks, "StringValue").reserved = 1;
+
+    let file = build_file(&header, &chunks);
+
+    assert!(from_reader(file.as_slice(), strict_options()).is_err());
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+
+    assert!(summary.recovered());
+    assert!(summary.diagnostics()[0].chunk_name().is_none());
+
+    // Both StringValue instances are gone, along with their properties and
+    // their entries in the PRNT chunk.
+    assert_eq!(summary.instance_count(), 3);
+
+    let root = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(root.class, "Folder");
+    assert_eq!(root.children().len(), 1);
+
+    let folder = dom.get_by_ref(root.children()[0]).unwrap();
+    assert_eq!(folder.class, "Folder");
+
+    let int_value = dom.get_by_ref(folder.children()[0]).unwrap();
+    assert_eq!(int_value.class, "IntValue");
+}
+
+/// A file that's cut off before its END chunk should keep everything it had.
+#[test]
+fn missing_end_chunk() {
+    let (header, mut chunks) = encode_sample();
+
+    let end = chunks.pop().unwrap();
+    assert_eq!(&end.name, b"END\0");
+
+    let file = build_file(&header, &chunks);
+
+    assert!(from_reader(file.as_slice(), strict_options()).is_err());
+
+    let (_dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+
+    assert!(summary.recovered());
+    assert_eq!(summary.diagnostics().len(), 1);
+    assert_eq!(summary.diagnostics()[0].byte_offset(), file.len() as u64);
+    assert_eq!(summary.instance_count(), 5);
+}
+
+/// Without a PRNT chunk, every instance ends up at the top level.
+#[test]
+fn missing_prnt_chunk() {
+    let (header, mut chunks) = encode_sample();
+
+    chunks.retain(|chunk| &chunk.name != b"PRNT");
+
+    let file = build_file(&header, &chunks);
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+
+    assert!(summary.recovered());
+    assert_eq!(summary.instance_count(), 5);
+    assert_eq!(dom.root().children().len(), 5);
+}
+
+/// Intact files shouldn't report anything.
+#[test]
+fn intact_file_is_not_recovered() {
+    let (header, chunks) = encode_sample();
+    let file = build_file(&header, &chunks);
+
+    let (_dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+
+    assert!(!summary.recovered());
+    assert!(summary.diagnostics().is_empty());
+    assert_eq!(summary.instance_count(), 5);
+}
+
+/// Builds a flat tree of `count` instances of the given class, named
+/// `Value0`, `Value1`, and so on, each with a `Value` property.
+fn value_tree<T: Into<Variant>>(class_name: &str, values: impl Iterator<Item = T>) -> WeakDom {
+    let mut root = InstanceBuilder::new("Folder");
+
+    for (i, value) in values.enumerate() {
+        root.add_child(
+            InstanceBuilder::new(class_name)
+                .with_name(format!("Value{}", i))
+                .with_property("Value", value),
+        );
+    }
+
+    WeakDom::new(root)
+}
+
+fn encode_children(tree: &WeakDom) -> Vec<u8> {
+    let mut file = Vec::new();
+    crate::to_writer_default(&mut file, tree, tree.root().children()).unwrap();
+    file
+}
+
+/// Maps each decoded instance's name to its `Value` property, if it has one.
+fn values_by_name(dom: &WeakDom) -> Vec<(String, Option<Variant>)> {
+    let mut values: Vec<_> = dom
+        .root()
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = dom.get_by_ref(*referent).unwrap();
+            (
+                instance.name.clone(),
+                instance.properties.get("Value").cloned(),
+            )
+        })
+        .collect();
+
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    values
+}
+
+/// A PROP chunk that's cut off partway through its values should keep the
+/// values that came before the cut.
+#[test]
+fn short_prop_chunk() {
+    let tree = value_tree("StringValue", (0..5).map(|i| format!("v{}", i)));
+    let intact = encode_children(&tree);
+    let (header, mut chunks) = split_file(&intact);
+
+    // Each value is a u32 length and two bytes of text. Cutting two bytes into
+    // the fourth value leaves three whole values.
+    let (prop, type_offset) = find_prop_chunk(&mut chunks, "Value");
+    prop.data.truncate(type_offset + 1 + 6 * 3 + 2);
+
+    let file = build_file(&header, &chunks);
+
+    let error = from_reader(file.as_slice(), strict_options()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The PROP chunk for StringValue.Value ended after 3 of its 5 values"
+    );
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+    assert!(summary.recovered());
+    assert_eq!(summary.property_count(), 3);
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].chunk_name(), Some("PROP"));
+    assert_eq!(
+        diagnostics[0].message(),
+        "The PROP chunk for StringValue.Value ended after 3 of its 5 values; \
+        2 instances were left without it"
+    );
+
+    let intact_values = values_by_name(&from_reader(intact.as_slice(), strict_options()).unwrap());
+    let salvaged_values = values_by_name(&dom);
+
+    assert_eq!(salvaged_values[..3], intact_values[..3]);
+    for (name, value) in &salvaged_values[3..] {
+        assert_eq!(*value, None, "{} should not have a value", name);
+    }
+}
+
+/// An interleaved array that's cut off can't be partly read, since reading a
+/// shorter array from what's left would give the wrong values. The property
+/// is left off of every instance instead.
+#[test]
+fn short_interleaved_prop_chunk() {
+    let tree = value_tree("IntValue", (1..=5).map(|i| i64::from(i * 1000 + 7)));
+    let (header, mut chunks) = split_file(&encode_children(&tree));
+
+    let (prop, _) = find_prop_chunk(&mut chunks, "Value");
+    let len = prop.data.len();
+    prop.data.truncate(len - 9);
+
+    let file = build_file(&header, &chunks);
+
+    let error = from_reader(file.as_slice(), strict_options()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The PROP chunk for IntValue.Value ended before all 5 of its values, which can't be \
+         partly read"
+    );
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+    assert!(summary.recovered());
+    assert_eq!(summary.property_count(), 0);
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code(), DiagnosticCode::TruncatedProperty);
+    assert_eq!(diagnostics[0].property(), Some("IntValue.Value"));
+    assert_eq!(
+        diagnostics[0].message(),
+        "The PROP chunk for IntValue.Value ended before all 5 of its values, which can't be \
+         partly read; every instance was left without it"
+    );
+
+    assert!(values_by_name(&dom)
+        .iter()
+        .all(|(_, value)| value.is_none()));
+}
+
+/// A complete interleaved array with fewer values than there are instances
+/// looks the same as one that was cut off, so it's dropped too.
+#[test]
+fn prop_chunk_with_fewer_interleaved_values() {
+    let tree = value_tree("IntValue", (1..=5).map(i64::from));
+    let intact = encode_children(&tree);
+    let (header, mut chunks) = split_file(&intact);
+
+    let short_tree = value_tree("IntValue", (1..=2).map(i64::from));
+    let (_, mut short_chunks) = split_file(&encode_children(&short_tree));
+
+    let (short_prop, _) = find_prop_chunk(&mut short_chunks, "Value");
+    let short_data = short_prop.data.clone();
+    find_prop_chunk(&mut chunks, "Value").0.data = short_data;
+
+    let file = build_file(&header, &chunks);
+
+    assert!(from_reader(file.as_slice(), strict_options()).is_err());
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+    assert_eq!(summary.diagnostics().len(), 1);
+    assert!(values_by_name(&dom)
+        .iter()
+        .all(|(_, value)| value.is_none()));
+}
+
+/// A PROP chunk with no values at all leaves every instance without the
+/// property.
+#[test]
+fn empty_prop_chunk() {
+    let tree = value_tree("StringValue", (0..3).map(|i| format!("v{}", i)));
+    let (header, mut chunks) = split_file(&encode_children(&tree));
+
+    let (prop, type_offset) = find_prop_chunk(&mut chunks, "Value");
+    prop.data.truncate(type_offset + 1);
+
+    let file = build_file(&header, &chunks);
+
+    let (dom, summary) = from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+    assert_eq!(summary.instance_count(), 3);
+    assert_eq!(summary.property_count(), 0);
+    assert!(values_by_name(&dom)
+        .iter()
+        .all(|(_, value)| value.is_none()));
+}
+
+/// One damaged file for each of several kinds of diagnostic.
+fn damaged_files() -> Vec<Vec<u8>> {
+    let mut files = Vec::new();
+
+    let (header, mut chunks) = encode_sample();
+    let prop = chunks
+        .iter_mut()
+        .find(|chunk| &chunk.name == b"PROP")
+        .unwrap();
+    prop.data[..4].copy_from_slice(&9999u32.to_le_bytes());
+    files.push(build_file(&header, &chunks));
+
+    let (header, mut chunks) = encode_sample();
+    find_inst_chunk(&mut chunks, "IntValue").reserved = 1;
+    files.push(build_file(&header, &chunks));
+
+    let (header, mut chunks) = encode_sample();
+    chunks.pop();
+    files.push(build_file(&header, &chunks));
+
+    let (header, mut chunks) = encode_sample();
+    chunks.retain(|chunk| &chunk.name != b"PRNT");
+    files.push(build_file(&header, &chunks));
+
+    let tree = value_tree("StringValue", (0..5).map(|i| format!("v{}", i)));
+    let (header, mut chunks) = split_file(&encode_children(&tree));
+    let (prop, type_offset) = find_prop_chunk(&mut chunks, "Value");
+    prop.data.truncate(type_offset + 1 + 6 * 3 + 2);
+    files.push(build_file(&header, &chunks));
+
+    files
+}
+
+/// The first diagnostic from decoding each of the given files.
+fn first_diagnostics(files: &[Vec<u8>]) -> Vec<Diagnostic> {
+    files
+        .iter()
+        .map(|file| {
+            let (_dom, summary) =
+                from_reader_with_summary(file.as_slice(), recover_options()).unwrap();
+            summary.diagnostics()[0].clone()
+        })
+        .collect()
+}
+
+#[test]
+fn diagnostic_codes() {
+    let diagnostics = first_diagnostics(&damaged_files());
+
+    let codes: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.code().as_str(), diagnostic.severity()))
+        .collect();
+
+    assert_eq!(
+        codes,
+        [
+            ("RBXD0001", Severity::Error),
+            ("RBXD0002", Severity::Error),
+            ("RBXD0003", Severity::Warning),
+            ("RBXD0005", Severity::Warning),
+            ("RBXD0006", Severity::Error),
+        ]
+    );
+    assert_eq!(diagnostics[0].code(), DiagnosticCode::DamagedChunk);
+    assert_eq!(diagnostics[4].property(), Some("StringValue.Value"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn diagnostics_json() {
+    let diagnostics = first_diagnostics(&damaged_files());
+
+    insta::assert_json_snapshot!(diagnostics);
+}
@@ -0,0 +1,118 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use rbx_dom_weak::{
+    types::{Ref, SeededRefProvider, SharedString, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+use crate::{decode_into, from_reader, to_writer_default, DecodeErrorKind, DecodeOptions};
+
+/// Encodes a model with a Ref property and a SharedString, both of which
+/// depend on how instances are identified.
+fn encode_sample() -> Vec<u8> {
+    let part_a = InstanceBuilder::new("Part").with_name("A");
+    let part_b = InstanceBuilder::new("Part")
+        .with_name("B")
+        .with_property("Size", Vector3::new(4.0, 1.0, 2.0));
+    let weld = InstanceBuilder::new("Weld")
+        .with_property("Part0", part_a.referent())
+        .with_property("Part1", part_b.referent());
+    let mesh = InstanceBuilder::new("MeshPart")
+        .with_property("PhysicalConfigData", SharedString::new(b"physics".to_vec()));
+
+    let tree =
+        WeakDom::new(InstanceBuilder::new("Model").with_children(vec![part_a, part_b, weld, mesh]));
+
+    let mut buffer = Vec::new();
+    to_writer_default(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    buffer
+}
+
+/// Every Ref in the dom, parents first.
+fn refs(dom: &WeakDom) -> Vec<Ref> {
+    let mut refs = Vec::new();
+    let mut to_visit = vec![dom.root_ref()];
+
+    while let Some(referent) = to_visit.pop() {
+        refs.push(referent);
+        to_visit.extend(dom.get_by_ref(referent).unwrap().children().iter().rev());
+    }
+
+    refs
+}
+
+/// Decodes the file and encodes it again, returning the decoded dom and a
+/// hash of the new file.
+fn round_trip(file: &[u8], options: DecodeOptions) -> (WeakDom, u64) {
+    let dom = from_reader(file, options).unwrap();
+
+    let mut encoded = Vec::new();
+    to_writer_default(&mut encoded, &dom, dom.root().children()).unwrap();
+
+    let mut hasher = DefaultHasher::new();
+    encoded.hash(&mut hasher);
+
+    (dom, hasher.finish())
+}
+
+#[test]
+fn seeded_round_trips_match() {
+    let file = encode_sample();
+    let options = DecodeOptions::new().ref_provider(SeededRefProvider::new(1818));
+
+    let (first_dom, first_hash) = round_trip(&file, options.clone());
+    let (second_dom, second_hash) = round_trip(&file, options);
+
+    assert_eq!(first_hash, second_hash);
+    assert_eq!(refs(&first_dom), refs(&second_dom));
+
+    // Ref properties point at the same Refs too.
+    let weld_ref = first_dom
+        .get_by_ref(first_dom.root().children()[0])
+        .unwrap()
+        .children()[2];
+    let part0: Ref = first_dom.read_property(weld_ref, "Part0").unwrap();
+    let second_part0: Ref = second_dom.read_property(weld_ref, "Part0").unwrap();
+    assert_eq!(part0, second_part0);
+}
+
+#[test]
+fn seeds_and_defaults_differ() {
+    let file = encode_sample();
+
+    let seeded = |seed| {
+        from_reader(
+            file.as_slice(),
+            DecodeOptions::new().ref_provider(SeededRefProvider::new(seed)),
+        )
+        .unwrap()
+    };
+
+    assert_ne!(refs(&seeded(1)), refs(&seeded(2)));
+
+    let random = || from_reader(file.as_slice(), DecodeOptions::new()).unwrap();
+    assert_ne!(refs(&random()), refs(&random()));
+}
+
+/// Clones of a seeded provider repeat its Refs, so decoding into the same dom
+/// with both is an error instead of replacing the first copy's instances.
+#[test]
+fn cloned_seeds_collide_in_one_dom() {
+    let file = encode_sample();
+    let options = DecodeOptions::new().ref_provider(SeededRefProvider::new(7));
+
+    let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+    let root = dom.root_ref();
+    let first = decode_into(&mut dom, root, file.as_slice(), options.clone()).unwrap();
+    let before = refs(&dom);
+
+    let err = decode_into(&mut dom, root, file.as_slice(), options).unwrap_err();
+    assert_eq!(err.kind(), DecodeErrorKind::DuplicateRef);
+
+    // The dom was left the way the first decode left it.
+    assert_eq!(refs(&dom), before);
+    assert_eq!(dom.root().children(), first.as_slice());
+}
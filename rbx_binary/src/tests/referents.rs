@@ -0,0 +1,63 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{
+    core::{BinaryReferent, RbxReadExt, RbxWriteExt},
+    from_reader_default,
+    tests::util::{build_file, split_file},
+    to_writer_default,
+};
+
+#[test]
+fn referent_arrays_round_trip() {
+    let referents: Vec<_> = [-1, 0, 3, 1, 2]
+        .iter()
+        .copied()
+        .map(BinaryReferent::new)
+        .collect();
+
+    let mut buffer = Vec::new();
+    buffer
+        .write_referent_array(referents.iter().copied())
+        .unwrap();
+
+    let mut decoded = vec![BinaryReferent::NONE; referents.len()];
+    buffer.as_slice().read_referent_array(&mut decoded).unwrap();
+
+    assert_eq!(decoded, referents);
+    assert!(decoded[0].is_none());
+    assert_eq!(decoded[2].get(), 3);
+    assert_eq!(decoded[2].to_string(), "3");
+}
+
+/// A PRNT chunk that gives an instance a parent that was never declared is
+/// reported with the referent from the file.
+#[test]
+fn undeclared_parent() {
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Part").with_name("Floor")),
+    );
+
+    let mut file = Vec::new();
+    to_writer_default(&mut file, &dom, &[dom.root_ref()]).unwrap();
+    let (header, mut chunks) = split_file(&file);
+
+    let prnt = chunks.iter_mut().find(|c| &c.name == b"PRNT").unwrap();
+    let mut reader = &prnt.data[5..];
+    let mut subjects = vec![BinaryReferent::NONE; 2];
+    let mut parents = vec![BinaryReferent::NONE; 2];
+    reader.read_referent_array(&mut subjects).unwrap();
+    reader.read_referent_array(&mut parents).unwrap();
+
+    let child = parents.iter().position(|parent| !parent.is_none()).unwrap();
+    parents[child] = BinaryReferent::new(7);
+
+    let mut data = prnt.data[..5].to_vec();
+    data.write_referent_array(subjects.into_iter()).unwrap();
+    data.write_referent_array(parents.into_iter()).unwrap();
+    prnt.data = data;
+
+    let error = from_reader_default(build_file(&header, &chunks).as_slice()).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("File referred to instance 7, which was not declared"));
+}
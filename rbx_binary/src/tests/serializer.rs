@@ -3,7 +3,10 @@ use rbx_dom_weak::{
     InstanceBuilder, WeakDom,
 };
 
-use crate::{encode, text_deserializer::DecodedModel};
+use crate::{
+    encode, from_reader_default, tests::util::split_chunks, text_deserializer::DecodedModel,
+    to_writer, EncodeOptions,
+};
 
 /// A basic test to make sure we can serialize the simplest instance: a Folder.
 #[test]
@@ -153,3 +156,54 @@ fn part_color() {
     let decoded = DecodedModel::from_reader(buf.as_slice());
     insta::assert_yaml_snapshot!(decoded);
 }
+
+/// Returns the names of the properties written to each PROP chunk in a file.
+fn prop_chunk_names(file: &[u8]) -> Vec<String> {
+    split_chunks(file)
+        .into_iter()
+        .filter(|chunk| &chunk.name == b"PROP")
+        .map(|chunk| {
+            // PROP chunks start with a u32 type ID, then the property name.
+            let data = &chunk.data;
+            let len = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+            String::from_utf8(data[8..8 + len].to_vec()).unwrap()
+        })
+        .collect()
+}
+
+/// Ensures that classes whose instances are all named after their ClassName
+/// don't get a Name PROP chunk with `omit_default_names`, and that decoding
+/// gives those instances back the same name.
+#[test]
+fn omit_default_names() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Folder"),
+        InstanceBuilder::new("StringValue").with_name("Custom"),
+        InstanceBuilder::new("StringValue"),
+    ]));
+
+    let mut buffer = Vec::new();
+    let options = EncodeOptions::new().omit_default_names(true);
+    to_writer(&mut buffer, &tree, &[tree.root_ref()], options).expect("failed to encode model");
+
+    let names = prop_chunk_names(&buffer);
+    assert_eq!(names.iter().filter(|name| *name == "Name").count(), 1);
+
+    let decoded = from_reader_default(buffer.as_slice()).expect("failed to decode model");
+    let root = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    assert_eq!(root.name, "Folder");
+
+    let child_names: Vec<_> = root
+        .children()
+        .iter()
+        .map(|id| decoded.get_by_ref(*id).unwrap().name.as_str())
+        .collect();
+    assert_eq!(child_names, ["Folder", "Custom", "StringValue"]);
+
+    // Without the option, every class gets a Name chunk.
+    let mut buffer = Vec::new();
+    encode(&tree, &[tree.root_ref()], &mut buffer).expect("failed to encode model");
+
+    let names = prop_chunk_names(&buffer);
+    assert_eq!(names.iter().filter(|name| *name == "Name").count(), 2);
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{chunk::Chunk, from_reader_with_summary, to_writer_default, DecodeOptions};
+
+fn build_file() -> Vec<u8> {
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_name("Vehicle")
+            .with_child(InstanceBuilder::new("Part").with_name("Chassis"))
+            .with_child(InstanceBuilder::new("Part").with_name("Body"))
+            .with_child(InstanceBuilder::new("Script").with_name("Drive")),
+    );
+
+    let mut file = Vec::new();
+    to_writer_default(&mut file, &dom, &[dom.root_ref()]).unwrap();
+    file
+}
+
+/// The class name that an INST chunk describes.
+fn inst_class_name(chunk: &Chunk) -> &str {
+    assert_eq!(&chunk.name, b"INST");
+
+    // INST chunks start with a u32 type ID, then the class name.
+    let data = &chunk.data;
+    let len = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    std::str::from_utf8(&data[8..8 + len]).unwrap()
+}
+
+/// The chunk offset and index of every decoded instance, keyed by name.
+fn locations(file: &[u8], options: DecodeOptions) -> BTreeMap<String, (u64, usize)> {
+    let (dom, summary) = from_reader_with_summary(file, options.source_locations(true)).unwrap();
+    assert_eq!(summary.source_locations().len(), 4);
+
+    dom.descendants(dom.root_ref())
+        .skip(1)
+        .map(|instance| {
+            let location = summary.source_locations()[&instance.referent()];
+            let offset = location.chunk_offset();
+
+            let chunk = Chunk::decode(&file[offset as usize..]).unwrap();
+            assert_eq!(inst_class_name(&chunk), instance.class);
+
+            (instance.name.clone(), (offset, location.index()))
+        })
+        .collect()
+}
+
+#[test]
+fn inst_chunk_locations() {
+    let _ = env_logger::try_init();
+
+    let file = build_file();
+    let locations = locations(&file, DecodeOptions::new());
+
+    let (chassis_offset, chassis_index) = locations["Chassis"];
+    let (body_offset, body_index) = locations["Body"];
+    assert_eq!(chassis_offset, body_offset);
+    assert_ne!(chassis_index, body_index);
+
+    assert_eq!(locations["Vehicle"].1, 0);
+    assert_eq!(locations["Drive"].1, 0);
+    assert_ne!(locations["Vehicle"].0, locations["Drive"].0);
+}
+
+#[test]
+fn same_locations_when_recovering() {
+    let _ = env_logger::try_init();
+
+    let file = build_file();
+
+    assert_eq!(
+        locations(&file, DecodeOptions::new()),
+        locations(&file, DecodeOptions::new().recover(true))
+    );
+}
+
+#[test]
+fn off_by_default() {
+    let _ = env_logger::try_init();
+
+    let file = build_file();
+    let (_dom, summary) = from_reader_with_summary(file.as_slice(), DecodeOptions::new()).unwrap();
+
+    assert!(summary.source_locations().is_empty());
+}
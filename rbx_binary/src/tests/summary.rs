@@ -0,0 +1,39 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader_with_summary, to_writer_with_summary, DecodeOptions, EncodeOptions};
+
+/// Ensures that the byte counts reported for encoding and decoding the same
+/// file both match the size of that file.
+#[test]
+fn byte_counts_match_file_size() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+        InstanceBuilder::new("StringValue"),
+        InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("IntValue")),
+    ]));
+
+    let mut buffer = Vec::new();
+    let encode_summary = to_writer_with_summary(
+        &mut buffer,
+        &tree,
+        &[tree.root_ref()],
+        EncodeOptions::default(),
+    )
+    .expect("failed to encode model");
+
+    assert_eq!(encode_summary.bytes_written(), buffer.len() as u64);
+    assert_eq!(encode_summary.instance_count(), 5);
+
+    let (decoded, decode_summary) =
+        from_reader_with_summary(buffer.as_slice(), DecodeOptions::default())
+            .expect("failed to decode model");
+
+    assert_eq!(decode_summary.bytes_read(), buffer.len() as u64);
+    assert_eq!(decode_summary.instance_count(), 5);
+    assert_eq!(decoded.root().children().len(), 1);
+
+    // Both StringValue instances are written with a Value property because
+    // one of them has it set. No IntValue has a Value set, so none is written.
+    assert_eq!(encode_summary.property_count(), 2);
+    assert_eq!(decode_summary.property_count(), 2);
+}
@@ -0,0 +1,28 @@
+use std::convert::TryFrom;
+
+use crate::{supported_types, types::Type};
+
+/// Every binary type is decoded into a type that can be written back out.
+#[test]
+fn decoded_types_are_supported() {
+    for id in 0..=u8::MAX {
+        if let Ok(ty) = Type::try_from(id) {
+            let rbx_type = ty.to_default_rbx_type().unwrap();
+
+            assert!(supported_types().contains(&rbx_type), "{:?}", rbx_type);
+            assert!(Type::from_rbx_type(rbx_type).is_some());
+        }
+    }
+}
+
+#[test]
+fn unsupported_properties() {
+    let database = rbx_reflection_database::get();
+    let unsupported: Vec<_> = database
+        .unsupported_properties(supported_types())
+        .into_iter()
+        .map(|(class, property)| format!("{}.{}", class, property))
+        .collect();
+
+    insta::assert_snapshot!(unsupported.join("\n"));
+}
@@ -0,0 +1,81 @@
+use std::{sync::Arc, thread};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+use crate::{from_reader_default, to_writer_default};
+
+#[allow(dead_code)]
+fn types_are_send_and_sync() {
+    fn trait_test<T: Send + Sync>() {}
+
+    trait_test::<crate::DecodeError>();
+    trait_test::<crate::EncodeError>();
+    trait_test::<crate::DecodeSummary>();
+    trait_test::<crate::EncodeSummary>();
+}
+
+fn make_file(instance_count: usize) -> Vec<u8> {
+    let children = (0..instance_count).map(|i| {
+        InstanceBuilder::new("StringValue")
+            .with_name(format!("Value {}", i))
+            .with_property("Value", "Hello, threads!")
+    });
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(children));
+
+    let mut buffer = Vec::new();
+    to_writer_default(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    buffer
+}
+
+/// Decodes several files at once. This is also useful to run under a thread
+/// sanitizer.
+#[test]
+fn decode_concurrently() {
+    let files: Vec<_> = (1..=4).map(|i| make_file(i * 25)).collect();
+
+    let handles: Vec<_> = files
+        .into_iter()
+        .map(|file| {
+            thread::spawn(move || {
+                let dom = from_reader_default(file.as_slice()).unwrap();
+                let root = dom.get_by_ref(dom.root().children()[0]).unwrap();
+                root.children().len()
+            })
+        })
+        .collect();
+
+    let counts: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(counts, [25, 50, 75, 100]);
+}
+
+#[test]
+fn read_dom_from_many_threads() {
+    let dom = Arc::new(from_reader_default(make_file(100).as_slice()).unwrap());
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let dom = Arc::clone(&dom);
+
+            thread::spawn(move || {
+                let root = dom.get_by_ref(dom.root().children()[0]).unwrap();
+
+                root.children()
+                    .iter()
+                    .filter(|referent| {
+                        let instance = dom.get_by_ref(**referent).unwrap();
+                        instance.name.starts_with("Value ")
+                    })
+                    .count()
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 100);
+    }
+}
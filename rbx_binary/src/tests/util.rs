@@ -2,7 +2,14 @@ use std::{fs, path::Path};
 
 use rbx_dom_weak::DomViewer;
 
-use crate::{deserializer::decode, encode, text_deserializer::DecodedModel};
+use crate::{
+    chunk::Chunk,
+    core::{RbxWriteExt, FILE_MAGIC_HEADER},
+    deserializer::decode,
+    encode,
+    text_deserializer::DecodedModel,
+    DecodeOptions,
+};
 
 /// Run a basic gauntlet of tests to verify that the serializer and deserializer
 /// can handle this model correctly.
@@ -28,7 +35,7 @@ pub fn run_model_base_suite(model_path: impl AsRef<Path>) {
 
     // Decode the test file and snapshot a stable version of the resulting tree.
     // This should properly test the deserializer.
-    let decoded = decode(contents.as_slice()).unwrap();
+    let decoded = decode(contents.as_slice(), DecodeOptions::default()).unwrap();
     let decoded_viewed = DomViewer::new().view_children(&decoded);
     insta::assert_yaml_snapshot!(format!("{}__decoded", model_stem), decoded_viewed);
 
@@ -51,5 +58,87 @@ pub fn run_model_base_suite(model_path: impl AsRef<Path>) {
     // We don't make any assertions about the result right now, as our format
     // support is still lacking. In the future, we should assert that this is
     // the same as the original decoding of the test file.
-    decode(encoded.as_slice()).unwrap();
+    decode(encoded.as_slice(), DecodeOptions::default()).unwrap();
+}
+
+/// The length of the header at the start of every binary file.
+const FILE_HEADER_LEN: usize = 32;
+
+/// A chunk that can be changed by tests and written back out by `build_file`.
+#[derive(Clone)]
+pub struct RawChunk {
+    pub name: [u8; 4],
+    pub reserved: u32,
+    pub data: Vec<u8>,
+}
+
+/// Splits an encoded file into its decompressed chunks, skipping over the file
+/// header.
+pub fn split_chunks(file: &[u8]) -> Vec<Chunk> {
+    assert!(file.starts_with(FILE_MAGIC_HEADER));
+
+    let mut input = &file[FILE_HEADER_LEN..];
+    let mut chunks = Vec::new();
+
+    while !input.is_empty() {
+        chunks.push(Chunk::decode(&mut input).unwrap());
+    }
+
+    chunks
+}
+
+/// Splits an encoded file into its header and its chunks.
+///
+/// The chunks are decompressed so that tests can easily change their contents.
+pub fn split_file(file: &[u8]) -> (Vec<u8>, Vec<RawChunk>) {
+    let header = file[..FILE_HEADER_LEN].to_vec();
+    let chunks = split_chunks(file)
+        .into_iter()
+        .map(|chunk| RawChunk {
+            name: chunk.name,
+            reserved: 0,
+            data: chunk.data,
+        })
+        .collect();
+
+    (header, chunks)
+}
+
+/// Finds the PROP chunk for the property with the given name, returning it and
+/// the offset of its type byte.
+pub fn find_prop_chunk<'a>(
+    chunks: &'a mut [RawChunk],
+    prop_name: &str,
+) -> (&'a mut RawChunk, usize) {
+    chunks
+        .iter_mut()
+        .filter(|chunk| &chunk.name == b"PROP")
+        .find_map(|chunk| {
+            // PROP chunks start with a u32 type ID, then the property name.
+            let len =
+                u32::from_le_bytes([chunk.data[4], chunk.data[5], chunk.data[6], chunk.data[7]]);
+            let type_offset = 8 + len as usize;
+
+            if &chunk.data[8..type_offset] == prop_name.as_bytes() {
+                Some((chunk, type_offset))
+            } else {
+                None
+            }
+        })
+        .unwrap()
+}
+
+/// Writes a file out of a header and chunks, leaving the chunks uncompressed.
+pub fn build_file(header: &[u8], chunks: &[RawChunk]) -> Vec<u8> {
+    let mut file = header.to_vec();
+
+    for chunk in chunks {
+        file.extend_from_slice(&chunk.name);
+        file.write_le_u32(0).unwrap();
+        file.write_le_u32(chunk.data.len() as u32).unwrap();
+        file.write_le_u32(chunk.reserved).unwrap();
+        file.extend_from_slice(&chunk.data);
+    }
+
+    file
 }
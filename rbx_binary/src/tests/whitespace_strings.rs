@@ -0,0 +1,166 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{from_reader_default, to_writer_default};
+
+static EDGES: &[&str] = &["", " ", "  ", "\t", "\n", "\r", "\r\n", " \r\n\t"];
+
+static MIDDLES: &[&str] = &[
+    "",
+    " ",
+    "a",
+    "a b",
+    "line\nline",
+    "line\r\nline",
+    "line\rline",
+    "mixed\r\n\n\r",
+];
+
+/// Every combination of leading whitespace, contents, and trailing
+/// whitespace, which covers empty and whitespace-only strings too.
+fn strings() -> Vec<String> {
+    let mut strings = Vec::new();
+
+    for start in EDGES {
+        for middle in MIDDLES {
+            for end in EDGES {
+                strings.push(format!("{}{}{}", start, middle, end));
+            }
+        }
+    }
+
+    strings
+}
+
+/// A folder of StringValues whose Name and Value are both set to each string.
+fn build_dom() -> WeakDom {
+    let values = strings().into_iter().map(|string| {
+        InstanceBuilder::new("StringValue")
+            .with_name(string.clone())
+            .with_property("Value", string)
+    });
+
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Folder").with_children(values)),
+    )
+}
+
+fn names_and_values(dom: &WeakDom) -> Vec<(String, String)> {
+    let folder = dom.get_by_ref(dom.root().children()[0]).unwrap();
+
+    folder
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = dom.get_by_ref(*referent).unwrap();
+            let value = match instance.properties.get("Value") {
+                Some(Variant::String(value)) => value.to_string(),
+                other => panic!("expected a String, got {:?}", other),
+            };
+
+            (instance.name.clone(), value)
+        })
+        .collect()
+}
+
+fn through_binary(dom: &WeakDom) -> WeakDom {
+    let mut buffer = Vec::new();
+    to_writer_default(&mut buffer, dom, dom.root().children()).unwrap();
+    from_reader_default(buffer.as_slice()).unwrap()
+}
+
+fn through_xml(dom: &WeakDom) -> WeakDom {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, dom, dom.root().children()).unwrap();
+    rbx_xml::from_reader_default(buffer.as_slice()).unwrap()
+}
+
+fn assert_same(expected: &[(String, String)], actual: &WeakDom, path: &str) {
+    let actual = names_and_values(actual);
+    assert_eq!(expected.len(), actual.len(), "{}", path);
+
+    for (expected, actual) in expected.iter().zip(&actual) {
+        assert_eq!(
+            expected.0.as_bytes(),
+            actual.0.as_bytes(),
+            "Name changed going {}",
+            path
+        );
+        assert_eq!(
+            expected.1.as_bytes(),
+            actual.1.as_bytes(),
+            "Value changed going {}",
+            path
+        );
+    }
+}
+
+#[test]
+fn round_trips_preserve_bytes() {
+    let _ = env_logger::try_init();
+
+    let dom = build_dom();
+    let expected = names_and_values(&dom);
+
+    assert_same(&expected, &through_binary(&dom), "through binary");
+    assert_same(&expected, &through_xml(&dom), "through XML");
+}
+
+#[test]
+fn cross_conversion_preserves_bytes() {
+    let _ = env_logger::try_init();
+
+    let dom = build_dom();
+    let expected = names_and_values(&dom);
+
+    let binary_then_xml = through_xml(&through_binary(&dom));
+    assert_same(&expected, &binary_then_xml, "binary then XML");
+
+    let xml_then_binary = through_binary(&through_xml(&dom));
+    assert_same(&expected, &xml_then_binary, "XML then binary");
+}
+
+/// Script sources, which are ProtectedStrings rather than Strings, come out of
+/// both formats and every conversion between them with the bytes they went in
+/// with.
+#[test]
+fn script_sources_agree() {
+    let _ = env_logger::try_init();
+
+    let scripts = strings()
+        .into_iter()
+        .map(|source| InstanceBuilder::new("ModuleScript").with_property("Source", source));
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Folder").with_children(scripts)),
+    );
+
+    let sources = |dom: &WeakDom| -> Vec<Vec<u8>> {
+        let folder = dom.get_by_ref(dom.root().children()[0]).unwrap();
+
+        folder
+            .children()
+            .iter()
+            .map(
+                |referent| match dom.get_by_ref(*referent).unwrap().properties.get("Source") {
+                    Some(Variant::String(source)) => source.as_bytes().to_vec(),
+                    other => panic!("expected a String, got {:?}", other),
+                },
+            )
+            .collect()
+    };
+    let expected = sources(&dom);
+
+    for (path, converted) in &[
+        ("through binary", through_binary(&dom)),
+        ("through XML", through_xml(&dom)),
+        ("binary then XML", through_xml(&through_binary(&dom))),
+        ("XML then binary", through_binary(&through_xml(&dom))),
+    ] {
+        assert!(
+            expected == sources(converted),
+            "Source changed going {}",
+            path
+        );
+    }
+}
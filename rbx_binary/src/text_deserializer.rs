@@ -15,8 +15,11 @@ use rbx_dom_weak::types::{
 use serde::{ser::SerializeSeq, Serialize, Serializer};
 
 use crate::{
-    chunk::Chunk, core::RbxReadExt, deserializer::special_case_to_rotation,
-    deserializer::FileHeader, types::Type,
+    chunk::Chunk,
+    core::{BinaryReferent, RbxReadExt},
+    deserializer::special_case_to_rotation,
+    deserializer::FileHeader,
+    types::Type,
 };
 
 #[derive(Debug, Serialize)]
@@ -120,8 +123,7 @@ fn decode_inst_chunk<R: Read>(
 
     count_by_type_id.insert(type_id, num_instances as usize);
 
-    let mut referents = vec![0; num_instances as usize];
-    reader.read_referent_array(&mut referents).unwrap();
+    let referents = read_referents(&mut reader, num_instances as usize);
 
     let mut remaining = Vec::new();
     reader.read_to_end(&mut remaining).unwrap();
@@ -169,21 +171,23 @@ fn decode_prop_chunk<R: Read>(
     }
 }
 
+/// Reads an array of referents, keeping them as plain numbers so that they're
+/// written out that way.
+fn read_referents<R: Read>(reader: &mut R, count: usize) -> Vec<i32> {
+    let mut referents = vec![BinaryReferent::NONE; count];
+    reader.read_referent_array(&mut referents).unwrap();
+
+    referents.into_iter().map(BinaryReferent::get).collect()
+}
+
 fn decode_prnt_chunk<R: Read>(mut reader: R) -> DecodedChunk {
     let version = reader.read_u8().unwrap();
     let num_referents = reader.read_le_u32().unwrap();
 
-    let mut subjects = vec![0; num_referents as usize];
-    let mut parents = vec![0; num_referents as usize];
-
-    reader.read_referent_array(&mut subjects).unwrap();
-    reader.read_referent_array(&mut parents).unwrap();
+    let subjects = read_referents(&mut reader, num_referents as usize);
+    let parents = read_referents(&mut reader, num_referents as usize);
 
-    let links = subjects
-        .iter()
-        .copied()
-        .zip(parents.iter().copied())
-        .collect();
+    let links = subjects.into_iter().zip(parents).collect();
 
     let mut remaining = Vec::new();
     reader.read_to_end(&mut remaining).unwrap();
@@ -366,23 +370,12 @@ impl DecodedValues {
                 for rotation in rotations.iter_mut() {
                     let id = reader.read_u8().unwrap();
                     if id == 0 {
-                        *rotation = Matrix3::new(
-                            Vector3::new(
-                                reader.read_le_f32().unwrap(),
-                                reader.read_le_f32().unwrap(),
-                                reader.read_le_f32().unwrap(),
-                            ),
-                            Vector3::new(
-                                reader.read_le_f32().unwrap(),
-                                reader.read_le_f32().unwrap(),
-                                reader.read_le_f32().unwrap(),
-                            ),
-                            Vector3::new(
-                                reader.read_le_f32().unwrap(),
-                                reader.read_le_f32().unwrap(),
-                                reader.read_le_f32().unwrap(),
-                            ),
-                        );
+                        let mut components = [0.0; 9];
+                        for component in &mut components {
+                            *component = reader.read_le_f32().unwrap();
+                        }
+
+                        *rotation = Matrix3::from_components(&components);
                     } else {
                         *rotation = special_case_to_rotation(id).unwrap();
                     }
@@ -414,12 +407,7 @@ impl DecodedValues {
 
                 Some(DecodedValues::Enum(values))
             }
-            Type::Ref => {
-                let mut refs = vec![0; prop_count];
-                reader.read_referent_array(&mut refs).unwrap();
-
-                Some(DecodedValues::Ref(refs))
-            }
+            Type::Ref => Some(DecodedValues::Ref(read_referents(&mut reader, prop_count))),
             Type::Color3 => {
                 let mut r = vec![0.0; prop_count];
                 let mut g = vec![0.0; prop_count];
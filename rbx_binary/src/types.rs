@@ -40,42 +40,77 @@ pub enum Type {
     SharedString = 0x1C,
 }
 
-impl Type {
-    pub fn from_rbx_type(rbx_type: VariantType) -> Option<Type> {
-        Some(match rbx_type {
-            // These types all serialize the same way in the binary format.
-            VariantType::String => Type::String,
-            VariantType::BinaryString => Type::String,
-            VariantType::Content => Type::String,
+/// Generates `Type::from_rbx_type` and the list of every `VariantType` that
+/// it maps to a binary type, so that the two can't disagree.
+macro_rules! rbx_type_mappings {
+    { $($rbx_type: ident => $binary_type: ident,)* } => {
+        impl Type {
+            pub fn from_rbx_type(rbx_type: VariantType) -> Option<Type> {
+                Some(match rbx_type {
+                    $(VariantType::$rbx_type => Type::$binary_type,)*
+                    _ => return None,
+                })
+            }
+        }
+
+        /// Every type of value that can be written to a binary file.
+        pub const SUPPORTED_TYPES: &[VariantType] = &[$(VariantType::$rbx_type,)*];
+    };
+}
 
-            VariantType::Bool => Type::Bool,
-            VariantType::Int32 => Type::Int32,
-            VariantType::Float32 => Type::Float32,
-            VariantType::Float64 => Type::Float64,
-            VariantType::UDim => Type::UDim,
-            VariantType::UDim2 => Type::UDim2,
-            VariantType::Ray => Type::Ray,
-            VariantType::Faces => Type::Faces,
-            VariantType::Axes => Type::Axes,
-            VariantType::BrickColor => Type::BrickColor,
-            VariantType::Color3 => Type::Color3,
-            VariantType::Vector2 => Type::Vector2,
-            VariantType::Vector3 => Type::Vector3,
-            VariantType::CFrame => Type::CFrame,
-            VariantType::Enum => Type::Enum,
-            VariantType::Ref => Type::Ref,
-            VariantType::Vector3int16 => Type::Vector3int16,
-            VariantType::NumberSequence => Type::NumberSequence,
-            VariantType::ColorSequence => Type::ColorSequence,
-            VariantType::NumberRange => Type::NumberRange,
-            VariantType::Rect => Type::Rect,
-            VariantType::PhysicalProperties => Type::PhysicalProperties,
-            VariantType::Color3uint8 => Type::Color3uint8,
-            VariantType::Int64 => Type::Int64,
-            VariantType::SharedString => Type::SharedString,
+rbx_type_mappings! {
+    // These types all serialize the same way in the binary format.
+    String => String,
+    BinaryString => String,
+    Content => String,
 
-            _ => return None,
-        })
+    Bool => Bool,
+    Int32 => Int32,
+    Float32 => Float32,
+    Float64 => Float64,
+    UDim => UDim,
+    UDim2 => UDim2,
+    Ray => Ray,
+    Faces => Faces,
+    Axes => Axes,
+    BrickColor => BrickColor,
+    Color3 => Color3,
+    Vector2 => Vector2,
+    Vector3 => Vector3,
+    CFrame => CFrame,
+    Enum => Enum,
+    Ref => Ref,
+    Vector3int16 => Vector3int16,
+    NumberSequence => NumberSequence,
+    ColorSequence => ColorSequence,
+    NumberRange => NumberRange,
+    Rect => Rect,
+    PhysicalProperties => PhysicalProperties,
+    Color3uint8 => Color3uint8,
+    Int64 => Int64,
+    SharedString => SharedString,
+}
+
+impl Type {
+    /// Tells whether values of this type are written one after another, so
+    /// that the start of a chunk holds whole values for the first instances.
+    /// Other types are split across arrays sized by the number of values, and
+    /// can only be read as a whole.
+    pub fn is_sequential(&self) -> bool {
+        matches!(
+            self,
+            Type::String
+                | Type::Bool
+                | Type::Float64
+                | Type::Ray
+                | Type::Faces
+                | Type::Axes
+                | Type::Vector3int16
+                | Type::NumberSequence
+                | Type::ColorSequence
+                | Type::NumberRange
+                | Type::PhysicalProperties
+        )
     }
 
     pub fn to_default_rbx_type(&self) -> Option<VariantType> {
@@ -0,0 +1,238 @@
+//! Checks that values kept in a blob store are never held in memory all at
+//! once, by counting the memory allocated on each thread.
+//!
+//! This is an integration test instead of a unit test so that it can have its
+//! own global allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use rbx_binary::DecodeOptions;
+use rbx_dom_weak::{
+    types::{BinaryString, Blob, BlobStore, SharedString, TempFileStore, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    // Memory can be freed on a different thread than it was allocated on, so
+    // these can go below zero.
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = CURRENT.try_with(|current| {
+            current.set(current.get() + layout.size() as isize);
+            let _ = PEAK.try_with(|peak| peak.set(peak.get().max(current.get())));
+        });
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = CURRENT.try_with(|current| current.set(current.get() - layout.size() as isize));
+
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs `f`, returning its result and the most memory that was allocated on
+/// this thread at any one time while it ran.
+fn peak_allocated<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let start = CURRENT.with(Cell::get);
+    PEAK.with(|peak| peak.set(start));
+
+    let result = f();
+
+    (result, (PEAK.with(Cell::get) - start) as usize)
+}
+
+const PAYLOAD_LEN: u64 = 64 * 1024 * 1024;
+const ALLOCATION_CAP: usize = 4 * 1024 * 1024;
+
+/// Generates bytes that don't compress well, without keeping them in memory.
+struct Noise {
+    state: u32,
+    remaining: u64,
+}
+
+impl Noise {
+    fn new(seed: u32, len: u64) -> Self {
+        Noise {
+            state: seed,
+            remaining: len,
+        }
+    }
+}
+
+impl Read for Noise {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.remaining as usize);
+
+        for byte in &mut buf[..len] {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 17;
+            self.state ^= self.state << 5;
+            *byte = self.state as u8;
+        }
+
+        self.remaining -= len as u64;
+        Ok(len)
+    }
+}
+
+/// Tells whether the blob contains exactly the bytes that `expected` gives,
+/// reading both a piece at a time.
+fn same_contents(blob: &Blob, mut expected: impl Read) -> bool {
+    let mut actual = blob.open().unwrap();
+    let mut actual_buf = vec![0; 64 * 1024];
+    let mut expected_buf = vec![0; 64 * 1024];
+
+    loop {
+        let len = expected.read(&mut expected_buf).unwrap();
+        actual.read_exact(&mut actual_buf[..len]).unwrap();
+
+        if actual_buf[..len] != expected_buf[..len] {
+            return false;
+        }
+        if len == 0 {
+            return actual.read(&mut actual_buf).unwrap() == 0;
+        }
+    }
+}
+
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        TempFile(std::env::temp_dir().join(format!("{}-{}", std::process::id(), name)))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn large_values_stay_out_of_memory() {
+    let _ = env_logger::try_init();
+
+    let store: Arc<dyn BlobStore> = Arc::new(TempFileStore::new().unwrap());
+
+    let binary = Blob::store(&store, Noise::new(1, PAYLOAD_LEN)).unwrap();
+    let shared = Blob::store(&store, Noise::new(2, PAYLOAD_LEN)).unwrap();
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_property("Payload", BinaryString::from_blob(binary))
+            .with_property("SharedPayload", SharedString::from_blob(shared).unwrap()),
+    );
+
+    let file = TempFile::new("rbx-binary-blob-store.rbxm");
+
+    let (encoded, encode_peak) = peak_allocated(|| {
+        let mut output = BufWriter::new(File::create(&file.0)?);
+        rbx_binary::to_writer_default(&mut output, &dom, &[dom.root_ref()]).unwrap();
+        output.flush()
+    });
+    encoded.unwrap();
+    drop(dom);
+
+    assert!(
+        fs::metadata(&file.0).unwrap().len() > 2 * PAYLOAD_LEN,
+        "both payloads should have been written"
+    );
+    assert!(
+        encode_peak < ALLOCATION_CAP,
+        "encoding allocated {} bytes at once",
+        encode_peak
+    );
+
+    let options = DecodeOptions::new()
+        .blob_store(Arc::clone(&store))
+        .blob_threshold(1024 * 1024);
+
+    let (decoded, decode_peak) = peak_allocated(|| {
+        rbx_binary::from_reader(BufReader::new(File::open(&file.0).unwrap()), options)
+    });
+    let decoded = decoded.unwrap();
+
+    assert!(
+        decode_peak < ALLOCATION_CAP,
+        "decoding allocated {} bytes at once",
+        decode_peak
+    );
+
+    let root = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+    match root.properties.get("Payload") {
+        Some(Variant::BinaryString(value)) => {
+            let blob = value.blob().expect("Payload should be in the blob store");
+            assert_eq!(blob.len(), PAYLOAD_LEN);
+            assert!(same_contents(blob, Noise::new(1, PAYLOAD_LEN)));
+        }
+        other => panic!("expected a BinaryString, got {:?}", other),
+    }
+
+    match root.properties.get("SharedPayload") {
+        Some(Variant::SharedString(value)) => {
+            let blob = value
+                .blob()
+                .expect("SharedPayload should be in the blob store");
+            assert_eq!(blob.len(), PAYLOAD_LEN);
+            assert!(same_contents(blob, Noise::new(2, PAYLOAD_LEN)));
+        }
+        other => panic!("expected a SharedString, got {:?}", other),
+    }
+}
+
+#[test]
+fn small_values_stay_in_memory() {
+    let _ = env_logger::try_init();
+
+    let store: Arc<dyn BlobStore> = Arc::new(TempFileStore::new().unwrap());
+
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_property("Small", BinaryString::from(b"small".to_vec()))
+            .with_property("Large", BinaryString::from(vec![7; 4096])),
+    );
+
+    let mut file = Vec::new();
+    rbx_binary::to_writer_default(&mut file, &dom, &[dom.root_ref()]).unwrap();
+
+    let options = DecodeOptions::new().blob_store(store).blob_threshold(1024);
+    let decoded = rbx_binary::from_reader(file.as_slice(), options).unwrap();
+    let root = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+    match root.properties.get("Small") {
+        Some(Variant::BinaryString(value)) => {
+            let contents: &[u8] = value.as_ref();
+            assert!(value.blob().is_none());
+            assert_eq!(contents, b"small");
+        }
+        other => panic!("expected a BinaryString, got {:?}", other),
+    }
+
+    match root.properties.get("Large") {
+        Some(Variant::BinaryString(value)) => {
+            let contents: &[u8] = value.as_ref();
+            assert_eq!(value.blob().map(Blob::len), Some(4096));
+            assert_eq!(contents, &[7; 4096][..]);
+        }
+        other => panic!("expected a BinaryString, got {:?}", other),
+    }
+}
@@ -0,0 +1,40 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+/// Roughly the number of properties a Part has, which is about as many as most
+/// instances get.
+const PROPERTY_COUNT: usize = 40;
+
+pub fn property_lookup(c: &mut Criterion) {
+    let mut builder = InstanceBuilder::new("Part");
+    for i in 0..PROPERTY_COUNT {
+        builder.add_property(format!("Property{}", i), i as i32);
+    }
+
+    let dom = WeakDom::new(builder);
+    let instance = dom.root();
+
+    let names: Vec<String> = (0..PROPERTY_COUNT)
+        .map(|i| format!("Property{}", i))
+        .collect();
+
+    c.bench_function("Look up 40 properties", |b| {
+        b.iter(|| {
+            for name in &names {
+                black_box(instance.properties.get(name.as_str()));
+            }
+        });
+    });
+
+    c.bench_function("Iterate 40 properties", |b| {
+        b.iter(|| {
+            for property in &instance.properties {
+                black_box(property);
+            }
+        });
+    });
+}
+
+criterion_group!(properties, property_lookup);
+criterion_main!(properties);
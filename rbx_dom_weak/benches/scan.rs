@@ -0,0 +1,75 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rbx_dom_weak::{
+    types::{CFrame, Matrix3, Ref, Variant, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+/// Builds a dom of folders full of parts, with a few other instances mixed in
+/// that don't have a CFrame.
+fn mixed_dom() -> WeakDom {
+    let mut root = InstanceBuilder::new("Folder");
+
+    for i in 0..100 {
+        let mut folder = InstanceBuilder::new("Folder");
+
+        for j in 0..100 {
+            let position = Vector3::new(i as f32, j as f32, 0.0);
+            folder.add_child(
+                InstanceBuilder::new("Part")
+                    .with_property("CFrame", CFrame::new(position, Matrix3::identity())),
+            );
+        }
+
+        folder.add_child(InstanceBuilder::new("Script"));
+        root.add_child(folder);
+    }
+
+    WeakDom::new(root)
+}
+
+/// The approach that scan_property replaces: collect every descendant, then
+/// collect the matching values.
+fn collect_cframes(dom: &WeakDom) -> Vec<(Ref, CFrame)> {
+    let mut descendants = Vec::new();
+    let mut to_visit = vec![dom.root_ref()];
+
+    while let Some(referent) = to_visit.pop() {
+        let instance = dom.get_by_ref(referent).unwrap();
+        descendants.push(referent);
+        to_visit.extend(instance.children().iter().rev());
+    }
+
+    descendants
+        .into_iter()
+        .filter_map(
+            |referent| match dom.get_by_ref(referent).unwrap().properties.get("CFrame") {
+                Some(Variant::CFrame(value)) => Some((referent, *value)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+pub fn scan(c: &mut Criterion) {
+    let dom = mixed_dom();
+
+    c.bench_function("Scan CFrames of 10,000 parts", |b| {
+        b.iter(|| {
+            for value in dom.scan_property::<CFrame>(dom.root_ref(), "CFrame") {
+                black_box(value);
+            }
+        });
+    });
+
+    c.bench_function("Collect CFrames of 10,000 parts", |b| {
+        b.iter(|| {
+            for value in collect_cframes(&dom) {
+                black_box(value);
+            }
+        });
+    });
+}
+
+criterion_group!(scans, scan);
+criterion_main!(scans);
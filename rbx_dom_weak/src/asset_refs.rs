@@ -0,0 +1,424 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use rbx_types::{Attributes, BinaryString, Content, Ref, Variant};
+
+use crate::{dom::WeakDom, instance::Instance};
+
+/// The property that Roblox stores an instance's attributes in.
+const ATTRIBUTES_PROPERTY: &str = "AttributesSerialize";
+
+/// Properties that hold asset URLs, but are stored as strings instead of
+/// `Content` in some files.
+const STRING_ASSET_PROPERTIES: &[(&str, &str)] = &[("Animation", "AnimationId")];
+
+/// A place in a dom that refers to a Roblox asset by its ID, found by
+/// [`WeakDom::collect_asset_refs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AssetRefSite {
+    /// The instance that refers to the asset.
+    pub referent: Ref,
+
+    /// The name of the property that refers to the asset. For attributes, this
+    /// is `AttributesSerialize`.
+    pub property: String,
+
+    /// The name of the attribute that refers to the asset, if the reference
+    /// is stored in an attribute.
+    pub attribute: Option<String>,
+
+    /// The ID of the asset.
+    pub asset_id: u64,
+}
+
+impl WeakDom {
+    /// Finds every reference to a Roblox asset in the given instance and its
+    /// descendants, in depth-first order.
+    ///
+    /// References are found in:
+    ///
+    /// - `Content` properties, like `Decal.Texture` and `Sound.SoundId`
+    /// - asset properties that are sometimes stored as strings, like
+    ///   `Animation.AnimationId`
+    /// - string attributes, whether they've been decoded into a
+    ///   `Variant::Attributes` or are still stored in a `BinaryString`
+    ///
+    /// Only URLs recognized by [`Content::asset_id`] count as references.
+    /// Attributes in a `BinaryString` that [`Attributes::from_binary`] can't
+    /// read are skipped.
+    pub fn collect_asset_refs(&self, root: Ref) -> Vec<AssetRefSite> {
+        let mut sites = Vec::new();
+
+        for referent in self.descendants_of(root) {
+            let instance = self.get_by_ref(referent).unwrap();
+
+            for (name, value) in &instance.properties {
+                if let Some(asset_id) = property_asset_id(&instance.class, name, value) {
+                    sites.push(AssetRefSite {
+                        referent,
+                        property: name.clone(),
+                        attribute: None,
+                        asset_id,
+                    });
+                }
+
+                let attributes = match attributes_of(name, value) {
+                    Some(attributes) => attributes,
+                    None => continue,
+                };
+
+                for (attribute, asset_id) in string_asset_ids(&attributes) {
+                    sites.push(AssetRefSite {
+                        referent,
+                        property: name.clone(),
+                        attribute: Some(attribute.to_owned()),
+                        asset_id,
+                    });
+                }
+            }
+        }
+
+        sites
+    }
+
+    /// Rewrites every reference found by [`WeakDom::collect_asset_refs`] whose
+    /// asset ID is a key in `map` to refer to the asset ID it maps to, and
+    /// returns the number of references that were rewritten.
+    ///
+    /// Rewritten references always use the `rbxassetid://<id>` form. Values
+    /// keep their type, so a reference stored in a string stays a string.
+    pub fn rewrite_asset_refs(&mut self, root: Ref, map: &HashMap<u64, u64>) -> usize {
+        let new_url = |asset_id: u64| {
+            map.get(&asset_id)
+                .map(|new_id| format!("rbxassetid://{}", new_id))
+        };
+
+        let mut rewritten = 0;
+
+        for referent in self.descendants_of(root) {
+            let instance = self.get_by_ref_mut(referent).unwrap();
+
+            for (name, value) in &mut instance.properties {
+                if let Some(url) =
+                    property_asset_id(&instance.class, name, value).and_then(&new_url)
+                {
+                    *value = match value {
+                        Variant::String(_) => Variant::from(url),
+                        _ => Variant::Content(Content::from(url)),
+                    };
+                    rewritten += 1;
+                    continue;
+                }
+
+                let mut attributes = match attributes_of(name, value) {
+                    Some(attributes) => attributes.into_owned(),
+                    None => continue,
+                };
+
+                let count = rewrite_string_attributes(&mut attributes, |url| {
+                    Content::from(url).asset_id().and_then(&new_url)
+                });
+
+                if count == 0 {
+                    continue;
+                }
+
+                *value = match value {
+                    Variant::Attributes(_) => Variant::Attributes(attributes),
+                    // Attributes read from a BinaryString only hold types
+                    // that can be written back.
+                    _ => Variant::BinaryString(BinaryString::from(attributes.to_binary().unwrap())),
+                };
+                rewritten += count;
+            }
+        }
+
+        rewritten
+    }
+
+    /// Lists the given instance and all of its descendants, parents first.
+    fn descendants_of(&self, root: Ref) -> Vec<Ref> {
+        self.descendants(root).map(Instance::referent).collect()
+    }
+}
+
+fn property_asset_id(class: &str, name: &str, value: &Variant) -> Option<u64> {
+    match value {
+        Variant::Content(content) => content.asset_id(),
+        Variant::String(url) if STRING_ASSET_PROPERTIES.contains(&(class, name)) => {
+            Content::from(&**url).asset_id()
+        }
+        _ => None,
+    }
+}
+
+/// The attributes stored in a property, if it holds any. Attributes that
+/// haven't been decoded yet are read from the `BinaryString` Roblox stores
+/// them in.
+fn attributes_of<'a>(name: &str, value: &'a Variant) -> Option<Cow<'a, Attributes>> {
+    match (name, value) {
+        (_, Variant::Attributes(attributes)) => Some(Cow::Borrowed(attributes)),
+        (ATTRIBUTES_PROPERTY, Variant::BinaryString(blob)) => {
+            Attributes::from_binary(blob.as_ref()).map(Cow::Owned)
+        }
+        _ => None,
+    }
+}
+
+/// The names and asset IDs of every string attribute that refers to an asset.
+fn string_asset_ids(attributes: &Attributes) -> impl Iterator<Item = (&str, u64)> {
+    attributes.iter().filter_map(|(name, value)| match value {
+        Variant::String(url) => Some((name, Content::from(&**url).asset_id()?)),
+        _ => None,
+    })
+}
+
+/// Replaces the string attributes for which `replace` returns a new value,
+/// returning how many were replaced.
+fn rewrite_string_attributes<F>(attributes: &mut Attributes, mut replace: F) -> usize
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let replacements: Vec<(String, String)> = attributes
+        .iter()
+        .filter_map(|(name, value)| match value {
+            Variant::String(value) => Some((name.to_owned(), replace(value)?)),
+            _ => None,
+        })
+        .collect();
+
+    let count = replacements.len();
+
+    // Attributes that already exist keep their position when they're set.
+    for (name, new_value) in replacements {
+        attributes.insert(name, new_value);
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::Vector3;
+
+    use crate::InstanceBuilder;
+
+    fn push_string(blob: &mut Vec<u8>, value: &str) {
+        blob.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        blob.extend_from_slice(value.as_bytes());
+    }
+
+    /// Serializes a Float32 attribute, a String attribute holding `icon`, and
+    /// a Vector3 attribute, in that order.
+    fn attributes(icon: &str) -> BinaryString {
+        let mut blob = 3u32.to_le_bytes().to_vec();
+
+        push_string(&mut blob, "Speed");
+        blob.push(0x05);
+        blob.extend_from_slice(&16.0f32.to_le_bytes());
+
+        push_string(&mut blob, "Icon");
+        blob.push(0x02);
+        push_string(&mut blob, icon);
+
+        push_string(&mut blob, "Offset");
+        blob.push(0x11);
+        for component in &[1.0f32, 2.0, 3.0] {
+            blob.extend_from_slice(&component.to_le_bytes());
+        }
+
+        BinaryString::from(blob)
+    }
+
+    fn fixture() -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_property("AttributesSerialize", attributes("rbxassetid://400"))
+                .with_children(vec![
+                    InstanceBuilder::new("Decal")
+                        .with_property("Texture", Content::from("rbxassetid://100")),
+                    InstanceBuilder::new("MeshPart")
+                        .with_property(
+                            "MeshId",
+                            Content::from("http://www.roblox.com/asset/?id=200"),
+                        )
+                        .with_property("TextureID", Content::from("rbxasset://textures/face.png"))
+                        .with_property("Size", Vector3::new(1.0, 1.0, 1.0))
+                        .with_child(
+                            InstanceBuilder::new("Sound")
+                                .with_property("SoundId", Content::from("rbxassetid://300")),
+                        ),
+                    InstanceBuilder::new("Animation")
+                        .with_property("AnimationId", "rbxassetid://100"),
+                    InstanceBuilder::new("StringValue").with_property("Value", "rbxassetid://999"),
+                ]),
+        )
+    }
+
+    fn summarize(dom: &WeakDom) -> Vec<(String, String, Option<String>, u64)> {
+        dom.collect_asset_refs(dom.root_ref())
+            .into_iter()
+            .map(|site| {
+                let class = dom.get_by_ref(site.referent).unwrap().class.clone();
+                (class, site.property, site.attribute, site.asset_id)
+            })
+            .collect()
+    }
+
+    fn site(
+        class: &str,
+        property: &str,
+        attribute: Option<&str>,
+        asset_id: u64,
+    ) -> (String, String, Option<String>, u64) {
+        (
+            class.to_owned(),
+            property.to_owned(),
+            attribute.map(str::to_owned),
+            asset_id,
+        )
+    }
+
+    #[test]
+    fn collect() {
+        let dom = fixture();
+
+        assert_eq!(
+            summarize(&dom),
+            vec![
+                site("Model", "AttributesSerialize", Some("Icon"), 400),
+                site("Decal", "Texture", None, 100),
+                site("MeshPart", "MeshId", None, 200),
+                site("Sound", "SoundId", None, 300),
+                site("Animation", "AnimationId", None, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_subtree() {
+        let dom = fixture();
+        let mesh_part_ref = dom.root().children()[1];
+
+        let sites = dom.collect_asset_refs(mesh_part_ref);
+        let ids: Vec<u64> = sites.iter().map(|site| site.asset_id).collect();
+
+        assert_eq!(ids, vec![200, 300]);
+        assert_eq!(sites[0].referent, mesh_part_ref);
+    }
+
+    #[test]
+    fn rewrite() {
+        let mut dom = fixture();
+        let root_ref = dom.root_ref();
+
+        let mut map = HashMap::new();
+        map.insert(100, 1100);
+        map.insert(200, 1200);
+        map.insert(400, 1400);
+
+        assert_eq!(dom.rewrite_asset_refs(root_ref, &map), 4);
+
+        assert_eq!(
+            summarize(&dom),
+            vec![
+                site("Model", "AttributesSerialize", Some("Icon"), 1400),
+                site("Decal", "Texture", None, 1100),
+                site("MeshPart", "MeshId", None, 1200),
+                site("Sound", "SoundId", None, 300),
+                site("Animation", "AnimationId", None, 1100),
+            ]
+        );
+
+        // The other attributes are untouched.
+        assert_eq!(
+            dom.root().properties["AttributesSerialize"],
+            Variant::BinaryString(attributes("rbxassetid://1400"))
+        );
+
+        let children = dom.root().children().to_vec();
+
+        let mesh_part = dom.get_by_ref(children[1]).unwrap();
+        assert_eq!(
+            mesh_part.properties["MeshId"],
+            Variant::Content(Content::from("rbxassetid://1200"))
+        );
+
+        let animation = dom.get_by_ref(children[2]).unwrap();
+        assert_eq!(
+            animation.properties["AnimationId"],
+            Variant::from("rbxassetid://1100")
+        );
+    }
+
+    #[test]
+    fn decoded_attributes() {
+        let attributes: Attributes = vec![
+            ("Speed", Variant::Float32(16.0)),
+            ("Icon", Variant::from("rbxassetid://400")),
+            ("Badge", Variant::from("rbxassetid://500")),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Model").with_property("AttributesSerialize", attributes),
+        );
+        let root_ref = dom.root_ref();
+
+        assert_eq!(
+            summarize(&dom),
+            vec![
+                site("Model", "AttributesSerialize", Some("Icon"), 400),
+                site("Model", "AttributesSerialize", Some("Badge"), 500),
+            ]
+        );
+
+        let mut map = HashMap::new();
+        map.insert(400, 1400);
+
+        assert_eq!(dom.rewrite_asset_refs(root_ref, &map), 1);
+
+        let expected: Attributes = vec![
+            ("Speed", Variant::Float32(16.0)),
+            ("Icon", Variant::from("rbxassetid://1400")),
+            ("Badge", Variant::from("rbxassetid://500")),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            dom.root().properties["AttributesSerialize"],
+            Variant::Attributes(expected)
+        );
+    }
+
+    #[test]
+    fn unreadable_attributes() {
+        // An attribute of an unknown type stops the search, since there's no
+        // way to know where the next attribute starts.
+        let mut blob = 1u32.to_le_bytes().to_vec();
+        push_string(&mut blob, "Mystery");
+        blob.push(0xFF);
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_property("AttributesSerialize", BinaryString::from(blob)),
+        );
+
+        assert!(dom.collect_asset_refs(dom.root_ref()).is_empty());
+
+        // Blobs that are cut off are ignored too.
+        let mut truncated: Vec<u8> = attributes("rbxassetid://400").into();
+        truncated.truncate(truncated.len() - 1);
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_property("AttributesSerialize", BinaryString::from(truncated)),
+        );
+
+        assert!(dom.collect_asset_refs(dom.root_ref()).is_empty());
+    }
+}
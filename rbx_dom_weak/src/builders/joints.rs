@@ -0,0 +1,194 @@
+//! Helpers for connecting parts together with joints and attachments.
+//!
+//! Each helper inserts a new instance into the dom and returns its Ref. Like
+//! Roblox Studio, joints are parented to `part0`.
+
+use rbx_types::{CFrame, Ref, Variant};
+
+use crate::{InstanceBuilder, WeakDom};
+
+/// Creates a `WeldConstraint` that holds `part1` in place relative to `part0`.
+///
+/// WeldConstraints keep whatever offset the parts have when the weld is made,
+/// so neither part is moved.
+///
+/// The weld's parts are set through `Part0` and `Part1`, which the reflection
+/// database bundled with rbx_xml and rbx_binary doesn't list as serialized.
+/// Encoders need to be told to write unknown properties to keep them.
+///
+/// Panics if `part0` is not in the dom.
+pub fn weld_constraint(dom: &mut WeakDom, part0: Ref, part1: Ref) -> Ref {
+    let weld = InstanceBuilder::new("WeldConstraint")
+        .with_property("Part0", part0)
+        .with_property("Part1", part1);
+
+    dom.insert(part0, weld)
+}
+
+/// Creates a `Motor6D` connecting `part0` to `part1`.
+///
+/// `c0` and `c1` are the joint's position relative to `part0` and `part1`.
+/// Roblox keeps the two lined up, so that `part0.CFrame * c0` is the same as
+/// `part1.CFrame * c1`. If `part0` has a `CFrame` property, `part1` is moved to
+/// satisfy that, just like it would be once the file is loaded.
+///
+/// Panics if either part is not in the dom.
+pub fn motor6d(dom: &mut WeakDom, part0: Ref, part1: Ref, c0: CFrame, c1: CFrame) -> Ref {
+    let part0_cframe = match dom
+        .get_by_ref(part0)
+        .map(|part| part.properties.get("CFrame"))
+    {
+        Some(Some(Variant::CFrame(cframe))) => Some(*cframe),
+        Some(_) => None,
+        None => panic!("cannot create a Motor6D for a part0 that does not exist"),
+    };
+
+    let part1_instance = dom
+        .get_by_ref_mut(part1)
+        .unwrap_or_else(|| panic!("cannot create a Motor6D for a part1 that does not exist"));
+
+    if let Some(part0_cframe) = part0_cframe {
+        part1_instance.properties.insert(
+            "CFrame".to_owned(),
+            (part0_cframe * c0 * c1.inverse()).into(),
+        );
+    }
+
+    let motor = InstanceBuilder::new("Motor6D")
+        .with_property("Part0", part0)
+        .with_property("Part1", part1)
+        .with_property("C0", c0)
+        .with_property("C1", c1);
+
+    dom.insert(part0, motor)
+}
+
+/// Creates an `Attachment` under `parent`, placed at `cframe` relative to it.
+///
+/// Panics if `parent` is not in the dom.
+pub fn attachment(dom: &mut WeakDom, parent: Ref, cframe: CFrame) -> Ref {
+    dom.insert(
+        parent,
+        InstanceBuilder::new("Attachment").with_property("CFrame", cframe),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::{Matrix3, Vector3};
+
+    /// A rotation of 90 degrees around the Y axis.
+    fn quarter_turn() -> Matrix3 {
+        Matrix3::new(
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+        )
+    }
+
+    fn no_rotation() -> Matrix3 {
+        Matrix3::new(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        )
+    }
+
+    fn two_parts() -> (WeakDom, Ref, Ref) {
+        let torso = InstanceBuilder::new("Part")
+            .with_name("Torso")
+            .with_property(
+                "CFrame",
+                CFrame::new(Vector3::new(0.0, 3.0, 0.0), quarter_turn()),
+            );
+        let arm = InstanceBuilder::new("Part").with_name("Arm");
+        let torso_ref = torso.referent();
+        let arm_ref = arm.referent();
+
+        let dom = WeakDom::new(InstanceBuilder::new("Model").with_children(vec![torso, arm]));
+
+        (dom, torso_ref, arm_ref)
+    }
+
+    fn property(dom: &WeakDom, referent: Ref, name: &str) -> Variant {
+        dom.get_by_ref(referent).unwrap().properties[name].clone()
+    }
+
+    #[test]
+    fn weld_constraint_refs() {
+        let (mut dom, torso, arm) = two_parts();
+        let weld = weld_constraint(&mut dom, torso, arm);
+
+        let instance = dom.get_by_ref(weld).unwrap();
+        assert_eq!(instance.class, "WeldConstraint");
+        assert_eq!(instance.parent(), torso);
+        assert_eq!(property(&dom, weld, "Part0"), Variant::Ref(torso));
+        assert_eq!(property(&dom, weld, "Part1"), Variant::Ref(arm));
+        assert!(!dom
+            .get_by_ref(arm)
+            .unwrap()
+            .properties
+            .contains_key("CFrame"));
+    }
+
+    #[test]
+    fn motor6d_moves_part1() {
+        let (mut dom, torso, arm) = two_parts();
+        let c0 = CFrame::new(Vector3::new(1.5, 0.5, 0.0), no_rotation());
+        let c1 = CFrame::new(Vector3::new(-0.5, 0.5, 0.0), no_rotation());
+
+        let motor = motor6d(&mut dom, torso, arm, c0, c1);
+
+        let instance = dom.get_by_ref(motor).unwrap();
+        assert_eq!(instance.class, "Motor6D");
+        assert_eq!(instance.parent(), torso);
+        assert_eq!(property(&dom, motor, "C0"), Variant::CFrame(c0));
+        assert_eq!(property(&dom, motor, "C1"), Variant::CFrame(c1));
+
+        let torso_cframe = match property(&dom, torso, "CFrame") {
+            Variant::CFrame(cframe) => cframe,
+            other => panic!("expected a CFrame, got {:?}", other),
+        };
+        let arm_cframe = match property(&dom, arm, "CFrame") {
+            Variant::CFrame(cframe) => cframe,
+            other => panic!("expected a CFrame, got {:?}", other),
+        };
+
+        // The joint lines up from both sides.
+        assert_eq!(torso_cframe * c0, arm_cframe * c1);
+        assert_eq!(arm_cframe.position, Vector3::new(0.0, 3.0, -2.0));
+    }
+
+    #[test]
+    fn motor6d_without_part0_cframe() {
+        let (mut dom, torso, arm) = two_parts();
+        dom.get_by_ref_mut(torso).unwrap().properties.clear();
+
+        let c0 = CFrame::new(Vector3::new(1.0, 0.0, 0.0), no_rotation());
+        motor6d(&mut dom, torso, arm, c0, c0);
+
+        assert!(!dom
+            .get_by_ref(arm)
+            .unwrap()
+            .properties
+            .contains_key("CFrame"));
+    }
+
+    #[test]
+    fn attachment_cframe() {
+        let (mut dom, torso, _) = two_parts();
+        let cframe = CFrame::new(Vector3::new(0.0, 1.0, 0.0), quarter_turn());
+
+        let attachment = attachment(&mut dom, torso, cframe);
+
+        let instance = dom.get_by_ref(attachment).unwrap();
+        assert_eq!(instance.class, "Attachment");
+        assert_eq!(instance.parent(), torso);
+        assert_eq!(
+            property(&dom, attachment, "CFrame"),
+            Variant::CFrame(cframe)
+        );
+    }
+}
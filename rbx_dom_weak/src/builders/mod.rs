@@ -0,0 +1,3 @@
+//! Helpers for building common structures of instances.
+
+pub mod joints;
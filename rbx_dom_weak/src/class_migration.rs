@@ -0,0 +1,532 @@
+use std::fmt;
+
+use rbx_reflection::{ClassTag, ReflectionDatabase};
+use rbx_types::{Ref, Variant};
+
+use crate::{
+    dom::WeakDom,
+    property_write::find_on_class,
+    ref_cleanup::{DanglingRefPolicy, RefSite},
+};
+
+/// Something that [`WeakDom::plan_class_migrations`] found to do about an
+/// instance of a legacy class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ClassMigration {
+    /// The full name of the instance, like `Workspace.Part`, from before
+    /// anything was migrated.
+    pub instance: String,
+
+    /// The referent of the instance.
+    pub referent: Ref,
+
+    /// The instance's legacy class.
+    pub class: String,
+
+    /// What to do about the instance.
+    pub action: MigrationAction,
+
+    /// Whether the action was carried out. This is only ever true in the
+    /// report from [`WeakDom::apply_class_migrations`], and never for
+    /// [`MigrationAction::Unresolved`].
+    pub applied: bool,
+}
+
+/// The ways that [`WeakDom::plan_class_migrations`] deals with a legacy
+/// class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MigrationAction {
+    /// The instance can become an instance of a modern class that does the
+    /// same job, like a `Hat` becoming an `Accessory`. It keeps its referent,
+    /// name, and children.
+    Replace {
+        /// The class that the instance becomes.
+        class: String,
+
+        /// The stored properties that the new class doesn't have, which are
+        /// removed.
+        dropped_properties: Vec<String>,
+    },
+
+    /// The instance doesn't do anything in Roblox anymore, so it and its
+    /// descendants can be removed.
+    Remove {
+        /// Why the instance is removed, and what to use instead.
+        reason: String,
+    },
+
+    /// There's no mechanical replacement for the instance, so it's left alone
+    /// for a person to deal with.
+    Unresolved {
+        /// What the class is usually replaced with, if anything is.
+        suggestion: Option<String>,
+    },
+}
+
+/// What [`WeakDom::plan_class_migrations`] and
+/// [`WeakDom::apply_class_migrations`] found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MigrationReport {
+    /// Every instance of a legacy class, in the order given by
+    /// [`WeakDom::descendants`]. Descendants of removed instances aren't
+    /// listed, since they're removed along with them.
+    pub migrations: Vec<ClassMigration>,
+
+    /// Ref properties that pointed into removed instances, which were set to
+    /// `Ref::none()`.
+    pub nulled_refs: Vec<RefSite>,
+}
+
+impl MigrationReport {
+    /// Iterates over the migrations that were carried out.
+    pub fn applied(&self) -> impl Iterator<Item = &ClassMigration> {
+        self.migrations.iter().filter(|migration| migration.applied)
+    }
+
+    /// Iterates over the instances that were left alone because they need a
+    /// person to migrate them.
+    pub fn unresolved(&self) -> impl Iterator<Item = &ClassMigration> {
+        self.migrations
+            .iter()
+            .filter(|migration| matches!(migration.action, MigrationAction::Unresolved { .. }))
+    }
+}
+
+impl fmt::Display for ClassMigration {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match &self.action {
+            MigrationAction::Replace {
+                class,
+                dropped_properties,
+            } => {
+                write!(
+                    output,
+                    "{} {} {} a {}",
+                    self.class,
+                    self.instance,
+                    if self.applied { "became" } else { "can become" },
+                    class
+                )?;
+
+                if !dropped_properties.is_empty() {
+                    write!(
+                        output,
+                        "; dropped properties: {}",
+                        dropped_properties.join(", ")
+                    )?;
+                }
+
+                Ok(())
+            }
+            MigrationAction::Remove { reason } => write!(
+                output,
+                "{} {} {} removed: {}",
+                self.class,
+                self.instance,
+                if self.applied { "was" } else { "can be" },
+                reason
+            ),
+            MigrationAction::Unresolved { suggestion } => {
+                write!(
+                    output,
+                    "{} {} is deprecated and needs to be migrated by hand",
+                    self.class, self.instance
+                )?;
+
+                if let Some(suggestion) = suggestion {
+                    write!(output, "; consider {}", suggestion)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// What to do about a legacy class that has a known replacement.
+enum Rule {
+    Replace {
+        class: &'static str,
+
+        /// Properties set on the new instance, which the legacy class didn't
+        /// need.
+        set: &'static [(&'static str, Variant)],
+    },
+    Remove(&'static str),
+    Suggest(&'static str),
+}
+
+/// Legacy classes and their replacements. The body movers aren't deprecated
+/// in every version of the reflection database, so they're covered here
+/// whether or not the database says so.
+static RULES: &[(&str, Rule)] = &[
+    (
+        "HopperBin",
+        Rule::Replace {
+            class: "Tool",
+            set: &[("RequiresHandle", Variant::Bool(false))],
+        },
+    ),
+    (
+        "Hat",
+        Rule::Replace {
+            class: "Accessory",
+            set: &[],
+        },
+    ),
+    (
+        "GuiMain",
+        Rule::Replace {
+            class: "ScreenGui",
+            set: &[],
+        },
+    ),
+    (
+        "Message",
+        Rule::Remove("messages are no longer shown; use a TextLabel in a ScreenGui instead"),
+    ),
+    (
+        "Hint",
+        Rule::Remove("hints are no longer shown; use a TextLabel in a ScreenGui instead"),
+    ),
+    (
+        "BodyAngularVelocity",
+        Rule::Suggest("an AngularVelocity constraint"),
+    ),
+    ("BodyForce", Rule::Suggest("a VectorForce constraint")),
+    ("BodyGyro", Rule::Suggest("an AlignOrientation constraint")),
+    ("BodyPosition", Rule::Suggest("an AlignPosition constraint")),
+    ("BodyThrust", Rule::Suggest("a VectorForce constraint")),
+    ("BodyVelocity", Rule::Suggest("a LinearVelocity constraint")),
+    (
+        "RocketPropulsion",
+        Rule::Suggest("AlignPosition and AlignOrientation constraints"),
+    ),
+];
+
+impl WeakDom {
+    /// Finds the instances of legacy classes in a subtree and decides what to
+    /// do about each of them, without changing anything.
+    ///
+    /// Classes with a mechanical replacement are migrated by
+    /// [`WeakDom::apply_class_migrations`]: `HopperBin` becomes a `Tool` that
+    /// doesn't require a handle, `Hat` becomes `Accessory`, `GuiMain` becomes
+    /// `ScreenGui`, and `Message` and `Hint` are removed. Replacements only
+    /// happen if the database has the new class. Body movers like
+    /// `BodyVelocity` and every other class that the database marks as
+    /// deprecated are listed as [`MigrationAction::Unresolved`].
+    pub fn plan_class_migrations(
+        &self,
+        root: Ref,
+        database: &ReflectionDatabase,
+    ) -> MigrationReport {
+        let mut migrations = Vec::new();
+        let mut to_visit = vec![root];
+
+        while let Some(referent) = to_visit.pop() {
+            let instance = match self.get_by_ref(referent) {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            let action = plan_action(self, referent, database);
+            let removed = matches!(action, Some(MigrationAction::Remove { .. }));
+
+            if let Some(action) = action {
+                migrations.push(ClassMigration {
+                    instance: self.full_name(referent).unwrap(),
+                    referent,
+                    class: instance.class.clone(),
+                    action,
+                    applied: false,
+                });
+            }
+
+            if !removed {
+                to_visit.extend(instance.children().iter().rev());
+            }
+        }
+
+        MigrationReport {
+            migrations,
+            nulled_refs: Vec::new(),
+        }
+    }
+
+    /// Migrates the instances of legacy classes in a subtree that have a
+    /// mechanical replacement, as described by
+    /// [`WeakDom::plan_class_migrations`], and returns what was done.
+    ///
+    /// Ref properties that pointed into removed instances are set to
+    /// `Ref::none()`. The dom's root can't be removed, so it's left alone if
+    /// it's one of the classes that would be.
+    pub fn apply_class_migrations(
+        &mut self,
+        root: Ref,
+        database: &ReflectionDatabase,
+    ) -> MigrationReport {
+        let mut report = self.plan_class_migrations(root, database);
+
+        for migration in &mut report.migrations {
+            match &migration.action {
+                MigrationAction::Replace {
+                    class,
+                    dropped_properties,
+                } => {
+                    let set = match find_rule(&migration.class) {
+                        Some(Rule::Replace { set, .. }) => *set,
+                        _ => &[],
+                    };
+
+                    let instance = self.get_by_ref_mut(migration.referent).unwrap();
+                    instance.class = class.clone();
+
+                    for name in dropped_properties {
+                        instance.properties.shift_remove(name);
+                    }
+
+                    for (name, value) in set {
+                        instance
+                            .properties
+                            .insert((*name).to_owned(), value.clone());
+                    }
+
+                    migration.applied = true;
+                }
+                MigrationAction::Remove { .. } => {
+                    if migration.referent == self.root_ref() {
+                        continue;
+                    }
+
+                    let nulled = self.destroy_with_ref_cleanup(
+                        migration.referent,
+                        DanglingRefPolicy::NullDanglingRefs,
+                    );
+                    report.nulled_refs.extend(nulled);
+                    migration.applied = true;
+                }
+                MigrationAction::Unresolved { .. } => {}
+            }
+        }
+
+        report
+    }
+}
+
+fn find_rule(class_name: &str) -> Option<&'static Rule> {
+    RULES
+        .iter()
+        .find(|(name, _)| *name == class_name)
+        .map(|(_, rule)| rule)
+}
+
+fn plan_action(
+    dom: &WeakDom,
+    referent: Ref,
+    database: &ReflectionDatabase,
+) -> Option<MigrationAction> {
+    let instance = dom.get_by_ref(referent).unwrap();
+    let deprecated = database
+        .classes
+        .get(instance.class.as_str())
+        .is_some_and(|class| class.tags.contains(&ClassTag::Deprecated));
+
+    let action = match find_rule(&instance.class) {
+        Some(Rule::Replace { class, .. }) if database.classes.contains_key(*class) => {
+            let dropped_properties = instance
+                .properties
+                .keys()
+                .filter(|name| find_on_class(database, class, name).is_none())
+                .cloned()
+                .collect();
+
+            MigrationAction::Replace {
+                class: (*class).to_owned(),
+                dropped_properties,
+            }
+        }
+        Some(Rule::Replace { class, .. }) => MigrationAction::Unresolved {
+            suggestion: Some(format!("a {}", class)),
+        },
+        Some(Rule::Remove(reason)) => MigrationAction::Remove {
+            reason: (*reason).to_owned(),
+        },
+        Some(Rule::Suggest(suggestion)) => MigrationAction::Unresolved {
+            suggestion: Some((*suggestion).to_owned()),
+        },
+        None if deprecated => MigrationAction::Unresolved { suggestion: None },
+        None => return None,
+    };
+
+    Some(action)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_reflection::{ClassDescriptor, DataType, PropertyDescriptor};
+    use rbx_types::{Enum, VariantType};
+
+    use crate::InstanceBuilder;
+
+    fn database() -> ReflectionDatabase<'static> {
+        let mut backpack_item = ClassDescriptor::new("BackpackItem");
+        backpack_item.properties.insert(
+            "TextureId".into(),
+            PropertyDescriptor::new("TextureId", DataType::Value(VariantType::Content)),
+        );
+
+        let mut hopper_bin = ClassDescriptor::new("HopperBin");
+        hopper_bin.superclass = Some("BackpackItem".into());
+        hopper_bin.tags.insert(ClassTag::Deprecated);
+        hopper_bin.properties.insert(
+            "BinType".into(),
+            PropertyDescriptor::new("BinType", DataType::Enum("BinType".into())),
+        );
+
+        let mut tool = ClassDescriptor::new("Tool");
+        tool.superclass = Some("BackpackItem".into());
+        tool.properties.insert(
+            "RequiresHandle".into(),
+            PropertyDescriptor::new("RequiresHandle", DataType::Value(VariantType::Bool)),
+        );
+
+        let mut message = ClassDescriptor::new("Message");
+        message.tags.insert(ClassTag::Deprecated);
+
+        let mut glue = ClassDescriptor::new("Glue");
+        glue.tags.insert(ClassTag::Deprecated);
+
+        let mut database = ReflectionDatabase::new();
+        for class in [backpack_item, hopper_bin, tool, message, glue] {
+            database.classes.insert(class.name.clone(), class);
+        }
+        database
+    }
+
+    fn legacy_dom() -> WeakDom {
+        let message = InstanceBuilder::new("Message")
+            .with_name("Welcome")
+            .with_child(InstanceBuilder::new("Message").with_name("Nested"));
+        let pointer =
+            InstanceBuilder::new("ObjectValue").with_property("Value", message.referent());
+
+        WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Model")
+                .with_child(
+                    InstanceBuilder::new("HopperBin")
+                        .with_name("Clone")
+                        .with_property("BinType", Enum::from_u32(2))
+                        .with_property(
+                            "TextureId",
+                            rbx_types::Content::from("rbxasset://clone.png"),
+                        ),
+                )
+                .with_child(message)
+                .with_child(pointer)
+                .with_child(InstanceBuilder::new("Glue"))
+                .with_child(InstanceBuilder::new("BodyVelocity")),
+        )
+    }
+
+    #[test]
+    fn plan() {
+        let dom = legacy_dom();
+        let report = dom.plan_class_migrations(dom.root_ref(), &database());
+
+        let actions: Vec<_> = report
+            .migrations
+            .iter()
+            .map(|migration| (migration.instance.as_str(), &migration.action))
+            .collect();
+
+        assert_eq!(
+            actions,
+            [
+                (
+                    "Model.Clone",
+                    &MigrationAction::Replace {
+                        class: "Tool".to_owned(),
+                        dropped_properties: vec!["BinType".to_owned()],
+                    }
+                ),
+                (
+                    "Model.Welcome",
+                    &MigrationAction::Remove {
+                        reason: "messages are no longer shown; use a TextLabel in a ScreenGui \
+                                 instead"
+                            .to_owned(),
+                    }
+                ),
+                (
+                    "Model.Glue",
+                    &MigrationAction::Unresolved { suggestion: None }
+                ),
+                (
+                    "Model.BodyVelocity",
+                    &MigrationAction::Unresolved {
+                        suggestion: Some("a LinearVelocity constraint".to_owned()),
+                    }
+                ),
+            ]
+        );
+
+        assert_eq!(report.applied().count(), 0);
+        assert_eq!(report.unresolved().count(), 2);
+        assert_eq!(dom.len(), 7, "planning shouldn't change the dom");
+    }
+
+    #[test]
+    fn apply() {
+        let mut dom = legacy_dom();
+        let report = dom.apply_class_migrations(dom.root_ref(), &database());
+
+        let messages: Vec<_> = report.migrations.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "HopperBin Model.Clone became a Tool; dropped properties: BinType",
+                "Message Model.Welcome was removed: messages are no longer shown; use a \
+                 TextLabel in a ScreenGui instead",
+                "Glue Model.Glue is deprecated and needs to be migrated by hand",
+                "BodyVelocity Model.BodyVelocity is deprecated and needs to be migrated by \
+                 hand; consider a LinearVelocity constraint",
+            ]
+        );
+
+        let children = dom.root().children().to_vec();
+        assert_eq!(children.len(), 4);
+
+        let tool = dom.get_by_ref(children[0]).unwrap();
+        assert_eq!(tool.class, "Tool");
+        assert_eq!(tool.name, "Clone");
+        assert_eq!(
+            tool.properties.keys().collect::<Vec<_>>(),
+            ["TextureId", "RequiresHandle"]
+        );
+        assert_eq!(tool.properties["RequiresHandle"], Variant::Bool(false));
+
+        let pointer = dom.get_by_ref(children[1]).unwrap();
+        assert_eq!(pointer.properties["Value"], Variant::Ref(Ref::none()));
+        assert_eq!(report.nulled_refs.len(), 1);
+        assert_eq!(report.nulled_refs[0].referent, children[1]);
+
+        assert_eq!(dom.get_by_ref(children[2]).unwrap().class, "Glue");
+        assert_eq!(dom.get_by_ref(children[3]).unwrap().class, "BodyVelocity");
+    }
+
+    #[test]
+    fn root_is_never_removed() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Message"));
+        let report = dom.apply_class_migrations(dom.root_ref(), &database());
+
+        assert_eq!(report.migrations.len(), 1);
+        assert!(!report.migrations[0].applied);
+        assert_eq!(dom.root().class, "Message");
+    }
+}
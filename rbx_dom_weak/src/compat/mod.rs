@@ -0,0 +1,66 @@
+//! Adapters that mimic the rbx_dom_weak 1.x API on top of
+//! [`WeakDom`][crate::WeakDom], for crates that can't move to the new API all
+//! at once. This module is only available with the `compat` feature.
+//!
+//! Most 1.x code only needs its imports changed from `rbx_dom_weak::*` to
+//! `rbx_dom_weak::compat::*`:
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use rbx_dom_weak::compat::{RbxInstanceProperties, RbxTree, RbxValue};
+//!
+//! let mut tree = RbxTree::new(RbxInstanceProperties {
+//!     name: "Game".to_owned(),
+//!     class_name: "DataModel".to_owned(),
+//!     properties: HashMap::new(),
+//! });
+//!
+//! let mut properties = HashMap::new();
+//! properties.insert("Value".to_owned(), RbxValue::Bool { value: true });
+//!
+//! let root_id = tree.get_root_id();
+//! let value_id = tree.insert_instance(
+//!     RbxInstanceProperties {
+//!         name: "Enabled".to_owned(),
+//!         class_name: "BoolValue".to_owned(),
+//!         properties,
+//!     },
+//!     root_id,
+//! );
+//!
+//! let instance = tree.get_instance(value_id).unwrap();
+//! assert_eq!(instance.name, "Enabled");
+//! assert_eq!(instance.get_parent_id(), Some(root_id));
+//! ```
+//!
+//! The adapters behave like their 1.x counterparts, with a few differences
+//! that come from keeping everything in a `WeakDom`:
+//!
+//! - [`RbxTree::get_instance`] and [`RbxTree::descendants`] return copies of
+//!   instances instead of references, converting their properties each time.
+//!   Changing a copy doesn't change the tree.
+//! - [`RbxTree::get_instance_mut`] returns an [`RbxInstanceMut`], which writes
+//!   its changes back to the tree when it's dropped. Unlike `&mut
+//!   RbxInstance`, it needs to be bound with `let mut` to be changed.
+//! - Properties whose types didn't exist in 1.x, like `Axes` and `Faces`, are
+//!   left out of those copies. Writing an instance back never removes them.
+//! - [`RbxId`] is [`Ref`][crate::types::Ref], and [`RbxValueType`] is
+//!   [`VariantType`][crate::types::VariantType].
+//! - `BrickColor` and `SharedString` values use the types from
+//!   [`rbx_dom_weak::types`][crate::types]. Their methods are mostly the same,
+//!   but `SharedString::md5_hash` is gone in favor of `SharedString::hash`.
+//! - [`RbxTree::descendants`] visits instances in depth-first order, with
+//!   children in the order they were added, instead of in no particular
+//!   order.
+//!
+//! Anything not listed here, like `UnresolvedRbxValue`, has no adapter. Use
+//! [`RbxTree::into_dom`] to get at the `WeakDom` and the rest of the current
+//! API.
+
+mod tree;
+mod value;
+
+pub use self::{tree::*, value::*};
+
+pub use rbx_types::{BrickColor, SharedString};
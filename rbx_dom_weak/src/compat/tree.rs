@@ -0,0 +1,519 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    ops::{Deref, DerefMut},
+};
+
+use rbx_types::{Ref, Variant};
+
+use crate::{Instance, InstanceBuilder, WeakDom};
+
+use super::value::{has_rbx_value, RbxValue};
+
+/// The ID of an instance, which is the same as a [`Ref`].
+pub type RbxId = Ref;
+
+/// The name, class, and properties of an instance, used to create new
+/// instances in an [`RbxTree`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RbxInstanceProperties {
+    /// The instance's name.
+    pub name: String,
+
+    /// The instance's class.
+    pub class_name: String,
+
+    /// The instance's properties, not including `Name` or `ClassName`.
+    pub properties: HashMap<String, RbxValue>,
+}
+
+impl RbxInstanceProperties {
+    fn into_builder(self) -> InstanceBuilder {
+        InstanceBuilder::new(self.class_name)
+            .with_name(self.name)
+            .with_properties(sorted(self.properties))
+    }
+}
+
+/// A copy of an instance from an [`RbxTree`], which derefs to its
+/// [`RbxInstanceProperties`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RbxInstance {
+    data: RbxInstanceProperties,
+    id: RbxId,
+    children: Vec<RbxId>,
+    parent: Option<RbxId>,
+}
+
+impl RbxInstance {
+    fn copy_of(instance: &Instance) -> Self {
+        let properties = instance
+            .properties
+            .iter()
+            .filter_map(|(key, value)| {
+                let value = RbxValue::try_from(value).ok()?;
+                Some((key.clone(), value))
+            })
+            .collect();
+
+        RbxInstance {
+            data: RbxInstanceProperties {
+                name: instance.name.clone(),
+                class_name: instance.class.clone(),
+                properties,
+            },
+            id: instance.referent(),
+            children: instance.children().to_vec(),
+            parent: Some(instance.parent()).filter(Ref::is_some),
+        }
+    }
+
+    /// Returns the instance's ID.
+    pub fn get_id(&self) -> RbxId {
+        self.id
+    }
+
+    /// Returns the ID of the instance's parent, or `None` for the root.
+    pub fn get_parent_id(&self) -> Option<RbxId> {
+        self.parent
+    }
+
+    /// Returns the IDs of the instance's children.
+    pub fn get_children_ids(&self) -> &[RbxId] {
+        &self.children
+    }
+
+    /// Sorts the instance's children with a key extraction function.
+    pub fn sort_children_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(RbxId) -> K,
+        K: Ord,
+    {
+        self.children.sort_by_key(|&id| f(id));
+    }
+
+    /// Sorts the instance's children with a key extraction function, without
+    /// keeping children with equal keys in order.
+    pub fn sort_children_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(RbxId) -> K,
+        K: Ord,
+    {
+        self.children.sort_unstable_by_key(|&id| f(id));
+    }
+}
+
+impl Deref for RbxInstance {
+    type Target = RbxInstanceProperties;
+
+    fn deref(&self) -> &RbxInstanceProperties {
+        &self.data
+    }
+}
+
+impl DerefMut for RbxInstance {
+    fn deref_mut(&mut self) -> &mut RbxInstanceProperties {
+        &mut self.data
+    }
+}
+
+/// A copy of an instance that writes its name, class, properties, and the
+/// order of its children back to the tree when it's dropped. Created by
+/// [`RbxTree::get_instance_mut`].
+#[derive(Debug)]
+pub struct RbxInstanceMut<'a> {
+    dom: &'a mut WeakDom,
+    instance: RbxInstance,
+}
+
+impl Deref for RbxInstanceMut<'_> {
+    type Target = RbxInstance;
+
+    fn deref(&self) -> &RbxInstance {
+        &self.instance
+    }
+}
+
+impl DerefMut for RbxInstanceMut<'_> {
+    fn deref_mut(&mut self) -> &mut RbxInstance {
+        &mut self.instance
+    }
+}
+
+impl Drop for RbxInstanceMut<'_> {
+    fn drop(&mut self) {
+        let target = self.dom.get_by_ref_mut(self.instance.id).unwrap();
+        let copy = &mut self.instance.data;
+
+        target.name = std::mem::take(&mut copy.name);
+        target.class = std::mem::take(&mut copy.class_name);
+
+        // Properties that couldn't be copied are kept, while the rest are
+        // only kept if they're still in the copy.
+        target
+            .properties
+            .retain(|key, value| copy.properties.contains_key(key) || !has_rbx_value(value.ty()));
+
+        for (key, value) in sorted(std::mem::take(&mut copy.properties)) {
+            target.properties.insert(key, value);
+        }
+
+        target.children = std::mem::take(&mut self.instance.children);
+    }
+}
+
+/// A tree of instances with the rbx_dom_weak 1.x API, kept in a [`WeakDom`].
+#[derive(Debug)]
+pub struct RbxTree {
+    dom: WeakDom,
+}
+
+impl RbxTree {
+    /// Creates a tree with a root instance with the given properties.
+    pub fn new(root_properties: RbxInstanceProperties) -> RbxTree {
+        RbxTree {
+            dom: WeakDom::new(root_properties.into_builder()),
+        }
+    }
+
+    /// Returns the `WeakDom` that the tree is kept in.
+    pub fn dom(&self) -> &WeakDom {
+        &self.dom
+    }
+
+    /// Returns the `WeakDom` that the tree is kept in, for making changes
+    /// with the current API.
+    pub fn dom_mut(&mut self) -> &mut WeakDom {
+        &mut self.dom
+    }
+
+    /// Unwraps the tree into the `WeakDom` that it's kept in.
+    pub fn into_dom(self) -> WeakDom {
+        self.dom
+    }
+
+    /// Returns the ID of the root instance.
+    pub fn get_root_id(&self) -> RbxId {
+        self.dom.root_ref()
+    }
+
+    /// Iterates over the IDs of every instance in the tree.
+    pub fn iter_all_ids(&self) -> impl Iterator<Item = RbxId> + '_ {
+        self.dom
+            .descendants(self.dom.root_ref())
+            .map(Instance::referent)
+    }
+
+    /// Returns a copy of the instance with the given ID.
+    pub fn get_instance(&self, id: RbxId) -> Option<RbxInstance> {
+        self.dom.get_by_ref(id).map(RbxInstance::copy_of)
+    }
+
+    /// Returns a copy of the instance with the given ID, which writes any
+    /// changes made to it back to the tree when it's dropped.
+    pub fn get_instance_mut(&mut self, id: RbxId) -> Option<RbxInstanceMut<'_>> {
+        let instance = self.dom.get_by_ref(id).map(RbxInstance::copy_of)?;
+
+        Some(RbxInstanceMut {
+            dom: &mut self.dom,
+            instance,
+        })
+    }
+
+    /// Moves an instance and its descendants into another tree, keeping their
+    /// IDs.
+    ///
+    /// ## Panics
+    /// Panics if `source_id` isn't in this tree or is its root, or if
+    /// `dest_parent_id` isn't in `dest_tree`.
+    pub fn move_instance(
+        &mut self,
+        source_id: RbxId,
+        dest_tree: &mut RbxTree,
+        dest_parent_id: RbxId,
+    ) {
+        self.dom
+            .transfer(source_id, &mut dest_tree.dom, dest_parent_id);
+    }
+
+    /// Moves an instance to a new parent within the tree.
+    ///
+    /// ## Panics
+    /// Panics if either instance isn't in the tree, if `id` is the root, or if
+    /// `dest_parent_id` is `id` or one of its descendants.
+    pub fn set_parent(&mut self, id: RbxId, dest_parent_id: RbxId) {
+        if self
+            .dom
+            .descendants(id)
+            .any(|instance| instance.referent() == dest_parent_id)
+        {
+            panic!("set_parent cannot create circular references");
+        }
+
+        self.dom.transfer_within(id, dest_parent_id);
+    }
+
+    /// Adds a new instance to the tree, returning its ID.
+    ///
+    /// ## Panics
+    /// Panics if `parent_id` isn't in the tree.
+    pub fn insert_instance(
+        &mut self,
+        properties: RbxInstanceProperties,
+        parent_id: RbxId,
+    ) -> RbxId {
+        self.dom.insert(parent_id, properties.into_builder())
+    }
+
+    /// Removes an instance and its descendants from the tree, returning them
+    /// as a new tree with the same IDs. Returns `None` if the instance isn't
+    /// in the tree.
+    ///
+    /// ## Panics
+    /// Panics if `root_id` is the root of the tree.
+    pub fn remove_instance(&mut self, root_id: RbxId) -> Option<RbxTree> {
+        if root_id == self.dom.root_ref() {
+            panic!("Cannot remove root ID from tree!");
+        }
+
+        let instance = self.dom.get_by_ref(root_id)?;
+        let children = instance.children().to_vec();
+        let builder = InstanceBuilder::new(instance.class.clone())
+            .with_referent(root_id)
+            .with_name(instance.name.clone())
+            .with_properties(instance.properties.clone());
+
+        let mut removed = WeakDom::new(builder);
+        for child in children {
+            self.dom.transfer(child, &mut removed, root_id);
+        }
+        self.dom.destroy(root_id);
+
+        Some(RbxTree { dom: removed })
+    }
+
+    /// Iterates over copies of the descendants of the given instance, not
+    /// including the instance itself, in depth-first order.
+    ///
+    /// ## Panics
+    /// Panics if `id` isn't in the tree.
+    pub fn descendants(&self, id: RbxId) -> Descendants<'_> {
+        let mut inner = self.dom.descendants(id);
+        inner
+            .next()
+            .expect("Cannot enumerate descendants of an instance not in the tree");
+
+        Descendants { inner }
+    }
+}
+
+impl From<WeakDom> for RbxTree {
+    fn from(dom: WeakDom) -> RbxTree {
+        RbxTree { dom }
+    }
+}
+
+impl From<RbxTree> for WeakDom {
+    fn from(tree: RbxTree) -> WeakDom {
+        tree.dom
+    }
+}
+
+/// An iterator over copies of the descendants of an instance, created by
+/// [`RbxTree::descendants`].
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    inner: crate::Descendants<'a>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = RbxInstance;
+
+    fn next(&mut self) -> Option<RbxInstance> {
+        self.inner.next().map(RbxInstance::copy_of)
+    }
+}
+
+/// Orders properties by name, so that converting a `HashMap` of them always
+/// gives the same property order.
+fn sorted(properties: HashMap<String, RbxValue>) -> Vec<(String, Variant)> {
+    let mut properties: Vec<(String, Variant)> = properties
+        .into_iter()
+        .map(|(key, value)| (key, value.into()))
+        .collect();
+    properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    properties
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::Faces;
+
+    fn folder(name: &str) -> RbxInstanceProperties {
+        RbxInstanceProperties {
+            name: name.to_owned(),
+            class_name: "Folder".to_owned(),
+            properties: HashMap::new(),
+        }
+    }
+
+    fn names(tree: &RbxTree, id: RbxId) -> Vec<String> {
+        tree.descendants(id)
+            .map(|instance| instance.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let mut tree = RbxTree::new(folder("Root"));
+        let root_id = tree.get_root_id();
+
+        let mut properties = folder("Value");
+        properties.class_name = "IntValue".to_owned();
+        properties
+            .properties
+            .insert("Value".to_owned(), RbxValue::Int32 { value: 5 });
+        let value_id = tree.insert_instance(properties, root_id);
+
+        let value = tree.get_instance(value_id).unwrap();
+        assert_eq!(value.get_id(), value_id);
+        assert_eq!(value.get_parent_id(), Some(root_id));
+        assert_eq!(value.class_name, "IntValue");
+        assert_eq!(
+            value.properties.get("Value"),
+            Some(&RbxValue::Int32 { value: 5 })
+        );
+
+        let root = tree.get_instance(root_id).unwrap();
+        assert_eq!(root.get_parent_id(), None);
+        assert_eq!(root.get_children_ids(), &[value_id]);
+
+        assert_eq!(
+            tree.dom()
+                .get_by_ref(value_id)
+                .unwrap()
+                .properties
+                .get("Value"),
+            Some(&Variant::Int32(5))
+        );
+    }
+
+    #[test]
+    fn changes_are_written_back() {
+        let mut tree = RbxTree::new(folder("Root"));
+        let root_id = tree.get_root_id();
+        let a_id = tree.insert_instance(folder("A"), root_id);
+        let b_id = tree.insert_instance(folder("B"), root_id);
+
+        tree.dom_mut()
+            .get_by_ref_mut(root_id)
+            .unwrap()
+            .properties
+            .insert("Faces".to_owned(), Faces::TOP.into());
+
+        {
+            let mut root = tree.get_instance_mut(root_id).unwrap();
+            assert!(!root.properties.contains_key("Faces"));
+
+            root.name = "Renamed".to_owned();
+            root.properties
+                .insert("Archivable".to_owned(), RbxValue::Bool { value: false });
+            root.sort_children_by_key(|id| if id == b_id { 0 } else { 1 });
+        }
+
+        let root = tree.dom().root();
+        assert_eq!(root.name, "Renamed");
+        assert_eq!(root.children(), &[b_id, a_id]);
+        assert_eq!(
+            root.properties.get("Archivable"),
+            Some(&Variant::Bool(false))
+        );
+        assert_eq!(root.properties.get("Faces"), Some(&Faces::TOP.into()));
+
+        tree.get_instance_mut(root_id)
+            .unwrap()
+            .properties
+            .remove("Archivable");
+        assert!(tree.dom().root().properties.get("Archivable").is_none());
+    }
+
+    #[test]
+    fn descendants() {
+        let mut tree = RbxTree::new(folder("Root"));
+        let root_id = tree.get_root_id();
+        let a_id = tree.insert_instance(folder("A"), root_id);
+        tree.insert_instance(folder("B"), a_id);
+        tree.insert_instance(folder("C"), root_id);
+
+        assert_eq!(names(&tree, root_id), ["A", "B", "C"]);
+        assert_eq!(names(&tree, a_id), ["B"]);
+        assert_eq!(tree.iter_all_ids().count(), 4);
+    }
+
+    #[test]
+    fn move_and_set_parent() {
+        let mut source = RbxTree::new(folder("Source"));
+        let source_root_id = source.get_root_id();
+        let a_id = source.insert_instance(folder("A"), source_root_id);
+        let b_id = source.insert_instance(folder("B"), a_id);
+
+        let mut dest = RbxTree::new(folder("Dest"));
+        let dest_root_id = dest.get_root_id();
+        source.move_instance(a_id, &mut dest, dest_root_id);
+
+        assert!(source.get_instance(a_id).is_none());
+        assert!(source.get_instance(b_id).is_none());
+        assert_eq!(names(&dest, dest_root_id), ["A", "B"]);
+
+        dest.set_parent(b_id, dest_root_id);
+        assert_eq!(
+            dest.get_instance(b_id).unwrap().get_parent_id(),
+            Some(dest_root_id)
+        );
+        assert!(dest
+            .get_instance(a_id)
+            .unwrap()
+            .get_children_ids()
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "circular")]
+    fn set_parent_to_descendant() {
+        let mut tree = RbxTree::new(folder("Root"));
+        let root_id = tree.get_root_id();
+        let a_id = tree.insert_instance(folder("A"), root_id);
+        let b_id = tree.insert_instance(folder("B"), a_id);
+
+        tree.set_parent(a_id, b_id);
+    }
+
+    #[test]
+    fn remove_instance() {
+        let mut tree = RbxTree::new(folder("Root"));
+        let root_id = tree.get_root_id();
+        let a_id = tree.insert_instance(folder("A"), root_id);
+        let b_id = tree.insert_instance(folder("B"), a_id);
+
+        let removed = tree.remove_instance(a_id).unwrap();
+        assert!(tree.get_instance(a_id).is_none());
+        assert!(tree
+            .get_instance(root_id)
+            .unwrap()
+            .get_children_ids()
+            .is_empty());
+
+        assert_eq!(removed.get_root_id(), a_id);
+        assert_eq!(removed.get_instance(a_id).unwrap().name, "A");
+        assert_eq!(removed.get_instance(a_id).unwrap().get_parent_id(), None);
+        assert_eq!(
+            removed.get_instance(b_id).unwrap().get_parent_id(),
+            Some(a_id)
+        );
+
+        assert!(tree.remove_instance(a_id).is_none());
+    }
+}
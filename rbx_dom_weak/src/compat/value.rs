@@ -0,0 +1,580 @@
+use std::{convert::TryFrom, error::Error, fmt};
+
+use rbx_types::{
+    BrickColor, CFrame, Color3, Color3uint8, CustomPhysicalProperties, Enum, NumberRange, Ref,
+    SharedString, UDim, UDim2, Variant, VariantType, Vector2, Vector2int16, Vector3, Vector3int16,
+};
+
+use super::RbxId;
+
+/// The type of an [`RbxValue`]. This is the same as the current
+/// [`VariantType`], which has a variant for every 1.x type.
+pub type RbxValueType = VariantType;
+
+/// A property value in the shape used by rbx_dom_weak 1.x. It can be converted
+/// into a [`Variant`], and back again for every type that 1.x supported.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum RbxValue {
+    BinaryString { value: Vec<u8> },
+    BrickColor { value: BrickColor },
+    Bool { value: bool },
+    CFrame { value: [f32; 12] },
+    Color3 { value: [f32; 3] },
+    Color3uint8 { value: [u8; 3] },
+    ColorSequence { value: ColorSequence },
+    Content { value: String },
+    Enum { value: u32 },
+    Float32 { value: f32 },
+    Float64 { value: f64 },
+    Int32 { value: i32 },
+    Int64 { value: i64 },
+    NumberRange { value: (f32, f32) },
+    NumberSequence { value: NumberSequence },
+    PhysicalProperties { value: Option<PhysicalProperties> },
+    Ray { value: Ray },
+    Rect { value: Rect },
+    Ref { value: Option<RbxId> },
+    SharedString { value: SharedString },
+    String { value: String },
+    UDim { value: (f32, i32) },
+    UDim2 { value: (f32, i32, f32, i32) },
+    Vector2 { value: [f32; 2] },
+    Vector2int16 { value: [i16; 2] },
+    Vector3 { value: [f32; 3] },
+    Vector3int16 { value: [i16; 3] },
+}
+
+impl RbxValue {
+    /// Returns the type of this value.
+    pub fn get_type(&self) -> RbxValueType {
+        match self {
+            RbxValue::BinaryString { .. } => VariantType::BinaryString,
+            RbxValue::BrickColor { .. } => VariantType::BrickColor,
+            RbxValue::Bool { .. } => VariantType::Bool,
+            RbxValue::CFrame { .. } => VariantType::CFrame,
+            RbxValue::Color3 { .. } => VariantType::Color3,
+            RbxValue::Color3uint8 { .. } => VariantType::Color3uint8,
+            RbxValue::ColorSequence { .. } => VariantType::ColorSequence,
+            RbxValue::Content { .. } => VariantType::Content,
+            RbxValue::Enum { .. } => VariantType::Enum,
+            RbxValue::Float32 { .. } => VariantType::Float32,
+            RbxValue::Float64 { .. } => VariantType::Float64,
+            RbxValue::Int32 { .. } => VariantType::Int32,
+            RbxValue::Int64 { .. } => VariantType::Int64,
+            RbxValue::NumberRange { .. } => VariantType::NumberRange,
+            RbxValue::NumberSequence { .. } => VariantType::NumberSequence,
+            RbxValue::PhysicalProperties { .. } => VariantType::PhysicalProperties,
+            RbxValue::Ray { .. } => VariantType::Ray,
+            RbxValue::Rect { .. } => VariantType::Rect,
+            RbxValue::Ref { .. } => VariantType::Ref,
+            RbxValue::SharedString { .. } => VariantType::SharedString,
+            RbxValue::String { .. } => VariantType::String,
+            RbxValue::UDim { .. } => VariantType::UDim,
+            RbxValue::UDim2 { .. } => VariantType::UDim2,
+            RbxValue::Vector2 { .. } => VariantType::Vector2,
+            RbxValue::Vector2int16 { .. } => VariantType::Vector2int16,
+            RbxValue::Vector3 { .. } => VariantType::Vector3,
+            RbxValue::Vector3int16 { .. } => VariantType::Vector3int16,
+        }
+    }
+}
+
+/// Tells whether values of the given type can be converted to an `RbxValue`.
+pub(crate) fn has_rbx_value(ty: VariantType) -> bool {
+    matches!(
+        ty,
+        VariantType::BinaryString
+            | VariantType::BrickColor
+            | VariantType::Bool
+            | VariantType::CFrame
+            | VariantType::Color3
+            | VariantType::Color3uint8
+            | VariantType::ColorSequence
+            | VariantType::Content
+            | VariantType::Enum
+            | VariantType::Float32
+            | VariantType::Float64
+            | VariantType::Int32
+            | VariantType::Int64
+            | VariantType::NumberRange
+            | VariantType::NumberSequence
+            | VariantType::PhysicalProperties
+            | VariantType::Ray
+            | VariantType::Rect
+            | VariantType::Ref
+            | VariantType::SharedString
+            | VariantType::String
+            | VariantType::UDim
+            | VariantType::UDim2
+            | VariantType::Vector2
+            | VariantType::Vector2int16
+            | VariantType::Vector3
+            | VariantType::Vector3int16
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(missing_docs)]
+pub struct ColorSequence {
+    pub keypoints: Vec<ColorSequenceKeypoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(missing_docs)]
+pub struct ColorSequenceKeypoint {
+    pub time: f32,
+    pub color: [f32; 3],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(missing_docs)]
+pub struct NumberSequence {
+    pub keypoints: Vec<NumberSequenceKeypoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(missing_docs)]
+pub struct NumberSequenceKeypoint {
+    pub time: f32,
+    pub value: f32,
+    pub envelope: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(missing_docs)]
+pub struct Ray {
+    pub origin: [f32; 3],
+    pub direction: [f32; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(missing_docs)]
+pub struct Rect {
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+/// Custom physical properties. An `RbxValue::PhysicalProperties` of `None`
+/// means the part uses its material's default properties.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(missing_docs)]
+pub struct PhysicalProperties {
+    pub density: f32,
+    pub friction: f32,
+    pub elasticity: f32,
+    pub friction_weight: f32,
+    pub elasticity_weight: f32,
+}
+
+/// The error returned when converting a [`Variant`] whose type didn't exist in
+/// rbx_dom_weak 1.x, like `Axes` or `Faces`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedVariant {
+    ty: VariantType,
+}
+
+impl UnsupportedVariant {
+    /// The type of the value that couldn't be converted.
+    pub fn ty(&self) -> VariantType {
+        self.ty
+    }
+}
+
+impl fmt::Display for UnsupportedVariant {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            output,
+            "{:?} values have no equivalent in rbx_dom_weak 1.x",
+            self.ty
+        )
+    }
+}
+
+impl Error for UnsupportedVariant {}
+
+impl From<RbxValue> for Variant {
+    fn from(value: RbxValue) -> Variant {
+        match value {
+            RbxValue::BinaryString { value } => Variant::BinaryString(value.into()),
+            RbxValue::BrickColor { value } => Variant::BrickColor(value),
+            RbxValue::Bool { value } => Variant::Bool(value),
+            RbxValue::CFrame { value } => Variant::CFrame(CFrame::from_components(&value)),
+            RbxValue::Color3 { value: [r, g, b] } => Variant::Color3(Color3::new(r, g, b)),
+            RbxValue::Color3uint8 { value: [r, g, b] } => {
+                Variant::Color3uint8(Color3uint8::new(r, g, b))
+            }
+            RbxValue::ColorSequence { value } => {
+                let keypoints = value
+                    .keypoints
+                    .into_iter()
+                    .map(
+                        |ColorSequenceKeypoint {
+                             time,
+                             color: [r, g, b],
+                         }| {
+                            rbx_types::ColorSequenceKeypoint::new(time, Color3::new(r, g, b))
+                        },
+                    )
+                    .collect();
+
+                Variant::ColorSequence(rbx_types::ColorSequence { keypoints })
+            }
+            RbxValue::Content { value } => Variant::Content(value.into()),
+            RbxValue::Enum { value } => Variant::Enum(Enum::from_u32(value)),
+            RbxValue::Float32 { value } => Variant::Float32(value),
+            RbxValue::Float64 { value } => Variant::Float64(value),
+            RbxValue::Int32 { value } => Variant::Int32(value),
+            RbxValue::Int64 { value } => Variant::Int64(value),
+            RbxValue::NumberRange { value: (min, max) } => {
+                Variant::NumberRange(NumberRange::new(min, max))
+            }
+            RbxValue::NumberSequence { value } => {
+                let keypoints = value
+                    .keypoints
+                    .into_iter()
+                    .map(|keypoint| {
+                        rbx_types::NumberSequenceKeypoint::new(
+                            keypoint.time,
+                            keypoint.value,
+                            keypoint.envelope,
+                        )
+                    })
+                    .collect();
+
+                Variant::NumberSequence(rbx_types::NumberSequence { keypoints })
+            }
+            RbxValue::PhysicalProperties { value } => Variant::PhysicalProperties(match value {
+                Some(properties) => {
+                    rbx_types::PhysicalProperties::Custom(CustomPhysicalProperties {
+                        density: properties.density,
+                        friction: properties.friction,
+                        elasticity: properties.elasticity,
+                        friction_weight: properties.friction_weight,
+                        elasticity_weight: properties.elasticity_weight,
+                    })
+                }
+                None => rbx_types::PhysicalProperties::Default,
+            }),
+            RbxValue::Ray { value } => Variant::Ray(rbx_types::Ray::new(
+                vector3(value.origin),
+                vector3(value.direction),
+            )),
+            RbxValue::Rect { value } => Variant::Rect(rbx_types::Rect::new(
+                Vector2::new(value.min.0, value.min.1),
+                Vector2::new(value.max.0, value.max.1),
+            )),
+            RbxValue::Ref { value } => Variant::Ref(value.unwrap_or_else(Ref::none)),
+            RbxValue::SharedString { value } => Variant::SharedString(value),
+            RbxValue::String { value } => Variant::from(value),
+            RbxValue::UDim {
+                value: (scale, offset),
+            } => Variant::UDim(UDim::new(scale, offset)),
+            RbxValue::UDim2 {
+                value: (x_scale, x_offset, y_scale, y_offset),
+            } => Variant::UDim2(UDim2::new(
+                UDim::new(x_scale, x_offset),
+                UDim::new(y_scale, y_offset),
+            )),
+            RbxValue::Vector2 { value: [x, y] } => Variant::Vector2(Vector2::new(x, y)),
+            RbxValue::Vector2int16 { value: [x, y] } => {
+                Variant::Vector2int16(Vector2int16::new(x, y))
+            }
+            RbxValue::Vector3 { value } => Variant::Vector3(vector3(value)),
+            RbxValue::Vector3int16 { value: [x, y, z] } => {
+                Variant::Vector3int16(Vector3int16::new(x, y, z))
+            }
+        }
+    }
+}
+
+impl TryFrom<&Variant> for RbxValue {
+    type Error = UnsupportedVariant;
+
+    fn try_from(value: &Variant) -> Result<RbxValue, UnsupportedVariant> {
+        Ok(match value {
+            Variant::BinaryString(value) => {
+                let value: &[u8] = value.as_ref();
+                RbxValue::BinaryString {
+                    value: value.to_vec(),
+                }
+            }
+            Variant::BrickColor(value) => RbxValue::BrickColor { value: *value },
+            Variant::Bool(value) => RbxValue::Bool { value: *value },
+            Variant::CFrame(value) => RbxValue::CFrame {
+                value: value.to_components(),
+            },
+            Variant::Color3(value) => RbxValue::Color3 {
+                value: [value.r, value.g, value.b],
+            },
+            Variant::Color3uint8(value) => RbxValue::Color3uint8 {
+                value: [value.r, value.g, value.b],
+            },
+            Variant::ColorSequence(value) => RbxValue::ColorSequence {
+                value: ColorSequence {
+                    keypoints: value
+                        .keypoints
+                        .iter()
+                        .map(|keypoint| ColorSequenceKeypoint {
+                            time: keypoint.time,
+                            color: [keypoint.color.r, keypoint.color.g, keypoint.color.b],
+                        })
+                        .collect(),
+                },
+            },
+            Variant::Content(value) => {
+                let value: &str = value.as_ref();
+                RbxValue::Content {
+                    value: value.to_owned(),
+                }
+            }
+            Variant::Enum(value) => RbxValue::Enum {
+                value: value.to_u32(),
+            },
+            Variant::Float32(value) => RbxValue::Float32 { value: *value },
+            Variant::Float64(value) => RbxValue::Float64 { value: *value },
+            Variant::Int32(value) => RbxValue::Int32 { value: *value },
+            Variant::Int64(value) => RbxValue::Int64 { value: *value },
+            Variant::NumberRange(value) => RbxValue::NumberRange {
+                value: (value.min, value.max),
+            },
+            Variant::NumberSequence(value) => RbxValue::NumberSequence {
+                value: NumberSequence {
+                    keypoints: value
+                        .keypoints
+                        .iter()
+                        .map(|keypoint| NumberSequenceKeypoint {
+                            time: keypoint.time,
+                            value: keypoint.value,
+                            envelope: keypoint.envelope,
+                        })
+                        .collect(),
+                },
+            },
+            Variant::PhysicalProperties(value) => RbxValue::PhysicalProperties {
+                value: match value {
+                    rbx_types::PhysicalProperties::Custom(properties) => Some(PhysicalProperties {
+                        density: properties.density,
+                        friction: properties.friction,
+                        elasticity: properties.elasticity,
+                        friction_weight: properties.friction_weight,
+                        elasticity_weight: properties.elasticity_weight,
+                    }),
+                    rbx_types::PhysicalProperties::Default => None,
+                },
+            },
+            Variant::Ray(value) => RbxValue::Ray {
+                value: Ray {
+                    origin: [value.origin.x, value.origin.y, value.origin.z],
+                    direction: [value.direction.x, value.direction.y, value.direction.z],
+                },
+            },
+            Variant::Rect(value) => RbxValue::Rect {
+                value: Rect {
+                    min: (value.min.x, value.min.y),
+                    max: (value.max.x, value.max.y),
+                },
+            },
+            Variant::Ref(value) => RbxValue::Ref {
+                value: Some(*value).filter(Ref::is_some),
+            },
+            Variant::SharedString(value) => RbxValue::SharedString {
+                value: value.clone(),
+            },
+            Variant::String(value) => RbxValue::String {
+                value: value.to_string(),
+            },
+            Variant::UDim(value) => RbxValue::UDim {
+                value: (value.scale, value.offset),
+            },
+            Variant::UDim2(value) => RbxValue::UDim2 {
+                value: (value.x.scale, value.x.offset, value.y.scale, value.y.offset),
+            },
+            Variant::Vector2(value) => RbxValue::Vector2 {
+                value: [value.x, value.y],
+            },
+            Variant::Vector2int16(value) => RbxValue::Vector2int16 {
+                value: [value.x, value.y],
+            },
+            Variant::Vector3(value) => RbxValue::Vector3 {
+                value: [value.x, value.y, value.z],
+            },
+            Variant::Vector3int16(value) => RbxValue::Vector3int16 {
+                value: [value.x, value.y, value.z],
+            },
+            _ => return Err(UnsupportedVariant { ty: value.ty() }),
+        })
+    }
+}
+
+impl TryFrom<Variant> for RbxValue {
+    type Error = UnsupportedVariant;
+
+    fn try_from(value: Variant) -> Result<RbxValue, UnsupportedVariant> {
+        RbxValue::try_from(&value)
+    }
+}
+
+fn vector3([x, y, z]: [f32; 3]) -> Vector3 {
+    Vector3::new(x, y, z)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::{Axes, Faces, Region3, Region3int16};
+
+    /// Converts the value to a `Variant` and back, checking that it has the
+    /// same type at each step.
+    fn round_trip(value: RbxValue) {
+        let variant = Variant::from(value.clone());
+        assert_eq!(variant.ty(), value.get_type());
+
+        let converted = RbxValue::try_from(&variant).unwrap();
+        assert_eq!(converted, value);
+        assert_eq!(RbxValue::try_from(variant).unwrap(), value);
+    }
+
+    #[test]
+    fn every_type_round_trips() {
+        round_trip(RbxValue::BinaryString {
+            value: b"\x00binary\xFF".to_vec(),
+        });
+        round_trip(RbxValue::BrickColor {
+            value: BrickColor::ReallyRed,
+        });
+        round_trip(RbxValue::Bool { value: true });
+        round_trip(RbxValue::CFrame {
+            value: [1.0, 2.0, 3.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0],
+        });
+        round_trip(RbxValue::Color3 {
+            value: [0.25, 0.5, 1.0],
+        });
+        round_trip(RbxValue::Color3uint8 {
+            value: [64, 128, 255],
+        });
+        round_trip(RbxValue::ColorSequence {
+            value: ColorSequence {
+                keypoints: vec![
+                    ColorSequenceKeypoint {
+                        time: 0.0,
+                        color: [1.0, 0.0, 0.0],
+                    },
+                    ColorSequenceKeypoint {
+                        time: 1.0,
+                        color: [0.0, 0.0, 1.0],
+                    },
+                ],
+            },
+        });
+        round_trip(RbxValue::Content {
+            value: "rbxassetid://1818".to_owned(),
+        });
+        round_trip(RbxValue::Enum { value: 3 });
+        round_trip(RbxValue::Float32 { value: 0.5 });
+        round_trip(RbxValue::Float64 { value: -0.25 });
+        round_trip(RbxValue::Int32 { value: -7 });
+        round_trip(RbxValue::Int64 { value: 1 << 40 });
+        round_trip(RbxValue::NumberRange { value: (1.0, 5.0) });
+        round_trip(RbxValue::NumberSequence {
+            value: NumberSequence {
+                keypoints: vec![
+                    NumberSequenceKeypoint {
+                        time: 0.0,
+                        value: 2.0,
+                        envelope: 0.5,
+                    },
+                    NumberSequenceKeypoint {
+                        time: 1.0,
+                        value: 4.0,
+                        envelope: 0.0,
+                    },
+                ],
+            },
+        });
+        round_trip(RbxValue::PhysicalProperties { value: None });
+        round_trip(RbxValue::PhysicalProperties {
+            value: Some(PhysicalProperties {
+                density: 0.7,
+                friction: 0.3,
+                elasticity: 0.5,
+                friction_weight: 1.0,
+                elasticity_weight: 1.0,
+            }),
+        });
+        round_trip(RbxValue::Ray {
+            value: Ray {
+                origin: [1.0, 2.0, 3.0],
+                direction: [0.0, -1.0, 0.0],
+            },
+        });
+        round_trip(RbxValue::Rect {
+            value: Rect {
+                min: (0.0, 0.0),
+                max: (16.0, 9.0),
+            },
+        });
+        round_trip(RbxValue::Ref { value: None });
+        round_trip(RbxValue::Ref {
+            value: Some(Ref::new()),
+        });
+        round_trip(RbxValue::SharedString {
+            value: SharedString::new(b"shared".to_vec()),
+        });
+        round_trip(RbxValue::String {
+            value: "Hello, world!".to_owned(),
+        });
+        round_trip(RbxValue::UDim { value: (0.5, 10) });
+        round_trip(RbxValue::UDim2 {
+            value: (0.5, 10, 1.0, -20),
+        });
+        round_trip(RbxValue::Vector2 { value: [1.0, 2.0] });
+        round_trip(RbxValue::Vector2int16 { value: [-1, 2] });
+        round_trip(RbxValue::Vector3 {
+            value: [1.0, 2.0, 3.0],
+        });
+        round_trip(RbxValue::Vector3int16 { value: [-1, 2, -3] });
+    }
+
+    #[test]
+    fn converted_values() {
+        assert_eq!(
+            Variant::from(RbxValue::Ref { value: None }),
+            Variant::Ref(Ref::none())
+        );
+        assert_eq!(
+            Variant::from(RbxValue::UDim2 {
+                value: (0.5, 10, 1.0, -20),
+            }),
+            Variant::UDim2(UDim2::new(UDim::new(0.5, 10), UDim::new(1.0, -20)))
+        );
+        assert_eq!(
+            Variant::from(RbxValue::PhysicalProperties { value: None }),
+            Variant::PhysicalProperties(rbx_types::PhysicalProperties::Default)
+        );
+    }
+
+    #[test]
+    fn new_types_are_unsupported() {
+        let values = [
+            Variant::Axes(Axes::X),
+            Variant::Faces(Faces::TOP),
+            Variant::Region3(Region3::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 1.0, 1.0),
+            )),
+            Variant::Region3int16(Region3int16::new(
+                Vector3int16::new(0, 0, 0),
+                Vector3int16::new(1, 1, 1),
+            )),
+        ];
+
+        for value in &values {
+            let err = RbxValue::try_from(value).unwrap_err();
+            assert_eq!(err.ty(), value.ty());
+            assert!(!has_rbx_value(value.ty()));
+        }
+    }
+}
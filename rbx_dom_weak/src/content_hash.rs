@@ -0,0 +1,726 @@
+use std::{collections::HashMap, fmt};
+
+use rbx_types::{Ref, Variant};
+use serde::{ser, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::WeakDom;
+
+/// Options available for computing a [`content_hash`].
+#[derive(Debug, Clone)]
+pub struct ContentHashOptions {
+    canonicalize_nan: bool,
+}
+
+impl ContentHashOptions {
+    /// Constructs a `ContentHashOptions` with all values set to their
+    /// defaults.
+    #[inline]
+    pub fn new() -> Self {
+        ContentHashOptions {
+            canonicalize_nan: true,
+        }
+    }
+
+    /// Determines whether all NaN floats hash the same.
+    ///
+    /// There are many different NaN bit patterns, and which one a value ends
+    /// up with can change when moving between file formats even though every
+    /// one of them means the same thing. Setting this to false hashes NaNs by
+    /// their exact bits like every other float.
+    ///
+    /// This is on by default.
+    #[inline]
+    pub fn canonicalize_nan(self, canonicalize_nan: bool) -> Self {
+        ContentHashOptions { canonicalize_nan }
+    }
+}
+
+impl Default for ContentHashOptions {
+    fn default() -> ContentHashOptions {
+        ContentHashOptions::new()
+    }
+}
+
+/// Computes a SHA-256 hash of the logical contents of `root` and its
+/// descendants.
+///
+/// Two trees that hold the same instances hash the same, regardless of which
+/// file format they were read from or what order their properties were
+/// inserted in. The hash covers each instance's class, name, properties, and
+/// children, in order. Floats are hashed by their bits. Refs are hashed as the
+/// path from `root` to the instance they point to, so the hash doesn't depend
+/// on the Ref values the dom happened to assign. Refs that point outside of
+/// `root` hash the same as each other.
+///
+/// The exact hash of a tree may change between versions of rbx_dom_weak, so
+/// hashes should only be compared when they were computed by the same version.
+///
+/// ## Panics
+/// Panics if `root` does not refer to an instance in `dom`.
+pub fn content_hash(dom: &WeakDom, root: Ref, options: ContentHashOptions) -> [u8; 32] {
+    let paths = RefPaths::new(dom, root);
+    let mut hasher = Sha256::new();
+
+    hash_instance(&mut hasher, dom, root, &paths, &options);
+
+    hasher.finalize().into()
+}
+
+fn hash_instance(
+    hasher: &mut Sha256,
+    dom: &WeakDom,
+    referent: Ref,
+    paths: &RefPaths,
+    options: &ContentHashOptions,
+) {
+    let instance = dom.get_by_ref(referent).unwrap();
+
+    hash_str(hasher, &instance.class);
+    hash_str(hasher, &instance.name);
+
+    let mut properties: Vec<_> = instance.properties.iter().collect();
+    properties.sort_unstable_by_key(|(key, _)| *key);
+
+    hasher.update((properties.len() as u64).to_le_bytes());
+    for (key, value) in properties {
+        hash_str(hasher, key);
+        hash_value(hasher, value, paths, options);
+    }
+
+    hasher.update((instance.children().len() as u64).to_le_bytes());
+    for &child in instance.children() {
+        hash_instance(hasher, dom, child, paths, options);
+    }
+}
+
+fn hash_value(
+    hasher: &mut Sha256,
+    value: &Variant,
+    paths: &RefPaths,
+    options: &ContentHashOptions,
+) {
+    match value {
+        // Refs and SharedStrings don't have a serialized form that reflects
+        // their contents, so we handle them ourselves. Binary data is hashed
+        // by its content hash, which values kept in a blob already have, so
+        // hashing them never has to read from the blob's store.
+        Variant::Ref(referent) => {
+            hash_str(hasher, "Ref");
+
+            match paths.path_to(*referent) {
+                RefPath::Null => hasher.update([0]),
+                RefPath::Outside => hasher.update([1]),
+                RefPath::Inside(path) => {
+                    hasher.update([2]);
+                    hasher.update((path.len() as u64).to_le_bytes());
+                    for index in path {
+                        hasher.update((index as u64).to_le_bytes());
+                    }
+                }
+            }
+        }
+        Variant::SharedString(shared_string) => {
+            hash_str(hasher, "SharedString");
+            hash_bytes(hasher, shared_string.hash().as_bytes());
+        }
+        Variant::BinaryString(binary_string) => {
+            hash_str(hasher, "BinaryString");
+            hash_bytes(hasher, &binary_string.content_hash());
+        }
+        _ => {
+            let mut value_hasher = ValueHasher { hasher, options };
+
+            if value.serialize(&mut value_hasher).is_err() {
+                // Every other kind of value can be serialized, but if one
+                // ever can't, its debug representation is the next best thing.
+                hash_str(hasher, &format!("{:?}", value));
+            }
+        }
+    }
+}
+
+fn hash_str(hasher: &mut Sha256, value: &str) {
+    hash_bytes(hasher, value.as_bytes());
+}
+
+fn hash_bytes(hasher: &mut Sha256, value: &[u8]) {
+    hasher.update((value.len() as u64).to_le_bytes());
+    hasher.update(value);
+}
+
+/// Knows where every instance under a root is, so that Refs can be hashed by
+/// their position in the tree instead of their value.
+struct RefPaths<'a> {
+    dom: &'a WeakDom,
+    root: Ref,
+
+    /// The index of every descendant of the root in its parent's list of
+    /// children.
+    child_indices: HashMap<Ref, usize>,
+}
+
+enum RefPath {
+    Null,
+    Outside,
+    Inside(Vec<usize>),
+}
+
+impl<'a> RefPaths<'a> {
+    fn new(dom: &'a WeakDom, root: Ref) -> Self {
+        let mut child_indices = HashMap::new();
+        let mut to_visit = vec![root];
+
+        while let Some(referent) = to_visit.pop() {
+            let instance = dom
+                .get_by_ref(referent)
+                .expect("content_hash was given a Ref that isn't in the dom");
+
+            for (index, &child) in instance.children().iter().enumerate() {
+                child_indices.insert(child, index);
+                to_visit.push(child);
+            }
+        }
+
+        RefPaths {
+            dom,
+            root,
+            child_indices,
+        }
+    }
+
+    fn path_to(&self, referent: Ref) -> RefPath {
+        if referent.is_none() {
+            return RefPath::Null;
+        }
+
+        if referent != self.root && !self.child_indices.contains_key(&referent) {
+            return RefPath::Outside;
+        }
+
+        let mut path = Vec::new();
+        let mut current = referent;
+
+        while current != self.root {
+            path.push(self.child_indices[&current]);
+            current = self.dom.get_by_ref(current).unwrap().parent();
+        }
+
+        path.reverse();
+        RefPath::Inside(path)
+    }
+}
+
+/// A serde serializer that feeds values into a hasher in an unambiguous form.
+struct ValueHasher<'a> {
+    hasher: &'a mut Sha256,
+    options: &'a ContentHashOptions,
+}
+
+#[derive(Debug)]
+struct HashError(String);
+
+impl fmt::Display for HashError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(output, "{}", self.0)
+    }
+}
+
+impl std::error::Error for HashError {}
+
+impl ser::Error for HashError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        HashError(message.to_string())
+    }
+}
+
+impl ValueHasher<'_> {
+    fn tag(&mut self, tag: u8) {
+        self.hasher.update([tag]);
+    }
+
+    fn str(&mut self, value: &str) {
+        hash_str(self.hasher, value);
+    }
+}
+
+impl<'a, 'b> ser::Serializer for &'a mut ValueHasher<'b> {
+    type Ok = ();
+    type Error = HashError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, value: bool) -> Result<(), HashError> {
+        self.tag(1);
+        self.hasher.update([value as u8]);
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), HashError> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), HashError> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), HashError> {
+        self.serialize_i64(value.into())
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), HashError> {
+        self.tag(2);
+        self.hasher.update(value.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), HashError> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), HashError> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), HashError> {
+        self.serialize_u64(value.into())
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), HashError> {
+        self.tag(3);
+        self.hasher.update(value.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<(), HashError> {
+        let value = if self.options.canonicalize_nan && value.is_nan() {
+            f32::NAN
+        } else {
+            value
+        };
+
+        self.tag(4);
+        self.hasher.update(value.to_bits().to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<(), HashError> {
+        let value = if self.options.canonicalize_nan && value.is_nan() {
+            f64::NAN
+        } else {
+            value
+        };
+
+        self.tag(5);
+        self.hasher.update(value.to_bits().to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), HashError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), HashError> {
+        self.tag(6);
+        self.str(value);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), HashError> {
+        self.tag(7);
+        hash_bytes(self.hasher, value);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), HashError> {
+        self.tag(8);
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), HashError> {
+        self.tag(9);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), HashError> {
+        self.tag(10);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), HashError> {
+        self.tag(11);
+        self.str(name);
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), HashError> {
+        self.tag(12);
+        self.str(variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        self.tag(13);
+        self.str(name);
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        self.tag(14);
+        self.str(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, HashError> {
+        self.tag(15);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, HashError> {
+        self.tag(15);
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, _len: usize) -> Result<Self, HashError> {
+        self.tag(16);
+        self.str(name);
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, HashError> {
+        self.tag(17);
+        self.str(variant);
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, HashError> {
+        self.tag(18);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self, HashError> {
+        self.tag(19);
+        self.str(name);
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, HashError> {
+        self.tag(20);
+        self.str(variant);
+        Ok(self)
+    }
+}
+
+// Sequences of every kind are written as their elements followed by an end
+// marker. Elements always start with a tag below 32, so the marker can't be
+// mistaken for one.
+const END: u8 = 0xFF;
+
+impl ser::SerializeSeq for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), HashError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HashError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        self.str(key);
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut ValueHasher<'_> {
+    type Ok = ();
+    type Error = HashError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), HashError> {
+        self.str(key);
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), HashError> {
+        self.tag(END);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::Vector3;
+
+    use crate::InstanceBuilder;
+
+    fn hash(dom: &WeakDom) -> [u8; 32] {
+        content_hash(dom, dom.root_ref(), ContentHashOptions::new())
+    }
+
+    fn welded_parts() -> WeakDom {
+        let part0 = InstanceBuilder::new("Part")
+            .with_name("Part0")
+            .with_property("Anchored", true)
+            .with_property("Size", Vector3::new(4.0, 1.0, 2.0));
+        let part1 = InstanceBuilder::new("Part").with_name("Part1");
+
+        let weld = InstanceBuilder::new("Weld")
+            .with_property("Part0", part0.referent())
+            .with_property("Part1", part1.referent());
+
+        WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_child(part0)
+                .with_child(part1)
+                .with_child(weld),
+        )
+    }
+
+    #[test]
+    fn independent_of_refs_and_property_order() {
+        let first = welded_parts();
+
+        // Building the same tree again gives every instance a new Ref and
+        // inserts Part0's properties in a different order.
+        let part0 = InstanceBuilder::new("Part")
+            .with_name("Part0")
+            .with_property("Size", Vector3::new(4.0, 1.0, 2.0))
+            .with_property("Anchored", true);
+        let part1 = InstanceBuilder::new("Part").with_name("Part1");
+
+        let weld = InstanceBuilder::new("Weld")
+            .with_property("Part1", part1.referent())
+            .with_property("Part0", part0.referent());
+
+        let second = WeakDom::new(
+            InstanceBuilder::new("Model")
+                .with_child(part0)
+                .with_child(part1)
+                .with_child(weld),
+        );
+
+        assert_eq!(hash(&first), hash(&second));
+    }
+
+    #[test]
+    fn changes_with_content() {
+        let original = welded_parts();
+        let original_hash = hash(&original);
+
+        let mut changed = welded_parts();
+        let part0_ref = changed.root().children()[0];
+        changed
+            .get_by_ref_mut(part0_ref)
+            .unwrap()
+            .properties
+            .insert("Anchored".to_owned(), false.into());
+        assert_ne!(hash(&changed), original_hash);
+
+        // Pointing the weld at a different part changes the hash, too.
+        let mut rewelded = welded_parts();
+        let part1_ref = rewelded.root().children()[1];
+        let weld_ref = rewelded.root().children()[2];
+        rewelded
+            .get_by_ref_mut(weld_ref)
+            .unwrap()
+            .properties
+            .insert("Part0".to_owned(), part1_ref.into());
+        assert_ne!(hash(&rewelded), original_hash);
+
+        let mut renamed = welded_parts();
+        let part1_ref = renamed.root().children()[1];
+        renamed.get_by_ref_mut(part1_ref).unwrap().name = "Other".to_owned();
+        assert_ne!(hash(&renamed), original_hash);
+    }
+
+    #[test]
+    fn refs_outside_root() {
+        let dom = welded_parts();
+        let weld_ref = dom.root().children()[2];
+
+        // Hashed on its own, the weld's targets are both outside of the root,
+        // which is different from them being null.
+        let mut unwelded = welded_parts();
+        let unwelded_weld_ref = unwelded.root().children()[2];
+        let weld = unwelded.get_by_ref_mut(unwelded_weld_ref).unwrap();
+        weld.properties
+            .insert("Part0".to_owned(), Ref::none().into());
+        weld.properties
+            .insert("Part1".to_owned(), Ref::none().into());
+
+        assert_ne!(
+            content_hash(&dom, weld_ref, ContentHashOptions::new()),
+            content_hash(&unwelded, unwelded_weld_ref, ContentHashOptions::new())
+        );
+    }
+
+    #[test]
+    fn nan_policy() {
+        let quiet =
+            WeakDom::new(InstanceBuilder::new("NumberValue").with_property("Value", f64::NAN));
+        let other_nan = f64::from_bits(f64::NAN.to_bits() | 1);
+        let signaling =
+            WeakDom::new(InstanceBuilder::new("NumberValue").with_property("Value", other_nan));
+
+        let canonical = ContentHashOptions::new();
+        assert_eq!(
+            content_hash(&quiet, quiet.root_ref(), canonical.clone()),
+            content_hash(&signaling, signaling.root_ref(), canonical)
+        );
+
+        let exact = ContentHashOptions::new().canonicalize_nan(false);
+        assert_ne!(
+            content_hash(&quiet, quiet.root_ref(), exact.clone()),
+            content_hash(&signaling, signaling.root_ref(), exact)
+        );
+    }
+
+    #[test]
+    fn unreadable_blobs() {
+        use std::{fs, sync::Arc};
+
+        use rbx_types::{BinaryString, Blob, BlobStore, SharedString, TempFileStore};
+
+        let store = TempFileStore::new().unwrap();
+        let dir = store.path().to_owned();
+        let store: Arc<dyn BlobStore> = Arc::new(store);
+
+        let stored = |data: &[u8]| Blob::store(&store, data).unwrap();
+        let blobs = WeakDom::new(
+            InstanceBuilder::new("MeshPart")
+                .with_property("Tags", BinaryString::from_blob(stored(b"tags")))
+                .with_property(
+                    "PhysicalConfigData",
+                    SharedString::from_blob(stored(b"physics")).unwrap(),
+                ),
+        );
+        let in_memory = WeakDom::new(
+            InstanceBuilder::new("MeshPart")
+                .with_property("Tags", BinaryString::from(b"tags".to_vec()))
+                .with_property("PhysicalConfigData", SharedString::new(b"physics".to_vec())),
+        );
+
+        // Hashing values kept in blobs doesn't read them from the store.
+        for entry in fs::read_dir(dir).unwrap() {
+            fs::remove_file(entry.unwrap().path()).unwrap();
+        }
+
+        assert_eq!(hash(&blobs), hash(&in_memory));
+    }
+}
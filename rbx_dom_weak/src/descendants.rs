@@ -0,0 +1,94 @@
+use rbx_types::Ref;
+
+use crate::{dom::WeakDom, instance::Instance};
+
+/// An iterator over an instance and all of its descendants, created by
+/// [`WeakDom::descendants`].
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    dom: &'a WeakDom,
+    to_visit: Vec<Ref>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a Instance;
+
+    fn next(&mut self) -> Option<&'a Instance> {
+        loop {
+            let referent = self.to_visit.pop()?;
+
+            if let Some(instance) = self.dom.get_by_ref(referent) {
+                // Children are pushed in reverse so that the first child is
+                // the next instance visited.
+                self.to_visit.extend(instance.children().iter().rev());
+                return Some(instance);
+            }
+        }
+    }
+}
+
+impl WeakDom {
+    /// Returns an iterator over the given instance and all of its
+    /// descendants, in depth-first order with parents before their children.
+    ///
+    /// Instances are visited lazily, so changes to the dom can't be made while
+    /// iterating. If the instance isn't in the dom, the iterator is empty.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Folder")
+    ///         .with_child(InstanceBuilder::new("Model").with_child(InstanceBuilder::new("Part")))
+    ///         .with_child(InstanceBuilder::new("Script")),
+    /// );
+    ///
+    /// let classes: Vec<&str> = dom
+    ///     .descendants(dom.root_ref())
+    ///     .map(|instance| instance.class.as_str())
+    ///     .collect();
+    ///
+    /// assert_eq!(classes, ["Folder", "Model", "Part", "Script"]);
+    /// ```
+    pub fn descendants(&self, root: Ref) -> Descendants<'_> {
+        Descendants {
+            dom: self,
+            to_visit: vec![root],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    #[test]
+    fn subtree() {
+        let model = InstanceBuilder::new("Model")
+            .with_child(InstanceBuilder::new("Part").with_name("A"))
+            .with_child(InstanceBuilder::new("Part").with_name("B"));
+        let model_ref = model.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(model)
+                .with_child(InstanceBuilder::new("Script")),
+        );
+
+        let names: Vec<&str> = dom
+            .descendants(model_ref)
+            .map(|instance| instance.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["Model", "A", "B"]);
+    }
+
+    #[test]
+    fn missing_root() {
+        let dom = WeakDom::new(InstanceBuilder::new("Folder"));
+
+        assert_eq!(dom.descendants(Ref::new()).count(), 0);
+    }
+}
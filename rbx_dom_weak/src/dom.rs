@@ -1,8 +1,15 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::OnceLock,
+};
 
 use rbx_types::Ref;
 
-use crate::instance::{Instance, InstanceBuilder};
+use crate::{
+    instance::{Instance, InstanceBuilder},
+    observer::DomObserver,
+    ref_index::RefIndex,
+};
 
 /// Represents a DOM containing one or more Roblox instances.
 ///
@@ -11,10 +18,32 @@ use crate::instance::{Instance, InstanceBuilder};
 ///
 /// When constructing instances, you'll want to create [`InstanceBuilder`]
 /// objects and insert them into the tree.
+///
+/// ## Referents
+/// Every instance is identified by a randomly generated [`Ref`]. Refs are never
+/// reused: once an instance is destroyed or transferred out of a `WeakDom`, its
+/// Ref will not refer to anything in that `WeakDom` again, and looking it up
+/// will return `None`.
+///
+/// Destroying instances frees the memory used by the instances themselves, but
+/// not the space the `WeakDom` reserved to store them. Long-lived doms that
+/// insert and destroy many instances can call [`WeakDom::shrink_to_fit`] to
+/// release that space.
+///
+/// ## Threads
+/// `WeakDom` is `Send` and `Sync`, so a dom can be moved to another thread or
+/// shared between threads behind a reference or an `Arc`.
 #[derive(Debug)]
 pub struct WeakDom {
     instances: HashMap<Ref, Instance>,
     root_ref: Ref,
+
+    /// The index used by `referencers_of`, if it's been enabled. It's built
+    /// the first time it's used after being enabled or thrown away.
+    pub(crate) ref_index: Option<OnceLock<RefIndex>>,
+
+    /// The observer registered with `set_observer`, if there is one.
+    pub(crate) observer: Option<Box<dyn DomObserver>>,
 }
 
 impl WeakDom {
@@ -30,6 +59,7 @@ impl WeakDom {
                 children: Vec::with_capacity(builder.children.len()),
                 parent: Ref::none(),
                 name: builder.name,
+                has_name: builder.has_name,
                 class: builder.class,
                 properties: builder.properties,
             },
@@ -38,6 +68,8 @@ impl WeakDom {
         let mut dom = WeakDom {
             root_ref,
             instances,
+            ref_index: None,
+            observer: None,
         };
 
         for child in builder.children {
@@ -59,9 +91,30 @@ impl WeakDom {
 
     /// Returns a _mutable_ reference to the root instance of the `WeakDom`.
     pub fn root_mut(&mut self) -> &mut Instance {
+        self.invalidate_ref_index();
         self.instances.get_mut(&self.root_ref).unwrap()
     }
 
+    /// Returns the number of instances in the `WeakDom`, including the root
+    /// instance.
+    // A WeakDom always contains its root instance, so it can never be empty.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Returns the number of instances the `WeakDom` can hold without
+    /// allocating more space.
+    pub fn capacity(&self) -> usize {
+        self.instances.capacity()
+    }
+
+    /// Releases as much of the space reserved for storing instances as
+    /// possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.instances.shrink_to_fit();
+    }
+
     /// Returns a reference to an instance by referent, or `None` if it is not
     /// found.
     pub fn get_by_ref(&self, referent: Ref) -> Option<&Instance> {
@@ -71,16 +124,79 @@ impl WeakDom {
     /// Returns a _mutable_ reference to an instance by referent, or `None` if
     /// it is not found.
     pub fn get_by_ref_mut(&mut self, referent: Ref) -> Option<&mut Instance> {
+        self.invalidate_ref_index();
         self.instances.get_mut(&referent)
     }
 
+    /// Returns the names of an instance and all of its ancestors joined by
+    /// periods, like `Instance:GetFullName()` in Roblox, or `None` if the
+    /// instance is not found.
+    ///
+    /// The name of the root instance is included.
+    pub fn full_name(&self, referent: Ref) -> Option<String> {
+        let mut components = Vec::new();
+        let mut current = self.get_by_ref(referent)?;
+
+        loop {
+            components.push(current.name.as_str());
+
+            match self.get_by_ref(current.parent) {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+
+        components.reverse();
+        Some(components.join("."))
+    }
+
     /// Insert a new instance into the DOM with the given parent.
     ///
     /// ## Panics
-    /// Panics if `parent_ref` does not refer to an instance in the DOM.
+    /// Panics if `parent_ref` does not refer to an instance in the DOM, or if
+    /// the builder or any of its descendants has a referent that already
+    /// refers to one. The whole tree is checked first, so nothing is inserted
+    /// in that case.
     pub fn insert(&mut self, parent_ref: Ref, builder: InstanceBuilder) -> Ref {
+        if !self.instances.contains_key(&parent_ref) {
+            panic!("cannot insert into parent that does not exist");
+        }
+
+        if let Some(taken) = self.taken_referent(&builder) {
+            panic!(
+                "cannot insert an instance with referent {}, which is already in the DOM",
+                taken
+            );
+        }
+
+        self.insert_unchecked(parent_ref, builder)
+    }
+
+    /// Finds the first referent in the given tree that's already in the DOM or
+    /// used more than once by the tree itself.
+    fn taken_referent(&self, builder: &InstanceBuilder) -> Option<Ref> {
+        let mut referents = HashSet::new();
+        let mut to_check = vec![builder];
+
+        while let Some(current) = to_check.pop() {
+            if self.instances.contains_key(&current.referent) || !referents.insert(current.referent)
+            {
+                return Some(current.referent);
+            }
+
+            to_check.extend(&current.children);
+        }
+
+        None
+    }
+
+    fn insert_unchecked(&mut self, parent_ref: Ref, builder: InstanceBuilder) -> Ref {
         let referent = builder.referent;
 
+        if let Some(index) = self.ref_index.as_mut().and_then(OnceLock::get_mut) {
+            index.add(referent, &builder.properties);
+        }
+
         self.instances.insert(
             referent,
             Instance {
@@ -88,20 +204,22 @@ impl WeakDom {
                 children: Vec::with_capacity(builder.children.len()),
                 parent: parent_ref,
                 name: builder.name,
+                has_name: builder.has_name,
                 class: builder.class,
                 properties: builder.properties,
             },
         );
 
-        let parent = self
-            .instances
+        self.instances
             .get_mut(&parent_ref)
-            .unwrap_or_else(|| panic!("cannot insert into parent that does not exist"));
+            .unwrap()
+            .children
+            .push(referent);
 
-        parent.children.push(referent);
+        self.notify(|observer, dom| observer.on_insert(dom, referent));
 
         for child in builder.children {
-            self.insert(referent, child);
+            self.insert_unchecked(referent, child);
         }
 
         referent
@@ -119,6 +237,10 @@ impl WeakDom {
             panic!("cannot destroy the root instance of a WeakDom");
         }
 
+        self.invalidate_ref_index();
+
+        self.notify_destroy(referent);
+
         let instance = self
             .instances
             .get(&referent)
@@ -148,12 +270,33 @@ impl WeakDom {
     /// `dest_parent_ref` does not refer to an instance in `other_dom`.
     ///
     /// Will also panic if `referent` refers to the root instance in this
-    /// `WeakDom`.
+    /// `WeakDom`, or if the instance or any of its descendants has the same
+    /// referent as an instance in `dest`. Nothing is moved in that case.
     pub fn transfer(&mut self, referent: Ref, dest: &mut WeakDom, dest_parent_ref: Ref) {
         if referent == self.root_ref {
             panic!("cannot transfer the root instance of WeakDom");
         }
 
+        if self.get_by_ref(referent).is_some() {
+            let taken = self
+                .descendants(referent)
+                .map(Instance::referent)
+                .find(|moving| dest.instances.contains_key(moving));
+
+            if let Some(taken) = taken {
+                panic!(
+                    "cannot move an instance with referent {}, which is already in the \
+                     destination DOM",
+                    taken
+                );
+            }
+        }
+
+        self.invalidate_ref_index();
+        dest.invalidate_ref_index();
+
+        self.notify_destroy(referent);
+
         let mut instance = self
             .instances
             .remove(&referent)
@@ -189,6 +332,16 @@ impl WeakDom {
             panic!("cannot move an instance into an instance that does not exist")
         });
         dest_parent.children.push(referent);
+
+        if dest.observer.is_some() {
+            let moved: Vec<Ref> = dest.descendants(referent).map(Instance::referent).collect();
+
+            dest.notify(|observer, dom| {
+                for referent in moved {
+                    observer.on_insert(dom, referent);
+                }
+            });
+        }
     }
 
     /// Move the instance with the given referent to a new parent within the
@@ -227,6 +380,18 @@ impl WeakDom {
             .get_mut(&dest_parent_ref)
             .unwrap_or_else(|| panic!("cannot move into an instance that does not exist"));
         dest_parent.children.push(referent);
+
+        self.notify(|observer, dom| {
+            observer.on_reparent(dom, referent, parent_ref, dest_parent_ref)
+        });
+    }
+
+    /// Throws away the index used by `referencers_of` after a change that it
+    /// can't keep track of, so that it's built again the next time it's used.
+    fn invalidate_ref_index(&mut self) {
+        if let Some(index) = &mut self.ref_index {
+            index.take();
+        }
     }
 }
 
@@ -235,6 +400,118 @@ mod test {
     use super::*;
 
     use crate::DomViewer;
+    use rbx_types::Vector3;
+
+    #[test]
+    fn insert_with_referent() {
+        let referent = Ref::new();
+        let child = InstanceBuilder::new("Part").with_referent(referent);
+        assert_eq!(child.referent(), referent);
+
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+
+        assert_eq!(dom.insert(root_ref, child), referent);
+        assert_eq!(dom.get_by_ref(referent).unwrap().parent(), root_ref);
+    }
+
+    #[test]
+    #[should_panic(expected = "which is already in the DOM")]
+    fn insert_duplicate_referent() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+
+        let referent = dom.insert(root_ref, InstanceBuilder::new("Part"));
+        dom.insert(
+            root_ref,
+            InstanceBuilder::new("Model").with_referent(referent),
+        );
+    }
+
+    #[test]
+    fn insert_duplicate_descendant_referent() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+        let existing = dom.insert(root_ref, InstanceBuilder::new("Part"));
+
+        let model = InstanceBuilder::new("Model").with_child(
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("Part").with_referent(existing)),
+        );
+        let model_ref = model.referent;
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            dom.insert(root_ref, model);
+        }));
+        assert!(result.is_err());
+
+        // None of the tree was inserted.
+        assert!(dom.get_by_ref(model_ref).is_none());
+        assert_eq!(dom.root().children(), [existing]);
+        assert_eq!(dom.len(), 2);
+    }
+
+    #[test]
+    fn explicit_name() {
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+            InstanceBuilder::new("Part"),
+            InstanceBuilder::new("Part").without_name(),
+            InstanceBuilder::new("Part").without_name().with_name("Part"),
+        ]));
+
+        let names: Vec<_> = dom
+            .root()
+            .children()
+            .iter()
+            .map(|id| dom.get_by_ref(*id).unwrap().explicit_name())
+            .collect();
+
+        assert_eq!(names, [Some("Part"), None, Some("Part")]);
+    }
+
+    #[test]
+    fn explicit_name_after_rename() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Part").without_name());
+        let root_ref = dom.root_ref();
+        assert_eq!(dom.root().name, "Part");
+
+        let root = dom.get_by_ref_mut(root_ref).unwrap();
+        root.name = "Renamed".to_owned();
+        assert_eq!(root.explicit_name(), Some("Renamed"));
+
+        root.clear_name();
+        assert_eq!(root.name, "Part");
+        assert_eq!(root.explicit_name(), None);
+    }
+
+    #[test]
+    fn transfer_duplicate_referent() {
+        let child = InstanceBuilder::new("Part").with_name("Child");
+        let child_ref = child.referent;
+        let target = InstanceBuilder::new("Folder").with_child(child);
+        let target_ref = target.referent;
+
+        let mut source = WeakDom::new(InstanceBuilder::new("Folder").with_child(target));
+        let mut dest = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let dest_root = dest.root_ref();
+        dest.insert(
+            dest_root,
+            InstanceBuilder::new("Part")
+                .with_name("Existing")
+                .with_referent(child_ref),
+        );
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            source.transfer(target_ref, &mut dest, dest_root);
+        }));
+        assert!(result.is_err());
+
+        // Neither dom was changed.
+        assert_eq!(source.get_by_ref(child_ref).unwrap().name, "Child");
+        assert_eq!(dest.get_by_ref(child_ref).unwrap().name, "Existing");
+        assert!(source.get_by_ref(target_ref).is_some());
+        assert_eq!(dest.root().children(), [child_ref]);
+    }
 
     #[test]
     fn transfer() {
@@ -292,4 +569,77 @@ mod test {
         // This snapshot should have Root and SpawnLocation contained in Dest.
         insta::assert_yaml_snapshot!(viewer.view_children(&dom));
     }
+
+    /// Tools commonly share a dom between threads, so we make sure that's
+    /// always possible.
+    #[allow(dead_code)]
+    fn dom_is_send_and_sync() {
+        fn trait_test<T: Send + Sync>() {}
+
+        trait_test::<WeakDom>();
+        trait_test::<Instance>();
+        trait_test::<InstanceBuilder>();
+    }
+
+    #[test]
+    fn shrink_after_churn() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+        let kept_ref = dom.insert(root_ref, InstanceBuilder::new("Folder"));
+
+        let mut stale_refs = Vec::new();
+
+        for i in 0..1_000_000 {
+            // Keep a handful of instances alive at any given time so that the
+            // dom is doing something closer to real work.
+            if i % 1000 == 0 {
+                dom.insert(kept_ref, InstanceBuilder::new("Folder"));
+                continue;
+            }
+
+            let referent = dom.insert(root_ref, InstanceBuilder::new("Folder"));
+            dom.destroy(referent);
+
+            if i % 1000 == 1 {
+                stale_refs.push(referent);
+            }
+        }
+
+        assert_eq!(dom.len(), 1002);
+        assert!(dom.capacity() >= dom.len());
+
+        dom.shrink_to_fit();
+
+        // HashMap can keep some extra space around, but it should be on the
+        // order of the number of live instances, not every instance ever
+        // inserted.
+        assert!(dom.capacity() < 4 * dom.len());
+
+        for referent in stale_refs {
+            assert!(dom.get_by_ref(referent).is_none());
+        }
+
+        assert_eq!(dom.get_by_ref(kept_ref).unwrap().children().len(), 1000);
+    }
+
+    #[test]
+    fn properties_keep_insertion_order() {
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Part")
+                .with_property("Transparency", 0.5f32)
+                .with_property("Anchored", true)
+                .with_property("Size", Vector3::new(1.0, 2.0, 3.0))
+                .with_property("CanCollide", false),
+        );
+
+        let root_ref = dom.root_ref();
+        let part = dom.get_by_ref_mut(root_ref).unwrap();
+
+        // Overwriting a property keeps it where it was.
+        part.properties.insert("Anchored".to_owned(), false.into());
+        part.properties.shift_remove("Size");
+
+        let names: Vec<_> = part.properties.keys().map(String::as_str).collect();
+        assert_eq!(names, ["Transparency", "Anchored", "CanCollide"]);
+    }
 }
@@ -0,0 +1,448 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use rbx_reflection::{
+    PropertyDescriptor, PropertyKind, PropertySerialization, ReflectionDatabase, Scriptability,
+};
+use rbx_types::{Ref, Variant};
+
+use crate::dom::WeakDom;
+
+/// Where the value of an [`EffectiveProperty`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PropertyOrigin {
+    /// The instance has a value for the property, and the reflection database
+    /// knows about it.
+    Stored,
+
+    /// The instance doesn't have a value for the property, so the default
+    /// listed in the reflection database is used.
+    Default,
+
+    /// The instance has a value for the property, but the reflection database
+    /// doesn't list it on the instance's class or any of its superclasses.
+    Unknown,
+
+    /// The property doesn't serialize and the instance doesn't have a value
+    /// for it, but Lua scripts can access it. These are only listed when
+    /// [`EffectivePropertiesOptions::include_scriptable`] is enabled, and
+    /// never have a value.
+    Scriptable,
+}
+
+/// One entry returned by [`WeakDom::effective_properties`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct EffectiveProperty<'a> {
+    /// The canonical name of the property, like `Size`. Values stored under an
+    /// alias, like `size`, are listed under the name it's an alias for.
+    /// Properties the reflection database doesn't know keep the name they were
+    /// stored under.
+    pub name: &'a str,
+
+    /// The value of the property. This is only `None` for properties from
+    /// [`PropertyOrigin::Scriptable`].
+    pub value: Option<&'a Variant>,
+
+    /// Where the value came from.
+    pub origin: PropertyOrigin,
+}
+
+/// Options available for [`WeakDom::effective_properties_with_options`].
+#[derive(Debug, Clone)]
+pub struct EffectivePropertiesOptions {
+    include_scriptable: bool,
+}
+
+impl EffectivePropertiesOptions {
+    /// Constructs an `EffectivePropertiesOptions` with all values set to their
+    /// defaults.
+    #[inline]
+    pub fn new() -> Self {
+        EffectivePropertiesOptions {
+            include_scriptable: false,
+        }
+    }
+
+    /// Determines whether properties that don't serialize but can be accessed
+    /// by Lua scripts, like `BasePart.Position`, are listed. They're listed
+    /// with [`PropertyOrigin::Scriptable`] and no value.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn include_scriptable(self, include_scriptable: bool) -> Self {
+        EffectivePropertiesOptions { include_scriptable }
+    }
+}
+
+impl Default for EffectivePropertiesOptions {
+    fn default() -> EffectivePropertiesOptions {
+        EffectivePropertiesOptions::new()
+    }
+}
+
+impl WeakDom {
+    /// Lists every property of an instance that would show up in a file,
+    /// sorted by canonical name.
+    ///
+    /// This includes the properties stored on the instance, along with the
+    /// defaults listed in the given reflection database for serializable
+    /// properties of its class and superclasses that the instance doesn't
+    /// have. Each entry says which of those it is.
+    ///
+    /// ## Panics
+    /// Panics if the instance is not present in the dom.
+    pub fn effective_properties<'a>(
+        &'a self,
+        referent: Ref,
+        database: &'a ReflectionDatabase,
+    ) -> Vec<EffectiveProperty<'a>> {
+        self.effective_properties_with_options(
+            referent,
+            database,
+            EffectivePropertiesOptions::default(),
+        )
+    }
+
+    /// The same as [`WeakDom::effective_properties`], but with the given
+    /// options.
+    ///
+    /// ## Panics
+    /// Panics if the instance is not present in the dom.
+    pub fn effective_properties_with_options<'a>(
+        &'a self,
+        referent: Ref,
+        database: &'a ReflectionDatabase,
+        options: EffectivePropertiesOptions,
+    ) -> Vec<EffectiveProperty<'a>> {
+        let instance = self
+            .get_by_ref(referent)
+            .expect("cannot list the properties of an instance that does not exist");
+
+        let mut entries: BTreeMap<&'a str, EffectiveProperty<'a>> = BTreeMap::new();
+
+        for (name, value) in &instance.properties {
+            let (canonical, origin) = match canonical_name(database, &instance.class, name) {
+                Some(canonical) => (canonical, PropertyOrigin::Stored),
+                None => (name.as_str(), PropertyOrigin::Unknown),
+            };
+
+            let entry = EffectiveProperty {
+                name: canonical,
+                value: Some(value),
+                origin,
+            };
+
+            // If a value is stored under both a canonical name and one of its
+            // aliases, the one under the canonical name wins.
+            match entries.entry(canonical) {
+                Entry::Vacant(vacant) => {
+                    vacant.insert(entry);
+                }
+                Entry::Occupied(mut occupied) => {
+                    if name == canonical {
+                        occupied.insert(entry);
+                    }
+                }
+            }
+        }
+
+        let mut class_name = Some(instance.class.as_str());
+
+        while let Some(class) = class_name.and_then(|name| database.classes.get(name)) {
+            for descriptor in class.properties.values() {
+                let name = descriptor.name.as_ref();
+                if entries.contains_key(name) {
+                    continue;
+                }
+
+                let serializes = match &descriptor.kind {
+                    PropertyKind::Canonical {
+                        serialization: PropertySerialization::DoesNotSerialize,
+                    } => false,
+                    PropertyKind::Canonical { .. } => true,
+                    _ => continue,
+                };
+
+                if serializes {
+                    if let Some(value) = default_value(database, &instance.class, name) {
+                        entries.insert(
+                            name,
+                            EffectiveProperty {
+                                name,
+                                value: Some(value),
+                                origin: PropertyOrigin::Default,
+                            },
+                        );
+                    }
+                } else if options.include_scriptable && is_scriptable(descriptor) {
+                    entries.insert(
+                        name,
+                        EffectiveProperty {
+                            name,
+                            value: None,
+                            origin: PropertyOrigin::Scriptable,
+                        },
+                    );
+                }
+            }
+
+            class_name = class.superclass.as_deref();
+        }
+
+        entries.into_values().collect()
+    }
+}
+
+/// Finds the canonical name of a property on a class or any of its
+/// superclasses, following aliases.
+fn canonical_name<'db>(
+    database: &'db ReflectionDatabase<'db>,
+    class_name: &str,
+    property_name: &str,
+) -> Option<&'db str> {
+    let mut class_name = Some(class_name);
+
+    while let Some(class) = class_name.and_then(|name| database.classes.get(name)) {
+        if let Some(descriptor) = class.properties.get(property_name) {
+            return match &descriptor.kind {
+                PropertyKind::Alias { alias_for } => Some(alias_for.as_ref()),
+                _ => Some(descriptor.name.as_ref()),
+            };
+        }
+
+        class_name = class.superclass.as_deref();
+    }
+
+    None
+}
+
+fn default_value<'db>(
+    database: &'db ReflectionDatabase<'db>,
+    class_name: &str,
+    property_name: &str,
+) -> Option<&'db Variant> {
+    let mut class_name = Some(class_name);
+
+    while let Some(class) = class_name.and_then(|name| database.classes.get(name)) {
+        if let Some(value) = class.default_properties.get(property_name) {
+            return Some(value);
+        }
+
+        class_name = class.superclass.as_deref();
+    }
+
+    None
+}
+
+fn is_scriptable(descriptor: &PropertyDescriptor) -> bool {
+    !matches!(descriptor.scriptability, Scriptability::None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_reflection::{ClassDescriptor, DataType};
+    use rbx_types::{VariantType, Vector3};
+
+    use crate::InstanceBuilder;
+
+    fn descriptor(
+        name: &'static str,
+        ty: VariantType,
+        serialization: PropertySerialization<'static>,
+        scriptability: Scriptability,
+    ) -> PropertyDescriptor<'static> {
+        let mut descriptor = PropertyDescriptor::new(name, DataType::Value(ty));
+        descriptor.kind = PropertyKind::Canonical { serialization };
+        descriptor.scriptability = scriptability;
+        descriptor
+    }
+
+    fn alias(
+        name: &'static str,
+        ty: VariantType,
+        alias_for: &'static str,
+    ) -> PropertyDescriptor<'static> {
+        let mut descriptor = PropertyDescriptor::new(name, DataType::Value(ty));
+        descriptor.kind = PropertyKind::Alias {
+            alias_for: alias_for.into(),
+        };
+        descriptor
+    }
+
+    fn database() -> ReflectionDatabase<'static> {
+        let mut base_part = ClassDescriptor::new("BasePart");
+        for descriptor in [
+            descriptor(
+                "Anchored",
+                VariantType::Bool,
+                PropertySerialization::Serializes,
+                Scriptability::ReadWrite,
+            ),
+            descriptor(
+                "Size",
+                VariantType::Vector3,
+                PropertySerialization::SerializesAs("size".into()),
+                Scriptability::ReadWrite,
+            ),
+            alias("size", VariantType::Vector3, "Size"),
+            descriptor(
+                "Transparency",
+                VariantType::Float32,
+                PropertySerialization::Serializes,
+                Scriptability::ReadWrite,
+            ),
+            descriptor(
+                "Position",
+                VariantType::Vector3,
+                PropertySerialization::DoesNotSerialize,
+                Scriptability::ReadWrite,
+            ),
+            descriptor(
+                "ReceiveAge",
+                VariantType::Float32,
+                PropertySerialization::DoesNotSerialize,
+                Scriptability::None,
+            ),
+        ] {
+            base_part
+                .properties
+                .insert(descriptor.name.clone(), descriptor);
+        }
+
+        let mut part = ClassDescriptor::new("Part");
+        part.superclass = Some("BasePart".into());
+        part.default_properties
+            .insert("Anchored".into(), Variant::Bool(false));
+        part.default_properties
+            .insert("Size".into(), Vector3::new(4.0, 1.2, 2.0).into());
+        part.default_properties
+            .insert("Transparency".into(), Variant::Float32(0.0));
+
+        let mut database = ReflectionDatabase::new();
+        database.classes.insert("BasePart".into(), base_part);
+        database.classes.insert("Part".into(), part);
+        database
+    }
+
+    /// A Part with two stored overrides, one of them under an alias, and a
+    /// property the database doesn't know about.
+    fn part_dom() -> (WeakDom, Ref) {
+        let part = InstanceBuilder::new("Part")
+            .with_property("Anchored", true)
+            .with_property("size", Vector3::new(1.0, 1.0, 1.0))
+            .with_property("Glow", 0.5f32);
+        let part_ref = part.referent();
+
+        (WeakDom::new(part), part_ref)
+    }
+
+    fn summarize(properties: Vec<EffectiveProperty<'_>>) -> Vec<(&str, PropertyOrigin)> {
+        properties
+            .into_iter()
+            .map(|property| (property.name, property.origin))
+            .collect()
+    }
+
+    #[test]
+    fn stored_and_default() {
+        let (dom, part_ref) = part_dom();
+        let database = database();
+
+        let properties = dom.effective_properties(part_ref, &database);
+
+        assert_eq!(
+            properties[2],
+            EffectiveProperty {
+                name: "Size",
+                value: Some(&Vector3::new(1.0, 1.0, 1.0).into()),
+                origin: PropertyOrigin::Stored,
+            }
+        );
+        assert_eq!(
+            properties[3],
+            EffectiveProperty {
+                name: "Transparency",
+                value: Some(&Variant::Float32(0.0)),
+                origin: PropertyOrigin::Default,
+            }
+        );
+        assert_eq!(
+            summarize(properties),
+            [
+                ("Anchored", PropertyOrigin::Stored),
+                ("Glow", PropertyOrigin::Unknown),
+                ("Size", PropertyOrigin::Stored),
+                ("Transparency", PropertyOrigin::Default),
+            ]
+        );
+    }
+
+    #[test]
+    fn include_scriptable() {
+        let (dom, part_ref) = part_dom();
+        let database = database();
+
+        let options = EffectivePropertiesOptions::new().include_scriptable(true);
+        let properties = dom.effective_properties_with_options(part_ref, &database, options);
+
+        assert_eq!(
+            properties[2],
+            EffectiveProperty {
+                name: "Position",
+                value: None,
+                origin: PropertyOrigin::Scriptable,
+            }
+        );
+        assert_eq!(
+            summarize(properties),
+            [
+                ("Anchored", PropertyOrigin::Stored),
+                ("Glow", PropertyOrigin::Unknown),
+                ("Position", PropertyOrigin::Scriptable),
+                ("Size", PropertyOrigin::Stored),
+                ("Transparency", PropertyOrigin::Default),
+            ]
+        );
+    }
+
+    #[test]
+    fn canonical_name_wins_over_alias() {
+        let part = InstanceBuilder::new("Part")
+            .with_property("size", Vector3::new(1.0, 1.0, 1.0))
+            .with_property("Size", Vector3::new(2.0, 2.0, 2.0));
+        let part_ref = part.referent();
+        let dom = WeakDom::new(part);
+        let database = database();
+
+        let properties = dom.effective_properties(part_ref, &database);
+        let size = properties
+            .iter()
+            .find(|property| property.name == "Size")
+            .unwrap();
+
+        assert_eq!(size.value, Some(&Vector3::new(2.0, 2.0, 2.0).into()));
+        assert_eq!(
+            properties
+                .iter()
+                .filter(|property| property.name == "Size")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn unknown_class() {
+        let sparkles = InstanceBuilder::new("Sparkles").with_property("Enabled", true);
+        let sparkles_ref = sparkles.referent();
+        let dom = WeakDom::new(sparkles);
+        let database = database();
+
+        assert_eq!(
+            summarize(dom.effective_properties(sparkles_ref, &database)),
+            [("Enabled", PropertyOrigin::Unknown)]
+        );
+    }
+}
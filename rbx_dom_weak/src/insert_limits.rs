@@ -0,0 +1,429 @@
+use std::{collections::HashSet, error::Error, fmt};
+
+use rbx_types::Ref;
+
+use crate::{dom::WeakDom, instance::InstanceBuilder, stats::property_size};
+
+/// Limits on the trees that [`WeakDom::insert_limited`] will insert, for trees
+/// that come from somewhere untrusted, like a network sync protocol.
+#[derive(Debug, Clone)]
+pub struct InsertLimits {
+    max_instances: usize,
+    max_depth: usize,
+    max_property_bytes: usize,
+}
+
+impl InsertLimits {
+    /// Constructs an `InsertLimits` with all values set to their defaults.
+    #[inline]
+    pub fn new() -> Self {
+        InsertLimits {
+            max_instances: 100_000,
+            max_depth: 1000,
+            max_property_bytes: 16 * 1024 * 1024,
+        }
+    }
+
+    /// Sets the most instances that one tree can contain, counting the
+    /// instance at its top.
+    ///
+    /// This is 100,000 by default.
+    #[inline]
+    pub fn max_instances(self, max_instances: usize) -> Self {
+        InsertLimits {
+            max_instances,
+            ..self
+        }
+    }
+
+    /// Sets how deeply instances can be nested in one tree. The instance at
+    /// the top of the tree has a depth of one.
+    ///
+    /// This is 1000 by default.
+    #[inline]
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        InsertLimits { max_depth, ..self }
+    }
+
+    /// Sets the most bytes that the properties of a single instance can take
+    /// up, counting the length of each name along with the contents of
+    /// strings and other variable-length values. Values with a fixed size
+    /// count as the size of a `Variant`. This is the same estimate that
+    /// [`DomStats`][crate::DomStats] reports.
+    ///
+    /// This is 16 MiB by default.
+    #[inline]
+    pub fn max_property_bytes(self, max_property_bytes: usize) -> Self {
+        InsertLimits {
+            max_property_bytes,
+            ..self
+        }
+    }
+}
+
+impl Default for InsertLimits {
+    fn default() -> InsertLimits {
+        InsertLimits::new()
+    }
+}
+
+/// The error returned by [`WeakDom::insert_limited`]. Instances are named by
+/// the full name they would have had in the dom, like `Workspace.Model.Part`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InsertError {
+    /// The parent that the tree was going to be inserted into isn't in the
+    /// dom.
+    MissingParent {
+        /// The referent that was given as the parent.
+        parent: Ref,
+    },
+
+    /// The tree has more instances than
+    /// [`InsertLimits::max_instances`] allows.
+    TooManyInstances {
+        /// The first instance past the limit.
+        instance: String,
+
+        /// The limit that was exceeded.
+        limit: usize,
+    },
+
+    /// The tree is nested more deeply than [`InsertLimits::max_depth`]
+    /// allows.
+    TooDeep {
+        /// The first instance past the limit.
+        instance: String,
+
+        /// The limit that was exceeded.
+        limit: usize,
+    },
+
+    /// An instance's properties take up more bytes than
+    /// [`InsertLimits::max_property_bytes`] allows.
+    PropertiesTooLarge {
+        /// The instance with the properties.
+        instance: String,
+
+        /// How many bytes the properties take up.
+        bytes: usize,
+
+        /// The limit that was exceeded.
+        limit: usize,
+    },
+
+    /// An instance's referent is already used by an instance in the dom or by
+    /// another instance in the tree.
+    DuplicateReferent {
+        /// The instance with the referent.
+        instance: String,
+
+        /// The referent that was used more than once.
+        referent: Ref,
+    },
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InsertError::MissingParent { parent } => write!(
+                output,
+                "The parent with referent {:?} was not present in the dom",
+                parent
+            ),
+            InsertError::TooManyInstances { instance, limit } => write!(
+                output,
+                "Instance {} is past the limit of {} instances in one tree",
+                instance, limit
+            ),
+            InsertError::TooDeep { instance, limit } => write!(
+                output,
+                "Instance {} is nested more than {} levels deep",
+                instance, limit
+            ),
+            InsertError::PropertiesTooLarge {
+                instance,
+                bytes,
+                limit,
+            } => write!(
+                output,
+                "The properties of instance {} take up {} bytes, more than the limit of {}",
+                instance, bytes, limit
+            ),
+            InsertError::DuplicateReferent { instance, referent } => write!(
+                output,
+                "Instance {} has the referent {:?}, which is already in use",
+                instance, referent
+            ),
+        }
+    }
+}
+
+impl Error for InsertError {}
+
+impl WeakDom {
+    /// Inserts a tree of instances into the dom with the given parent, like
+    /// [`WeakDom::insert`], after checking it against the given limits.
+    ///
+    /// The whole tree is checked before anything is inserted, so the dom is
+    /// left unchanged when an error is returned. Besides the limits, the
+    /// tree's referents are checked to make sure that none of them are already
+    /// in use, which is an error here instead of a panic like in `insert`.
+    pub fn insert_limited(
+        &mut self,
+        parent_ref: Ref,
+        builder: InstanceBuilder,
+        limits: &InsertLimits,
+    ) -> Result<Ref, InsertError> {
+        let parent_name = self
+            .full_name(parent_ref)
+            .ok_or(InsertError::MissingParent { parent: parent_ref })?;
+
+        // Every instance that's been checked, along with the index of its
+        // parent, so that errors can name the instance they're about.
+        let mut checked: Vec<(&InstanceBuilder, Option<usize>)> = Vec::new();
+        let mut to_check = vec![(&builder, None, 1)];
+        let mut referents = HashSet::new();
+
+        let name_of = |checked: &[(&InstanceBuilder, Option<usize>)], index: usize| {
+            let mut components = Vec::new();
+            let mut current = Some(index);
+
+            while let Some(index) = current {
+                let (builder, parent) = checked[index];
+                components.push(builder.name.as_str());
+                current = parent;
+            }
+
+            components.push(parent_name.as_str());
+            components.reverse();
+            components.join(".")
+        };
+
+        while let Some((current, parent, depth)) = to_check.pop() {
+            let index = checked.len();
+            checked.push((current, parent));
+
+            if index >= limits.max_instances {
+                return Err(InsertError::TooManyInstances {
+                    instance: name_of(&checked, index),
+                    limit: limits.max_instances,
+                });
+            }
+
+            if depth > limits.max_depth {
+                return Err(InsertError::TooDeep {
+                    instance: name_of(&checked, index),
+                    limit: limits.max_depth,
+                });
+            }
+
+            if self.get_by_ref(current.referent).is_some() || !referents.insert(current.referent) {
+                return Err(InsertError::DuplicateReferent {
+                    instance: name_of(&checked, index),
+                    referent: current.referent,
+                });
+            }
+
+            let bytes = current
+                .properties
+                .iter()
+                .map(|(name, value)| property_size(name, value))
+                .sum();
+
+            if bytes > limits.max_property_bytes {
+                return Err(InsertError::PropertiesTooLarge {
+                    instance: name_of(&checked, index),
+                    bytes,
+                    limit: limits.max_property_bytes,
+                });
+            }
+
+            // Children are pushed in reverse so that they're checked in
+            // order, which keeps "the first instance past the limit" the same
+            // one that `insert` would have reached first.
+            for child in current.children.iter().rev() {
+                to_check.push((child, Some(index), depth + 1));
+            }
+        }
+
+        Ok(self.insert(parent_ref, builder))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{content_hash, ContentHashOptions};
+
+    fn sample_dom() -> (WeakDom, Ref) {
+        let workspace = InstanceBuilder::new("Workspace").with_child(
+            InstanceBuilder::new("Part")
+                .with_name("Baseplate")
+                .with_property("Anchored", true),
+        );
+        let workspace_ref = workspace.referent();
+
+        (
+            WeakDom::new(InstanceBuilder::new("DataModel").with_child(workspace)),
+            workspace_ref,
+        )
+    }
+
+    fn hash(dom: &WeakDom) -> [u8; 32] {
+        content_hash(dom, dom.root_ref(), ContentHashOptions::new())
+    }
+
+    /// A model with a Part and a Folder holding two more Parts, five instances
+    /// in all.
+    fn model() -> InstanceBuilder {
+        InstanceBuilder::new("Model")
+            .with_name("House")
+            .with_child(InstanceBuilder::new("Part").with_name("Floor"))
+            .with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("Walls")
+                    .with_child(InstanceBuilder::new("Part").with_name("North"))
+                    .with_child(InstanceBuilder::new("Part").with_name("South")),
+            )
+    }
+
+    #[test]
+    fn within_limits() {
+        let (mut dom, workspace_ref) = sample_dom();
+
+        let limits = InsertLimits::new().max_instances(5).max_depth(3);
+        let house_ref = dom.insert_limited(workspace_ref, model(), &limits).unwrap();
+
+        assert_eq!(dom.len(), 8);
+        assert_eq!(dom.get_by_ref(house_ref).unwrap().name, "House");
+    }
+
+    #[test]
+    fn too_many_instances() {
+        let (mut dom, workspace_ref) = sample_dom();
+        let before = hash(&dom);
+
+        let limits = InsertLimits::new().max_instances(4);
+        let error = dom
+            .insert_limited(workspace_ref, model(), &limits)
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            InsertError::TooManyInstances {
+                instance: "DataModel.Workspace.House.Walls.South".to_owned(),
+                limit: 4,
+            }
+        );
+        assert_eq!(dom.len(), 3);
+        assert_eq!(hash(&dom), before);
+    }
+
+    #[test]
+    fn too_deep() {
+        let (mut dom, workspace_ref) = sample_dom();
+
+        let limits = InsertLimits::new().max_depth(2);
+        let error = dom
+            .insert_limited(workspace_ref, model(), &limits)
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            InsertError::TooDeep {
+                instance: "DataModel.Workspace.House.Walls.North".to_owned(),
+                limit: 2,
+            }
+        );
+        assert_eq!(dom.len(), 3);
+    }
+
+    #[test]
+    fn properties_too_large() {
+        let (mut dom, workspace_ref) = sample_dom();
+        let before = hash(&dom);
+
+        let tree = model().with_child(
+            InstanceBuilder::new("Script")
+                .with_name("Huge")
+                .with_property("Source", "x".repeat(1000)),
+        );
+
+        let limits = InsertLimits::new().max_property_bytes(1000);
+        let error = dom
+            .insert_limited(workspace_ref, tree, &limits)
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            InsertError::PropertiesTooLarge {
+                instance: "DataModel.Workspace.House.Huge".to_owned(),
+                bytes: 1006,
+                limit: 1000,
+            }
+        );
+        assert_eq!(dom.len(), 3);
+        assert_eq!(hash(&dom), before);
+    }
+
+    #[test]
+    fn duplicate_referents() {
+        let (mut dom, workspace_ref) = sample_dom();
+        let before = hash(&dom);
+        let limits = InsertLimits::new();
+
+        // A referent that's already in the dom would replace the instance
+        // using it.
+        let tree = model().with_child(
+            InstanceBuilder::new("Part")
+                .with_name("Stolen")
+                .with_referent(workspace_ref),
+        );
+        let error = dom
+            .insert_limited(workspace_ref, tree, &limits)
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            InsertError::DuplicateReferent {
+                instance: "DataModel.Workspace.House.Stolen".to_owned(),
+                referent: workspace_ref,
+            }
+        );
+
+        // So would a referent used twice within the tree.
+        let referent = Ref::new();
+        let tree = InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Part").with_referent(referent))
+            .with_child(InstanceBuilder::new("Part").with_referent(referent));
+        let error = dom
+            .insert_limited(workspace_ref, tree, &limits)
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            InsertError::DuplicateReferent {
+                instance: "DataModel.Workspace.Folder.Part".to_owned(),
+                referent,
+            }
+        );
+
+        assert_eq!(dom.len(), 3);
+        assert_eq!(hash(&dom), before);
+    }
+
+    #[test]
+    fn missing_parent() {
+        let (mut dom, _) = sample_dom();
+        let parent = Ref::new();
+
+        let error = dom
+            .insert_limited(parent, model(), &InsertLimits::new())
+            .unwrap_err();
+
+        assert_eq!(error, InsertError::MissingParent { parent });
+        assert_eq!(dom.len(), 3);
+    }
+}
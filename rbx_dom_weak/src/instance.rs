@@ -1,5 +1,4 @@
-use std::collections::HashMap;
-
+use indexmap::IndexMap;
 use rbx_types::{Ref, Variant};
 
 /**
@@ -35,8 +34,9 @@ let dom = WeakDom::new(data_model);
 pub struct InstanceBuilder {
     pub(crate) referent: Ref,
     pub(crate) name: String,
+    pub(crate) has_name: bool,
     pub(crate) class: String,
-    pub(crate) properties: HashMap<String, Variant>,
+    pub(crate) properties: IndexMap<String, Variant>,
     pub(crate) children: Vec<InstanceBuilder>,
 }
 
@@ -50,8 +50,9 @@ impl InstanceBuilder {
         InstanceBuilder {
             referent: Ref::new(),
             name,
+            has_name: true,
             class,
-            properties: HashMap::new(),
+            properties: IndexMap::new(),
             children: Vec::new(),
         }
     }
@@ -61,10 +62,22 @@ impl InstanceBuilder {
         self.referent
     }
 
+    /// Change the referent of the `InstanceBuilder`, which becomes the `Ref`
+    /// of the instance once it's inserted into a dom. Every instance in a dom
+    /// must have a different referent.
+    ///
+    /// This is useful with a [`RefProvider`][rbx_types::RefProvider] like
+    /// [`SeededRefProvider`][rbx_types::SeededRefProvider] to build the same
+    /// tree with the same Refs every time.
+    pub fn with_referent(self, referent: Ref) -> Self {
+        Self { referent, ..self }
+    }
+
     /// Change the name of the `InstanceBuilder`.
     pub fn with_name<S: Into<String>>(self, name: S) -> Self {
         Self {
             name: name.into(),
+            has_name: true,
             ..self
         }
     }
@@ -72,6 +85,22 @@ impl InstanceBuilder {
     /// Change the name of the `InstanceBuilder`.
     pub fn set_name<S: Into<String>>(&mut self, name: S) {
         self.name = name.into();
+        self.has_name = true;
+    }
+
+    /// Mark the `InstanceBuilder` as having no `Name` of its own, like an
+    /// instance read from a file that left its Name out. It's still named
+    /// after its ClassName, but [`Instance::explicit_name`] returns `None`.
+    pub fn without_name(mut self) -> Self {
+        self.clear_name();
+        self
+    }
+
+    /// Mark the `InstanceBuilder` as having no `Name` of its own. See
+    /// [`InstanceBuilder::without_name`].
+    pub fn clear_name(&mut self) {
+        self.name = self.class.clone();
+        self.has_name = false;
     }
 
     /// Add a new property to the `InstanceBuilder`.
@@ -85,6 +114,11 @@ impl InstanceBuilder {
         self.properties.insert(key.into(), value.into());
     }
 
+    /// Tells whether the `InstanceBuilder` has a property with the given name.
+    pub fn has_property(&self, key: &str) -> bool {
+        self.properties.contains_key(key)
+    }
+
     /// Add multiple properties to the `InstanceBuilder` at once.
     pub fn with_properties<K, V, I>(mut self, props: I) -> Self
     where
@@ -155,16 +189,47 @@ pub struct Instance {
     pub(crate) parent: Ref,
 
     /// The instance's name, corresponding to the `Name` property.
+    ///
+    /// Every instance has a name. When a file doesn't give an instance a
+    /// `Name`, rbx_xml and rbx_binary both use its ClassName instead. Decoding
+    /// with their `preserve_missing_names` option also remembers that the
+    /// Name was missing, which [`Instance::explicit_name`] reports.
     pub name: String,
 
+    pub(crate) has_name: bool,
+
     /// The instance's class, corresponding to the `ClassName` property.
     pub class: String,
 
     /// Any properties stored on the object that are not `Name` or `ClassName`.
-    pub properties: HashMap<String, Variant>,
+    ///
+    /// Properties are kept in the order that they were inserted in, which is
+    /// the order that rbx_xml and rbx_binary write them out in. Removing a
+    /// property with `shift_remove` keeps the order of the rest intact, while
+    /// `swap_remove` is faster but moves the last property into its place.
+    pub properties: IndexMap<String, Variant>,
 }
 
 impl Instance {
+    /// Returns this instance's name, or `None` if it was created without one
+    /// and is still named after its ClassName. Encoders leave out the `Name`
+    /// of instances without one, so decoding with `preserve_missing_names`
+    /// and encoding again keeps it missing.
+    pub fn explicit_name(&self) -> Option<&str> {
+        if !self.has_name && self.name == self.class {
+            None
+        } else {
+            Some(&self.name)
+        }
+    }
+
+    /// Marks this instance as having no `Name` of its own, naming it after its
+    /// ClassName. See [`InstanceBuilder::without_name`].
+    pub fn clear_name(&mut self) {
+        self.name = self.class.clone();
+        self.has_name = false;
+    }
+
     /// Returns this instance's referent. It will always be non-null.
     #[inline]
     pub fn referent(&self) -> Ref {
@@ -41,14 +41,46 @@
 
 #![deny(missing_docs)]
 
+pub mod builders;
+
+#[cfg(feature = "compat")]
+pub mod compat;
+
+mod asset_refs;
+mod class_migration;
+mod content_hash;
+mod descendants;
 mod dom;
+mod effective_properties;
+mod insert_limits;
 mod instance;
+mod observer;
+mod property_lint;
+mod property_read;
+mod property_scan;
+mod property_write;
+mod ref_cleanup;
+mod ref_index;
+mod stats;
 mod viewer;
 
 pub use rbx_types as types;
 
 pub use crate::{
+    asset_refs::AssetRefSite,
+    class_migration::{ClassMigration, MigrationAction, MigrationReport},
+    content_hash::{content_hash, ContentHashOptions},
+    descendants::Descendants,
     dom::WeakDom,
+    effective_properties::{EffectivePropertiesOptions, EffectiveProperty, PropertyOrigin},
+    insert_limits::{InsertError, InsertLimits},
     instance::{Instance, InstanceBuilder},
+    observer::DomObserver,
+    property_lint::{PropertyLint, PropertyLintKind},
+    property_read::PropertyReadError,
+    property_write::{TypeMismatch, UnknownPropertyPolicy},
+    ref_cleanup::{DanglingRefPolicy, RefSite},
+    ref_index::{RefIndex, Referencers},
+    stats::{DomStats, InstanceSize, PropertyTotal, SizeBucket, StatsOptions},
     viewer::{DomViewer, ViewedInstance},
 };
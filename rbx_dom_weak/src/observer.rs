@@ -0,0 +1,268 @@
+use std::fmt;
+
+use rbx_types::Ref;
+
+use crate::{dom::WeakDom, instance::Instance};
+
+/// Receives a call whenever instances are added to, removed from, or moved
+/// within a [`WeakDom`], once it's been registered with
+/// [`WeakDom::set_observer`].
+///
+/// This is meant for applications that keep their own tables keyed by
+/// [`Ref`], like which instances are selected, and need to keep them in step
+/// with the dom. Every method does nothing by default, so observers only need
+/// to implement the ones they care about.
+///
+/// Callbacks happen synchronously, from inside the method that changed the
+/// dom. Changes made through [`WeakDom::get_by_ref_mut`] and
+/// [`WeakDom::root_mut`], like renaming an instance or setting a property,
+/// aren't reported.
+///
+/// ## Reentrancy
+/// Each callback is given a shared reference to the dom, so it can read
+/// anything in it, but it can't change the dom. The dom is borrowed for as
+/// long as the callback runs, so the compiler rules out changes instead of a
+/// check at runtime.
+pub trait DomObserver: Send + Sync {
+    /// Called for each instance added by [`WeakDom::insert`], or moved into
+    /// this dom by [`WeakDom::transfer`].
+    ///
+    /// The instance has already been added to its parent. When a builder with
+    /// children is inserted, this is called for the new instance before its
+    /// children, in the order given by [`WeakDom::descendants`].
+    fn on_insert(&mut self, dom: &WeakDom, referent: Ref) {
+        let _ = (dom, referent);
+    }
+
+    /// Called for each instance removed by [`WeakDom::destroy`], or moved
+    /// out of this dom by [`WeakDom::transfer`].
+    ///
+    /// This is called for the instance and every one of its descendants, in
+    /// the order given by [`WeakDom::descendants`], before any of them are
+    /// removed. They can all still be read during the callback.
+    fn on_destroy(&mut self, dom: &WeakDom, referent: Ref) {
+        let _ = (dom, referent);
+    }
+
+    /// Called when [`WeakDom::transfer_within`] moves an instance from
+    /// `old_parent` to `new_parent`, after it's been moved. Its descendants
+    /// move with it, but aren't reported.
+    fn on_reparent(&mut self, dom: &WeakDom, referent: Ref, old_parent: Ref, new_parent: Ref) {
+        let _ = (dom, referent, old_parent, new_parent);
+    }
+}
+
+impl fmt::Debug for dyn DomObserver {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(output, "DomObserver")
+    }
+}
+
+impl WeakDom {
+    /// Registers an observer that's told about instances being inserted,
+    /// destroyed, and moved from now on, replacing any that was registered
+    /// before.
+    pub fn set_observer(&mut self, observer: Box<dyn DomObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Removes the observer registered with [`WeakDom::set_observer`] and
+    /// returns it, if there is one.
+    pub fn take_observer(&mut self) -> Option<Box<dyn DomObserver>> {
+        self.observer.take()
+    }
+
+    /// Calls the observer with a view of the dom, if there is one. Without an
+    /// observer, this is a single branch.
+    #[inline]
+    pub(crate) fn notify<F>(&mut self, callback: F)
+    where
+        F: FnOnce(&mut dyn DomObserver, &WeakDom),
+    {
+        if let Some(mut observer) = self.observer.take() {
+            callback(&mut *observer, self);
+            self.observer = Some(observer);
+        }
+    }
+
+    /// Tells the observer that an instance and its descendants are about to
+    /// be removed from the dom. Nothing is reported for an instance that
+    /// isn't in the dom.
+    pub(crate) fn notify_destroy(&mut self, referent: Ref) {
+        self.notify(|observer, dom| {
+            let doomed: Vec<Ref> = dom.descendants(referent).map(Instance::referent).collect();
+
+            for referent in doomed {
+                observer.on_destroy(dom, referent);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::InstanceBuilder;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Event {
+        Insert(String),
+        Destroy(String),
+        Reparent(String, String, String),
+    }
+
+    /// Records every callback, naming instances by their full name so that
+    /// the sequence is easy to read.
+    struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+    impl Recorder {
+        fn name(dom: &WeakDom, referent: Ref) -> String {
+            dom.full_name(referent).unwrap()
+        }
+    }
+
+    impl DomObserver for Recorder {
+        fn on_insert(&mut self, dom: &WeakDom, referent: Ref) {
+            let event = Event::Insert(Recorder::name(dom, referent));
+            self.0.lock().unwrap().push(event);
+        }
+
+        fn on_destroy(&mut self, dom: &WeakDom, referent: Ref) {
+            let event = Event::Destroy(Recorder::name(dom, referent));
+            self.0.lock().unwrap().push(event);
+        }
+
+        fn on_reparent(&mut self, dom: &WeakDom, referent: Ref, old_parent: Ref, new_parent: Ref) {
+            let event = Event::Reparent(
+                Recorder::name(dom, referent),
+                Recorder::name(dom, old_parent),
+                Recorder::name(dom, new_parent),
+            );
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn observed(dom: &mut WeakDom) -> Arc<Mutex<Vec<Event>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        dom.set_observer(Box::new(Recorder(Arc::clone(&events))));
+        events
+    }
+
+    fn take_events(events: &Arc<Mutex<Vec<Event>>>) -> Vec<Event> {
+        std::mem::take(&mut *events.lock().unwrap())
+    }
+
+    fn insert(name: &str) -> Event {
+        Event::Insert(name.to_owned())
+    }
+
+    fn destroy(name: &str) -> Event {
+        Event::Destroy(name.to_owned())
+    }
+
+    #[test]
+    fn scripted_mutations() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Root"));
+        let root_ref = dom.root_ref();
+        let events = observed(&mut dom);
+
+        // A bulk insert reports each instance before its children.
+        let model_ref = dom.insert(
+            root_ref,
+            InstanceBuilder::new("Model")
+                .with_name("Model")
+                .with_child(
+                    InstanceBuilder::new("Part")
+                        .with_name("A")
+                        .with_child(InstanceBuilder::new("Decal").with_name("Decal")),
+                )
+                .with_child(InstanceBuilder::new("Part").with_name("B")),
+        );
+        let folder_ref = dom.insert(root_ref, InstanceBuilder::new("Folder").with_name("Folder"));
+
+        assert_eq!(
+            take_events(&events),
+            [
+                insert("Root.Model"),
+                insert("Root.Model.A"),
+                insert("Root.Model.A.Decal"),
+                insert("Root.Model.B"),
+                insert("Root.Folder"),
+            ]
+        );
+
+        let a_ref = dom.get_by_ref(model_ref).unwrap().children()[0];
+        dom.transfer_within(a_ref, folder_ref);
+
+        assert_eq!(
+            take_events(&events),
+            [Event::Reparent(
+                "Root.Folder.A".to_owned(),
+                "Root.Model".to_owned(),
+                "Root.Folder".to_owned()
+            )]
+        );
+
+        // A subtree destroy reports the whole subtree while it can still be
+        // read.
+        dom.destroy(model_ref);
+        dom.destroy(folder_ref);
+
+        assert_eq!(
+            take_events(&events),
+            [
+                destroy("Root.Model"),
+                destroy("Root.Model.B"),
+                destroy("Root.Folder"),
+                destroy("Root.Folder.A"),
+                destroy("Root.Folder.A.Decal"),
+            ]
+        );
+        assert_eq!(dom.len(), 1);
+    }
+
+    #[test]
+    fn transfer_between_doms() {
+        let mut source = WeakDom::new(InstanceBuilder::new("Folder").with_name("Source"));
+        let mut dest = WeakDom::new(InstanceBuilder::new("Folder").with_name("Dest"));
+        let (source_root, dest_root) = (source.root_ref(), dest.root_ref());
+
+        let model_ref = source.insert(
+            source_root,
+            InstanceBuilder::new("Model")
+                .with_name("Model")
+                .with_child(InstanceBuilder::new("Part").with_name("Part")),
+        );
+
+        let source_events = observed(&mut source);
+        let dest_events = observed(&mut dest);
+
+        source.transfer(model_ref, &mut dest, dest_root);
+
+        assert_eq!(
+            take_events(&source_events),
+            [destroy("Source.Model"), destroy("Source.Model.Part")]
+        );
+        assert_eq!(
+            take_events(&dest_events),
+            [insert("Dest.Model"), insert("Dest.Model.Part")]
+        );
+    }
+
+    #[test]
+    fn taken_observer_is_not_called() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Root"));
+        let root_ref = dom.root_ref();
+        let events = observed(&mut dom);
+
+        assert!(dom.take_observer().is_some());
+        let child_ref = dom.insert(root_ref, InstanceBuilder::new("Folder"));
+        dom.destroy(child_ref);
+
+        assert!(take_events(&events).is_empty());
+        assert!(dom.take_observer().is_none());
+    }
+}
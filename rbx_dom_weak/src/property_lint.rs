@@ -0,0 +1,373 @@
+use std::fmt;
+
+use rbx_reflection::{PropertyDescriptor, PropertyKind, PropertyTag, ReflectionDatabase};
+use rbx_types::{Ref, VariantType};
+
+use crate::{
+    dom::WeakDom,
+    property_write::{expected_type, find_on_class, serialized_descriptor, value_fits},
+};
+
+/// A problem with a stored property found by [`WeakDom::lint_properties`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PropertyLint {
+    /// The full name of the instance, like `Workspace.Part`.
+    pub instance: String,
+
+    /// The referent of the instance.
+    pub referent: Ref,
+
+    /// The name that the property is stored under.
+    pub property: String,
+
+    /// What's wrong with the property.
+    pub kind: PropertyLintKind,
+}
+
+/// The kinds of problems reported by [`WeakDom::lint_properties`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PropertyLintKind {
+    /// The property isn't listed on the instance's class or any of its
+    /// superclasses, like `Text` on a Frame.
+    UnknownProperty {
+        /// A property in the class hierarchy whose name only differs by case,
+        /// if there is one.
+        similar: Option<String>,
+    },
+
+    /// The property exists, but its value has a type that can't be stored in
+    /// it, like a `UDim2` in `Part.Size`.
+    WrongType {
+        /// The type that the reflection database lists for the property.
+        expected: VariantType,
+
+        /// The type of the stored value.
+        actual: VariantType,
+    },
+
+    /// The property is deprecated, and the reflection database names the
+    /// property that replaced it.
+    Deprecated {
+        /// The name of the property to use instead.
+        replacement: String,
+    },
+}
+
+impl PropertyLint {
+    /// A short description of how to fix the problem, if one is known.
+    pub fn suggestion(&self) -> Option<String> {
+        match &self.kind {
+            PropertyLintKind::UnknownProperty { similar } => similar
+                .as_ref()
+                .map(|similar| format!("rename it to {}", similar)),
+            PropertyLintKind::WrongType { expected, .. } => {
+                Some(format!("store a {:?} value instead", expected))
+            }
+            PropertyLintKind::Deprecated { replacement } => {
+                Some(format!("use {} instead", replacement))
+            }
+        }
+    }
+}
+
+impl fmt::Display for PropertyLint {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            PropertyLintKind::UnknownProperty { .. } => write!(
+                output,
+                "Instance {} has a property named {}, which its class does not have",
+                self.instance, self.property
+            )?,
+            PropertyLintKind::WrongType { expected, actual } => write!(
+                output,
+                "Property {} of instance {} has type {:?}, but holds a value of type {:?}",
+                self.property, self.instance, expected, actual
+            )?,
+            PropertyLintKind::Deprecated { .. } => write!(
+                output,
+                "Property {} of instance {} is deprecated",
+                self.property, self.instance
+            )?,
+        }
+
+        if let Some(suggestion) = self.suggestion() {
+            write!(output, "; {}", suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl WeakDom {
+    /// Checks the stored properties of an instance and its descendants against
+    /// the given reflection database, in the order given by
+    /// [`WeakDom::descendants`].
+    ///
+    /// Properties are reported when they don't exist anywhere in their
+    /// instance's class hierarchy, when their value has the wrong type, or
+    /// when they're deprecated in favor of another property. Types are
+    /// checked the same way as [`WeakDom::set_property_checked`]. Instances
+    /// whose class isn't in the database are skipped.
+    pub fn lint_properties(&self, root: Ref, database: &ReflectionDatabase) -> Vec<PropertyLint> {
+        let mut lints = Vec::new();
+
+        for instance in self.descendants(root) {
+            if !database.classes.contains_key(instance.class.as_str()) {
+                continue;
+            }
+
+            for (name, value) in &instance.properties {
+                let kind = match find_on_class(database, &instance.class, name) {
+                    None => Some(PropertyLintKind::UnknownProperty {
+                        similar: similar_name(database, &instance.class, name),
+                    }),
+                    Some(descriptor) => {
+                        check_descriptor(database, &instance.class, descriptor, value.ty())
+                    }
+                };
+
+                if let Some(kind) = kind {
+                    lints.push(PropertyLint {
+                        instance: self.full_name(instance.referent()).unwrap(),
+                        referent: instance.referent(),
+                        property: name.clone(),
+                        kind,
+                    });
+                }
+            }
+        }
+
+        lints
+    }
+}
+
+fn check_descriptor(
+    database: &ReflectionDatabase,
+    class_name: &str,
+    descriptor: &PropertyDescriptor,
+    actual: VariantType,
+) -> Option<PropertyLintKind> {
+    let canonical = match &descriptor.kind {
+        PropertyKind::Alias { alias_for } => {
+            if descriptor.tags.contains(&PropertyTag::Deprecated) {
+                return Some(PropertyLintKind::Deprecated {
+                    replacement: alias_for.to_string(),
+                });
+            }
+
+            find_on_class(database, class_name, alias_for)?
+        }
+        _ => descriptor,
+    };
+
+    let fits = |descriptor: &PropertyDescriptor| match expected_type(descriptor) {
+        Some(expected) => value_fits(expected, actual),
+        None => true,
+    };
+
+    if fits(canonical) || serialized_descriptor(database, class_name, canonical).is_some_and(fits) {
+        None
+    } else {
+        Some(PropertyLintKind::WrongType {
+            expected: expected_type(canonical).unwrap(),
+            actual,
+        })
+    }
+}
+
+/// Finds a property in a class hierarchy whose name matches the given one
+/// except for case.
+fn similar_name(
+    database: &ReflectionDatabase,
+    class_name: &str,
+    property_name: &str,
+) -> Option<String> {
+    let mut class_name = Some(class_name);
+
+    while let Some(class) = class_name.and_then(|name| database.classes.get(name)) {
+        let similar = class
+            .properties
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(property_name));
+
+        if let Some(similar) = similar {
+            return Some(similar.to_string());
+        }
+
+        class_name = class.superclass.as_deref();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_reflection::{ClassDescriptor, DataType};
+    use rbx_types::{BrickColor, Color3, Content, UDim, UDim2, Vector3};
+
+    use crate::InstanceBuilder;
+
+    fn database() -> ReflectionDatabase<'static> {
+        let mut base_part = ClassDescriptor::new("BasePart");
+        base_part.properties.insert(
+            "Size".into(),
+            PropertyDescriptor::new("Size", DataType::Value(VariantType::Vector3)),
+        );
+        base_part.properties.insert(
+            "BrickColor".into(),
+            PropertyDescriptor::new("BrickColor", DataType::Value(VariantType::BrickColor)),
+        );
+
+        let mut brick_color =
+            PropertyDescriptor::new("brickColor", DataType::Value(VariantType::BrickColor));
+        brick_color.kind = PropertyKind::Alias {
+            alias_for: "BrickColor".into(),
+        };
+        brick_color.tags.insert(PropertyTag::Deprecated);
+        base_part
+            .properties
+            .insert("brickColor".into(), brick_color);
+
+        let mut part = ClassDescriptor::new("Part");
+        part.superclass = Some("BasePart".into());
+
+        let mut frame = ClassDescriptor::new("Frame");
+        frame.properties.insert(
+            "Size".into(),
+            PropertyDescriptor::new("Size", DataType::Value(VariantType::UDim2)),
+        );
+        frame.properties.insert(
+            "BackgroundColor3".into(),
+            PropertyDescriptor::new("BackgroundColor3", DataType::Value(VariantType::Color3)),
+        );
+
+        let mut text_label = ClassDescriptor::new("TextLabel");
+        text_label.superclass = Some("Frame".into());
+        text_label.properties.insert(
+            "Text".into(),
+            PropertyDescriptor::new("Text", DataType::Value(VariantType::String)),
+        );
+
+        let mut database = ReflectionDatabase::new();
+        database.classes.insert("BasePart".into(), base_part);
+        database.classes.insert("Part".into(), part);
+        database.classes.insert("Frame".into(), frame);
+        database.classes.insert("TextLabel".into(), text_label);
+        database
+    }
+
+    /// A dom with one of each kind of lint, along with properties that are
+    /// fine.
+    fn lint_dom() -> WeakDom {
+        let udim2 = UDim2::new(UDim::new(0.0, 100), UDim::new(0.0, 50));
+
+        WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Model")
+                .with_child(
+                    InstanceBuilder::new("Part")
+                        .with_name("Brick")
+                        .with_property("Size", udim2)
+                        .with_property("brickColor", BrickColor::BrightRed)
+                        .with_property("BrickColor", BrickColor::BrightRed),
+                )
+                .with_child(
+                    InstanceBuilder::new("Frame")
+                        .with_name("Panel")
+                        .with_property("Size", udim2)
+                        .with_property("Text", "Hello")
+                        .with_property("backgroundcolor3", Color3::new(1.0, 1.0, 1.0))
+                        .with_child(
+                            InstanceBuilder::new("TextLabel")
+                                .with_name("Label")
+                                .with_property("Text", Content::from("Hello")),
+                        ),
+                )
+                .with_child(
+                    InstanceBuilder::new("Gizmo")
+                        .with_name("Unknown")
+                        .with_property("Size", Vector3::new(1.0, 1.0, 1.0)),
+                ),
+        )
+    }
+
+    #[test]
+    fn each_kind() {
+        let dom = lint_dom();
+        let lints = dom.lint_properties(dom.root_ref(), &database());
+
+        let kinds: Vec<_> = lints
+            .iter()
+            .map(|lint| (lint.instance.as_str(), lint.property.as_str(), &lint.kind))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                (
+                    "Model.Brick",
+                    "Size",
+                    &PropertyLintKind::WrongType {
+                        expected: VariantType::Vector3,
+                        actual: VariantType::UDim2,
+                    }
+                ),
+                (
+                    "Model.Brick",
+                    "brickColor",
+                    &PropertyLintKind::Deprecated {
+                        replacement: "BrickColor".to_owned(),
+                    }
+                ),
+                (
+                    "Model.Panel",
+                    "Text",
+                    &PropertyLintKind::UnknownProperty { similar: None }
+                ),
+                (
+                    "Model.Panel",
+                    "backgroundcolor3",
+                    &PropertyLintKind::UnknownProperty {
+                        similar: Some("BackgroundColor3".to_owned()),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn messages() {
+        let dom = lint_dom();
+        let messages: Vec<_> = dom
+            .lint_properties(dom.root_ref(), &database())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(
+            messages,
+            [
+                "Property Size of instance Model.Brick has type Vector3, but holds a value of \
+                 type UDim2; store a Vector3 value instead",
+                "Property brickColor of instance Model.Brick is deprecated; use BrickColor instead",
+                "Instance Model.Panel has a property named Text, which its class does not have",
+                "Instance Model.Panel has a property named backgroundcolor3, which its class does \
+                 not have; rename it to BackgroundColor3",
+            ]
+        );
+    }
+
+    #[test]
+    fn subtree() {
+        let dom = lint_dom();
+        let panel_ref = dom.root().children()[1];
+        let label_ref = dom.get_by_ref(panel_ref).unwrap().children()[0];
+
+        assert_eq!(dom.lint_properties(panel_ref, &database()).len(), 2);
+        assert!(dom.lint_properties(label_ref, &database()).is_empty());
+    }
+}
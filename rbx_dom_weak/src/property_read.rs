@@ -0,0 +1,327 @@
+use std::{convert::TryFrom, error::Error, fmt};
+
+use rbx_reflection::ReflectionDatabase;
+use rbx_types::{Ref, Variant, VariantType, VariantTypeError};
+
+use crate::dom::WeakDom;
+
+/// The error returned by [`WeakDom::read_property`] and
+/// [`WeakDom::read_property_or_default`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PropertyReadError {
+    /// The instance was not present in the dom.
+    MissingInstance {
+        /// The referent that was looked up.
+        referent: Ref,
+    },
+
+    /// The instance did not have the property, and no default value was
+    /// available for it.
+    MissingProperty {
+        /// The full name of the instance, like `Workspace.Part`.
+        instance: String,
+
+        /// The name of the property that was read.
+        property: String,
+    },
+
+    /// The property held a value of a different type than the one requested.
+    WrongType {
+        /// The full name of the instance, like `Workspace.Part`.
+        instance: String,
+
+        /// The name of the property that was read.
+        property: String,
+
+        /// The type that was requested.
+        expected: VariantType,
+
+        /// The type of the value that the property actually held.
+        actual: VariantType,
+    },
+}
+
+impl fmt::Display for PropertyReadError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PropertyReadError::MissingInstance { referent } => write!(
+                output,
+                "The instance with referent {:?} was not present in the dom",
+                referent
+            ),
+            PropertyReadError::MissingProperty { instance, property } => write!(
+                output,
+                "The instance {} does not have a property named {}",
+                instance, property
+            ),
+            PropertyReadError::WrongType {
+                instance,
+                property,
+                expected,
+                actual,
+            } => write!(
+                output,
+                "Expected property {} of instance {} to be of type {:?}, but it was of type {:?}",
+                property, instance, expected, actual
+            ),
+        }
+    }
+}
+
+impl Error for PropertyReadError {}
+
+impl WeakDom {
+    /// Reads the property with the given name from an instance, converting it
+    /// to `T`.
+    ///
+    /// `T` can be any type held by a [`Variant`], either owned, which copies
+    /// the value, or borrowed from the dom:
+    ///
+    /// ```
+    /// use rbx_dom_weak::{types::Vector3, InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Part").with_property("Size", Vector3::new(4.0, 1.0, 2.0)),
+    /// );
+    ///
+    /// let size: Vector3 = dom.read_property(dom.root_ref(), "Size").unwrap();
+    /// let size_ref: &Vector3 = dom.read_property(dom.root_ref(), "Size").unwrap();
+    ///
+    /// assert_eq!(size, *size_ref);
+    /// assert!(dom.read_property::<f32>(dom.root_ref(), "Size").is_err());
+    /// ```
+    pub fn read_property<'a, T>(&'a self, referent: Ref, name: &str) -> Result<T, PropertyReadError>
+    where
+        T: TryFrom<&'a Variant, Error = VariantTypeError>,
+    {
+        let instance = self
+            .get_by_ref(referent)
+            .ok_or(PropertyReadError::MissingInstance { referent })?;
+
+        match instance.properties.get(name) {
+            Some(value) => self.convert_property(referent, name, value),
+            None => Err(self.missing_property(referent, name)),
+        }
+    }
+
+    /// The same as [`WeakDom::read_property`], but falls back to the default
+    /// value listed for the instance's class in the given reflection database
+    /// when the instance doesn't have the property.
+    ///
+    /// Defaults defined on superclasses are used too.
+    pub fn read_property_or_default<'a, T>(
+        &'a self,
+        referent: Ref,
+        name: &str,
+        database: &'a ReflectionDatabase,
+    ) -> Result<T, PropertyReadError>
+    where
+        T: TryFrom<&'a Variant, Error = VariantTypeError>,
+    {
+        let instance = self
+            .get_by_ref(referent)
+            .ok_or(PropertyReadError::MissingInstance { referent })?;
+
+        if let Some(value) = instance.properties.get(name) {
+            return self.convert_property(referent, name, value);
+        }
+
+        let mut class_name = Some(instance.class.as_str());
+
+        while let Some(class) = class_name.and_then(|name| database.classes.get(name)) {
+            if let Some(value) = class.default_properties.get(name) {
+                return self.convert_property(referent, name, value);
+            }
+
+            class_name = class.superclass.as_deref();
+        }
+
+        Err(self.missing_property(referent, name))
+    }
+
+    fn convert_property<'a, T>(
+        &self,
+        referent: Ref,
+        name: &str,
+        value: &'a Variant,
+    ) -> Result<T, PropertyReadError>
+    where
+        T: TryFrom<&'a Variant, Error = VariantTypeError>,
+    {
+        T::try_from(value).map_err(|error| PropertyReadError::WrongType {
+            instance: self.full_name(referent).unwrap(),
+            property: name.to_owned(),
+            expected: error.expected(),
+            actual: error.actual(),
+        })
+    }
+
+    fn missing_property(&self, referent: Ref, name: &str) -> PropertyReadError {
+        PropertyReadError::MissingProperty {
+            instance: self.full_name(referent).unwrap(),
+            property: name.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_reflection::ClassDescriptor;
+    use rbx_types::{Content, Vector3};
+
+    use crate::InstanceBuilder;
+
+    fn part_dom() -> (WeakDom, Ref) {
+        let part = InstanceBuilder::new("Part")
+            .with_name("Brick")
+            .with_property("Size", Vector3::new(4.0, 1.0, 2.0))
+            .with_property("Anchored", true);
+        let part_ref = part.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Model")
+                .with_child(part),
+        );
+
+        (dom, part_ref)
+    }
+
+    fn database() -> ReflectionDatabase<'static> {
+        let mut base_part = ClassDescriptor::new("BasePart");
+        base_part
+            .default_properties
+            .insert("Transparency".into(), Variant::Float32(0.5));
+        base_part
+            .default_properties
+            .insert("Anchored".into(), Variant::Bool(false));
+
+        let mut part = ClassDescriptor::new("Part");
+        part.superclass = Some("BasePart".into());
+        part.default_properties.insert(
+            "TextureID".into(),
+            Variant::Content(Content::from("rbxassetid://1818")),
+        );
+
+        let mut database = ReflectionDatabase::new();
+        database.classes.insert("BasePart".into(), base_part);
+        database.classes.insert("Part".into(), part);
+        database
+    }
+
+    #[test]
+    fn read_owned_and_borrowed() {
+        let (dom, part_ref) = part_dom();
+
+        let size: Vector3 = dom.read_property(part_ref, "Size").unwrap();
+        assert_eq!(size, Vector3::new(4.0, 1.0, 2.0));
+
+        let anchored: &bool = dom.read_property(part_ref, "Anchored").unwrap();
+        assert!(*anchored);
+    }
+
+    #[test]
+    fn missing_instance() {
+        let (dom, _) = part_dom();
+        let referent = Ref::new();
+
+        assert_eq!(
+            dom.read_property::<bool>(referent, "Anchored"),
+            Err(PropertyReadError::MissingInstance { referent })
+        );
+    }
+
+    #[test]
+    fn missing_property() {
+        let (dom, part_ref) = part_dom();
+
+        let error = dom
+            .read_property::<f32>(part_ref, "Transparency")
+            .unwrap_err();
+        assert_eq!(
+            error,
+            PropertyReadError::MissingProperty {
+                instance: "Model.Brick".to_owned(),
+                property: "Transparency".to_owned(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "The instance Model.Brick does not have a property named Transparency"
+        );
+    }
+
+    #[test]
+    fn wrong_type() {
+        let (dom, part_ref) = part_dom();
+
+        assert_eq!(
+            dom.read_property::<Vector3>(part_ref, "Anchored"),
+            Err(PropertyReadError::WrongType {
+                instance: "Model.Brick".to_owned(),
+                property: "Anchored".to_owned(),
+                expected: VariantType::Vector3,
+                actual: VariantType::Bool,
+            })
+        );
+    }
+
+    #[test]
+    fn default_fallback() {
+        let (dom, part_ref) = part_dom();
+        let database = database();
+
+        // Values on the instance win over defaults.
+        let anchored: bool = dom
+            .read_property_or_default(part_ref, "Anchored", &database)
+            .unwrap();
+        assert!(anchored);
+
+        let texture: &Content = dom
+            .read_property_or_default(part_ref, "TextureID", &database)
+            .unwrap();
+        assert_eq!(AsRef::<str>::as_ref(texture), "rbxassetid://1818");
+
+        // Defaults are inherited from superclasses.
+        let transparency: f32 = dom
+            .read_property_or_default(part_ref, "Transparency", &database)
+            .unwrap();
+        assert_eq!(transparency, 0.5);
+    }
+
+    #[test]
+    fn default_fallback_errors() {
+        let (dom, part_ref) = part_dom();
+        let database = database();
+
+        assert_eq!(
+            dom.read_property_or_default::<bool>(part_ref, "Locked", &database),
+            Err(PropertyReadError::MissingProperty {
+                instance: "Model.Brick".to_owned(),
+                property: "Locked".to_owned(),
+            })
+        );
+
+        assert_eq!(
+            dom.read_property_or_default::<bool>(part_ref, "Transparency", &database),
+            Err(PropertyReadError::WrongType {
+                instance: "Model.Brick".to_owned(),
+                property: "Transparency".to_owned(),
+                expected: VariantType::Bool,
+                actual: VariantType::Float32,
+            })
+        );
+
+        let folder_ref = dom.root_ref();
+        assert_eq!(
+            dom.read_property_or_default::<f32>(folder_ref, "Transparency", &database),
+            Err(PropertyReadError::MissingProperty {
+                instance: "Model".to_owned(),
+                property: "Transparency".to_owned(),
+            })
+        );
+    }
+}
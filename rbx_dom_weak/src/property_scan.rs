@@ -0,0 +1,194 @@
+use std::convert::TryFrom;
+
+use rbx_reflection::ReflectionDatabase;
+use rbx_types::{Ref, Variant, VariantTypeError};
+
+use crate::dom::WeakDom;
+
+impl WeakDom {
+    /// Finds every instance in the given subtree that has the property with
+    /// the given name, converting each value to `T`.
+    ///
+    /// Instances are visited in the same order as [`WeakDom::descendants`].
+    /// Instances that don't have the property, or whose value isn't a `T`, are
+    /// skipped. Values are read lazily, so nothing is collected unless the
+    /// caller collects it.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let dom = WeakDom::new(
+    ///     InstanceBuilder::new("Folder")
+    ///         .with_child(InstanceBuilder::new("Part").with_property("Anchored", true))
+    ///         .with_child(InstanceBuilder::new("Part").with_property("Anchored", false))
+    ///         .with_child(InstanceBuilder::new("Model")),
+    /// );
+    ///
+    /// let anchored = dom
+    ///     .scan_property::<bool>(dom.root_ref(), "Anchored")
+    ///     .filter(|(_, anchored)| *anchored)
+    ///     .count();
+    ///
+    /// assert_eq!(anchored, 1);
+    /// ```
+    pub fn scan_property<'a, T>(
+        &'a self,
+        root: Ref,
+        property: &'a str,
+    ) -> impl Iterator<Item = (Ref, T)> + 'a
+    where
+        T: TryFrom<&'a Variant, Error = VariantTypeError> + 'a,
+    {
+        self.descendants(root).filter_map(move |instance| {
+            let value = instance.properties.get(property)?;
+            let value = T::try_from(value).ok()?;
+
+            Some((instance.referent(), value))
+        })
+    }
+
+    /// The same as [`WeakDom::scan_property`], but only includes instances
+    /// whose class is `class_name` or inherits from it according to the given
+    /// reflection database, like `Instance:IsA` in Roblox.
+    ///
+    /// Instances of classes that aren't in the database are skipped.
+    pub fn scan_property_isa<'a, T>(
+        &'a self,
+        root: Ref,
+        class_name: &'a str,
+        property: &'a str,
+        database: &'a ReflectionDatabase,
+    ) -> impl Iterator<Item = (Ref, T)> + 'a
+    where
+        T: TryFrom<&'a Variant, Error = VariantTypeError> + 'a,
+    {
+        self.descendants(root).filter_map(move |instance| {
+            if !database.is_a(&instance.class, class_name) {
+                return None;
+            }
+
+            let value = instance.properties.get(property)?;
+            let value = T::try_from(value).ok()?;
+
+            Some((instance.referent(), value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_reflection::ClassDescriptor;
+    use rbx_types::{CFrame, Matrix3, Vector3};
+
+    use crate::InstanceBuilder;
+
+    fn cframe(x: f32) -> CFrame {
+        CFrame::new(Vector3::new(x, 0.0, 0.0), Matrix3::identity())
+    }
+
+    /// A mixed tree of parts, a non-part that happens to have a CFrame, and a
+    /// part holding the wrong type of value.
+    fn mixed_dom() -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Root")
+                .with_child(
+                    InstanceBuilder::new("Model")
+                        .with_child(
+                            InstanceBuilder::new("Part")
+                                .with_name("A")
+                                .with_property("CFrame", cframe(1.0)),
+                        )
+                        .with_child(
+                            InstanceBuilder::new("WedgePart")
+                                .with_name("B")
+                                .with_property("CFrame", cframe(2.0)),
+                        ),
+                )
+                .with_child(
+                    InstanceBuilder::new("Attachment")
+                        .with_name("C")
+                        .with_property("CFrame", cframe(3.0)),
+                )
+                .with_child(
+                    InstanceBuilder::new("Part")
+                        .with_name("D")
+                        .with_property("CFrame", "not a CFrame"),
+                )
+                .with_child(InstanceBuilder::new("Part").with_name("E")),
+        )
+    }
+
+    fn database() -> ReflectionDatabase<'static> {
+        let mut database = ReflectionDatabase::new();
+
+        for (name, superclass) in &[
+            ("Instance", None),
+            ("PVInstance", Some("Instance")),
+            ("BasePart", Some("PVInstance")),
+            ("FormFactorPart", Some("BasePart")),
+            ("Part", Some("FormFactorPart")),
+            ("WedgePart", Some("FormFactorPart")),
+            ("Attachment", Some("Instance")),
+        ] {
+            let mut class = ClassDescriptor::new(*name);
+            class.superclass = superclass.map(Into::into);
+            database.classes.insert((*name).into(), class);
+        }
+
+        database
+    }
+
+    fn names<T>(dom: &WeakDom, values: impl Iterator<Item = (Ref, T)>) -> Vec<String> {
+        values
+            .map(|(referent, _)| dom.get_by_ref(referent).unwrap().name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn scan() {
+        let dom = mixed_dom();
+
+        let values: Vec<(Ref, CFrame)> = dom.scan_property(dom.root_ref(), "CFrame").collect();
+        let positions: Vec<f32> = values.iter().map(|(_, value)| value.position.x).collect();
+        assert_eq!(positions, [1.0, 2.0, 3.0]);
+        assert_eq!(names(&dom, values.into_iter()), ["A", "B", "C"]);
+
+        // Values can be borrowed from the dom, too.
+        let strings = dom.scan_property::<&str>(dom.root_ref(), "CFrame");
+        assert_eq!(names(&dom, strings), ["D"]);
+    }
+
+    #[test]
+    fn scan_subtree() {
+        let dom = mixed_dom();
+        let model_ref = dom.root().children()[0];
+
+        let values = dom.scan_property::<CFrame>(model_ref, "CFrame");
+        assert_eq!(names(&dom, values), ["A", "B"]);
+
+        assert_eq!(dom.scan_property::<CFrame>(Ref::new(), "CFrame").count(), 0);
+    }
+
+    #[test]
+    fn scan_isa() {
+        let dom = mixed_dom();
+        let database = database();
+
+        let parts =
+            dom.scan_property_isa::<&CFrame>(dom.root_ref(), "BasePart", "CFrame", &database);
+        assert_eq!(names(&dom, parts), ["A", "B"]);
+
+        let wedges =
+            dom.scan_property_isa::<CFrame>(dom.root_ref(), "WedgePart", "CFrame", &database);
+        assert_eq!(names(&dom, wedges), ["B"]);
+
+        // Attachment isn't a BasePart, but it is an Instance. D's value isn't
+        // a CFrame and E doesn't have one, so they're still skipped.
+        let instances =
+            dom.scan_property_isa::<CFrame>(dom.root_ref(), "Instance", "CFrame", &database);
+        assert_eq!(names(&dom, instances), ["A", "B", "C"]);
+    }
+}
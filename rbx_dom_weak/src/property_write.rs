@@ -0,0 +1,470 @@
+use std::{error::Error, fmt};
+
+use rbx_reflection::{
+    DataType, PropertyDescriptor, PropertyKind, PropertySerialization, ReflectionDatabase,
+};
+use rbx_types::{Ref, Variant, VariantType};
+
+use crate::dom::WeakDom;
+
+/// Decides what [`WeakDom::set_property_checked_with_policy`] does with
+/// classes and properties that aren't in the reflection database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum UnknownPropertyPolicy {
+    /// Store the value without checking it. This is the default, since the
+    /// database may be older than the file being edited.
+    #[default]
+    Allow,
+
+    /// Return an error instead of storing the value.
+    Error,
+}
+
+/// The error returned by [`WeakDom::set_property_checked`] and
+/// [`WeakDom::set_property_checked_with_policy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypeMismatch {
+    /// The value's type can't be stored in the property.
+    WrongType {
+        /// The full name of the instance, like `Workspace.Part`.
+        instance: String,
+
+        /// The name of the property that was written.
+        property: String,
+
+        /// The type that the reflection database lists for the property.
+        expected: VariantType,
+
+        /// The type of the value that was given.
+        actual: VariantType,
+    },
+
+    /// The instance's class isn't in the reflection database.
+    UnknownClass {
+        /// The full name of the instance, like `Workspace.Part`.
+        instance: String,
+
+        /// The class of the instance.
+        class: String,
+    },
+
+    /// The property isn't listed on the instance's class or any of its
+    /// superclasses.
+    UnknownProperty {
+        /// The full name of the instance, like `Workspace.Part`.
+        instance: String,
+
+        /// The class of the instance.
+        class: String,
+
+        /// The name of the property that was written.
+        property: String,
+    },
+}
+
+impl fmt::Display for TypeMismatch {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypeMismatch::WrongType {
+                instance,
+                property,
+                expected,
+                actual,
+            } => write!(
+                output,
+                "Property {} of instance {} has type {:?}, but a value of type {:?} was given",
+                property, instance, expected, actual
+            ),
+            TypeMismatch::UnknownClass { instance, class } => write!(
+                output,
+                "The class {} of instance {} is not in the reflection database",
+                class, instance
+            ),
+            TypeMismatch::UnknownProperty {
+                instance,
+                class,
+                property,
+            } => write!(
+                output,
+                "The class {} of instance {} does not have a property named {}",
+                class, instance, property
+            ),
+        }
+    }
+}
+
+impl Error for TypeMismatch {}
+
+impl WeakDom {
+    /// Sets a property on an instance after checking that the value's type
+    /// matches the type listed for it in the given reflection database,
+    /// returning the previous value if there was one.
+    ///
+    /// The check accepts the same substitutions that the encoders do, like a
+    /// `Content` value for a `String` property or a `Color3uint8` for
+    /// `BasePart.Color`. The value is stored as it was given.
+    ///
+    /// Classes and properties that aren't in the database are allowed. Use
+    /// [`WeakDom::set_property_checked_with_policy`] to reject them instead.
+    ///
+    /// ## Panics
+    /// Panics if the instance is not present in the dom.
+    pub fn set_property_checked<N, V>(
+        &mut self,
+        referent: Ref,
+        name: N,
+        value: V,
+        database: &ReflectionDatabase,
+    ) -> Result<Option<Variant>, TypeMismatch>
+    where
+        N: Into<String>,
+        V: Into<Variant>,
+    {
+        self.set_property_checked_with_policy(
+            referent,
+            name,
+            value,
+            database,
+            UnknownPropertyPolicy::default(),
+        )
+    }
+
+    /// The same as [`WeakDom::set_property_checked`], but lets the caller
+    /// decide what happens to classes and properties that aren't in the
+    /// reflection database.
+    ///
+    /// ## Panics
+    /// Panics if the instance is not present in the dom.
+    pub fn set_property_checked_with_policy<N, V>(
+        &mut self,
+        referent: Ref,
+        name: N,
+        value: V,
+        database: &ReflectionDatabase,
+        unknown: UnknownPropertyPolicy,
+    ) -> Result<Option<Variant>, TypeMismatch>
+    where
+        N: Into<String>,
+        V: Into<Variant>,
+    {
+        let name = name.into();
+        let value = value.into();
+
+        let instance = self
+            .get_by_ref(referent)
+            .expect("cannot set a property on an instance that does not exist");
+
+        match find_descriptor(database, &instance.class, &name) {
+            Lookup::Found(descriptor) => {
+                let serialized = serialized_descriptor(database, &instance.class, descriptor);
+
+                let fits = |descriptor: &PropertyDescriptor| match expected_type(descriptor) {
+                    Some(expected) => value_fits(expected, value.ty()),
+                    None => true,
+                };
+
+                if !fits(descriptor) && !serialized.is_some_and(fits) {
+                    return Err(TypeMismatch::WrongType {
+                        instance: self.full_name(referent).unwrap(),
+                        property: name,
+                        expected: expected_type(descriptor).unwrap(),
+                        actual: value.ty(),
+                    });
+                }
+            }
+            Lookup::UnknownClass if unknown == UnknownPropertyPolicy::Error => {
+                return Err(TypeMismatch::UnknownClass {
+                    instance: self.full_name(referent).unwrap(),
+                    class: instance.class.clone(),
+                });
+            }
+            Lookup::UnknownProperty if unknown == UnknownPropertyPolicy::Error => {
+                return Err(TypeMismatch::UnknownProperty {
+                    instance: self.full_name(referent).unwrap(),
+                    class: instance.class.clone(),
+                    property: name,
+                });
+            }
+            Lookup::UnknownClass | Lookup::UnknownProperty => {}
+        }
+
+        let instance = self.get_by_ref_mut(referent).unwrap();
+        Ok(instance.properties.insert(name, value))
+    }
+}
+
+enum Lookup<'db> {
+    Found(&'db PropertyDescriptor<'db>),
+    UnknownClass,
+    UnknownProperty,
+}
+
+/// Finds the canonical descriptor for a property on a class or any of its
+/// superclasses, following aliases.
+fn find_descriptor<'db>(
+    database: &'db ReflectionDatabase<'db>,
+    class_name: &str,
+    property_name: &str,
+) -> Lookup<'db> {
+    if !database.classes.contains_key(class_name) {
+        return Lookup::UnknownClass;
+    }
+
+    let descriptor = match find_on_class(database, class_name, property_name) {
+        Some(descriptor) => descriptor,
+        None => return Lookup::UnknownProperty,
+    };
+
+    match &descriptor.kind {
+        PropertyKind::Alias { alias_for } => match find_on_class(database, class_name, alias_for) {
+            Some(canonical) => Lookup::Found(canonical),
+            None => Lookup::UnknownProperty,
+        },
+        _ => Lookup::Found(descriptor),
+    }
+}
+
+/// Finds the descriptor a canonical property is written to files as, if it
+/// differs from the canonical one.
+pub(crate) fn serialized_descriptor<'db>(
+    database: &'db ReflectionDatabase<'db>,
+    class_name: &str,
+    canonical: &PropertyDescriptor,
+) -> Option<&'db PropertyDescriptor<'db>> {
+    match &canonical.kind {
+        PropertyKind::Canonical {
+            serialization: PropertySerialization::SerializesAs(serialized_name),
+        } => find_on_class(database, class_name, serialized_name),
+        _ => None,
+    }
+}
+
+pub(crate) fn find_on_class<'db>(
+    database: &'db ReflectionDatabase<'db>,
+    class_name: &str,
+    property_name: &str,
+) -> Option<&'db PropertyDescriptor<'db>> {
+    let mut class_name = Some(class_name);
+
+    while let Some(class) = class_name.and_then(|name| database.classes.get(name)) {
+        if let Some(descriptor) = class.properties.get(property_name) {
+            return Some(descriptor);
+        }
+
+        class_name = class.superclass.as_deref();
+    }
+
+    None
+}
+
+pub(crate) fn expected_type(descriptor: &PropertyDescriptor) -> Option<VariantType> {
+    match &descriptor.data_type {
+        DataType::Value(ty) => Some(*ty),
+        DataType::Enum(_) => Some(VariantType::Enum),
+
+        // We can't check types that this version doesn't know about.
+        _ => None,
+    }
+}
+
+/// Tells whether the encoders accept a value of type `actual` for a property
+/// of type `expected`.
+pub(crate) fn value_fits(expected: VariantType, actual: VariantType) -> bool {
+    let is_string = |ty| {
+        matches!(
+            ty,
+            VariantType::String | VariantType::Content | VariantType::BinaryString
+        )
+    };
+
+    expected == actual
+        || (is_string(expected) && is_string(actual))
+        || (expected == VariantType::Color3uint8 && actual == VariantType::Color3)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_reflection::ClassDescriptor;
+    use rbx_types::{Color3, Color3uint8, Content, Vector2, Vector3};
+
+    use crate::InstanceBuilder;
+
+    fn part_dom() -> (WeakDom, Ref) {
+        let part = InstanceBuilder::new("Part")
+            .with_name("Brick")
+            .with_property("Size", Vector3::new(4.0, 1.0, 2.0));
+        let part_ref = part.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Model")
+                .with_child(part),
+        );
+
+        (dom, part_ref)
+    }
+
+    fn database() -> ReflectionDatabase<'static> {
+        let mut base_part = ClassDescriptor::new("BasePart");
+        base_part.properties.insert(
+            "Size".into(),
+            PropertyDescriptor::new("Size", DataType::Value(VariantType::Vector3)),
+        );
+
+        let mut color = PropertyDescriptor::new("Color", DataType::Value(VariantType::Color3));
+        color.kind = PropertyKind::Canonical {
+            serialization: PropertySerialization::SerializesAs("Color3uint8".into()),
+        };
+        base_part.properties.insert("Color".into(), color);
+        base_part.properties.insert(
+            "Color3uint8".into(),
+            PropertyDescriptor::new("Color3uint8", DataType::Value(VariantType::Color3uint8)),
+        );
+
+        let mut part = ClassDescriptor::new("Part");
+        part.superclass = Some("BasePart".into());
+        part.properties.insert(
+            "TextureID".into(),
+            PropertyDescriptor::new("TextureID", DataType::Value(VariantType::Content)),
+        );
+
+        let mut database = ReflectionDatabase::new();
+        database.classes.insert("BasePart".into(), base_part);
+        database.classes.insert("Part".into(), part);
+        database
+    }
+
+    #[test]
+    fn valid_set() {
+        let (mut dom, part_ref) = part_dom();
+        let database = database();
+
+        let previous = dom
+            .set_property_checked(part_ref, "Size", Vector3::new(1.0, 1.0, 1.0), &database)
+            .unwrap();
+        assert_eq!(
+            previous,
+            Some(Variant::Vector3(Vector3::new(4.0, 1.0, 2.0)))
+        );
+
+        let previous = dom
+            .set_property_checked(part_ref, "Color", Color3::new(1.0, 0.0, 0.0), &database)
+            .unwrap();
+        assert_eq!(previous, None);
+
+        let part = dom.get_by_ref(part_ref).unwrap();
+        assert_eq!(
+            part.properties.get("Size"),
+            Some(&Variant::Vector3(Vector3::new(1.0, 1.0, 1.0)))
+        );
+    }
+
+    #[test]
+    fn coerced_set() {
+        let (mut dom, part_ref) = part_dom();
+        let database = database();
+
+        dom.set_property_checked(part_ref, "TextureID", "rbxassetid://1818", &database)
+            .unwrap();
+        dom.set_property_checked(part_ref, "Color", Color3uint8::new(255, 0, 0), &database)
+            .unwrap();
+
+        let part = dom.get_by_ref(part_ref).unwrap();
+        assert_eq!(
+            part.properties.get("TextureID"),
+            Some(&Variant::from("rbxassetid://1818"))
+        );
+        assert_eq!(
+            part.properties.get("Color"),
+            Some(&Variant::Color3uint8(Color3uint8::new(255, 0, 0)))
+        );
+    }
+
+    #[test]
+    fn rejected_set() {
+        let (mut dom, part_ref) = part_dom();
+        let database = database();
+
+        let error = dom
+            .set_property_checked(part_ref, "Size", Vector2::new(1.0, 2.0), &database)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            TypeMismatch::WrongType {
+                instance: "Model.Brick".to_owned(),
+                property: "Size".to_owned(),
+                expected: VariantType::Vector3,
+                actual: VariantType::Vector2,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Property Size of instance Model.Brick has type Vector3, but a value of type Vector2 \
+             was given"
+        );
+
+        // A Color3 can't be stored where a Content is expected, and a rejected
+        // value leaves the old one in place.
+        assert!(dom
+            .set_property_checked(part_ref, "TextureID", Content::from("a"), &database)
+            .is_ok());
+        assert!(dom
+            .set_property_checked(part_ref, "TextureID", Color3::new(0.0, 0.0, 0.0), &database)
+            .is_err());
+        assert_eq!(
+            dom.get_by_ref(part_ref)
+                .unwrap()
+                .properties
+                .get("TextureID"),
+            Some(&Variant::Content(Content::from("a")))
+        );
+    }
+
+    #[test]
+    fn unknown_policy() {
+        let (mut dom, part_ref) = part_dom();
+        let database = database();
+        let folder_ref = dom.root_ref();
+
+        dom.set_property_checked(folder_ref, "Anything", 5i32, &database)
+            .unwrap();
+        dom.set_property_checked(part_ref, "Anything", 5i32, &database)
+            .unwrap();
+
+        assert_eq!(
+            dom.set_property_checked_with_policy(
+                folder_ref,
+                "Anything",
+                6i32,
+                &database,
+                UnknownPropertyPolicy::Error
+            ),
+            Err(TypeMismatch::UnknownClass {
+                instance: "Model".to_owned(),
+                class: "Folder".to_owned(),
+            })
+        );
+        assert_eq!(
+            dom.set_property_checked_with_policy(
+                part_ref,
+                "Anything",
+                6i32,
+                &database,
+                UnknownPropertyPolicy::Error
+            ),
+            Err(TypeMismatch::UnknownProperty {
+                instance: "Model.Brick".to_owned(),
+                class: "Part".to_owned(),
+                property: "Anything".to_owned(),
+            })
+        );
+        assert_eq!(
+            dom.get_by_ref(part_ref).unwrap().properties.get("Anything"),
+            Some(&Variant::Int32(5))
+        );
+    }
+}
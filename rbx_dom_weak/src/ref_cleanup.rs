@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+
+use rbx_types::{Ref, Variant};
+
+use crate::{dom::WeakDom, instance::Instance};
+
+/// What [`WeakDom::destroy_with_ref_cleanup`] does with Ref properties that
+/// point into the instances it destroys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DanglingRefPolicy {
+    /// Sets each of these properties to `Ref::none()`, which is what Roblox
+    /// does when an instance is destroyed.
+    NullDanglingRefs,
+
+    /// Leaves these properties alone, like [`WeakDom::destroy`] does. They'll
+    /// point to instances that no longer exist.
+    LeaveDangling,
+}
+
+/// A Ref property that points to an instance that isn't in the dom, found by
+/// [`WeakDom::destroy_with_ref_cleanup`] or [`WeakDom::find_dangling_refs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RefSite {
+    /// The instance with the property.
+    pub referent: Ref,
+
+    /// The name of the property.
+    pub property: String,
+
+    /// The instance that the property pointed to.
+    pub target: Ref,
+}
+
+impl WeakDom {
+    /// Destroys the instance with the given referent, like
+    /// [`WeakDom::destroy`], and then deals with any Ref properties elsewhere
+    /// in the dom that pointed to it or one of its descendants according to
+    /// `policy`.
+    ///
+    /// Returns every property that pointed into the destroyed instances, in
+    /// the order given by [`WeakDom::descendants`]. With
+    /// [`DanglingRefPolicy::NullDanglingRefs`], these are the properties that
+    /// were set to `Ref::none()`. Refs between the destroyed instances aren't
+    /// included, since they're gone too.
+    ///
+    /// This visits every instance in the dom once.
+    ///
+    /// ## Panics
+    /// Panics under the same conditions as [`WeakDom::destroy`].
+    pub fn destroy_with_ref_cleanup(
+        &mut self,
+        referent: Ref,
+        policy: DanglingRefPolicy,
+    ) -> Vec<RefSite> {
+        let destroyed: HashSet<Ref> = self.descendants(referent).map(Instance::referent).collect();
+        self.destroy(referent);
+
+        let remaining: Vec<Ref> = self
+            .descendants(self.root_ref())
+            .map(Instance::referent)
+            .collect();
+
+        let mut sites = Vec::new();
+
+        for referent in remaining {
+            let instance = self.get_by_ref_mut(referent).unwrap();
+
+            for (name, value) in &mut instance.properties {
+                let target = match value {
+                    Variant::Ref(target) if destroyed.contains(target) => *target,
+                    _ => continue,
+                };
+
+                if policy == DanglingRefPolicy::NullDanglingRefs {
+                    *value = Variant::Ref(Ref::none());
+                }
+
+                sites.push(RefSite {
+                    referent,
+                    property: name.clone(),
+                    target,
+                });
+            }
+        }
+
+        sites
+    }
+
+    /// Finds every Ref property in the dom that isn't `Ref::none()` and points
+    /// to an instance that isn't in the dom, in the order given by
+    /// [`WeakDom::descendants`].
+    ///
+    /// These are left behind by [`WeakDom::destroy`], and would be written
+    /// out as referents to instances that aren't in the file.
+    pub fn find_dangling_refs(&self) -> Vec<RefSite> {
+        let mut sites = Vec::new();
+
+        for instance in self.descendants(self.root_ref()) {
+            for (name, value) in &instance.properties {
+                if let Variant::Ref(target) = value {
+                    if target.is_some() && self.get_by_ref(*target).is_none() {
+                        sites.push(RefSite {
+                            referent: instance.referent(),
+                            property: name.clone(),
+                            target: *target,
+                        });
+                    }
+                }
+            }
+        }
+
+        sites
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    /// A Workspace holding two parts and a Weld between them, which sits in a
+    /// Folder so that destroying either part leaves the weld behind.
+    fn weld_dom() -> (WeakDom, Ref, Ref, Ref) {
+        let part0 = InstanceBuilder::new("Part").with_name("Base");
+        let part1 = InstanceBuilder::new("Part").with_name("Door");
+        let (part0_ref, part1_ref) = (part0.referent(), part1.referent());
+
+        let weld = InstanceBuilder::new("Weld")
+            .with_property("Part0", part0_ref)
+            .with_property("Part1", part1_ref);
+        let weld_ref = weld.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Workspace")
+                .with_child(part0)
+                .with_child(part1)
+                .with_child(InstanceBuilder::new("Folder").with_child(weld)),
+        );
+
+        (dom, part0_ref, part1_ref, weld_ref)
+    }
+
+    fn weld_part(dom: &WeakDom, weld_ref: Ref, property: &str) -> Option<Variant> {
+        dom.get_by_ref(weld_ref)
+            .unwrap()
+            .properties
+            .get(property)
+            .cloned()
+    }
+
+    #[test]
+    fn null_dangling_refs() {
+        let (mut dom, part0_ref, part1_ref, weld_ref) = weld_dom();
+
+        let sites = dom.destroy_with_ref_cleanup(part0_ref, DanglingRefPolicy::NullDanglingRefs);
+
+        assert_eq!(
+            sites,
+            [RefSite {
+                referent: weld_ref,
+                property: "Part0".to_owned(),
+                target: part0_ref,
+            }]
+        );
+        assert_eq!(
+            weld_part(&dom, weld_ref, "Part0"),
+            Some(Variant::Ref(Ref::none()))
+        );
+        assert_eq!(
+            weld_part(&dom, weld_ref, "Part1"),
+            Some(Variant::Ref(part1_ref))
+        );
+        assert!(dom.find_dangling_refs().is_empty());
+    }
+
+    #[test]
+    fn leave_dangling() {
+        let (mut dom, part0_ref, _, weld_ref) = weld_dom();
+
+        let sites = dom.destroy_with_ref_cleanup(part0_ref, DanglingRefPolicy::LeaveDangling);
+
+        assert_eq!(sites.len(), 1);
+        assert_eq!(
+            weld_part(&dom, weld_ref, "Part0"),
+            Some(Variant::Ref(part0_ref))
+        );
+        assert_eq!(dom.find_dangling_refs(), sites);
+    }
+
+    #[test]
+    fn refs_inside_destroyed_subtree() {
+        let (mut dom, part0_ref, part1_ref, weld_ref) = weld_dom();
+        let folder_ref = dom.get_by_ref(weld_ref).unwrap().parent();
+
+        // Refs from inside the destroyed instances and refs to them both go
+        // away along with the weld.
+        dom.get_by_ref_mut(part1_ref)
+            .unwrap()
+            .properties
+            .insert("Target".to_owned(), Variant::Ref(weld_ref));
+
+        let sites = dom.destroy_with_ref_cleanup(folder_ref, DanglingRefPolicy::NullDanglingRefs);
+
+        assert_eq!(
+            sites,
+            [RefSite {
+                referent: part1_ref,
+                property: "Target".to_owned(),
+                target: weld_ref,
+            }]
+        );
+        assert!(dom.find_dangling_refs().is_empty());
+        assert!(dom.get_by_ref(part0_ref).is_some());
+    }
+
+    #[test]
+    fn plain_destroy_is_found() {
+        let (mut dom, _, part1_ref, weld_ref) = weld_dom();
+
+        dom.destroy(part1_ref);
+
+        assert_eq!(
+            dom.find_dangling_refs(),
+            [RefSite {
+                referent: weld_ref,
+                property: "Part1".to_owned(),
+                target: part1_ref,
+            }]
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::HashMap, slice, sync::OnceLock};
+
+use indexmap::{map, IndexMap};
+use rbx_types::{Ref, Variant};
+
+use crate::{descendants::Descendants, dom::WeakDom};
+
+/// A map from instances to the Ref properties that point to them, for
+/// answering "what points at this instance?" without looking at every
+/// property in the dom.
+///
+/// A `RefIndex` doesn't change when the dom it was built from does. To keep
+/// one up to date automatically, use [`WeakDom::enable_ref_index`] instead.
+#[derive(Debug, Clone, Default)]
+pub struct RefIndex {
+    referencers: HashMap<Ref, Vec<(Ref, String)>>,
+}
+
+impl RefIndex {
+    /// Builds an index of every Ref property in the dom that isn't
+    /// `Ref::none()`.
+    pub fn build(dom: &WeakDom) -> RefIndex {
+        let mut index = RefIndex::default();
+
+        for instance in dom.descendants(dom.root_ref()) {
+            index.add(instance.referent(), &instance.properties);
+        }
+
+        index
+    }
+
+    /// Returns the instances with Ref properties pointing to `target`, along
+    /// with the name of each property, in the order given by
+    /// [`WeakDom::descendants`].
+    pub fn referencers_of(&self, target: Ref) -> Referencers<'_> {
+        let entries = match self.referencers.get(&target) {
+            Some(entries) => entries.as_slice(),
+            None => &[],
+        };
+
+        Referencers {
+            inner: ReferencersInner::Indexed(entries.iter()),
+        }
+    }
+
+    /// Adds the Ref properties of an instance to the index.
+    pub(crate) fn add(&mut self, referent: Ref, properties: &IndexMap<String, Variant>) {
+        for (name, value) in properties {
+            if let Variant::Ref(target) = value {
+                if target.is_some() {
+                    self.referencers
+                        .entry(*target)
+                        .or_default()
+                        .push((referent, name.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the Ref properties pointing to an instance, created by
+/// [`WeakDom::referencers_of`] or [`RefIndex::referencers_of`]. Each item is
+/// the referent of an instance and the name of its property.
+#[derive(Debug, Clone)]
+pub struct Referencers<'a> {
+    inner: ReferencersInner<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum ReferencersInner<'a> {
+    Indexed(slice::Iter<'a, (Ref, String)>),
+    Scan {
+        target: Ref,
+        descendants: Descendants<'a>,
+        current: Option<(Ref, map::Iter<'a, String, Variant>)>,
+    },
+}
+
+impl<'a> Iterator for Referencers<'a> {
+    type Item = (Ref, &'a str);
+
+    fn next(&mut self) -> Option<(Ref, &'a str)> {
+        match &mut self.inner {
+            ReferencersInner::Indexed(entries) => entries
+                .next()
+                .map(|(referent, name)| (*referent, name.as_str())),
+            ReferencersInner::Scan {
+                target,
+                descendants,
+                current,
+            } => loop {
+                if let Some((referent, properties)) = current {
+                    for (name, value) in properties {
+                        if *value == Variant::Ref(*target) {
+                            return Some((*referent, name.as_str()));
+                        }
+                    }
+                }
+
+                let instance = descendants.next()?;
+                *current = Some((instance.referent(), instance.properties.iter()));
+            },
+        }
+    }
+}
+
+impl WeakDom {
+    /// Starts keeping a [`RefIndex`] for this dom, which makes
+    /// [`WeakDom::referencers_of`] much faster when it's called many times.
+    ///
+    /// The index is built the first time it's used. Inserting instances adds
+    /// them to an index that's already been built, while anything else that
+    /// could change a Ref property, like [`WeakDom::get_by_ref_mut`] or
+    /// [`WeakDom::destroy`], throws the index away to be built again the next
+    /// time it's used.
+    pub fn enable_ref_index(&mut self) {
+        if self.ref_index.is_none() {
+            self.ref_index = Some(OnceLock::new());
+        }
+    }
+
+    /// Stops keeping a [`RefIndex`] for this dom, freeing the memory it used.
+    pub fn disable_ref_index(&mut self) {
+        self.ref_index = None;
+    }
+
+    /// Returns the instances with Ref properties pointing to `target`, along
+    /// with the name of each property, in the order given by
+    /// [`WeakDom::descendants`]. Instances that were inserted after the index
+    /// was built come last.
+    ///
+    /// Unless [`WeakDom::enable_ref_index`] has been called, this looks at
+    /// every property in the dom.
+    ///
+    /// ```
+    /// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+    ///
+    /// let part = InstanceBuilder::new("Part");
+    /// let part_ref = part.referent();
+    ///
+    /// let mut dom = WeakDom::new(
+    ///     InstanceBuilder::new("Model")
+    ///         .with_property("PrimaryPart", part_ref)
+    ///         .with_child(part),
+    /// );
+    /// dom.enable_ref_index();
+    ///
+    /// let referencers: Vec<_> = dom.referencers_of(part_ref).collect();
+    /// assert_eq!(referencers, [(dom.root_ref(), "PrimaryPart")]);
+    /// ```
+    pub fn referencers_of(&self, target: Ref) -> Referencers<'_> {
+        match &self.ref_index {
+            Some(index) => index
+                .get_or_init(|| RefIndex::build(self))
+                .referencers_of(target),
+            None => Referencers {
+                inner: ReferencersInner::Scan {
+                    target,
+                    descendants: self.descendants(self.root_ref()),
+                    current: None,
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::InstanceBuilder;
+
+    /// A model whose PrimaryPart is welded to another part, with an
+    /// ObjectValue that points to the model.
+    fn weld_dom() -> (WeakDom, Ref, Ref, Ref) {
+        let base = InstanceBuilder::new("Part").with_name("Base");
+        let door = InstanceBuilder::new("Part").with_name("Door");
+        let (base_ref, door_ref) = (base.referent(), door.referent());
+
+        let model = InstanceBuilder::new("Model")
+            .with_property("PrimaryPart", base_ref)
+            .with_child(base)
+            .with_child(door)
+            .with_child(
+                InstanceBuilder::new("Weld")
+                    .with_property("Part0", base_ref)
+                    .with_property("Part1", door_ref),
+            );
+        let model_ref = model.referent();
+
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Workspace")
+                .with_child(model)
+                .with_child(InstanceBuilder::new("ObjectValue").with_property("Value", model_ref)),
+        );
+
+        (dom, model_ref, base_ref, door_ref)
+    }
+
+    /// The names of the instances and properties that point to `target`.
+    fn referencers(dom: &WeakDom, target: Ref) -> Vec<(String, String)> {
+        dom.referencers_of(target)
+            .map(|(referent, property)| {
+                (
+                    dom.get_by_ref(referent).unwrap().name.clone(),
+                    property.to_owned(),
+                )
+            })
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected
+            .iter()
+            .map(|(name, property)| (name.to_string(), property.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn scan_and_index_agree() {
+        let (mut dom, model_ref, base_ref, door_ref) = weld_dom();
+
+        let scanned: Vec<_> = [model_ref, base_ref, door_ref]
+            .iter()
+            .map(|target| referencers(&dom, *target))
+            .collect();
+
+        assert_eq!(scanned[0], pairs(&[("ObjectValue", "Value")]));
+        assert_eq!(
+            scanned[1],
+            pairs(&[("Model", "PrimaryPart"), ("Weld", "Part0")])
+        );
+        assert_eq!(scanned[2], pairs(&[("Weld", "Part1")]));
+
+        dom.enable_ref_index();
+        let indexed: Vec<_> = [model_ref, base_ref, door_ref]
+            .iter()
+            .map(|target| referencers(&dom, *target))
+            .collect();
+        assert_eq!(indexed, scanned);
+
+        let snapshot = RefIndex::build(&dom);
+        assert_eq!(snapshot.referencers_of(base_ref).count(), 2);
+        assert_eq!(snapshot.referencers_of(Ref::new()).count(), 0);
+    }
+
+    #[test]
+    fn mutation_after_build() {
+        let (mut dom, model_ref, base_ref, door_ref) = weld_dom();
+        dom.enable_ref_index();
+        assert_eq!(dom.referencers_of(door_ref).count(), 1);
+
+        // Changing a property through `get_by_ref_mut` throws the index away.
+        let weld_ref = dom.get_by_ref(model_ref).unwrap().children()[2];
+        dom.get_by_ref_mut(weld_ref)
+            .unwrap()
+            .properties
+            .insert("Part1".to_owned(), Variant::Ref(base_ref));
+        assert_eq!(referencers(&dom, door_ref), []);
+        assert_eq!(
+            referencers(&dom, base_ref),
+            pairs(&[
+                ("Model", "PrimaryPart"),
+                ("Weld", "Part0"),
+                ("Weld", "Part1")
+            ])
+        );
+
+        // Inserted instances are added to the index that was just rebuilt.
+        let root_ref = dom.root_ref();
+        dom.insert(
+            root_ref,
+            InstanceBuilder::new("ObjectValue")
+                .with_name("Second")
+                .with_property("Value", door_ref),
+        );
+        assert_eq!(referencers(&dom, door_ref), pairs(&[("Second", "Value")]));
+
+        // Destroying instances removes the Refs they had.
+        dom.destroy(weld_ref);
+        assert_eq!(
+            referencers(&dom, base_ref),
+            pairs(&[("Model", "PrimaryPart")])
+        );
+
+        // Instances that are moved to another dom take their Refs with them.
+        let mut other = WeakDom::new(InstanceBuilder::new("Folder"));
+        other.enable_ref_index();
+        assert_eq!(other.referencers_of(base_ref).count(), 0);
+
+        let other_root = other.root_ref();
+        dom.transfer(model_ref, &mut other, other_root);
+        assert_eq!(dom.referencers_of(base_ref).count(), 0);
+        assert_eq!(
+            referencers(&other, base_ref),
+            pairs(&[("Model", "PrimaryPart")])
+        );
+
+        dom.disable_ref_index();
+        assert_eq!(referencers(&dom, door_ref), pairs(&[("Second", "Value")]));
+    }
+}
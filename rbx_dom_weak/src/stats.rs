@@ -0,0 +1,341 @@
+use std::{cmp::Reverse, collections::BTreeMap, mem};
+
+use rbx_types::{Ref, Variant};
+
+use crate::dom::WeakDom;
+
+/// The upper bounds of the buckets in [`DomStats::size_histogram`], in bytes.
+/// Values larger than the last bound go in a final bucket with no bound.
+const BUCKET_BOUNDS: [usize; 6] = [16, 256, 4 * 1024, 64 * 1024, 1024 * 1024, 16 * 1024 * 1024];
+
+/// Options available for [`WeakDom::stats_with_options`].
+#[derive(Debug, Clone)]
+pub struct StatsOptions {
+    largest_instances: usize,
+}
+
+impl StatsOptions {
+    /// Constructs a `StatsOptions` with all values set to their defaults.
+    #[inline]
+    pub fn new() -> Self {
+        StatsOptions {
+            largest_instances: 10,
+        }
+    }
+
+    /// Sets how many instances are listed in
+    /// [`DomStats::largest_instances`].
+    ///
+    /// This is 10 by default.
+    #[inline]
+    pub fn largest_instances(self, largest_instances: usize) -> Self {
+        StatsOptions { largest_instances }
+    }
+}
+
+impl Default for StatsOptions {
+    fn default() -> StatsOptions {
+        StatsOptions::new()
+    }
+}
+
+/// Statistics about an instance and its descendants, created by
+/// [`WeakDom::stats`].
+///
+/// Sizes are estimates of how many bytes property values take up in memory.
+/// Strings and other variable-length values count as the length of their
+/// contents, read from a blob's length when they're kept in a blob store.
+/// Values with a fixed size count as the size of a `Variant`. These are the
+/// same estimates that [`InsertLimits::max_property_bytes`][crate::InsertLimits::max_property_bytes]
+/// checks against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DomStats {
+    /// The number of instances visited.
+    pub instance_count: usize,
+
+    /// The number of instances of each class.
+    pub class_counts: BTreeMap<String, usize>,
+
+    /// The instances whose properties take up the most bytes, largest first.
+    /// Instances of the same size are listed in the order they were visited.
+    pub largest_instances: Vec<InstanceSize>,
+
+    /// How many property values fall into each size range, from smallest to
+    /// largest.
+    pub size_histogram: Vec<SizeBucket>,
+
+    /// The total size of each property across every instance that has it,
+    /// keyed by the name the property is stored under.
+    pub property_totals: BTreeMap<String, PropertyTotal>,
+}
+
+/// One entry in [`DomStats::largest_instances`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct InstanceSize {
+    /// The referent of the instance.
+    pub referent: Ref,
+
+    /// The full name of the instance, like `Workspace.Part`.
+    pub instance: String,
+
+    /// The class of the instance.
+    pub class: String,
+
+    /// The size of the instance's properties, counting the length of each
+    /// property's name along with its value.
+    pub bytes: usize,
+}
+
+/// One entry in [`DomStats::size_histogram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SizeBucket {
+    /// The largest size of a value in this bucket, or `None` for the last
+    /// bucket, which holds everything larger than the ones before it.
+    pub max_bytes: Option<usize>,
+
+    /// The number of values in this bucket.
+    pub count: usize,
+}
+
+/// One entry in [`DomStats::property_totals`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct PropertyTotal {
+    /// The size of every value of the property added together.
+    pub bytes: usize,
+
+    /// The number of instances with a value for the property.
+    pub instance_count: usize,
+}
+
+impl WeakDom {
+    /// Collects statistics about an instance and all of its descendants. If
+    /// the instance isn't in the dom, the statistics are empty.
+    pub fn stats(&self, root: Ref) -> DomStats {
+        self.stats_with_options(root, StatsOptions::default())
+    }
+
+    /// The same as [`WeakDom::stats`], but with the given options.
+    pub fn stats_with_options(&self, root: Ref, options: StatsOptions) -> DomStats {
+        let mut stats = DomStats {
+            instance_count: 0,
+            class_counts: BTreeMap::new(),
+            largest_instances: Vec::new(),
+            size_histogram: BUCKET_BOUNDS
+                .iter()
+                .map(|bound| Some(*bound))
+                .chain(Some(None))
+                .map(|max_bytes| SizeBucket {
+                    max_bytes,
+                    count: 0,
+                })
+                .collect(),
+            property_totals: BTreeMap::new(),
+        };
+
+        let mut sizes = Vec::new();
+
+        for instance in self.descendants(root) {
+            stats.instance_count += 1;
+            *stats
+                .class_counts
+                .entry(instance.class.clone())
+                .or_insert(0) += 1;
+
+            let mut instance_bytes = 0;
+
+            for (name, value) in &instance.properties {
+                let bytes = value_size(value);
+                instance_bytes += name.len() + bytes;
+
+                let bucket = BUCKET_BOUNDS
+                    .iter()
+                    .position(|bound| bytes <= *bound)
+                    .unwrap_or(BUCKET_BOUNDS.len());
+                stats.size_histogram[bucket].count += 1;
+
+                let total = stats.property_totals.entry(name.clone()).or_default();
+                total.bytes += bytes;
+                total.instance_count += 1;
+            }
+
+            sizes.push((instance_bytes, instance.referent()));
+        }
+
+        // The sort is stable, so instances of the same size stay in the order
+        // they were visited.
+        sizes.sort_by_key(|(bytes, _)| Reverse(*bytes));
+        sizes.truncate(options.largest_instances);
+
+        stats.largest_instances = sizes
+            .into_iter()
+            .map(|(bytes, referent)| InstanceSize {
+                referent,
+                instance: self.full_name(referent).unwrap(),
+                class: self.get_by_ref(referent).unwrap().class.clone(),
+                bytes,
+            })
+            .collect();
+
+        stats
+    }
+}
+
+/// Estimates how many bytes a property takes up, counting its name.
+pub(crate) fn property_size(name: &str, value: &Variant) -> usize {
+    name.len() + value_size(value)
+}
+
+/// Estimates how many bytes a property value takes up, without reading values
+/// that are kept in a blob store.
+fn value_size(value: &Variant) -> usize {
+    let fixed = mem::size_of::<Variant>();
+
+    match value {
+        Variant::String(value) => value.len(),
+        Variant::Content(value) => AsRef::<str>::as_ref(value).len(),
+        Variant::BinaryString(value) => match value.blob() {
+            Some(blob) => blob.len() as usize,
+            None => AsRef::<[u8]>::as_ref(value).len(),
+        },
+        Variant::SharedString(value) => match value.blob() {
+            Some(blob) => blob.len() as usize,
+            None => value.data().len(),
+        },
+        Variant::ColorSequence(value) => fixed + mem::size_of_val(&value.keypoints[..]),
+        Variant::NumberSequence(value) => fixed + mem::size_of_val(&value.keypoints[..]),
+        _ => fixed,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::{BinaryString, SharedString};
+
+    use crate::InstanceBuilder;
+
+    fn bucket_counts(stats: &DomStats) -> Vec<usize> {
+        stats
+            .size_histogram
+            .iter()
+            .map(|bucket| bucket.count)
+            .collect()
+    }
+
+    /// A place with scripts and mesh parts whose payloads have known sizes.
+    /// Every instance's only property is its payload.
+    fn payload_dom() -> WeakDom {
+        let script = |name: &str, len: usize| {
+            InstanceBuilder::new("Script")
+                .with_name(name)
+                .with_property("Source", "x".repeat(len))
+        };
+        let mesh = |name: &str, len: usize| {
+            InstanceBuilder::new("MeshPart")
+                .with_name(name)
+                .with_property("PhysicsData", BinaryString::from(vec![0; len]))
+        };
+
+        WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(
+                    InstanceBuilder::new("Workspace")
+                        .with_child(mesh("Rock", 5000))
+                        .with_child(mesh("Pebble", 10))
+                        .with_child(mesh("Boulder", 100_000)),
+                )
+                .with_child(
+                    InstanceBuilder::new("ServerScriptService")
+                        .with_child(script("Main", 2000))
+                        .with_child(script("Tiny", 10))
+                        .with_child(script("Huge", 2_000_000)),
+                ),
+        )
+    }
+
+    #[test]
+    fn largest_instances() {
+        let dom = payload_dom();
+        let stats =
+            dom.stats_with_options(dom.root_ref(), StatsOptions::new().largest_instances(4));
+
+        let largest: Vec<_> = stats
+            .largest_instances
+            .iter()
+            .map(|size| (size.instance.as_str(), size.class.as_str(), size.bytes))
+            .collect();
+
+        assert_eq!(
+            largest,
+            [
+                ("DataModel.ServerScriptService.Huge", "Script", 2_000_006),
+                ("DataModel.Workspace.Boulder", "MeshPart", 100_011),
+                ("DataModel.Workspace.Rock", "MeshPart", 5011),
+                ("DataModel.ServerScriptService.Main", "Script", 2006),
+            ]
+        );
+    }
+
+    #[test]
+    fn totals() {
+        let dom = payload_dom();
+        let stats = dom.stats(dom.root_ref());
+
+        assert_eq!(stats.instance_count, 9);
+        assert_eq!(stats.class_counts.get("MeshPart"), Some(&3));
+        assert_eq!(stats.class_counts.get("DataModel"), Some(&1));
+
+        // Only six instances have properties, and the rest have nothing to
+        // sort them by, so they come last in the order they were visited.
+        assert_eq!(stats.largest_instances.len(), 9);
+        assert_eq!(stats.largest_instances[6].instance, "DataModel");
+        assert_eq!(stats.largest_instances[6].bytes, 0);
+
+        assert_eq!(
+            stats.property_totals.get("PhysicsData"),
+            Some(&PropertyTotal {
+                bytes: 105_010,
+                instance_count: 3,
+            })
+        );
+        assert_eq!(
+            stats.property_totals.get("Source"),
+            Some(&PropertyTotal {
+                bytes: 2_002_010,
+                instance_count: 3,
+            })
+        );
+
+        assert_eq!(bucket_counts(&stats), [2, 0, 1, 1, 1, 1, 0]);
+        assert_eq!(stats.size_histogram[6].max_bytes, None);
+    }
+
+    #[test]
+    fn subtree_and_fixed_sizes() {
+        let mesh = SharedString::new(vec![1; 300]);
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Part")
+                    .with_property("Anchored", true)
+                    .with_property("MeshData", mesh),
+            ),
+        );
+        let part_ref = dom.root().children()[0];
+
+        let stats = dom.stats(part_ref);
+        assert_eq!(stats.instance_count, 1);
+        assert_eq!(
+            stats.largest_instances[0].bytes,
+            "Anchored".len() + mem::size_of::<Variant>() + "MeshData".len() + 300
+        );
+
+        let missing = dom.stats(Ref::new());
+        assert_eq!(missing.instance_count, 0);
+        assert!(missing.largest_instances.is_empty());
+    }
+}
@@ -104,7 +104,10 @@ impl DomViewer {
                             write!(hash_hex, "{:02x}", byte).unwrap();
                         }
                         ViewedValue::SharedString {
-                            len: shared_string.data().len(),
+                            len: match shared_string.blob() {
+                                Some(blob) => blob.len() as usize,
+                                None => shared_string.data().len(),
+                            },
                             hash: hash_hex,
                         }
                     }
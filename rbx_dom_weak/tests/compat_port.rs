@@ -0,0 +1,136 @@
+//! A small program written against the rbx_dom_weak 1.x API, ported to the
+//! `compat` module by changing nothing but its imports.
+
+#![cfg(feature = "compat")]
+
+use std::collections::HashMap;
+
+use rbx_dom_weak::compat::{RbxId, RbxInstanceProperties, RbxTree, RbxValue};
+
+fn script(name: &str, source: &str) -> RbxInstanceProperties {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "Source".to_owned(),
+        RbxValue::String {
+            value: source.to_owned(),
+        },
+    );
+    properties.insert("Disabled".to_owned(), RbxValue::Bool { value: false });
+
+    RbxInstanceProperties {
+        name: name.to_owned(),
+        class_name: "Script".to_owned(),
+        properties,
+    }
+}
+
+fn folder(name: &str) -> RbxInstanceProperties {
+    RbxInstanceProperties {
+        name: name.to_owned(),
+        class_name: "Folder".to_owned(),
+        properties: HashMap::new(),
+    }
+}
+
+/// Builds a place with a few scripts scattered around it, some of them empty.
+fn build_place() -> (RbxTree, RbxId) {
+    let mut tree = RbxTree::new(RbxInstanceProperties {
+        name: "Place".to_owned(),
+        class_name: "DataModel".to_owned(),
+        properties: HashMap::new(),
+    });
+    let root_id = tree.get_root_id();
+
+    let workspace_id = tree.insert_instance(folder("Workspace"), root_id);
+    tree.insert_instance(script("Spin", "while true do\n\twait()\nend"), workspace_id);
+    tree.insert_instance(script("Placeholder", ""), workspace_id);
+
+    let old_id = tree.insert_instance(folder("Old"), root_id);
+    tree.insert_instance(script("Legacy", "print('hi')"), old_id);
+
+    (tree, old_id)
+}
+
+/// Finds the ID of every script under the root, along with how many lines of
+/// source it has.
+fn script_lines(tree: &RbxTree) -> Vec<(RbxId, usize)> {
+    let mut scripts = Vec::new();
+
+    for instance in tree.descendants(tree.get_root_id()) {
+        if instance.class_name != "Script" {
+            continue;
+        }
+
+        let lines = match instance.properties.get("Source") {
+            Some(RbxValue::String { value }) => value.lines().count(),
+            _ => 0,
+        };
+
+        scripts.push((instance.get_id(), lines));
+    }
+
+    scripts
+}
+
+#[test]
+fn ported_program() {
+    let (mut tree, old_id) = build_place();
+    let root_id = tree.get_root_id();
+
+    // Disable every empty script, and move the rest into one folder.
+    let scripts_id = tree.insert_instance(folder("Scripts"), root_id);
+
+    for (id, lines) in script_lines(&tree) {
+        if lines == 0 {
+            tree.get_instance_mut(id)
+                .unwrap()
+                .properties
+                .insert("Disabled".to_owned(), RbxValue::Bool { value: true });
+        } else {
+            tree.set_parent(id, scripts_id);
+        }
+    }
+
+    let old = tree.remove_instance(old_id).unwrap();
+    assert_eq!(old.get_instance(old_id).unwrap().name, "Old");
+    assert!(old
+        .get_instance(old_id)
+        .unwrap()
+        .get_children_ids()
+        .is_empty());
+
+    let scripts = tree.get_instance(scripts_id).unwrap();
+    let moved: Vec<String> = scripts
+        .get_children_ids()
+        .iter()
+        .map(|&id| tree.get_instance(id).unwrap().name.clone())
+        .collect();
+    assert_eq!(moved, ["Spin", "Legacy"]);
+
+    let placeholder = tree
+        .descendants(root_id)
+        .find(|instance| instance.name == "Placeholder")
+        .unwrap();
+    assert_eq!(
+        placeholder.properties.get("Disabled"),
+        Some(&RbxValue::Bool { value: true })
+    );
+
+    // The result is an ordinary WeakDom underneath.
+    let dom = tree.into_dom();
+    let full_names: Vec<String> = dom
+        .descendants(dom.root_ref())
+        .map(|instance| dom.full_name(instance.referent()).unwrap())
+        .collect();
+    assert_eq!(
+        full_names,
+        [
+            "Place",
+            "Place.Workspace",
+            "Place.Workspace.Placeholder",
+            "Place.Scripts",
+            "Place.Scripts.Spin",
+            "Place.Scripts.Legacy",
+        ]
+    );
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeSet;
+
+use crate::ReflectionDatabase;
+
+/// A set of class names, used to pick out the instances that matter for a job
+/// like extracting every script from a place.
+///
+/// The presets include subclasses that the given database knows about, so
+/// `ClassFilter::geometry` covers `MeshPart` and `UnionOperation` as well as
+/// `Part`. Filters can be combined:
+///
+/// ```
+/// use rbx_reflection::ClassFilter;
+///
+/// let modules = ClassFilter::new(["ModuleScript", "Folder"].iter().copied());
+///
+/// let only_modules = ClassFilter::scripts().intersection(&modules);
+/// assert!(only_modules.contains("ModuleScript"));
+/// assert!(!only_modules.contains("Script"));
+/// assert!(!only_modules.contains("Folder"));
+///
+/// let scripts_and_folders = ClassFilter::scripts().union(&modules);
+/// assert!(scripts_and_folders.contains("Script"));
+/// assert!(scripts_and_folders.contains("Folder"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClassFilter {
+    classes: BTreeSet<String>,
+}
+
+impl ClassFilter {
+    /// Creates a filter that contains exactly the given classes.
+    pub fn new<I, S>(classes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            classes: classes.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Creates a filter that contains every class in the database that is one
+    /// of the given classes or a subclass of one.
+    pub fn descendants_of(database: &ReflectionDatabase, ancestors: &[&str]) -> Self {
+        let classes = database
+            .classes
+            .keys()
+            .filter(|class| {
+                ancestors
+                    .iter()
+                    .any(|ancestor| database.is_a(class, ancestor))
+            })
+            .map(|class| class.to_string())
+            .collect();
+
+        Self { classes }
+    }
+
+    /// Every kind of script: `Script`, `LocalScript`, `ModuleScript`, and
+    /// `CoreScript`.
+    ///
+    /// Unlike the other presets, this one doesn't need a database, since
+    /// Roblox doesn't add new script classes.
+    pub fn scripts() -> Self {
+        Self::new(
+            ["Script", "LocalScript", "ModuleScript", "CoreScript"]
+                .iter()
+                .copied(),
+        )
+    }
+
+    /// 2D user interface: containers like `ScreenGui` and `BillboardGui`,
+    /// elements like `Frame` and `TextLabel`, and modifiers like `UICorner`
+    /// and `UIListLayout`.
+    pub fn gui(database: &ReflectionDatabase) -> Self {
+        Self::descendants_of(database, &["GuiBase2d", "UIBase"])
+    }
+
+    /// The physical parts of a model: every kind of `BasePart`, plus the
+    /// `WeldConstraint`s and `Attachment`s that hold them together.
+    pub fn geometry(database: &ReflectionDatabase) -> Self {
+        Self::descendants_of(database, &["BasePart", "WeldConstraint", "Attachment"])
+    }
+
+    /// Returns a filter containing the classes in either filter.
+    pub fn union(&self, other: &ClassFilter) -> ClassFilter {
+        Self {
+            classes: self.classes.union(&other.classes).cloned().collect(),
+        }
+    }
+
+    /// Returns a filter containing only the classes in both filters.
+    pub fn intersection(&self, other: &ClassFilter) -> ClassFilter {
+        Self {
+            classes: self.classes.intersection(&other.classes).cloned().collect(),
+        }
+    }
+
+    /// Tells whether the filter contains the given class.
+    pub fn contains(&self, class_name: &str) -> bool {
+        self.classes.contains(class_name)
+    }
+
+    /// Iterates over the classes in the filter in alphabetical order.
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.classes.iter().map(String::as_str)
+    }
+}
@@ -39,6 +39,62 @@ impl<'a> ReflectionDatabase<'a> {
             enums: HashMap::new(),
         }
     }
+
+    /// Tells whether the class named `class_name` is `ancestor_name` or one of
+    /// its subclasses. Classes that aren't in the database aren't anything.
+    pub fn is_a(&self, class_name: &str, ancestor_name: &str) -> bool {
+        let mut current = self.classes.get(class_name);
+
+        while let Some(class) = current {
+            if class.name == ancestor_name {
+                return true;
+            }
+
+            current = class
+                .superclass
+                .as_deref()
+                .and_then(|name| self.classes.get(name));
+        }
+
+        false
+    }
+
+    /// Lists the properties that would fail to encode with a format that can
+    /// only write values of the `supported` types, as pairs of class and
+    /// property names, sorted by class and then by property.
+    ///
+    /// Only properties that serialize are checked, using the type of the
+    /// property that each one is written as. Aliases are left out, since
+    /// they're written through the property they alias.
+    pub fn unsupported_properties(&self, supported: &[VariantType]) -> Vec<(&str, &str)> {
+        let mut unsupported = Vec::new();
+
+        for (class_name, class) in &self.classes {
+            for (property_name, property) in &class.properties {
+                let serialized = match &property.kind {
+                    PropertyKind::Canonical {
+                        serialization: PropertySerialization::Serializes,
+                    } => property,
+                    PropertyKind::Canonical {
+                        serialization: PropertySerialization::SerializesAs(name),
+                    } => class.properties.get(name).unwrap_or(property),
+                    _ => continue,
+                };
+
+                let ty = match &serialized.data_type {
+                    DataType::Value(ty) => *ty,
+                    DataType::Enum(_) => VariantType::Enum,
+                };
+
+                if !supported.contains(&ty) {
+                    unsupported.push((class_name.as_ref(), property_name.as_ref()));
+                }
+            }
+        }
+
+        unsupported.sort_unstable();
+        unsupported
+    }
 }
 
 /// Describes a class of Instance, its properties, and its relation to other
@@ -190,6 +246,12 @@ pub struct EnumDescriptor<'a> {
     /// All of the members of this enum, stored as a map from names to values.
     #[serde(serialize_with = "crate::serde_util::ordered_map")]
     pub items: HashMap<Cow<'a, str>, u32>,
+
+    /// Values that files saved by older versions of Roblox can contain, mapped
+    /// to the values that mean the same thing today. These come from items
+    /// that were renamed, renumbered, or retired in favor of another item.
+    #[serde(default, serialize_with = "crate::serde_util::ordered_map")]
+    pub value_migrations: HashMap<u32, u32>,
 }
 
 impl<'a> EnumDescriptor<'a> {
@@ -198,6 +260,14 @@ impl<'a> EnumDescriptor<'a> {
         Self {
             name: name.into(),
             items: HashMap::new(),
+            value_migrations: HashMap::new(),
         }
     }
+
+    /// Returns the value that a value from an older file should be replaced
+    /// with, if it's one of this enum's
+    /// [`value_migrations`][EnumDescriptor::value_migrations].
+    pub fn migrate_value(&self, value: u32) -> Option<u32> {
+        self.value_migrations.get(&value).copied()
+    }
 }
@@ -1,8 +1,10 @@
+mod class_filter;
 mod class_tag;
 mod database;
 mod property_tag;
 mod serde_util;
 
+pub use class_filter::*;
 pub use class_tag::*;
 pub use database::*;
 pub use property_tag::*;
@@ -0,0 +1,421 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Write},
+};
+
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    WeakDom,
+};
+
+/// One way that two doms compared by [`compare_doms`] differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomMismatch {
+    /// The full name of the instance that differs, like
+    /// `DataModel.Workspace.Part`, as it's named in the left dom.
+    pub path: String,
+
+    /// What differs about the instance.
+    pub description: String,
+}
+
+impl fmt::Display for DomMismatch {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(output, "{}: {}", self.path, self.description)
+    }
+}
+
+/// Compares the trees of instances in two doms, starting from their roots, and
+/// returns every place they differ.
+///
+/// Instances match when their classes, names, and properties are the same, and
+/// their children match in the same order. The order that properties were
+/// inserted doesn't matter. Refs match when they point to the same place in
+/// each tree, since the Ref values themselves are different in every dom.
+/// Other values are compared with `==`, so NaN floats never match.
+pub fn compare_doms(left: &WeakDom, right: &WeakDom) -> Vec<DomMismatch> {
+    let mut comparison = Comparison {
+        left: Side::new(left),
+        right: Side::new(right),
+        mismatches: Vec::new(),
+    };
+
+    let root = left.root();
+    comparison.compare_instance(left.root_ref(), right.root_ref(), root.name.clone());
+    comparison.mismatches
+}
+
+/// Formats the mismatches returned by [`compare_doms`] the way that
+/// [`assert_dom_eq!`] prints them.
+pub fn format_mismatches(mismatches: &[DomMismatch]) -> String {
+    let mut output = String::new();
+
+    let plural = if mismatches.len() == 1 { "" } else { "s" };
+    writeln!(
+        output,
+        "doms are not equal ({} difference{}):",
+        mismatches.len(),
+        plural
+    )
+    .unwrap();
+
+    for mismatch in mismatches {
+        writeln!(output, "  {}:", mismatch.path).unwrap();
+
+        for line in mismatch.description.lines() {
+            writeln!(output, "      {}", line).unwrap();
+        }
+    }
+
+    output
+}
+
+/// Asserts that two doms hold the same trees of instances, as decided by
+/// [`compare_doms`](crate::compare_doms). When they don't, the panic message
+/// lists every instance that differs and how.
+///
+/// ```
+/// use rbx_test_util::{assert_dom_eq, dom_tree};
+///
+/// let left = dom_tree! { Folder { Part { Anchored = true } } };
+/// let right = dom_tree! { Folder { Part { Anchored = true } } };
+///
+/// assert_dom_eq!(left, right);
+/// ```
+#[macro_export]
+macro_rules! assert_dom_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        let mismatches = $crate::compare_doms(&$left, &$right);
+
+        if !mismatches.is_empty() {
+            panic!("{}", $crate::format_mismatches(&mismatches));
+        }
+    }};
+}
+
+struct Side<'a> {
+    dom: &'a WeakDom,
+
+    /// The position of every instance in the dom, as the index of each
+    /// instance's ancestor among its siblings, starting from the root.
+    positions: HashMap<Ref, Vec<usize>>,
+}
+
+impl<'a> Side<'a> {
+    fn new(dom: &'a WeakDom) -> Self {
+        let mut positions = HashMap::new();
+        let mut stack = vec![(dom.root_ref(), Vec::new())];
+
+        while let Some((referent, position)) = stack.pop() {
+            let instance = dom.get_by_ref(referent).unwrap();
+
+            for (index, child) in instance.children().iter().enumerate() {
+                let mut child_position = position.clone();
+                child_position.push(index);
+                stack.push((*child, child_position));
+            }
+
+            positions.insert(referent, position);
+        }
+
+        Self { dom, positions }
+    }
+
+    /// Describes where a Ref points, for mismatch messages.
+    fn describe_ref(&self, referent: Ref) -> String {
+        if referent.is_none() {
+            return "a null Ref".to_owned();
+        }
+
+        match self.positions.get(&referent) {
+            Some(position) => {
+                let mut current = self.dom.root();
+                let mut name = current.name.clone();
+
+                for index in position {
+                    current = self.dom.get_by_ref(current.children()[*index]).unwrap();
+                    name.push('.');
+                    name.push_str(&current.name);
+                }
+
+                format!("a Ref to {}", name)
+            }
+            None => format!("a Ref to {:?}, which isn't in the dom", referent),
+        }
+    }
+}
+
+struct Comparison<'a> {
+    left: Side<'a>,
+    right: Side<'a>,
+    mismatches: Vec<DomMismatch>,
+}
+
+impl Comparison<'_> {
+    fn push(&mut self, path: &str, description: String) {
+        self.mismatches.push(DomMismatch {
+            path: path.to_owned(),
+            description,
+        });
+    }
+
+    fn compare_instance(&mut self, left_ref: Ref, right_ref: Ref, path: String) {
+        let left = self.left.dom.get_by_ref(left_ref).unwrap();
+        let right = self.right.dom.get_by_ref(right_ref).unwrap();
+
+        if left.class != right.class {
+            self.push(
+                &path,
+                format!(
+                    "ClassName is {:?} on the left but {:?} on the right",
+                    left.class, right.class
+                ),
+            );
+        }
+
+        if left.name != right.name {
+            self.push(
+                &path,
+                format!(
+                    "Name is {:?} on the left but {:?} on the right",
+                    left.name, right.name
+                ),
+            );
+        }
+
+        let property_names: BTreeSet<&String> = left
+            .properties
+            .keys()
+            .chain(right.properties.keys())
+            .collect();
+
+        for name in property_names {
+            match (left.properties.get(name), right.properties.get(name)) {
+                (Some(left_value), Some(right_value)) => {
+                    if !self.values_match(left_value, right_value) {
+                        let description = format!(
+                            "property {} differs\n left: {}\nright: {}",
+                            name,
+                            self.left.describe(left_value),
+                            self.right.describe(right_value)
+                        );
+                        self.push(&path, description);
+                    }
+                }
+                (Some(value), None) => {
+                    let description = format!(
+                        "property {} is only on the left: {}",
+                        name,
+                        self.left.describe(value)
+                    );
+                    self.push(&path, description);
+                }
+                (None, Some(value)) => {
+                    let description = format!(
+                        "property {} is only on the right: {}",
+                        name,
+                        self.right.describe(value)
+                    );
+                    self.push(&path, description);
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+
+        let left_children = left.children();
+        let right_children = right.children();
+
+        for (left_child, right_child) in left_children.iter().zip(right_children) {
+            let child = self.left.dom.get_by_ref(*left_child).unwrap();
+            let child_path = format!("{}.{}", path, child.name);
+            self.compare_instance(*left_child, *right_child, child_path);
+        }
+
+        if left_children.len() != right_children.len() {
+            let (side, extra, dom) = if left_children.len() > right_children.len() {
+                (
+                    "left",
+                    &left_children[right_children.len()..],
+                    self.left.dom,
+                )
+            } else {
+                (
+                    "right",
+                    &right_children[left_children.len()..],
+                    self.right.dom,
+                )
+            };
+
+            let mut description = format!(
+                "has {} children on the left but {} on the right; the extra {} on the {}:",
+                left_children.len(),
+                right_children.len(),
+                if extra.len() == 1 {
+                    "one is"
+                } else {
+                    "ones are"
+                },
+                side
+            );
+
+            for referent in extra {
+                let child = dom.get_by_ref(*referent).unwrap();
+                write!(description, "\n  {} {:?}", child.class, child.name).unwrap();
+            }
+
+            self.push(&path, description);
+        }
+    }
+
+    fn values_match(&self, left: &Variant, right: &Variant) -> bool {
+        match (left, right) {
+            (Variant::Ref(left), Variant::Ref(right)) => {
+                match (left.is_none(), right.is_none()) {
+                    (true, true) => true,
+                    (false, false) => {
+                        match (
+                            self.left.positions.get(left),
+                            self.right.positions.get(right),
+                        ) {
+                            (Some(left), Some(right)) => left == right,
+                            // Refs that point outside of each dom can only be
+                            // compared by their values.
+                            (None, None) => left == right,
+                            _ => false,
+                        }
+                    }
+                    _ => false,
+                }
+            }
+            _ => left == right,
+        }
+    }
+}
+
+impl Side<'_> {
+    fn describe(&self, value: &Variant) -> String {
+        match value {
+            Variant::Ref(referent) => self.describe_ref(*referent),
+            other => format!("{:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::types::Vector3;
+
+    use crate::dom_tree;
+
+    #[test]
+    fn equal_trees() {
+        let left = dom_tree! {
+            Folder "Root" {
+                Part { Anchored = true, Transparency = 0.5f32 }
+                Folder {}
+            }
+        };
+
+        // Properties that are inserted in a different order still match.
+        let right = dom_tree! {
+            Folder "Root" {
+                Part { Transparency = 0.5f32, Anchored = true }
+                Folder {}
+            }
+        };
+
+        assert_eq!(compare_doms(&left, &right), []);
+        assert_dom_eq!(left, right);
+    }
+
+    #[test]
+    fn refs_match_by_position() {
+        let make = || {
+            let mut dom = dom_tree! {
+                Folder {
+                    ObjectValue "Pointer" {}
+                    Part "Target" {}
+                }
+            };
+
+            let root = dom.root();
+            let (pointer, target) = (root.children()[0], root.children()[1]);
+            dom.get_by_ref_mut(pointer)
+                .unwrap()
+                .properties
+                .insert("Value".to_owned(), Variant::Ref(target));
+
+            dom
+        };
+
+        let (left, mut right) = (make(), make());
+        assert_dom_eq!(left, right);
+
+        let root = right.root();
+        let pointer = root.children()[0];
+        right
+            .get_by_ref_mut(pointer)
+            .unwrap()
+            .properties
+            .insert("Value".to_owned(), Variant::Ref(pointer));
+
+        assert_eq!(
+            compare_doms(&left, &right),
+            [DomMismatch {
+                path: "Folder.Pointer".to_owned(),
+                description: "property Value differs\n \
+                              left: a Ref to Folder.Target\n\
+                              right: a Ref to Folder.Pointer"
+                    .to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn mismatches() {
+        let left = dom_tree! {
+            Model "Car" {
+                Part "Body" {
+                    Size = Vector3::new(4.0, 1.0, 2.0),
+                    Anchored = true,
+                }
+                Part "Wheel" {}
+            }
+        };
+        let right = dom_tree! {
+            Model "Truck" {
+                Part "Body" {
+                    Size = Vector3::new(4.0, 2.0, 2.0),
+                    Locked = true,
+                }
+                Part "Wheel" {}
+                Part "Wheel" {}
+                Seat {}
+            }
+        };
+
+        let mismatches = compare_doms(&left, &right);
+        let descriptions: Vec<_> = mismatches.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            descriptions,
+            [
+                "Car: Name is \"Car\" on the left but \"Truck\" on the right",
+                "Car.Body: property Anchored is only on the left: Bool(true)",
+                "Car.Body: property Locked is only on the right: Bool(true)",
+                "Car.Body: property Size differs\n \
+                 left: Vector3(Vector3 { x: 4.0, y: 1.0, z: 2.0 })\n\
+                 right: Vector3(Vector3 { x: 4.0, y: 2.0, z: 2.0 })",
+                "Car: has 2 children on the left but 4 on the right; the extra ones are on the \
+                 right:\n  Part \"Wheel\"\n  Seat \"Seat\"",
+            ]
+        );
+
+        let formatted = format_mismatches(&mismatches);
+        assert!(formatted.starts_with("doms are not equal (5 differences):\n"));
+        assert!(
+            formatted.contains("\n  Car.Body:\n      property Size differs\n       left: Vector3")
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use rbx_dom_weak::WeakDom;
+
+/// Encodes every instance under the root of a dom as an XML model.
+///
+/// Panics if encoding fails.
+pub fn encode_xml(dom: &WeakDom, options: rbx_xml::EncodeOptions) -> String {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options)
+        .expect("failed to encode XML model");
+
+    String::from_utf8(buffer).expect("rbx_xml wrote invalid UTF-8")
+}
+
+/// Encodes every instance under the root of a dom as a binary model.
+///
+/// Panics if encoding fails.
+pub fn encode_binary(dom: &WeakDom, options: rbx_binary::EncodeOptions) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    rbx_binary::to_writer(&mut buffer, dom, dom.root().children(), options)
+        .expect("failed to encode binary model");
+
+    buffer
+}
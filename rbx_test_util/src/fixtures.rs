@@ -0,0 +1,289 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The formats that models and places can be saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Binary,
+    Xml,
+}
+
+impl Format {
+    /// The other format, which a fixture's paired file is saved in.
+    pub fn other(self) -> Self {
+        match self {
+            Format::Binary => Format::Xml,
+            Format::Xml => Format::Binary,
+        }
+    }
+}
+
+/// Whether a fixture is a model or a place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixtureKind {
+    Model,
+    Place,
+}
+
+impl FixtureKind {
+    fn dir_name(self) -> &'static str {
+        match self {
+            FixtureKind::Model => "models",
+            FixtureKind::Place => "places",
+        }
+    }
+
+    fn file_name(self, format: Format) -> &'static str {
+        match (self, format) {
+            (FixtureKind::Model, Format::Binary) => "binary.rbxm",
+            (FixtureKind::Model, Format::Xml) => "xml.rbxmx",
+            (FixtureKind::Place, Format::Binary) => "binary.rbxl",
+            (FixtureKind::Place, Format::Xml) => "xml.rbxlx",
+        }
+    }
+}
+
+/// The directory holding the rbx-test-files repository, which is a submodule
+/// at the root of this repository.
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("test-files")
+}
+
+/// A model or place in the test files, which is a directory holding the same
+/// instances saved in each format that Studio could save them in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixture {
+    kind: FixtureKind,
+    dir: PathBuf,
+}
+
+impl Fixture {
+    /// The model with the given name, like `ref-child`, in the shared test
+    /// files.
+    pub fn model(name: &str) -> Self {
+        Self::in_dir(FixtureKind::Model, fixtures_dir().join("models").join(name))
+    }
+
+    /// The place with the given name in the shared test files.
+    pub fn place(name: &str) -> Self {
+        Self::in_dir(FixtureKind::Place, fixtures_dir().join("places").join(name))
+    }
+
+    /// A fixture laid out like the shared test files, but in some other
+    /// directory.
+    pub fn in_dir(kind: FixtureKind, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            kind,
+            dir: dir.into(),
+        }
+    }
+
+    /// Every fixture of the given kind in the shared test files, sorted by
+    /// name. This is empty if the submodule hasn't been checked out.
+    pub fn all(kind: FixtureKind) -> Vec<Self> {
+        Self::all_in(kind, fixtures_dir())
+    }
+
+    /// Every fixture of the given kind in a directory laid out like the shared
+    /// test files, sorted by name.
+    pub fn all_in(kind: FixtureKind, root: impl AsRef<Path>) -> Vec<Self> {
+        let entries = match fs::read_dir(root.as_ref().join(kind.dir_name())) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut fixtures: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| Self::in_dir(kind, entry.path()))
+            .collect();
+
+        fixtures.sort_by(|a, b| a.dir.cmp(&b.dir));
+        fixtures
+    }
+
+    #[inline]
+    pub fn kind(&self) -> FixtureKind {
+        self.kind
+    }
+
+    /// The fixture's name, which is the name of its directory.
+    pub fn name(&self) -> &str {
+        self.dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("")
+    }
+
+    /// Where the fixture's file in the given format is, whether or not it
+    /// exists.
+    pub fn path(&self, format: Format) -> PathBuf {
+        self.dir.join(self.kind.file_name(format))
+    }
+
+    /// Tells whether the fixture has been saved in the given format.
+    pub fn has(&self, format: Format) -> bool {
+        self.path(format).is_file()
+    }
+
+    /// Reads the fixture's file in the given format.
+    ///
+    /// ## Panics
+    /// Panics if the file can't be read, mentioning the submodule when it
+    /// looks like it hasn't been checked out.
+    pub fn read(&self, format: Format) -> Vec<u8> {
+        let path = self.path(format);
+
+        fs::read(&path).unwrap_or_else(|err| {
+            let hint = if is_empty_dir(&fixtures_dir()) {
+                "; the test files are a submodule, so run `git submodule update --init`"
+            } else {
+                ""
+            };
+
+            panic!("couldn't read fixture {}: {}{}", path.display(), err, hint)
+        })
+    }
+
+    /// Reads the fixture's XML file as a string.
+    pub fn read_xml(&self) -> String {
+        String::from_utf8(self.read(Format::Xml)).unwrap_or_else(|err| {
+            panic!(
+                "fixture {} isn't UTF-8: {}",
+                self.path(Format::Xml).display(),
+                err
+            )
+        })
+    }
+
+    /// Reads the fixture in both formats, binary first, if it has both.
+    pub fn read_pair(&self) -> Option<(Vec<u8>, Vec<u8>)> {
+        if self.has(Format::Binary) && self.has(Format::Xml) {
+            Some((self.read(Format::Binary), self.read(Format::Xml)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Finds the file saved in the other format next to a fixture's file, like
+/// `xml.rbxmx` next to `binary.rbxm`. Returns `None` if `path` isn't a
+/// fixture's file or the other file doesn't exist.
+pub fn paired_path(path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = path.as_ref();
+    let file_name = path.file_name()?.to_str()?;
+
+    let (kind, format) = [FixtureKind::Model, FixtureKind::Place]
+        .iter()
+        .flat_map(|kind| {
+            [Format::Binary, Format::Xml]
+                .iter()
+                .map(move |format| (*kind, *format))
+        })
+        .find(|(kind, format)| kind.file_name(*format) == file_name)?;
+
+    let paired = path.with_file_name(kind.file_name(format.other()));
+
+    if paired.is_file() {
+        Some(paired)
+    } else {
+        None
+    }
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    match fs::read_dir(path) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::env;
+
+    /// A directory laid out like the shared test files, with a model saved in
+    /// both formats and a place saved only as XML.
+    struct TempFixtures(PathBuf);
+
+    impl TempFixtures {
+        fn new(name: &str) -> Self {
+            let root =
+                env::temp_dir().join(format!("rbx_test_util-{}-{}", name, std::process::id()));
+
+            let model = root.join("models").join("two-parts");
+            fs::create_dir_all(&model).unwrap();
+            fs::write(model.join("binary.rbxm"), b"<roblox!").unwrap();
+            fs::write(model.join("xml.rbxmx"), "<roblox/>").unwrap();
+
+            let place = root.join("places").join("baseplate");
+            fs::create_dir_all(&place).unwrap();
+            fs::write(place.join("xml.rbxlx"), "<roblox/>").unwrap();
+
+            TempFixtures(root)
+        }
+    }
+
+    impl Drop for TempFixtures {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn paired_formats() {
+        let fixtures = TempFixtures::new("paired");
+
+        let models = Fixture::all_in(FixtureKind::Model, &fixtures.0);
+        assert_eq!(models.len(), 1);
+
+        let model = &models[0];
+        assert_eq!(model.name(), "two-parts");
+        assert_eq!(
+            model.read_pair(),
+            Some((b"<roblox!".to_vec(), b"<roblox/>".to_vec()))
+        );
+
+        assert_eq!(
+            paired_path(model.path(Format::Binary)),
+            Some(model.path(Format::Xml))
+        );
+        assert_eq!(
+            paired_path(model.path(Format::Xml)),
+            Some(model.path(Format::Binary))
+        );
+
+        let place = Fixture::in_dir(FixtureKind::Place, fixtures.0.join("places/baseplate"));
+        assert!(place.has(Format::Xml));
+        assert!(!place.has(Format::Binary));
+        assert_eq!(place.read_xml(), "<roblox/>");
+        assert_eq!(place.read_pair(), None);
+        assert_eq!(paired_path(place.path(Format::Xml)), None);
+
+        assert_eq!(
+            paired_path(fixtures.0.join("models/two-parts/notes.txt")),
+            None
+        );
+    }
+
+    #[test]
+    fn shared_fixtures() {
+        let model = Fixture::model("ref-child");
+        assert_eq!(model.name(), "ref-child");
+        assert!(model
+            .path(Format::Xml)
+            .ends_with("test-files/models/ref-child/xml.rbxmx"));
+
+        let place = Fixture::place("baseplate-566");
+        assert!(place
+            .path(Format::Binary)
+            .ends_with("test-files/places/baseplate-566/binary.rbxl"));
+    }
+}
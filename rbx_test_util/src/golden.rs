@@ -0,0 +1,164 @@
+use std::{env, fmt::Write, fs, io::ErrorKind, path::Path};
+
+/// Setting this environment variable to anything other than `0` makes
+/// [`assert_golden`] rewrite golden files instead of checking them.
+const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// The number of matching lines shown around the lines that differ.
+const CONTEXT_LINES: usize = 3;
+
+/// Asserts that `actual` is exactly the contents of the golden file at `path`.
+///
+/// When the `UPDATE_SNAPSHOTS` environment variable is set, the file is
+/// written with `actual` instead, creating it and its directory if needed.
+/// Golden files that don't exist yet are an error otherwise, so that a
+/// misspelled path doesn't quietly pass.
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+
+    if should_update() {
+        if fs::read_to_string(path).ok().as_deref() != Some(actual) {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap();
+            }
+
+            fs::write(path, actual)
+                .unwrap_or_else(|err| panic!("couldn't write {}: {}", path.display(), err));
+        }
+
+        return;
+    }
+
+    let expected = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => panic!(
+            "golden file {} doesn't exist; run the tests with {}=1 to create it",
+            path.display(),
+            UPDATE_VAR
+        ),
+        Err(err) => panic!("couldn't read golden file {}: {}", path.display(), err),
+    };
+
+    if expected != actual {
+        panic!(
+            "output doesn't match golden file {}; run the tests with {}=1 to update it\n{}",
+            path.display(),
+            UPDATE_VAR,
+            diff(&expected, actual)
+        );
+    }
+}
+
+/// Asserts that two strings are exactly the same. When they aren't, the panic
+/// message shows the lines that differ, with `-` marking the expected lines and
+/// `+` the actual ones.
+pub fn assert_text_eq(expected: &str, actual: &str) {
+    if expected != actual {
+        panic!("text doesn't match\n{}", diff(expected, actual));
+    }
+}
+
+fn should_update() -> bool {
+    match env::var_os(UPDATE_VAR) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
+/// Describes the lines that differ between two strings. The lines that both
+/// strings start and end with are left out, apart from a few around the
+/// difference.
+fn diff(expected: &str, actual: &str) -> String {
+    // Splitting on '\n' instead of using `lines` keeps differences in line
+    // endings and in the last line's newline.
+    let expected_lines: Vec<&str> = expected.split('\n').collect();
+    let actual_lines: Vec<&str> = actual.split('\n').collect();
+
+    let prefix = expected_lines
+        .iter()
+        .zip(&actual_lines)
+        .take_while(|(expected, actual)| expected == actual)
+        .count();
+
+    let max_suffix = expected_lines.len().min(actual_lines.len()) - prefix;
+    let suffix = expected_lines
+        .iter()
+        .rev()
+        .zip(actual_lines.iter().rev())
+        .take(max_suffix)
+        .take_while(|(expected, actual)| expected == actual)
+        .count();
+
+    let mut output = String::new();
+    let context_start = prefix.saturating_sub(CONTEXT_LINES);
+
+    writeln!(output, "@@ line {} @@", context_start + 1).unwrap();
+
+    for line in &expected_lines[context_start..prefix] {
+        writeln!(output, "  {:?}", line).unwrap();
+    }
+
+    for line in &expected_lines[prefix..expected_lines.len() - suffix] {
+        writeln!(output, "- {:?}", line).unwrap();
+    }
+
+    for line in &actual_lines[prefix..actual_lines.len() - suffix] {
+        writeln!(output, "+ {:?}", line).unwrap();
+    }
+
+    let context_end = (expected_lines.len() - suffix + CONTEXT_LINES).min(expected_lines.len());
+    for line in &expected_lines[expected_lines.len() - suffix..context_end] {
+        writeln!(output, "  {:?}", line).unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diff_shows_changed_lines() {
+        let expected = "a\nb\nc\nd\ne\nf\ng\nh\n";
+        let actual = "a\nb\nc\nd\nE\nE2\nf\ng\nh\n";
+
+        assert_eq!(
+            diff(expected, actual),
+            "@@ line 2 @@\n  \"b\"\n  \"c\"\n  \"d\"\n- \"e\"\n+ \"E\"\n+ \"E2\"\n  \"f\"\n  \
+             \"g\"\n  \"h\"\n"
+        );
+    }
+
+    #[test]
+    fn diff_shows_line_endings() {
+        assert_eq!(
+            diff("one\ntwo", "one\r\ntwo\n"),
+            "@@ line 1 @@\n- \"one\"\n- \"two\"\n+ \"one\\r\"\n+ \"two\"\n+ \"\"\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "text doesn't match")]
+    fn text_mismatch() {
+        assert_text_eq("<foo/>", "<foo />");
+    }
+
+    #[test]
+    fn golden_files() {
+        let dir = env::temp_dir().join(format!("rbx_test_util-golden-{}", std::process::id()));
+        let path = dir.join("nested").join("output.txt");
+
+        let missing = std::panic::catch_unwind(|| assert_golden(&path, "contents\n"));
+        assert!(missing.is_err());
+
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "contents\n").unwrap();
+        assert_golden(&path, "contents\n");
+
+        let mismatch = std::panic::catch_unwind(|| assert_golden(&path, "changed\n"));
+        assert!(mismatch.is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
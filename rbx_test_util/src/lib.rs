@@ -0,0 +1,53 @@
+//! Helpers shared by the tests of the crates in rbx-dom.
+//!
+//! Trees of instances can be written out with [`dom_tree!`] and compared with
+//! [`assert_dom_eq!`]:
+//!
+//! ```
+//! use rbx_test_util::{assert_dom_eq, dom_tree};
+//!
+//! let dom = dom_tree! {
+//!     Folder "Stuff" {
+//!         Part "Brick" {
+//!             Anchored = true,
+//!         }
+//!         Folder {}
+//!     }
+//! };
+//!
+//! let same = dom_tree! {
+//!     Folder "Stuff" {
+//!         Part "Brick" { Anchored = true }
+//!         Folder "Folder" {}
+//!     }
+//! };
+//!
+//! assert_dom_eq!(dom, same);
+//! ```
+//!
+//! Output can be checked against files with [`assert_golden`], and models and
+//! places from the shared rbx-test-files repository can be loaded in either
+//! format with [`Fixture`]. Doms can be written out in either format with
+//! [`encode_xml`] and [`encode_binary`].
+
+mod compare;
+mod encode;
+mod fixtures;
+mod golden;
+mod query;
+mod tree;
+mod xml;
+
+pub use crate::{
+    compare::{compare_doms, format_mismatches, DomMismatch},
+    encode::{encode_binary, encode_xml},
+    fixtures::{fixtures_dir, paired_path, Fixture, FixtureKind, Format},
+    golden::{assert_golden, assert_text_eq},
+    query::{first_child, textures},
+    xml::assert_xml_eq,
+};
+
+// Used by the macros in this crate, so that crates using them don't need to
+// depend on rbx_dom_weak themselves.
+#[doc(hidden)]
+pub use rbx_dom_weak::{InstanceBuilder, WeakDom};
@@ -0,0 +1,27 @@
+use rbx_dom_weak::{
+    types::{Content, Variant},
+    Instance, WeakDom,
+};
+
+/// Returns the first instance under the root of a dom, which is usually the
+/// model that a test decoded.
+pub fn first_child(dom: &WeakDom) -> &Instance {
+    dom.get_by_ref(dom.root().children()[0]).unwrap()
+}
+
+/// Returns the `Texture` of each child of the first instance under the root of
+/// a dom, like a folder full of decals.
+///
+/// Panics if any of those children don't have a `Content` texture.
+pub fn textures(dom: &WeakDom) -> Vec<&Content> {
+    first_child(dom)
+        .children()
+        .iter()
+        .map(
+            |referent| match dom.get_by_ref(*referent).unwrap().properties.get("Texture") {
+                Some(Variant::Content(content)) => content,
+                other => panic!("expected a Content texture, got {:?}", other),
+            },
+        )
+        .collect()
+}
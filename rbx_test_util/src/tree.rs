@@ -0,0 +1,163 @@
+/// Builds an [`InstanceBuilder`][rbx_dom_weak::InstanceBuilder] out of a
+/// description of a tree of instances.
+///
+/// Each instance is its ClassName, then optionally its Name as a string
+/// literal, then a block. The block contains the instance's properties, written
+/// as `Name = value` and separated by commas, and its children, which are
+/// written the same way as the instance itself. Instances without a Name are
+/// named after their class.
+///
+/// Properties whose names aren't identifiers can be written as string
+/// literals, like `"Some Property" = 5`.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+/// use rbx_test_util::instance_tree;
+///
+/// let mut dom = WeakDom::new(InstanceBuilder::new("Workspace"));
+/// let car = dom.insert(
+///     dom.root_ref(),
+///     instance_tree! {
+///         Model "Car" {
+///             Part "Body" {
+///                 Anchored = true,
+///                 Transparency = 0.5f32,
+///                 Attachment {}
+///             }
+///         }
+///     },
+/// );
+///
+/// assert_eq!(dom.get_by_ref(car).unwrap().name, "Car");
+/// ```
+#[macro_export]
+macro_rules! instance_tree {
+    ($class:ident $name:literal { $($body:tt)* }) => {
+        $crate::__instance_body!(
+            $crate::InstanceBuilder::new(stringify!($class)).with_name($name);
+            $($body)*
+        )
+    };
+    ($class:ident { $($body:tt)* }) => {
+        $crate::__instance_body!(
+            $crate::InstanceBuilder::new(stringify!($class));
+            $($body)*
+        )
+    };
+}
+
+/// Builds a [`WeakDom`][rbx_dom_weak::WeakDom] whose root is the given tree of
+/// instances, which is written the same way as it is for [`instance_tree!`].
+///
+/// ```
+/// use rbx_test_util::dom_tree;
+///
+/// let dom = dom_tree! {
+///     DataModel {
+///         Workspace {
+///             Part "Baseplate" { Anchored = true }
+///         }
+///     }
+/// };
+///
+/// assert_eq!(dom.root().class, "DataModel");
+/// ```
+#[macro_export]
+macro_rules! dom_tree {
+    ($($tree:tt)*) => {
+        $crate::WeakDom::new($crate::instance_tree!($($tree)*))
+    };
+}
+
+/// Adds the properties and children from the body of an instance in
+/// `instance_tree!` to a builder, one at a time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __instance_body {
+    ($builder:expr;) => {
+        $builder
+    };
+
+    ($builder:expr; $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__instance_body!(
+            $builder.with_property(stringify!($key), $value);
+            $($($rest)*)?
+        )
+    };
+    ($builder:expr; $key:literal = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__instance_body!(
+            $builder.with_property($key, $value);
+            $($($rest)*)?
+        )
+    };
+
+    ($builder:expr; $class:ident $name:literal { $($body:tt)* } , $($rest:tt)*) => {
+        $crate::__instance_body!(
+            $builder.with_child($crate::instance_tree!($class $name { $($body)* }));
+            $($rest)*
+        )
+    };
+    ($builder:expr; $class:ident $name:literal { $($body:tt)* } $($rest:tt)*) => {
+        $crate::__instance_body!(
+            $builder.with_child($crate::instance_tree!($class $name { $($body)* }));
+            $($rest)*
+        )
+    };
+    ($builder:expr; $class:ident { $($body:tt)* } , $($rest:tt)*) => {
+        $crate::__instance_body!(
+            $builder.with_child($crate::instance_tree!($class { $($body)* }));
+            $($rest)*
+        )
+    };
+    ($builder:expr; $class:ident { $($body:tt)* } $($rest:tt)*) => {
+        $crate::__instance_body!(
+            $builder.with_child($crate::instance_tree!($class { $($body)* }));
+            $($rest)*
+        )
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use rbx_dom_weak::types::{Variant, Vector3};
+
+    #[test]
+    fn properties_and_children() {
+        let dom = dom_tree! {
+            Folder "Root" {
+                Part "First" {
+                    Anchored = true,
+                    "Custom Property" = 5i32,
+                    Size = Vector3::new(1.0, 2.0, 3.0),
+                },
+                Part {
+                    Attachment {}
+                }
+                StringValue "Last" { Value = "hello" }
+            }
+        };
+
+        let root = dom.root();
+        assert_eq!(root.name, "Root");
+        assert_eq!(root.children().len(), 3);
+
+        let first = dom.get_by_ref(root.children()[0]).unwrap();
+        assert_eq!(first.name, "First");
+        assert_eq!(first.properties["Anchored"], Variant::Bool(true));
+        assert_eq!(first.properties["Custom Property"], Variant::Int32(5));
+        assert_eq!(
+            first.properties["Size"],
+            Variant::Vector3(Vector3::new(1.0, 2.0, 3.0))
+        );
+
+        let second = dom.get_by_ref(root.children()[1]).unwrap();
+        assert_eq!(second.name, "Part");
+        assert!(second.properties.is_empty());
+
+        let attachment = dom.get_by_ref(second.children()[0]).unwrap();
+        assert_eq!(attachment.class, "Attachment");
+
+        let last = dom.get_by_ref(root.children()[2]).unwrap();
+        assert_eq!(last.properties["Value"], Variant::from("hello"));
+    }
+}
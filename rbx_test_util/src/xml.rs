@@ -0,0 +1,87 @@
+use xml::reader::{Error as XmlReadError, EventReader, ParserConfig, XmlEvent};
+
+/// Asserts that two XML documents have the same elements, attributes, and
+/// text, ignoring comments and whitespace between elements.
+///
+/// This is useful for checking writers whose exact formatting doesn't matter.
+/// When the documents differ, the panic message shows the first events that
+/// don't match along with both documents.
+pub fn assert_xml_eq(expected: &str, actual: &str) {
+    let mut expected_events = events(expected);
+    let mut actual_events = events(actual);
+
+    let fail = |reason: String| -> ! {
+        panic!(
+            "{}\n\nExpected XML:\n{}\n\nActual XML:\n{}",
+            reason, expected, actual
+        )
+    };
+
+    loop {
+        match (expected_events.next(), actual_events.next()) {
+            (Some(expected), Some(actual)) => {
+                if expected != actual {
+                    fail(format!(
+                        "Expected event: {:#?}\nActual event: {:#?}",
+                        expected, actual
+                    ));
+                }
+            }
+            (None, None) => break,
+            _ => fail("Event streams were different lengths!".to_owned()),
+        }
+    }
+}
+
+/// Reads the events of a document, skipping whitespace. Reading stops after
+/// the end of the document or the first error.
+fn events(source: &str) -> impl Iterator<Item = Result<XmlEvent, XmlReadError>> + '_ {
+    let mut reader: EventReader<&[u8]> = ParserConfig::new()
+        .ignore_comments(true)
+        .create_reader(source.as_bytes());
+    let mut finished = false;
+
+    std::iter::from_fn(move || loop {
+        if finished {
+            return None;
+        }
+
+        match reader.next() {
+            Ok(XmlEvent::Whitespace(_)) => continue,
+            Ok(XmlEvent::EndDocument) => {
+                finished = true;
+                return Some(Ok(XmlEvent::EndDocument));
+            }
+            Ok(event) => return Some(Ok(event)),
+            Err(err) => {
+                finished = true;
+                return Some(Err(err));
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn formatting_is_ignored() {
+        assert_xml_eq(
+            "<roblox version=\"4\"><Item class=\"Folder\"/></roblox>",
+            "<!-- A comment -->\n<roblox version='4'>\n  <Item class=\"Folder\"></Item>\n</roblox>",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected event")]
+    fn attributes_are_compared() {
+        assert_xml_eq("<foo bar=\"1\"/>", "<foo bar=\"2\"/>");
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected event")]
+    fn text_is_compared() {
+        assert_xml_eq("<foo> bar </foo>", "<foo>bar</foo>");
+    }
+}
@@ -0,0 +1,502 @@
+use std::{convert::TryInto, error::Error, fmt, iter::FromIterator};
+
+use crate::{
+    BrickColor, Color3, ColorSequence, ColorSequenceKeypoint, NumberRange, NumberSequence,
+    NumberSequenceKeypoint, Rect, UDim, UDim2, Variant, VariantType, Vector2, Vector3,
+};
+
+// The IDs that identify each attribute's type in the binary format.
+const STRING: u8 = 0x02;
+const BOOL: u8 = 0x03;
+const FLOAT32: u8 = 0x05;
+const FLOAT64: u8 = 0x06;
+const UDIM: u8 = 0x09;
+const UDIM2: u8 = 0x0a;
+const BRICK_COLOR: u8 = 0x0e;
+const COLOR3: u8 = 0x0f;
+const VECTOR2: u8 = 0x10;
+const VECTOR3: u8 = 0x11;
+const NUMBER_SEQUENCE: u8 = 0x17;
+const COLOR_SEQUENCE: u8 = 0x19;
+const NUMBER_RANGE: u8 = 0x1b;
+const RECT: u8 = 0x1c;
+
+/// A set of attributes on an instance, like the ones set by
+/// `Instance:SetAttribute` in Roblox. Each attribute has a name and a value.
+///
+/// Attributes are kept in the order they were added, which is also the order
+/// they're written in, so that files round-trip without changing.
+///
+/// ## See Also
+/// * [Attributes on Roblox Developer Hub](https://developer.roblox.com/en-us/articles/instance-attributes)
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Attributes {
+    data: Vec<(String, Variant)>,
+}
+
+impl Attributes {
+    /// Creates an empty set of attributes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value of the attribute with the given name, if there is one.
+    pub fn get(&self, name: &str) -> Option<&Variant> {
+        self.data
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the value of an attribute, returning its previous value if it had
+    /// one. Attributes that already exist keep their position.
+    pub fn insert<N: Into<String>, V: Into<Variant>>(
+        &mut self,
+        name: N,
+        value: V,
+    ) -> Option<Variant> {
+        let name = name.into();
+        let value = value.into();
+
+        match self.data.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, existing)) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.data.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Removes an attribute, returning its value if it had one.
+    pub fn remove(&mut self, name: &str) -> Option<Variant> {
+        let index = self.data.iter().position(|(key, _)| key == name)?;
+        Some(self.data.remove(index).1)
+    }
+
+    /// The attributes and their values, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Variant)> {
+        self.data.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// The number of attributes.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Tells whether there are no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Decodes attributes from the binary format that Roblox stores in the
+    /// `AttributesSerialize` property: a count, followed by each attribute's
+    /// name, a byte that identifies its type, and its value, with every
+    /// number little-endian.
+    ///
+    /// Returns `None` if the data is malformed or holds a type that isn't
+    /// supported yet. Empty data has no attributes.
+    pub fn from_binary(data: &[u8]) -> Option<Self> {
+        let mut attributes = Attributes::new();
+
+        if data.is_empty() {
+            return Some(attributes);
+        }
+
+        let mut input = Input { data };
+        let count = input.u32()?;
+
+        for _ in 0..count {
+            let name = input.string()?;
+            let value = match input.u8()? {
+                STRING => Variant::String(input.string()?.into()),
+                BOOL => Variant::Bool(input.u8()? != 0),
+                FLOAT32 => Variant::Float32(input.f32()?),
+                FLOAT64 => Variant::Float64(input.f64()?),
+                UDIM => Variant::UDim(input.udim()?),
+                UDIM2 => Variant::UDim2(UDim2::new(input.udim()?, input.udim()?)),
+                BRICK_COLOR => {
+                    let number = input.u32()?.try_into().ok()?;
+                    Variant::BrickColor(BrickColor::from_number(number)?)
+                }
+                COLOR3 => Variant::Color3(input.color3()?),
+                VECTOR2 => Variant::Vector2(input.vector2()?),
+                VECTOR3 => Variant::Vector3(Vector3::new(input.f32()?, input.f32()?, input.f32()?)),
+                NUMBER_SEQUENCE => {
+                    let mut keypoints = Vec::new();
+                    for _ in 0..input.u32()? {
+                        let envelope = input.f32()?;
+                        let time = input.f32()?;
+                        let value = input.f32()?;
+                        keypoints.push(NumberSequenceKeypoint::new(time, value, envelope));
+                    }
+                    Variant::NumberSequence(NumberSequence { keypoints })
+                }
+                COLOR_SEQUENCE => {
+                    let mut keypoints = Vec::new();
+                    for _ in 0..input.u32()? {
+                        // ColorSequence keypoints have an envelope in this
+                        // format, but it's always zero and has nowhere to go.
+                        // Any other value is caught when callers compare the
+                        // attributes written back with the original data.
+                        let _envelope = input.f32()?;
+                        let time = input.f32()?;
+                        let color = input.color3()?;
+                        keypoints.push(ColorSequenceKeypoint::new(time, color));
+                    }
+                    Variant::ColorSequence(ColorSequence { keypoints })
+                }
+                NUMBER_RANGE => Variant::NumberRange(NumberRange::new(input.f32()?, input.f32()?)),
+                RECT => Variant::Rect(Rect::new(input.vector2()?, input.vector2()?)),
+                _ => return None,
+            };
+
+            attributes.insert(name, value);
+        }
+
+        if input.data.is_empty() {
+            Some(attributes)
+        } else {
+            None
+        }
+    }
+
+    /// Encodes attributes into the binary format read by
+    /// [`Attributes::from_binary`]. No attributes encode to no data.
+    pub fn to_binary(&self) -> Result<Vec<u8>, AttributeTypeError> {
+        let mut output = Vec::new();
+
+        if self.is_empty() {
+            return Ok(output);
+        }
+
+        output.extend_from_slice(&(self.len() as u32).to_le_bytes());
+
+        for (name, value) in self.iter() {
+            write_string(&mut output, name.as_bytes());
+
+            match value {
+                Variant::String(value) => {
+                    output.push(STRING);
+                    write_string(&mut output, value.as_bytes());
+                }
+                Variant::Bool(value) => output.extend_from_slice(&[BOOL, *value as u8]),
+                Variant::Float32(value) => {
+                    output.push(FLOAT32);
+                    write_f32s(&mut output, &[*value]);
+                }
+                Variant::Float64(value) => {
+                    output.push(FLOAT64);
+                    output.extend_from_slice(&value.to_le_bytes());
+                }
+                Variant::UDim(value) => {
+                    output.push(UDIM);
+                    write_udim(&mut output, value);
+                }
+                Variant::UDim2(value) => {
+                    output.push(UDIM2);
+                    write_udim(&mut output, &value.x);
+                    write_udim(&mut output, &value.y);
+                }
+                Variant::BrickColor(value) => {
+                    output.push(BRICK_COLOR);
+                    output.extend_from_slice(&(*value as u32).to_le_bytes());
+                }
+                Variant::Color3(value) => {
+                    output.push(COLOR3);
+                    write_f32s(&mut output, &[value.r, value.g, value.b]);
+                }
+                Variant::Vector2(value) => {
+                    output.push(VECTOR2);
+                    write_f32s(&mut output, &[value.x, value.y]);
+                }
+                Variant::Vector3(value) => {
+                    output.push(VECTOR3);
+                    write_f32s(&mut output, &[value.x, value.y, value.z]);
+                }
+                Variant::NumberSequence(value) => {
+                    output.push(NUMBER_SEQUENCE);
+                    output.extend_from_slice(&(value.keypoints.len() as u32).to_le_bytes());
+
+                    for keypoint in &value.keypoints {
+                        write_f32s(
+                            &mut output,
+                            &[keypoint.envelope, keypoint.time, keypoint.value],
+                        );
+                    }
+                }
+                Variant::ColorSequence(value) => {
+                    output.push(COLOR_SEQUENCE);
+                    output.extend_from_slice(&(value.keypoints.len() as u32).to_le_bytes());
+
+                    for keypoint in &value.keypoints {
+                        let color = keypoint.color;
+                        write_f32s(
+                            &mut output,
+                            &[0.0, keypoint.time, color.r, color.g, color.b],
+                        );
+                    }
+                }
+                Variant::NumberRange(value) => {
+                    output.push(NUMBER_RANGE);
+                    write_f32s(&mut output, &[value.min, value.max]);
+                }
+                Variant::Rect(value) => {
+                    output.push(RECT);
+                    write_f32s(
+                        &mut output,
+                        &[value.min.x, value.min.y, value.max.x, value.max.y],
+                    );
+                }
+                other => {
+                    return Err(AttributeTypeError {
+                        name: name.to_owned(),
+                        ty: other.ty(),
+                    })
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+impl<N: Into<String>, V: Into<Variant>> FromIterator<(N, V)> for Attributes {
+    fn from_iter<I: IntoIterator<Item = (N, V)>>(iter: I) -> Self {
+        let mut attributes = Attributes::new();
+
+        for (name, value) in iter {
+            attributes.insert(name, value);
+        }
+
+        attributes
+    }
+}
+
+impl IntoIterator for Attributes {
+    type Item = (String, Variant);
+    type IntoIter = std::vec::IntoIter<(String, Variant)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+/// The error returned when encoding an attribute whose type can't be stored in
+/// an attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeTypeError {
+    name: String,
+    ty: VariantType,
+}
+
+impl AttributeTypeError {
+    /// The name of the attribute.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type of the attribute's value.
+    #[inline]
+    pub fn ty(&self) -> VariantType {
+        self.ty
+    }
+}
+
+impl fmt::Display for AttributeTypeError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            output,
+            "attribute {:?} has a value of type {:?}, which can't be stored in an attribute",
+            self.name, self.ty
+        )
+    }
+}
+
+impl Error for AttributeTypeError {}
+
+fn write_string(output: &mut Vec<u8>, value: &[u8]) {
+    output.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    output.extend_from_slice(value);
+}
+
+fn write_f32s(output: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        output.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn write_udim(output: &mut Vec<u8>, value: &UDim) {
+    output.extend_from_slice(&value.scale.to_le_bytes());
+    output.extend_from_slice(&value.offset.to_le_bytes());
+}
+
+/// The part of an attribute blob that hasn't been read yet. Every method
+/// returns `None` if there isn't enough data left.
+struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl Input<'_> {
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.data.len() < N {
+            return None;
+        }
+
+        let (bytes, rest) = self.data.split_at(N);
+        self.data = rest;
+        bytes.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes::<1>().map(|[byte]| byte)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.bytes().map(f32::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.bytes().map(f64::from_le_bytes)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = self.u32()? as usize;
+        if self.data.len() < len {
+            return None;
+        }
+
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+
+    fn udim(&mut self) -> Option<UDim> {
+        Some(UDim::new(
+            self.f32()?,
+            self.bytes().map(i32::from_le_bytes)?,
+        ))
+    }
+
+    fn color3(&mut self) -> Option<Color3> {
+        Some(Color3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    fn vector2(&mut self) -> Option<Vector2> {
+        Some(Vector2::new(self.f32()?, self.f32()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_keeps_order() {
+        let mut attributes = Attributes::new();
+        attributes.insert("Speed", 16.0f64);
+        attributes.insert("Team", "Red");
+        assert_eq!(
+            attributes.insert("Speed", 20.0f64),
+            Some(Variant::Float64(16.0))
+        );
+
+        let names: Vec<_> = attributes.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["Speed", "Team"]);
+        assert_eq!(attributes.get("Speed"), Some(&Variant::Float64(20.0)));
+
+        assert_eq!(attributes.remove("Speed"), Some(Variant::Float64(20.0)));
+        assert_eq!(attributes.remove("Speed"), None);
+        assert_eq!(attributes.len(), 1);
+    }
+
+    fn sample() -> Attributes {
+        vec![
+            ("Team", Variant::from("Red")),
+            ("Alive", Variant::Bool(true)),
+            ("Speed", Variant::Float64(16.5)),
+            ("Scale", Variant::Float32(0.25)),
+            (
+                "Offset",
+                Variant::UDim2(UDim2::new(UDim::new(0.5, -10), UDim::new(1.0, 20))),
+            ),
+            ("Tint", Variant::Color3(Color3::new(1.0, 0.5, 0.0))),
+            ("Color", Variant::BrickColor(BrickColor::BrightRed)),
+            ("Spawn", Variant::Vector3(Vector3::new(1.0, 2.0, 3.0))),
+            ("Range", Variant::NumberRange(NumberRange::new(1.0, 5.0))),
+            (
+                "Fade",
+                Variant::NumberSequence(NumberSequence {
+                    keypoints: vec![
+                        NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+                        NumberSequenceKeypoint::new(1.0, 0.0, 0.5),
+                    ],
+                }),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let attributes = sample();
+        let data = attributes.to_binary().unwrap();
+
+        assert_eq!(Attributes::from_binary(&data), Some(attributes));
+    }
+
+    #[test]
+    fn binary_layout() {
+        let attributes: Attributes = vec![("A", Variant::Bool(true)), ("Name", "Hi".into())]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            attributes.to_binary().unwrap(),
+            b"\x02\x00\x00\x00\
+              \x01\x00\x00\x00A\x03\x01\
+              \x04\x00\x00\x00Name\x02\x02\x00\x00\x00Hi"
+        );
+
+        assert_eq!(Attributes::new().to_binary().unwrap(), b"");
+        assert_eq!(Attributes::from_binary(b""), Some(Attributes::new()));
+    }
+
+    #[test]
+    fn unreadable_binary() {
+        let data = sample().to_binary().unwrap();
+
+        for len in [1, 4, 10, data.len() - 1] {
+            assert_eq!(Attributes::from_binary(&data[..len]), None);
+        }
+
+        let mut extra = data.clone();
+        extra.push(0);
+        assert_eq!(Attributes::from_binary(&extra), None);
+
+        // Roblox stores CFrames as type 0x14, which isn't supported yet.
+        let mut unknown = data;
+        unknown.extend_from_slice(b"\x01\x00\x00\x00C\x14\x00\x00\x00\x00");
+        unknown[0] += 1;
+        assert_eq!(Attributes::from_binary(&unknown), None);
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let attributes: Attributes = vec![("Count", Variant::Int32(5))].into_iter().collect();
+        let error = attributes.to_binary().unwrap_err();
+
+        assert_eq!(error.name(), "Count");
+        assert_eq!(error.ty(), VariantType::Int32);
+    }
+}
@@ -1,3 +1,5 @@
+use std::{error::Error, fmt, ops::Mul};
+
 /// Represents any Roblox enum value.
 ///
 /// Roblox enums are not strongly typed, so the meaning of a value depends on
@@ -31,6 +33,7 @@ impl Enum {
 /// * [`Vector2int16`][struct.Vector2int16.html]
 /// * [Vector2 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Vector2)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct Vector2 {
     pub x: f32,
     pub y: f32,
@@ -51,6 +54,7 @@ impl Vector2 {
 ///
 /// [Vector2]: struct.Vector2.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
 pub struct Vector2int16 {
     pub x: i16,
     pub y: i16,
@@ -68,6 +72,7 @@ impl Vector2int16 {
 /// * [`Vector3int16`][struct.Vector3int16.html]
 /// * [Vector3 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Vector3)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct Vector3 {
     pub x: f32,
     pub y: f32,
@@ -89,6 +94,7 @@ impl Vector3 {
 ///
 /// [Vector3]: struct.Vector3.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
 pub struct Vector3int16 {
     pub x: i16,
     pub y: i16,
@@ -111,6 +117,7 @@ impl Vector3int16 {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "PascalCase")
 )]
+#[repr(C)]
 pub struct CFrame {
     pub position: Vector3,
     pub orientation: Matrix3,
@@ -123,11 +130,142 @@ impl CFrame {
             orientation,
         }
     }
+
+    /// Creates a CFrame from its position followed by its rotation matrix in
+    /// row-major order, the same order that `CFrame:GetComponents` returns
+    /// them in Roblox:
+    ///
+    /// `X, Y, Z, R00, R01, R02, R10, R11, R12, R20, R21, R22`
+    ///
+    /// The values aren't checked. Use
+    /// [`try_from_components`](#method.try_from_components) for values from
+    /// somewhere that can't be trusted.
+    pub fn from_components(components: &[f32; 12]) -> Self {
+        let [x, y, z, rotation @ ..] = *components;
+
+        Self {
+            position: Vector3::new(x, y, z),
+            orientation: Matrix3::from_components(&rotation),
+        }
+    }
+
+    /// Like [`from_components`](#method.from_components), but returns an
+    /// error if any of the values are NaN or infinite.
+    ///
+    /// If `require_orthonormal` is set, this also returns an error unless the
+    /// rows of the rotation matrix are unit length and perpendicular to each
+    /// other, give or take rounding error.
+    pub fn try_from_components(
+        components: &[f32; 12],
+        require_orthonormal: bool,
+    ) -> Result<Self, CFrameComponentsError> {
+        if let Some(index) = components.iter().position(|value| !value.is_finite()) {
+            return Err(CFrameComponentsError::NotFinite { index });
+        }
+
+        let cframe = Self::from_components(components);
+
+        if require_orthonormal && !cframe.orientation.is_orthonormal() {
+            return Err(CFrameComponentsError::NotOrthonormal);
+        }
+
+        Ok(cframe)
+    }
+
+    /// Returns the components of this CFrame in the same order as
+    /// [`from_components`](#method.from_components) takes them.
+    pub fn to_components(&self) -> [f32; 12] {
+        let [r00, r01, r02, r10, r11, r12, r20, r21, r22] = self.orientation.to_components();
+
+        [
+            self.position.x,
+            self.position.y,
+            self.position.z,
+            r00,
+            r01,
+            r02,
+            r10,
+            r11,
+            r12,
+            r20,
+            r21,
+            r22,
+        ]
+    }
+
+    /// Returns the CFrame that undoes this one, so that `cframe *
+    /// cframe.inverse()` has no translation or rotation.
+    ///
+    /// Like Roblox, this assumes that the orientation is a rotation, which
+    /// makes its inverse the same as its transpose.
+    pub fn inverse(&self) -> Self {
+        let orientation = self.orientation.transpose();
+        let position = orientation * self.position;
+
+        Self {
+            position: Vector3::new(-position.x, -position.y, -position.z),
+            orientation,
+        }
+    }
+}
+
+/// The error returned by
+/// [`CFrame::try_from_components`](struct.CFrame.html#method.try_from_components).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CFrameComponentsError {
+    /// The component at the given index was NaN or infinite.
+    NotFinite { index: usize },
+
+    /// The rotation matrix wasn't orthonormal.
+    NotOrthonormal,
+}
+
+impl fmt::Display for CFrameComponentsError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFinite { index } => {
+                write!(output, "CFrame component {} is not a finite number", index)
+            }
+            Self::NotOrthonormal => write!(output, "CFrame rotation matrix is not orthonormal"),
+        }
+    }
+}
+
+impl Error for CFrameComponentsError {}
+
+/// Composes two CFrames, like `a * b` in Roblox. The result applies `rhs`
+/// first, then `self`.
+impl Mul for CFrame {
+    type Output = CFrame;
+
+    fn mul(self, rhs: CFrame) -> CFrame {
+        CFrame {
+            position: self * rhs.position,
+            orientation: self.orientation * rhs.orientation,
+        }
+    }
+}
+
+/// Transforms a point from this CFrame's object space into world space.
+impl Mul<Vector3> for CFrame {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Vector3 {
+        let rotated = self.orientation * rhs;
+
+        Vector3::new(
+            rotated.x + self.position.x,
+            rotated.y + self.position.y,
+            rotated.z + self.position.z,
+        )
+    }
 }
 
 /// Used to represent the `orientation` field of `CFrame` and not a standalone
 /// type in Roblox.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct Matrix3 {
     pub x: Vector3,
     pub y: Vector3,
@@ -146,6 +284,84 @@ impl Matrix3 {
             z: Vector3::new(0.0, 0.0, 1.0),
         }
     }
+
+    /// Creates a matrix from its values in row-major order, like the rotation
+    /// components of `CFrame:GetComponents` in Roblox.
+    pub fn from_components(components: &[f32; 9]) -> Self {
+        let [r00, r01, r02, r10, r11, r12, r20, r21, r22] = *components;
+
+        Self {
+            x: Vector3::new(r00, r01, r02),
+            y: Vector3::new(r10, r11, r12),
+            z: Vector3::new(r20, r21, r22),
+        }
+    }
+
+    /// Returns the values of this matrix in row-major order.
+    pub fn to_components(&self) -> [f32; 9] {
+        [
+            self.x.x, self.x.y, self.x.z, self.y.x, self.y.y, self.y.z, self.z.x, self.z.y,
+            self.z.z,
+        ]
+    }
+
+    /// Tells whether the rows of this matrix are unit length and perpendicular
+    /// to each other, within a tolerance that allows for the rounding error
+    /// that CFrames saved by Roblox usually have.
+    fn is_orthonormal(&self) -> bool {
+        const TOLERANCE: f32 = 1e-3;
+
+        let close = |value: f32, expected: f32| (value - expected).abs() <= TOLERANCE;
+        let rows = [self.x, self.y, self.z];
+
+        rows.iter().all(|row| close(dot(*row, *row), 1.0))
+            && close(dot(self.x, self.y), 0.0)
+            && close(dot(self.x, self.z), 0.0)
+            && close(dot(self.y, self.z), 0.0)
+    }
+
+    /// Swaps the rows and columns of this matrix.
+    pub fn transpose(&self) -> Self {
+        Self {
+            x: Vector3::new(self.x.x, self.y.x, self.z.x),
+            y: Vector3::new(self.x.y, self.y.y, self.z.y),
+            z: Vector3::new(self.x.z, self.y.z, self.z.z),
+        }
+    }
+}
+
+/// Multiplies two matrices, treating `x`, `y`, and `z` as rows.
+impl Mul for Matrix3 {
+    type Output = Matrix3;
+
+    fn mul(self, rhs: Matrix3) -> Matrix3 {
+        let columns = rhs.transpose();
+        let row = |row: Vector3| {
+            Vector3::new(
+                dot(row, columns.x),
+                dot(row, columns.y),
+                dot(row, columns.z),
+            )
+        };
+
+        Matrix3 {
+            x: row(self.x),
+            y: row(self.y),
+            z: row(self.z),
+        }
+    }
+}
+
+impl Mul<Vector3> for Matrix3 {
+    type Output = Vector3;
+
+    fn mul(self, rhs: Vector3) -> Vector3 {
+        Vector3::new(dot(self.x, rhs), dot(self.y, rhs), dot(self.z, rhs))
+    }
+}
+
+fn dot(a: Vector3, b: Vector3) -> f32 {
+    a.x * b.x + a.y * b.y + a.z * b.z
 }
 
 /// Represents any color, including HDR colors.
@@ -155,6 +371,7 @@ impl Matrix3 {
 ///   `Color3` on some types and does not represent HDR colors.
 /// * [Color3 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Color3)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct Color3 {
     pub r: f32,
     pub g: f32,
@@ -178,6 +395,7 @@ impl Color3 {
 ///
 /// [BasePart.Color]: https://developer.roblox.com/en-us/api-reference/property/BasePart/Color
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
 pub struct Color3uint8 {
     pub r: u8,
     pub g: u8,
@@ -190,6 +408,30 @@ impl Color3uint8 {
     }
 }
 
+impl From<Color3uint8> for Color3 {
+    fn from(value: Color3uint8) -> Self {
+        Self {
+            r: f32::from(value.r) / 255.0,
+            g: f32::from(value.g) / 255.0,
+            b: f32::from(value.b) / 255.0,
+        }
+    }
+}
+
+impl From<Color3> for Color3uint8 {
+    /// Converts a `Color3` to a `Color3uint8`, clamping each component to the
+    /// range 0 to 1 first.
+    fn from(value: Color3) -> Self {
+        let convert = |component: f32| (component.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        Self {
+            r: convert(value.r),
+            g: convert(value.g),
+            b: convert(value.b),
+        }
+    }
+}
+
 /// Represents a ray in 3D space. Direction does not have to be a unit vector,
 /// and is used by APIs like [`Workspace:FindPartOnRay`][FindPartOnRay] to set a
 /// max distance.
@@ -204,6 +446,7 @@ impl Color3uint8 {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "PascalCase")
 )]
+#[repr(C)]
 pub struct Ray {
     pub origin: Vector3,
     pub direction: Vector3,
@@ -221,6 +464,7 @@ impl Ray {
 /// * [`Region3int16`](struct.Region3int16.html)
 /// * [Region3 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Region3)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct Region3 {
     pub min: Vector3,
     pub max: Vector3,
@@ -241,6 +485,7 @@ impl Region3 {
 ///
 /// [Region3]: struct.Region3.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
 pub struct Region3int16 {
     pub min: Vector3int16,
     pub max: Vector3int16,
@@ -257,6 +502,7 @@ impl Region3int16 {
 /// ## See Also
 /// * [Rect on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Rect)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct Rect {
     pub min: Vector2,
     pub max: Vector2,
@@ -274,6 +520,7 @@ impl Rect {
 /// ## See Also
 /// * [UDim on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/UDim)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct UDim {
     pub scale: f32,
     pub offset: i32,
@@ -291,6 +538,7 @@ impl UDim {
 /// ## See Also
 /// * [UDim2 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/UDim2)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct UDim2 {
     pub x: UDim,
     pub y: UDim,
@@ -307,6 +555,7 @@ impl UDim2 {
 /// ## See Also
 /// * [NumberRange on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/NumberRange)
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct NumberRange {
     pub min: f32,
     pub max: f32,
@@ -344,6 +593,7 @@ pub struct ColorSequence {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "PascalCase")
 )]
+#[repr(C)]
 pub struct ColorSequenceKeypoint {
     pub time: f32,
     pub color: Color3,
@@ -383,6 +633,7 @@ pub struct NumberSequence {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "PascalCase")
 )]
+#[repr(C)]
 pub struct NumberSequenceKeypoint {
     pub time: f32,
     pub value: f32,
@@ -421,6 +672,103 @@ serde_tuple! {
     Matrix3(x: Vector3, y: Vector3, z: Vector3),
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A rotation of 90 degrees around the Y axis.
+    fn quarter_turn() -> Matrix3 {
+        Matrix3::new(
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+        )
+    }
+
+    fn no_rotation() -> Matrix3 {
+        Matrix3::new(
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn cframe_components_order() {
+        // CFrame.new(1, 2, 3) * CFrame.Angles(0, math.pi / 2, 0) in Roblox,
+        // rounded. GetComponents returns the rows of the rotation matrix.
+        let components = [1.0, 2.0, 3.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0];
+
+        let cframe = CFrame::from_components(&components);
+        assert_eq!(cframe.position, Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(cframe.orientation, quarter_turn());
+        assert_eq!(cframe.to_components(), components);
+    }
+
+    #[test]
+    fn cframe_components_validation() {
+        let mut components =
+            CFrame::new(Vector3::new(1.0, 2.0, 3.0), quarter_turn()).to_components();
+        assert!(CFrame::try_from_components(&components, true).is_ok());
+
+        // Values saved by Roblox are rarely exact.
+        components[3] = 1e-5;
+        assert!(CFrame::try_from_components(&components, true).is_ok());
+
+        components[7] = 2.0;
+        assert_eq!(
+            CFrame::try_from_components(&components, true),
+            Err(CFrameComponentsError::NotOrthonormal)
+        );
+        assert!(CFrame::try_from_components(&components, false).is_ok());
+
+        components[4] = f32::NAN;
+        components[10] = f32::INFINITY;
+        assert_eq!(
+            CFrame::try_from_components(&components, false),
+            Err(CFrameComponentsError::NotFinite { index: 4 })
+        );
+    }
+
+    #[test]
+    fn cframe_transforms_points() {
+        let cframe = CFrame::new(Vector3::new(1.0, 2.0, 3.0), quarter_turn());
+
+        // The object space X axis points along world -Z after the turn.
+        assert_eq!(
+            cframe * Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn cframe_composition() {
+        let a = CFrame::new(Vector3::new(0.0, 5.0, 0.0), quarter_turn());
+        let b = CFrame::new(Vector3::new(2.0, 0.0, 0.0), quarter_turn());
+
+        let composed = a * b;
+        assert_eq!(composed.position, Vector3::new(0.0, 5.0, -2.0));
+        assert_eq!(composed.orientation, quarter_turn() * quarter_turn());
+        assert_eq!(
+            composed * Vector3::new(1.0, 1.0, 1.0),
+            a * (b * Vector3::new(1.0, 1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn cframe_inverse() {
+        let cframe = CFrame::new(Vector3::new(1.0, 2.0, 3.0), quarter_turn());
+        let round_trip = cframe * cframe.inverse();
+
+        assert_eq!(round_trip.position, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(round_trip.orientation, no_rotation());
+        assert_eq!(
+            cframe.inverse() * (cframe * Vector3::new(4.0, 5.0, 6.0)),
+            Vector3::new(4.0, 5.0, 6.0)
+        );
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod serde_test {
     use super::*;
@@ -1,66 +1,198 @@
+use std::{
+    hash::{Hash, Hasher},
+    io,
+};
+
+use crate::blob::{Blob, LazyBlob};
+
 /// Container for untyped binary data.
 ///
 /// `BinaryString` is used in cases where the type of the underlying data is
 /// unknown or unimplemented. Where possible, stronger types that interpret the
 /// underlying bytes should be preferred.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+///
+/// A `BinaryString` can also be backed by a [`Blob`], in which case its
+/// contents are read from the blob's store the first time they're needed.
+#[derive(Debug, Clone)]
 pub struct BinaryString {
-    buffer: Vec<u8>,
+    repr: Repr,
+}
+
+#[derive(Debug, Clone)]
+enum Repr {
+    Buffer(Vec<u8>),
+    Stored(LazyBlob),
 }
 
 impl BinaryString {
     #[inline]
     pub fn new() -> Self {
-        Self { buffer: Vec::new() }
+        Self {
+            repr: Repr::Buffer(Vec::new()),
+        }
+    }
+
+    /// Creates a `BinaryString` whose contents are kept in the given blob.
+    ///
+    /// Any method that gives access to the contents reads them into memory
+    /// first. [`try_data`][BinaryString::try_data] and
+    /// [`try_into_vec`][BinaryString::try_into_vec] return an error if the
+    /// blob's store can't give them back, while `AsRef`, `AsMut`, and
+    /// [`into_vec`][BinaryString::into_vec] panic. Comparing and hashing
+    /// values never reads from the store. Encoders can avoid reading the
+    /// contents by checking [`blob`][BinaryString::blob] first.
+    #[inline]
+    pub fn from_blob(blob: Blob) -> Self {
+        Self {
+            repr: Repr::Stored(LazyBlob::new(blob)),
+        }
+    }
+
+    /// The blob that this value's contents are kept in, if any.
+    #[inline]
+    pub fn blob(&self) -> Option<&Blob> {
+        match &self.repr {
+            Repr::Buffer(_) => None,
+            Repr::Stored(stored) => Some(stored.blob()),
+        }
+    }
+
+    /// The contents of this value, or the error from reading them back from
+    /// the blob they're kept in.
+    pub fn try_data(&self) -> io::Result<&[u8]> {
+        match &self.repr {
+            Repr::Buffer(buffer) => Ok(buffer),
+            Repr::Stored(stored) => stored.try_data().map(Vec::as_slice),
+        }
     }
 
+    /// ## Panics
+    /// Panics if this value is kept in a blob whose store can't give its
+    /// contents back. Use [`try_into_vec`][BinaryString::try_into_vec] to
+    /// handle that instead.
     #[inline]
     pub fn into_vec(self) -> Vec<u8> {
-        self.buffer
+        match self.repr {
+            Repr::Buffer(buffer) => buffer,
+            Repr::Stored(stored) => stored.into_vec(),
+        }
+    }
+
+    pub fn try_into_vec(self) -> io::Result<Vec<u8>> {
+        match self.repr {
+            Repr::Buffer(buffer) => Ok(buffer),
+            Repr::Stored(stored) => stored.try_into_vec(),
+        }
+    }
+
+    /// The length of the contents in bytes, which doesn't need to read
+    /// values kept in a blob.
+    fn len(&self) -> u64 {
+        match &self.repr {
+            Repr::Buffer(buffer) => buffer.len() as u64,
+            Repr::Stored(stored) => stored.blob().len(),
+        }
+    }
+
+    /// The BLAKE3 hash of the contents. Values kept in a blob use the hash
+    /// computed when the blob was stored, so this never reads from the store.
+    pub fn content_hash(&self) -> [u8; 32] {
+        match &self.repr {
+            Repr::Buffer(buffer) => *blake3::hash(buffer).as_bytes(),
+            Repr::Stored(stored) => stored.blob().content_hash(),
+        }
+    }
+
+    /// Gives mutable access to the contents, reading them from the blob
+    /// they're kept in first if there is one.
+    fn buffer_mut(&mut self) -> &mut Vec<u8> {
+        if let Repr::Stored(stored) = &self.repr {
+            let buffer = stored.clone().into_vec();
+            self.repr = Repr::Buffer(buffer);
+        }
+
+        match &mut self.repr {
+            Repr::Buffer(buffer) => buffer,
+            Repr::Stored(_) => unreachable!(),
+        }
+    }
+}
+
+impl Default for BinaryString {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for BinaryString {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::Buffer(buffer), Repr::Buffer(other_buffer)) => buffer == other_buffer,
+
+            // Values kept in a blob are compared by their hashes so that
+            // comparing them can't fail.
+            _ => self.len() == other.len() && self.content_hash() == other.content_hash(),
+        }
+    }
+}
+
+impl Eq for BinaryString {}
+
+impl Hash for BinaryString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal values can be kept in memory or in a blob, so both are
+        // hashed by their content hash.
+        state.write(&self.content_hash());
     }
 }
 
 impl From<Vec<u8>> for BinaryString {
     fn from(buffer: Vec<u8>) -> Self {
-        Self { buffer }
+        Self {
+            repr: Repr::Buffer(buffer),
+        }
     }
 }
 
 impl From<&'_ [u8]> for BinaryString {
     fn from(buffer: &[u8]) -> Self {
-        Self {
-            buffer: buffer.to_vec(),
-        }
+        Self::from(buffer.to_vec())
     }
 }
 
 impl From<BinaryString> for Vec<u8> {
     fn from(value: BinaryString) -> Self {
-        value.buffer
+        value.into_vec()
     }
 }
 
 impl AsRef<[u8]> for BinaryString {
     fn as_ref(&self) -> &[u8] {
-        &self.buffer
+        match &self.repr {
+            Repr::Buffer(buffer) => buffer,
+            Repr::Stored(stored) => stored.data(),
+        }
     }
 }
 
 impl AsRef<Vec<u8>> for BinaryString {
     fn as_ref(&self) -> &Vec<u8> {
-        &self.buffer
+        match &self.repr {
+            Repr::Buffer(buffer) => buffer,
+            Repr::Stored(stored) => stored.data(),
+        }
     }
 }
 
 impl AsMut<[u8]> for BinaryString {
     fn as_mut(&mut self) -> &mut [u8] {
-        &mut self.buffer
+        self.buffer_mut()
     }
 }
 
 impl AsMut<Vec<u8>> for BinaryString {
     fn as_mut(&mut self) -> &mut Vec<u8> {
-        &mut self.buffer
+        self.buffer_mut()
     }
 }
 
@@ -68,16 +200,20 @@ impl AsMut<Vec<u8>> for BinaryString {
 mod serde_impl {
     use super::*;
 
-    use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+    use serde::{
+        de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer,
+    };
 
     impl Serialize for BinaryString {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let data = self.try_data().map_err(S::Error::custom)?;
+
             if serializer.is_human_readable() {
-                let encoded = base64::encode(&self.buffer);
+                let encoded = base64::encode(data);
 
                 serializer.serialize_str(&encoded)
             } else {
-                serializer.serialize_bytes(&self.buffer)
+                serializer.serialize_bytes(data)
             }
         }
     }
@@ -88,15 +224,72 @@ mod serde_impl {
                 let encoded = <&str>::deserialize(deserializer)?;
                 let buffer = base64::decode(encoded).map_err(D::Error::custom)?;
 
-                Ok(BinaryString { buffer })
+                Ok(BinaryString::from(buffer))
             } else {
                 let buffer = <Vec<u8>>::deserialize(deserializer)?;
-                Ok(BinaryString { buffer })
+                Ok(BinaryString::from(buffer))
             }
         }
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::{Blob, BlobStore, TempFileStore};
+
+    fn stored(data: &[u8]) -> BinaryString {
+        let store: Arc<dyn BlobStore> = Arc::new(TempFileStore::new().unwrap());
+        BinaryString::from_blob(Blob::store(&store, data).unwrap())
+    }
+
+    #[test]
+    fn stored_contents() {
+        let value = stored(b"kept elsewhere");
+
+        assert_eq!(value.blob().unwrap().len(), 14);
+        assert_eq!(AsRef::<[u8]>::as_ref(&value), b"kept elsewhere");
+        assert_eq!(value, BinaryString::from(b"kept elsewhere".to_vec()));
+        assert_eq!(value.clone().into_vec(), b"kept elsewhere");
+    }
+
+    #[test]
+    fn unreadable_contents() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let value = BinaryString::from_blob(crate::blob::unreadable_blob(b"gone"));
+        let in_memory = BinaryString::from(b"gone".to_vec());
+
+        assert!(value.try_data().is_err());
+
+        // Comparing and hashing use the hash the blob was stored with.
+        assert_eq!(value, in_memory);
+        assert_ne!(value, BinaryString::from(b"here".to_vec()));
+        assert_eq!(value.content_hash(), in_memory.content_hash());
+
+        let hash = |value: &BinaryString| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&value), hash(&in_memory));
+
+        assert!(value.try_into_vec().is_err());
+    }
+
+    #[test]
+    fn stored_mutation() {
+        let mut value = stored(b"abc");
+        AsMut::<Vec<u8>>::as_mut(&mut value).push(b'd');
+
+        assert!(value.blob().is_none());
+        assert_eq!(value.into_vec(), b"abcd");
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod serde_test {
     use super::*;
@@ -0,0 +1,358 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+};
+
+use crate::CountingReader;
+
+/// Somewhere to keep binary data that's too large to comfortably hold in
+/// memory, like a video file that was accidentally saved into a `StringValue`.
+///
+/// Data in a store is referred to by a [`Blob`]. `BinaryString` and
+/// `SharedString` values can be backed by a blob, in which case their contents
+/// are only read back from the store when something needs them.
+pub trait BlobStore: fmt::Debug + Send + Sync {
+    /// Copies everything from `data` into the store, returning a key that the
+    /// data can be read back with.
+    fn put(&self, data: &mut dyn Read) -> io::Result<u64>;
+
+    /// Opens the data with the given key for reading.
+    fn get(&self, key: u64) -> io::Result<Box<dyn Read + '_>>;
+
+    /// Lets the store throw away the data with the given key, which is no
+    /// longer used by any `Blob`.
+    fn remove(&self, key: u64);
+}
+
+/// A handle to data kept in a [`BlobStore`]. Cloning a `Blob` is cheap, and
+/// its data is removed from the store once every clone has been dropped.
+#[derive(Clone)]
+pub struct Blob {
+    inner: Arc<BlobInner>,
+}
+
+struct BlobInner {
+    store: Arc<dyn BlobStore>,
+    key: u64,
+    len: u64,
+    hash: blake3::Hash,
+}
+
+impl Blob {
+    /// Copies everything from `data` into `store`, returning a handle to it.
+    pub fn store<R: Read>(store: &Arc<dyn BlobStore>, data: R) -> io::Result<Blob> {
+        let mut data = HashingReader {
+            inner: CountingReader::new(data),
+            hasher: blake3::Hasher::new(),
+        };
+        let key = store.put(&mut data)?;
+
+        Ok(Blob {
+            inner: Arc::new(BlobInner {
+                store: Arc::clone(store),
+                key,
+                len: data.inner.bytes_read(),
+                hash: data.hasher.finalize(),
+            }),
+        })
+    }
+
+    /// The length of the blob's data in bytes.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.inner.len
+    }
+
+    /// The BLAKE3 hash of the blob's data, which was computed while it was
+    /// being stored, so this never reads from the store.
+    #[inline]
+    pub fn content_hash(&self) -> [u8; 32] {
+        *self.inner.hash.as_bytes()
+    }
+
+    pub(crate) fn blake3_hash(&self) -> blake3::Hash {
+        self.inner.hash
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.len == 0
+    }
+
+    /// Opens the blob's data for reading.
+    pub fn open(&self) -> io::Result<Box<dyn Read + '_>> {
+        self.inner.store.get(self.inner.key)
+    }
+
+    /// Reads all of the blob's data into memory.
+    pub fn load(&self) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(self.inner.len as usize);
+        self.open()?.read_to_end(&mut data)?;
+
+        if data.len() as u64 != self.inner.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "blob should have had {} bytes, but the store gave back {}",
+                    self.inner.len,
+                    data.len()
+                ),
+            ));
+        }
+
+        Ok(data)
+    }
+}
+
+impl fmt::Debug for Blob {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        output
+            .debug_struct("Blob")
+            .field("key", &self.inner.key)
+            .field("len", &self.inner.len)
+            .finish()
+    }
+}
+
+impl Drop for BlobInner {
+    fn drop(&mut self) {
+        self.store.remove(self.key);
+    }
+}
+
+/// Hashes and counts the data read through it.
+struct HashingReader<R> {
+    inner: CountingReader<R>,
+    hasher: blake3::Hasher,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.hasher.update(&buf[..len]);
+
+        Ok(len)
+    }
+}
+
+/// A blob whose data is read into memory the first time it's needed.
+pub(crate) struct LazyBlob {
+    blob: Blob,
+    data: OnceLock<Vec<u8>>,
+}
+
+impl LazyBlob {
+    pub fn new(blob: Blob) -> Self {
+        LazyBlob {
+            blob,
+            data: OnceLock::new(),
+        }
+    }
+
+    pub fn blob(&self) -> &Blob {
+        &self.blob
+    }
+
+    /// The blob's data, which is read from its store if it hasn't been already.
+    pub fn try_data(&self) -> io::Result<&Vec<u8>> {
+        if let Some(data) = self.data.get() {
+            return Ok(data);
+        }
+
+        let data = self.blob.load()?;
+        Ok(self.data.get_or_init(|| data))
+    }
+
+    /// Like [`try_data`][LazyBlob::try_data], but panics if the store can't
+    /// give the data back. Only APIs that can't report errors, like `AsRef`,
+    /// should use this.
+    pub fn data(&self) -> &Vec<u8> {
+        self.try_data()
+            .unwrap_or_else(|err| unreadable(&self.blob, err))
+    }
+
+    pub fn try_into_vec(self) -> io::Result<Vec<u8>> {
+        match self.data.into_inner() {
+            Some(data) => Ok(data),
+            None => self.blob.load(),
+        }
+    }
+
+    /// Like [`try_into_vec`][LazyBlob::try_into_vec], but panics if the store
+    /// can't give the data back.
+    pub fn into_vec(self) -> Vec<u8> {
+        let blob = self.blob.clone();
+        self.try_into_vec()
+            .unwrap_or_else(|err| unreadable(&blob, err))
+    }
+}
+
+fn unreadable(blob: &Blob, err: io::Error) -> ! {
+    panic!("could not read {:?} back from its store: {}", blob, err)
+}
+
+impl Clone for LazyBlob {
+    fn clone(&self) -> Self {
+        // The clone reads the data again if it needs it, instead of holding a
+        // second copy of it from the start.
+        LazyBlob::new(self.blob.clone())
+    }
+}
+
+impl fmt::Debug for LazyBlob {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        self.blob.fmt(output)
+    }
+}
+
+/// A [`BlobStore`] that keeps each blob in its own file in a temporary
+/// directory. The directory is deleted when the store is dropped, which can't
+/// happen until every `Blob` in it has been dropped.
+#[derive(Debug)]
+pub struct TempFileStore {
+    dir: PathBuf,
+    next_key: AtomicU64,
+}
+
+impl TempFileStore {
+    /// Creates a store in a new directory inside of the system's temporary
+    /// directory.
+    pub fn new() -> io::Result<Self> {
+        Self::new_in(std::env::temp_dir())
+    }
+
+    /// Creates a store in a new directory inside of `parent`.
+    pub fn new_in<P: AsRef<Path>>(parent: P) -> io::Result<Self> {
+        static NEXT_DIR: AtomicU64 = AtomicU64::new(0);
+
+        loop {
+            let name = format!(
+                "rbx-blobs-{}-{}",
+                process::id(),
+                NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+            );
+            let dir = parent.as_ref().join(name);
+
+            match fs::create_dir(&dir) {
+                Ok(()) => {
+                    return Ok(TempFileStore {
+                        dir,
+                        next_key: AtomicU64::new(0),
+                    })
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// The directory that this store keeps its blobs in.
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    fn blob_path(&self, key: u64) -> PathBuf {
+        self.dir.join(key.to_string())
+    }
+}
+
+impl BlobStore for TempFileStore {
+    fn put(&self, data: &mut dyn Read) -> io::Result<u64> {
+        let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+        let path = self.blob_path(key);
+
+        let result = File::create(&path).and_then(|file| {
+            let mut file = BufWriter::new(file);
+            io::copy(data, &mut file)?;
+            file.flush()
+        });
+
+        match result {
+            Ok(()) => Ok(key),
+            Err(err) => {
+                let _ = fs::remove_file(&path);
+                Err(err)
+            }
+        }
+    }
+
+    fn get(&self, key: u64) -> io::Result<Box<dyn Read + '_>> {
+        let file = File::open(self.blob_path(key))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+
+    fn remove(&self, key: u64) {
+        let _ = fs::remove_file(self.blob_path(key));
+    }
+}
+
+impl Drop for TempFileStore {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Stores `data` in a blob, then deletes it from the store behind the blob's
+/// back, so that reading it fails.
+#[cfg(test)]
+pub(crate) fn unreadable_blob(data: &[u8]) -> Blob {
+    let store = TempFileStore::new().unwrap();
+    let dir = store.path().to_owned();
+    let store: Arc<dyn BlobStore> = Arc::new(store);
+    let blob = Blob::store(&store, data).unwrap();
+
+    for entry in fs::read_dir(dir).unwrap() {
+        fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+
+    blob
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_store() -> Arc<dyn BlobStore> {
+        Arc::new(TempFileStore::new().unwrap())
+    }
+
+    #[test]
+    fn store_and_load() {
+        let store = temp_store();
+        let blob = Blob::store(&store, &b"hello, blob"[..]).unwrap();
+
+        assert_eq!(blob.len(), 11);
+        assert_eq!(blob.load().unwrap(), b"hello, blob");
+
+        let mut opened = Vec::new();
+        blob.open().unwrap().read_to_end(&mut opened).unwrap();
+        assert_eq!(opened, b"hello, blob");
+    }
+
+    #[test]
+    fn removed_when_dropped() {
+        let store = TempFileStore::new().unwrap();
+        let dir = store.path().to_owned();
+        let store: Arc<dyn BlobStore> = Arc::new(store);
+
+        let blob = Blob::store(&store, &b"temporary"[..]).unwrap();
+        let clone = blob.clone();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        drop(blob);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        drop(clone);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        drop(store);
+        assert!(!dir.exists());
+    }
+}
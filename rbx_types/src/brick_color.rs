@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::Color3uint8;
+
 macro_rules! make_brick_color {
     ({
         $([
@@ -62,6 +64,37 @@ macro_rules! make_brick_color {
                     _ => None,
                 }
             }
+
+            /// Returns the color that this BrickColor appears as.
+            pub fn to_color3uint8(self) -> Color3uint8 {
+                match self {
+                    $(
+                        BrickColor::$enum => Color3uint8::new($color3_r, $color3_g, $color3_b),
+                    )+
+                }
+            }
+
+            /// Finds the BrickColor whose color is closest to the given color.
+            ///
+            /// This is roughly equivalent to `BrickColor.new(color3)` from
+            /// within Roblox.
+            pub fn from_color3uint8(color: Color3uint8) -> BrickColor {
+                let distance = |other: Color3uint8| {
+                    let r = i32::from(color.r) - i32::from(other.r);
+                    let g = i32::from(color.g) - i32::from(other.g);
+                    let b = i32::from(color.b) - i32::from(other.b);
+
+                    r * r + g * g + b * b
+                };
+
+                // min_by_key returns the first of several equal elements,
+                // which favors the lower-numbered colors just like Roblox.
+                [$(BrickColor::$enum,)+]
+                    .iter()
+                    .copied()
+                    .min_by_key(|brick_color| distance(brick_color.to_color3uint8()))
+                    .unwrap()
+            }
         }
 
         impl fmt::Display for BrickColor {
@@ -335,6 +368,26 @@ mod tests {
     fn from_number() {
         assert_eq!(BrickColor::from_number(1030), Some(BrickColor::PastelBrown));
     }
+
+    #[test]
+    fn to_color3uint8() {
+        assert_eq!(
+            BrickColor::BrightRed.to_color3uint8(),
+            Color3uint8::new(196, 40, 28)
+        );
+    }
+
+    #[test]
+    fn from_color3uint8() {
+        assert_eq!(
+            BrickColor::from_color3uint8(Color3uint8::new(196, 40, 28)),
+            BrickColor::BrightRed
+        );
+        assert_eq!(
+            BrickColor::from_color3uint8(Color3uint8::new(250, 1, 2)),
+            BrickColor::ReallyRed
+        );
+    }
 }
 
 #[cfg(all(test, feature = "serde"))]
@@ -1,6 +1,11 @@
+use std::{collections::HashMap, sync::Arc};
+
 /// A reference to a Roblox asset.
 ///
 /// When exposed to Lua, this is just a string.
+///
+/// Cloning a `Content` is cheap: clones share the same URL until one of them
+/// is mutated, at which point that clone gets its own copy.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(
     feature = "serde",
@@ -8,31 +13,172 @@
     serde(transparent)
 )]
 pub struct Content {
-    url: String,
+    url: Arc<String>,
 }
 
 impl Content {
     #[inline]
     pub fn new() -> Self {
-        Content { url: String::new() }
+        Content::default()
     }
 
     #[inline]
     pub fn into_string(self) -> String {
-        self.url
+        Arc::try_unwrap(self.url).unwrap_or_else(|url| (*url).clone())
+    }
+
+    /// Tells whether two `Content` values share the same storage, which is
+    /// true for clones that haven't been mutated since.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Content) -> bool {
+        Arc::ptr_eq(&self.url, &other.url)
+    }
+
+    /// The ID of the Roblox asset that this URL refers to, if it's one of the
+    /// forms recognized by [`Content::normalize`].
+    #[inline]
+    pub fn asset_id(&self) -> Option<u64> {
+        roblox_asset_id(&self.url)
+    }
+
+    /// Rewrites URLs that refer to an asset on the Roblox website into the
+    /// canonical `rbxassetid://<id>` form.
+    ///
+    /// `rbxassetid://` URLs and `http(s)` URLs pointing at the asset endpoints
+    /// of `roblox.com`, `www.roblox.com`, and `assetdelivery.roblox.com` are
+    /// recognized. Schemes and hosts are matched without regard to case, and
+    /// fragments are ignored. Anything else, including URLs with query
+    /// parameters other than a single numeric `id`, is returned unchanged.
+    ///
+    /// The returned value shares storage with this one if nothing changed.
+    pub fn normalize(&self) -> Content {
+        match roblox_asset_id(&self.url) {
+            Some(id) => {
+                let normalized = format!("rbxassetid://{}", id);
+
+                if normalized == *self.url {
+                    self.clone()
+                } else {
+                    Content::from(normalized)
+                }
+            }
+            None => self.clone(),
+        }
+    }
+}
+
+/// Rewrites `Content` values into the form returned by [`Content::normalize`],
+/// keeping track of each distinct value that changed.
+#[derive(Debug, Clone, Default)]
+pub struct ContentNormalizer {
+    cache: HashMap<Content, Content>,
+    changes: Vec<(Content, Content)>,
+}
+
+impl ContentNormalizer {
+    /// Creates a normalizer that hasn't seen any values yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the normalized form of the given value. Each distinct value is
+    /// only normalized once, and equal values share the same normalized copy.
+    pub fn normalize(&mut self, value: Content) -> Content {
+        if let Some(normalized) = self.cache.get(&value) {
+            return normalized.clone();
+        }
+
+        let normalized = value.normalize();
+        if normalized != value {
+            self.changes.push((value.clone(), normalized.clone()));
+        }
+
+        self.cache.insert(value, normalized.clone());
+        normalized
+    }
+
+    /// Every value that was changed, paired with what it was changed to, in
+    /// the order they were first seen.
+    pub fn into_changes(self) -> Vec<(Content, Content)> {
+        self.changes
+    }
+}
+
+/// Finds the ID of the asset that the given URL refers to, if it's a URL for a
+/// Roblox asset.
+fn roblox_asset_id(url: &str) -> Option<u64> {
+    // Fragments are never sent to the server, so they can't change which
+    // asset a URL refers to.
+    let url = match url.find('#') {
+        Some(index) => &url[..index],
+        None => url,
+    };
+
+    let (scheme, rest) = url.split_once("://")?;
+
+    if scheme.eq_ignore_ascii_case("rbxassetid") {
+        return parse_asset_id(rest);
+    }
+
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+
+    let (location, query) = rest.split_once('?')?;
+    let (host, path) = match location.find('/') {
+        Some(index) => location.split_at(index),
+        None => (location, ""),
+    };
+
+    let host = host.to_ascii_lowercase();
+    let path = path.trim_end_matches('/').to_ascii_lowercase();
+
+    let is_asset_endpoint = match host.as_str() {
+        "roblox.com" | "www.roblox.com" => path == "/asset",
+        "assetdelivery.roblox.com" => path == "/v1/asset",
+        _ => false,
+    };
+
+    if !is_asset_endpoint {
+        return None;
+    }
+
+    // Other parameters, like `version`, can select something other than the
+    // latest version of the asset, which `rbxassetid://` can't express.
+    let mut id = None;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+        if key.eq_ignore_ascii_case("id") && id.is_none() {
+            id = Some(parse_asset_id(value)?);
+        } else {
+            return None;
+        }
+    }
+
+    id
+}
+
+fn parse_asset_id(id: &str) -> Option<u64> {
+    // `u64::from_str` accepts a leading `+`, which isn't part of any asset ID.
+    if id.is_empty() || !id.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
     }
+
+    id.parse().ok()
 }
 
 impl From<String> for Content {
     fn from(url: String) -> Self {
-        Self { url }
+        Self { url: Arc::new(url) }
     }
 }
 
 impl From<&'_ str> for Content {
     fn from(url: &str) -> Self {
         Self {
-            url: url.to_owned(),
+            url: Arc::new(url.to_owned()),
         }
     }
 }
@@ -51,12 +197,197 @@ impl AsRef<String> for Content {
 
 impl AsMut<str> for Content {
     fn as_mut(&mut self) -> &mut str {
-        &mut self.url
+        Arc::make_mut(&mut self.url).as_mut_str()
     }
 }
 
 impl AsMut<String> for Content {
     fn as_mut(&mut self) -> &mut String {
-        &mut self.url
+        Arc::make_mut(&mut self.url)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clones_share_storage() {
+        let first = Content::from("rbxassetid://1818");
+        let second = first.clone();
+
+        assert!(first.ptr_eq(&second));
+    }
+
+    #[test]
+    fn mutation_copies() {
+        let first = Content::from("rbxassetid://1818");
+        let mut second = first.clone();
+
+        let url: &mut String = second.as_mut();
+        url.push_str("?version=2");
+
+        assert!(!first.ptr_eq(&second));
+        assert_eq!(AsRef::<str>::as_ref(&first), "rbxassetid://1818");
+        assert_eq!(AsRef::<str>::as_ref(&second), "rbxassetid://1818?version=2");
+    }
+
+    #[test]
+    fn into_string_unshared() {
+        let content = Content::from("rbxassetid://1818");
+        let copy = content.clone();
+
+        assert_eq!(content.into_string(), "rbxassetid://1818");
+        assert_eq!(copy.into_string(), "rbxassetid://1818");
+    }
+
+    fn normalized(url: &str) -> String {
+        Content::from(url).normalize().into_string()
+    }
+
+    #[test]
+    fn normalize_canonical_shares_storage() {
+        let content = Content::from("rbxassetid://1818");
+        let normalized = content.normalize();
+
+        assert!(content.ptr_eq(&normalized));
+    }
+
+    #[test]
+    fn normalize_rbxassetid() {
+        assert_eq!(normalized("rbxassetid://1818"), "rbxassetid://1818");
+        assert_eq!(normalized("RbxAssetId://1818"), "rbxassetid://1818");
+        assert_eq!(normalized("rbxassetid://001818"), "rbxassetid://1818");
+    }
+
+    #[test]
+    fn normalize_website_urls() {
+        let urls = [
+            "http://www.roblox.com/asset/?id=1818",
+            "http://www.roblox.com/asset?id=1818",
+            "https://www.roblox.com/asset/?id=1818",
+            "http://roblox.com/asset/?id=1818",
+            "HTTP://WWW.ROBLOX.COM/Asset/?ID=1818",
+            "https://assetdelivery.roblox.com/v1/asset/?id=1818",
+            "https://assetdelivery.roblox.com/v1/asset?id=1818",
+        ];
+
+        for url in &urls {
+            assert_eq!(normalized(url), "rbxassetid://1818", "{}", url);
+        }
+    }
+
+    #[test]
+    fn normalize_query_strings() {
+        assert_eq!(
+            normalized("http://www.roblox.com/asset/?&id=1818&"),
+            "rbxassetid://1818"
+        );
+
+        // Any other parameter keeps the URL as-is, wherever it appears.
+        let urls = [
+            "http://www.roblox.com/asset/?id=1818&version=2",
+            "http://www.roblox.com/asset/?version=2&id=1818",
+            "http://www.roblox.com/asset/?id=1818&id=1818",
+            "http://www.roblox.com/asset/?id=1818&flag",
+            "http://www.roblox.com/asset/",
+            "http://www.roblox.com/asset/?",
+        ];
+
+        for url in &urls {
+            assert_eq!(normalized(url), *url);
+        }
+    }
+
+    #[test]
+    fn normalize_fragments() {
+        assert_eq!(
+            normalized("http://www.roblox.com/asset/?id=1818#top"),
+            "rbxassetid://1818"
+        );
+        assert_eq!(normalized("rbxassetid://1818#top"), "rbxassetid://1818");
+
+        // A fragment before the query string hides the ID.
+        let url = "http://www.roblox.com/asset/#?id=1818";
+        assert_eq!(normalized(url), url);
+    }
+
+    #[test]
+    fn normalize_non_numeric_ids() {
+        let urls = [
+            "rbxassetid://",
+            "rbxassetid://abc",
+            "rbxassetid://+1818",
+            "rbxassetid://18 18",
+            "rbxassetid://1818?version=2",
+            "rbxassetid://99999999999999999999999",
+            "http://www.roblox.com/asset/?id=",
+            "http://www.roblox.com/asset/?id=abc",
+            "http://www.roblox.com/asset/?id=-1818",
+            "http://www.roblox.com/asset/?id=1818abc",
+        ];
+
+        for url in &urls {
+            assert_eq!(normalized(url), *url);
+        }
+    }
+
+    #[test]
+    fn normalize_other_urls() {
+        let urls = [
+            "",
+            "rbxasset://textures/face.png",
+            "rbxthumb://type=Asset&id=1818&w=150&h=150",
+            "rbxhttp://asset/?id=1818",
+            "ftp://www.roblox.com/asset/?id=1818",
+            "http://www.example.com/asset/?id=1818",
+            "http://www.roblox.com.example.com/asset/?id=1818",
+            "http://www.roblox.com:80/asset/?id=1818",
+            "http://www.roblox.com/thumbs/asset.ashx?id=1818",
+            "http://www.roblox.com/asset/extra/?id=1818",
+            "www.roblox.com/asset/?id=1818",
+        ];
+
+        for url in &urls {
+            assert_eq!(normalized(url), *url);
+        }
+    }
+
+    #[test]
+    fn asset_ids() {
+        let id = |url: &str| Content::from(url).asset_id();
+
+        assert_eq!(id("rbxassetid://1818"), Some(1818));
+        assert_eq!(id("https://www.roblox.com/asset/?id=1818"), Some(1818));
+        assert_eq!(
+            id("https://assetdelivery.roblox.com/v1/asset?id=5"),
+            Some(5)
+        );
+        assert_eq!(id("rbxasset://textures/face.png"), None);
+        assert_eq!(id("https://www.roblox.com/asset/?id=1818&version=2"), None);
+    }
+
+    #[test]
+    fn normalizer_records_changes() {
+        let mut normalizer = ContentNormalizer::new();
+
+        let website = "http://www.roblox.com/asset/?id=1818";
+        let first = normalizer.normalize(Content::from(website));
+        let second = normalizer.normalize(Content::from(website));
+        normalizer.normalize(Content::from("rbxassetid://5"));
+        normalizer.normalize(Content::from("https://www.roblox.com/asset/?id=7"));
+
+        assert_eq!(first, Content::from("rbxassetid://1818"));
+        assert!(first.ptr_eq(&second));
+        assert_eq!(
+            normalizer.into_changes(),
+            vec![
+                (Content::from(website), Content::from("rbxassetid://1818")),
+                (
+                    Content::from("https://www.roblox.com/asset/?id=7"),
+                    Content::from("rbxassetid://7")
+                ),
+            ]
+        );
     }
 }
@@ -0,0 +1,120 @@
+use std::io::{self, Read, Write};
+
+/// Wraps a reader and keeps track of how many bytes have been read from it.
+///
+/// rbx_binary and rbx_xml use this to report how much of a file they read.
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    bytes_read: u64,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        CountingReader {
+            inner,
+            bytes_read: 0,
+        }
+    }
+
+    /// The number of bytes read through this reader so far.
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.bytes_read += len as u64;
+
+        Ok(len)
+    }
+}
+
+/// Wraps a writer and keeps track of how many bytes have been written to it.
+///
+/// rbx_binary and rbx_xml use this to report how much of a file they wrote.
+#[derive(Debug)]
+pub struct CountingWriter<W> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        CountingWriter {
+            inner,
+            bytes_written: 0,
+        }
+    }
+
+    /// The number of bytes written through this writer so far.
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.bytes_written += len as u64;
+
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counts_bytes() {
+        let mut reader = CountingReader::new(&b"Hello, world!"[..]);
+        let mut buffer = [0; 5];
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(reader.bytes_read(), 5);
+
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        assert_eq!(reader.bytes_read(), 13);
+
+        let mut writer = CountingWriter::new(Vec::new());
+        writer.write_all(b"Hello").unwrap();
+        writer.write_all(b", world!").unwrap();
+        assert_eq!(writer.bytes_written(), 13);
+        assert_eq!(writer.into_inner(), b"Hello, world!");
+    }
+}
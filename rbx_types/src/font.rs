@@ -0,0 +1,240 @@
+/// A font face, made up of a font family and the weight and style to use from
+/// it.
+///
+/// ## See Also
+/// * [Font on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Font)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "PascalCase")
+)]
+pub struct Font {
+    /// The URL of the font family's description, like
+    /// `rbxasset://fonts/families/SourceSansPro.json`.
+    pub family: String,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+
+    /// The URL of the font file that Roblox last loaded for this face. Roblox
+    /// fills this in itself, and leaves it out of files when it hasn't loaded
+    /// the font yet.
+    pub cached_face_id: Option<String>,
+}
+
+impl Font {
+    pub fn new(family: &str, weight: FontWeight, style: FontStyle) -> Self {
+        Self {
+            family: family.to_owned(),
+            weight,
+            style,
+            cached_face_id: None,
+        }
+    }
+}
+
+impl Default for Font {
+    /// The font that Roblox gives new text objects: Source Sans Pro, with a
+    /// regular weight and normal style.
+    fn default() -> Self {
+        Self::new(
+            "rbxasset://fonts/families/SourceSansPro.json",
+            FontWeight::Regular,
+            FontStyle::Normal,
+        )
+    }
+}
+
+/// The weight of a [`Font`], which is a number from 100 to 900 for the
+/// weights Roblox names.
+///
+/// Weights that Roblox doesn't have a name for are kept as `Other` instead of
+/// being rounded to the nearest named one, so that they're written back
+/// exactly as they were read.
+///
+/// ## See Also
+/// * [FontWeight on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/enum/FontWeight)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "u16", into = "u16")
+)]
+pub enum FontWeight {
+    Thin,
+    ExtraLight,
+    Light,
+    #[default]
+    Regular,
+    Medium,
+    SemiBold,
+    Bold,
+    ExtraBold,
+    Heavy,
+    Other(u16),
+}
+
+impl FontWeight {
+    pub fn from_u16(value: u16) -> Self {
+        match value {
+            100 => FontWeight::Thin,
+            200 => FontWeight::ExtraLight,
+            300 => FontWeight::Light,
+            400 => FontWeight::Regular,
+            500 => FontWeight::Medium,
+            600 => FontWeight::SemiBold,
+            700 => FontWeight::Bold,
+            800 => FontWeight::ExtraBold,
+            900 => FontWeight::Heavy,
+            other => FontWeight::Other(other),
+        }
+    }
+
+    pub fn to_u16(self) -> u16 {
+        match self {
+            FontWeight::Thin => 100,
+            FontWeight::ExtraLight => 200,
+            FontWeight::Light => 300,
+            FontWeight::Regular => 400,
+            FontWeight::Medium => 500,
+            FontWeight::SemiBold => 600,
+            FontWeight::Bold => 700,
+            FontWeight::ExtraBold => 800,
+            FontWeight::Heavy => 900,
+            FontWeight::Other(value) => value,
+        }
+    }
+}
+
+impl From<u16> for FontWeight {
+    fn from(value: u16) -> Self {
+        Self::from_u16(value)
+    }
+}
+
+impl From<FontWeight> for u16 {
+    fn from(weight: FontWeight) -> Self {
+        weight.to_u16()
+    }
+}
+
+/// The style of a [`Font`], which Roblox stores as the value of its
+/// `FontStyle` enum.
+///
+/// Like [`FontWeight`], values that Roblox doesn't have a name for are kept
+/// as `Other`.
+///
+/// ## See Also
+/// * [FontStyle on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/enum/FontStyle)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "u8", into = "u8")
+)]
+pub enum FontStyle {
+    #[default]
+    Normal,
+    Italic,
+    Other(u8),
+}
+
+impl FontStyle {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => FontStyle::Normal,
+            1 => FontStyle::Italic,
+            other => FontStyle::Other(other),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            FontStyle::Normal => 0,
+            FontStyle::Italic => 1,
+            FontStyle::Other(value) => value,
+        }
+    }
+
+    /// The name of this style in Roblox's `FontStyle` enum, if it has one.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            FontStyle::Normal => Some("Normal"),
+            FontStyle::Italic => Some("Italic"),
+            FontStyle::Other(_) => None,
+        }
+    }
+
+    /// Finds the style with the given name in Roblox's `FontStyle` enum.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Normal" => Some(FontStyle::Normal),
+            "Italic" => Some(FontStyle::Italic),
+            _ => None,
+        }
+    }
+}
+
+impl From<u8> for FontStyle {
+    fn from(value: u8) -> Self {
+        Self::from_u8(value)
+    }
+}
+
+impl From<FontStyle> for u8 {
+    fn from(style: FontStyle) -> Self {
+        style.to_u8()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weights_round_trip() {
+        for value in 0..=u16::MAX {
+            assert_eq!(FontWeight::from_u16(value).to_u16(), value);
+        }
+
+        assert_eq!(FontWeight::from_u16(700), FontWeight::Bold);
+        assert_eq!(FontWeight::from_u16(450), FontWeight::Other(450));
+    }
+
+    #[test]
+    fn styles_round_trip() {
+        for value in 0..=u8::MAX {
+            assert_eq!(FontStyle::from_u8(value).to_u8(), value);
+        }
+
+        assert_eq!(FontStyle::from_name("Italic"), Some(FontStyle::Italic));
+        assert_eq!(FontStyle::Other(5).name(), None);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_test {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let font = Font {
+            cached_face_id: Some("rbxasset://fonts/Arimo-Bold.ttf".to_owned()),
+            ..Font::new(
+                "rbxasset://fonts/families/Arimo.json",
+                FontWeight::Other(450),
+                FontStyle::Italic,
+            )
+        };
+
+        let ser = serde_json::to_string(&font).unwrap();
+        assert_eq!(
+            ser,
+            "{\"Family\":\"rbxasset://fonts/families/Arimo.json\",\"Weight\":450,\"Style\":1,\
+             \"CachedFaceId\":\"rbxasset://fonts/Arimo-Bold.ttf\"}"
+        );
+
+        let de: Font = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, font);
+    }
+}
@@ -0,0 +1,232 @@
+//! Conversions between this crate's math types and glam's, enabled by the
+//! `glam` feature.
+//!
+//! Roblox and glam both use right-handed coordinates with Y up, so no axes are
+//! flipped. The one thing to keep in mind is matrix layout: [`Matrix3`] stores
+//! the rows of a rotation matrix, like `CFrame:GetComponents` returns them,
+//! while glam's matrices store columns. These conversions transpose between
+//! the two, so the same point comes out of `cframe * point` and
+//! `Mat4::from(cframe).transform_point3(point)`.
+//!
+//! A [`CFrame`] becomes a [`Mat4`] whose fourth column is the CFrame's
+//! position, or an [`Affine3A`] whose translation is. Converting back from a
+//! `Mat4` keeps its upper 3x4 part and drops its bottom row, which is only
+//! `[0, 0, 0, 1]` for matrices made from a CFrame.
+
+use glam::{Affine3A, I16Vec2, I16Vec3, Mat3, Mat3A, Mat4, Vec2, Vec3, Vec3A};
+
+use crate::{CFrame, Color3, Matrix3, Vector2, Vector2int16, Vector3, Vector3int16};
+
+impl From<Vector2> for Vec2 {
+    fn from(value: Vector2) -> Self {
+        Vec2::new(value.x, value.y)
+    }
+}
+
+impl From<Vec2> for Vector2 {
+    fn from(value: Vec2) -> Self {
+        Vector2::new(value.x, value.y)
+    }
+}
+
+impl From<Vector3> for Vec3 {
+    fn from(value: Vector3) -> Self {
+        Vec3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vec3> for Vector3 {
+    fn from(value: Vec3) -> Self {
+        Vector3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vector3> for Vec3A {
+    fn from(value: Vector3) -> Self {
+        Vec3A::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vec3A> for Vector3 {
+    fn from(value: Vec3A) -> Self {
+        Vector3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vector2int16> for I16Vec2 {
+    fn from(value: Vector2int16) -> Self {
+        I16Vec2::new(value.x, value.y)
+    }
+}
+
+impl From<I16Vec2> for Vector2int16 {
+    fn from(value: I16Vec2) -> Self {
+        Vector2int16::new(value.x, value.y)
+    }
+}
+
+impl From<Vector3int16> for I16Vec3 {
+    fn from(value: Vector3int16) -> Self {
+        I16Vec3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<I16Vec3> for Vector3int16 {
+    fn from(value: I16Vec3) -> Self {
+        Vector3int16::new(value.x, value.y, value.z)
+    }
+}
+
+/// Puts red, green, and blue in `x`, `y`, and `z`.
+impl From<Color3> for Vec3 {
+    fn from(value: Color3) -> Self {
+        Vec3::new(value.r, value.g, value.b)
+    }
+}
+
+impl From<Vec3> for Color3 {
+    fn from(value: Vec3) -> Self {
+        Color3::new(value.x, value.y, value.z)
+    }
+}
+
+/// The rows of the `Matrix3` become the rows of the `Mat3`.
+impl From<Matrix3> for Mat3 {
+    fn from(value: Matrix3) -> Self {
+        Mat3::from_cols(value.x.into(), value.y.into(), value.z.into()).transpose()
+    }
+}
+
+impl From<Mat3> for Matrix3 {
+    fn from(value: Mat3) -> Self {
+        let rows = value.transpose();
+        Matrix3::new(rows.x_axis.into(), rows.y_axis.into(), rows.z_axis.into())
+    }
+}
+
+impl From<Matrix3> for Mat3A {
+    fn from(value: Matrix3) -> Self {
+        Mat3A::from(Mat3::from(value))
+    }
+}
+
+impl From<Mat3A> for Matrix3 {
+    fn from(value: Mat3A) -> Self {
+        Mat3::from(value).into()
+    }
+}
+
+impl From<CFrame> for Affine3A {
+    fn from(value: CFrame) -> Self {
+        Affine3A {
+            matrix3: value.orientation.into(),
+            translation: value.position.into(),
+        }
+    }
+}
+
+impl From<Affine3A> for CFrame {
+    fn from(value: Affine3A) -> Self {
+        CFrame::new(value.translation.into(), value.matrix3.into())
+    }
+}
+
+/// The rotation goes in the upper 3x3 part, and the position in the fourth
+/// column.
+impl From<CFrame> for Mat4 {
+    fn from(value: CFrame) -> Self {
+        let rotation = Mat3::from(value.orientation);
+
+        Mat4::from_cols(
+            rotation.x_axis.extend(0.0),
+            rotation.y_axis.extend(0.0),
+            rotation.z_axis.extend(0.0),
+            Vec3::from(value.position).extend(1.0),
+        )
+    }
+}
+
+/// The bottom row of the matrix is dropped, since a CFrame can't hold a
+/// projection.
+impl From<Mat4> for CFrame {
+    fn from(value: Mat4) -> Self {
+        let rotation = Mat3::from_cols(
+            value.x_axis.truncate(),
+            value.y_axis.truncate(),
+            value.z_axis.truncate(),
+        );
+
+        CFrame::new(value.w_axis.truncate().into(), rotation.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use glam::Vec4;
+
+    /// `CFrame.new(1, 2, 3) * CFrame.Angles(0, math.pi / 2, 0)` in Roblox,
+    /// rounded.
+    fn turned_cframe() -> CFrame {
+        CFrame::from_components(&[1.0, 2.0, 3.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0])
+    }
+
+    #[test]
+    fn mat4_layout() {
+        let matrix = Mat4::from(turned_cframe());
+
+        // glam stores columns, so R02 = 1 is the first value of the third
+        // column, and the position is the fourth column.
+        assert_eq!(
+            matrix.to_cols_array(),
+            [
+                0.0, 0.0, -1.0, 0.0, //
+                0.0, 1.0, 0.0, 0.0, //
+                1.0, 0.0, 0.0, 0.0, //
+                1.0, 2.0, 3.0, 1.0,
+            ]
+        );
+        assert_eq!(matrix.row(0), Vec4::new(0.0, 0.0, 1.0, 1.0));
+
+        // Turning 90 degrees around Y takes +X to -Z.
+        let point = Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(turned_cframe() * point, Vector3::new(1.0, 2.0, 2.0));
+        assert_eq!(
+            matrix.transform_point3(point.into()),
+            Vec3::new(1.0, 2.0, 2.0)
+        );
+
+        assert_eq!(CFrame::from(matrix), turned_cframe());
+    }
+
+    #[test]
+    fn affine_round_trip() {
+        let affine = Affine3A::from(turned_cframe());
+
+        assert_eq!(affine.translation, Vec3A::new(1.0, 2.0, 3.0));
+        assert_eq!(Mat4::from(affine), Mat4::from(turned_cframe()));
+        assert_eq!(CFrame::from(affine), turned_cframe());
+    }
+
+    #[test]
+    fn vectors_and_colors() {
+        assert_eq!(Vec2::from(Vector2::new(1.5, -2.0)), Vec2::new(1.5, -2.0));
+        assert_eq!(
+            Vector3::from(Vec3::new(1.0, 2.0, 3.0)),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            I16Vec3::from(Vector3int16::new(i16::MIN, 0, i16::MAX)),
+            I16Vec3::new(i16::MIN, 0, i16::MAX)
+        );
+        assert_eq!(
+            Vector2int16::from(I16Vec2::new(-7, 7)),
+            Vector2int16::new(-7, 7)
+        );
+        assert_eq!(
+            Vec3::from(Color3::new(1.0, 0.5, 0.25)),
+            Vec3::new(1.0, 0.5, 0.25)
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{Content, Variant};
+
+/// Hands out shared copies of `String` and `Content` values, so that identical
+/// strings all point to the same allocation. Decoders use this to keep files
+/// that repeat the same names, attributes, or asset URLs from storing a copy of
+/// each one.
+#[derive(Debug, Clone, Default)]
+pub struct StringInterner {
+    max_len: usize,
+    strings: HashSet<Arc<str>>,
+    contents: HashSet<Content>,
+}
+
+impl StringInterner {
+    /// Creates an interner that shares values that are at most `max_len` bytes
+    /// long. Longer values are rarely repeated, so they're returned as-is.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            max_len,
+            strings: HashSet::new(),
+            contents: HashSet::new(),
+        }
+    }
+
+    /// Returns a string equal to the given one, sharing storage with any equal
+    /// string interned before it.
+    pub fn intern_str(&mut self, value: Arc<str>) -> Arc<str> {
+        if value.len() > self.max_len {
+            return value;
+        }
+
+        match self.strings.get(&value) {
+            Some(existing) => Arc::clone(existing),
+            None => {
+                self.strings.insert(Arc::clone(&value));
+                value
+            }
+        }
+    }
+
+    /// Returns a value equal to the given one, sharing storage with any equal
+    /// value interned before it.
+    pub fn intern_content(&mut self, value: Content) -> Content {
+        let url: &str = value.as_ref();
+        if url.len() > self.max_len {
+            return value;
+        }
+
+        match self.contents.get(&value) {
+            Some(existing) => existing.clone(),
+            None => {
+                self.contents.insert(value.clone());
+                value
+            }
+        }
+    }
+
+    /// Interns the given value in place if it's a `String` or `Content`.
+    /// Values of other types are left alone.
+    pub fn intern(&mut self, value: &mut Variant) {
+        match value {
+            Variant::String(string) => *string = self.intern_str(Arc::clone(string)),
+            Variant::Content(content) => *content = self.intern_content(content.clone()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shares_storage() {
+        let mut interner = StringInterner::new(32);
+
+        let first = interner.intern_content(Content::from("rbxassetid://1818"));
+        let second = interner.intern_content(Content::from("rbxassetid://1818"));
+        let other = interner.intern_content(Content::from("rbxassetid://1819"));
+
+        assert!(first.ptr_eq(&second));
+        assert!(!first.ptr_eq(&other));
+
+        let first = interner.intern_str("Plastic".into());
+        let second = interner.intern_str("Plastic".into());
+        let other = interner.intern_str("Neon".into());
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+    }
+
+    #[test]
+    fn skips_long_values() {
+        let mut interner = StringInterner::new(4);
+
+        let first = interner.intern_content(Content::from("rbxassetid://1818"));
+        let second = interner.intern_content(Content::from("rbxassetid://1818"));
+        assert!(!first.ptr_eq(&second));
+
+        let first = interner.intern_str("Plastic".into());
+        let second = interner.intern_str("Plastic".into());
+        assert!(!Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn interns_variants() {
+        let mut interner = StringInterner::new(32);
+
+        let mut first = Variant::from("Hello");
+        let mut second = Variant::from("Hello");
+        let mut number = Variant::Int32(7);
+        interner.intern(&mut first);
+        interner.intern(&mut second);
+        interner.intern(&mut number);
+
+        match (&first, &second) {
+            (Variant::String(first), Variant::String(second)) => {
+                assert!(Arc::ptr_eq(first, second))
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(number, Variant::Int32(7));
+
+        // Replacing one value's string leaves the others alone.
+        second = Variant::from("Goodbye");
+        assert_eq!(first, Variant::from("Hello"));
+        assert_eq!(second, Variant::from("Goodbye"));
+    }
+}
@@ -12,7 +12,7 @@ use rbx_dom_weak::{
 
 use crate::{
     BrickColor, CFrame, Color3, Color3uint8, ColorSequence, ColorSequenceKeypoint,
-    CustomPhysicalProperties, Enum, Matrix3, NumberRange, NumberSequence, NumberSequenceKeypoint,
+    CustomPhysicalProperties, Enum, NumberRange, NumberSequence, NumberSequenceKeypoint,
     PhysicalProperties, Ray, Rect, SharedString, UDim, UDim2, Variant, Vector2, Vector2int16,
     Vector3, Vector3int16,
 };
@@ -34,14 +34,7 @@ impl TryFrom<RbxValue> for Variant {
                 Variant::BrickColor(BrickColor::from_number(value as u16).unwrap())
             }
 
-            RbxValue::CFrame { value } => Variant::CFrame(CFrame::new(
-                Vector3::new(value[0], value[1], value[2]),
-                Matrix3 {
-                    x: Vector3::new(value[3], value[4], value[5]),
-                    y: Vector3::new(value[6], value[7], value[8]),
-                    z: Vector3::new(value[9], value[10], value[11]),
-                },
-            )),
+            RbxValue::CFrame { value } => Variant::CFrame(CFrame::from_components(&value)),
 
             RbxValue::Color3 { value } => {
                 Variant::Color3(Color3::new(value[0], value[1], value[2]))
@@ -148,7 +141,9 @@ impl TryFrom<Variant> for RbxValue {
 
     fn try_from(value: Variant) -> Result<Self, Self::Error> {
         Ok(match value {
-            Variant::String(value) => RbxValue::String { value },
+            Variant::String(value) => RbxValue::String {
+                value: value.to_string(),
+            },
             Variant::Bool(value) => RbxValue::Bool { value },
             Variant::Int32(value) => RbxValue::Int32 { value },
             Variant::Int64(value) => RbxValue::Int64 { value },
@@ -164,20 +159,7 @@ impl TryFrom<Variant> for RbxValue {
             },
 
             Variant::CFrame(value) => RbxValue::CFrame {
-                value: [
-                    value.position.x,
-                    value.position.y,
-                    value.position.z,
-                    value.orientation.x.x,
-                    value.orientation.x.y,
-                    value.orientation.x.z,
-                    value.orientation.y.x,
-                    value.orientation.y.y,
-                    value.orientation.y.z,
-                    value.orientation.z.x,
-                    value.orientation.z.y,
-                    value.orientation.z.z,
-                ],
+                value: value.to_components(),
             },
 
             Variant::Color3(value) => RbxValue::Color3 {
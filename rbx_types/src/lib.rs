@@ -5,25 +5,45 @@ mod serde_util;
 #[cfg(feature = "legacy-compat")]
 mod legacy_compat;
 
+#[cfg(feature = "glam")]
+mod glam_conversions;
+
+#[cfg(feature = "mint")]
+mod mint_conversions;
+
+mod attributes;
 mod axes;
 mod basic_types;
 mod binary_string;
+mod blob;
 mod brick_color;
 mod content;
+mod counting;
 mod faces;
+mod font;
+mod interner;
 mod lister;
 mod physical_properties;
 mod referent;
 mod shared_string;
+mod unique_id;
 mod variant;
+mod variant_view;
 
+pub use attributes::*;
 pub use axes::*;
 pub use basic_types::*;
 pub use binary_string::*;
+pub use blob::*;
 pub use brick_color::*;
 pub use content::*;
+pub use counting::*;
 pub use faces::*;
+pub use font::*;
+pub use interner::*;
 pub use physical_properties::*;
 pub use referent::*;
 pub use shared_string::*;
+pub use unique_id::*;
 pub use variant::*;
+pub use variant_view::*;
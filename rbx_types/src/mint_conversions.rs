@@ -0,0 +1,210 @@
+//! Conversions between this crate's math types and mint's, enabled by the
+//! `mint` feature. Most math libraries can convert to and from mint, so this
+//! works with the ones that don't have a feature of their own here.
+//!
+//! mint has separate types for row-major and column-major matrices. A
+//! [`Matrix3`] converts to and from [`RowMatrix3`] without moving anything,
+//! since it stores rows too, and to [`ColumnMatrix3`] by transposing. A
+//! [`CFrame`] becomes a [`ColumnMatrix4`] whose fourth column is its
+//! position, laid out the same way as glam's `Mat4`. Converting back keeps
+//! the upper 3x4 part of the matrix and drops its bottom row.
+
+use mint::{
+    ColumnMatrix3, ColumnMatrix4, RowMatrix3, Vector2 as MintVector2, Vector3 as MintVector3,
+    Vector4,
+};
+
+use crate::{CFrame, Color3, Matrix3, Vector2, Vector2int16, Vector3, Vector3int16};
+
+impl From<Vector2> for MintVector2<f32> {
+    fn from(value: Vector2) -> Self {
+        MintVector2 {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<MintVector2<f32>> for Vector2 {
+    fn from(value: MintVector2<f32>) -> Self {
+        Vector2::new(value.x, value.y)
+    }
+}
+
+impl From<Vector3> for MintVector3<f32> {
+    fn from(value: Vector3) -> Self {
+        MintVector3 {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl From<MintVector3<f32>> for Vector3 {
+    fn from(value: MintVector3<f32>) -> Self {
+        Vector3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vector2int16> for MintVector2<i16> {
+    fn from(value: Vector2int16) -> Self {
+        MintVector2 {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<MintVector2<i16>> for Vector2int16 {
+    fn from(value: MintVector2<i16>) -> Self {
+        Vector2int16::new(value.x, value.y)
+    }
+}
+
+impl From<Vector3int16> for MintVector3<i16> {
+    fn from(value: Vector3int16) -> Self {
+        MintVector3 {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl From<MintVector3<i16>> for Vector3int16 {
+    fn from(value: MintVector3<i16>) -> Self {
+        Vector3int16::new(value.x, value.y, value.z)
+    }
+}
+
+/// Puts red, green, and blue in `x`, `y`, and `z`.
+impl From<Color3> for MintVector3<f32> {
+    fn from(value: Color3) -> Self {
+        MintVector3 {
+            x: value.r,
+            y: value.g,
+            z: value.b,
+        }
+    }
+}
+
+impl From<MintVector3<f32>> for Color3 {
+    fn from(value: MintVector3<f32>) -> Self {
+        Color3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Matrix3> for RowMatrix3<f32> {
+    fn from(value: Matrix3) -> Self {
+        RowMatrix3 {
+            x: value.x.into(),
+            y: value.y.into(),
+            z: value.z.into(),
+        }
+    }
+}
+
+impl From<RowMatrix3<f32>> for Matrix3 {
+    fn from(value: RowMatrix3<f32>) -> Self {
+        Matrix3::new(value.x.into(), value.y.into(), value.z.into())
+    }
+}
+
+impl From<Matrix3> for ColumnMatrix3<f32> {
+    fn from(value: Matrix3) -> Self {
+        RowMatrix3::from(value).into()
+    }
+}
+
+impl From<ColumnMatrix3<f32>> for Matrix3 {
+    fn from(value: ColumnMatrix3<f32>) -> Self {
+        RowMatrix3::from(value).into()
+    }
+}
+
+impl From<CFrame> for ColumnMatrix4<f32> {
+    fn from(value: CFrame) -> Self {
+        let columns = ColumnMatrix3::from(value.orientation);
+        let extend = |column: MintVector3<f32>, w: f32| Vector4 {
+            x: column.x,
+            y: column.y,
+            z: column.z,
+            w,
+        };
+
+        ColumnMatrix4 {
+            x: extend(columns.x, 0.0),
+            y: extend(columns.y, 0.0),
+            z: extend(columns.z, 0.0),
+            w: extend(value.position.into(), 1.0),
+        }
+    }
+}
+
+impl From<ColumnMatrix4<f32>> for CFrame {
+    fn from(value: ColumnMatrix4<f32>) -> Self {
+        let truncate = |column: Vector4<f32>| MintVector3 {
+            x: column.x,
+            y: column.y,
+            z: column.z,
+        };
+
+        let columns = ColumnMatrix3 {
+            x: truncate(value.x),
+            y: truncate(value.y),
+            z: truncate(value.z),
+        };
+
+        CFrame::new(truncate(value.w).into(), columns.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `CFrame.new(1, 2, 3) * CFrame.Angles(0, math.pi / 2, 0)` in Roblox,
+    /// rounded.
+    fn turned_cframe() -> CFrame {
+        CFrame::from_components(&[1.0, 2.0, 3.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 0.0])
+    }
+
+    #[test]
+    fn column_matrix4_layout() {
+        let matrix = ColumnMatrix4::from(turned_cframe());
+        let flat: [f32; 16] = matrix.into();
+
+        assert_eq!(
+            flat,
+            [
+                0.0, 0.0, -1.0, 0.0, //
+                0.0, 1.0, 0.0, 0.0, //
+                1.0, 0.0, 0.0, 0.0, //
+                1.0, 2.0, 3.0, 1.0,
+            ]
+        );
+        assert_eq!(CFrame::from(matrix), turned_cframe());
+    }
+
+    #[test]
+    fn row_matrix3_keeps_rows() {
+        let rows: [f32; 9] = RowMatrix3::from(turned_cframe().orientation).into();
+        assert_eq!(rows, turned_cframe().orientation.to_components());
+
+        let columns = ColumnMatrix3::from(turned_cframe().orientation);
+        assert_eq!(Matrix3::from(columns), turned_cframe().orientation);
+    }
+
+    #[test]
+    fn vectors_and_colors() {
+        let vector: MintVector3<i16> = Vector3int16::new(i16::MIN, 0, i16::MAX).into();
+        assert_eq!(
+            Vector3int16::from(vector),
+            Vector3int16::new(i16::MIN, 0, i16::MAX)
+        );
+
+        let color: [f32; 3] = MintVector3::from(Color3::new(1.0, 0.5, 0.25)).into();
+        assert_eq!(color, [1.0, 0.5, 0.25]);
+    }
+}
@@ -29,6 +29,7 @@ impl From<CustomPhysicalProperties> for PhysicalProperties {
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[repr(C)]
 pub struct CustomPhysicalProperties {
     pub density: f32,
     pub friction: f32,
@@ -34,7 +34,7 @@ impl Ref {
         self.0.is_none()
     }
 
-    fn value(&self) -> u128 {
+    pub(crate) fn value(&self) -> u128 {
         match self.0 {
             Some(value) => value.get(),
             None => 0,
@@ -58,6 +58,90 @@ impl FromStr for Ref {
     }
 }
 
+/// A source of new `Ref` values, used by anything that creates many
+/// instances at once, like the rbx_binary and rbx_xml decoders.
+///
+/// Providers are cloned along with their position in their sequence, so a
+/// clone produces the same `Ref` values as the original would have. This is
+/// what makes decoding the same file twice give the same Refs, but it also
+/// means that instances made from two clones can't go in the same dom, since
+/// their Refs collide. rbx_dom_weak panics instead of letting one instance
+/// replace another.
+pub trait RefProvider: fmt::Debug + Send + Sync {
+    /// Returns a new `Ref` that points to something.
+    fn next_ref(&mut self) -> Ref;
+
+    /// Creates a boxed copy of this provider. This is what makes
+    /// `Box<dyn RefProvider>` cloneable.
+    fn box_clone(&self) -> Box<dyn RefProvider>;
+}
+
+impl Clone for Box<dyn RefProvider> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// A `RefProvider` that returns random `Ref` values, the same as `Ref::new`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomRefProvider;
+
+impl RefProvider for RandomRefProvider {
+    fn next_ref(&mut self) -> Ref {
+        Ref::new()
+    }
+
+    fn box_clone(&self) -> Box<dyn RefProvider> {
+        Box::new(*self)
+    }
+}
+
+/// A `RefProvider` that derives `Ref` values from a seed.
+///
+/// Two providers created with the same seed always return the same sequence
+/// of `Ref` values, so code that creates instances in the same order gets the
+/// same `Ref` for each of them every time it runs. The values are spread out
+/// like random ones, but aren't suitable for anything security-sensitive.
+#[derive(Debug, Clone)]
+pub struct SeededRefProvider {
+    state: u64,
+}
+
+impl SeededRefProvider {
+    /// Creates a provider whose sequence of `Ref` values is determined by
+    /// `seed`.
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        SeededRefProvider { state: seed }
+    }
+
+    /// Steps the SplitMix64 generator.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+}
+
+impl RefProvider for SeededRefProvider {
+    fn next_ref(&mut self) -> Ref {
+        loop {
+            let value = (u128::from(self.next_u64()) << 64) | u128::from(self.next_u64());
+
+            if let Some(value) = NonZeroU128::new(value) {
+                return Ref(Some(value));
+            }
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn RefProvider> {
+        Box::new(self.clone())
+    }
+}
+
 #[cfg(feature = "serde")]
 mod serde_impl {
     use super::*;
@@ -152,6 +236,37 @@ mod test {
     fn size() {
         assert_eq!(std::mem::size_of::<Ref>(), std::mem::size_of::<u128>());
     }
+
+    #[test]
+    fn seeded_sequences_repeat() {
+        let first: Vec<Ref> = {
+            let mut provider = SeededRefProvider::new(1818);
+            (0..100).map(|_| provider.next_ref()).collect()
+        };
+
+        let mut provider = SeededRefProvider::new(1818);
+        let second: Vec<Ref> = (0..100).map(|_| provider.next_ref()).collect();
+
+        assert_eq!(first, second);
+        assert!(first.iter().all(Ref::is_some));
+
+        let mut unique = first.clone();
+        unique.sort_by_key(Ref::value);
+        unique.dedup();
+        assert_eq!(unique.len(), first.len());
+
+        let mut other_seed = SeededRefProvider::new(1819);
+        assert_ne!(other_seed.next_ref(), first[0]);
+    }
+
+    #[test]
+    fn boxed_providers_clone_their_position() {
+        let mut provider: Box<dyn RefProvider> = Box::new(SeededRefProvider::new(5));
+        provider.next_ref();
+
+        let mut copy = provider.clone();
+        assert_eq!(copy.next_ref(), provider.next_ref());
+    }
 }
 
 #[cfg(all(test, feature = "serde"))]
@@ -1,11 +1,14 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
     hash::{Hash, Hasher},
+    io,
     sync::{Arc, Mutex, Weak},
 };
 
 use blake3::Hash as Blake3Hash;
 
+use crate::blob::{Blob, LazyBlob};
+
 lazy_static::lazy_static! {
     static ref STRING_CACHE: Arc<Mutex<HashMap<Blake3Hash, Weak<Vec<u8>>>>> = {
         Arc::new(Mutex::new(HashMap::new()))
@@ -15,9 +18,14 @@ lazy_static::lazy_static! {
 /// A version of `BinaryString` used for data that's commonly repeated.
 /// `rbx_types` automatically deduplicates data as it's loaded into
 /// `SharedString` values.
+///
+/// A `SharedString` can also be backed by a [`Blob`], in which case its
+/// contents are read from the blob's store the first time they're needed.
+/// These aren't deduplicated with other values.
 #[derive(Debug, Clone)]
 pub struct SharedString {
     data: Option<Arc<Vec<u8>>>,
+    stored: Option<Arc<LazyBlob>>,
     hash: Blake3Hash,
 }
 
@@ -59,13 +67,55 @@ impl SharedString {
 
         SharedString {
             data: Some(data),
+            stored: None,
             hash,
         }
     }
 
+    /// Construct a SharedString whose contents are kept in the given blob.
+    /// The blob's hash was computed when it was stored, so this doesn't read
+    /// from the store.
+    ///
+    /// [`try_data`][SharedString::try_data] reads the contents into memory
+    /// the first time it's called. Encoders can avoid this by checking
+    /// [`blob`][SharedString::blob] first.
+    pub fn from_blob(blob: Blob) -> io::Result<SharedString> {
+        Ok(SharedString {
+            data: None,
+            hash: blob.blake3_hash(),
+            stored: Some(Arc::new(LazyBlob::new(blob))),
+        })
+    }
+
+    /// The contents of this value.
+    ///
+    /// ## Panics
+    /// Panics if this value is kept in a blob whose store can't give its
+    /// contents back. Use [`try_data`][SharedString::try_data] to handle that
+    /// instead.
     #[inline]
     pub fn data(&self) -> &[u8] {
-        &self.data.as_ref().unwrap()
+        match (&self.data, &self.stored) {
+            (Some(data), _) => data,
+            (None, Some(stored)) => stored.data(),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    /// The contents of this value, or the error from reading them back from
+    /// the blob they're kept in.
+    pub fn try_data(&self) -> io::Result<&[u8]> {
+        match (&self.data, &self.stored) {
+            (Some(data), _) => Ok(data),
+            (None, Some(stored)) => stored.try_data().map(Vec::as_slice),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    /// The blob that this value's contents are kept in, if any.
+    #[inline]
+    pub fn blob(&self) -> Option<&Blob> {
+        self.stored.as_deref().map(LazyBlob::blob)
     }
 
     #[inline]
@@ -102,7 +152,14 @@ impl Drop for SharedString {
         // If the reference we're about to drop is the very last reference to
         // the buffer, we'll be able to unwrap it and remove it from the
         // SharedString cache.
-        if Arc::try_unwrap(self.data.take().unwrap()).is_ok() {
+        let data = match self.data.take() {
+            Some(data) => data,
+
+            // Values kept in a blob were never added to the cache.
+            None => return,
+        };
+
+        if Arc::try_unwrap(data).is_ok() {
             let mut cache = match STRING_CACHE.lock() {
                 Ok(v) => v,
                 Err(_) => {
@@ -112,7 +169,15 @@ impl Drop for SharedString {
                 }
             };
 
-            cache.remove(&self.hash);
+            // Another thread may have created a SharedString with the same
+            // contents between us letting go of the buffer and taking the
+            // lock, in which case the entry now belongs to that string and
+            // needs to stay.
+            if let Entry::Occupied(occupied) = cache.entry(self.hash) {
+                if occupied.get().strong_count() == 0 {
+                    occupied.remove();
+                }
+            }
         }
     }
 }
@@ -169,6 +234,31 @@ mod test {
         assert!(Arc::ptr_eq(data_1, data_2));
     }
 
+    #[test]
+    fn from_blob() {
+        use crate::{BlobStore, TempFileStore};
+
+        let store: Arc<dyn BlobStore> = Arc::new(TempFileStore::new().unwrap());
+        let blob = Blob::store(&store, &[5, 4, 3][..]).unwrap();
+
+        let stored = SharedString::from_blob(blob).unwrap();
+        let in_memory = SharedString::new(vec![5, 4, 3]);
+
+        assert_eq!(stored, in_memory);
+        assert_eq!(stored.hash(), in_memory.hash());
+        assert_eq!(stored.try_data().unwrap(), in_memory.data());
+        assert!(stored.blob().is_some());
+        assert!(in_memory.blob().is_none());
+    }
+
+    #[test]
+    fn unreadable_blob() {
+        let stored = SharedString::from_blob(crate::blob::unreadable_blob(&[1, 2])).unwrap();
+
+        assert!(stored.try_data().is_err());
+        assert_eq!(stored, SharedString::new(vec![1, 2]));
+    }
+
     #[test]
     fn drop() {
         {
@@ -179,4 +269,30 @@ mod test {
             let _y = SharedString::new(vec![5, 6, 7, 1]);
         }
     }
+
+    #[test]
+    fn insert_and_drop_across_threads() {
+        // Each thread repeatedly creates and drops the same string, which
+        // exercises the cache from many threads at once. Handles that are
+        // alive at the same time should always share a buffer.
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    for _ in 0..10_000 {
+                        let handle_1 = SharedString::new(vec![9, 8, 7, 6]);
+                        let handle_2 = SharedString::new(vec![9, 8, 7, 6]);
+
+                        let data_1 = handle_1.data.as_ref().unwrap();
+                        let data_2 = handle_2.data.as_ref().unwrap();
+
+                        assert!(Arc::ptr_eq(data_1, data_2));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
 }
@@ -0,0 +1,267 @@
+use std::{
+    error::Error,
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The time that Roblox counts `UniqueId` timestamps from, which is the
+/// start of 2021 in seconds since the Unix epoch.
+const ROBLOX_EPOCH: u64 = 1_609_459_200;
+
+/// The index given to the next `UniqueId` made by `UniqueId::generate`.
+static NEXT_INDEX: AtomicU32 = AtomicU32::new(0);
+
+/// An identifier that Roblox gives every instance, which stays the same
+/// across saves instead of changing like a [`Ref`][crate::Ref] does.
+///
+/// A `UniqueId` is 16 bytes: a random number, the time it was created, and an
+/// index that tells apart IDs created in the same second. It's written as 32
+/// hexadecimal digits, in that order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UniqueId {
+    index: u32,
+    time: u32,
+    random: i64,
+}
+
+impl UniqueId {
+    pub fn new(index: u32, time: u32, random: i64) -> Self {
+        Self {
+            index,
+            time,
+            random,
+        }
+    }
+
+    /// Creates a new `UniqueId` the same way Roblox does when an instance is
+    /// created, for giving copies of an instance IDs of their own.
+    pub fn generate() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            index: NEXT_INDEX.fetch_add(1, Ordering::Relaxed),
+            time: now.saturating_sub(ROBLOX_EPOCH) as u32,
+            random: rand::random(),
+        }
+    }
+
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The number of seconds between the start of 2021 and when this ID was
+    /// created.
+    #[inline]
+    pub fn time(&self) -> u32 {
+        self.time
+    }
+
+    #[inline]
+    pub fn random(&self) -> i64 {
+        self.random
+    }
+
+    /// The 16 bytes that make up this ID, in the order they're written.
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.value().to_be_bytes()
+    }
+
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self::from_value(u128::from_be_bytes(bytes))
+    }
+
+    fn value(&self) -> u128 {
+        (u128::from(self.random as u64) << 64)
+            | (u128::from(self.time) << 32)
+            | u128::from(self.index)
+    }
+
+    fn from_value(value: u128) -> Self {
+        Self {
+            index: value as u32,
+            time: (value >> 32) as u32,
+            random: (value >> 64) as u64 as i64,
+        }
+    }
+}
+
+impl fmt::Display for UniqueId {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{:032x}", self.value())
+    }
+}
+
+impl FromStr for UniqueId {
+    type Err = UniqueIdParseError;
+
+    /// Reads a `UniqueId` from exactly 32 hexadecimal digits.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.len() != 32 || !input.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(UniqueIdParseError {
+                input: input.to_owned(),
+            });
+        }
+
+        // Every digit was checked above, so this can't fail.
+        let value = u128::from_str_radix(input, 16).unwrap();
+        Ok(Self::from_value(value))
+    }
+}
+
+/// The error returned when a string isn't a valid `UniqueId`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueIdParseError {
+    input: String,
+}
+
+impl fmt::Display for UniqueIdParseError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            output,
+            "{:?} is not a UniqueId, which must be 32 hexadecimal digits",
+            self.input
+        )
+    }
+}
+
+impl Error for UniqueIdParseError {}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::*;
+
+    use serde::{
+        de::{Error, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    impl Serialize for UniqueId {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            if serializer.is_human_readable() {
+                serializer.serialize_str(&self.to_string())
+            } else {
+                serializer.serialize_u128(self.value())
+            }
+        }
+    }
+
+    struct UniqueIdVisitor;
+
+    impl<'de> Visitor<'de> for UniqueIdVisitor {
+        type Value = UniqueId;
+
+        fn expecting(&self, out: &mut fmt::Formatter) -> fmt::Result {
+            write!(out, "a Roblox UniqueId")
+        }
+
+        fn visit_u128<E: Error>(self, value: u128) -> Result<Self::Value, E> {
+            Ok(UniqueId::from_value(value))
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+            value.parse().map_err(E::custom)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for UniqueId {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_str(UniqueIdVisitor)
+            } else {
+                deserializer.deserialize_u128(UniqueIdVisitor)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let id = UniqueId::new(0x0000_1c3e, 0x02e9_c68d, -0x4b4e_7725_319c_d4b9);
+        assert_eq!(id.to_string(), "b4b188dace632b4702e9c68d00001c3e");
+        assert_eq!(UniqueId::default().to_string(), "0".repeat(32));
+    }
+
+    #[test]
+    fn from_str() {
+        let input = "b4b188dace632b4702e9c68d00001c3e";
+        let id: UniqueId = input.parse().unwrap();
+
+        assert_eq!(id.index(), 0x0000_1c3e);
+        assert_eq!(id.time(), 0x02e9_c68d);
+        assert_eq!(id.random(), -0x4b4e_7725_319c_d4b9);
+        assert_eq!(id.to_string(), input);
+
+        // Uppercase digits are accepted, but always written in lowercase.
+        let upper: UniqueId = input.to_uppercase().parse().unwrap();
+        assert_eq!(upper, id);
+
+        assert!("b4b188dace632b47".parse::<UniqueId>().is_err());
+        assert!("+4b188dace632b4702e9c68d00001c3e"
+            .parse::<UniqueId>()
+            .is_err());
+        assert!("x4b188dace632b4702e9c68d00001c3e"
+            .parse::<UniqueId>()
+            .is_err());
+    }
+
+    #[test]
+    fn bytes() {
+        let id: UniqueId = "b4b188dace632b4702e9c68d00001c3e".parse().unwrap();
+        let bytes = id.to_bytes();
+
+        assert_eq!(bytes[0], 0xb4);
+        assert_eq!(bytes[15], 0x3e);
+        assert_eq!(UniqueId::from_bytes(bytes), id);
+    }
+
+    #[test]
+    fn generated_ids_are_distinct() {
+        let first = UniqueId::generate();
+        let second = UniqueId::generate();
+
+        assert_ne!(first, second);
+        assert_ne!(first.index(), second.index());
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_test {
+    use super::*;
+
+    #[test]
+    fn human() {
+        let id: UniqueId = "b4b188dace632b4702e9c68d00001c3e".parse().unwrap();
+
+        let ser = serde_json::to_string(&id).unwrap();
+        assert_eq!(ser, "\"b4b188dace632b4702e9c68d00001c3e\"");
+
+        let de: UniqueId = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, id);
+    }
+
+    #[test]
+    fn non_human() {
+        let id = UniqueId::generate();
+
+        let ser = bincode::serialize(&id).unwrap();
+        let de = bincode::deserialize(&ser).unwrap();
+
+        assert_eq!(id, de);
+    }
+}
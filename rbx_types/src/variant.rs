@@ -1,7 +1,10 @@
+use std::{convert::TryFrom, error::Error, fmt, sync::Arc};
+
 use crate::{
-    Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence, Content, Enum,
-    Faces, NumberRange, NumberSequence, PhysicalProperties, Ray, Rect, Ref, Region3, Region3int16,
-    SharedString, UDim, UDim2, Vector2, Vector2int16, Vector3, Vector3int16,
+    Attributes, Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
+    Content, Enum, Faces, Font, NumberRange, NumberSequence, PhysicalProperties, Ray, Rect, Ref,
+    Region3, Region3int16, SharedString, UDim, UDim2, UniqueId, Vector2, Vector2int16, Vector3,
+    Vector3int16,
 };
 
 /// Reduces boilerplate from listing different values of Variant by wrapping
@@ -52,11 +55,51 @@ macro_rules! make_variant {
                     Self::$variant_name(value)
                 }
             }
+
+            impl TryFrom<Variant> for $inner_type {
+                type Error = VariantTypeError;
+
+                fn try_from(value: Variant) -> Result<Self, Self::Error> {
+                    match value {
+                        Variant::$variant_name(inner) => Ok(inner),
+                        other => Err(VariantTypeError {
+                            expected: VariantType::$variant_name,
+                            actual: other.ty(),
+                        }),
+                    }
+                }
+            }
+
+            impl<'a> TryFrom<&'a Variant> for &'a $inner_type {
+                type Error = VariantTypeError;
+
+                fn try_from(value: &'a Variant) -> Result<Self, Self::Error> {
+                    match value {
+                        Variant::$variant_name(inner) => Ok(inner),
+                        other => Err(VariantTypeError {
+                            expected: VariantType::$variant_name,
+                            actual: other.ty(),
+                        }),
+                    }
+                }
+            }
+
+            impl TryFrom<&'_ Variant> for $inner_type {
+                type Error = VariantTypeError;
+
+                fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+                    <&$inner_type>::try_from(value).map(Clone::clone)
+                }
+            }
         )*
 
         /// Represents any type that can be held in a `Variant`.
+        ///
+        /// Each type's discriminant is its position in the list of types,
+        /// starting from zero, and is stored as a `u32`.
         #[derive(Debug, Clone, Copy, PartialEq, Eq)]
         #[non_exhaustive]
+        #[repr(u32)]
         #[cfg_attr(
             feature = "serde",
             derive(serde::Serialize, serde::Deserialize),
@@ -83,6 +126,33 @@ macro_rules! make_variant {
                 $( trait_test::<$inner_type>(); )*
                 trait_test::<SharedString>();
             }
+
+            /// The same as `conversions_are_exhaustive`, but for getting
+            /// values back out of a `Variant`.
+            #[allow(dead_code)]
+            fn try_conversions_are_exhaustive() {
+                fn trait_test<T>()
+                where
+                    T: TryFrom<Variant> + for<'a> TryFrom<&'a Variant>,
+                    for<'a> &'a T: TryFrom<&'a Variant>,
+                {
+                }
+
+                $( trait_test::<$inner_type>(); )*
+            }
+
+            /// This test makes sure that values can be shared between threads.
+            /// SharedString in particular relies on a global cache, which must
+            /// stay synchronized.
+            #[allow(dead_code)]
+            fn values_are_send_and_sync() {
+                fn trait_test<T: Send + Sync>() {}
+
+                trait_test::<Variant>();
+                trait_test::<VariantType>();
+                $( trait_test::<$inner_type>(); )*
+                trait_test::<SharedString>();
+            }
         }
     };
 }
@@ -119,18 +189,122 @@ make_variant! {
         serde(with = "crate::shared_string::variant_serialization"),
     )]
     SharedString(SharedString),
-    String(String),
+    /// Strings are shared between clones, like [`Content`]. Replacing the
+    /// string in one value never changes any other value.
+    String(Arc<str>),
     UDim(UDim),
     UDim2(UDim2),
     Vector2(Vector2),
     Vector2int16(Vector2int16),
     Vector3(Vector3),
     Vector3int16(Vector3int16),
+    Attributes(Attributes),
+    OptionalCFrame(Option<CFrame>),
+    Font(Font),
+    UniqueId(UniqueId),
 }
 
 impl From<&'_ str> for Variant {
     fn from(value: &str) -> Self {
-        Self::String(value.to_owned())
+        Self::String(value.into())
+    }
+}
+
+impl From<String> for Variant {
+    fn from(value: String) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl TryFrom<Variant> for String {
+    type Error = VariantTypeError;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        <&str>::try_from(&value).map(str::to_owned)
+    }
+}
+
+impl TryFrom<&'_ Variant> for String {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &Variant) -> Result<Self, Self::Error> {
+        <&str>::try_from(value).map(str::to_owned)
+    }
+}
+
+impl<'a> TryFrom<&'a Variant> for &'a str {
+    type Error = VariantTypeError;
+
+    fn try_from(value: &'a Variant) -> Result<Self, Self::Error> {
+        <&Arc<str>>::try_from(value).map(|value| &**value)
+    }
+}
+
+/// The error returned when converting a `Variant` into a type that it doesn't
+/// hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantTypeError {
+    expected: VariantType,
+    actual: VariantType,
+}
+
+impl VariantTypeError {
+    /// The type that the `Variant` was being converted into.
+    #[inline]
+    pub fn expected(&self) -> VariantType {
+        self.expected
+    }
+
+    /// The type of the value that the `Variant` actually held.
+    #[inline]
+    pub fn actual(&self) -> VariantType {
+        self.actual
+    }
+}
+
+impl fmt::Display for VariantTypeError {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            output,
+            "expected a value of type {:?}, but it was of type {:?}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl Error for VariantTypeError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn try_from_matching_type() {
+        let value = Variant::Vector2(Vector2::new(5.0, 7.0));
+
+        assert_eq!(Vector2::try_from(&value), Ok(Vector2::new(5.0, 7.0)));
+        assert_eq!(<&Vector2>::try_from(&value), Ok(&Vector2::new(5.0, 7.0)));
+        assert_eq!(Vector2::try_from(value), Ok(Vector2::new(5.0, 7.0)));
+    }
+
+    #[test]
+    fn try_from_wrong_type() {
+        let error = f32::try_from(Variant::Float64(1.0)).unwrap_err();
+
+        assert_eq!(error.expected(), VariantType::Float32);
+        assert_eq!(error.actual(), VariantType::Float64);
+        assert_eq!(
+            error.to_string(),
+            "expected a value of type Float32, but it was of type Float64"
+        );
+    }
+
+    #[test]
+    fn try_from_borrowed_shares_storage() {
+        let content = Content::from("rbxassetid://1818");
+        let value = Variant::Content(content.clone());
+
+        assert!(Content::try_from(&value).unwrap().ptr_eq(&content));
     }
 }
 
@@ -0,0 +1,561 @@
+use std::{fmt, io, marker::PhantomData};
+
+use crate::{
+    Attributes, CFrame, Color3, Color3uint8, ColorSequenceKeypoint, CustomPhysicalProperties, Font,
+    Matrix3, NumberRange, NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Region3,
+    Region3int16, UDim, UDim2, Variant, VariantType, Vector2, Vector2int16, Vector3, Vector3int16,
+};
+
+/// A borrowed view of the value held by a [`Variant`], laid out so that it
+/// can be handed across an FFI boundary. Created with
+/// [`Variant::as_raw_view`].
+///
+/// `ty` tells which field of `data` holds the value. Types that own a buffer
+/// are viewed as a pointer to it and its length:
+///
+/// - `BinaryString`, `Content`, `SharedString`, and `String` use `bytes`
+/// - `ColorSequence` and `NumberSequence` point to their keypoints
+///
+/// Those pointers are valid for as long as the `Variant` is borrowed, which
+/// is what the view's lifetime tracks. For a property of an instance in a
+/// dom, that's as long as the dom is borrowed. Every other type is copied
+/// into the view. Creating a view never allocates, unless a value kept in a
+/// [`Blob`][crate::Blob] has to be read back into memory.
+///
+/// ```
+/// use rbx_types::{Variant, VariantType, VariantView, Vector3};
+///
+/// // Stands in for a function written in C, which only knows the layout of
+/// // the view.
+/// extern "C" fn magnitude(view: *const VariantView) -> f32 {
+///     let view = unsafe { &*view };
+///
+///     if view.ty as u32 != VariantType::Vector3 as u32 {
+///         return -1.0;
+///     }
+///
+///     let vector = unsafe { view.data.vector3 };
+///     (vector.x * vector.x + vector.y * vector.y + vector.z * vector.z).sqrt()
+/// }
+///
+/// extern "C" fn byte_len(view: *const VariantView) -> usize {
+///     let view = unsafe { &*view };
+///
+///     match view.ty {
+///         VariantType::String | VariantType::Content => unsafe { view.data.bytes.len },
+///         _ => 0,
+///     }
+/// }
+///
+/// let size = Variant::Vector3(Vector3::new(3.0, 4.0, 0.0));
+/// assert_eq!(magnitude(&size.as_raw_view()), 5.0);
+///
+/// let name = Variant::from("Baseplate");
+/// assert_eq!(byte_len(&name.as_raw_view()), 9);
+/// assert_eq!(magnitude(&name.as_raw_view()), -1.0);
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VariantView<'a> {
+    pub ty: VariantType,
+    pub data: VariantViewData,
+    borrow: PhantomData<&'a Variant>,
+}
+
+impl<'a> VariantView<'a> {
+    fn new(ty: VariantType, data: VariantViewData) -> Self {
+        VariantView {
+            ty,
+            data,
+            borrow: PhantomData,
+        }
+    }
+
+    /// The bytes of a `BinaryString`, `Content`, `SharedString`, or `String`,
+    /// or `None` for any other type.
+    pub fn bytes(&self) -> Option<&'a [u8]> {
+        match self.ty {
+            VariantType::BinaryString
+            | VariantType::Content
+            | VariantType::SharedString
+            | VariantType::String => {
+                // SAFETY: `bytes` is the field written for these types, and
+                // was created from a slice borrowed for `'a`.
+                Some(unsafe { self.data.bytes.as_slice() })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for VariantView<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("VariantView")
+            .field("ty", &self.ty)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The value held by a [`VariantView`]. Which field is valid depends on the
+/// view's `ty`, and is named after that type.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union VariantViewData {
+    /// The bits of an `Axes` value.
+    pub axes: u8,
+    pub bytes: RawSlice<u8>,
+    pub boolean: bool,
+    /// The number of a `BrickColor`.
+    pub brick_color: u16,
+    pub cframe: CFrame,
+    pub color3: Color3,
+    pub color3uint8: Color3uint8,
+    pub color_sequence: RawSlice<ColorSequenceKeypoint>,
+    pub enum_value: u32,
+    /// The bits of a `Faces` value.
+    pub faces: u8,
+    pub float32: f32,
+    pub float64: f64,
+    pub int32: i32,
+    pub int64: i64,
+    pub number_range: NumberRange,
+    pub number_sequence: RawSlice<NumberSequenceKeypoint>,
+    pub physical_properties: RawPhysicalProperties,
+    pub ray: Ray,
+    pub rect: Rect,
+    /// The little-endian bytes of a `Ref`, which are all zero for a `Ref`
+    /// that points to nothing.
+    pub referent: [u8; 16],
+    pub region3: Region3,
+    pub region3int16: Region3int16,
+    pub udim: UDim,
+    pub udim2: UDim2,
+    pub vector2: Vector2,
+    pub vector2int16: Vector2int16,
+    pub vector3: Vector3,
+    pub vector3int16: Vector3int16,
+    /// A pointer to an `Attributes` value, which has no C layout and can only
+    /// be read from Rust.
+    pub attributes: *const Attributes,
+    pub optional_cframe: RawOptionalCFrame,
+    /// A pointer to a `Font` value, which, like `Attributes`, can only be read
+    /// from Rust.
+    pub font: *const Font,
+    /// The bytes of a `UniqueId`, in the order they're written as hex.
+    pub unique_id: [u8; 16],
+}
+
+/// A pointer to the start of a slice and the number of elements in it.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RawSlice<T> {
+    pub ptr: *const T,
+    pub len: usize,
+}
+
+impl<T> RawSlice<T> {
+    fn new(slice: &[T]) -> Self {
+        RawSlice {
+            ptr: slice.as_ptr(),
+            len: slice.len(),
+        }
+    }
+
+    /// Turns this back into a slice.
+    ///
+    /// # Safety
+    ///
+    /// The slice this was created from must still be borrowed for `'a`.
+    pub unsafe fn as_slice<'a>(&self) -> &'a [T] {
+        std::slice::from_raw_parts(self.ptr, self.len)
+    }
+}
+
+/// `PhysicalProperties` as viewed by a [`VariantView`]. When `custom` is
+/// false, the default physical properties of the part's material apply and
+/// `properties` is all zeroes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawPhysicalProperties {
+    pub custom: bool,
+    pub properties: CustomPhysicalProperties,
+}
+
+/// An optional `CFrame` as viewed by a [`VariantView`]. When `present` is
+/// false, there's no CFrame and `cframe` is all zeroes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawOptionalCFrame {
+    pub present: bool,
+    pub cframe: CFrame,
+}
+
+impl Variant {
+    /// Creates a view of this value that can be handed across an FFI
+    /// boundary. See [`VariantView`] for how each type is laid out.
+    ///
+    /// ## Panics
+    /// Panics if this is a `BinaryString` or `SharedString` kept in a blob
+    /// whose store can't give its contents back. Use
+    /// [`try_as_raw_view`][Variant::try_as_raw_view] to handle that instead.
+    pub fn as_raw_view(&self) -> VariantView<'_> {
+        self.try_as_raw_view()
+            .unwrap_or_else(|err| panic!("could not view {:?}: {}", self.ty(), err))
+    }
+
+    /// Like [`as_raw_view`][Variant::as_raw_view], but returns the error from
+    /// reading a `BinaryString` or `SharedString` back from the blob it's
+    /// kept in. That's also the only time creating a view allocates.
+    pub fn try_as_raw_view(&self) -> io::Result<VariantView<'_>> {
+        let data = match self {
+            Variant::Axes(value) => VariantViewData { axes: value.bits() },
+            Variant::BinaryString(value) => VariantViewData {
+                bytes: RawSlice::new(value.try_data()?),
+            },
+            Variant::Bool(value) => VariantViewData { boolean: *value },
+            Variant::BrickColor(value) => VariantViewData {
+                brick_color: *value as u16,
+            },
+            Variant::CFrame(value) => VariantViewData { cframe: *value },
+            Variant::Color3(value) => VariantViewData { color3: *value },
+            Variant::Color3uint8(value) => VariantViewData {
+                color3uint8: *value,
+            },
+            Variant::ColorSequence(value) => VariantViewData {
+                color_sequence: RawSlice::new(&value.keypoints),
+            },
+            Variant::Content(value) => VariantViewData {
+                bytes: RawSlice::new(AsRef::<str>::as_ref(value).as_bytes()),
+            },
+            Variant::Enum(value) => VariantViewData {
+                enum_value: value.to_u32(),
+            },
+            Variant::Faces(value) => VariantViewData {
+                faces: value.bits(),
+            },
+            Variant::Float32(value) => VariantViewData { float32: *value },
+            Variant::Float64(value) => VariantViewData { float64: *value },
+            Variant::Int32(value) => VariantViewData { int32: *value },
+            Variant::Int64(value) => VariantViewData { int64: *value },
+            Variant::NumberRange(value) => VariantViewData {
+                number_range: *value,
+            },
+            Variant::NumberSequence(value) => VariantViewData {
+                number_sequence: RawSlice::new(&value.keypoints),
+            },
+            Variant::PhysicalProperties(value) => VariantViewData {
+                physical_properties: match value {
+                    PhysicalProperties::Default => RawPhysicalProperties {
+                        custom: false,
+                        properties: CustomPhysicalProperties {
+                            density: 0.0,
+                            friction: 0.0,
+                            elasticity: 0.0,
+                            friction_weight: 0.0,
+                            elasticity_weight: 0.0,
+                        },
+                    },
+                    PhysicalProperties::Custom(properties) => RawPhysicalProperties {
+                        custom: true,
+                        properties: *properties,
+                    },
+                },
+            },
+            Variant::Ray(value) => VariantViewData { ray: *value },
+            Variant::Rect(value) => VariantViewData { rect: *value },
+            Variant::Ref(value) => VariantViewData {
+                referent: value.value().to_le_bytes(),
+            },
+            Variant::Region3(value) => VariantViewData { region3: *value },
+            Variant::Region3int16(value) => VariantViewData {
+                region3int16: *value,
+            },
+            Variant::SharedString(value) => VariantViewData {
+                bytes: RawSlice::new(value.try_data()?),
+            },
+            Variant::String(value) => VariantViewData {
+                bytes: RawSlice::new(value.as_bytes()),
+            },
+            Variant::UDim(value) => VariantViewData { udim: *value },
+            Variant::UDim2(value) => VariantViewData { udim2: *value },
+            Variant::Vector2(value) => VariantViewData { vector2: *value },
+            Variant::Vector2int16(value) => VariantViewData {
+                vector2int16: *value,
+            },
+            Variant::Vector3(value) => VariantViewData { vector3: *value },
+            Variant::Vector3int16(value) => VariantViewData {
+                vector3int16: *value,
+            },
+            Variant::Attributes(value) => VariantViewData { attributes: value },
+            Variant::OptionalCFrame(value) => VariantViewData {
+                optional_cframe: match value {
+                    Some(cframe) => RawOptionalCFrame {
+                        present: true,
+                        cframe: *cframe,
+                    },
+                    None => RawOptionalCFrame {
+                        present: false,
+                        cframe: CFrame::new(
+                            Vector3::new(0.0, 0.0, 0.0),
+                            Matrix3 {
+                                x: Vector3::new(0.0, 0.0, 0.0),
+                                y: Vector3::new(0.0, 0.0, 0.0),
+                                z: Vector3::new(0.0, 0.0, 0.0),
+                            },
+                        ),
+                    },
+                },
+            },
+            Variant::Font(value) => VariantViewData { font: value },
+            Variant::UniqueId(value) => VariantViewData {
+                unique_id: value.to_bytes(),
+            },
+        };
+
+        Ok(VariantView::new(self.ty(), data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        Axes, BinaryString, BrickColor, ColorSequence, Content, Enum, Faces, NumberSequence, Ref,
+        SharedString,
+    };
+
+    /// Views the value, checks its type, and reads the field of `data` that
+    /// the type uses. The value is dropped afterwards, so this can only be
+    /// used for types that are copied into the view.
+    macro_rules! view {
+        ($value: expr, $ty: ident, $field: ident) => {{
+            let value = $value;
+            let view = value.as_raw_view();
+            assert_eq!(view.ty, VariantType::$ty);
+            unsafe { view.data.$field }
+        }};
+    }
+
+    #[test]
+    fn byte_backed() {
+        let values = [
+            Variant::BinaryString(BinaryString::from(vec![0, 1, 2, 255])),
+            Variant::Content(Content::from("rbxassetid://1818")),
+            Variant::SharedString(SharedString::new(b"shared".to_vec())),
+            Variant::from("Hello, world!"),
+        ];
+
+        let expected: [&[u8]; 4] = [
+            &[0, 1, 2, 255],
+            b"rbxassetid://1818",
+            b"shared",
+            b"Hello, world!",
+        ];
+
+        for (value, expected) in values.iter().zip(expected.iter()) {
+            let view = value.as_raw_view();
+            let bytes = view.bytes().unwrap();
+
+            assert_eq!(view.ty, value.ty());
+            assert_eq!(bytes, *expected);
+            assert_eq!(unsafe { view.data.bytes.len }, expected.len());
+        }
+
+        // The view points into the value instead of copying it.
+        let value = Variant::from("Hello");
+        let string = match &value {
+            Variant::String(string) => string,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            value.as_raw_view().bytes().unwrap().as_ptr(),
+            string.as_ptr()
+        );
+
+        assert_eq!(Variant::Bool(true).as_raw_view().bytes(), None);
+    }
+
+    #[test]
+    fn unreadable_blob() {
+        let value = Variant::BinaryString(BinaryString::from_blob(crate::blob::unreadable_blob(
+            b"gone",
+        )));
+
+        assert!(value.try_as_raw_view().is_err());
+    }
+
+    #[test]
+    fn sequences() {
+        let colors = ColorSequence {
+            keypoints: vec![
+                ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 0.0, 0.0)),
+                ColorSequenceKeypoint::new(1.0, Color3::new(0.0, 0.0, 1.0)),
+            ],
+        };
+        let value = Variant::ColorSequence(colors.clone());
+        let view = value.as_raw_view();
+        assert_eq!(view.ty, VariantType::ColorSequence);
+        let keypoints = unsafe { view.data.color_sequence.as_slice() };
+        assert_eq!(keypoints, colors.keypoints.as_slice());
+
+        let numbers = NumberSequence {
+            keypoints: vec![
+                NumberSequenceKeypoint::new(0.0, 5.0, 0.5),
+                NumberSequenceKeypoint::new(0.5, 1.0, 0.0),
+                NumberSequenceKeypoint::new(1.0, 2.0, 0.25),
+            ],
+        };
+        let value = Variant::NumberSequence(numbers.clone());
+        let view = value.as_raw_view();
+        assert_eq!(view.ty, VariantType::NumberSequence);
+        let keypoints = unsafe { view.data.number_sequence.as_slice() };
+        assert_eq!(keypoints, numbers.keypoints.as_slice());
+    }
+
+    #[test]
+    fn scalars() {
+        let axes = Axes::from_bits(0b101).unwrap();
+        assert_eq!(view!(Variant::Axes(axes), Axes, axes), 0b101);
+
+        let faces = Faces::from_bits(0b10_0011).unwrap();
+        assert_eq!(view!(Variant::Faces(faces), Faces, faces), 0b10_0011);
+
+        assert!(view!(Variant::Bool(true), Bool, boolean));
+
+        let brick_color = BrickColor::from_number(194).unwrap();
+        assert_eq!(
+            view!(Variant::BrickColor(brick_color), BrickColor, brick_color),
+            194
+        );
+
+        let value = Variant::Enum(Enum::from_u32(7));
+        assert_eq!(view!(value, Enum, enum_value), 7);
+
+        assert_eq!(view!(Variant::Float32(0.5), Float32, float32), 0.5);
+        assert_eq!(view!(Variant::Float64(-2.25), Float64, float64), -2.25);
+        assert_eq!(view!(Variant::Int32(-12), Int32, int32), -12);
+        assert_eq!(view!(Variant::Int64(1 << 40), Int64, int64), 1 << 40);
+    }
+
+    #[test]
+    fn plain_structs() {
+        let cframe = CFrame::new(
+            Vector3::new(1.0, 2.0, 3.0),
+            Matrix3 {
+                x: Vector3::new(0.0, 0.0, 1.0),
+                y: Vector3::new(0.0, 1.0, 0.0),
+                z: Vector3::new(-1.0, 0.0, 0.0),
+            },
+        );
+        assert_eq!(view!(Variant::CFrame(cframe), CFrame, cframe), cframe);
+
+        let color = Color3::new(0.25, 0.5, 0.75);
+        assert_eq!(view!(Variant::Color3(color), Color3, color3), color);
+
+        let color = Color3uint8::new(10, 20, 30);
+        assert_eq!(
+            view!(Variant::Color3uint8(color), Color3uint8, color3uint8),
+            color
+        );
+
+        let range = NumberRange::new(1.0, 4.0);
+        assert_eq!(
+            view!(Variant::NumberRange(range), NumberRange, number_range),
+            range
+        );
+
+        let ray = Ray::new(Vector3::new(0.0, 10.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        assert_eq!(view!(Variant::Ray(ray), Ray, ray), ray);
+
+        let rect = Rect::new(Vector2::new(0.0, 0.0), Vector2::new(16.0, 9.0));
+        assert_eq!(view!(Variant::Rect(rect), Rect, rect), rect);
+
+        let region = Region3::new(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0));
+        assert_eq!(view!(Variant::Region3(region), Region3, region3), region);
+
+        let region = Region3int16::new(Vector3int16::new(-4, -5, -6), Vector3int16::new(4, 5, 6));
+        assert_eq!(
+            view!(Variant::Region3int16(region), Region3int16, region3int16),
+            region
+        );
+
+        let udim = UDim::new(0.5, -10);
+        assert_eq!(view!(Variant::UDim(udim), UDim, udim), udim);
+
+        let udim2 = UDim2::new(UDim::new(1.0, 0), UDim::new(0.0, 36));
+        assert_eq!(view!(Variant::UDim2(udim2), UDim2, udim2), udim2);
+
+        let vector = Vector2::new(-3.0, 8.5);
+        assert_eq!(view!(Variant::Vector2(vector), Vector2, vector2), vector);
+
+        let vector = Vector2int16::new(-3, 8);
+        assert_eq!(
+            view!(Variant::Vector2int16(vector), Vector2int16, vector2int16),
+            vector
+        );
+
+        let vector = Vector3::new(1.0, -2.0, 3.5);
+        assert_eq!(view!(Variant::Vector3(vector), Vector3, vector3), vector);
+
+        let vector = Vector3int16::new(1, -2, 3);
+        assert_eq!(
+            view!(Variant::Vector3int16(vector), Vector3int16, vector3int16),
+            vector
+        );
+    }
+
+    #[test]
+    fn physical_properties() {
+        let value = Variant::PhysicalProperties(PhysicalProperties::Default);
+        let raw = view!(value, PhysicalProperties, physical_properties);
+        assert!(!raw.custom);
+        assert_eq!(raw.properties.density, 0.0);
+
+        let custom = CustomPhysicalProperties {
+            density: 0.7,
+            friction: 0.3,
+            elasticity: 0.5,
+            friction_weight: 1.0,
+            elasticity_weight: 1.0,
+        };
+        let value = Variant::PhysicalProperties(PhysicalProperties::Custom(custom));
+        let raw = view!(value, PhysicalProperties, physical_properties);
+        assert!(raw.custom);
+        assert_eq!(raw.properties, custom);
+    }
+
+    #[test]
+    fn optional_cframe() {
+        let raw = view!(
+            Variant::OptionalCFrame(None),
+            OptionalCFrame,
+            optional_cframe
+        );
+        assert!(!raw.present);
+        assert_eq!(raw.cframe.position, Vector3::new(0.0, 0.0, 0.0));
+
+        let cframe = CFrame::new(Vector3::new(0.0, 5.0, 0.0), Matrix3::identity());
+        let raw = view!(
+            Variant::OptionalCFrame(Some(cframe)),
+            OptionalCFrame,
+            optional_cframe
+        );
+        assert!(raw.present);
+        assert_eq!(raw.cframe, cframe);
+    }
+
+    #[test]
+    fn referents() {
+        let none = view!(Variant::Ref(Ref::none()), Ref, referent);
+        assert_eq!(none, [0; 16]);
+
+        let referent = Ref::new();
+        let bytes = view!(Variant::Ref(referent), Ref, referent);
+        assert_eq!(
+            format!("{:032x}", u128::from_le_bytes(bytes)),
+            referent.to_string()
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Decodes a directory of model files into one dom, the way build tools put a
+//! place together from a tree of fragments.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use fs_err as fs;
+use rbx_dom_weak::{types::Ref, InstanceBuilder, WeakDom};
+
+use crate::ModelKind;
+
+/// Options for [`assemble_from_dir`].
+#[derive(Debug, Clone, Default)]
+pub struct AssembleOptions {
+    stop_on_error: bool,
+}
+
+impl AssembleOptions {
+    pub fn new() -> Self {
+        AssembleOptions {
+            stop_on_error: false,
+        }
+    }
+
+    /// Determines whether assembly stops at the first file that can't be
+    /// decoded. Otherwise, the file is recorded as a failure and the rest of
+    /// the directory is still read.
+    ///
+    /// This is off by default.
+    pub fn stop_on_error(self, stop_on_error: bool) -> Self {
+        AssembleOptions { stop_on_error }
+    }
+}
+
+/// What happened to each file that [`assemble_from_dir`] found. Paths are
+/// relative to the directory that was assembled.
+#[derive(Debug, Default)]
+pub struct AssembleReport {
+    /// Every file that was decoded, in the order they were read.
+    pub decoded: Vec<DecodedFile>,
+
+    /// Every file or directory that couldn't be read. Nothing from a file
+    /// that failed is in the dom.
+    pub failures: Vec<FailedFile>,
+
+    /// Files that were left alone because their extension isn't one of a
+    /// model or place.
+    pub skipped: Vec<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct DecodedFile {
+    pub path: PathBuf,
+
+    /// The format of the file, which comes from its contents rather than its
+    /// extension.
+    pub format: ModelKind,
+
+    /// The file's top-level instances, as they are in the dom now.
+    pub roots: Vec<Ref>,
+
+    /// Problems that the decoder worked around.
+    pub diagnostics: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct FailedFile {
+    pub path: PathBuf,
+    pub error: anyhow::Error,
+}
+
+/// Decodes every model and place file in a directory and its subdirectories
+/// into `dom`.
+///
+/// The top-level instances of the files directly in `path` are put under
+/// `parent`, and each subdirectory becomes a Folder with the same name, holding
+/// the instances from the files inside of it. Entries are read in order of
+/// their names, so the same directory always gives the same tree.
+///
+/// Each file is decoded on its own, so Ref properties only point to instances
+/// from the same file, even when two files use the same referents.
+pub fn assemble_from_dir(
+    path: &Path,
+    dom: &mut WeakDom,
+    parent: Ref,
+    options: &AssembleOptions,
+) -> AssembleReport {
+    let mut report = AssembleReport::default();
+    assemble_dir(path, Path::new(""), dom, parent, options, &mut report);
+    report
+}
+
+/// Assembles the directory at `relative` within `root`, returning false if
+/// assembly should stop.
+fn assemble_dir(
+    root: &Path,
+    relative: &Path,
+    dom: &mut WeakDom,
+    parent: Ref,
+    options: &AssembleOptions,
+    report: &mut AssembleReport,
+) -> bool {
+    let entries = match read_dir_sorted(&root.join(relative)) {
+        Ok(entries) => entries,
+        Err(error) => {
+            report.failures.push(FailedFile {
+                path: relative.to_owned(),
+                error,
+            });
+            return !options.stop_on_error;
+        }
+    };
+
+    for (name, is_dir) in entries {
+        let path = relative.join(&name);
+
+        if is_dir {
+            let folder = InstanceBuilder::new("Folder").with_name(name);
+            let folder_ref = dom.insert(parent, folder);
+
+            if !assemble_dir(root, &path, dom, folder_ref, options, report) {
+                return false;
+            }
+        } else if ModelKind::from_path(&path).is_err() {
+            report.skipped.push(path);
+        } else {
+            match decode_file(&root.join(&path)) {
+                Ok((format, mut file_dom, diagnostics)) => {
+                    let roots = file_dom.root().children().to_vec();
+                    for &referent in &roots {
+                        file_dom.transfer(referent, dom, parent);
+                    }
+
+                    report.decoded.push(DecodedFile {
+                        path,
+                        format,
+                        roots,
+                        diagnostics,
+                    });
+                }
+                Err(error) => {
+                    report.failures.push(FailedFile { path, error });
+
+                    if options.stop_on_error {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    true
+}
+
+/// Lists the names of the entries in a directory, and whether each is a
+/// directory, sorted by name.
+fn read_dir_sorted(path: &Path) -> anyhow::Result<Vec<(String, bool)>> {
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|name| anyhow!("file name is not valid UTF-8: {:?}", name))?;
+
+        entries.push((name, entry.file_type()?.is_dir()));
+    }
+
+    entries.sort();
+    Ok(entries)
+}
+
+fn decode_file(path: &Path) -> anyhow::Result<(ModelKind, WeakDom, Vec<String>)> {
+    let contents = fs::read(path)?;
+    let format = ModelKind::from_contents(&contents)
+        .ok_or_else(|| anyhow!("not a Roblox model or place file: {}", path.display()))?;
+
+    let (dom, diagnostics) = match format {
+        ModelKind::Xml => {
+            let options = rbx_xml::DecodeOptions::new()
+                .property_behavior(rbx_xml::DecodePropertyBehavior::ReadUnknown);
+            let (dom, summary) = rbx_xml::from_reader_with_summary(contents.as_slice(), options)?;
+
+            let diagnostics = summary.diagnostics().iter().map(ToString::to_string);
+            (dom, diagnostics.collect())
+        }
+
+        ModelKind::Binary => {
+            let options = rbx_binary::DecodeOptions::new();
+            let (dom, summary) =
+                rbx_binary::from_reader_with_summary(contents.as_slice(), options)?;
+
+            let diagnostics = summary.diagnostics().iter().map(ToString::to_string);
+            (dom, diagnostics.collect())
+        }
+    };
+
+    Ok((format, dom, diagnostics))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::types::Variant;
+
+    /// A Model with an ObjectValue that points at a Part next to it, using
+    /// the same referents in every file it's written to.
+    static CRATE_XML: &str = r#"<roblox version="4">
+        <Item class="Model" referent="RBX0">
+            <Properties>
+                <string name="Name">Crate</string>
+            </Properties>
+            <Item class="Part" referent="RBX1">
+                <Properties>
+                    <string name="Name">Lid</string>
+                </Properties>
+            </Item>
+            <Item class="ObjectValue" referent="RBX2">
+                <Properties>
+                    <string name="Name">Target</string>
+                    <Ref name="Value">RBX1</Ref>
+                </Properties>
+            </Item>
+        </Item>
+    </roblox>"#;
+
+    fn baseplate_binary() -> Vec<u8> {
+        let dom = WeakDom::new(InstanceBuilder::new("Part").with_name("Baseplate"));
+        let mut output = Vec::new();
+        rbx_binary::to_writer_default(&mut output, &dom, &[dom.root_ref()]).unwrap();
+        output
+    }
+
+    /// Lays out a project with both formats, a file in one format that's named
+    /// like the other, a corrupt file, and a file that isn't a model.
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+
+        fs::create_dir_all(path.join("Workspace/Props")).unwrap();
+        fs::create_dir_all(path.join("ReplicatedStorage")).unwrap();
+
+        fs::write(path.join("README.md"), "# My Place").unwrap();
+        fs::write(path.join("Workspace/Baseplate.rbxm"), baseplate_binary()).unwrap();
+        fs::write(path.join("Workspace/Props/Crate.rbxmx"), CRATE_XML).unwrap();
+        fs::write(path.join("ReplicatedStorage/Crate.rbxm"), CRATE_XML).unwrap();
+        fs::write(
+            path.join("ReplicatedStorage/Broken.rbxm"),
+            b"<roblox!\x89\xff\x0d\x0a\x1a\x0a\x00\x00",
+        )
+        .unwrap();
+
+        dir
+    }
+
+    fn full_names(dom: &WeakDom) -> Vec<String> {
+        dom.descendants(dom.root_ref())
+            .skip(1)
+            .map(|instance| dom.full_name(instance.referent()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn mixed_formats() {
+        let dir = project();
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel").with_name("Game"));
+        let root_ref = dom.root_ref();
+
+        let report = assemble_from_dir(dir.path(), &mut dom, root_ref, &AssembleOptions::new());
+
+        let decoded: Vec<_> = report
+            .decoded
+            .iter()
+            .map(|file| (file.path.as_path(), file.format, file.roots.len()))
+            .collect();
+        assert_eq!(
+            decoded,
+            [
+                (Path::new("ReplicatedStorage/Crate.rbxm"), ModelKind::Xml, 1),
+                (Path::new("Workspace/Baseplate.rbxm"), ModelKind::Binary, 1),
+                (Path::new("Workspace/Props/Crate.rbxmx"), ModelKind::Xml, 1),
+            ]
+        );
+        assert!(report
+            .decoded
+            .iter()
+            .all(|file| file.diagnostics.is_empty()));
+
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(
+            report.failures[0].path,
+            Path::new("ReplicatedStorage/Broken.rbxm")
+        );
+        assert_eq!(report.skipped, [Path::new("README.md")]);
+
+        assert_eq!(
+            full_names(&dom),
+            [
+                "Game.ReplicatedStorage",
+                "Game.ReplicatedStorage.Crate",
+                "Game.ReplicatedStorage.Crate.Lid",
+                "Game.ReplicatedStorage.Crate.Target",
+                "Game.Workspace",
+                "Game.Workspace.Baseplate",
+                "Game.Workspace.Props",
+                "Game.Workspace.Props.Crate",
+                "Game.Workspace.Props.Crate.Lid",
+                "Game.Workspace.Props.Crate.Target",
+            ]
+        );
+
+        // Both crates use the same referents, but each ObjectValue points to
+        // the Lid from its own file.
+        for file in [&report.decoded[0], &report.decoded[2]] {
+            let model = dom.get_by_ref(file.roots[0]).unwrap();
+            let (lid_ref, target_ref) = (model.children()[0], model.children()[1]);
+            let target = dom.get_by_ref(target_ref).unwrap();
+
+            assert_eq!(target.properties.get("Value"), Some(&Variant::Ref(lid_ref)));
+        }
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let dir = project();
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel").with_name("Game"));
+        let root_ref = dom.root_ref();
+
+        let options = AssembleOptions::new().stop_on_error(true);
+        let report = assemble_from_dir(dir.path(), &mut dom, root_ref, &options);
+
+        // Broken.rbxm is the first model file, so nothing is decoded.
+        assert!(report.decoded.is_empty());
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(full_names(&dom), ["Game.ReplicatedStorage"]);
+    }
+}
@@ -1,11 +1,16 @@
+mod assemble;
+
 use std::io::{self, BufReader, BufWriter};
 use std::path::{Path, PathBuf};
 use std::process;
 
 use anyhow::{anyhow, bail, Context};
 use fs_err::File;
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
 use structopt::StructOpt;
 
+use crate::assemble::{assemble_from_dir, AssembleOptions};
+
 #[derive(Debug, StructOpt)]
 struct Options {
     #[structopt(subcommand)]
@@ -19,6 +24,22 @@ enum Subcommand {
 
     /// View a binary file as an undefined text representation.
     ViewBinary { input: PathBuf },
+
+    /// Show the format, metadata, and top-level instances of a model or place
+    /// file without decoding the rest of it.
+    Peek { input: PathBuf },
+
+    /// Decode every model and place file in a directory into one file, with a
+    /// Folder for each subdirectory.
+    Assemble {
+        input: PathBuf,
+        output: PathBuf,
+
+        /// Stop at the first file that can't be decoded, instead of reporting
+        /// every one of them.
+        #[structopt(long)]
+        stop_on_error: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,12 +60,37 @@ impl ModelKind {
             )),
         }
     }
+
+    /// Tells which format a file is in from the start of its contents, no
+    /// matter what it's named.
+    fn from_contents(contents: &[u8]) -> Option<ModelKind> {
+        if contents.starts_with(b"<roblox!") {
+            return Some(ModelKind::Binary);
+        }
+
+        let contents = contents.strip_prefix(b"\xef\xbb\xbf").unwrap_or(contents);
+        let start = contents
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())?;
+
+        if contents[start..].starts_with(b"<roblox") || contents[start..].starts_with(b"<?xml") {
+            Some(ModelKind::Xml)
+        } else {
+            None
+        }
+    }
 }
 
 fn run(options: Options) -> anyhow::Result<()> {
     match options.subcommand {
         Subcommand::Convert { input, output } => convert(&input, &output)?,
         Subcommand::ViewBinary { input } => view_binary(&input)?,
+        Subcommand::Peek { input } => peek(&input)?,
+        Subcommand::Assemble {
+            input,
+            output,
+            stop_on_error,
+        } => assemble(&input, &output, stop_on_error)?,
     }
 
     Ok(())
@@ -109,6 +155,101 @@ fn view_binary(input_path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn peek(input_path: &Path) -> anyhow::Result<()> {
+    let input_kind = ModelKind::from_path(input_path)?;
+    let input_file = BufReader::new(File::open(input_path)?);
+
+    let (metadata, top_level) = match input_kind {
+        ModelKind::Xml => {
+            let info = rbx_xml::peek(input_file)
+                .with_context(|| format!("Failed to read {}", input_path.display()))?;
+
+            (info.metadata().clone(), info.top_level().to_vec())
+        }
+
+        ModelKind::Binary => {
+            let info = rbx_binary::peek(input_file)
+                .with_context(|| format!("Failed to read {}", input_path.display()))?;
+
+            (info.metadata().clone(), info.top_level().to_vec())
+        }
+    };
+
+    println!("Format: {:?}", input_kind);
+
+    let mut metadata: Vec<_> = metadata.into_iter().collect();
+    metadata.sort();
+
+    for (key, value) in metadata {
+        println!("Metadata: {} = {}", key, value);
+    }
+
+    for (class_name, name) in top_level {
+        println!("{} {:?}", class_name, name);
+    }
+
+    Ok(())
+}
+
+fn assemble(input_path: &Path, output_path: &Path, stop_on_error: bool) -> anyhow::Result<()> {
+    let output_kind = ModelKind::from_path(output_path)?;
+
+    let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+    let root_ref = dom.root_ref();
+
+    let options = AssembleOptions::new().stop_on_error(stop_on_error);
+    let report = assemble_from_dir(input_path, &mut dom, root_ref, &options);
+
+    for file in &report.decoded {
+        println!(
+            "Decoded {} ({:?}, {} instances)",
+            file.path.display(),
+            file.format,
+            file.roots.len()
+        );
+
+        for diagnostic in &file.diagnostics {
+            println!("  {}", diagnostic);
+        }
+    }
+
+    for path in &report.skipped {
+        println!("Skipped {}", path.display());
+    }
+
+    for failure in &report.failures {
+        eprintln!(
+            "Failed to read {}: {:?}",
+            failure.path.display(),
+            failure.error
+        );
+    }
+
+    if !report.failures.is_empty() {
+        bail!("{} files could not be read", report.failures.len());
+    }
+
+    let output_file = BufWriter::new(File::create(output_path)?);
+    let root_ids = dom.root().children();
+
+    match output_kind {
+        ModelKind::Xml => {
+            let options = rbx_xml::EncodeOptions::new()
+                .property_behavior(rbx_xml::EncodePropertyBehavior::WriteUnknown);
+
+            rbx_xml::to_writer(output_file, &dom, root_ids, options)
+                .with_context(|| format!("Failed to write {}", output_path.display()))?;
+        }
+
+        ModelKind::Binary => {
+            rbx_binary::to_writer_default(output_file, &dom, root_ids)
+                .with_context(|| format!("Failed to write {}", output_path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
 fn main() {
     let options = Options::from_args();
 
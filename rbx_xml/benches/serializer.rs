@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use rbx_dom_weak::{
+    types::{CFrame, Color3, Matrix3, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+/// Builds a dom full of parts, whose properties are almost entirely numbers.
+fn parts(count: usize) -> WeakDom {
+    let parts = (0..count).map(|i| {
+        let offset = i as f32 * 0.37;
+
+        InstanceBuilder::new("Part")
+            .with_name(format!("Part {}", i))
+            .with_property("Anchored", true)
+            .with_property(
+                "CFrame",
+                CFrame::new(
+                    Vector3::new(offset, offset * 2.5, -offset),
+                    Matrix3 {
+                        x: Vector3::new(0.70710677, 0.0, -0.70710677),
+                        y: Vector3::new(0.0, 1.0, 0.0),
+                        z: Vector3::new(0.70710677, 0.0, 0.70710677),
+                    },
+                ),
+            )
+            .with_property("Color", Color3::new(0.64, 0.63, 0.65))
+            .with_property("Size", Vector3::new(4.0, 1.2, offset + 2.0))
+            .with_property("Transparency", offset / 1000.0)
+            .with_property("Velocity", Vector3::new(0.0, -9.81, 0.0))
+    });
+
+    WeakDom::new(InstanceBuilder::new("Model").with_children(parts))
+}
+
+/// Benchmarks encoding a dom of `count` parts. Large doms take long enough to
+/// encode that criterion's default of 100 samples isn't practical for them.
+fn bench_parts(c: &mut Criterion, name: &str, count: usize, sample_size: usize) {
+    let tree = parts(count);
+    let root_ref = tree.root_ref();
+
+    let mut buffer = Vec::new();
+
+    // Encode once into the buffer to pre-size it.
+    rbx_xml::to_writer_default(&mut buffer, &tree, &[root_ref]).unwrap();
+    let len = buffer.len();
+    buffer.clear();
+
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(len as u64));
+    group.sample_size(sample_size);
+    group.bench_function("to_writer_default", |b| {
+        b.iter(|| {
+            rbx_xml::to_writer_default(&mut buffer, &tree, &[root_ref]).unwrap();
+            buffer.clear();
+        });
+    });
+    group.finish();
+}
+
+pub fn ser_parts_10000(c: &mut Criterion) {
+    bench_parts(c, "Serialize 10,000 Parts", 10_000, 100);
+}
+
+pub fn ser_parts_100000(c: &mut Criterion) {
+    bench_parts(c, "Serialize 100,000 Parts", 100_000, 10);
+}
+
+criterion_group!(serializer, ser_parts_10000, ser_parts_100000);
+criterion_main!(serializer);
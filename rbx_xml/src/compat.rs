@@ -2,18 +2,156 @@
 
 #![allow(unused)]
 
-use rbx_dom_weak::types::{Variant, VariantType};
+use rbx_dom_weak::types::{Color3, Color3uint8, Variant, VariantType, Vector3, Vector3int16};
 
 pub enum TodoValueConversionType {
     Converted(Variant),
+
+    /// The value was converted to a narrower type, but some of it was outside
+    /// of that type's range, so it was clamped to fit.
+    Clamped(Variant),
+
     Unnecessary,
     Failed,
 }
 
 pub trait TodoValueConversion {
+    /// Converts a value to a wider type that can hold every value of its own
+    /// type, like `Vector3int16` to `Vector3`. These conversions never lose
+    /// anything, so they're safe to apply whenever a file has an older, narrow
+    /// type than the reflection database expects.
+    fn try_convert_ref(&self, target_type: VariantType) -> TodoValueConversionType;
+
+    /// Like [`try_convert_ref`][TodoValueConversion::try_convert_ref], but
+    /// also converts values back to the narrow types that some properties are
+    /// serialized as, like `Color3` to `Color3uint8` for `BasePart.Color`.
+    fn try_convert_or_narrow_ref(&self, target_type: VariantType) -> TodoValueConversionType;
+}
+
+impl TodoValueConversion for Variant {
     fn try_convert_ref(&self, target_type: VariantType) -> TodoValueConversionType {
-        TodoValueConversionType::Unnecessary
+        match (self, target_type) {
+            (Variant::Vector3int16(value), VariantType::Vector3) => {
+                TodoValueConversionType::Converted(
+                    Vector3::new(value.x.into(), value.y.into(), value.z.into()).into(),
+                )
+            }
+            (Variant::Color3uint8(value), VariantType::Color3) => {
+                TodoValueConversionType::Converted(Color3::from(*value).into())
+            }
+            _ => TodoValueConversionType::Unnecessary,
+        }
+    }
+
+    fn try_convert_or_narrow_ref(&self, target_type: VariantType) -> TodoValueConversionType {
+        let (narrowed, clamped) = match (self, target_type) {
+            (Variant::Vector3(value), VariantType::Vector3int16) => {
+                let (x, x_clamped) = narrow_to_i16(value.x);
+                let (y, y_clamped) = narrow_to_i16(value.y);
+                let (z, z_clamped) = narrow_to_i16(value.z);
+
+                (
+                    Vector3int16::new(x, y, z).into(),
+                    x_clamped || y_clamped || z_clamped,
+                )
+            }
+            (Variant::Color3(value), VariantType::Color3uint8) => {
+                let in_range = |component: f32| (0.0..=1.0).contains(&component);
+                let clamped = !(in_range(value.r) && in_range(value.g) && in_range(value.b));
+
+                (Color3uint8::from(*value).into(), clamped)
+            }
+            _ => return self.try_convert_ref(target_type),
+        };
+
+        if clamped {
+            TodoValueConversionType::Clamped(narrowed)
+        } else {
+            TodoValueConversionType::Converted(narrowed)
+        }
+    }
+}
+
+/// Rounds a float to the nearest `i16`, telling whether it had to be clamped
+/// to get there. NaN becomes zero, which counts as clamping.
+fn narrow_to_i16(value: f32) -> (i16, bool) {
+    let rounded = value.round();
+
+    if rounded.is_nan() {
+        (0, true)
+    } else if rounded < f32::from(i16::MIN) {
+        (i16::MIN, true)
+    } else if rounded > f32::from(i16::MAX) {
+        (i16::MAX, true)
+    } else {
+        (rounded as i16, false)
     }
 }
 
-impl TodoValueConversion for Variant {}
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn narrow(value: Variant, target_type: VariantType) -> (Variant, bool) {
+        match value.try_convert_or_narrow_ref(target_type) {
+            TodoValueConversionType::Converted(value) => (value, false),
+            TodoValueConversionType::Clamped(value) => (value, true),
+            _ => panic!("{:?} wasn't converted to {:?}", value, target_type),
+        }
+    }
+
+    #[test]
+    fn vector3int16_round_trip() {
+        let original: Variant = Vector3int16::new(-32768, 0, 32767).into();
+
+        let widened = match original.try_convert_ref(VariantType::Vector3) {
+            TodoValueConversionType::Converted(value) => value,
+            _ => panic!("Vector3int16 wasn't widened"),
+        };
+        assert_eq!(widened, Vector3::new(-32768.0, 0.0, 32767.0).into());
+
+        assert_eq!(
+            narrow(widened, VariantType::Vector3int16),
+            (original, false)
+        );
+    }
+
+    #[test]
+    fn color3uint8_round_trip() {
+        let original: Variant = Color3uint8::new(0, 128, 255).into();
+
+        let widened = match original.try_convert_ref(VariantType::Color3) {
+            TodoValueConversionType::Converted(value) => value,
+            _ => panic!("Color3uint8 wasn't widened"),
+        };
+        assert_eq!(widened, Color3::new(0.0, 128.0 / 255.0, 1.0).into());
+
+        assert_eq!(narrow(widened, VariantType::Color3uint8), (original, false));
+    }
+
+    #[test]
+    fn narrowing_clamps() {
+        assert_eq!(
+            narrow(
+                Vector3::new(40000.0, -2.4, f32::NAN).into(),
+                VariantType::Vector3int16
+            ),
+            (Vector3int16::new(32767, -2, 0).into(), true)
+        );
+
+        assert_eq!(
+            narrow(Color3::new(1.5, 0.5, -1.0).into(), VariantType::Color3uint8),
+            (Color3uint8::new(255, 128, 0).into(), true)
+        );
+    }
+
+    #[test]
+    fn narrowing_only_happens_when_asked_for() {
+        let color: Variant = Color3::new(0.5, 0.5, 0.5).into();
+
+        assert!(matches!(
+            color.try_convert_ref(VariantType::Color3uint8),
+            TodoValueConversionType::Unnecessary
+        ));
+    }
+}
@@ -1,6 +1,12 @@
-use std::io::{Read, Write};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
 
-use rbx_reflection::{PropertyDescriptor, PropertyKind, PropertySerialization};
+use rbx_dom_weak::types::{SharedString, VariantType};
+use rbx_reflection::{
+    ClassDescriptor, DataType, PropertyDescriptor, PropertyKind, PropertySerialization, PropertyTag,
+};
 
 use crate::{
     deserializer_core::XmlEventReader,
@@ -39,6 +45,146 @@ pub trait XmlType: Sized {
     }
 }
 
+/// Properties of the root instance that can be written to a place as `<Meta>`
+/// elements with the same name, and that are read back onto the root when
+/// decoding. Each of them holds a BinaryString, which is written as base64.
+pub const ROOT_META_PROPERTIES: &[&str] = &["AttributesSerialize", "Tags"];
+
+/// Properties that the reflection database says don't serialize, but that old
+/// files still have. rbx_xml reads and writes these anyway so that re-saving an
+/// old file doesn't lose them. Since only properties present on an instance
+/// are written, they only show up in files whose instances already had them.
+pub const PRESERVED_LEGACY_PROPERTIES: &[&str] = &["RobloxLocked"];
+
+/// The value of the `version` attribute on `<roblox>` that Roblox writes
+/// today, and that rbx_xml writes unless told otherwise.
+pub const DOC_VERSION: u32 = 4;
+
+/// The oldest document version rbx_xml reads. Version 3 files are laid out
+/// the same way as version 4 ones as far as rbx_xml can tell.
+pub const OLDEST_DOC_VERSION: u32 = 3;
+
+/// The `referent` attribute of an `<Item>`, or the contents of a `<Ref>`
+/// property that points to one. These are only meaningful within one
+/// document, and are kept apart from other strings so that a property name
+/// can't be passed where a referent belongs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct XmlReferent(String);
+
+impl XmlReferent {
+    pub fn new(referent: String) -> Self {
+        XmlReferent(referent)
+    }
+
+    /// The referent that rbx_xml writes for the `index`th instance it writes
+    /// to a document.
+    pub fn from_index(index: u32) -> Self {
+        XmlReferent(index.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for XmlReferent {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        output.write_str(&self.0)
+    }
+}
+
+/// Wraps a reader and, if asked to, builds a `LineIndex` of everything read
+/// through it.
+pub struct IndexingReader<R> {
+    inner: R,
+    line_index: Option<LineIndex>,
+}
+
+impl<R> IndexingReader<R> {
+    pub fn new(inner: R, build_index: bool) -> Self {
+        IndexingReader {
+            inner,
+            line_index: build_index.then(LineIndex::default),
+        }
+    }
+
+    pub fn line_index(&self) -> Option<&LineIndex> {
+        self.line_index.as_ref()
+    }
+}
+
+impl<R: Read> Read for IndexingReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+
+        if let Some(line_index) = &mut self.line_index {
+            line_index.extend(&buf[..len]);
+        }
+
+        Ok(len)
+    }
+}
+
+/// Remembers enough about the layout of a document to turn a position given
+/// by xml-rs, which counts lines and characters, back into a byte offset.
+#[derive(Debug, Default)]
+pub struct LineIndex {
+    /// The number of bytes indexed so far.
+    len: u64,
+
+    /// The byte offset of the start of every line after the first one.
+    line_starts: Vec<u64>,
+
+    /// The byte offset of every character that takes up more than one byte,
+    /// paired with how many extra bytes it takes up.
+    wide_chars: Vec<(u64, u64)>,
+}
+
+impl LineIndex {
+    fn extend(&mut self, data: &[u8]) {
+        let offset = self.len;
+        self.len += data.len() as u64;
+
+        for (index, &byte) in data.iter().enumerate() {
+            let at = offset + index as u64;
+
+            match byte {
+                b'\n' => self.line_starts.push(at + 1),
+                0xf0..=0xff => self.wide_chars.push((at, 3)),
+                0xe0..=0xef => self.wide_chars.push((at, 2)),
+                0xc0..=0xdf => self.wide_chars.push((at, 1)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Converts a 0-based line and column, in characters, into a byte offset.
+    pub fn byte_offset(&self, line: u64, column: u64) -> u64 {
+        let line_start = match line.checked_sub(1) {
+            Some(index) => self.line_starts[index as usize],
+            None => 0,
+        };
+
+        let first_wide_char = self.wide_chars.partition_point(|&(at, _)| at < line_start);
+        let mut offset = line_start + column;
+
+        for &(at, extra) in &self.wide_chars[first_wide_char..] {
+            if at >= offset {
+                break;
+            }
+
+            offset += extra;
+        }
+
+        offset
+    }
+}
+
 pub fn find_canonical_property_descriptor(
     class_name: &str,
     property_name: &str,
@@ -53,6 +199,89 @@ pub fn find_serialized_property_descriptor(
     find_property_descriptors(class_name, property_name).map(|(_canonical, serialized)| serialized)
 }
 
+/// Finds the property descriptor with exactly the given name on the given class
+/// or one of its superclasses. Unlike the other lookups here, aliases are
+/// returned as-is instead of being resolved to their canonical property.
+pub fn find_exact_property_descriptor(
+    class_name: &str,
+    property_name: &str,
+) -> Option<&'static PropertyDescriptor<'static>> {
+    class_and_superclasses(class_name).find_map(|class| class.properties.get(property_name))
+}
+
+/// Tells whether the given property on the given class used to be a BrickColor
+/// and is now an alias for a Color3 property, like `BasePart.BrickColor`.
+pub fn is_legacy_brick_color(class_name: &str, property_name: &str) -> bool {
+    match find_exact_property_descriptor(class_name, property_name) {
+        Some(descriptor) => {
+            matches!(descriptor.kind, PropertyKind::Alias { .. })
+                && matches!(
+                    descriptor.data_type,
+                    DataType::Value(VariantType::BrickColor)
+                )
+        }
+        None => false,
+    }
+}
+
+/// Tells whether the reflection database lists the given property on the
+/// given class or one of its superclasses, but says that it isn't serialized
+/// anymore, like `BasePart.Elasticity`.
+pub fn is_unserialized_property(class_name: &str, property_name: &str) -> bool {
+    match find_exact_property_descriptor(class_name, property_name) {
+        Some(descriptor) => {
+            matches!(
+                descriptor.kind,
+                PropertyKind::Canonical {
+                    serialization: PropertySerialization::DoesNotSerialize
+                }
+            ) && !PRESERVED_LEGACY_PROPERTIES.contains(&descriptor.name.as_ref())
+        }
+        None => false,
+    }
+}
+
+/// Finds the name of the non-deprecated BrickColor property that used to hold
+/// the value of the given Color3 property, if there is one. This is the
+/// reverse of `is_legacy_brick_color`.
+pub fn find_legacy_brick_color_name(class_name: &str, property_name: &str) -> Option<&'static str> {
+    class_and_superclasses(class_name)
+        .flat_map(|class| class.properties.values())
+        .find(|descriptor| {
+            let is_alias = match &descriptor.kind {
+                PropertyKind::Alias { alias_for } => alias_for == property_name,
+                _ => false,
+            };
+
+            is_alias
+                && matches!(
+                    descriptor.data_type,
+                    DataType::Value(VariantType::BrickColor)
+                )
+                && !descriptor.tags.contains(&PropertyTag::Deprecated)
+        })
+        .map(|descriptor| descriptor.name.as_ref())
+}
+
+/// Iterates over the given class's descriptor followed by the descriptors of
+/// each of its superclasses.
+fn class_and_superclasses(
+    class_name: &str,
+) -> impl Iterator<Item = &'static ClassDescriptor<'static>> {
+    let database = rbx_reflection_database::get();
+    let mut next = database.classes.get(class_name);
+
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = current
+            .superclass
+            .as_ref()
+            .and_then(|superclass| database.classes.get(superclass.as_ref()));
+
+        Some(current)
+    })
+}
+
 /// Find both the canonical and serialized property descriptors for a given
 /// class and property name pair. These might be the same descriptor!
 fn find_property_descriptors(
@@ -81,6 +310,11 @@ fn find_property_descriptors(
                         return Some((property_descriptor, property_descriptor))
                     }
                     PropertySerialization::DoesNotSerialize => {
+                        if PRESERVED_LEGACY_PROPERTIES.contains(&property_descriptor.name.as_ref())
+                        {
+                            return Some((property_descriptor, property_descriptor));
+                        }
+
                         // FIXME: Is this the correct solution?
                         return None;
                     }
@@ -144,3 +378,19 @@ fn find_property_descriptors(
         }
     }
 }
+
+/// Computes the key that a SharedString is written with. Roblox Studio uses the
+/// base64-encoded MD5 hash of the string's contents, and some versions of
+/// Studio deduplicate SharedStrings incorrectly if the key is anything else.
+pub(crate) fn shared_string_key(value: &SharedString) -> Result<String, io::Error> {
+    let digest = match value.blob() {
+        Some(blob) => {
+            let mut context = md5::Context::new();
+            io::copy(&mut blob.open()?, &mut context)?;
+            context.compute()
+        }
+        None => md5::compute(value.data()),
+    };
+
+    Ok(base64::encode(&digest.0))
+}
@@ -1,34 +1,156 @@
-use std::{collections::HashMap, io::Read};
+use std::{
+    cell::Cell,
+    collections::{hash_map::Entry, HashMap},
+    convert::TryFrom,
+    io::Read,
+    ops::{Deref, DerefMut},
+    rc::Rc,
+    sync::Arc,
+};
 
+use indexmap::IndexMap;
 use log::trace;
 use rbx_dom_weak::{
-    types::{Ref, SharedString, Variant, VariantType},
+    types::{
+        BinaryString, BlobStore, BrickColor, Color3, ContentNormalizer, CountingReader, Enum, Ref,
+        RefProvider, SharedString, StringInterner, Variant, VariantType,
+    },
     InstanceBuilder, WeakDom,
 };
-use rbx_reflection::DataType;
+use rbx_reflection::{ClassFilter, DataType};
+use xml::common::TextPosition;
 
 use crate::{
     compat::{TodoValueConversion, TodoValueConversionType},
-    core::find_canonical_property_descriptor,
-    error::{DecodeError, DecodeErrorKind},
-    types::read_value_xml,
+    core::{
+        find_canonical_property_descriptor, is_legacy_brick_color, is_unserialized_property,
+        shared_string_key, IndexingReader, XmlReferent, DOC_VERSION, OLDEST_DOC_VERSION,
+        ROOT_META_PROPERTIES,
+    },
+    error::{DecodeError, DecodeErrorDetail, DecodeErrorKind},
+    legacy_elements::LegacyElement,
+    processing_instruction::ProcessingInstruction,
+    summary::{DecodeSummary, Diagnostic, MigratedEnumValue, SourceLocation},
+    types::{attributes_from_binary, read_value_xml, ATTRIBUTES_PROPERTY},
 };
 
-use crate::deserializer_core::{XmlEventReader, XmlReadEvent};
-
-pub fn decode_internal<R: Read>(source: R, options: DecodeOptions) -> Result<WeakDom, DecodeError> {
-    let mut tree = WeakDom::new(InstanceBuilder::new("DataModel"));
+use crate::deserializer_core::{
+    find_attribute, Base64Contents, ExtraEndTagFilter, XmlEventReader, XmlReadEvent,
+};
 
+pub fn decode_internal<R: Read>(
+    source: R,
+    mut options: DecodeOptions,
+) -> Result<(WeakDom, DecodeSummary), DecodeError> {
+    let mut tree = WeakDom::new(options.new_builder("DataModel"));
     let root_id = tree.root_ref();
 
-    let mut iterator = XmlEventReader::from_source(source);
-    let mut state = ParseState::new(&mut tree, options);
+    let (_roots, summary) = decode_into_internal(&mut tree, root_id, source, options)?;
+
+    Ok((tree, summary))
+}
+
+/// Decodes a document into an existing tree, inserting its top-level Items
+/// under `root_id` and returning their IDs. If decoding fails, anything that
+/// was inserted is removed again.
+pub fn decode_into_internal<R: Read>(
+    tree: &mut WeakDom,
+    root_id: Ref,
+    source: R,
+    options: DecodeOptions,
+) -> Result<(Vec<Ref>, DecodeSummary), DecodeError> {
+    phase_span!("decode", recover = options.recover);
+
+    let existing_children = tree
+        .get_by_ref(root_id)
+        .expect("cannot decode into parent that does not exist")
+        .children()
+        .len();
+
+    let indexing_reader = IndexingReader::new(source, options.source_locations);
+    let filter = ExtraEndTagFilter::new(CountingReader::new(indexing_reader));
+    let extra_end_tags = filter.removed();
+
+    let mut iterator = XmlEventReader::from_source(filter);
+    let mut state = ParseState::new(StateTree::Borrowed(tree), root_id, options, extra_end_tags);
+
+    let result = {
+        phase_span!("read_items");
+        deserialize_root(&mut iterator, &mut state, root_id)
+    };
+
+    sort_processing_instructions(&mut iterator, &mut state);
+
+    if result.is_ok() && !state.filtered_out.is_empty() {
+        // Recovering from an error may have removed some of these already.
+        for id in std::mem::take(&mut state.filtered_out) {
+            if state.tree.get_by_ref(id).is_some() {
+                state.tree.destroy(id);
+            }
+        }
+
+        state.forget_removed_instances();
+    }
+
+    let roots = state.tree.get_by_ref(root_id).unwrap().children()[existing_children..].to_vec();
 
-    deserialize_root(&mut iterator, &mut state, root_id)?;
-    apply_referent_rewrites(&mut state);
-    apply_shared_string_rewrites(&mut state);
+    let result = result.and_then(|_| apply_referent_rewrites(&mut state));
+    let result = result.and_then(|_| apply_shared_string_rewrites(&mut state));
 
-    Ok(tree)
+    if let Err(err) = result {
+        for id in roots {
+            state.tree.destroy(id);
+        }
+
+        return Err(err);
+    }
+
+    let counting_reader = iterator.source().get_ref();
+
+    // Recovering from an error may have removed some of these instances.
+    let source_locations = match counting_reader.get_ref().line_index() {
+        Some(line_index) => state
+            .item_positions
+            .iter()
+            .filter(|(id, _)| state.tree.get_by_ref(*id).is_some())
+            .map(|&(id, position)| {
+                let location = SourceLocation {
+                    line: position.row as usize + 1,
+                    column: position.column as usize + 1,
+                    byte_offset: line_index.byte_offset(position.row, position.column),
+                };
+
+                (id, location)
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let summary = DecodeSummary {
+        bytes_read: counting_reader.bytes_read(),
+        instance_count: state.instance_count,
+        property_count: state.property_count,
+        recovered: state.recovered,
+        diagnostics: state.diagnostics,
+        normalized_content: state
+            .content_normalizer
+            .map(ContentNormalizer::into_changes)
+            .unwrap_or_default(),
+        legacy_elements: state.legacy_elements,
+        migrated_enum_values: state.migrated_enum_values,
+        source_locations,
+        processing_instructions: state.processing_instructions,
+        version: state.version,
+    };
+
+    debug_event!(
+        bytes_read = summary.bytes_read,
+        instances = summary.instance_count,
+        properties = summary.property_count,
+        "decoded document"
+    );
+
+    Ok((roots, summary))
 }
 
 /// Describes the strategy that rbx_xml should use when deserializing
@@ -64,10 +186,53 @@ pub enum DecodePropertyBehavior {
     NoReflection,
 }
 
+/// Describes what rbx_xml should do with Ref properties that refer to an Item
+/// that isn't in the document being decoded.
+///
+/// When decoding a model into an existing tree with [`decode_into`], these
+/// are usually Refs to instances that were outside the model when it was
+/// saved.
+///
+/// [`decode_into`]: crate::decode_into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UnresolvedRefBehavior {
+    /// Sets the property to a null Ref. This is what Studio does when
+    /// inserting a model. This is the default.
+    SetNull,
+
+    /// Returns an error with the kind
+    /// [`DecodeErrorKind::UnresolvedRef`][crate::DecodeErrorKind::UnresolvedRef].
+    Error,
+}
+
 /// Options available for deserializing an XML-format model or place.
+///
+/// Unlike rbx_binary, rbx_xml has no option for string values that aren't
+/// valid UTF-8. An XML document declares its encoding, so text that doesn't
+/// match it means the document is malformed, and decoding it is always an
+/// error. Binary data in XML files is base64 encoded and never has this
+/// problem.
 #[derive(Debug, Clone)]
 pub struct DecodeOptions {
     property_behavior: DecodePropertyBehavior,
+    recover: bool,
+    recover_from_property_errors: bool,
+    intern_strings: bool,
+    intern_max_len: usize,
+    normalize_content: bool,
+    legacy_elements: bool,
+    class_filter: Option<ClassFilter>,
+    source_locations: bool,
+    blob_store: Option<Arc<dyn BlobStore>>,
+    blob_threshold: usize,
+    ref_provider: Option<Box<dyn RefProvider>>,
+    read_attributes: bool,
+    drop_unserialized_properties: bool,
+    unresolved_ref_behavior: UnresolvedRefBehavior,
+    migrate_enum_values: bool,
+    accept_newer_versions: bool,
+    preserve_missing_names: bool,
 }
 
 impl DecodeOptions {
@@ -76,45 +241,863 @@ impl DecodeOptions {
     pub fn new() -> Self {
         DecodeOptions {
             property_behavior: DecodePropertyBehavior::IgnoreUnknown,
+            recover: false,
+            recover_from_property_errors: false,
+            intern_strings: false,
+            intern_max_len: 1024,
+            normalize_content: false,
+            legacy_elements: false,
+            class_filter: None,
+            source_locations: false,
+            blob_store: None,
+            blob_threshold: 16 * 1024 * 1024,
+            ref_provider: None,
+            read_attributes: false,
+            drop_unserialized_properties: false,
+            unresolved_ref_behavior: UnresolvedRefBehavior::SetNull,
+            migrate_enum_values: true,
+            accept_newer_versions: false,
+            preserve_missing_names: false,
+        }
+    }
+
+    /// Determines how rbx_xml will deserialize properties, especially unknown
+    /// ones.
+    #[inline]
+    pub fn property_behavior(self, property_behavior: DecodePropertyBehavior) -> Self {
+        DecodeOptions {
+            property_behavior,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will try to salvage as much of a damaged
+    /// document as it can instead of returning an error.
+    ///
+    /// With this option set, an `Item` that can't be read is dropped along
+    /// with all of its descendants, and decoding continues with the next
+    /// `Item`. Every dropped `Item` is recorded as a diagnostic in the
+    /// [`DecodeSummary`][crate::DecodeSummary]. Errors that leave the XML
+    /// parser itself unusable, like malformed XML syntax, are still returned.
+    ///
+    /// This also accepts the extra `</Properties>` tag that some third-party
+    /// exporters write after an empty `<Properties/>` element, which is
+    /// otherwise an error.
+    ///
+    /// When the SharedString dictionary has two different entries with the
+    /// same key, which happens in some corrupted files, the first one is kept
+    /// and the second is recorded as a diagnostic. Without this option, that's
+    /// an error, since there's no way to tell which entry is right.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn recover(self, recover: bool) -> Self {
+        DecodeOptions { recover, ..self }
+    }
+
+    /// Determines whether rbx_xml will skip property values that can't be
+    /// read instead of returning an error.
+    ///
+    /// With this option set, a property whose value is malformed or has a
+    /// type rbx_xml doesn't know is left off of its instance, and the rest of
+    /// the instance and its children are decoded as usual. Every skipped
+    /// property is recorded as a diagnostic in the
+    /// [`DecodeSummary`][crate::DecodeSummary], along with the instance it was
+    /// on. Problems with the structure of the document, like an `Item`
+    /// without a class, are still returned as errors unless
+    /// [`recover`][Self::recover] is also set.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn recover_from_property_errors(self, recover_from_property_errors: bool) -> Self {
+        DecodeOptions {
+            recover_from_property_errors,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will deduplicate `String` and `Content`
+    /// property values as it decodes them.
+    ///
+    /// With this option set, every instance with the same string value, like
+    /// a material name or an asset URL, shares one copy of it instead of
+    /// holding its own. Mutating a shared `Content` value makes a copy first,
+    /// and a `String` value can only be replaced, so editing one instance
+    /// leaves the others alone.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn intern_strings(self, intern_strings: bool) -> Self {
+        DecodeOptions {
+            intern_strings,
+            ..self
+        }
+    }
+
+    /// Sets the length in bytes of the longest value that will be
+    /// deduplicated when [`intern_strings`][DecodeOptions::intern_strings] is
+    /// enabled.
+    ///
+    /// This is 1024 bytes by default.
+    #[inline]
+    pub fn intern_max_len(self, intern_max_len: usize) -> Self {
+        DecodeOptions {
+            intern_max_len,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will rewrite `Content` property values that
+    /// point to Roblox assets into the canonical `rbxassetid://<id>` form. See
+    /// [`Content::normalize`][rbx_dom_weak::types::Content::normalize] for
+    /// which URLs are rewritten.
+    ///
+    /// The original and rewritten form of every changed value are recorded in
+    /// [`DecodeSummary::normalized_content`][crate::DecodeSummary::normalized_content].
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn normalize_content(self, normalize_content: bool) -> Self {
+        DecodeOptions {
+            normalize_content,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will keep elements that it doesn't
+    /// understand when they're found directly inside `<roblox>` or an
+    /// `<Item>`, like the `<HashMap>` and `<Custom>` elements in places saved
+    /// before 2015.
+    ///
+    /// With this option set, each of these elements is kept as XML in
+    /// [`DecodeSummary::legacy_elements`][crate::DecodeSummary::legacy_elements].
+    /// Without it, the first one found is an error that names the element.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn legacy_elements(self, legacy_elements: bool) -> Self {
+        DecodeOptions {
+            legacy_elements,
+            ..self
+        }
+    }
+
+    /// Makes rbx_xml leave out every instance whose class isn't in the given
+    /// filter. The children of an instance that's left out take its place
+    /// under its parent, so a filter of only scripts still finds the scripts
+    /// inside of models. Ref properties that point to an instance that was
+    /// left out are set to none.
+    ///
+    /// ```
+    /// use rbx_reflection::ClassFilter;
+    /// use rbx_xml::DecodeOptions;
+    ///
+    /// let document = r#"
+    ///     <roblox version="4">
+    ///         <Item class="Model" referent="RBX0">
+    ///             <Item class="Part" referent="RBX1" />
+    ///             <Item class="Script" referent="RBX2" />
+    ///         </Item>
+    ///     </roblox>
+    /// "#;
+    ///
+    /// let database = rbx_reflection_database::get();
+    /// let options = DecodeOptions::new().class_filter(ClassFilter::geometry(database));
+    ///
+    /// let dom = rbx_xml::from_str(document, options).unwrap();
+    /// let classes: Vec<_> = dom
+    ///     .root()
+    ///     .children()
+    ///     .iter()
+    ///     .map(|referent| dom.get_by_ref(*referent).unwrap().class.as_str())
+    ///     .collect();
+    ///
+    /// assert_eq!(classes, ["Part"]);
+    /// ```
+    ///
+    /// By default, every instance is kept.
+    #[inline]
+    pub fn class_filter(self, class_filter: ClassFilter) -> Self {
+        DecodeOptions {
+            class_filter: Some(class_filter),
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will record where each instance's `<Item>`
+    /// tag starts in the document, so that tools can point back to the
+    /// source of a problem they find in the dom.
+    ///
+    /// With this option set, the line, column, and byte offset of every
+    /// decoded instance is recorded in
+    /// [`DecodeSummary::source_locations`][crate::DecodeSummary::source_locations].
+    /// When [`recover`][DecodeOptions::recover] removes an extra
+    /// `</Properties>` tag, the columns and byte offsets of anything later on
+    /// the same line come out short by the length of that tag.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn source_locations(self, source_locations: bool) -> Self {
+        DecodeOptions {
+            source_locations,
+            ..self
+        }
+    }
+
+    /// Makes rbx_xml keep `BinaryString` and `SharedString` values longer
+    /// than [`blob_threshold`][DecodeOptions::blob_threshold] in the given
+    /// store instead of in memory. Their contents are only read back when
+    /// something needs them, and both rbx_xml and rbx_binary encode them
+    /// straight from the store.
+    ///
+    /// The base64 text of each value is still read into memory, since it
+    /// arrives from the XML parser all at once, but it's decoded into the
+    /// store a piece at a time.
+    ///
+    /// By default, every value is kept in memory.
+    #[inline]
+    pub fn blob_store(self, blob_store: Arc<dyn BlobStore>) -> Self {
+        DecodeOptions {
+            blob_store: Some(blob_store),
+            ..self
+        }
+    }
+
+    /// Sets the decoded length in bytes above which values are kept in the
+    /// [`blob_store`][DecodeOptions::blob_store], if there is one.
+    ///
+    /// This is 16 MiB by default.
+    #[inline]
+    pub fn blob_threshold(self, blob_threshold: usize) -> Self {
+        DecodeOptions {
+            blob_threshold,
+            ..self
+        }
+    }
+
+    /// Sets where rbx_xml gets the `Ref` of each instance it creates, including
+    /// the `DataModel` at the root of the dom.
+    ///
+    /// Decoding the same file with a
+    /// [`SeededRefProvider`][rbx_dom_weak::types::SeededRefProvider] always
+    /// gives its instances the same Refs. The provider is cloned along with
+    /// these options, so every decode that uses a clone of the same options
+    /// starts from the same place in the provider's sequence.
+    ///
+    /// That also means decoding twice into the same dom with clones of the
+    /// same options gives the second set of instances Refs that are already
+    /// taken, which makes [`decode_into`][crate::decode_into] return a
+    /// [`DuplicateRef`][crate::DecodeErrorKind::DuplicateRef] error without
+    /// changing the dom. Use a provider with a different seed for each decode
+    /// into one dom.
+    ///
+    /// By default, Refs are random.
+    #[inline]
+    pub fn ref_provider<P: RefProvider + 'static>(self, ref_provider: P) -> Self {
+        DecodeOptions {
+            ref_provider: Some(Box::new(ref_provider)),
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will decode the `AttributesSerialize`
+    /// property into a [`Variant::Attributes`] value, so that attributes can
+    /// be read and changed without knowing their binary format.
+    ///
+    /// Contents that can't be decoded, like attributes with a type that isn't
+    /// supported yet, are kept as a `BinaryString`, as are contents that
+    /// wouldn't be written back exactly the same way. rbx_xml writes
+    /// `Attributes` values back in the same binary format, but rbx_binary
+    /// doesn't support them yet.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn read_attributes(self, read_attributes: bool) -> Self {
+        DecodeOptions {
+            read_attributes,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will drop properties that the reflection
+    /// database knows about, but says are no longer serialized, like
+    /// `BasePart.Elasticity` and `BasePart.Friction` in places saved before
+    /// 2015.
+    ///
+    /// Without this option, these are treated like properties the database
+    /// doesn't know about: they're read as-is with
+    /// [`DecodePropertyBehavior::ReadUnknown`] and are an error with
+    /// [`DecodePropertyBehavior::ErrorOnUnknown`]. With it, they're always
+    /// dropped, so old files decode to the same properties as files saved
+    /// today. This has no effect with
+    /// [`DecodePropertyBehavior::NoReflection`].
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn drop_unserialized_properties(self, drop_unserialized_properties: bool) -> Self {
+        DecodeOptions {
+            drop_unserialized_properties,
+            ..self
+        }
+    }
+
+    /// Determines what happens to Ref properties that refer to an Item that
+    /// isn't in the document. See [`UnresolvedRefBehavior`] for details.
+    #[inline]
+    pub fn unresolved_ref_behavior(self, unresolved_ref_behavior: UnresolvedRefBehavior) -> Self {
+        DecodeOptions {
+            unresolved_ref_behavior,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will replace enum values that the
+    /// reflection database says are from an older version of Roblox with the
+    /// values that replaced them, like a `Lighting.Technology` of `Legacy`
+    /// becoming `Compatibility`.
+    ///
+    /// Every replaced value is recorded in
+    /// [`DecodeSummary::migrated_enum_values`][crate::DecodeSummary::migrated_enum_values].
+    /// Turn this off to keep each value exactly as it is in the file, like
+    /// when archiving places. This has no effect with
+    /// [`DecodePropertyBehavior::NoReflection`].
+    ///
+    /// This is on by default.
+    #[inline]
+    pub fn migrate_enum_values(self, migrate_enum_values: bool) -> Self {
+        DecodeOptions {
+            migrate_enum_values,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will try to decode documents with a
+    /// `version` newer than 4, the newest it understands.
+    ///
+    /// With this option set, a newer document is read as if it were version
+    /// 4, which works as long as the newer version only added things rbx_xml
+    /// can skip over. A diagnostic says which version the document was. The
+    /// version is still available from
+    /// [`DecodeSummary::version`][crate::DecodeSummary::version], so it can
+    /// be written back out with
+    /// [`EncodeOptions::document_version`][crate::EncodeOptions::document_version].
+    /// Without it, these documents are an error that gives their version from
+    /// [`DecodeError::document_version`][crate::DecodeError::document_version].
+    ///
+    /// Versions 3 and 4 are always decoded, and older or non-numeric versions
+    /// are always an error. This is off by default.
+    #[inline]
+    pub fn accept_newer_versions(self, accept_newer_versions: bool) -> Self {
+        DecodeOptions {
+            accept_newer_versions,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will remember which Items had no `Name`
+    /// property, like instances that were encoded with
+    /// `EncodeOptions::omit_default_names`.
+    ///
+    /// Those instances are always named after their ClassName. With this on,
+    /// [`Instance::explicit_name`][rbx_dom_weak::Instance::explicit_name] also
+    /// returns `None` for them, and rbx_xml and rbx_binary leave their Name
+    /// out again when they're encoded. Instances from
+    /// [`InstanceStream`][crate::InstanceStream] aren't affected.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn preserve_missing_names(self, preserve_missing_names: bool) -> Self {
+        DecodeOptions {
+            preserve_missing_names,
+            ..self
+        }
+    }
+
+    /// Creates a builder for a new instance, taking its referent from the
+    /// configured `RefProvider` if there is one.
+    pub(crate) fn new_builder(&mut self, class: &str) -> InstanceBuilder {
+        let builder = InstanceBuilder::new(class);
+
+        match &mut self.ref_provider {
+            Some(provider) => builder.with_referent(provider.next_ref()),
+            None => builder,
+        }
+    }
+
+    /// Tells whether instances of the given class should be kept, according to
+    /// the `class_filter` option.
+    pub(crate) fn keeps_class(&self, class: &str) -> bool {
+        match &self.class_filter {
+            Some(filter) => filter.contains(class),
+            None => true,
+        }
+    }
+
+    /// Reads base64 contents, keeping them in the `blob_store` if there is one
+    /// and they're too long to keep in memory.
+    pub(crate) fn read_binary_contents<R: Read>(
+        &self,
+        reader: &mut XmlEventReader<R>,
+    ) -> Result<Base64Contents, DecodeError> {
+        match &self.blob_store {
+            Some(store) => reader.read_base64_characters_to_store(store, self.blob_threshold),
+            None => reader.read_base64_characters().map(Base64Contents::Buffer),
+        }
+    }
+
+    /// A utility function to determine whether or not we should reference the
+    /// reflection database at all.
+    pub(crate) fn use_reflection(&self) -> bool {
+        self.property_behavior != DecodePropertyBehavior::NoReflection
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> DecodeOptions {
+        DecodeOptions::new()
+    }
+}
+
+/// An event read by an [`InstanceStream`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum StreamEvent {
+    /// An `<Item>` was read, along with its properties.
+    Instance(StreamedInstance),
+
+    /// An entry of the document's SharedString dictionary was read. Places
+    /// usually keep this dictionary at the end of the file, after every
+    /// instance that refers to it.
+    SharedString {
+        /// The hash that properties refer to this value by, found in
+        /// [`StreamedInstance::shared_strings`].
+        hash: String,
+
+        /// The value itself.
+        value: SharedString,
+    },
+}
+
+/// An instance read by an [`InstanceStream`].
+///
+/// Ref and SharedString properties can't be resolved until the whole document
+/// has been read, so they aren't included in
+/// [`properties`][StreamedInstance::properties]. They're listed by
+/// [`refs`][StreamedInstance::refs] and
+/// [`shared_strings`][StreamedInstance::shared_strings] instead, holding the
+/// referent or hash written in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamedInstance {
+    pub(crate) referent: Option<XmlReferent>,
+    pub(crate) parent: Option<XmlReferent>,
+    pub(crate) class: String,
+    pub(crate) name: String,
+    pub(crate) properties: IndexMap<String, Variant>,
+    pub(crate) refs: Vec<(String, String)>,
+    pub(crate) shared_strings: Vec<(String, String)>,
+}
+
+impl StreamedInstance {
+    /// The referent that the instance has in the file, if it has one.
+    pub fn referent(&self) -> Option<&str> {
+        self.referent.as_ref().map(XmlReferent::as_str)
+    }
+
+    /// The referent of the instance's parent, or `None` if the instance is at
+    /// the top of the document. If the parent was left out by
+    /// [`DecodeOptions::class_filter`], this is the nearest ancestor that
+    /// wasn't. Parents without a referent are also reported as `None`.
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_ref().map(XmlReferent::as_str)
+    }
+
+    /// The instance's ClassName.
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+
+    /// The instance's Name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The instance's properties, other than its Name and any Ref or
+    /// SharedString properties.
+    pub fn properties(&self) -> &IndexMap<String, Variant> {
+        &self.properties
+    }
+
+    /// The instance's Ref properties that aren't null, as pairs of property
+    /// names and the referents they point to.
+    pub fn refs(&self) -> &[(String, String)] {
+        &self.refs
+    }
+
+    /// The instance's SharedString properties, as pairs of property names and
+    /// the hashes of their values in the document's SharedString dictionary.
+    pub fn shared_strings(&self) -> &[(String, String)] {
+        &self.shared_strings
+    }
+
+    /// Takes the instance's properties, leaving it with none.
+    pub fn into_properties(self) -> IndexMap<String, Variant> {
+        self.properties
+    }
+}
+
+/// Reads a document one instance at a time, created with
+/// [`decode_instances_iter`][crate::decode_instances_iter].
+///
+/// Only the Items that are still open are kept in memory, so a document of
+/// any size can be scanned without building a tree with everything in it.
+/// Instances are yielded in the order their `<Item>` elements start, which
+/// means parents always come before their children.
+///
+/// Once an error has been returned, the stream ends.
+/// [`DecodeOptions::recover`] and [`DecodeOptions::source_locations`] have no
+/// effect on it, and elements read because of
+/// [`DecodeOptions::legacy_elements`] are skipped.
+pub struct InstanceStream<R: Read> {
+    reader: XmlEventReader<ExtraEndTagFilter<CountingReader<R>>>,
+    state: ParseState<'static>,
+    stage: StreamStage,
+
+    /// The Items that have started but not ended yet, innermost last.
+    open_items: Vec<OpenItem>,
+
+    /// Whether the reader is inside of a `<SharedStrings>` element.
+    in_shared_strings: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamStage {
+    Start,
+    Items,
+    Done,
+}
+
+struct OpenItem {
+    /// The instance holding this Item's state until it ends.
+    id: Ref,
+
+    /// The referent reported as the parent of this Item's children. Items
+    /// that are filtered out pass along the one they were given.
+    children_parent: Option<XmlReferent>,
+}
+
+impl<R: Read> InstanceStream<R> {
+    pub(crate) fn new(source: R, mut options: DecodeOptions) -> Self {
+        let tree = WeakDom::new(options.new_builder("DataModel"));
+        let root_id = tree.root_ref();
+
+        let filter = ExtraEndTagFilter::new(CountingReader::new(source));
+        let extra_end_tags = filter.removed();
+
+        InstanceStream {
+            reader: XmlEventReader::from_source(filter),
+            state: ParseState::new(StateTree::Owned(tree), root_id, options, extra_end_tags),
+            stage: StreamStage::Start,
+            open_items: Vec::new(),
+            in_shared_strings: false,
+        }
+    }
+
+    /// The number of bytes that have been read from the source so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.reader.source().get_ref().bytes_read()
+    }
+
+    fn next_event(&mut self) -> Result<Option<StreamEvent>, DecodeError> {
+        if self.stage == StreamStage::Start {
+            let accept_newer_versions = self.state.options.accept_newer_versions;
+            self.state.version =
+                deserialize_document_start(&mut self.reader, accept_newer_versions)?;
+            self.stage = StreamStage::Items;
+        }
+
+        loop {
+            if self.in_shared_strings {
+                if let Some(event) = self.next_shared_string()? {
+                    return Ok(Some(event));
+                }
+
+                continue;
+            }
+
+            match self.reader.expect_peek()? {
+                XmlReadEvent::StartElement { name, .. } => match name.local_name.as_str() {
+                    "Item" => {
+                        if let Some(instance) = self.start_item()? {
+                            return Ok(Some(StreamEvent::Instance(instance)));
+                        }
+                    }
+                    "Properties" => {
+                        // Properties are only allowed before an Item's
+                        // children, since the Item has already been yielded
+                        // by the time we reach them.
+                        let event = self.reader.expect_next()?;
+                        return Err(self
+                            .reader
+                            .error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+                    }
+                    "External" if self.open_items.is_empty() => {
+                        self.reader.eat_unknown_tag()?;
+                    }
+                    "Meta" if self.open_items.is_empty() => {
+                        deserialize_metadata(&mut self.reader, &mut self.state)?;
+                    }
+                    "SharedStrings" if self.open_items.is_empty() => {
+                        self.reader.expect_start_with_name("SharedStrings")?;
+                        self.in_shared_strings = true;
+                    }
+                    _ => self.skip_legacy_element()?,
+                },
+                XmlReadEvent::EndElement { name } => {
+                    if name.local_name == "Item" && !self.open_items.is_empty() {
+                        self.reader.expect_next()?;
+
+                        let item = self.open_items.pop().unwrap();
+                        self.state.tree.destroy(item.id);
+                    } else if name.local_name == "roblox" && self.open_items.is_empty() {
+                        self.reader.expect_next()?;
+                        return Ok(None);
+                    } else {
+                        let event = self.reader.expect_next()?;
+                        return Err(self
+                            .reader
+                            .error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+                    }
+                }
+                XmlReadEvent::EndDocument if self.open_items.is_empty() => return Ok(None),
+                _ => {
+                    let event = self.reader.expect_next()?;
+                    return Err(self
+                        .reader
+                        .error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+                }
+            }
+        }
+    }
+
+    /// Reads the start of an Item, up to its first child or its end, and
+    /// returns it unless it's filtered out.
+    fn start_item(&mut self) -> Result<Option<StreamedInstance>, DecodeError> {
+        let reader = &mut self.reader;
+        let state = &mut self.state;
+
+        let (class_name, referent) = {
+            let attributes = reader.expect_start_with_name("Item")?;
+
+            let class = find_attribute(&attributes, "class")
+                .ok_or_else(|| reader.error(DecodeErrorDetail::MissingAttribute("class")))?
+                .to_owned();
+            let referent = find_attribute(&attributes, "referent")
+                .map(|referent| XmlReferent::new(referent.to_owned()));
+
+            (class, referent)
+        };
+
+        trace!("Class {} with referent {:?}", class_name, referent);
+
+        let (parent_id, parent) = match self.open_items.last() {
+            Some(item) => (item.id, item.children_parent.clone()),
+            None => (state.root_id, None),
+        };
+
+        let instance_id = state.insert_instance(reader, parent_id, &class_name)?;
+
+        let mut properties = IndexMap::new();
+
+        while let XmlReadEvent::StartElement { name, .. } = reader.expect_peek()? {
+            match name.local_name.as_str() {
+                "Properties" => {
+                    deserialize_properties(reader, state, instance_id, &mut properties)?;
+                    check_extra_end_tag(reader, state)?;
+                }
+                "Item" => break,
+                _ => {
+                    deserialize_legacy_element(reader, state, instance_id)?;
+                    state.legacy_elements.clear();
+                }
+            }
+        }
+
+        // Every placeholder value read so far belongs to this Item.
+        let referent_rewrites = std::mem::take(&mut state.referent_rewrites);
+        let shared_string_rewrites = std::mem::take(&mut state.shared_string_rewrites);
+
+        let keep = state.options.keeps_class(&class_name);
+        let children_parent = if keep {
+            referent.clone()
+        } else {
+            parent.clone()
+        };
+
+        self.open_items.push(OpenItem {
+            id: instance_id,
+            children_parent,
+        });
+
+        if !keep {
+            return Ok(None);
+        }
+
+        // Properties that were read and thrown away can still leave a
+        // placeholder behind, so only the ones that were kept are listed.
+        let mut refs = Vec::new();
+        for rewrite in referent_rewrites {
+            if properties.shift_remove(&rewrite.property_name).is_some() {
+                refs.push((rewrite.property_name, rewrite.referent_value.into_string()));
+            }
+        }
+
+        let mut shared_strings = Vec::new();
+        for rewrite in shared_string_rewrites {
+            if properties.shift_remove(&rewrite.property_name).is_some() {
+                shared_strings.push((rewrite.property_name, rewrite.shared_string_hash));
+            }
+        }
+
+        let name = take_name(reader, &mut properties)?.unwrap_or_else(|| class_name.clone());
+        finish_properties(state, &mut properties);
+
+        state.instance_count += 1;
+        state.property_count += properties.len();
+
+        Ok(Some(StreamedInstance {
+            referent,
+            parent,
+            class: class_name,
+            name,
+            properties,
+            refs,
+            shared_strings,
+        }))
+    }
+
+    /// Reads the next entry of the SharedString dictionary, or the end of the
+    /// dictionary.
+    fn next_shared_string(&mut self) -> Result<Option<StreamEvent>, DecodeError> {
+        match self.reader.expect_peek()? {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "SharedString" => {
+                let hash = deserialize_shared_string(&mut self.reader, &mut self.state)?;
+                let value = self
+                    .state
+                    .known_shared_strings
+                    .get(&hash)
+                    .cloned()
+                    .ok_or_else(|| {
+                        self.reader.error(DecodeErrorDetail::bug(
+                            "a SharedString that was just read wasn't recorded",
+                        ))
+                    })?;
+
+                Ok(Some(StreamEvent::SharedString { hash, value }))
+            }
+            XmlReadEvent::EndElement { name } if name.local_name == "SharedStrings" => {
+                self.reader.expect_end_with_name("SharedStrings")?;
+                self.in_shared_strings = false;
+
+                Ok(None)
+            }
+            _ => {
+                let event = self.reader.expect_next()?;
+                Err(self
+                    .reader
+                    .error(DecodeErrorDetail::UnexpectedXmlEvent(event)))
+            }
+        }
+    }
+
+    /// Reads past an element we don't know about, which is an error unless
+    /// legacy elements are allowed.
+    fn skip_legacy_element(&mut self) -> Result<(), DecodeError> {
+        let parent_id = match self.open_items.last() {
+            Some(item) => item.id,
+            None => self.state.root_id,
+        };
+
+        deserialize_legacy_element(&mut self.reader, &mut self.state, parent_id)?;
+        self.state.legacy_elements.clear();
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for InstanceStream<R> {
+    type Item = Result<StreamEvent, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stage == StreamStage::Done {
+            return None;
+        }
+
+        match self.next_event() {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.stage = StreamStage::Done;
+                None
+            }
+            Err(err) => {
+                self.stage = StreamStage::Done;
+                Some(Err(err))
+            }
         }
     }
+}
 
-    /// Determines how rbx_xml will deserialize properties, especially unknown
-    /// ones.
-    #[inline]
-    pub fn property_behavior(self, property_behavior: DecodePropertyBehavior) -> Self {
-        DecodeOptions { property_behavior }
-    }
+/// The tree that a `ParseState` puts instances into. It's borrowed when a
+/// whole document is decoded, and owned by an `InstanceStream`, which only
+/// keeps the instances that are still open in it.
+enum StateTree<'a> {
+    Borrowed(&'a mut WeakDom),
+    Owned(WeakDom),
+}
 
-    /// A utility function to determine whether or not we should reference the
-    /// reflection database at all.
-    pub(crate) fn use_reflection(&self) -> bool {
-        self.property_behavior != DecodePropertyBehavior::NoReflection
+impl Deref for StateTree<'_> {
+    type Target = WeakDom;
+
+    fn deref(&self) -> &WeakDom {
+        match self {
+            StateTree::Borrowed(tree) => tree,
+            StateTree::Owned(tree) => tree,
+        }
     }
 }
 
-impl Default for DecodeOptions {
-    fn default() -> DecodeOptions {
-        DecodeOptions::new()
+impl DerefMut for StateTree<'_> {
+    fn deref_mut(&mut self) -> &mut WeakDom {
+        match self {
+            StateTree::Borrowed(tree) => tree,
+            StateTree::Owned(tree) => tree,
+        }
     }
 }
 
 /// The state needed to deserialize an XML model into an `WeakDom`.
 pub struct ParseState<'a> {
-    tree: &'a mut WeakDom,
+    tree: StateTree<'a>,
     options: DecodeOptions,
 
+    /// The instance that the document's top-level Items are inserted into.
+    /// This is the tree's root unless decoding into an existing tree.
+    root_id: Ref,
+
     /// Metadata deserialized from 'Meta' fields in the file.
     /// Known fields are:
     /// - ExplicitAutoJoints
     metadata: HashMap<String, String>,
 
+    /// Processing instructions found outside of every Item, in the order they
+    /// appeared.
+    processing_instructions: Vec<ProcessingInstruction>,
+
     /// A map referent strings to IDs. This map is filled up as instances are
     /// deserialized, and referred to when filling out Ref properties.
     ///
     /// We need to do that step in two passes because it's possible for
     /// instances to refer to instances that are later in the file.
-    referents_to_ids: HashMap<String, Ref>,
+    referents_to_ids: HashMap<XmlReferent, Ref>,
 
     /// A list of Ref property rewrites to apply. After the first
     /// deserialization pass, we enumerate over this list and fill in the
@@ -129,12 +1112,58 @@ pub struct ParseState<'a> {
     /// pass. This works just like referent rewriting since the shared string
     /// dictionary is usually at the end of the XML file.
     shared_string_rewrites: Vec<SharedStringRewrite>,
+
+    /// The number of instances deserialized so far.
+    instance_count: usize,
+
+    /// The number of properties deserialized so far, not counting names.
+    property_count: usize,
+
+    /// Whether any part of the document was skipped because of an error.
+    recovered: bool,
+
+    /// Problems found and worked around while deserializing.
+    diagnostics: Vec<Diagnostic>,
+
+    /// Deduplicates String and Content values, if the `intern_strings` option
+    /// is set.
+    string_interner: Option<StringInterner>,
+
+    /// Rewrites Content values, if the `normalize_content` option is set.
+    content_normalizer: Option<ContentNormalizer>,
+
+    /// Elements kept because the `legacy_elements` option is set.
+    legacy_elements: Vec<LegacyElement>,
+
+    /// Enum values replaced because the `migrate_enum_values` option is set.
+    migrated_enum_values: Vec<MigratedEnumValue>,
+
+    /// The number of extra `</Properties>` tags that have been removed from
+    /// the document so far, and how many of those we've already handled.
+    extra_end_tags: Rc<Cell<usize>>,
+    extra_end_tags_handled: usize,
+
+    /// Instances left out by the `class_filter` option. They stay in the tree
+    /// until the whole document has been read.
+    filtered_out: Vec<Ref>,
+
+    /// Where the `<Item>` tag of each instance starts, if the
+    /// `source_locations` option is set.
+    item_positions: Vec<(Ref, TextPosition)>,
+
+    /// The `version` attribute of the document's `<roblox>` tag, once it's
+    /// been read.
+    version: u32,
 }
 
 struct ReferentRewrite {
     id: Ref,
     property_name: String,
-    referent_value: String,
+    referent_value: XmlReferent,
+
+    /// Where the property was read, for errors about referents that aren't in
+    /// the document.
+    position: TextPosition,
 }
 
 struct SharedStringRewrite {
@@ -144,27 +1173,105 @@ struct SharedStringRewrite {
 }
 
 impl<'a> ParseState<'a> {
-    fn new(tree: &mut WeakDom, options: DecodeOptions) -> ParseState {
+    fn new(
+        tree: StateTree<'a>,
+        root_id: Ref,
+        options: DecodeOptions,
+        extra_end_tags: Rc<Cell<usize>>,
+    ) -> ParseState<'a> {
+        let string_interner = if options.intern_strings {
+            Some(StringInterner::new(options.intern_max_len))
+        } else {
+            None
+        };
+
+        let content_normalizer = if options.normalize_content {
+            Some(ContentNormalizer::new())
+        } else {
+            None
+        };
+
         ParseState {
             tree,
+            root_id,
             options,
             metadata: HashMap::new(),
+            processing_instructions: Vec::new(),
             referents_to_ids: HashMap::new(),
             referent_rewrites: Vec::new(),
             known_shared_strings: HashMap::new(),
             shared_string_rewrites: Vec::new(),
+            instance_count: 0,
+            property_count: 0,
+            recovered: false,
+            diagnostics: Vec::new(),
+            string_interner,
+            content_normalizer,
+            legacy_elements: Vec::new(),
+            migrated_enum_values: Vec::new(),
+            extra_end_tags,
+            extra_end_tags_handled: 0,
+            filtered_out: Vec::new(),
+            item_positions: Vec::new(),
+            version: DOC_VERSION,
+        }
+    }
+
+    /// Tells whether large binary values should be kept in a blob store.
+    pub(crate) fn uses_blob_store(&self) -> bool {
+        self.options.blob_store.is_some()
+    }
+
+    /// Tells whether `AttributesSerialize` should be decoded into
+    /// `Attributes`.
+    pub(crate) fn reads_attributes(&self) -> bool {
+        self.options.read_attributes
+    }
+
+    /// Inserts a new instance of the given class, taking its Ref from the
+    /// configured `RefProvider` if there is one. A provider can hand out Refs
+    /// that are already in the tree, which is an error instead of a panic.
+    fn insert_instance<R: Read>(
+        &mut self,
+        reader: &XmlEventReader<R>,
+        parent_id: Ref,
+        class_name: &str,
+    ) -> Result<Ref, DecodeError> {
+        let builder = self.options.new_builder(class_name);
+        let referent = builder.referent();
+
+        if self.tree.get_by_ref(referent).is_some() {
+            return Err(reader.error(DecodeErrorDetail::DuplicateRef(referent)));
         }
+
+        Ok(self.tree.insert(parent_id, builder))
+    }
+
+    /// Reads base64 contents for a binary value, which might end up in the
+    /// blob store if the options have one.
+    pub(crate) fn read_binary_contents<R: Read>(
+        &self,
+        reader: &mut XmlEventReader<R>,
+    ) -> Result<Base64Contents, DecodeError> {
+        self.options.read_binary_contents(reader)
     }
 
     /// Marks that a property on this instance needs to be rewritten once we
     /// have a complete view of how referents map to Ref values.
     ///
     /// This is used to deserialize non-null Ref values correctly.
-    pub fn add_referent_rewrite(&mut self, id: Ref, property_name: String, referent_value: String) {
+    pub fn add_referent_rewrite(
+        &mut self,
+        id: Ref,
+        property_name: String,
+        referent_value: XmlReferent,
+        position: TextPosition,
+    ) {
         self.referent_rewrites.push(ReferentRewrite {
             id,
             property_name,
             referent_value,
+            position,
         });
     }
 
@@ -184,49 +1291,163 @@ impl<'a> ParseState<'a> {
             shared_string_hash,
         });
     }
+
+    /// Drops any bookkeeping for instances that have been removed from the
+    /// tree, which happens when an instance is skipped during recovery.
+    fn forget_removed_instances(&mut self) {
+        let tree = &*self.tree;
+
+        self.referents_to_ids
+            .retain(|_, id| tree.get_by_ref(*id).is_some());
+        self.referent_rewrites
+            .retain(|rewrite| tree.get_by_ref(rewrite.id).is_some());
+        self.shared_string_rewrites
+            .retain(|rewrite| tree.get_by_ref(rewrite.id).is_some());
+        self.legacy_elements.retain(|element| match element.parent {
+            Some(parent) => tree.get_by_ref(parent).is_some(),
+            None => true,
+        });
+    }
 }
 
-fn apply_referent_rewrites(state: &mut ParseState) {
+fn apply_referent_rewrites(state: &mut ParseState) -> Result<(), DecodeError> {
+    phase_span!("resolve_referents", count = state.referent_rewrites.len());
+
     for rewrite in &state.referent_rewrites {
         let new_value = match state.referents_to_ids.get(&rewrite.referent_value) {
             Some(id) => *id,
-            None => continue,
+            None => match state.options.unresolved_ref_behavior {
+                // The property was already set to a null Ref when it was read.
+                UnresolvedRefBehavior::SetNull => continue,
+                UnresolvedRefBehavior::Error => {
+                    let class_name = match state.tree.get_by_ref(rewrite.id) {
+                        Some(instance) => instance.class.clone(),
+                        None => return Err(missing_rewrite_target(rewrite.position)),
+                    };
+
+                    return Err(DecodeError::new_at(
+                        DecodeErrorDetail::UnresolvedRef {
+                            class_name,
+                            property_name: rewrite.property_name.clone(),
+                            referent: rewrite.referent_value.to_string(),
+                        },
+                        rewrite.position,
+                    ));
+                }
+            },
         };
 
         let instance = state
             .tree
             .get_by_ref_mut(rewrite.id)
-            .expect("rbx_xml bug: had ID in referent rewrite list that didn't end up in the tree");
+            .ok_or_else(|| missing_rewrite_target(rewrite.position))?;
 
         instance
             .properties
             .insert(rewrite.property_name.clone(), Variant::Ref(new_value));
     }
+
+    Ok(())
+}
+
+fn missing_rewrite_target(position: TextPosition) -> DecodeError {
+    DecodeError::new_at(
+        DecodeErrorDetail::bug("had ID in a rewrite list that didn't end up in the tree"),
+        position,
+    )
 }
 
-fn apply_shared_string_rewrites(state: &mut ParseState) {
+fn apply_shared_string_rewrites(state: &mut ParseState) -> Result<(), DecodeError> {
+    phase_span!(
+        "resolve_shared_strings",
+        count = state.shared_string_rewrites.len()
+    );
+
+    if !state.shared_string_rewrites.is_empty() {
+        reuse_existing_shared_strings(state);
+    }
+
     for rewrite in &state.shared_string_rewrites {
         let new_value = match state.known_shared_strings.get(&rewrite.shared_string_hash) {
             Some(v) => v.clone(),
             None => continue,
         };
 
-        let instance = state.tree.get_by_ref_mut(rewrite.id).expect(
-            "rbx_xml bug: had ID in SharedString rewrite list that didn't end up in the tree",
-        );
+        // SharedString properties don't keep where they were read.
+        let instance = state
+            .tree
+            .get_by_ref_mut(rewrite.id)
+            .ok_or_else(|| missing_rewrite_target(TextPosition::new()))?;
 
         instance.properties.insert(
             rewrite.property_name.clone(),
             Variant::SharedString(new_value),
         );
     }
+
+    Ok(())
 }
 
-fn deserialize_root<R: Read>(
+/// Matches the document's SharedStrings up with the ones already in the tree
+/// being decoded into.
+///
+/// Strings with the same contents as one in the tree use the tree's value, so
+/// that they share its memory or blob even if the two were read differently.
+/// Keys that the document's SharedString dictionary doesn't have are looked
+/// up in the tree too, which lets models saved with a place's SharedStrings
+/// left out be decoded back into that place.
+fn reuse_existing_shared_strings(state: &mut ParseState) {
+    // The new instances' SharedString properties haven't been filled in yet,
+    // so every SharedString in the tree was already there.
+    let tree = &*state.tree;
+    let mut existing = HashMap::new();
+
+    for instance in tree.descendants(tree.root_ref()) {
+        for value in instance.properties.values() {
+            if let Variant::SharedString(value) = value {
+                existing
+                    .entry(value.hash())
+                    .or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    if existing.is_empty() {
+        return;
+    }
+
+    for value in state.known_shared_strings.values_mut() {
+        if let Some(existing_value) = existing.get(&value.hash()) {
+            *value = existing_value.clone();
+        }
+    }
+
+    let known = &state.known_shared_strings;
+    let has_missing_keys = state
+        .shared_string_rewrites
+        .iter()
+        .any(|rewrite| !known.contains_key(&rewrite.shared_string_hash));
+
+    if has_missing_keys {
+        for value in existing.into_values() {
+            // Strings whose blobs can't be read again can't be matched by
+            // key, but they'll still be used for strings with the same
+            // contents.
+            if let Ok(key) = shared_string_key(&value) {
+                state.known_shared_strings.entry(key).or_insert(value);
+            }
+        }
+    }
+}
+
+/// Reads up to and including the opening `<roblox>` tag, checking that the
+/// document has a version we understand, and returns that version. Versions
+/// newer than the one rbx_xml writes are only allowed when
+/// `accept_newer_versions` is set.
+pub(crate) fn deserialize_document_start<R: Read>(
     reader: &mut XmlEventReader<R>,
-    state: &mut ParseState,
-    parent_id: Ref,
-) -> Result<(), DecodeError> {
+    accept_newer_versions: bool,
+) -> Result<u32, DecodeError> {
     match reader.expect_next()? {
         XmlReadEvent::StartDocument { .. } => {}
         _ => unreachable!(),
@@ -234,19 +1455,37 @@ fn deserialize_root<R: Read>(
 
     let doc_attributes = reader.expect_start_with_name("roblox")?;
 
-    let mut doc_version = None;
+    let doc_version = find_attribute(&doc_attributes, "version")
+        .ok_or_else(|| reader.error(DecodeErrorDetail::MissingAttribute("version")))?
+        .to_owned();
 
-    for attribute in doc_attributes.into_iter() {
-        if attribute.name.local_name.as_str() == "version" {
-            doc_version = Some(attribute.value);
+    match doc_version.parse::<u32>() {
+        Ok(version) if version > DOC_VERSION && !accept_newer_versions => {
+            Err(reader.error(DecodeErrorDetail::NewerDocVersion(version)))
         }
+        Ok(version) if version >= OLDEST_DOC_VERSION => Ok(version),
+        _ => Err(reader.error(DecodeErrorDetail::WrongDocVersion(doc_version))),
     }
+}
 
-    let doc_version =
-        doc_version.ok_or_else(|| reader.error(DecodeErrorKind::MissingAttribute("version")))?;
-
-    if doc_version != "4" {
-        return Err(reader.error(DecodeErrorKind::WrongDocVersion(doc_version)));
+fn deserialize_root<R: Read>(
+    reader: &mut XmlEventReader<R>,
+    state: &mut ParseState,
+    parent_id: Ref,
+) -> Result<(), DecodeError> {
+    state.version = deserialize_document_start(reader, state.options.accept_newer_versions)?;
+
+    if state.version > DOC_VERSION {
+        let error = reader.error(DecodeErrorDetail::NewerDocVersion(state.version));
+        log::warn!("Reading version {} document as version 4", state.version);
+
+        state.diagnostics.push(Diagnostic {
+            message: format!(
+                "The document is version {}, and was read as if it were version 4",
+                state.version
+            ),
+            ..Diagnostic::from_error(&error)
+        });
     }
 
     loop {
@@ -254,7 +1493,7 @@ fn deserialize_root<R: Read>(
             XmlReadEvent::StartElement { name, .. } => {
                 match name.local_name.as_str() {
                     "Item" => {
-                        deserialize_instance(reader, state, parent_id)?;
+                        deserialize_child_instance(reader, state, parent_id)?;
                     }
                     "External" => {
                         // This tag is always meaningless, there's nothing to do
@@ -268,24 +1507,23 @@ fn deserialize_root<R: Read>(
                         deserialize_shared_string_dict(reader, state)?;
                     }
                     _ => {
-                        let event = reader.expect_next().unwrap();
-                        return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                        deserialize_legacy_element(reader, state, parent_id)?;
                     }
                 }
             }
             XmlReadEvent::EndElement { name } => {
                 if name.local_name == "roblox" {
-                    reader.expect_next().unwrap();
+                    reader.expect_next()?;
                     break;
                 } else {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    let event = reader.expect_next()?;
+                    return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
             }
             XmlReadEvent::EndDocument => break,
             _ => {
-                let event = reader.expect_next().unwrap();
-                return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
             }
         }
     }
@@ -293,27 +1531,125 @@ fn deserialize_root<R: Read>(
     Ok(())
 }
 
-fn deserialize_metadata<R: Read>(
+/// Handles an element that isn't part of the format as rbx_xml knows it,
+/// found directly inside `<roblox>` or an `<Item>`. Places saved before 2015
+/// can have elements like `<HashMap>` in these spots.
+fn deserialize_legacy_element<R: Read>(
     reader: &mut XmlEventReader<R>,
     state: &mut ParseState,
+    parent_id: Ref,
 ) -> Result<(), DecodeError> {
-    let name = {
-        let attributes = reader.expect_start_with_name("Meta")?;
+    let name = match reader.expect_peek()? {
+        XmlReadEvent::StartElement { name, .. } => name.local_name.clone(),
+        _ => unreachable!(),
+    };
+
+    if !state.options.legacy_elements {
+        return Err(reader.error(DecodeErrorDetail::LegacyElement(name)));
+    }
 
-        let mut name = None;
+    let xml = reader.read_raw_element()?;
 
-        for attribute in attributes.into_iter() {
-            if attribute.name.local_name.as_str() == "name" {
-                name = Some(attribute.value);
-            }
+    // The instance that the document is decoded into isn't in the file, so
+    // elements directly inside <roblox> don't have a parent.
+    let parent = if parent_id == state.root_id {
+        None
+    } else {
+        Some(parent_id)
+    };
+    let index = state.tree.get_by_ref(parent_id).unwrap().children().len();
+
+    state.legacy_elements.push(LegacyElement {
+        parent,
+        index,
+        name,
+        xml,
+    });
+
+    Ok(())
+}
+
+/// Keeps the processing instructions that the reader skipped over at the top
+/// level of the document, and drops the ones inside of `<Item>` elements with a
+/// diagnostic, since there's nowhere to keep them.
+fn sort_processing_instructions<R: Read>(reader: &mut XmlEventReader<R>, state: &mut ParseState) {
+    for skipped in reader.take_processing_instructions() {
+        // Instructions before the document's root element or directly inside
+        // of it belong to the document as a whole.
+        if skipped.depth <= 1 {
+            state.processing_instructions.push(skipped.instruction);
+            continue;
         }
 
-        name.ok_or_else(|| reader.error(DecodeErrorKind::MissingAttribute("name")))?
+        log::warn!(
+            "Dropping the processing instruction <?{}?> on line {}, since it isn't at the top \
+             level of the document",
+            skipped.instruction.name,
+            skipped.position.row + 1
+        );
+
+        let line = (skipped.position.row + 1) as usize;
+        let column = skipped.position.column as usize;
+
+        // The other diagnostics were found while reading, so this one goes
+        // wherever it would have been if it had been reported right away.
+        let index = state
+            .diagnostics
+            .partition_point(|diagnostic| (diagnostic.line, diagnostic.column) <= (line, column));
+
+        state.diagnostics.insert(
+            index,
+            Diagnostic {
+                message: format!(
+                    "Dropped the processing instruction <?{}?>, since only ones outside of every \
+                 <Item> are kept",
+                    skipped.instruction.name
+                ),
+                line,
+                column,
+                kind: DecodeErrorKind::MalformedDocument,
+                instance: None,
+                property_name: None,
+            },
+        );
+    }
+}
+
+fn deserialize_metadata<R: Read>(
+    reader: &mut XmlEventReader<R>,
+    state: &mut ParseState,
+) -> Result<(), DecodeError> {
+    let name = {
+        let attributes = reader.expect_start_with_name("Meta")?;
+
+        find_attribute(&attributes, "name")
+            .ok_or_else(|| reader.error(DecodeErrorDetail::MissingAttribute("name")))?
+            .to_owned()
     };
 
     let value = reader.read_characters()?;
     reader.expect_end_with_name("Meta")?;
 
+    // Some properties of the root are hoisted into Meta elements by
+    // RootPropertyBehavior::HoistIntoMeta, so we put them back here.
+    if ROOT_META_PROPERTIES.contains(&name.as_str()) {
+        let data = BinaryString::from(base64::decode(value.trim()).map_err(|e| reader.error(e))?);
+        let data = if name == ATTRIBUTES_PROPERTY && state.reads_attributes() {
+            attributes_from_binary(data)
+        } else {
+            data.into()
+        };
+        let root_id = state.root_id;
+
+        state
+            .tree
+            .get_by_ref_mut(root_id)
+            .unwrap()
+            .properties
+            .insert(name.clone(), data);
+        state.property_count += 1;
+    }
+
     state.metadata.insert(name, value);
     Ok(())
 }
@@ -330,21 +1666,21 @@ fn deserialize_shared_string_dict<R: Read>(
                 if name.local_name == "SharedString" {
                     deserialize_shared_string(reader, state)?;
                 } else {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    let event = reader.expect_next()?;
+                    return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
             }
             XmlReadEvent::EndElement { name } => {
                 if name.local_name == "SharedStrings" {
                     break;
                 } else {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    let event = reader.expect_next()?;
+                    return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
             }
             _ => {
-                let event = reader.expect_next().unwrap();
-                return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
             }
         }
     }
@@ -353,124 +1689,287 @@ fn deserialize_shared_string_dict<R: Read>(
     Ok(())
 }
 
+/// Reads one entry of the SharedString dictionary, returning the hash it's
+/// known by.
 fn deserialize_shared_string<R: Read>(
     reader: &mut XmlEventReader<R>,
     state: &mut ParseState,
-) -> Result<(), DecodeError> {
+) -> Result<String, DecodeError> {
     let attributes = reader.expect_start_with_name("SharedString")?;
 
-    let mut md5_hash = None;
-    for attribute in attributes.into_iter() {
-        if attribute.name.local_name == "md5" {
-            md5_hash = Some(attribute.value);
-            break;
-        }
-    }
+    let md5_hash = find_attribute(&attributes, "md5")
+        .ok_or_else(|| reader.error(DecodeErrorDetail::MissingAttribute("md5")))?
+        .to_owned();
 
-    let md5_hash =
-        md5_hash.ok_or_else(|| reader.error(DecodeErrorKind::MissingAttribute("md5")))?;
+    let value = match state.options.read_binary_contents(reader)? {
+        Base64Contents::Buffer(buffer) => SharedString::new(buffer),
+        Base64Contents::Stored(blob) => SharedString::from_blob(blob)
+            .map_err(|e| reader.error(DecodeErrorDetail::BlobStore(e)))?,
+    };
 
-    let buffer = reader.read_base64_characters()?;
+    match state.known_shared_strings.entry(md5_hash.clone()) {
+        Entry::Vacant(vacant) => {
+            vacant.insert(value);
+        }
+        Entry::Occupied(occupied) if *occupied.get() != value => {
+            let error = reader.error(DecodeErrorDetail::ConflictingSharedString {
+                key: md5_hash.clone(),
+                first_len: shared_string_len(occupied.get()),
+                second_len: shared_string_len(&value),
+            });
+            if !state.options.recover {
+                return Err(error);
+            }
 
-    let value = SharedString::new(buffer);
+            log::warn!("Keeping the first of two SharedStrings: {}", error);
 
-    state.known_shared_strings.insert(md5_hash, value);
+            state.recovered = true;
+            state.diagnostics.push(Diagnostic {
+                message: format!(
+                    "Kept the first of two SharedStrings with the key '{}' and dropped the second",
+                    md5_hash
+                ),
+                ..Diagnostic::from_error(&error)
+            });
+        }
+        // Some documents repeat an entry word for word, which is harmless.
+        Entry::Occupied(_) => {}
+    }
 
     reader.expect_end_with_name("SharedString")?;
-    Ok(())
+    Ok(md5_hash)
 }
 
-fn deserialize_instance<R: Read>(
+/// The length in bytes of a SharedString's contents, without reading them in
+/// from a blob store.
+fn shared_string_len(value: &SharedString) -> u64 {
+    match value.blob() {
+        Some(blob) => blob.len(),
+        None => value.data().len() as u64,
+    }
+}
+
+/// Deserializes an `Item` as a new child of the given parent.
+///
+/// When recovery is enabled and the `Item` can't be read, it's removed from the
+/// tree along with everything under it, and the reader skips to the end of it
+/// so that deserialization can continue with its next sibling.
+fn deserialize_child_instance<R: Read>(
     reader: &mut XmlEventReader<R>,
     state: &mut ParseState,
     parent_id: Ref,
 ) -> Result<(), DecodeError> {
-    let (class_name, referent) = {
-        let attributes = reader.expect_start_with_name("Item")?;
+    if !state.options.recover {
+        return deserialize_instance(reader, state, parent_id);
+    }
 
-        let mut class = None;
-        let mut referent = None;
+    let depth = reader.depth();
+    let child_count = state.tree.get_by_ref(parent_id).unwrap().children().len();
+    let counts = (state.instance_count, state.property_count);
 
-        for attribute in attributes.into_iter() {
-            match attribute.name.local_name.as_str() {
-                "class" => class = Some(attribute.value),
-                "referent" => referent = Some(attribute.value),
-                _ => {}
+    match deserialize_instance(reader, state, parent_id) {
+        Err(err) if err.is_recoverable() => {
+            log::warn!("Skipping an Item that could not be read: {}", err);
+
+            state.recovered = true;
+            state.diagnostics.push(Diagnostic::from_error(&err));
+
+            // Anything we managed to read from this Item is being thrown away,
+            // so it shouldn't be counted.
+            state.instance_count = counts.0;
+            state.property_count = counts.1;
+
+            let parent = state.tree.get_by_ref(parent_id).unwrap();
+            if let Some(&partial_id) = parent.children().get(child_count) {
+                state.tree.destroy(partial_id);
+                state.forget_removed_instances();
             }
+
+            reader.skip_to_depth(depth)
         }
+        result => result,
+    }
+}
+
+fn deserialize_instance<R: Read>(
+    reader: &mut XmlEventReader<R>,
+    state: &mut ParseState,
+    parent_id: Ref,
+) -> Result<(), DecodeError> {
+    let (class_name, referent, position) = {
+        let attributes = reader.expect_start_with_name("Item")?;
+        let position = reader.text_position();
 
-        let class =
-            class.ok_or_else(|| reader.error(DecodeErrorKind::MissingAttribute("class")))?;
+        let class = find_attribute(&attributes, "class")
+            .ok_or_else(|| reader.error(DecodeErrorDetail::MissingAttribute("class")))?
+            .to_owned();
+        let referent = find_attribute(&attributes, "referent")
+            .map(|referent| XmlReferent::new(referent.to_owned()));
 
-        (class, referent)
+        (class, referent, position)
     };
 
     trace!("Class {} with referent {:?}", class_name, referent);
 
-    let builder = InstanceBuilder::new(class_name);
-    let instance_id = state.tree.insert(parent_id, builder);
+    let instance_id = state.insert_instance(reader, parent_id, &class_name)?;
+
+    // An instance that's filtered out is still read so that we can get past
+    // it, but its children are given to its parent instead.
+    let keep = state.options.keeps_class(&class_name);
+    let children_parent_id = if keep { instance_id } else { parent_id };
 
-    if let Some(referent) = referent {
+    // Refs to an instance that's filtered out are left as none.
+    if let (Some(referent), true) = (referent, keep) {
         state.referents_to_ids.insert(referent, instance_id);
     }
 
-    let mut properties: HashMap<String, Variant> = HashMap::new();
+    if keep && state.options.source_locations {
+        state.item_positions.push((instance_id, position));
+    }
+
+    let mut properties: IndexMap<String, Variant> = IndexMap::new();
 
     loop {
         match reader.expect_peek()? {
             XmlReadEvent::StartElement { name, .. } => match name.local_name.as_str() {
                 "Properties" => {
+                    detail_span!(
+                        "read_properties",
+                        class = %state.tree.get_by_ref(instance_id).unwrap().class
+                    );
                     deserialize_properties(reader, state, instance_id, &mut properties)?;
+                    check_extra_end_tag(reader, state)?;
                 }
                 "Item" => {
-                    deserialize_instance(reader, state, instance_id)?;
+                    deserialize_child_instance(reader, state, children_parent_id)?;
                 }
                 _ => {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    deserialize_legacy_element(reader, state, instance_id)?;
                 }
             },
             XmlReadEvent::EndElement { name } => {
                 if name.local_name != "Item" {
-                    let event = reader.expect_next().unwrap();
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    let event = reader.expect_next()?;
+                    return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
 
-                reader.expect_next().unwrap();
+                reader.expect_next()?;
 
                 break;
             }
             _ => {
-                let event = reader.expect_next().unwrap();
-                return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
             }
         }
     }
 
-    let instance = state.tree.get_by_ref_mut(instance_id).unwrap();
+    if !keep {
+        state.filtered_out.push(instance_id);
+        return Ok(());
+    }
 
-    instance.name = match properties.remove("Name") {
-        Some(value) => match value {
-            Variant::String(value) => value,
-            _ => return Err(reader.error(DecodeErrorKind::NameMustBeString(value.ty()))),
-        },
+    let name = take_name(reader, &mut properties)?;
+    finish_properties(state, &mut properties);
 
-        // TODO: Use reflection to get default name instead. This should only
-        // matter for ValueBase instances in files created by tools other than
-        // Roblox Studio.
-        None => instance.class.clone(),
-    };
+    state.instance_count += 1;
+    state.property_count += properties.len();
 
+    let preserve_missing_names = state.options.preserve_missing_names;
+    let instance = state.tree.get_by_ref_mut(instance_id).unwrap();
+    match name {
+        Some(name) => instance.name = name,
+        None if preserve_missing_names => instance.clear_name(),
+
+        // Items with no Name keep the ClassName they were created with as
+        // their name, which matches rbx_binary.
+        None => {}
+    }
     instance.properties = properties;
 
     Ok(())
 }
 
+/// Takes the `Name` property out of an Item's properties, if it has one.
+///
+/// Items with no Name are named after their ClassName, which matches
+/// rbx_binary. Encoding with `EncodeOptions::omit_default_names` relies on this
+/// to give them back the same name.
+fn take_name<R: Read>(
+    reader: &XmlEventReader<R>,
+    properties: &mut IndexMap<String, Variant>,
+) -> Result<Option<String>, DecodeError> {
+    match properties.shift_remove("Name") {
+        Some(Variant::String(value)) => Ok(Some(value.to_string())),
+        Some(value) => Err(reader.error(DecodeErrorDetail::NameMustBeString(value.ty()))),
+        None => Ok(None),
+    }
+}
+
+/// Normalizes an Item's Content values and deduplicates its String and Content
+/// values, if the options ask for it.
+fn finish_properties(state: &mut ParseState, properties: &mut IndexMap<String, Variant>) {
+    if let Some(normalizer) = &mut state.content_normalizer {
+        for value in properties.values_mut() {
+            if let Variant::Content(content) = value {
+                *content = normalizer.normalize(content.clone());
+            }
+        }
+    }
+
+    if let Some(interner) = &mut state.string_interner {
+        for value in properties.values_mut() {
+            interner.intern(value);
+        }
+    }
+}
+
+/// Some third-party exporters write an extra `</Properties>` tag after an
+/// empty `<Properties/>` element. Those tags are stripped out before the
+/// document reaches xml-rs, so here we decide what to do about one that came
+/// right after the Properties element we just read.
+fn check_extra_end_tag<R: Read>(
+    reader: &mut XmlEventReader<R>,
+    state: &mut ParseState,
+) -> Result<(), DecodeError> {
+    let position = reader.text_position();
+
+    // The extra tag sits between the Properties element and whatever comes
+    // next, so peeking at the next event moves the filter past it.
+    reader.expect_peek()?;
+
+    let removed = state.extra_end_tags.get();
+    if removed == state.extra_end_tags_handled {
+        return Ok(());
+    }
+
+    state.extra_end_tags_handled = removed;
+
+    let error = DecodeError::new_at(DecodeErrorDetail::ExtraPropertiesEndTag, position);
+    if !state.options.recover {
+        return Err(error);
+    }
+
+    log::warn!("Ignoring an extra </Properties> tag: {}", error);
+
+    state.diagnostics.push(Diagnostic {
+        message: "Ignored an extra </Properties> tag after an empty <Properties/> element"
+            .to_owned(),
+        line: error.line(),
+        column: error.column(),
+        kind: error.kind(),
+        instance: None,
+        property_name: None,
+    });
+
+    Ok(())
+}
+
 fn deserialize_properties<R: Read>(
     reader: &mut XmlEventReader<R>,
     state: &mut ParseState,
     instance_id: Ref,
-    props: &mut HashMap<String, Variant>,
+    props: &mut IndexMap<String, Variant>,
 ) -> Result<(), DecodeError> {
     reader.expect_start_with_name("Properties")?;
 
@@ -493,18 +1992,11 @@ fn deserialize_properties<R: Read>(
                 XmlReadEvent::StartElement {
                     name, attributes, ..
                 } => {
-                    let mut xml_property_name = None;
-
-                    for attribute in attributes {
-                        if attribute.name.local_name.as_str() == "name" {
-                            xml_property_name = Some(attribute.value.to_owned());
-                            break;
+                    let xml_property_name = match find_attribute(attributes, "name") {
+                        Some(value) => value.to_owned(),
+                        None => {
+                            return Err(reader.error(DecodeErrorDetail::MissingAttribute("name")))
                         }
-                    }
-
-                    let xml_property_name = match xml_property_name {
-                        Some(value) => value,
-                        None => return Err(reader.error(DecodeErrorKind::MissingAttribute("name"))),
                     };
 
                     (name.local_name.to_owned(), xml_property_name)
@@ -514,12 +2006,12 @@ fn deserialize_properties<R: Read>(
                         reader.expect_next()?;
                         return Ok(());
                     } else {
-                        let err = DecodeErrorKind::UnexpectedXmlEvent(reader.expect_next()?);
+                        let err = DecodeErrorDetail::UnexpectedXmlEvent(reader.expect_next()?);
                         return Err(reader.error(err));
                     }
                 }
                 _ => {
-                    let err = DecodeErrorKind::UnexpectedXmlEvent(reader.expect_next()?);
+                    let err = DecodeErrorDetail::UnexpectedXmlEvent(reader.expect_next()?);
                     return Err(reader.error(err));
                 }
             }
@@ -532,92 +2024,273 @@ fn deserialize_properties<R: Read>(
             xml_type_name
         );
 
-        let maybe_descriptor = if state.options.use_reflection() {
-            find_canonical_property_descriptor(&class_name, &xml_property_name)
-        } else {
-            None
+        if !state.options.recover_from_property_errors {
+            deserialize_property(
+                reader,
+                state,
+                instance_id,
+                &class_name,
+                &xml_type_name,
+                xml_property_name,
+                props,
+            )?;
+            continue;
+        }
+
+        let mark = reader.mark();
+        let referent_rewrites = state.referent_rewrites.len();
+        let shared_string_rewrites = state.shared_string_rewrites.len();
+
+        let result = deserialize_property(
+            reader,
+            state,
+            instance_id,
+            &class_name,
+            &xml_type_name,
+            xml_property_name.clone(),
+            props,
+        );
+
+        match result {
+            // Unknown properties are only an error because the options asked
+            // for one, so they aren't something to recover from.
+            Err(err) if err.is_recoverable() && err.kind() != DecodeErrorKind::UnknownProperty => {
+                log::warn!(
+                    "Skipping property {}.{} that could not be read: {}",
+                    class_name,
+                    xml_property_name,
+                    err
+                );
+
+                state.recovered = true;
+                state.diagnostics.push(Diagnostic::from_property_error(
+                    &err,
+                    instance_id,
+                    xml_property_name,
+                ));
+
+                // A Ref or SharedString that was read before the error left a
+                // placeholder behind that would otherwise be filled in later.
+                state.referent_rewrites.truncate(referent_rewrites);
+                state
+                    .shared_string_rewrites
+                    .truncate(shared_string_rewrites);
+
+                reader.skip_rest_of_element(mark)?;
+            }
+            result => result?,
+        }
+    }
+}
+
+/// Replaces an enum value from an older file with the one that replaced it,
+/// if the reflection database lists one, and records the change.
+fn migrate_enum_value(
+    state: &mut ParseState,
+    instance_id: Ref,
+    property_name: &str,
+    enum_name: &str,
+    original: Enum,
+) -> Variant {
+    let migrated = rbx_reflection_database::get()
+        .enums
+        .get(enum_name)
+        .and_then(|descriptor| descriptor.migrate_value(original.to_u32()));
+
+    match migrated {
+        Some(migrated) => {
+            state.migrated_enum_values.push(MigratedEnumValue {
+                instance: instance_id,
+                property_name: property_name.to_owned(),
+                enum_name: enum_name.to_owned(),
+                original_value: original.to_u32(),
+                migrated_value: migrated,
+            });
+
+            Variant::Enum(Enum::from_u32(migrated))
+        }
+        None => Variant::Enum(original),
+    }
+}
+
+/// Deserializes the value of one property of an instance, whose type and name
+/// have already been peeked at.
+fn deserialize_property<R: Read>(
+    reader: &mut XmlEventReader<R>,
+    state: &mut ParseState,
+    instance_id: Ref,
+    class_name: &str,
+    xml_type_name: &str,
+    xml_property_name: String,
+    props: &mut IndexMap<String, Variant>,
+) -> Result<(), DecodeError> {
+    let maybe_descriptor = if state.options.use_reflection() {
+        find_canonical_property_descriptor(class_name, &xml_property_name)
+    } else {
+        None
+    };
+
+    if let Some(descriptor) = maybe_descriptor {
+        let xml_value =
+            read_value_xml(reader, state, xml_type_name, instance_id, &descriptor.name)?;
+
+        // The property descriptor might specify a different type than the
+        // one we saw in the XML.
+        //
+        // This happens when property types are upgraded or if the
+        // serialized data type is different than the canonical one.
+        //
+        // For example:
+        // - Int/Float widening from 32-bit to 64-bit
+        // - BrickColor properties turning into Color3
+        // - Vector3int16 and Color3uint8 values in old files widening into
+        //   Vector3 and Color3
+        let expected_type = match &descriptor.data_type {
+            DataType::Value(data_type) => *data_type,
+            DataType::Enum(_enum_name) => VariantType::Enum,
+
+            // FIXME?
+            _ => unimplemented!(),
         };
 
-        if let Some(descriptor) = maybe_descriptor {
-            let xml_value =
-                read_value_xml(reader, state, &xml_type_name, instance_id, &descriptor.name)?;
-
-            // The property descriptor might specify a different type than the
-            // one we saw in the XML.
-            //
-            // This happens when property types are upgraded or if the
-            // serialized data type is different than the canonical one.
-            //
-            // For example:
-            // - Int/Float widening from 32-bit to 64-bit
-            // - BrickColor properties turning into Color3
-            let expected_type = match &descriptor.data_type {
-                DataType::Value(data_type) => *data_type,
-                DataType::Enum(_enum_name) => VariantType::Enum,
-
-                // FIXME?
-                _ => unimplemented!(),
-            };
-
-            let value = match xml_value.try_convert_ref(expected_type) {
-                // In this case, the property descriptor disagreed with the type
-                // in the file, but there was a conversion available.
-                TodoValueConversionType::Converted(value) => value,
-
-                // The property descriptor agreed with the type from the file,
-                // or the type in the descriptor was unknown and the
-                // deserializer is configured to ignore those issues
-                TodoValueConversionType::Unnecessary => xml_value,
-
-                // The property descriptor disagreed, and there was no
-                // conversion available. This is always an error.
-                TodoValueConversionType::Failed => {
-                    return Err(
-                        reader.error(DecodeErrorKind::UnsupportedPropertyConversion {
-                            class_name: class_name.clone(),
-                            property_name: descriptor.name.to_string(),
-                            expected_type,
-                            actual_type: xml_value.ty(),
-                        }),
-                    );
-                }
-            };
+        // Old files store some colors, like `BasePart.BrickColor`, as
+        // BrickColor palette numbers. Those properties are now aliases for
+        // Color3 properties, so we swap in the color the number stood for.
+        if is_legacy_brick_color(class_name, &xml_property_name) {
+            if let Some(value) = migrate_brick_color(&xml_value, expected_type) {
+                // Files can contain both the legacy property and the one
+                // that replaced it, in which case the newer one wins.
+                props.entry(descriptor.name.to_string()).or_insert(value);
+                return Ok(());
+            }
+        }
 
-            props.insert(descriptor.name.to_string(), value);
-        } else {
-            match state.options.property_behavior {
-                DecodePropertyBehavior::IgnoreUnknown => {
-                    // We don't care about this property, so we can read it and
-                    // throw it into the void.
-
-                    read_value_xml(
-                        reader,
-                        state,
-                        &xml_type_name,
-                        instance_id,
-                        &xml_property_name,
-                    )?;
-                }
-                DecodePropertyBehavior::ReadUnknown | DecodePropertyBehavior::NoReflection => {
-                    // We'll take this value as-is with no conversions on either
-                    // the name or value.
-
-                    let value = read_value_xml(
-                        reader,
-                        state,
-                        &xml_type_name,
-                        instance_id,
-                        &xml_property_name,
-                    )?;
-                    props.insert(xml_property_name, value);
-                }
-                DecodePropertyBehavior::ErrorOnUnknown => {
-                    return Err(reader.error(DecodeErrorKind::UnknownProperty {
-                        class_name,
-                        property_name: xml_property_name,
-                    }));
-                }
+        let value = match xml_value.try_convert_ref(expected_type) {
+            // In this case, the property descriptor disagreed with the type
+            // in the file, but there was a conversion available.
+            TodoValueConversionType::Converted(value) => value,
+
+            // Only narrowing conversions clamp, and decoding never narrows.
+            TodoValueConversionType::Clamped(_) => unreachable!(),
+
+            // The property descriptor agreed with the type from the file,
+            // or the type in the descriptor was unknown and the
+            // deserializer is configured to ignore those issues
+            TodoValueConversionType::Unnecessary => xml_value,
+
+            // The property descriptor disagreed, and there was no
+            // conversion available. This is always an error.
+            TodoValueConversionType::Failed => {
+                return Err(
+                    reader.error(DecodeErrorDetail::UnsupportedPropertyConversion {
+                        class_name: class_name.to_owned(),
+                        property_name: descriptor.name.to_string(),
+                        expected_type,
+                        actual_type: xml_value.ty(),
+                    }),
+                );
+            }
+        };
+
+        let value = match (&descriptor.data_type, value) {
+            (DataType::Enum(enum_name), Variant::Enum(original))
+                if state.options.migrate_enum_values =>
+            {
+                migrate_enum_value(state, instance_id, &descriptor.name, enum_name, original)
+            }
+            (_, value) => value,
+        };
+
+        props.insert(descriptor.name.to_string(), value);
+    } else if state.options.drop_unserialized_properties
+        && state.options.use_reflection()
+        && is_unserialized_property(class_name, &xml_property_name)
+    {
+        // The value still has to be read to get past it, but Refs and
+        // SharedStrings read this way leave placeholders behind that need
+        // to be cleaned up.
+        let referent_rewrites = state.referent_rewrites.len();
+        let shared_string_rewrites = state.shared_string_rewrites.len();
+
+        read_value_xml(
+            reader,
+            state,
+            xml_type_name,
+            instance_id,
+            &xml_property_name,
+        )?;
+
+        state.referent_rewrites.truncate(referent_rewrites);
+        state
+            .shared_string_rewrites
+            .truncate(shared_string_rewrites);
+    } else {
+        match state.options.property_behavior {
+            DecodePropertyBehavior::IgnoreUnknown => {
+                // We don't care about this property, so we can read it and
+                // throw it into the void.
+
+                read_value_xml(
+                    reader,
+                    state,
+                    xml_type_name,
+                    instance_id,
+                    &xml_property_name,
+                )?;
+            }
+            DecodePropertyBehavior::ReadUnknown | DecodePropertyBehavior::NoReflection => {
+                // We'll take this value as-is with no conversions on either
+                // the name or value.
+
+                let value = read_value_xml(
+                    reader,
+                    state,
+                    xml_type_name,
+                    instance_id,
+                    &xml_property_name,
+                )?;
+                props.insert(xml_property_name, value);
+            }
+            DecodePropertyBehavior::ErrorOnUnknown => {
+                return Err(reader.error(DecodeErrorDetail::UnknownProperty {
+                    class_name: class_name.to_owned(),
+                    property_name: xml_property_name,
+                }));
             }
         }
     }
+
+    Ok(())
+}
+
+/// Converts a BrickColor palette number into the color type that replaced it,
+/// or returns `None` if either the value or the new type isn't one we know how
+/// to migrate.
+fn migrate_brick_color(value: &Variant, expected_type: VariantType) -> Option<Variant> {
+    let number = match value {
+        Variant::Int32(number) => *number,
+        Variant::BrickColor(brick_color) => *brick_color as i32,
+        _ => return None,
+    };
+
+    // Roblox treats BrickColor numbers it doesn't recognize as Medium stone
+    // grey, so we do too.
+    let brick_color = u16::try_from(number)
+        .ok()
+        .and_then(BrickColor::from_number)
+        .unwrap_or_else(|| {
+            log::warn!(
+                "Unknown BrickColor number {}, using Medium stone grey",
+                number
+            );
+            BrickColor::MediumStoneGrey
+        });
+
+    // Newer files store these colors as Color3uint8, which are widened into
+    // whatever type the property has now, so we do the same here.
+    match expected_type {
+        VariantType::Color3 => Some(Color3::from(brick_color.to_color3uint8()).into()),
+        VariantType::Color3uint8 => Some(brick_color.to_color3uint8().into()),
+        _ => None,
+    }
 }
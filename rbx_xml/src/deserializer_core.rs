@@ -1,11 +1,27 @@
-use std::io::Read;
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::VecDeque,
+    io::{self, Read},
+    rc::Rc,
+    str::Chars,
+    sync::Arc,
+};
 
 use log::trace;
-use xml::{attribute::OwnedAttribute, reader::ParserConfig};
+use rbx_dom_weak::types::{Blob, BlobStore};
+use xml::{
+    attribute::OwnedAttribute,
+    common::{Position, TextPosition},
+    namespace::Namespace,
+    reader::ParserConfig,
+    writer::{EmitterConfig, XmlEvent as XmlWriteEvent},
+};
 
 use crate::{
     core::XmlType,
-    error::{DecodeError as NewDecodeError, DecodeErrorKind},
+    error::{DecodeError as NewDecodeError, DecodeErrorDetail},
+    processing_instruction::ProcessingInstruction,
 };
 
 pub use xml::reader::Error as XmlReadError;
@@ -17,24 +33,116 @@ pub struct XmlEventReader<R: Read> {
     reader: xml::EventReader<R>,
     peeked: Option<Result<XmlReadEvent, xml::reader::Error>>,
     finished: bool,
+
+    /// The whitespace that was skipped right before the most recent event
+    /// pulled from xml-rs, if any.
+    skipped_whitespace: Option<String>,
+
+    /// The number of elements that have been opened by events consumed from
+    /// this reader, but not yet closed.
+    depth: usize,
+
+    /// The number of elements that have been opened by events consumed from
+    /// this reader, including ones that have since been closed.
+    started: usize,
+
+    /// The processing instructions that have been skipped over, which haven't
+    /// been taken with
+    /// [`take_processing_instructions`][Self::take_processing_instructions]
+    /// yet.
+    processing_instructions: Vec<SkippedInstruction>,
+}
+
+/// A processing instruction that [`XmlEventReader`] skipped over, along with
+/// where it was found.
+#[derive(Debug, Clone)]
+pub(crate) struct SkippedInstruction {
+    pub instruction: ProcessingInstruction,
+
+    /// The number of elements that were open around the instruction.
+    pub depth: usize,
+    pub position: TextPosition,
+}
+
+/// A position between two elements, recorded by
+/// [`XmlEventReader::mark`] so that whatever is left of the element after it
+/// can be skipped later.
+#[derive(Debug, Clone, Copy)]
+pub struct ElementMark {
+    depth: usize,
+    started: usize,
 }
 
 impl<R: Read> Iterator for XmlEventReader<R> {
     type Item = XmlReadResult;
 
     fn next(&mut self) -> Option<XmlReadResult> {
-        if let Some(value) = self.peeked.take() {
-            return Some(value);
+        let next = match self.peeked.take() {
+            Some(value) => Some(value),
+            None => self.next_from_source(),
+        };
+
+        match &next {
+            Some(Ok(XmlReadEvent::StartElement { .. })) => {
+                self.depth += 1;
+                self.started += 1;
+            }
+            Some(Ok(XmlReadEvent::EndElement { .. })) => self.depth = self.depth.saturating_sub(1),
+            _ => {}
         }
 
+        next
+    }
+}
+
+impl<R: Read> XmlEventReader<R> {
+    /// Constructs a new `XmlEventReader` from a source that implements `Read`.
+    pub fn from_source(source: R) -> XmlEventReader<R> {
+        let reader = ParserConfig::new()
+            .ignore_comments(true)
+            .create_reader(source);
+
+        XmlEventReader {
+            reader,
+            peeked: None,
+            finished: false,
+            skipped_whitespace: None,
+            depth: 0,
+            started: 0,
+            processing_instructions: Vec::new(),
+        }
+    }
+
+    /// Pulls the next event out of xml-rs, skipping over whitespace and
+    /// processing instructions.
+    fn next_from_source(&mut self) -> Option<XmlReadResult> {
         if self.finished {
             return None;
         }
 
+        self.skipped_whitespace = None;
+
         loop {
             match self.reader.next() {
                 Ok(item) => match item {
-                    XmlReadEvent::Whitespace(_) => continue,
+                    XmlReadEvent::Whitespace(text) => {
+                        self.skipped_whitespace
+                            .get_or_insert_with(String::new)
+                            .push_str(&text);
+                        continue;
+                    }
+                    // Processing instructions can go anywhere that whitespace
+                    // can, so they're set aside for whoever's reading the
+                    // document to deal with instead of showing up in its
+                    // events.
+                    XmlReadEvent::ProcessingInstruction { name, data } => {
+                        self.processing_instructions.push(SkippedInstruction {
+                            instruction: ProcessingInstruction { name, data },
+                            depth: self.depth,
+                            position: self.reader.position(),
+                        });
+                        continue;
+                    }
                     XmlReadEvent::EndDocument => {
                         self.finished = true;
                         return Some(Ok(item));
@@ -48,21 +156,6 @@ impl<R: Read> Iterator for XmlEventReader<R> {
             }
         }
     }
-}
-
-impl<R: Read> XmlEventReader<R> {
-    /// Constructs a new `XmlEventReader` from a source that implements `Read`.
-    pub fn from_source(source: R) -> XmlEventReader<R> {
-        let reader = ParserConfig::new()
-            .ignore_comments(true)
-            .create_reader(source);
-
-        XmlEventReader {
-            reader,
-            peeked: None,
-            finished: false,
-        }
-    }
 
     /// Borrows the next element from the event stream without consuming it.
     pub fn peek(&mut self) -> Option<&XmlReadResult> {
@@ -70,11 +163,27 @@ impl<R: Read> XmlEventReader<R> {
             return self.peeked.as_ref();
         }
 
-        self.peeked = self.next();
+        self.peeked = self.next_from_source();
         self.peeked.as_ref()
     }
 
-    pub(crate) fn error<T: Into<DecodeErrorKind>>(&self, kind: T) -> NewDecodeError {
+    /// Borrows the source that this reader is pulling bytes from.
+    pub fn source(&self) -> &R {
+        self.reader.source()
+    }
+
+    /// Takes the processing instructions that have been skipped over since the
+    /// last time this was called.
+    pub(crate) fn take_processing_instructions(&mut self) -> Vec<SkippedInstruction> {
+        std::mem::take(&mut self.processing_instructions)
+    }
+
+    /// The position in the document of the events consumed so far.
+    pub fn text_position(&self) -> TextPosition {
+        self.reader.position()
+    }
+
+    pub(crate) fn error<T: Into<DecodeErrorDetail>>(&self, kind: T) -> NewDecodeError {
         NewDecodeError::new_from_reader(kind.into(), &self.reader)
     }
 
@@ -82,7 +191,7 @@ impl<R: Read> XmlEventReader<R> {
         match self.next() {
             Some(Ok(event)) => Ok(event),
             Some(Err(err)) => Err(self.error(err)),
-            None => Err(self.error(DecodeErrorKind::UnexpectedEof)),
+            None => Err(self.error(DecodeErrorDetail::UnexpectedEof)),
         }
     }
 
@@ -105,7 +214,7 @@ impl<R: Read> XmlEventReader<R> {
         match peeked_value {
             Some(Ok(event)) => Ok(event),
             Some(Err(_)) => Err(self.expect_next().unwrap_err()),
-            None => Err(self.error(DecodeErrorKind::UnexpectedEof)),
+            None => Err(self.error(DecodeErrorDetail::UnexpectedEof)),
         }
     }
 
@@ -127,12 +236,12 @@ impl<R: Read> XmlEventReader<R> {
                         attributes,
                         namespace,
                     };
-                    return Err(self.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    return Err(self.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
 
                 Ok(attributes)
             }
-            event => Err(self.error(DecodeErrorKind::UnexpectedXmlEvent(event))),
+            event => Err(self.error(DecodeErrorDetail::UnexpectedXmlEvent(event))),
         }
     }
 
@@ -144,12 +253,12 @@ impl<R: Read> XmlEventReader<R> {
         match &event {
             XmlReadEvent::EndElement { name, .. } => {
                 if name.local_name != expected_name {
-                    return Err(self.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    return Err(self.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
 
                 Ok(())
             }
-            _ => Err(self.error(DecodeErrorKind::UnexpectedXmlEvent(event))),
+            _ => Err(self.error(DecodeErrorDetail::UnexpectedXmlEvent(event))),
         }
     }
 
@@ -167,25 +276,30 @@ impl<R: Read> XmlEventReader<R> {
 
         match self.peek() {
             // If the next event is a `Characters` or `CData` event, we need to
-            // use `next` to take ownership over it (with some careful unwraps)
-            // and extract the data out of it.
+            // use `next` to take ownership over it and extract the data out of
+            // it.
             //
             // We could also clone the borrowed data obtained from peek, but
             // some of the character events can contain several megabytes of
             // data, so a copy is really expensive.
             Some(Ok(XmlReadEvent::Characters(_))) | Some(Ok(XmlReadEvent::CData(_))) => {
-                match self.next().unwrap().unwrap() {
-                    XmlReadEvent::Characters(value) | XmlReadEvent::CData(value) => Ok(Some(value)),
-                    _ => unreachable!(),
+                match self.next() {
+                    Some(Ok(XmlReadEvent::Characters(value)))
+                    | Some(Ok(XmlReadEvent::CData(value))) => Ok(Some(value)),
+                    _ => Err(self.error(DecodeErrorDetail::bug(
+                        "the event after a peeked text event wasn't text",
+                    ))),
                 }
             }
 
             // Since we can't use `?` (we have a `&Result` instead of a `Result`)
             // we have to do something similar to what it would do.
-            Some(Err(_)) => {
-                let kind = self.next().unwrap().unwrap_err();
-                Err(self.error(kind))
-            }
+            Some(Err(_)) => match self.next() {
+                Some(Err(kind)) => Err(self.error(kind)),
+                _ => Err(self.error(DecodeErrorDetail::bug(
+                    "the event after a peeked error wasn't an error",
+                ))),
+            },
 
             None | Some(Ok(_)) => Ok(None),
         }
@@ -208,10 +322,20 @@ impl<R: Read> XmlEventReader<R> {
     /// For complexity, performance, and correctness reasons, we switched from
     /// #1 to #2. However, this means we need to coalesce `Characters` and
     /// `CData` events ourselves.
+    ///
+    /// xml-rs reports text that's only whitespace as a `Whitespace` event,
+    /// which is normally skipped. When that text is all there is between the
+    /// start tag and the end tag, it's returned instead, since it's the value.
     pub fn read_characters(&mut self) -> Result<String, NewDecodeError> {
         let mut buffer = match self.read_one_characters_event()? {
             Some(buffer) => buffer,
-            None => return Ok(String::new()),
+            None => {
+                if let Some(Ok(XmlReadEvent::EndElement { .. })) = self.peek() {
+                    return Ok(self.skipped_whitespace.take().unwrap_or_default());
+                }
+
+                return Ok(String::new());
+            }
         };
 
         while let Some(piece) = self.read_one_characters_event()? {
@@ -236,6 +360,34 @@ impl<R: Read> XmlEventReader<R> {
         base64::decode(&contents).map_err(|e| self.error(e))
     }
 
+    /// Like [`read_base64_characters`][Self::read_base64_characters], but
+    /// contents that decode to more than `threshold` bytes are decoded a piece
+    /// at a time into `store` instead of into memory.
+    pub(crate) fn read_base64_characters_to_store(
+        &mut self,
+        store: &Arc<dyn BlobStore>,
+        threshold: usize,
+    ) -> Result<Base64Contents, NewDecodeError> {
+        let contents = self.read_characters()?;
+
+        let encoded_len = contents.chars().filter(|c| !c.is_whitespace()).count();
+        if encoded_len / 4 * 3 <= threshold {
+            let contents: String = contents.chars().filter(|c| !c.is_whitespace()).collect();
+            return base64::decode(&contents)
+                .map(Base64Contents::Buffer)
+                .map_err(|e| self.error(e));
+        }
+
+        let mut decoder = Base64Decoder::new(&contents);
+        match Blob::store(store, &mut decoder) {
+            Ok(blob) => Ok(Base64Contents::Stored(blob)),
+            Err(err) => match decoder.error.take() {
+                Some(base64_err) => Err(self.error(base64_err)),
+                None => Err(self.error(DecodeErrorDetail::BlobStore(err))),
+            },
+        }
+    }
+
     /// Reads a tag completely and returns its text content. This is intended
     /// for parsing simple tags where we don't care about the attributes or
     /// children, only the text value, for Vector3s and such, which are encoded
@@ -272,6 +424,114 @@ impl<R: Read> XmlEventReader<R> {
         Ok(value)
     }
 
+    /// The number of elements that have been opened but not closed by the
+    /// events consumed so far.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Consume events from the iterator until all elements deeper than the
+    /// given depth have been closed.
+    ///
+    /// This is used to get back to a known position in the document after an
+    /// error left us somewhere in the middle of an element.
+    pub fn skip_to_depth(&mut self, depth: usize) -> Result<(), NewDecodeError> {
+        while self.depth > depth {
+            self.expect_next()?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the current position, which must be between two elements, so
+    /// that the element after it can be skipped with
+    /// [`skip_rest_of_element`][Self::skip_rest_of_element].
+    pub fn mark(&self) -> ElementMark {
+        ElementMark {
+            depth: self.depth,
+            started: self.started,
+        }
+    }
+
+    /// Skips whatever hasn't been consumed yet of the element that followed
+    /// `mark`, whether none, some, or all of it has been consumed already.
+    ///
+    /// This is used to get past a property whose value couldn't be read,
+    /// which can fail before its start tag or partway through its contents.
+    pub fn skip_rest_of_element(&mut self, mark: ElementMark) -> Result<(), NewDecodeError> {
+        if self.started == mark.started {
+            self.eat_unknown_tag()
+        } else {
+            self.skip_to_depth(mark.depth)
+        }
+    }
+
+    /// Consume events from the iterator until we reach the end of the next tag,
+    /// returning everything that was consumed as XML.
+    pub fn read_raw_element(&mut self) -> Result<String, NewDecodeError> {
+        let mut writer = EmitterConfig::new()
+            .write_document_declaration(false)
+            .normalize_empty_elements(false)
+            .create_writer(Vec::new());
+        let mut depth = 0;
+
+        loop {
+            let event = self.expect_next()?;
+
+            match &event {
+                XmlReadEvent::StartElement { .. } => depth += 1,
+                XmlReadEvent::EndElement { .. } => depth -= 1,
+                _ => {}
+            }
+
+            let event = match &event {
+                // Elements carry every namespace that's in scope, including
+                // ones declared on <roblox>. Writing all of those would declare
+                // them again on this element, so we only keep the prefixes the
+                // element uses, which the element can't be read without.
+                XmlReadEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    let mut namespace = Namespace::empty();
+                    let names = std::iter::once(name).chain(attributes.iter().map(|a| &a.name));
+
+                    for name in names {
+                        if let (Some(prefix), Some(uri)) = (&name.prefix, &name.namespace) {
+                            if prefix != "xml" && prefix != "xmlns" {
+                                namespace.put(prefix.as_str(), uri.as_str());
+                            }
+                        }
+                    }
+
+                    Some(XmlWriteEvent::StartElement {
+                        name: name.borrow(),
+                        attributes: attributes
+                            .iter()
+                            .map(|attribute| attribute.borrow())
+                            .collect(),
+                        namespace: Cow::Owned(namespace),
+                    })
+                }
+                event => event.as_writer_event(),
+            };
+
+            if let Some(event) = event {
+                if writer.write(event).is_err() {
+                    return Err(self.error(DecodeErrorDetail::bug(
+                        "couldn't write an event that was just read",
+                    )));
+                }
+            }
+
+            if depth == 0 {
+                break;
+            }
+        }
+
+        String::from_utf8(writer.into_inner())
+            .map_err(|_| self.error(DecodeErrorDetail::bug("xml-rs wrote invalid UTF-8")))
+    }
+
     /// Consume events from the iterator until we reach the end of the next tag.
     pub fn eat_unknown_tag(&mut self) -> Result<(), NewDecodeError> {
         let mut depth = 0;
@@ -302,3 +562,365 @@ impl<R: Read> XmlEventReader<R> {
         Ok(())
     }
 }
+
+/// Finds the value of the attribute with the given name. Attributes can come in
+/// any order, and an attribute with a namespace prefix, like `xsi:version`, is
+/// never taken for the unprefixed one.
+pub fn find_attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.prefix.is_none() && attribute.name.local_name == name)
+        .map(|attribute| attribute.value.as_str())
+}
+
+/// Binary contents read by
+/// [`read_base64_characters_to_store`][XmlEventReader::read_base64_characters_to_store].
+pub(crate) enum Base64Contents {
+    Buffer(Vec<u8>),
+    Stored(Blob),
+}
+
+/// Decodes base64 text a piece at a time, skipping over whitespace.
+struct Base64Decoder<'a> {
+    chars: Chars<'a>,
+
+    /// Characters waiting to be decoded. Pieces are always a multiple of four
+    /// characters long, except for the last one, so that padding is only ever
+    /// found at the end.
+    piece: String,
+
+    /// Bytes decoded from the last piece that haven't been read yet, starting
+    /// at `position`.
+    decoded: Vec<u8>,
+    position: usize,
+
+    /// The error returned by the base64 crate, if there was one. `Read` can
+    /// only return an `io::Error`, so this is kept around for the caller.
+    error: Option<base64::DecodeError>,
+}
+
+impl<'a> Base64Decoder<'a> {
+    const PIECE_LEN: usize = 8 * 1024;
+
+    fn new(contents: &'a str) -> Self {
+        Base64Decoder {
+            chars: contents.chars(),
+            piece: String::with_capacity(Self::PIECE_LEN),
+            decoded: Vec::new(),
+            position: 0,
+            error: None,
+        }
+    }
+}
+
+impl Read for Base64Decoder<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.decoded.len() {
+            self.piece.clear();
+            self.piece.extend(
+                self.chars
+                    .by_ref()
+                    .filter(|c| !c.is_whitespace())
+                    .take(Self::PIECE_LEN),
+            );
+
+            self.decoded = match base64::decode(&self.piece) {
+                Ok(decoded) => decoded,
+                Err(err) => {
+                    let message = err.to_string();
+                    self.error = Some(err);
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+                }
+            };
+            self.position = 0;
+        }
+
+        let len = buf.len().min(self.decoded.len() - self.position);
+        buf[..len].copy_from_slice(&self.decoded[self.position..self.position + len]);
+        self.position += len;
+
+        Ok(len)
+    }
+}
+
+/// Removes the `</Properties>` tag that some third-party exporters write right
+/// after an empty `<Properties/>` element.
+///
+/// Files like this are accepted by Roblox Studio, but aren't well-formed XML,
+/// so xml-rs won't read them at all. Instead of handing xml-rs the broken
+/// document, we strip out the extra tags beforehand and count them, leaving it
+/// to the deserializer to decide whether that's acceptable.
+pub struct ExtraEndTagFilter<R> {
+    inner: R,
+
+    /// Bytes read from `inner` that haven't been handed out yet, starting at
+    /// `position`.
+    buffer: Vec<u8>,
+    position: usize,
+
+    /// Everything in `buffer` before this index has been checked for extra
+    /// end tags and can be handed out.
+    checked: usize,
+
+    /// Indices in `buffer` where an extra end tag was removed.
+    removed_at: VecDeque<usize>,
+
+    /// The number of removed tags that have been passed by the bytes handed
+    /// out so far, shared with the deserializer.
+    removed: Rc<Cell<usize>>,
+
+    eof: bool,
+}
+
+const EMPTY_PROPERTIES: &[u8] = b"<Properties/>";
+const PROPERTIES_END: &[u8] = b"</Properties>";
+
+/// The start and end of each kind of markup whose contents aren't part of the
+/// document's elements, and so can't hold an extra end tag.
+const SKIPPED: &[(&[u8], &[u8])] = &[
+    // CDATA sections
+    (b"<![CDATA[", b"]]>"),
+    // Comments
+    (b"<!--", b"-->"),
+    // Processing instructions
+    (b"<?", b"?>"),
+];
+
+impl<R: Read> ExtraEndTagFilter<R> {
+    pub fn new(inner: R) -> Self {
+        ExtraEndTagFilter {
+            inner,
+            buffer: Vec::new(),
+            position: 0,
+            checked: 0,
+            removed_at: VecDeque::new(),
+            removed: Rc::new(Cell::new(0)),
+            eof: false,
+        }
+    }
+
+    /// Returns a counter of how many extra end tags have been removed from the
+    /// part of the document read so far.
+    pub fn removed(&self) -> Rc<Cell<usize>> {
+        Rc::clone(&self.removed)
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reads more of the document from the inner reader, then checks as much
+    /// of it as possible.
+    fn fill(&mut self) -> io::Result<()> {
+        self.buffer.drain(..self.position);
+        self.checked -= self.position;
+        for index in &mut self.removed_at {
+            *index -= self.position;
+        }
+        self.position = 0;
+
+        let old_len = self.buffer.len();
+        self.buffer.resize(old_len + 8 * 1024, 0);
+        let len = self.inner.read(&mut self.buffer[old_len..])?;
+        self.buffer.truncate(old_len + len);
+
+        if len == 0 {
+            self.eof = true;
+        }
+
+        self.check();
+        Ok(())
+    }
+
+    /// Finds and removes extra end tags in the unchecked part of the buffer,
+    /// leaving the contents of CDATA sections, comments, and processing
+    /// instructions alone.
+    ///
+    /// Anything too close to the end of the buffer to be sure about is left
+    /// unchecked until we've read more.
+    fn check(&mut self) {
+        let mut index = self.checked;
+
+        loop {
+            index += match self.buffer[index..].iter().position(|&byte| byte == b'<') {
+                Some(offset) => offset,
+                None => break,
+            };
+
+            let rest = &self.buffer[index..];
+
+            let partial = is_partial(rest, EMPTY_PROPERTIES)
+                || SKIPPED.iter().any(|(start, _)| is_partial(rest, start));
+
+            if !self.eof && partial {
+                self.checked = index;
+                return;
+            }
+
+            let skipped = SKIPPED.iter().find(|(start, _)| rest.starts_with(start));
+
+            if let Some((start, end)) = skipped {
+                match find(&rest[start.len()..], end) {
+                    Some(offset) => index += start.len() + offset + end.len(),
+                    None if self.eof => break,
+                    None => {
+                        self.checked = index;
+                        return;
+                    }
+                }
+            } else if rest.starts_with(EMPTY_PROPERTIES) {
+                let after = index + EMPTY_PROPERTIES.len();
+                let tag_start = after
+                    + self.buffer[after..]
+                        .iter()
+                        .take_while(|byte| byte.is_ascii_whitespace())
+                        .count();
+                let candidate = &self.buffer[tag_start..];
+
+                if candidate.starts_with(PROPERTIES_END) {
+                    self.buffer
+                        .drain(tag_start..tag_start + PROPERTIES_END.len());
+                    self.removed_at.push_back(tag_start);
+                    index = tag_start;
+                } else if !self.eof && PROPERTIES_END.starts_with(candidate) {
+                    self.checked = index;
+                    return;
+                } else {
+                    index = after;
+                }
+            } else {
+                index += 1;
+            }
+        }
+
+        self.checked = self.buffer.len();
+    }
+}
+
+/// Tells whether `data` is cut off partway through `pattern`.
+fn is_partial(data: &[u8], pattern: &[u8]) -> bool {
+    data.len() < pattern.len() && pattern.starts_with(data)
+}
+
+pub(crate) fn find(data: &[u8], pattern: &[u8]) -> Option<usize> {
+    data.windows(pattern.len())
+        .position(|window| window == pattern)
+}
+
+impl<R: Read> Read for ExtraEndTagFilter<R> {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        if output.is_empty() {
+            return Ok(0);
+        }
+
+        while self.position == self.checked {
+            if self.eof {
+                // Anything removed at the very end of the document has been
+                // passed now, too.
+                self.removed.set(self.removed.get() + self.removed_at.len());
+                self.removed_at.clear();
+
+                return Ok(0);
+            }
+
+            self.fill()?;
+        }
+
+        let len = output.len().min(self.checked - self.position);
+        let end = self.position + len;
+        output[..len].copy_from_slice(&self.buffer[self.position..end]);
+
+        while let Some(&index) = self.removed_at.front() {
+            if index >= end {
+                break;
+            }
+
+            self.removed_at.pop_front();
+            self.removed.set(self.removed.get() + 1);
+        }
+
+        self.position = end;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A reader that only hands out one byte at a time, so that every pattern
+    /// ends up split across reads.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&first, rest)) if !output.is_empty() => {
+                    output[0] = first;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn filter(input: &str) -> (String, usize) {
+        let mut filter = ExtraEndTagFilter::new(Trickle(input.as_bytes()));
+        let removed = filter.removed();
+
+        let mut output = String::new();
+        filter.read_to_string(&mut output).unwrap();
+
+        (output, removed.get())
+    }
+
+    #[test]
+    fn removes_extra_tags() {
+        assert_eq!(
+            filter("<Item><Properties/>\n  </Properties></Item>"),
+            ("<Item><Properties/>\n  </Item>".to_owned(), 1)
+        );
+        assert_eq!(
+            filter("<Properties/></Properties><Properties/></Properties>"),
+            ("<Properties/><Properties/>".to_owned(), 2)
+        );
+        assert_eq!(
+            filter("<!-- x --><Properties/></Properties><?y?>"),
+            ("<!-- x --><Properties/><?y?>".to_owned(), 1)
+        );
+    }
+
+    #[test]
+    fn leaves_everything_else() {
+        for input in &[
+            "<Item><Properties/></Item>",
+            "<Item><Properties></Properties></Item>",
+            "<Properties/></Propertie",
+            "<Properties/>",
+            "<Properties",
+            "<![CDATA[<Properties/></Properties>]]>",
+            "<![CDATA[<Properties/></Properties>",
+            "<!-- <Properties/></Properties> -->",
+            "<!-- <Properties/></Properties>",
+            "<?note <Properties/></Properties>?>",
+            "<Properties/><!-- </Properties> -->",
+            "<!--->--><Properties/>",
+        ] {
+            assert_eq!(filter(input), (input.to_string(), 0));
+        }
+    }
+
+    #[test]
+    fn attributes_by_name() {
+        let mut reader = XmlEventReader::from_source(
+            "<Item xmlns:x='urn:x' x:class='Wrong' referent='RBX1' class='Part'/>".as_bytes(),
+        );
+        reader.expect_next().unwrap();
+        let attributes = reader.expect_start_with_name("Item").unwrap();
+
+        assert_eq!(find_attribute(&attributes, "class"), Some("Part"));
+        assert_eq!(find_attribute(&attributes, "referent"), Some("RBX1"));
+        assert_eq!(find_attribute(&attributes, "name"), None);
+    }
+}
@@ -3,7 +3,9 @@ use std::{
     io::{self, Read, Write},
 };
 
-use rbx_dom_weak::types::VariantType;
+use rbx_dom_weak::types::{Ref, VariantType};
+
+use crate::summary::SkipReason;
 
 /// An error that can occur when deserializing an XML-format model or place.
 #[derive(Debug)]
@@ -16,13 +18,15 @@ pub struct DecodeError {
 
 impl DecodeError {
     pub(crate) fn new_from_reader<R: Read>(
-        kind: DecodeErrorKind,
+        kind: DecodeErrorDetail,
         reader: &xml::EventReader<R>,
     ) -> DecodeError {
         use xml::common::Position;
 
-        let pos = reader.position();
+        DecodeError::new_at(kind, reader.position())
+    }
 
+    pub(crate) fn new_at(kind: DecodeErrorDetail, pos: xml::common::TextPosition) -> DecodeError {
         DecodeError {
             inner: Box::new(DecodeErrorImpl {
                 kind,
@@ -41,6 +45,62 @@ impl DecodeError {
     pub fn column(&self) -> usize {
         self.inner.column
     }
+
+    /// The category of problem that caused this error.
+    pub fn kind(&self) -> DecodeErrorKind {
+        self.inner.kind.kind()
+    }
+
+    /// Tells whether this error came from reading the document's source.
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        self.kind() == DecodeErrorKind::Io
+    }
+
+    /// Tells whether this error was caused by a document that isn't valid.
+    /// Decoding the same document again will fail the same way.
+    #[inline]
+    pub fn is_malformed(&self) -> bool {
+        matches!(
+            self.kind(),
+            DecodeErrorKind::MalformedDocument | DecodeErrorKind::MalformedValue
+        )
+    }
+
+    /// Tells whether this error was caused by a document that might be valid,
+    /// but uses something that this version of rbx_xml doesn't understand.
+    #[inline]
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self.kind(),
+            DecodeErrorKind::UnsupportedVersion
+                | DecodeErrorKind::UnsupportedType
+                | DecodeErrorKind::UnknownProperty
+        )
+    }
+
+    /// The version of a document that was too new to decode, if that's what
+    /// this error is about.
+    #[inline]
+    pub fn document_version(&self) -> Option<u32> {
+        match self.inner.kind {
+            DecodeErrorDetail::NewerDocVersion(version) => Some(version),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn detail(&self) -> &DecodeErrorDetail {
+        &self.inner.kind
+    }
+
+    /// Tells whether it's possible to keep reading the document after this
+    /// error. Errors from the XML parser itself leave the parser unusable.
+    pub(crate) fn is_recoverable(&self) -> bool {
+        !matches!(
+            self.inner.kind,
+            DecodeErrorDetail::Xml(_) | DecodeErrorDetail::UnexpectedEof
+        )
+    }
 }
 
 impl fmt::Display for DecodeError {
@@ -59,23 +119,69 @@ impl std::error::Error for DecodeError {
     }
 }
 
+/// The category of problem that caused a [`DecodeError`].
+///
+/// New categories may be added in minor releases, and errors may be moved
+/// into a new category that describes them better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The source couldn't be read, like when a file can't be read from disk,
+    /// or a value couldn't be written to the
+    /// [`blob_store`][crate::DecodeOptions::blob_store].
+    Io,
+
+    /// The document isn't well-formed XML, or its elements aren't arranged
+    /// like a Roblox model or place. This includes documents that end early
+    /// and items that are missing required attributes.
+    MalformedDocument,
+
+    /// A property's value couldn't be read, like a number that couldn't be
+    /// parsed or invalid base64.
+    MalformedValue,
+
+    /// The document declares a version of the format other than 4.
+    UnsupportedVersion,
+
+    /// A property has a type that rbx_xml doesn't know about, or that it
+    /// can't convert to the type the reflection database expects.
+    UnsupportedType,
+
+    /// A property isn't in the reflection database. This only happens when
+    /// decoding with
+    /// [`DecodePropertyBehavior::ErrorOnUnknown`][crate::DecodePropertyBehavior::ErrorOnUnknown].
+    UnknownProperty,
+
+    /// A Ref property refers to an Item that isn't in the document. This
+    /// only happens when decoding with
+    /// [`UnresolvedRefBehavior::Error`][crate::UnresolvedRefBehavior::Error].
+    UnresolvedRef,
+
+    /// The [`ref_provider`][crate::DecodeOptions::ref_provider] gave out a
+    /// Ref that was already taken, either by an instance in the dom being
+    /// decoded into or by another Item in the document.
+    DuplicateRef,
+}
+
 #[derive(Debug)]
 struct DecodeErrorImpl {
-    kind: DecodeErrorKind,
+    kind: DecodeErrorDetail,
     line: usize,
     column: usize,
 }
 
 #[derive(Debug)]
-pub(crate) enum DecodeErrorKind {
+pub(crate) enum DecodeErrorDetail {
     // Errors from other crates
     Xml(xml::reader::Error),
     ParseFloat(std::num::ParseFloatError),
     ParseInt(std::num::ParseIntError),
     DecodeBase64(base64::DecodeError),
+    BlobStore(io::Error),
 
     // Errors specific to rbx_xml
     WrongDocVersion(String),
+    NewerDocVersion(u32),
     UnexpectedEof,
     UnexpectedXmlEvent(xml::reader::XmlEvent),
     MissingAttribute(&'static str),
@@ -86,27 +192,92 @@ pub(crate) enum DecodeErrorKind {
     UnknownPropertyType(String),
     InvalidContent(&'static str),
     NameMustBeString(VariantType),
+    ExtraPropertiesEndTag,
+    LegacyElement(String),
     UnsupportedPropertyConversion {
         class_name: String,
         property_name: String,
         expected_type: VariantType,
         actual_type: VariantType,
     },
+    UnresolvedRef {
+        class_name: String,
+        property_name: String,
+        referent: String,
+    },
+    ConflictingSharedString {
+        key: String,
+        first_len: u64,
+        second_len: u64,
+    },
+    DuplicateRef(Ref),
+
+    /// Something that rbx_xml expected to always be true wasn't.
+    Bug(&'static str),
 }
 
-impl fmt::Display for DecodeErrorKind {
+impl DecodeErrorDetail {
+    /// Reports that something rbx_xml expected to always be true wasn't. This
+    /// panics in debug builds, so that the tests catch it, but is only an
+    /// error in release builds, so that a bad document can't bring down a
+    /// program that reads untrusted files.
+    pub(crate) fn bug(description: &'static str) -> Self {
+        debug_assert!(false, "rbx_xml bug: {}", description);
+        DecodeErrorDetail::Bug(description)
+    }
+
+    fn kind(&self) -> DecodeErrorKind {
+        use self::DecodeErrorDetail::*;
+
+        match self {
+            Xml(err) => match err.kind() {
+                xml::reader::ErrorKind::Io(_) => DecodeErrorKind::Io,
+                _ => DecodeErrorKind::MalformedDocument,
+            },
+            BlobStore(_) => DecodeErrorKind::Io,
+            UnexpectedEof
+            | UnexpectedXmlEvent(_)
+            | MissingAttribute(_)
+            | ExtraPropertiesEndTag
+            | LegacyElement(_)
+            | ConflictingSharedString { .. }
+            // We can't tell what caused a bug, but it was something about the
+            // document, since decoding the same one again will hit it again.
+            | Bug(_) => DecodeErrorKind::MalformedDocument,
+            ParseFloat(_) | ParseInt(_) | DecodeBase64(_) | InvalidContent(_)
+            | NameMustBeString(_) => DecodeErrorKind::MalformedValue,
+            WrongDocVersion(_) | NewerDocVersion(_) => DecodeErrorKind::UnsupportedVersion,
+            UnknownPropertyType(_) | UnsupportedPropertyConversion { .. } => {
+                DecodeErrorKind::UnsupportedType
+            }
+            UnknownProperty { .. } => DecodeErrorKind::UnknownProperty,
+            UnresolvedRef { .. } => DecodeErrorKind::UnresolvedRef,
+            DuplicateRef(_) => DecodeErrorKind::DuplicateRef,
+        }
+    }
+}
+
+impl fmt::Display for DecodeErrorDetail {
     fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
-        use self::DecodeErrorKind::*;
+        use self::DecodeErrorDetail::*;
 
         match self {
             Xml(err) => write!(output, "{}", err),
             ParseFloat(err) => write!(output, "{}", err),
             ParseInt(err) => write!(output, "{}", err),
             DecodeBase64(err) => write!(output, "{}", err),
+            BlobStore(err) => write!(output, "Could not write to the blob store: {}", err),
 
             WrongDocVersion(version) => {
                 write!(output, "Invalid version '{}', expected version 4", version)
             }
+            NewerDocVersion(version) => write!(
+                output,
+                "The document is version {}, which is newer than the version 4 that rbx_xml \
+                 understands. Decode with DecodeOptions::accept_newer_versions enabled to read \
+                 it anyway.",
+                version
+            ),
             UnexpectedEof => write!(output, "Unexpected end-of-file"),
             UnexpectedXmlEvent(event) => write!(output, "Unexpected XML event {:?}", event),
             MissingAttribute(attribute_name) => {
@@ -124,6 +295,19 @@ impl fmt::Display for DecodeErrorKind {
                 write!(output, "Unknown property type '{}'", prop_name)
             }
             InvalidContent(explain) => write!(output, "Invalid text content: {}", explain),
+            ExtraPropertiesEndTag => write!(
+                output,
+                "Found an extra </Properties> tag after an empty <Properties/> element. \
+                 Some third-party exporters write instances with no properties this way. \
+                 Decode with DecodeOptions::recover enabled to ignore the extra tag."
+            ),
+            LegacyElement(name) => write!(
+                output,
+                "Found a <{}> element, which rbx_xml doesn't understand. \
+                 Places saved before 2015 can contain elements like this. \
+                 Decode with DecodeOptions::legacy_elements enabled to keep it.",
+                name
+            ),
             NameMustBeString(ty) => write!(
                 output,
                 "The 'Name' property must be of type String, but it was {:?}",
@@ -139,21 +323,53 @@ impl fmt::Display for DecodeErrorKind {
                 "Property {}.{} is expected to be of type {:?}, but it was of type {:?}",
                 class_name, property_name, expected_type, actual_type
             ),
+            UnresolvedRef {
+                class_name,
+                property_name,
+                referent,
+            } => write!(
+                output,
+                "Property {}.{} refers to '{}', which isn't the referent of any Item in the \
+                 document",
+                class_name, property_name, referent
+            ),
+            ConflictingSharedString {
+                key,
+                first_len,
+                second_len,
+            } => write!(
+                output,
+                "The SharedString dictionary has two different entries with the key '{}', of {} \
+                 and {} bytes. Decode with DecodeOptions::recover enabled to keep the first one.",
+                key, first_len, second_len
+            ),
+            DuplicateRef(referent) => write!(
+                output,
+                "The RefProvider gave out the Ref {}, which was already taken",
+                referent
+            ),
+            Bug(description) => write!(
+                output,
+                "rbx_xml bug: {}. Please report this, along with the document that caused it.",
+                description
+            ),
         }
     }
 }
 
-impl std::error::Error for DecodeErrorKind {
+impl std::error::Error for DecodeErrorDetail {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        use self::DecodeErrorKind::*;
+        use self::DecodeErrorDetail::*;
 
         match self {
             Xml(err) => Some(err),
             ParseFloat(err) => Some(err),
             ParseInt(err) => Some(err),
             DecodeBase64(err) => Some(err),
+            BlobStore(err) => Some(err),
 
             WrongDocVersion(_)
+            | NewerDocVersion(_)
             | UnexpectedEof
             | UnexpectedXmlEvent(_)
             | MissingAttribute(_)
@@ -161,32 +377,38 @@ impl std::error::Error for DecodeErrorKind {
             | UnknownPropertyType(_)
             | InvalidContent(_)
             | NameMustBeString(_)
-            | UnsupportedPropertyConversion { .. } => None,
+            | ExtraPropertiesEndTag
+            | LegacyElement(_)
+            | UnsupportedPropertyConversion { .. }
+            | UnresolvedRef { .. }
+            | ConflictingSharedString { .. }
+            | DuplicateRef(_)
+            | Bug(_) => None,
         }
     }
 }
 
-impl From<xml::reader::Error> for DecodeErrorKind {
-    fn from(error: xml::reader::Error) -> DecodeErrorKind {
-        DecodeErrorKind::Xml(error)
+impl From<xml::reader::Error> for DecodeErrorDetail {
+    fn from(error: xml::reader::Error) -> DecodeErrorDetail {
+        DecodeErrorDetail::Xml(error)
     }
 }
 
-impl From<std::num::ParseFloatError> for DecodeErrorKind {
-    fn from(error: std::num::ParseFloatError) -> DecodeErrorKind {
-        DecodeErrorKind::ParseFloat(error)
+impl From<std::num::ParseFloatError> for DecodeErrorDetail {
+    fn from(error: std::num::ParseFloatError) -> DecodeErrorDetail {
+        DecodeErrorDetail::ParseFloat(error)
     }
 }
 
-impl From<std::num::ParseIntError> for DecodeErrorKind {
-    fn from(error: std::num::ParseIntError) -> DecodeErrorKind {
-        DecodeErrorKind::ParseInt(error)
+impl From<std::num::ParseIntError> for DecodeErrorDetail {
+    fn from(error: std::num::ParseIntError) -> DecodeErrorDetail {
+        DecodeErrorDetail::ParseInt(error)
     }
 }
 
-impl From<base64::DecodeError> for DecodeErrorKind {
-    fn from(error: base64::DecodeError) -> DecodeErrorKind {
-        DecodeErrorKind::DecodeBase64(error)
+impl From<base64::DecodeError> for DecodeErrorDetail {
+    fn from(error: base64::DecodeError) -> DecodeErrorDetail {
+        DecodeErrorDetail::DecodeBase64(error)
     }
 }
 
@@ -194,7 +416,7 @@ impl From<base64::DecodeError> for DecodeErrorKind {
 #[derive(Debug)]
 pub struct EncodeError {
     // This Box helps reduce the size of EncodeError a lot, which is important.
-    kind: Box<EncodeErrorKind>,
+    inner: Box<EncodeErrorImpl>,
 }
 
 impl EncodeError {
@@ -203,23 +425,97 @@ impl EncodeError {
         _writer: &xml::EventWriter<W>,
     ) -> EncodeError {
         EncodeError {
-            kind: Box::new(kind),
+            inner: Box::new(EncodeErrorImpl {
+                kind,
+                progress: None,
+            }),
+        }
+    }
+
+    /// Records how far encoding got before this error, if it came from the
+    /// output or from cancelling. Other errors don't depend on the output, so
+    /// they're left as-is.
+    pub(crate) fn with_progress(mut self, progress: EncodeProgress) -> EncodeError {
+        if self.is_io() || self.is_cancelled() {
+            self.inner.progress = Some(progress);
         }
+
+        self
+    }
+
+    /// Tells whether this error came from the output that the document was
+    /// being written to, like when a disk is full.
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        matches!(self.inner.kind, EncodeErrorKind::Io(_))
+    }
+
+    /// Tells whether encoding was stopped by the
+    /// [`progress`][crate::EncodeOptions::progress] callback.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.inner.kind, EncodeErrorKind::Cancelled)
+    }
+
+    /// The number of bytes written to the output before this error. This is
+    /// only available for IO errors and cancellation.
+    #[inline]
+    pub fn bytes_written(&self) -> Option<u64> {
+        self.inner
+            .progress
+            .as_ref()
+            .map(|progress| progress.bytes_written)
+    }
+
+    /// The number of instances whose properties were all written to the
+    /// output before this error. This is only available for IO errors and
+    /// cancellation.
+    #[inline]
+    pub fn instances_written(&self) -> Option<usize> {
+        self.inner
+            .progress
+            .as_ref()
+            .map(|progress| progress.instance_count)
+    }
+
+    /// The full name of the instance that was being written when this error
+    /// happened, if there was one. This is only available for IO errors and
+    /// cancellation.
+    #[inline]
+    pub fn failed_instance(&self) -> Option<&str> {
+        self.inner
+            .progress
+            .as_ref()
+            .and_then(|progress| progress.instance.as_deref())
     }
 }
 
 impl fmt::Display for EncodeError {
     fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
-        write!(output, "{}", self.kind)
+        write!(output, "{}", self.inner.kind)
     }
 }
 
 impl std::error::Error for EncodeError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        self.kind.source()
+        self.inner.kind.source()
     }
 }
 
+#[derive(Debug)]
+struct EncodeErrorImpl {
+    kind: EncodeErrorKind,
+    progress: Option<EncodeProgress>,
+}
+
+/// How far encoding got before an error happened.
+#[derive(Debug)]
+pub(crate) struct EncodeProgress {
+    pub bytes_written: u64,
+    pub instance_count: usize,
+    pub instance: Option<String>,
+}
+
 #[derive(Debug)]
 pub(crate) enum EncodeErrorKind {
     Io(io::Error),
@@ -236,6 +532,53 @@ pub(crate) enum EncodeErrorKind {
         expected_type: VariantType,
         actual_type: VariantType,
     },
+    RootProperty {
+        property_name: String,
+    },
+    RootPropertyNotHoistable {
+        property_name: String,
+    },
+    SkippedProperty {
+        instance: String,
+        property_name: String,
+        reason: SkipReason,
+    },
+    MisplacedProperty {
+        instance: String,
+        property_name: String,
+        suggestion: Option<String>,
+    },
+    UnsupportedAttributeType {
+        attribute: String,
+        ty: VariantType,
+    },
+    DuplicateSingleton {
+        class_name: String,
+        parent: String,
+    },
+    InvalidAttributeValue {
+        element: String,
+        attribute: String,
+        value: String,
+        character: char,
+    },
+    InvalidProcessingInstruction {
+        name: String,
+        reason: &'static str,
+    },
+    Cancelled,
+
+    /// Something that rbx_xml expected to always be true wasn't.
+    Bug(&'static str),
+}
+
+impl EncodeErrorKind {
+    /// Like [`DecodeErrorDetail::bug`], panics in debug builds but is only an
+    /// error in release builds.
+    pub(crate) fn bug(description: &'static str) -> Self {
+        debug_assert!(false, "rbx_xml bug: {}", description);
+        EncodeErrorKind::Bug(description)
+    }
 }
 
 impl fmt::Display for EncodeErrorKind {
@@ -267,6 +610,74 @@ impl fmt::Display for EncodeErrorKind {
                 "Property {}.{} is expected to be of type {:?}, but it was of type {:?}",
                 class_name, property_name, expected_type, actual_type
             ),
+            RootProperty { property_name } => write!(
+                output,
+                "The root instance has a property named {}, which can't be written to a place",
+                property_name
+            ),
+            RootPropertyNotHoistable { property_name } => write!(
+                output,
+                "The root instance has a property named {}, which can't be written as a <Meta> \
+                 element",
+                property_name
+            ),
+            SkippedProperty {
+                instance,
+                property_name,
+                reason,
+            } => write!(
+                output,
+                "Property {} on instance {} would be left out because {}",
+                property_name, instance, reason
+            ),
+            MisplacedProperty {
+                instance,
+                property_name,
+                suggestion,
+            } => {
+                write!(
+                    output,
+                    "Instance {} has a property named {}, which its class does not have",
+                    instance, property_name
+                )?;
+
+                match suggestion {
+                    Some(suggestion) => write!(output, "; {}", suggestion),
+                    None => Ok(()),
+                }
+            }
+            UnsupportedAttributeType { attribute, ty } => write!(
+                output,
+                "Attribute {} has type {:?}, which can't be stored in an attribute",
+                attribute, ty
+            ),
+            DuplicateSingleton { class_name, parent } => write!(
+                output,
+                "Found more than one {} in {}, which Roblox expects only one of",
+                class_name, parent
+            ),
+            InvalidAttributeValue {
+                element,
+                attribute,
+                value,
+                character,
+            } => write!(
+                output,
+                "Can't write {:?} as the {} attribute of a <{}> element, because XML can't \
+                 represent the character U+{:04X}",
+                value, attribute, element, *character as u32
+            ),
+            InvalidProcessingInstruction { name, reason } => write!(
+                output,
+                "Can't write the processing instruction {:?}, because {}",
+                name, reason
+            ),
+            Cancelled => write!(output, "Encoding was cancelled by the progress callback"),
+            Bug(description) => write!(
+                output,
+                "rbx_xml bug: {}. Please report this, along with the instances that caused it.",
+                description
+            ),
         }
     }
 }
@@ -281,14 +692,29 @@ impl std::error::Error for EncodeErrorKind {
 
             UnknownProperty { .. }
             | UnsupportedPropertyType(_)
-            | UnsupportedPropertyConversion { .. } => None,
+            | UnsupportedPropertyConversion { .. }
+            | RootProperty { .. }
+            | RootPropertyNotHoistable { .. }
+            | SkippedProperty { .. }
+            | MisplacedProperty { .. }
+            | UnsupportedAttributeType { .. }
+            | DuplicateSingleton { .. }
+            | InvalidAttributeValue { .. }
+            | InvalidProcessingInstruction { .. }
+            | Cancelled
+            | Bug(_) => None,
         }
     }
 }
 
 impl From<xml::writer::Error> for EncodeErrorKind {
     fn from(error: xml::writer::Error) -> EncodeErrorKind {
-        EncodeErrorKind::Xml(error)
+        // xml-rs wraps errors from the output, but we want them to look the
+        // same as any other IO error.
+        match error {
+            xml::writer::Error::Io(error) => EncodeErrorKind::Io(error),
+            error => EncodeErrorKind::Xml(error),
+        }
     }
 }
 
@@ -0,0 +1,50 @@
+use rbx_dom_weak::types::Ref;
+
+/// An element that rbx_xml doesn't understand, found directly inside
+/// `<roblox>` or an `<Item>`.
+///
+/// Places saved before 2015 can contain elements like this, such as the
+/// `<HashMap>` block of physics settings and `<Custom>` elements under
+/// Workspace. They're kept when decoding with
+/// [`DecodeOptions::legacy_elements`][crate::DecodeOptions::legacy_elements]
+/// and listed in
+/// [`DecodeSummary::legacy_elements`][crate::DecodeSummary::legacy_elements],
+/// and can be written back out with
+/// [`EncodeOptions::legacy_elements`][crate::EncodeOptions::legacy_elements].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LegacyElement {
+    pub(crate) parent: Option<Ref>,
+    pub(crate) index: usize,
+    pub(crate) name: String,
+    pub(crate) xml: String,
+}
+
+impl LegacyElement {
+    /// The instance that the element was found in, or `None` if it was
+    /// directly inside `<roblox>`.
+    #[inline]
+    pub fn parent(&self) -> Option<Ref> {
+        self.parent
+    }
+
+    /// The number of `<Item>` elements that came before this element in the
+    /// same parent.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The name of the element, like `HashMap`.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The element and everything inside it, as XML. Whitespace between
+    /// elements and comments are not kept.
+    #[inline]
+    pub fn xml(&self) -> &str {
+        &self.xml
+    }
+}
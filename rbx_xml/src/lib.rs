@@ -88,6 +88,23 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
+//! ## Malformed documents
+//! Decoding a document never panics, no matter how it's damaged: a bad
+//! document is a [`DecodeError`], or is skipped over when decoding with
+//! [`DecodeOptions::recover`]. The few functions that can panic, like
+//! [`decode_into`], only do so when they're called the wrong way, and say so in
+//! their documentation. The test suite checks this by decoding documents that
+//! have been cut short or damaged in every position.
+//!
+//! ## Line endings
+//! Strings keep their line endings exactly, so scripts written on Windows can
+//! be decoded and encoded again without every line changing. XML parsers are
+//! meant to turn `\r\n` and lone `\r` in a document into `\n`, so carriage
+//! returns are written as the character reference `&#13;`, which every parser,
+//! including Roblox's, reads back as `\r`. This is the same for `string` and
+//! `ProtectedString` values, and matches rbx_binary, which stores strings as
+//! they are.
+//!
 //! ## Configuration
 //! rbx_xml exposes no useful configuration yet, but there are methods that
 //! accept [`DecodeOptions`][DecodeOptions] and
@@ -105,14 +122,22 @@
 
 #![deny(missing_docs)]
 
+#[macro_use]
+mod instrument;
+
 mod compat;
 
 mod core;
 mod deserializer;
 mod deserializer_core;
 mod error;
+mod legacy_elements;
+mod peek;
+mod processing_instruction;
 mod serializer;
 mod serializer_core;
+mod singletons;
+mod summary;
 mod types;
 
 #[cfg(test)]
@@ -120,37 +145,164 @@ mod test_util;
 
 use std::io::{Read, Write};
 
-use rbx_dom_weak::{types::Ref, WeakDom};
+use rbx_dom_weak::{
+    types::{Ref, VariantType},
+    WeakDom,
+};
 
-use crate::{deserializer::decode_internal, serializer::encode_internal};
+use crate::{
+    deserializer::{decode_internal, decode_into_internal},
+    peek::peek_internal,
+    serializer::encode_internal,
+    types::SUPPORTED_TYPES,
+};
 
 pub use crate::{
-    deserializer::{DecodeOptions, DecodePropertyBehavior},
-    error::{DecodeError, EncodeError},
-    serializer::{EncodeOptions, EncodePropertyBehavior},
+    deserializer::{
+        DecodeOptions, DecodePropertyBehavior, InstanceStream, StreamEvent, StreamedInstance,
+        UnresolvedRefBehavior,
+    },
+    error::{DecodeError, DecodeErrorKind, EncodeError},
+    legacy_elements::LegacyElement,
+    peek::PeekInfo,
+    processing_instruction::ProcessingInstruction,
+    serializer::{
+        ChildOrder, EncodeOptions, EncodePropertyBehavior, OutputStyle, ProgressAction,
+        PropertyFilter, RootPropertyBehavior, SerializeProgress, SharedStringsPosition,
+        SingletonBehavior, XmlSerializer,
+    },
+    summary::{
+        ClampedProperty, DecodeSummary, EncodeProgressReport, EncodeSummary, MigratedEnumValue,
+        SkipReason, SkippedProperty, SourceLocation,
+    },
 };
 
 /// Decodes an XML-format model or place from something that implements the
 /// `std::io::Read` trait.
 pub fn from_reader<R: Read>(reader: R, options: DecodeOptions) -> Result<WeakDom, DecodeError> {
+    decode_internal(reader, options).map(|(tree, _summary)| tree)
+}
+
+/// Decodes an XML-format model or place from something that implements the
+/// `std::io::Read` trait, also returning a summary of what was read.
+pub fn from_reader_with_summary<R: Read>(
+    reader: R,
+    options: DecodeOptions,
+) -> Result<(WeakDom, DecodeSummary), DecodeError> {
     decode_internal(reader, options)
 }
 
 /// Decodes an XML-format model or place from something that implements the
 /// `std::io::Read` trait using the default decoder options.
 pub fn from_reader_default<R: Read>(reader: R) -> Result<WeakDom, DecodeError> {
-    decode_internal(reader, DecodeOptions::default())
+    decode_internal(reader, DecodeOptions::default()).map(|(tree, _summary)| tree)
 }
 
 /// Decodes an XML-format model or place from a string.
 pub fn from_str<S: AsRef<str>>(reader: S, options: DecodeOptions) -> Result<WeakDom, DecodeError> {
-    decode_internal(reader.as_ref().as_bytes(), options)
+    decode_internal(reader.as_ref().as_bytes(), options).map(|(tree, _summary)| tree)
 }
 
 /// Decodes an XML-format model or place from a string using the default decoder
 /// options.
 pub fn from_str_default<S: AsRef<str>>(reader: S) -> Result<WeakDom, DecodeError> {
     decode_internal(reader.as_ref().as_bytes(), DecodeOptions::default())
+        .map(|(tree, _summary)| tree)
+}
+
+/// Decodes an XML-format model or place into an existing tree, inserting each
+/// of its top-level instances under `parent` and returning their referents in
+/// order.
+///
+/// This is useful for decoding many files into one tree without building a
+/// new tree for each one. Ref properties are only resolved against instances
+/// from the same file, so files can't refer to each other's instances. Refs to
+/// instances that aren't in the file are set to null, or are an error with
+/// [`UnresolvedRefBehavior::Error`]. If the file has properties meant for the
+/// root of a place, like those written by
+/// [`RootPropertyBehavior::HoistIntoMeta`], they're put onto `parent`.
+///
+/// SharedStrings that are already in the tree are reused: values from the file
+/// with the same contents as one in the tree share its data, and SharedString
+/// properties whose key isn't in the file's SharedString dictionary are filled
+/// in from the tree's values when one of them has that key.
+///
+/// If decoding fails, any instances that were inserted are removed again.
+///
+/// ## Panics
+/// Panics if `parent` does not refer to an instance in the tree, or if a
+/// decoded instance is given a `Ref` that's already in it, which can only
+/// happen with a [`DecodeOptions::ref_provider`] that repeats Refs.
+pub fn decode_into<R: Read>(
+    tree: &mut WeakDom,
+    parent: Ref,
+    reader: R,
+    options: DecodeOptions,
+) -> Result<Vec<Ref>, DecodeError> {
+    decode_into_internal(tree, parent, reader, options).map(|(roots, _summary)| roots)
+}
+
+/// Decodes an XML-format model or place from something that implements the
+/// `std::io::Read` trait one instance at a time, without building a tree.
+///
+/// This is useful for scanning large files, like finding every Script in a
+/// place, since only the instances that are still being read are kept in
+/// memory. Refs and SharedStrings are reported as the raw referents and hashes
+/// written in the file, which the caller can resolve if it needs them. See
+/// [`InstanceStream`] for details.
+///
+/// ```
+/// use rbx_dom_weak::types::Variant;
+/// use rbx_xml::{DecodeOptions, StreamEvent};
+///
+/// let document = r#"
+///     <roblox version="4">
+///         <Item class="Script" referent="RBX1">
+///             <Properties>
+///                 <string name="Name">Main</string>
+///                 <ProtectedString name="Source">print("Hello!")</ProtectedString>
+///             </Properties>
+///         </Item>
+///     </roblox>
+/// "#;
+///
+/// for event in rbx_xml::decode_instances_iter(document.as_bytes(), DecodeOptions::new()) {
+///     if let StreamEvent::Instance(instance) = event? {
+///         assert_eq!(instance.name(), "Main");
+///         assert_eq!(
+///             instance.properties().get("Source"),
+///             Some(&Variant::from("print(\"Hello!\")")),
+///         );
+///     }
+/// }
+/// # Ok::<(), rbx_xml::DecodeError>(())
+/// ```
+pub fn decode_instances_iter<R: Read>(reader: R, options: DecodeOptions) -> InstanceStream<R> {
+    InstanceStream::new(reader, options)
+}
+
+/// Reads the metadata and top-level Items of an XML-format model or place,
+/// without decoding the rest of the document.
+///
+/// Only the class and Name of each top-level Item are read. Their other
+/// properties, their children, and the document's shared strings are skipped
+/// over without being parsed, so this is much faster
+/// than decoding the whole document.
+///
+/// The line and column of an error refer to a shortened copy of the
+/// document that only has the parts that are read.
+pub fn peek<R: Read>(reader: R) -> Result<PeekInfo, DecodeError> {
+    peek_internal(reader)
+}
+
+/// Lists every type of property value that rbx_xml can encode, taken from
+/// the same table that encoding uses. Properties holding any other type are
+/// skipped or are an error, depending on the [`EncodeOptions`].
+///
+/// Passing this to `ReflectionDatabase::unsupported_properties` lists the
+/// properties that rbx_xml can't write.
+pub fn supported_types() -> &'static [VariantType] {
+    SUPPORTED_TYPES
 }
 
 /// Serializes a subset of the given tree to an XML format model or place,
@@ -161,6 +313,19 @@ pub fn to_writer<W: Write>(
     ids: &[Ref],
     options: EncodeOptions,
 ) -> Result<(), EncodeError> {
+    encode_internal(writer, tree, ids, options)?;
+    Ok(())
+}
+
+/// Serializes a subset of the given tree to an XML format model or place,
+/// writing to something that implements the `std::io::Write` trait and
+/// returning a summary of what was written.
+pub fn to_writer_with_summary<W: Write>(
+    writer: W,
+    tree: &WeakDom,
+    ids: &[Ref],
+    options: EncodeOptions,
+) -> Result<EncodeSummary, EncodeError> {
     encode_internal(writer, tree, ids, options)
 }
 
@@ -172,5 +337,6 @@ pub fn to_writer_default<W: Write>(
     tree: &WeakDom,
     ids: &[Ref],
 ) -> Result<(), EncodeError> {
-    encode_internal(writer, tree, ids, EncodeOptions::default())
+    encode_internal(writer, tree, ids, EncodeOptions::default())?;
+    Ok(())
 }
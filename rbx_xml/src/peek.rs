@@ -0,0 +1,354 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+};
+
+use xml::common::TextPosition;
+
+use crate::{
+    core::ROOT_META_PROPERTIES,
+    deserializer::deserialize_document_start,
+    deserializer_core::{find, find_attribute, ExtraEndTagFilter, XmlEventReader, XmlReadEvent},
+    error::{DecodeError, DecodeErrorDetail},
+};
+
+/// What an XML model or place says about itself as a whole, read without
+/// decoding any of its instances' properties.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct PeekInfo {
+    pub(crate) metadata: HashMap<String, String>,
+    pub(crate) top_level: Vec<(String, String)>,
+    pub(crate) version: u32,
+}
+
+impl PeekInfo {
+    /// The document's metadata, like `ExplicitAutoJoints`, from its `Meta`
+    /// elements. Root properties written by
+    /// [`RootPropertyBehavior::HoistIntoMeta`][crate::RootPropertyBehavior::HoistIntoMeta]
+    /// aren't included.
+    #[inline]
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.metadata
+    }
+
+    /// The ClassName and Name of each of the document's top-level Items, in
+    /// order. For places, these are the services.
+    #[inline]
+    pub fn top_level(&self) -> &[(String, String)] {
+        &self.top_level
+    }
+
+    /// The `version` attribute of the document's `<roblox>` tag. Peeking
+    /// reads documents newer than rbx_xml understands, so that this can be
+    /// checked before decoding them.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+pub(crate) fn peek_internal<R: Read>(source: R) -> Result<PeekInfo, DecodeError> {
+    let mut document = Vec::new();
+    ExtraEndTagFilter::new(source)
+        .read_to_end(&mut document)
+        .map_err(io_error)?;
+
+    // Parsing is what takes most of the time when decoding, so the parts of
+    // the document that we don't need are cut out before xml-rs sees them.
+    let reduced = reduce(&document);
+
+    let mut reader = XmlEventReader::from_source(reduced.as_slice());
+    let version = deserialize_document_start(&mut reader, true)?;
+    let mut info = PeekInfo {
+        version,
+        ..PeekInfo::default()
+    };
+
+    loop {
+        match reader.expect_peek()? {
+            XmlReadEvent::StartElement { name, .. } => match name.local_name.as_str() {
+                "Meta" => {
+                    let name = {
+                        let attributes = reader.expect_start_with_name("Meta")?;
+
+                        find_attribute(&attributes, "name")
+                            .ok_or_else(|| {
+                                reader.error(DecodeErrorDetail::MissingAttribute("name"))
+                            })?
+                            .to_owned()
+                    };
+
+                    let value = reader.read_characters()?;
+                    reader.expect_end_with_name("Meta")?;
+
+                    if !ROOT_META_PROPERTIES.contains(&name.as_str()) {
+                        info.metadata.insert(name, value);
+                    }
+                }
+                "Item" => {
+                    let item = peek_item(&mut reader)?;
+                    info.top_level.push(item);
+                }
+                _ => reader.eat_unknown_tag()?,
+            },
+            XmlReadEvent::EndElement { .. } | XmlReadEvent::EndDocument => break,
+            _ => {
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+/// Reads the class and name of the next Item, skipping its other properties
+/// and its children.
+fn peek_item<R: Read>(reader: &mut XmlEventReader<R>) -> Result<(String, String), DecodeError> {
+    let class_name = {
+        let attributes = reader.expect_start_with_name("Item")?;
+
+        find_attribute(&attributes, "class")
+            .ok_or_else(|| reader.error(DecodeErrorDetail::MissingAttribute("class")))?
+            .to_owned()
+    };
+
+    let mut name = None;
+
+    loop {
+        match reader.expect_peek()? {
+            XmlReadEvent::StartElement { name: element, .. }
+                if element.local_name == "Properties" && name.is_none() =>
+            {
+                reader.expect_start_with_name("Properties")?;
+                name = Some(peek_name(reader)?);
+            }
+            XmlReadEvent::StartElement { .. } => reader.eat_unknown_tag()?,
+            XmlReadEvent::EndElement { .. } => {
+                reader.expect_end_with_name("Item")?;
+                break;
+            }
+            _ => {
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+            }
+        }
+    }
+
+    // Like the deserializer, Items without a name are named after their
+    // class.
+    let name = name.flatten().unwrap_or_else(|| class_name.clone());
+
+    Ok((class_name, name))
+}
+
+/// Looks for the Name property among the rest of an Item's properties,
+/// consuming the end of the `Properties` element.
+fn peek_name<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Option<String>, DecodeError> {
+    let mut name = None;
+
+    loop {
+        match reader.expect_peek()? {
+            XmlReadEvent::StartElement {
+                name: element,
+                attributes,
+                ..
+            } if element.local_name == "string"
+                && find_attribute(attributes, "name") == Some("Name") =>
+            {
+                name = Some(reader.read_tag_contents("string")?);
+            }
+            XmlReadEvent::StartElement { .. } => reader.eat_unknown_tag()?,
+            XmlReadEvent::EndElement { .. } => {
+                reader.expect_end_with_name("Properties")?;
+                return Ok(name);
+            }
+            _ => {
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+            }
+        }
+    }
+}
+
+/// Errors from reading the source happen before there's a position in the
+/// document to point to.
+fn io_error(err: io::Error) -> DecodeError {
+    DecodeError::new_at(DecodeErrorDetail::Xml(err.into()), TextPosition::new())
+}
+
+/// How `reduce` treats an element that's been opened but not closed yet.
+enum Element {
+    /// The element's tags are kept, but only some of its contents.
+    Kept,
+
+    /// The element is kept whole, starting at this offset.
+    Copied(usize),
+
+    /// The element is left out, or is inside an element that's copied whole.
+    Skipped,
+}
+
+/// Cuts a document down to what `peek` reads: the `<roblox>` element, its
+/// `Meta` elements, and its top-level Items with nothing in them but their
+/// Name property.
+///
+/// This only finds where each tag starts and ends, which is much faster than
+/// parsing the document. Anything that can't be made sense of is left for
+/// xml-rs to report.
+fn reduce(document: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut open: Vec<Element> = Vec::new();
+    let mut index = 0;
+
+    while let Some(offset) = document[index..].iter().position(|&byte| byte == b'<') {
+        let start = index + offset;
+        let rest = &document[start..];
+
+        let len = if rest.starts_with(b"<!--") {
+            find(rest, b"-->").map(|end| end + 3)
+        } else if rest.starts_with(b"<![CDATA[") {
+            find(rest, b"]]>").map(|end| end + 3)
+        } else if rest.starts_with(b"<?") {
+            find(rest, b"?>").map(|end| end + 2)
+        } else {
+            tag_len(rest)
+        };
+
+        let end = match len {
+            Some(len) => start + len,
+            None => {
+                output.extend_from_slice(rest);
+                return output;
+            }
+        };
+        let tag = &document[start..end];
+        index = end;
+
+        if tag.starts_with(b"<!") || tag.starts_with(b"<?") {
+            // Text and other markup is only kept outside of `<roblox>`, where
+            // the XML declaration is, or as part of an element that's copied.
+            if open.is_empty() {
+                output.extend_from_slice(tag);
+            }
+        } else if tag.starts_with(b"</") {
+            match open.pop() {
+                Some(Element::Kept) | None => output.extend_from_slice(tag),
+                Some(Element::Copied(start)) => output.extend_from_slice(&document[start..end]),
+                Some(Element::Skipped) => {}
+            }
+        } else {
+            let element = match open.last() {
+                None => Element::Kept,
+                Some(Element::Kept) => match (open.len(), tag_name(tag)) {
+                    (1, b"Meta") => Element::Copied(start),
+                    (1, b"Item") | (2, b"Properties") => Element::Kept,
+                    (3, b"string") if may_be_name(tag) => Element::Copied(start),
+                    _ => Element::Skipped,
+                },
+                Some(_) => Element::Skipped,
+            };
+
+            if tag.ends_with(b"/>") {
+                if let Element::Kept | Element::Copied(_) = element {
+                    output.extend_from_slice(tag);
+                }
+            } else {
+                if let Element::Kept = element {
+                    output.extend_from_slice(tag);
+                }
+
+                open.push(element);
+            }
+        }
+    }
+
+    output
+}
+
+/// The length of the tag at the start of `data`, which can have a `>` in a
+/// quoted attribute value.
+fn tag_len(data: &[u8]) -> Option<usize> {
+    let mut quote = None;
+
+    data.iter()
+        .position(|&byte| match quote {
+            Some(open) => {
+                if byte == open {
+                    quote = None;
+                }
+                false
+            }
+            None => {
+                if byte == b'"' || byte == b'\'' {
+                    quote = Some(byte);
+                }
+                byte == b'>'
+            }
+        })
+        .map(|end| end + 1)
+}
+
+/// The local name of a start tag, without any namespace prefix.
+fn tag_name(tag: &[u8]) -> &[u8] {
+    let name = &tag[1..];
+    let len = name
+        .iter()
+        .position(|&byte| byte.is_ascii_whitespace() || byte == b'/' || byte == b'>')
+        .unwrap_or(name.len());
+    let name = &name[..len];
+
+    match name.iter().rposition(|&byte| byte == b':') {
+        Some(colon) => &name[colon + 1..],
+        None => name,
+    }
+}
+
+/// Finds the raw value of an attribute in a start tag.
+fn attribute<'a>(tag: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    let mut rest = &tag[1 + tag_name(tag).len()..];
+
+    loop {
+        rest = trim_start(rest);
+        let name_len = rest
+            .iter()
+            .position(|&byte| byte == b'=' || byte.is_ascii_whitespace())?;
+        let this_name = &rest[..name_len];
+
+        rest = trim_start(&rest[name_len..]);
+        rest = trim_start(rest.strip_prefix(b"=")?);
+
+        let quote = *rest.first()?;
+        if quote != b'"' && quote != b'\'' {
+            return None;
+        }
+
+        let value_len = rest[1..].iter().position(|&byte| byte == quote)?;
+        let value = &rest[1..1 + value_len];
+
+        if this_name == name {
+            return Some(value);
+        }
+
+        rest = &rest[value_len + 2..];
+    }
+}
+
+/// Tells whether a property's start tag could be for its Name. Names with
+/// references in them, like `&#78;ame`, are left for xml-rs to unescape and
+/// check.
+fn may_be_name(tag: &[u8]) -> bool {
+    match attribute(tag, b"name") {
+        Some(name) => name == b"Name" || name.contains(&b'&'),
+        None => false,
+    }
+}
+
+fn trim_start(data: &[u8]) -> &[u8] {
+    let len = data
+        .iter()
+        .take_while(|byte| byte.is_ascii_whitespace())
+        .count();
+    &data[len..]
+}
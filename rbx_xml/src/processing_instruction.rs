@@ -0,0 +1,92 @@
+use crate::serializer_core::is_xml_char;
+
+/// A processing instruction, like `<?generator my-tool 1.2?>`.
+///
+/// Some tools put these in the files they make to say where the files came
+/// from. The ones at the top level of a document, outside of any `<Item>`, are
+/// listed in
+/// [`DecodeSummary::processing_instructions`][crate::DecodeSummary::processing_instructions],
+/// and can be written out with
+/// [`EncodeOptions::processing_instructions`][crate::EncodeOptions::processing_instructions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProcessingInstruction {
+    pub(crate) name: String,
+    pub(crate) data: Option<String>,
+}
+
+impl ProcessingInstruction {
+    /// Constructs a processing instruction with the given name and no data,
+    /// like `<?name?>`.
+    #[inline]
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        ProcessingInstruction {
+            name: name.into(),
+            data: None,
+        }
+    }
+
+    /// Sets the text that comes after the instruction's name, like
+    /// `my-tool 1.2` in `<?generator my-tool 1.2?>`.
+    #[inline]
+    pub fn with_data<S: Into<String>>(self, data: S) -> Self {
+        ProcessingInstruction {
+            data: Some(data.into()),
+            ..self
+        }
+    }
+
+    /// The instruction's name, which is also called its target.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The text that comes after the instruction's name, if there is any.
+    #[inline]
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+
+    /// Tells why this instruction can't be written to a document, if it can't
+    /// be.
+    pub(crate) fn problem(&self) -> Option<&'static str> {
+        let mut name_chars = self.name.chars();
+
+        match name_chars.next() {
+            None => return Some("its name is empty"),
+            Some(first) if !is_name_start_char(first) => {
+                return Some("its name doesn't start with a letter or underscore")
+            }
+            _ => {}
+        }
+
+        if !name_chars.all(is_name_char) {
+            return Some("its name contains characters that aren't allowed in XML names");
+        }
+
+        if self.name.eq_ignore_ascii_case("xml") {
+            return Some("the name 'xml' is reserved for the XML declaration");
+        }
+
+        if let Some(data) = &self.data {
+            if data.contains("?>") {
+                return Some("its data contains '?>'");
+            }
+
+            if data.chars().any(|c| !is_xml_char(c)) {
+                return Some("its data contains characters that XML can't represent");
+            }
+        }
+
+        None
+    }
+}
+
+fn is_name_start_char(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == ':'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c) || c.is_numeric() || matches!(c, '-' | '.' | '\u{B7}')
+}
@@ -1,16 +1,34 @@
-use std::{borrow::Cow, collections::HashMap, io::Write};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
 
+use indexmap::IndexMap;
 use rbx_dom_weak::{
-    types::{Ref, SharedString, SharedStringHash, Variant, VariantType},
-    WeakDom,
+    types::{
+        BrickColor, CFrame, Color3uint8, CountingWriter, Ref, SharedString, SharedStringHash,
+        UniqueId, Variant, VariantType, Vector3,
+    },
+    Instance, PropertyLintKind, WeakDom,
 };
-use rbx_reflection::DataType;
+use rbx_reflection::{DataType, PropertyTag};
 
 use crate::{
     compat::{TodoValueConversion, TodoValueConversionType},
-    core::find_serialized_property_descriptor,
-    error::{EncodeError as NewEncodeError, EncodeErrorKind},
-    types::write_value_xml,
+    core::{
+        find_canonical_property_descriptor, find_exact_property_descriptor,
+        find_legacy_brick_color_name, find_serialized_property_descriptor, shared_string_key,
+        XmlReferent, DOC_VERSION, ROOT_META_PROPERTIES,
+    },
+    error::{EncodeError as NewEncodeError, EncodeErrorKind, EncodeProgress},
+    legacy_elements::LegacyElement,
+    processing_instruction::ProcessingInstruction,
+    singletons::{check_duplicate_singletons, missing_singletons},
+    summary::{ClampedProperty, EncodeProgressReport, EncodeSummary, SkipReason, SkippedProperty},
+    types::{attributes_to_binary, write_value_xml},
 };
 
 use crate::serializer_core::{XmlEventWriter, XmlWriteEvent};
@@ -20,18 +38,409 @@ pub fn encode_internal<W: Write>(
     tree: &WeakDom,
     ids: &[Ref],
     options: EncodeOptions,
-) -> Result<(), NewEncodeError> {
-    let mut writer = XmlEventWriter::from_output(output);
-    let mut state = EmitState::new(options);
+) -> Result<EncodeSummary, NewEncodeError> {
+    phase_span!("encode");
+
+    let mut serializer = XmlSerializer::new(output, tree, ids, options);
+    while serializer.step(usize::MAX)? == SerializeProgress::InProgress {}
+
+    let summary = serializer.into_summary();
+
+    debug_event!(
+        bytes_written = summary.bytes_written,
+        instances = summary.instance_count,
+        properties = summary.property_count,
+        "encoded document"
+    );
+
+    Ok(summary)
+}
+
+/// Tells whether an [`XmlSerializer`] has finished its document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SerializeProgress {
+    /// There are instances left to write.
+    InProgress,
+
+    /// The whole document has been written.
+    Done,
+}
+
+/// Serializes a subset of a tree to an XML format model or place a few
+/// instances at a time, for callers that can't block until the whole document
+/// is written, like servers encoding inside of a poll loop.
+///
+/// Each call to [`step`][XmlSerializer::step] writes up to the given number of
+/// instances to the output. The finished document is exactly the same as the
+/// one written by [`to_writer`][crate::to_writer], which is itself a loop over
+/// `step`.
+///
+/// ```
+/// use rbx_dom_weak::{InstanceBuilder, WeakDom};
+/// use rbx_xml::{EncodeOptions, SerializeProgress, XmlSerializer};
+///
+/// let dom = WeakDom::new(
+///     InstanceBuilder::new("DataModel")
+///         .with_child(InstanceBuilder::new("Folder"))
+///         .with_child(InstanceBuilder::new("Folder")),
+/// );
+///
+/// let mut serializer =
+///     XmlSerializer::new(Vec::new(), &dom, dom.root().children(), EncodeOptions::new());
+///
+/// let mut steps = 1;
+/// while serializer.step(1)? == SerializeProgress::InProgress {
+///     // Anything written so far can be sent along before the next step.
+///     serializer.output_mut().clear();
+///     steps += 1;
+/// }
+///
+/// assert_eq!(steps, 2);
+/// # Ok::<(), rbx_xml::EncodeError>(())
+/// ```
+pub struct XmlSerializer<'dom, W: Write> {
+    tree: &'dom WeakDom,
+    ids: &'dom [Ref],
+    writer: XmlEventWriter<CountingWriter<W>>,
+    state: EmitState,
+    stage: SerializeStage,
+
+    /// The sibling lists that are being written, from the top-level instances
+    /// down to the children of the instance that was written last.
+    stack: Vec<Siblings<'dom>>,
+
+    /// How many instances from the tree have been written, and how many will
+    /// be once the document is done, for the `progress` option. Synthesized
+    /// singletons aren't counted in either.
+    instances_written: usize,
+    total_instances: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SerializeStage {
+    /// Nothing has been written yet.
+    Start,
+
+    /// The document has been started and instances are being written.
+    Items,
+
+    /// The document has been written.
+    Done,
+
+    /// Writing the document failed partway through.
+    Failed,
+}
+
+/// A list of sibling instances that's being written.
+struct Siblings<'dom> {
+    /// The instance the siblings belong to, or `None` for the top-level
+    /// instances of the document.
+    parent: Option<Ref>,
+
+    /// The siblings, in the order they're written in.
+    ids: Cow<'dom, [Ref]>,
+
+    /// How many of the siblings have been written so far.
+    next: usize,
+}
+
+impl<'dom, W: Write> XmlSerializer<'dom, W> {
+    /// Creates a serializer that will write `ids` and their descendants from
+    /// `tree` to `output`. Nothing is written until the first call to
+    /// [`step`][XmlSerializer::step].
+    pub fn new(
+        output: W,
+        tree: &'dom WeakDom,
+        ids: &'dom [Ref],
+        options: EncodeOptions,
+    ) -> XmlSerializer<'dom, W> {
+        let indent = match &options.output_style {
+            OutputStyle::Pretty { indent } => Some(indent.clone()),
+            OutputStyle::Compact => None,
+        };
+
+        XmlSerializer {
+            tree,
+            ids,
+            writer: XmlEventWriter::from_output(CountingWriter::new(output), indent)
+                .with_base64_line_length(options.base64_line_length),
+            state: EmitState::new(options),
+            stage: SerializeStage::Start,
+            stack: Vec::new(),
+            instances_written: 0,
+            total_instances: 0,
+        }
+    }
+
+    /// Writes up to `budget_items` more instances, along with everything that
+    /// comes between them, and tells whether the document is finished. A
+    /// budget of zero is treated as one, so every call makes progress.
+    ///
+    /// The first call also writes everything that comes before the first
+    /// instance, which includes the SharedString dictionary if it's written
+    /// at the start of the document. The call that writes the last instance
+    /// finishes the document and returns [`SerializeProgress::Done`].
+    ///
+    /// If the [`progress`][EncodeOptions::progress] callback cancels
+    /// encoding, this returns an error for which
+    /// [`EncodeError::is_cancelled`][crate::EncodeError::is_cancelled] is
+    /// true.
+    ///
+    /// ## Panics
+    /// Panics if called again after returning an error.
+    pub fn step(&mut self, budget_items: usize) -> Result<SerializeProgress, NewEncodeError> {
+        match self.stage {
+            SerializeStage::Done => return Ok(SerializeProgress::Done),
+            SerializeStage::Failed => panic!("cannot continue serializing after an error"),
+            SerializeStage::Start | SerializeStage::Items => {}
+        }
+
+        match self.write_items(budget_items.max(1)) {
+            Ok(progress) => Ok(progress),
+            Err(error) => {
+                self.stage = SerializeStage::Failed;
+
+                let progress = EncodeProgress {
+                    bytes_written: self.writer.output().bytes_written(),
+                    instance_count: self.state.instance_count,
+                    instance: self
+                        .state
+                        .current_instance
+                        .and_then(|referent| self.tree.full_name(referent)),
+                };
+
+                Err(error.with_progress(progress))
+            }
+        }
+    }
+
+    /// Borrows the output that the document is being written to.
+    pub fn output(&self) -> &W {
+        self.writer.output().get_ref()
+    }
+
+    /// Mutably borrows the output that the document is being written to, so
+    /// that what's been written so far can be taken out of it between steps.
+    pub fn output_mut(&mut self) -> &mut W {
+        self.writer.output_mut().get_mut()
+    }
+
+    /// Consumes the serializer, returning a summary of what it wrote.
+    pub fn into_summary(self) -> EncodeSummary {
+        EncodeSummary {
+            bytes_written: self.writer.output().bytes_written(),
+            instance_count: self.state.instance_count,
+            property_count: self.state.property_count,
+            skipped_properties: self.state.skipped_properties,
+            clamped_properties: self.state.clamped_properties,
+        }
+    }
+
+    fn write_items(&mut self, budget: usize) -> Result<SerializeProgress, NewEncodeError> {
+        let writer = &mut self.writer;
+        let state = &mut self.state;
+        let tree = self.tree;
+
+        if self.stage == SerializeStage::Start {
+            let order = serialize_start(writer, state, tree, self.ids)?;
+            self.total_instances = state.written_ids.len();
+            self.stack.push(Siblings {
+                parent: None,
+                ids: order,
+                next: 0,
+            });
+            self.stage = SerializeStage::Items;
+        }
+
+        {
+            phase_span!("write_items");
+
+            let mut written = 0;
+
+            while let Some(siblings) = self.stack.last_mut() {
+                state.current_instance = siblings.parent;
+                let index = siblings.next;
+
+                if index < siblings.ids.len() {
+                    if written == budget {
+                        return Ok(SerializeProgress::InProgress);
+                    }
+
+                    let id = siblings.ids[index];
+                    siblings.next += 1;
+
+                    serialize_legacy_elements(writer, state, siblings.parent, |i| i == index)?;
+                    serialize_instance(writer, state, tree, id)?;
+                    written += 1;
+                    self.instances_written += 1;
+
+                    if let Some(progress) = &state.options.progress {
+                        if self.instances_written.is_multiple_of(progress.interval) {
+                            let report = EncodeProgressReport {
+                                instances_written: self.instances_written,
+                                total_instances: self.total_instances,
+                                bytes_written: writer.output().bytes_written(),
+                            };
+
+                            if progress.report(&report) == ProgressAction::Cancel {
+                                log::debug!(
+                                    "Encoding was cancelled after {} of {} instances",
+                                    report.instances_written,
+                                    report.total_instances
+                                );
+                                return Err(writer.error(EncodeErrorKind::Cancelled));
+                            }
+                        }
+                    }
+
+                    // Synthesized singletons come before the instance's own
+                    // children, and don't count against the budget.
+                    if state.options.synthesize_singletons {
+                        let instance = tree.get_by_ref(id).unwrap();
+
+                        for singleton in missing_singletons(tree, instance) {
+                            serialize_instance(writer, state, &singleton, singleton.root_ref())?;
+                            writer.write(XmlWriteEvent::end_element())?;
+                        }
+                    }
+
+                    let children = tree.get_by_ref(id).unwrap().children();
+                    self.stack.push(Siblings {
+                        parent: Some(id),
+                        ids: state.order_siblings(tree, children),
+                        next: 0,
+                    });
+                } else {
+                    let parent = siblings.parent;
+                    serialize_legacy_elements(writer, state, parent, |i| i >= index)?;
+                    self.stack.pop();
+
+                    // The top-level list is the last one to finish, and the end
+                    // of the document is written once the span is closed.
+                    if parent.is_some() {
+                        writer.write(XmlWriteEvent::end_element())?;
+
+                        // Only the top-level list is left once a top-level
+                        // instance is finished.
+                        if state.options.flush_per_item && self.stack.len() == 1 {
+                            writer.flush()?;
+                        }
+                    }
+                }
+            }
+        }
+
+        serialize_end(writer, state)?;
+
+        if state.options.flush_per_item {
+            writer.flush()?;
+        }
+
+        self.stage = SerializeStage::Done;
+        Ok(SerializeProgress::Done)
+    }
+}
 
-    writer.write(XmlWriteEvent::start_element("roblox").attr("version", "4"))?;
+/// Writes everything in the document that comes before the first instance,
+/// returning the order that the given top-level instances should be written
+/// in.
+fn serialize_start<'a, W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+    tree: &WeakDom,
+    ids: &'a [Ref],
+) -> Result<Cow<'a, [Ref]>, NewEncodeError> {
+    if state.options.error_on_misplaced_properties {
+        check_misplaced_properties(writer, tree, ids)?;
+    }
+
+    check_duplicate_singletons(writer, tree, ids, state.options.ensure_singletons)?;
 
-    let mut property_buffer = Vec::new();
     for id in ids {
-        serialize_instance(&mut writer, &mut state, tree, *id, &mut property_buffer)?;
+        state
+            .written_ids
+            .extend(tree.descendants(*id).map(Instance::referent));
+    }
+
+    for instruction in &state.options.processing_instructions {
+        if let Some(reason) = instruction.problem() {
+            return Err(writer.error(EncodeErrorKind::InvalidProcessingInstruction {
+                name: instruction.name.clone(),
+                reason,
+            }));
+        }
+    }
+
+    let version = state.options.document_version.to_string();
+    writer.write(XmlWriteEvent::start_element("roblox").attr("version", &version))?;
+
+    for instruction in &state.options.processing_instructions {
+        writer.write(XmlWriteEvent::processing_instruction(
+            &instruction.name,
+            instruction.data.as_deref(),
+        ))?;
     }
 
-    serialize_shared_strings(&mut writer, &mut state)?;
+    serialize_root_properties(writer, state, tree, ids)?;
+
+    let order = {
+        phase_span!("order_instances");
+
+        state.order_siblings(tree, ids)
+    };
+
+    if state.options.shared_strings_position == SharedStringsPosition::BeforeItems {
+        {
+            phase_span!("collect_shared_strings");
+
+            for id in order.iter() {
+                collect_shared_strings(writer, state, tree, *id)?;
+            }
+        }
+
+        serialize_shared_strings(writer, state)?;
+    }
+
+    Ok(order)
+}
+
+/// Returns an error for the first property in the given subtrees that
+/// `WeakDom::lint_properties` says isn't on its instance's class at all.
+fn check_misplaced_properties<W: Write>(
+    writer: &XmlEventWriter<W>,
+    tree: &WeakDom,
+    ids: &[Ref],
+) -> Result<(), NewEncodeError> {
+    let database = rbx_reflection_database::get();
+
+    for id in ids {
+        let misplaced = tree
+            .lint_properties(*id, database)
+            .into_iter()
+            .find(|lint| matches!(lint.kind, PropertyLintKind::UnknownProperty { .. }));
+
+        if let Some(lint) = misplaced {
+            return Err(writer.error(EncodeErrorKind::MisplacedProperty {
+                suggestion: lint.suggestion(),
+                instance: lint.instance,
+                property_name: lint.property,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes everything in the document that comes after the last instance, up
+/// to the closing `<roblox>` tag.
+fn serialize_end<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+) -> Result<(), NewEncodeError> {
+    if state.options.shared_strings_position == SharedStringsPosition::AfterItems {
+        serialize_shared_strings(writer, state)?;
+    }
 
     writer.write(XmlWriteEvent::end_element())?;
 
@@ -68,10 +477,196 @@ pub enum EncodePropertyBehavior {
     NoReflection,
 }
 
+/// Describes where rbx_xml should write a file's SharedString dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SharedStringsPosition {
+    /// Writes the dictionary before any instances, so that readers can look
+    /// up each SharedString property as soon as they reach it.
+    ///
+    /// This makes rbx_xml go over the instances being written twice.
+    BeforeItems,
+
+    /// Writes the dictionary after every instance, like Roblox Studio does.
+    ///
+    /// This is the default.
+    AfterItems,
+}
+
+/// Describes what rbx_xml should do with properties of the tree's root
+/// instance when writing a place.
+///
+/// The root, usually a `DataModel`, isn't written as an `<Item>` when its
+/// children are being written, so its properties have nowhere to go unless
+/// they're hoisted into `<Meta>` elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RootPropertyBehavior {
+    /// Leaves the root's properties out of the file, matching the places that
+    /// Roblox Studio saves.
+    ///
+    /// This is the default.
+    Drop,
+
+    /// Returns an error if the root has any properties.
+    Error,
+
+    /// Writes `AttributesSerialize` and `Tags` as `<Meta>` elements with the
+    /// same name, holding their value as base64. rbx_xml puts these back onto
+    /// the root when decoding.
+    ///
+    /// Returns an error if the root has any other properties.
+    HoistIntoMeta,
+}
+
+/// Describes what rbx_xml should do when the instances being written have more
+/// than one singleton of the same class in the same parent, like two
+/// Workspaces in a DataModel or two Terrains in a Workspace. Roblox can fail to
+/// load files like these.
+///
+/// Services are singletons, as are the classes that Roblox puts in every place
+/// but that can't be created with `Instance.new`, according to the reflection
+/// database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SingletonBehavior {
+    /// Writes the instances without checking them.
+    ///
+    /// This is the default.
+    Ignore,
+
+    /// Logs a warning for each duplicate singleton, and writes the instances
+    /// anyway.
+    WarnOnDuplicates,
+
+    /// Returns an error for the first duplicate singleton, before anything is
+    /// written.
+    ErrorOnDuplicates,
+}
+
+/// Describes how rbx_xml should lay out the elements of the document it
+/// writes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OutputStyle {
+    /// Writes each element on its own line, indented once for every element
+    /// it's in.
+    ///
+    /// This is the default, indenting with two spaces.
+    Pretty {
+        /// What each level of indentation is made of, like `"\t"` or four
+        /// spaces.
+        indent: String,
+    },
+
+    /// Writes the whole document without any whitespace between elements,
+    /// which makes it smaller but hard to read.
+    Compact,
+}
+
+/// Describes which of an instance's properties rbx_xml should write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PropertyFilter {
+    /// Writes every property that the instance has.
+    ///
+    /// This is the default.
+    All,
+
+    /// Leaves out properties whose value is the default from the reflection
+    /// database, like Roblox Studio does. Floats, and the types made of them,
+    /// count as the default when they're within a small tolerance of it, since
+    /// values that have been through a few conversions rarely match exactly.
+    ///
+    /// Properties without a known default, including any that aren't in the
+    /// reflection database, are always written. Leaving a property out is
+    /// reported as [`SkipReason::DefaultValue`].
+    SkipDefaults,
+}
+
+/// Describes what order rbx_xml should write each instance's children in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ChildOrder {
+    /// Writes children in the order they're stored in the dom.
+    ///
+    /// This is the default.
+    Preserve,
+
+    /// Writes children sorted by their Name, and then by their ClassName.
+    /// Children with the same Name and ClassName keep the order they're
+    /// stored in.
+    ///
+    /// This makes the document the same no matter what order the instances
+    /// were added in, which keeps diffs of files in version control small. The
+    /// order of children matters to some scripts, so it's only worth using
+    /// when that isn't a concern.
+    SortByName,
+}
+
+/// Tells rbx_xml whether to keep going after a
+/// [`progress`][EncodeOptions::progress] report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProgressAction {
+    /// Keeps encoding.
+    Continue,
+
+    /// Stops encoding, returning an error for which
+    /// [`EncodeError::is_cancelled`][crate::EncodeError::is_cancelled] is
+    /// true.
+    Cancel,
+}
+
+type ProgressFn = dyn FnMut(&EncodeProgressReport) -> ProgressAction + Send;
+
+/// The callback set by [`EncodeOptions::progress`]. It's shared between
+/// clones of the options it's in.
+#[derive(Clone)]
+struct ProgressCallback {
+    interval: usize,
+    callback: Arc<Mutex<ProgressFn>>,
+}
+
+impl ProgressCallback {
+    fn report(&self, report: &EncodeProgressReport) -> ProgressAction {
+        let mut callback = self.callback.lock().unwrap();
+        (*callback)(report)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        output
+            .debug_struct("ProgressCallback")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Options available for serializing an XML-format model or place.
 #[derive(Debug, Clone)]
 pub struct EncodeOptions {
     property_behavior: EncodePropertyBehavior,
+    ref_aware_ordering: bool,
+    omit_default_names: bool,
+    legacy_brick_colors: bool,
+    shared_strings_position: SharedStringsPosition,
+    legacy_elements: Vec<LegacyElement>,
+    root_property_behavior: RootPropertyBehavior,
+    error_on_skip: Vec<SkipReason>,
+    error_on_misplaced_properties: bool,
+    flush_per_item: bool,
+    base64_line_length: Option<usize>,
+    ensure_singletons: SingletonBehavior,
+    synthesize_singletons: bool,
+    output_style: OutputStyle,
+    property_filter: PropertyFilter,
+    regenerate_unique_ids: bool,
+    processing_instructions: Vec<ProcessingInstruction>,
+    sort_properties: bool,
+    child_order: ChildOrder,
+    progress: Option<ProgressCallback>,
+    document_version: u32,
 }
 
 impl EncodeOptions {
@@ -80,14 +675,374 @@ impl EncodeOptions {
     pub fn new() -> Self {
         EncodeOptions {
             property_behavior: EncodePropertyBehavior::IgnoreUnknown,
+            ref_aware_ordering: false,
+            omit_default_names: false,
+            legacy_brick_colors: false,
+            shared_strings_position: SharedStringsPosition::AfterItems,
+            legacy_elements: Vec::new(),
+            root_property_behavior: RootPropertyBehavior::Drop,
+            error_on_skip: Vec::new(),
+            error_on_misplaced_properties: false,
+            flush_per_item: false,
+            base64_line_length: Some(72),
+            ensure_singletons: SingletonBehavior::Ignore,
+            synthesize_singletons: false,
+            output_style: OutputStyle::Pretty {
+                indent: "  ".to_owned(),
+            },
+            property_filter: PropertyFilter::All,
+            regenerate_unique_ids: false,
+            processing_instructions: Vec::new(),
+            sort_properties: false,
+            child_order: ChildOrder::Preserve,
+            progress: None,
+            document_version: DOC_VERSION,
         }
     }
 
     /// Determines how rbx_xml will serialize properties, especially unknown
     /// ones.
     #[inline]
-    pub fn property_behavior(self, property_behavior: EncodePropertyBehavior) -> Self {
-        EncodeOptions { property_behavior }
+    pub fn property_behavior(self, property_behavior: EncodePropertyBehavior) -> Self {
+        EncodeOptions {
+            property_behavior,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will reorder siblings so that the targets of
+    /// Ref properties are written before the instances that refer to them.
+    ///
+    /// Ordering is only ever changed among the children of a single parent, so
+    /// the hierarchy of the file is unaffected. Siblings that refer to each
+    /// other in a cycle keep their existing relative order.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn ref_aware_ordering(self, ref_aware_ordering: bool) -> Self {
+        EncodeOptions {
+            ref_aware_ordering,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will leave out the `Name` property for
+    /// instances that are named after their ClassName.
+    ///
+    /// Items without a `Name` are given their ClassName as a name when they're
+    /// decoded by rbx_xml or rbx_binary, so this doesn't lose any information.
+    ///
+    /// Instances with no
+    /// [`explicit_name`][rbx_dom_weak::Instance::explicit_name], like ones
+    /// decoded with `DecodeOptions::preserve_missing_names`, never get a Name,
+    /// whether this is on or not.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn omit_default_names(self, omit_default_names: bool) -> Self {
+        EncodeOptions {
+            omit_default_names,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will also write colors that used to be
+    /// BrickColors, like `BasePart.Color`, as the closest BrickColor under the
+    /// property's old name.
+    ///
+    /// This is intended for tools making files for old Roblox clients, which
+    /// don't understand the newer Color3 properties. Newer clients, rbx_xml,
+    /// and rbx_binary all prefer the Color3 property when both are present.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn legacy_brick_colors(self, legacy_brick_colors: bool) -> Self {
+        EncodeOptions {
+            legacy_brick_colors,
+            ..self
+        }
+    }
+
+    /// Determines where rbx_xml will write the file's SharedString
+    /// dictionary. Either way, SharedStrings are written in the order that
+    /// they're first used in.
+    #[inline]
+    pub fn shared_strings_position(self, shared_strings_position: SharedStringsPosition) -> Self {
+        EncodeOptions {
+            shared_strings_position,
+            ..self
+        }
+    }
+
+    /// Sets elements kept from an old place with
+    /// [`DecodeOptions::legacy_elements`][crate::DecodeOptions::legacy_elements]
+    /// to write back out.
+    ///
+    /// Each element is written in the same spot among its parent's children
+    /// that it was decoded from. Elements whose parent isn't being written are
+    /// left out. This should be used with the dom that the elements were
+    /// decoded alongside, since they refer to its instances.
+    ///
+    /// By default, no elements are written.
+    #[inline]
+    pub fn legacy_elements(self, legacy_elements: Vec<LegacyElement>) -> Self {
+        EncodeOptions {
+            legacy_elements,
+            ..self
+        }
+    }
+
+    /// Determines what rbx_xml will do with properties on the tree's root
+    /// instance when writing a place.
+    ///
+    /// This applies whenever a direct child of the root is being written
+    /// without the root itself. When the root is written, it's written as an
+    /// `<Item>` with all of its properties like any other instance.
+    #[inline]
+    pub fn root_property_behavior(self, root_property_behavior: RootPropertyBehavior) -> Self {
+        EncodeOptions {
+            root_property_behavior,
+            ..self
+        }
+    }
+
+    /// Makes rbx_xml return an error instead of leaving out a property for
+    /// any of the given reasons.
+    ///
+    /// Every property that's left out is listed in
+    /// [`EncodeSummary::skipped_properties`], so this is useful for tools that
+    /// would rather stop than lose data silently.
+    ///
+    /// By default, properties are left out without an error.
+    #[inline]
+    pub fn error_on_skip(self, error_on_skip: Vec<SkipReason>) -> Self {
+        EncodeOptions {
+            error_on_skip,
+            ..self
+        }
+    }
+
+    /// Makes rbx_xml check the instances being written with
+    /// [`WeakDom::lint_properties`] before writing anything, and return an
+    /// error for the first property that doesn't exist anywhere in its
+    /// instance's class hierarchy, like `Text` on a Frame. Properties with the
+    /// wrong type or deprecated names don't cause an error.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn error_on_misplaced_properties(self, error_on_misplaced_properties: bool) -> Self {
+        EncodeOptions {
+            error_on_misplaced_properties,
+            ..self
+        }
+    }
+
+    /// Makes rbx_xml flush the output after writing each top-level instance
+    /// and at the end of the document, so that a reader on the other end of a
+    /// pipe or socket can start on the document before all of it is written.
+    /// The document itself is the same either way.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn flush_per_item(self, flush_per_item: bool) -> Self {
+        EncodeOptions {
+            flush_per_item,
+            ..self
+        }
+    }
+
+    /// Sets how many characters of base64 are written on each line of
+    /// `BinaryString` and `SharedString` values, and of `<Meta>` elements
+    /// holding root properties. `None` writes each value on one line.
+    ///
+    /// By default, lines are 72 characters long, which is what Roblox Studio
+    /// writes.
+    #[inline]
+    pub fn base64_line_length(self, base64_line_length: Option<usize>) -> Self {
+        EncodeOptions {
+            base64_line_length,
+            ..self
+        }
+    }
+
+    /// Sets what rbx_xml does when there's more than one singleton of the
+    /// same class in the same parent, like two Terrains in a Workspace.
+    ///
+    /// By default, these are written without being checked.
+    #[inline]
+    pub fn ensure_singletons(self, ensure_singletons: SingletonBehavior) -> Self {
+        EncodeOptions {
+            ensure_singletons,
+            ..self
+        }
+    }
+
+    /// Makes rbx_xml write the singletons that Roblox Studio puts in every
+    /// place, like Workspace's Terrain and StarterPlayer's
+    /// StarterPlayerScripts, when their parent is written without them. They're
+    /// written with the default properties from the reflection database, and
+    /// aren't added to the dom.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn synthesize_singletons(self, synthesize_singletons: bool) -> Self {
+        EncodeOptions {
+            synthesize_singletons,
+            ..self
+        }
+    }
+
+    /// Sets how the elements of the document are laid out.
+    ///
+    /// By default, each element is on its own line and indented with two
+    /// spaces.
+    #[inline]
+    pub fn output_style(self, output_style: OutputStyle) -> Self {
+        EncodeOptions {
+            output_style,
+            ..self
+        }
+    }
+
+    /// Sets which of each instance's properties are written.
+    ///
+    /// By default, every property is written.
+    #[inline]
+    pub fn property_filter(self, property_filter: PropertyFilter) -> Self {
+        EncodeOptions {
+            property_filter,
+            ..self
+        }
+    }
+
+    /// Makes the document as small as rbx_xml can: no whitespace between
+    /// elements, base64 on one line, and no properties that have their
+    /// default value. This is the same as setting
+    /// [`OutputStyle::Compact`], [`PropertyFilter::SkipDefaults`], and a
+    /// [`base64_line_length`][EncodeOptions::base64_line_length] of `None`.
+    #[inline]
+    pub fn compact(self) -> Self {
+        self.output_style(OutputStyle::Compact)
+            .property_filter(PropertyFilter::SkipDefaults)
+            .base64_line_length(None)
+    }
+
+    /// Determines whether rbx_xml will give every instance a new `UniqueId`
+    /// instead of writing the one it has.
+    ///
+    /// Roblox expects every instance in a place to have a different
+    /// `UniqueId`, so this is useful for writing a copy of instances that will
+    /// be inserted next to the originals. Without it, every `UniqueId` is
+    /// written exactly as it is.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn regenerate_unique_ids(self, regenerate_unique_ids: bool) -> Self {
+        EncodeOptions {
+            regenerate_unique_ids,
+            ..self
+        }
+    }
+
+    /// Sets processing instructions to write right after the document's
+    /// `<roblox>` tag, like a `<?generator my-tool 1.2?>` saying which tool
+    /// made the file. Instructions that were decoded from another document are
+    /// listed in
+    /// [`DecodeSummary::processing_instructions`][crate::DecodeSummary::processing_instructions].
+    ///
+    /// Encoding fails if one of the instructions can't be written as XML, like
+    /// one named `xml` or with `?>` in its data.
+    ///
+    /// By default, no processing instructions are written.
+    #[inline]
+    pub fn processing_instructions(
+        self,
+        processing_instructions: Vec<ProcessingInstruction>,
+    ) -> Self {
+        EncodeOptions {
+            processing_instructions,
+            ..self
+        }
+    }
+
+    /// Determines whether rbx_xml will write each instance's properties
+    /// sorted by name, instead of in the order they're stored on the instance.
+    /// `Name` is always written first, like Roblox Studio does.
+    ///
+    /// Dom instances keep their properties in the order they were added, so
+    /// two tools that build the same instances can still write them
+    /// differently. Sorting them gives the same document either way.
+    ///
+    /// This is off by default.
+    #[inline]
+    pub fn sort_properties(self, sort_properties: bool) -> Self {
+        EncodeOptions {
+            sort_properties,
+            ..self
+        }
+    }
+
+    /// Sets what order rbx_xml writes each instance's children in, including
+    /// the top-level instances being written. When
+    /// [`ref_aware_ordering`][EncodeOptions::ref_aware_ordering] is also on,
+    /// siblings are put in this order before being reordered for their Refs.
+    ///
+    /// By default, children are written in the order they're stored in.
+    #[inline]
+    pub fn child_order(self, child_order: ChildOrder) -> Self {
+        EncodeOptions {
+            child_order,
+            ..self
+        }
+    }
+
+    /// Sets a callback that rbx_xml calls after every `interval` instances it
+    /// writes, with how many it's written out of how many it will write, and
+    /// how many bytes it's written so far. An interval of zero is treated as
+    /// one.
+    ///
+    /// The callback is called from inside encoding, after the properties of
+    /// the instance that completed the interval have been written. Returning
+    /// [`ProgressAction::Cancel`] stops encoding with an error for which
+    /// [`EncodeError::is_cancelled`][crate::EncodeError::is_cancelled] is
+    /// true. The output is left holding everything written up to that point,
+    /// which is the start of a document that isn't finished, and
+    /// [`EncodeError::bytes_written`][crate::EncodeError::bytes_written] says
+    /// how long it is.
+    ///
+    /// Only instances from the dom count towards the total, so instances
+    /// added by [`synthesize_singletons`][EncodeOptions::synthesize_singletons]
+    /// don't cause reports. Clones of these options share the same callback.
+    ///
+    /// By default, there is no callback.
+    pub fn progress<F>(self, interval: usize, callback: F) -> Self
+    where
+        F: FnMut(&EncodeProgressReport) -> ProgressAction + Send + 'static,
+    {
+        EncodeOptions {
+            progress: Some(ProgressCallback {
+                interval: interval.max(1),
+                callback: Arc::new(Mutex::new(callback)),
+            }),
+            ..self
+        }
+    }
+
+    /// Sets the `version` attribute written on the document's `<roblox>` tag.
+    /// Only the attribute changes; everything else is written the same way
+    /// as version 4.
+    ///
+    /// This is meant for writing a document back out with the version it was
+    /// decoded with, from [`DecodeSummary::version`][crate::DecodeSummary::version],
+    /// so that tools that check it see the same value as before.
+    ///
+    /// By default, this is 4.
+    #[inline]
+    pub fn document_version(self, document_version: u32) -> Self {
+        EncodeOptions {
+            document_version,
+            ..self
+        }
     }
 
     pub(crate) fn use_reflection(&self) -> bool {
@@ -106,78 +1061,454 @@ pub struct EmitState {
 
     /// A map of IDs written so far to the generated referent that they use.
     /// This map is used to correctly emit Ref properties.
-    referent_map: HashMap<Ref, u32>,
+    referent_map: HashMap<Ref, XmlReferent>,
 
-    /// The referent value that will be used for emitting the next instance.
+    /// The index of the next instance to be given a referent.
     next_referent: u32,
 
-    /// A map of all shared strings referenced so far while generating XML. This
-    /// map will be written as the file's SharedString dictionary.
-    shared_strings_to_emit: HashMap<SharedStringHash, SharedString>,
+    /// Every instance that's written to the document. Ref properties that
+    /// point anywhere else are written as `null`, since there'd be nothing in
+    /// the file for them to refer to.
+    written_ids: HashSet<Ref>,
+
+    /// A map of all shared strings referenced so far while generating XML,
+    /// along with the key they're written with, in the order they were first
+    /// referenced. This map will be written as the file's SharedString
+    /// dictionary.
+    shared_strings_to_emit: IndexMap<SharedStringHash, (String, SharedString)>,
+
+    /// The hash of the shared string that each key in `shared_strings_to_emit`
+    /// was given to, so that two strings whose MD5 hashes collide still get
+    /// different keys.
+    shared_string_keys: HashMap<String, SharedStringHash>,
+
+    /// The legacy elements from `EncodeOptions` to write in each instance, or
+    /// in the root for `None`.
+    legacy_elements: HashMap<Option<Ref>, Vec<LegacyElement>>,
+
+    /// The number of instances serialized so far.
+    instance_count: usize,
+
+    /// The number of properties serialized so far, not counting names.
+    property_count: usize,
+
+    /// The properties left out so far.
+    skipped_properties: Vec<SkippedProperty>,
+
+    /// The properties whose values were clamped so far.
+    clamped_properties: Vec<ClampedProperty>,
+
+    /// The instance that's being written, which is reported if writing to the
+    /// output fails.
+    current_instance: Option<Ref>,
 }
 
 impl EmitState {
-    pub fn new(options: EncodeOptions) -> EmitState {
+    pub fn new(mut options: EncodeOptions) -> EmitState {
+        let mut legacy_elements: HashMap<_, Vec<_>> = HashMap::new();
+
+        for element in std::mem::take(&mut options.legacy_elements) {
+            legacy_elements
+                .entry(element.parent)
+                .or_default()
+                .push(element);
+        }
+
         EmitState {
             options,
+            legacy_elements,
             referent_map: HashMap::new(),
             next_referent: 0,
-            shared_strings_to_emit: HashMap::new(),
+            written_ids: HashSet::new(),
+            shared_strings_to_emit: IndexMap::new(),
+            shared_string_keys: HashMap::new(),
+            instance_count: 0,
+            property_count: 0,
+            skipped_properties: Vec::new(),
+            clamped_properties: Vec::new(),
+            current_instance: None,
         }
     }
 
-    pub fn map_id(&mut self, id: Ref) -> u32 {
-        match self.referent_map.get(&id) {
-            Some(&value) => value,
-            None => {
-                let referent = self.next_referent;
-                self.referent_map.insert(id, referent);
-                self.next_referent += 1;
-                referent
+    /// Tells whether the instance with the given ID is written to the
+    /// document.
+    pub fn writes_instance(&self, id: Ref) -> bool {
+        self.written_ids.contains(&id)
+    }
+
+    pub fn map_id(&mut self, id: Ref) -> &XmlReferent {
+        let next_referent = &mut self.next_referent;
+
+        self.referent_map.entry(id).or_insert_with(|| {
+            let referent = XmlReferent::from_index(*next_referent);
+            *next_referent += 1;
+            referent
+        })
+    }
+
+    /// Adds a SharedString to the file's dictionary, returning the key that
+    /// properties should use to refer to it. Computing the key reads the
+    /// string's contents, which can fail if they're kept in a blob store.
+    pub fn add_shared_string(&mut self, value: SharedString) -> io::Result<&str> {
+        self.add_shared_string_with(value, shared_string_key)
+    }
+
+    /// Like `add_shared_string`, with the function that computes the key
+    /// for a string passed in, so that tests can make keys collide.
+    fn add_shared_string_with(
+        &mut self,
+        value: SharedString,
+        compute_key: fn(&SharedString) -> io::Result<String>,
+    ) -> io::Result<&str> {
+        let hash = value.hash();
+
+        if !self.shared_strings_to_emit.contains_key(&hash) {
+            let base_key = compute_key(&value)?;
+            let mut key = base_key.clone();
+            let mut suffix = 1;
+
+            // A key that's already taken belongs to a string with different
+            // contents, since strings with the same contents share an entry.
+            // Decoders would keep only one of them, so this one needs a key
+            // of its own.
+            if self.shared_string_keys.contains_key(&key) {
+                log::warn!(
+                    "Two different SharedStrings have the key {}, giving one of them a suffix",
+                    base_key
+                );
             }
+
+            while self.shared_string_keys.contains_key(&key) {
+                suffix += 1;
+                key = format!("{}-{}", base_key, suffix);
+            }
+
+            self.shared_string_keys.insert(key.clone(), hash);
+            self.shared_strings_to_emit.insert(hash, (key, value));
         }
+
+        Ok(&self.shared_strings_to_emit[&hash].0)
     }
 
-    pub fn add_shared_string(&mut self, value: SharedString) {
-        self.shared_strings_to_emit.insert(value.hash(), value);
+    /// Returns the order that the given sibling instances should be written
+    /// in. Unless `child_order` or `ref_aware_ordering` are set, this is the
+    /// order they were given in.
+    fn order_siblings<'a>(&self, tree: &WeakDom, ids: &'a [Ref]) -> Cow<'a, [Ref]> {
+        if ids.len() < 2 {
+            return Cow::Borrowed(ids);
+        }
+
+        let ids = match self.options.child_order {
+            ChildOrder::Preserve => Cow::Borrowed(ids),
+            ChildOrder::SortByName => {
+                let mut sorted = ids.to_vec();
+                sorted.sort_by_key(|id| {
+                    let instance = tree.get_by_ref(*id).unwrap();
+                    (instance.name.as_str(), instance.class.as_str())
+                });
+
+                Cow::Owned(sorted)
+            }
+        };
+
+        if self.options.ref_aware_ordering {
+            Cow::Owned(order_by_refs(tree, &ids))
+        } else {
+            ids
+        }
     }
 }
 
-/// Serialize a single instance.
+/// Orders a list of sibling instances so that any sibling whose subtree
+/// contains the target of a Ref property comes before the siblings that refer
+/// to it.
 ///
-/// `property_buffer` is a Vec that can be reused between calls to
-/// serialize_instance to make sorting properties more efficient.
-fn serialize_instance<'a, W: Write>(
+/// Whenever no sibling is free of unwritten dependencies, which only happens
+/// when there's a cycle, the earliest remaining sibling is chosen. With no
+/// Refs at all, this returns `ids` unchanged.
+fn order_by_refs(tree: &WeakDom, ids: &[Ref]) -> Vec<Ref> {
+    let index_of: HashMap<Ref, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    // Finds which of our siblings contains the given instance, if any.
+    let containing_sibling = |mut target: Ref| -> Option<usize> {
+        while let Some(instance) = tree.get_by_ref(target) {
+            if let Some(&index) = index_of.get(&target) {
+                return Some(index);
+            }
+
+            target = instance.parent();
+        }
+
+        None
+    };
+
+    // dependencies[i] contains the siblings that must be written before
+    // sibling i.
+    let mut dependencies: Vec<HashSet<usize>> = vec![HashSet::new(); ids.len()];
+    let mut to_visit = Vec::new();
+
+    for (index, id) in ids.iter().enumerate() {
+        to_visit.push(*id);
+
+        while let Some(current) = to_visit.pop() {
+            let instance = match tree.get_by_ref(current) {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            for value in instance.properties.values() {
+                if let Variant::Ref(target) = value {
+                    if let Some(target_index) = containing_sibling(*target) {
+                        if target_index != index {
+                            dependencies[index].insert(target_index);
+                        }
+                    }
+                }
+            }
+
+            to_visit.extend_from_slice(instance.children());
+        }
+    }
+
+    let mut written = vec![false; ids.len()];
+    let mut ordered = Vec::with_capacity(ids.len());
+
+    while ordered.len() < ids.len() {
+        let is_ready = |index: usize| {
+            !written[index]
+                && dependencies[index]
+                    .iter()
+                    .all(|dependency| written[*dependency])
+        };
+
+        let next = (0..ids.len())
+            .find(|index| is_ready(*index))
+            .or_else(|| (0..ids.len()).find(|index| !written[*index]))
+            .unwrap();
+
+        written[next] = true;
+        ordered.push(ids[next]);
+    }
+
+    ordered
+}
+
+/// Writes the start of an instance and its properties, leaving its element
+/// open so that its children can be written into it.
+fn serialize_instance<W: Write>(
     writer: &mut XmlEventWriter<W>,
     state: &mut EmitState,
-    tree: &'a WeakDom,
+    tree: &WeakDom,
     id: Ref,
-    property_buffer: &mut Vec<(&'a String, &'a Variant)>,
 ) -> Result<(), NewEncodeError> {
     let instance = tree.get_by_ref(id).unwrap();
-    let mapped_id = state.map_id(id);
+    let mapped_id = state.map_id(id).clone();
+    state.current_instance = Some(id);
 
     writer.write(
         XmlWriteEvent::start_element("Item")
             .attr("class", &instance.class)
-            .attr("referent", &mapped_id.to_string()),
+            .attr("referent", mapped_id.as_str()),
     )?;
 
     writer.write(XmlWriteEvent::start_element("Properties"))?;
 
-    write_value_xml(
+    let default_name = instance.explicit_name().is_none()
+        || (state.options.omit_default_names && instance.name == instance.class);
+
+    if !default_name {
+        write_value_xml(
+            writer,
+            state,
+            "Name",
+            &Variant::from(instance.name.as_str()),
+        )?;
+    } else {
+        skip_property(writer, state, tree, id, "Name", SkipReason::DefaultValue)?;
+    }
+
+    let on_skip =
+        |writer: &mut XmlEventWriter<W>, state: &mut EmitState, property_name: &str, reason| {
+            skip_property(writer, state, tree, id, property_name, reason)
+        };
+
+    visit_properties(
+        writer,
+        state,
+        instance,
+        on_skip,
+        |writer, state, property| {
+            let value = match &*property.value {
+                Variant::UniqueId(_) if state.options.regenerate_unique_ids => {
+                    Cow::Owned(Variant::UniqueId(UniqueId::generate()))
+                }
+                _ => property.value,
+            };
+
+            if property.clamped {
+                let instance = tree.full_name(id).unwrap_or_default();
+                log::warn!(
+                    "The value of {}.{} was clamped to fit in a {:?}",
+                    instance,
+                    property.name,
+                    value.ty()
+                );
+
+                state.clamped_properties.push(ClampedProperty {
+                    instance,
+                    property_name: property.name.to_owned(),
+                    original_value: property.original_value.clone(),
+                    written_value: value.clone().into_owned(),
+                });
+            }
+
+            write_value_xml(writer, state, property.serialized_name, &value)?;
+            state.property_count += 1;
+
+            if property.known && state.options.legacy_brick_colors {
+                write_legacy_brick_color(
+                    writer,
+                    state,
+                    &instance.class,
+                    property.name,
+                    property.original_value,
+                )?;
+            }
+
+            Ok(())
+        },
+    )?;
+
+    writer.write(XmlWriteEvent::end_element())?;
+    state.instance_count += 1;
+
+    Ok(())
+}
+
+/// Writes the legacy elements in the given parent whose index is accepted by
+/// `at_index`.
+fn serialize_legacy_elements<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &EmitState,
+    parent: Option<Ref>,
+    at_index: impl Fn(usize) -> bool,
+) -> Result<(), NewEncodeError> {
+    let elements = match state.legacy_elements.get(&parent) {
+        Some(elements) => elements,
+        None => return Ok(()),
+    };
+
+    for element in elements {
+        if at_index(element.index) {
+            writer.write_raw_element(&element.xml)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds every SharedString that will be written for the given instance and
+/// its descendants and adds it to the file's dictionary, without writing
+/// anything.
+fn collect_shared_strings<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+    tree: &WeakDom,
+    id: Ref,
+) -> Result<(), NewEncodeError> {
+    let instance = tree.get_by_ref(id).unwrap();
+
+    // Skipped properties are recorded when the instance is written, so
+    // they're ignored here to avoid listing them twice.
+    let on_skip = |_: &mut XmlEventWriter<W>, _: &mut EmitState, _: &str, _| Ok(());
+
+    visit_properties(
         writer,
         state,
-        "Name",
-        &Variant::String(instance.name.clone()),
+        instance,
+        on_skip,
+        |writer, state, property| {
+            if let Variant::SharedString(value) = &*property.value {
+                state
+                    .add_shared_string(value.clone())
+                    .map_err(|e| writer.error(e))?;
+            }
+
+            Ok(())
+        },
     )?;
 
-    // Move references to our properties into property_buffer so we can sort
-    // them and iterate them in order.
-    property_buffer.extend(&instance.properties);
-    property_buffer.sort_unstable_by_key(|(key, _)| *key);
+    for child_id in state.order_siblings(tree, instance.children()).iter() {
+        collect_shared_strings(writer, state, tree, *child_id)?;
+    }
+
+    Ok(())
+}
+
+/// A property that is going to be written, after any conversion from
+/// reflection has been applied.
+struct VisitedProperty<'a> {
+    /// The property's name on the instance.
+    name: &'a str,
+
+    /// The name that the property is written with.
+    serialized_name: &'a str,
+
+    /// The value that will be written.
+    value: Cow<'a, Variant>,
+
+    /// The value as it is stored on the instance.
+    original_value: &'a Variant,
 
-    for (property_name, value) in property_buffer.drain(..) {
+    /// Whether the property is in the reflection database.
+    known: bool,
+
+    /// Whether the value had to be clamped to fit the type it's serialized
+    /// as.
+    clamped: bool,
+}
+
+/// Calls `visit` for each of the instance's properties that will be written,
+/// other than `Name`, using the same rules for unknown properties and
+/// conversions no matter what the caller does with them. `on_skip` is called
+/// instead for each property that's left out.
+fn visit_properties<'a, W, S, F>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+    instance: &'a Instance,
+    mut on_skip: S,
+    mut visit: F,
+) -> Result<(), NewEncodeError>
+where
+    W: Write,
+    S: FnMut(
+        &mut XmlEventWriter<W>,
+        &mut EmitState,
+        &str,
+        SkipReason,
+    ) -> Result<(), NewEncodeError>,
+    F: FnMut(
+        &mut XmlEventWriter<W>,
+        &mut EmitState,
+        VisitedProperty<'a>,
+    ) -> Result<(), NewEncodeError>,
+{
+    // Instances keep their properties in insertion order, which is the order
+    // we write them in unless they're being sorted.
+    let mut sorted_properties;
+    let mut stored_properties;
+    let properties: &mut dyn Iterator<Item = (&'a String, &'a Variant)> =
+        if state.options.sort_properties {
+            let mut sorted: Vec<_> = instance.properties.iter().collect();
+            sorted.sort_by_key(|(name, _)| name.as_str());
+            sorted_properties = sorted.into_iter();
+            &mut sorted_properties
+        } else {
+            stored_properties = instance.properties.iter();
+            &mut stored_properties
+        };
+
+    for (property_name, value) in properties {
         let maybe_serialized_descriptor = if state.options.use_reflection() {
             find_serialized_property_descriptor(&instance.class, property_name)
         } else {
@@ -193,9 +1524,13 @@ fn serialize_instance<'a, W: Write>(
                 _ => unimplemented!(),
             };
 
-            let converted_value = match value.try_convert_ref(data_type) {
-                TodoValueConversionType::Converted(converted) => Cow::Owned(converted),
-                TodoValueConversionType::Unnecessary => Cow::Borrowed(value),
+            // Some properties are serialized as a narrower type than they're
+            // stored as, like `BasePart.Color`, which is written as a
+            // Color3uint8.
+            let (converted_value, clamped) = match value.try_convert_or_narrow_ref(data_type) {
+                TodoValueConversionType::Converted(converted) => (Cow::Owned(converted), false),
+                TodoValueConversionType::Clamped(converted) => (Cow::Owned(converted), true),
+                TodoValueConversionType::Unnecessary => (Cow::Borrowed(value), false),
                 TodoValueConversionType::Failed => {
                     return Err(
                         writer.error(EncodeErrorKind::UnsupportedPropertyConversion {
@@ -208,15 +1543,42 @@ fn serialize_instance<'a, W: Write>(
                 }
             };
 
-            write_value_xml(writer, state, &serialized_descriptor.name, &converted_value)?;
+            if state.options.property_filter == PropertyFilter::SkipDefaults
+                && is_default_value(&instance.class, property_name, value)
+            {
+                on_skip(writer, state, property_name, SkipReason::DefaultValue)?;
+                continue;
+            }
+
+            let property = VisitedProperty {
+                name: property_name,
+                serialized_name: &serialized_descriptor.name,
+                value: converted_value,
+                original_value: value,
+                known: true,
+                clamped,
+            };
+
+            visit(writer, state, property)?;
         } else {
             match state.options.property_behavior {
-                EncodePropertyBehavior::IgnoreUnknown => {}
+                EncodePropertyBehavior::IgnoreUnknown => {
+                    let reason = skip_reason(&instance.class, property_name);
+                    on_skip(writer, state, property_name, reason)?;
+                }
                 EncodePropertyBehavior::WriteUnknown | EncodePropertyBehavior::NoReflection => {
                     // We'll take this value as-is with no conversions on
                     // either the name or value.
+                    let property = VisitedProperty {
+                        name: property_name,
+                        serialized_name: property_name,
+                        value: Cow::Borrowed(value),
+                        original_value: value,
+                        known: false,
+                        clamped: false,
+                    };
 
-                    write_value_xml(writer, state, property_name, value)?;
+                    visit(writer, state, property)?;
                 }
                 EncodePropertyBehavior::ErrorOnUnknown => {
                     return Err(writer.error(EncodeErrorKind::UnknownProperty {
@@ -228,13 +1590,229 @@ fn serialize_instance<'a, W: Write>(
         }
     }
 
-    writer.write(XmlWriteEvent::end_element())?;
+    Ok(())
+}
 
-    for child_id in instance.children() {
-        serialize_instance(writer, state, tree, *child_id, property_buffer)?;
+/// Tells why a property without a serialized descriptor is being left out.
+fn skip_reason(class_name: &str, property_name: &str) -> SkipReason {
+    match find_exact_property_descriptor(class_name, property_name) {
+        Some(descriptor) if descriptor.tags.contains(&PropertyTag::ReadOnly) => {
+            SkipReason::ReadOnly
+        }
+        Some(_) => SkipReason::NotSerializable,
+        None => SkipReason::NotInDatabase,
     }
+}
 
-    writer.write(XmlWriteEvent::end_element())?;
+/// Tells whether a property has the default value for its class in the
+/// reflection database. Properties without a known default never do.
+fn is_default_value(class_name: &str, property_name: &str, value: &Variant) -> bool {
+    let descriptor = match find_canonical_property_descriptor(class_name, property_name) {
+        Some(descriptor) => descriptor,
+        None => return false,
+    };
+
+    let default = rbx_reflection_database::get()
+        .classes
+        .get(class_name)
+        .and_then(|class| class.default_properties.get(descriptor.name.as_ref()));
+
+    let default = match default {
+        Some(default) => default,
+        None => return false,
+    };
+
+    // Aliases like `BasePart.BrickColor` can hold a different type than the
+    // property they stand for.
+    match value.try_convert_ref(default.ty()) {
+        TodoValueConversionType::Converted(converted) => nearly_equal(&converted, default),
+        TodoValueConversionType::Unnecessary => nearly_equal(value, default),
+        TodoValueConversionType::Clamped(_) | TodoValueConversionType::Failed => false,
+    }
+}
+
+/// How far apart two floats can be, relative to the larger one, and still be
+/// treated as the same value by `PropertyFilter::SkipDefaults`.
+const FLOAT_TOLERANCE: f64 = 1e-5;
+
+/// Compares two values, allowing the floats in them to be slightly different.
+fn nearly_equal(a: &Variant, b: &Variant) -> bool {
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= FLOAT_TOLERANCE * a.abs().max(b.abs()).max(1.0)
+    }
+
+    fn close_all(a: &[f32], b: &[f32]) -> bool {
+        a.iter()
+            .zip(b)
+            .all(|(a, b)| close(f64::from(*a), f64::from(*b)))
+    }
+
+    fn vector3(value: &Vector3) -> [f32; 3] {
+        [value.x, value.y, value.z]
+    }
+
+    fn cframe(value: &CFrame) -> Vec<f32> {
+        let orientation = &value.orientation;
+        [value.position, orientation.x, orientation.y, orientation.z]
+            .iter()
+            .flat_map(vector3)
+            .collect()
+    }
+
+    match (a, b) {
+        (Variant::Float32(a), Variant::Float32(b)) => close_all(&[*a], &[*b]),
+        (Variant::Float64(a), Variant::Float64(b)) => close(*a, *b),
+        (Variant::Vector2(a), Variant::Vector2(b)) => close_all(&[a.x, a.y], &[b.x, b.y]),
+        (Variant::Vector3(a), Variant::Vector3(b)) => close_all(&vector3(a), &vector3(b)),
+        (Variant::Color3(a), Variant::Color3(b)) => close_all(&[a.r, a.g, a.b], &[b.r, b.g, b.b]),
+        (Variant::CFrame(a), Variant::CFrame(b)) => close_all(&cframe(a), &cframe(b)),
+        (Variant::UDim(a), Variant::UDim(b)) => {
+            a.offset == b.offset && close_all(&[a.scale], &[b.scale])
+        }
+        (Variant::UDim2(a), Variant::UDim2(b)) => {
+            a.x.offset == b.x.offset
+                && a.y.offset == b.y.offset
+                && close_all(&[a.x.scale, a.y.scale], &[b.x.scale, b.y.scale])
+        }
+        (Variant::NumberRange(a), Variant::NumberRange(b)) => {
+            close_all(&[a.min, a.max], &[b.min, b.max])
+        }
+        (Variant::Rect(a), Variant::Rect(b)) => close_all(
+            &[a.min.x, a.min.y, a.max.x, a.max.y],
+            &[b.min.x, b.min.y, b.max.x, b.max.y],
+        ),
+        _ => a == b,
+    }
+}
+
+/// Records that a property on the given instance is being left out, or
+/// returns an error if `EncodeOptions::error_on_skip` includes the reason.
+fn skip_property<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+    tree: &WeakDom,
+    id: Ref,
+    property_name: &str,
+    reason: SkipReason,
+) -> Result<(), NewEncodeError> {
+    let instance = tree.full_name(id).unwrap_or_default();
+
+    if state.options.error_on_skip.contains(&reason) {
+        return Err(writer.error(EncodeErrorKind::SkippedProperty {
+            instance,
+            property_name: property_name.to_owned(),
+            reason,
+        }));
+    }
+
+    debug_event!(
+        instance = instance.as_str(),
+        property = property_name,
+        reason = ?reason,
+        "skipped property"
+    );
+
+    state.skipped_properties.push(SkippedProperty {
+        instance,
+        property_name: property_name.to_owned(),
+        reason,
+    });
+
+    Ok(())
+}
+
+/// Writes the given color property a second time as a BrickColor, if the
+/// property used to be a BrickColor.
+fn write_legacy_brick_color<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &mut EmitState,
+    class_name: &str,
+    property_name: &str,
+    value: &Variant,
+) -> Result<(), NewEncodeError> {
+    let legacy_name = match find_legacy_brick_color_name(class_name, property_name) {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+
+    let color = match value {
+        Variant::Color3(color) => Color3uint8::from(*color),
+        Variant::Color3uint8(color) => *color,
+        _ => return Ok(()),
+    };
+
+    // BrickColor values are written as their number, just like Roblox did.
+    let number = BrickColor::from_color3uint8(color) as i32;
+    write_value_xml(writer, state, legacy_name, &Variant::Int32(number))?;
+    state.property_count += 1;
+
+    Ok(())
+}
+
+/// Handles the properties of the tree's root according to
+/// `EncodeOptions::root_property_behavior`, if the given instances are the
+/// children of the root.
+fn serialize_root_properties<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    state: &EmitState,
+    tree: &WeakDom,
+    ids: &[Ref],
+) -> Result<(), NewEncodeError> {
+    let root = tree.root();
+
+    let writes_children = ids
+        .iter()
+        .any(|id| tree.get_by_ref(*id).map(|instance| instance.parent()) == Some(tree.root_ref()));
+
+    if !writes_children || ids.contains(&tree.root_ref()) || root.properties.is_empty() {
+        return Ok(());
+    }
+
+    let mut property_names: Vec<&String> = root.properties.keys().collect();
+    property_names.sort();
+
+    match state.options.root_property_behavior {
+        RootPropertyBehavior::Drop => {
+            log::debug!(
+                "Dropping {} properties from the root instance",
+                property_names.len()
+            );
+        }
+        RootPropertyBehavior::Error => {
+            return Err(writer.error(EncodeErrorKind::RootProperty {
+                property_name: property_names[0].clone(),
+            }));
+        }
+        RootPropertyBehavior::HoistIntoMeta => {
+            for name in property_names {
+                if !ROOT_META_PROPERTIES.contains(&name.as_str()) {
+                    return Err(writer.error(EncodeErrorKind::RootPropertyNotHoistable {
+                        property_name: name.clone(),
+                    }));
+                }
+
+                let data = match &root.properties[name] {
+                    Variant::BinaryString(value) => Cow::Borrowed(value),
+                    Variant::Attributes(value) => Cow::Owned(attributes_to_binary(writer, value)?),
+                    other => {
+                        return Err(
+                            writer.error(EncodeErrorKind::UnsupportedPropertyConversion {
+                                class_name: root.class.clone(),
+                                property_name: name.clone(),
+                                expected_type: VariantType::BinaryString,
+                                actual_type: other.ty(),
+                            }),
+                        );
+                    }
+                };
+
+                writer.write(XmlWriteEvent::start_element("Meta").attr("name", name))?;
+                let encoded = writer.wrap_base64(base64::encode(data.as_ref()));
+                writer.write_string(&encoded)?;
+                writer.end_element()?;
+            }
+        }
+    }
 
     Ok(())
 }
@@ -243,27 +1821,285 @@ fn serialize_shared_strings<W: Write>(
     writer: &mut XmlEventWriter<W>,
     state: &mut EmitState,
 ) -> Result<(), NewEncodeError> {
+    phase_span!(
+        "write_shared_strings",
+        count = state.shared_strings_to_emit.len()
+    );
+
     if state.shared_strings_to_emit.is_empty() {
         return Ok(());
     }
 
     writer.write(XmlWriteEvent::start_element("SharedStrings"))?;
 
-    for value in state.shared_strings_to_emit.values() {
-        // Roblox expects SharedString hashes to be the same length as an MD5
-        // hash: 16 bytes, so we truncate our larger hashes to fit.
-        let full_hash = value.hash();
-        let truncated_hash = &full_hash.as_bytes()[..16];
-
-        writer.write(
-            XmlWriteEvent::start_element("SharedString")
-                .attr("md5", &base64::encode(truncated_hash)),
-        )?;
+    for (key, value) in state.shared_strings_to_emit.values() {
+        writer.write(XmlWriteEvent::start_element("SharedString").attr("md5", key))?;
 
-        writer.write_string(&base64::encode(value.data()))?;
+        match value.blob() {
+            Some(blob) => writer.write_base64_blob(blob)?,
+            None => {
+                let encoded = writer.wrap_base64(base64::encode(value.data()));
+                writer.write_string(&encoded)?;
+            }
+        }
         writer.end_element()?;
     }
 
     writer.end_element()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::InstanceBuilder;
+
+    fn encode_with_ordering(tree: &WeakDom, ids: &[Ref]) -> String {
+        let options = EncodeOptions::new().ref_aware_ordering(true);
+        let mut buffer = Vec::new();
+        encode_internal(&mut buffer, tree, ids, options).unwrap();
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn position_of(source: &str, name: &str) -> usize {
+        let pattern = format!(r#"<string name="Name">{}</string>"#, name);
+        source.find(&pattern).unwrap()
+    }
+
+    #[test]
+    fn weld_targets_come_first() {
+        let _ = env_logger::try_init();
+
+        let part_a = InstanceBuilder::new("Part").with_name("PartA");
+        let part_b = InstanceBuilder::new("Part").with_name("PartB");
+
+        let weld = InstanceBuilder::new("Weld")
+            .with_name("Weld")
+            .with_property("Part0", part_a.referent())
+            .with_property("Part1", part_b.referent());
+
+        let model = InstanceBuilder::new("Model")
+            .with_name("Model")
+            .with_child(weld)
+            .with_child(part_a)
+            .with_child(part_b);
+
+        let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_child(model));
+        let source = encode_with_ordering(&tree, tree.root().children());
+
+        let weld_position = position_of(&source, "Weld");
+        assert!(position_of(&source, "PartA") < weld_position);
+        assert!(position_of(&source, "PartB") < weld_position);
+
+        let decoded = crate::from_str_default(&source).unwrap();
+        let model = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+        let children: Vec<_> = model
+            .children()
+            .iter()
+            .map(|id| decoded.get_by_ref(*id).unwrap())
+            .collect();
+
+        let names: Vec<_> = children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, ["PartA", "PartB", "Weld"]);
+
+        let weld = children[2];
+        assert_eq!(
+            weld.properties.get("Part0"),
+            Some(&children[0].referent().into())
+        );
+        assert_eq!(
+            weld.properties.get("Part1"),
+            Some(&children[1].referent().into())
+        );
+    }
+
+    #[test]
+    fn nested_targets_order_parents() {
+        let target = InstanceBuilder::new("Part").with_name("Target");
+        let weld = InstanceBuilder::new("Weld")
+            .with_name("Weld")
+            .with_property("Part0", target.referent());
+
+        let welds = InstanceBuilder::new("Folder")
+            .with_name("Welds")
+            .with_child(weld);
+        let parts = InstanceBuilder::new("Folder")
+            .with_name("Parts")
+            .with_child(target);
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(welds)
+                .with_child(parts),
+        );
+        let source = encode_with_ordering(&tree, tree.root().children());
+
+        assert!(position_of(&source, "Parts") < position_of(&source, "Welds"));
+        assert!(position_of(&source, "Target") < position_of(&source, "Weld"));
+    }
+
+    #[test]
+    fn cycles_keep_existing_order() {
+        let mut first = InstanceBuilder::new("ObjectValue").with_name("First");
+        let second = InstanceBuilder::new("ObjectValue")
+            .with_name("Second")
+            .with_property("Value", first.referent());
+        first.add_property("Value", second.referent());
+
+        let third = InstanceBuilder::new("ObjectValue")
+            .with_name("Third")
+            .with_property("Value", Ref::none());
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(third)
+                .with_child(first)
+                .with_child(second),
+        );
+        let source = encode_with_ordering(&tree, tree.root().children());
+
+        assert!(position_of(&source, "Third") < position_of(&source, "First"));
+        assert!(position_of(&source, "First") < position_of(&source, "Second"));
+    }
+
+    #[test]
+    fn ordering_is_off_by_default() {
+        let target = InstanceBuilder::new("Part").with_name("Target");
+        let value = InstanceBuilder::new("ObjectValue")
+            .with_name("Value")
+            .with_property("Value", target.referent());
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(value)
+                .with_child(target),
+        );
+
+        let mut buffer = Vec::new();
+        encode_internal(
+            &mut buffer,
+            &tree,
+            tree.root().children(),
+            EncodeOptions::new(),
+        )
+        .unwrap();
+        let source = String::from_utf8(buffer).unwrap();
+
+        assert!(position_of(&source, "Value") < position_of(&source, "Target"));
+    }
+
+    #[test]
+    fn properties_keep_insertion_order() {
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Part")
+                    .with_property("Transparency", 0.5f32)
+                    .with_property("Anchored", true)
+                    .with_property("CanCollide", false),
+            ),
+        );
+
+        let mut buffer = Vec::new();
+        encode_internal(
+            &mut buffer,
+            &tree,
+            tree.root().children(),
+            EncodeOptions::new(),
+        )
+        .unwrap();
+        let source = String::from_utf8(buffer).unwrap();
+
+        let transparency = source.find(r#"name="Transparency""#).unwrap();
+        let anchored = source.find(r#"name="Anchored""#).unwrap();
+        let can_collide = source.find(r#"name="CanCollide""#).unwrap();
+
+        assert!(transparency < anchored);
+        assert!(anchored < can_collide);
+
+        // Decoding reads properties in the order they appear in the file, so
+        // the order survives a round trip.
+        let decoded = crate::from_str_default(&source).unwrap();
+        let part = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+        let names: Vec<_> = part.properties.keys().map(String::as_str).collect();
+        assert_eq!(names, ["Transparency", "Anchored", "CanCollide"]);
+    }
+
+    #[test]
+    fn one_instance_per_step() {
+        let _ = env_logger::try_init();
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("DataModel").with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("A")
+                    .with_child(InstanceBuilder::new("Folder").with_name("B"))
+                    .with_child(
+                        InstanceBuilder::new("Folder")
+                            .with_name("C")
+                            .with_child(InstanceBuilder::new("Folder").with_name("D")),
+                    ),
+            ),
+        );
+
+        let mut serializer = XmlSerializer::new(
+            Vec::new(),
+            &tree,
+            tree.root().children(),
+            EncodeOptions::new(),
+        );
+        let mut pieces = Vec::new();
+
+        loop {
+            let progress = serializer.step(1).unwrap();
+            pieces.push(String::from_utf8(std::mem::take(serializer.output_mut())).unwrap());
+
+            if progress == SerializeProgress::Done {
+                break;
+            }
+        }
+
+        // Each step writes one instance, and stops right before the next one.
+        // Closing tags go out with the step before them.
+        assert_eq!(pieces.len(), 4);
+        for (piece, name) in pieces.iter().zip(&["A", "B", "C", "D"]) {
+            assert_eq!(piece.matches("<Item ").count(), 1, "{}", piece);
+            assert!(piece.contains(&format!(">{}<", name)), "{}", piece);
+        }
+        assert!(pieces[1].ends_with("</Item>"), "{}", pieces[1]);
+        assert!(pieces[3].trim_end().ends_with("</roblox>"), "{}", pieces[3]);
+
+        assert_eq!(serializer.step(1).unwrap(), SerializeProgress::Done);
+        assert_eq!(serializer.into_summary().instance_count, 4);
+    }
+
+    #[test]
+    fn colliding_shared_string_keys_are_made_unique() {
+        let _ = env_logger::try_init();
+
+        fn same_key(_value: &SharedString) -> io::Result<String> {
+            Ok("X5R87GzoVqKYjBgqdQ1lNg==".to_owned())
+        }
+
+        let mut state = EmitState::new(EncodeOptions::new());
+        let first = SharedString::new(b"first".to_vec());
+        let second = SharedString::new(b"second".to_vec());
+
+        let first_key = state
+            .add_shared_string_with(first.clone(), same_key)
+            .unwrap()
+            .to_owned();
+        let second_key = state
+            .add_shared_string_with(second, same_key)
+            .unwrap()
+            .to_owned();
+        assert_eq!(first_key, "X5R87GzoVqKYjBgqdQ1lNg==");
+        assert_eq!(second_key, "X5R87GzoVqKYjBgqdQ1lNg==-2");
+
+        // Strings with the same contents still share an entry.
+        let again = state.add_shared_string_with(first, same_key).unwrap();
+        assert_eq!(again, first_key);
+        assert_eq!(state.shared_strings_to_emit.len(), 2);
+    }
+}
@@ -1,6 +1,14 @@
-use std::{fmt::Write as FmtWrite, io::Write};
+use std::{
+    fmt::{self, Write as FmtWrite},
+    io::{Read, Write},
+};
+
+use rbx_dom_weak::types::Blob;
 
-use xml::writer::{EmitterConfig, EventWriter};
+use xml::{
+    reader::{ParserConfig, XmlEvent as XmlReadEvent},
+    writer::{EmitterConfig, EventWriter},
+};
 
 pub use xml::writer::XmlEvent as XmlWriteEvent;
 
@@ -14,13 +22,30 @@ use crate::{
 pub struct XmlEventWriter<W> {
     inner: EventWriter<W>,
     character_buffer: String,
+    base64_line_length: Option<usize>,
+
+    /// What each level of indentation is made of, if elements are indented.
+    /// xml-rs keeps this to itself, but `write_tag_array` needs it to indent
+    /// the tags it writes without going through xml-rs.
+    indent: Option<String>,
+
+    /// The number of elements that have been started but not ended.
+    depth: usize,
 }
 
 impl<W: Write> XmlEventWriter<W> {
-    /// Constructs an `XmlEventWriter` from an output that implements `Write`.
-    pub fn from_output(output: W) -> XmlEventWriter<W> {
-        let inner = EmitterConfig::new()
-            .perform_indent(true)
+    /// Constructs an `XmlEventWriter` from an output that implements `Write`,
+    /// which indents each element with `indent` once for every element it's
+    /// in. `None` writes no whitespace between elements at all.
+    pub fn from_output(output: W, indent: Option<String>) -> XmlEventWriter<W> {
+        let config = match &indent {
+            Some(indent) => EmitterConfig::new()
+                .perform_indent(true)
+                .indent_string(indent.clone()),
+            None => EmitterConfig::new().perform_indent(false),
+        };
+
+        let inner = config
             .write_document_declaration(false)
             .normalize_empty_elements(false)
             .create_writer(output);
@@ -28,25 +53,126 @@ impl<W: Write> XmlEventWriter<W> {
         XmlEventWriter {
             inner,
             character_buffer: String::new(),
+            base64_line_length: None,
+            indent,
+            depth: 0,
+        }
+    }
+
+    /// Sets how many characters of base64 go on each line when it's written
+    /// by [`wrap_base64`][Self::wrap_base64] or
+    /// [`write_base64_blob`][Self::write_base64_blob]. `None` or `Some(0)`
+    /// puts it all on one line, which is the default.
+    pub fn with_base64_line_length(self, base64_line_length: Option<usize>) -> Self {
+        XmlEventWriter {
+            base64_line_length: base64_line_length.filter(|len| *len > 0),
+            ..self
+        }
+    }
+
+    /// Breaks base64 from `base64::encode` into lines, if this writer has a
+    /// line length set.
+    pub fn wrap_base64(&self, encoded: String) -> String {
+        match self.base64_line_length {
+            Some(line_length) => {
+                let mut wrapped =
+                    String::with_capacity(encoded.len() + encoded.len() / line_length);
+                wrap_lines(&encoded, line_length, &mut 0, &mut wrapped);
+                wrapped
+            }
+            None => encoded,
         }
     }
 
+    /// Borrows the output that this writer is emitting XML into.
+    pub fn output(&self) -> &W {
+        self.inner.inner_ref()
+    }
+
+    /// Mutably borrows the output that this writer is emitting XML into.
+    pub fn output_mut(&mut self) -> &mut W {
+        self.inner.inner_mut()
+    }
+
+    /// Flushes the output, so that everything written so far reaches its
+    /// destination instead of waiting in a buffer.
+    pub fn flush(&mut self) -> Result<(), NewEncodeError> {
+        self.inner.inner_mut().flush().map_err(|e| self.error(e))
+    }
+
     pub(crate) fn error<T: Into<EncodeErrorKind>>(&self, kind: T) -> NewEncodeError {
         NewEncodeError::new_from_writer(kind.into(), &self.inner)
     }
 
     pub fn end_element(&mut self) -> Result<(), NewEncodeError> {
-        self.inner
-            .write(XmlWriteEvent::end_element())
-            .map_err(|e| self.error(e))
+        self.write(XmlWriteEvent::end_element())
     }
 
     /// Writes a single XML event to the output stream.
+    ///
+    /// xml-rs escapes markup characters and line breaks in attribute values,
+    /// but writes everything else as-is, so a start tag with an attribute
+    /// that XML can't represent at all is an error instead.
     pub fn write<'a, E>(&mut self, event: E) -> Result<(), NewEncodeError>
     where
         E: Into<XmlWriteEvent<'a>>,
     {
-        self.inner.write(event).map_err(|e| self.error(e))
+        let event = event.into();
+
+        if let XmlWriteEvent::StartElement {
+            name, attributes, ..
+        } = &event
+        {
+            for attribute in attributes.iter() {
+                if let Some(character) = attribute.value.chars().find(|c| !is_xml_char(*c)) {
+                    return Err(self.error(EncodeErrorKind::InvalidAttributeValue {
+                        element: name.local_name.to_owned(),
+                        attribute: attribute.name.local_name.to_owned(),
+                        value: attribute.value.to_owned(),
+                        character,
+                    }));
+                }
+            }
+        }
+
+        let depth = match &event {
+            XmlWriteEvent::StartElement { .. } => self.depth + 1,
+            XmlWriteEvent::EndElement { .. } => self.depth.saturating_sub(1),
+            _ => self.depth,
+        };
+
+        self.inner.write(event).map_err(|e| self.error(e))?;
+        self.depth = depth;
+
+        Ok(())
+    }
+
+    /// Writes an element that was read by
+    /// [`XmlEventReader::read_raw_element`][crate::deserializer_core::XmlEventReader::read_raw_element],
+    /// indented to match the rest of the document.
+    pub fn write_raw_element(&mut self, xml: &str) -> Result<(), NewEncodeError> {
+        let reader = ParserConfig::new().create_reader(xml.as_bytes());
+
+        for event in reader {
+            let event = event.map_err(|_| {
+                self.error(EncodeErrorKind::bug(
+                    "an element read from a document couldn't be read again",
+                ))
+            })?;
+
+            match event {
+                XmlReadEvent::StartDocument { .. }
+                | XmlReadEvent::EndDocument
+                | XmlReadEvent::Whitespace(_) => {}
+                event => {
+                    if let Some(event) = event.as_writer_event() {
+                        self.write(event)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
     }
 
     /// Writes a string slice to the output stream as characters or CDATA.
@@ -54,12 +180,70 @@ impl<W: Write> XmlEventWriter<W> {
         write_characters_or_cdata(&mut self.inner, value)
     }
 
-    /// Writes a value that implements `Display` as characters or CDATA. Resuses
-    /// an internal buffer to avoid unnecessary allocations.
-    pub fn write_characters<T: std::fmt::Display>(
-        &mut self,
-        value: T,
-    ) -> Result<(), NewEncodeError> {
+    /// Writes the contents of a blob as base64, reading them from the blob's
+    /// store a piece at a time instead of all at once.
+    pub fn write_base64_blob(&mut self, blob: &Blob) -> Result<(), NewEncodeError> {
+        // Base64 never needs escaping, so it can go straight to the output
+        // once xml-rs has closed the start tag.
+        self.write(XmlWriteEvent::characters(""))?;
+
+        let mut reader = blob.open().map_err(|e| self.error(e))?;
+
+        // Pieces are encoded separately, so the position in the current line
+        // carries over from one piece to the next.
+        let mut column = 0;
+        let mut wrapped = String::new();
+
+        // Every piece but the last is a multiple of 3 bytes long, so that
+        // only the end of the output has padding.
+        let mut piece = vec![0; 3 * 4096];
+
+        loop {
+            let mut len = 0;
+            while len < piece.len() {
+                match reader.read(&mut piece[len..]) {
+                    Ok(0) => break,
+                    Ok(read) => len += read,
+                    Err(err) => return Err(self.error(err)),
+                }
+            }
+
+            let encoded = base64::encode(&piece[..len]);
+            let output = match self.base64_line_length {
+                Some(line_length) => {
+                    wrapped.clear();
+                    wrap_lines(&encoded, line_length, &mut column, &mut wrapped);
+                    &wrapped
+                }
+                None => &encoded,
+            };
+
+            self.inner
+                .inner_mut()
+                .write_all(output.as_bytes())
+                .map_err(|e| NewEncodeError::new_from_writer(e.into(), &self.inner))?;
+
+            if len < piece.len() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Writes a value as characters or CDATA. Numbers are formatted on the
+    /// stack, and anything else reuses an internal buffer to avoid unnecessary
+    /// allocations.
+    pub fn write_characters<T: XmlCharacters>(&mut self, value: T) -> Result<(), NewEncodeError> {
+        value.write_characters_to(self)
+    }
+
+    /// Writes the already-formatted text of a number as a `Characters` event.
+    /// Numbers never have leading or trailing whitespace, so this skips the
+    /// CDATA check that `write_string` does.
+    fn write_number_str(&mut self, value: &str) -> Result<(), NewEncodeError> {
+        self.write(XmlWriteEvent::characters(value))
+    }
+
+    fn write_display<T: fmt::Display>(&mut self, value: T) -> Result<(), NewEncodeError> {
         write!(self.character_buffer, "{}", value).unwrap();
         write_characters_or_cdata(&mut self.inner, &self.character_buffer)?;
         self.character_buffer.clear();
@@ -71,49 +255,346 @@ impl<W: Write> XmlEventWriter<W> {
         value.write_xml(self)
     }
 
+    /// Writes a start tag with the given name and no attributes, calls `body`
+    /// to write the contents of the element, then closes the element.
+    pub fn write_nested<F>(&mut self, tag: &str, body: F) -> Result<(), NewEncodeError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), NewEncodeError>,
+    {
+        self.write(XmlWriteEvent::start_element(tag))?;
+        body(self)?;
+        self.write(XmlWriteEvent::end_element())
+    }
+
     pub fn write_value_in_tag<T: XmlType>(
         &mut self,
         value: &T,
         tag: &str,
     ) -> Result<(), NewEncodeError> {
-        self.write(XmlWriteEvent::start_element(tag))?;
-        self.write_value(value)?;
-        self.write(XmlWriteEvent::end_element())
+        self.write_nested(tag, |writer| writer.write_value(value))
     }
 
     /// The same as `write_characters`, but wraps the characters in a tag with
     /// the given name and no attributes.
-    pub fn write_tag_characters<T: std::fmt::Display>(
+    pub fn write_tag_characters<T: XmlCharacters>(
         &mut self,
         tag: &str,
         value: T,
     ) -> Result<(), NewEncodeError> {
-        self.write(XmlWriteEvent::start_element(tag))?;
-        self.write_characters(value)?;
-        self.write(XmlWriteEvent::end_element())
+        self.write_nested(tag, |writer| writer.write_characters(value))
     }
 
-    /// Writes a list of values that implement `Display`, with each wrapped in
-    /// an associated tag. This method uses the same optimization as
-    /// `write_characters` to avoid extra allocations.
-    pub fn write_tag_array<T: std::fmt::Display>(
+    /// Writes a list of numbers, with each wrapped in its associated tag, like
+    /// the `<X>`, `<Y>`, and `<Z>` of a Vector3. The output is the same as
+    /// writing each number with `write_value_in_tag`.
+    pub fn write_tag_array<T: TagText>(
         &mut self,
-        values: &[T],
         tags: &[&str],
+        values: &[T],
     ) -> Result<(), NewEncodeError> {
         assert_eq!(values.len(), tags.len());
 
-        for (index, component) in values.iter().enumerate() {
-            self.write_tag_characters(tags[index], component)?;
+        self.write_tag_batch(tags.len(), |index| (tags[index], &values[index]))
+    }
+
+    /// Like `write_tag_array`, but for numbers of different types, like the
+    /// float scale and integer offset of a UDim.
+    pub fn write_tag_values(
+        &mut self,
+        tags: &[(&str, &dyn TagText)],
+    ) -> Result<(), NewEncodeError> {
+        self.write_tag_batch(tags.len(), |index| tags[index])
+    }
+
+    /// Writes `count` tags, getting each one's name and value from `tag`.
+    ///
+    /// Going through xml-rs costs a few allocations for every element, which
+    /// adds up for types like CFrame that are nothing but small numbers. None
+    /// of that is needed for a tag with no attributes whose text never needs
+    /// escaping, so every tag but the last is written straight to the output,
+    /// indented the same way xml-rs would. The last one goes through xml-rs
+    /// so that it knows an element was written, and indents what comes after
+    /// it correctly.
+    fn write_tag_batch<'a, F>(&mut self, count: usize, tag: F) -> Result<(), NewEncodeError>
+    where
+        F: Fn(usize) -> (&'a str, &'a dyn TagText),
+    {
+        let last = match count.checked_sub(1) {
+            Some(last) => last,
+            None => return Ok(()),
+        };
+
+        let mut buffer = std::mem::take(&mut self.character_buffer);
+
+        for index in 0..last {
+            let (name, value) = tag(index);
+
+            if let Some(indent) = &self.indent {
+                buffer.push('\n');
+                for _ in 0..self.depth {
+                    buffer.push_str(indent);
+                }
+            }
+
+            buffer.push('<');
+            buffer.push_str(name);
+            buffer.push('>');
+            value.push_tag_text(&mut buffer);
+            buffer.push_str("</");
+            buffer.push_str(name);
+            buffer.push('>');
         }
 
+        self.inner
+            .inner_mut()
+            .write_all(buffer.as_bytes())
+            .map_err(|e| NewEncodeError::new_from_writer(e.into(), &self.inner))?;
+
+        let (name, value) = tag(last);
+        buffer.clear();
+        value.push_tag_text(&mut buffer);
+
+        self.write(XmlWriteEvent::start_element(name))?;
+        self.write(XmlWriteEvent::characters(&buffer))?;
+        self.write(XmlWriteEvent::end_element())?;
+
+        buffer.clear();
+        self.character_buffer = buffer;
+
         Ok(())
     }
 }
 
+/// A number that `XmlEventWriter::write_tag_array` knows how to write.
+///
+/// This writes the same text as the number's `XmlType` implementation,
+/// including `INF`, `-INF`, and `NAN` for floats that aren't finite.
+pub trait TagText {
+    fn push_tag_text(&self, output: &mut String);
+}
+
+/// A value that `XmlEventWriter::write_characters` knows how to write.
+///
+/// Integers and floats are formatted with itoa and ryu, which don't need to
+/// allocate. Their output is kept identical to what `Display` produces, which
+/// is what rbx_xml has always written.
+pub trait XmlCharacters {
+    fn write_characters_to<W: Write>(
+        &self,
+        writer: &mut XmlEventWriter<W>,
+    ) -> Result<(), NewEncodeError>;
+}
+
+impl<T: XmlCharacters + ?Sized> XmlCharacters for &T {
+    fn write_characters_to<W: Write>(
+        &self,
+        writer: &mut XmlEventWriter<W>,
+    ) -> Result<(), NewEncodeError> {
+        (**self).write_characters_to(writer)
+    }
+}
+
+impl XmlCharacters for str {
+    fn write_characters_to<W: Write>(
+        &self,
+        writer: &mut XmlEventWriter<W>,
+    ) -> Result<(), NewEncodeError> {
+        writer.write_string(self)
+    }
+}
+
+impl XmlCharacters for String {
+    fn write_characters_to<W: Write>(
+        &self,
+        writer: &mut XmlEventWriter<W>,
+    ) -> Result<(), NewEncodeError> {
+        writer.write_string(self)
+    }
+}
+
+macro_rules! integer_characters {
+    ($($rust_type: ty),*) => {
+        $(
+            impl XmlCharacters for $rust_type {
+                fn write_characters_to<W: Write>(
+                    &self,
+                    writer: &mut XmlEventWriter<W>,
+                ) -> Result<(), NewEncodeError> {
+                    let mut buffer = itoa::Buffer::new();
+                    writer.write_number_str(buffer.format(*self))
+                }
+            }
+        )*
+    };
+}
+
+integer_characters!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! integer_tag_text {
+    ($($rust_type: ty),*) => {
+        $(
+            impl TagText for $rust_type {
+                fn push_tag_text(&self, output: &mut String) {
+                    output.push_str(itoa::Buffer::new().format(*self));
+                }
+            }
+        )*
+    };
+}
+
+integer_tag_text!(i16, i32, i64);
+
+macro_rules! float_characters {
+    ($($rust_type: ty => $mantissa_bits: expr, $exponent_bits: expr, $exponent_bias: expr),*) => {
+        $(
+            impl XmlCharacters for $rust_type {
+                fn write_characters_to<W: Write>(
+                    &self,
+                    writer: &mut XmlEventWriter<W>,
+                ) -> Result<(), NewEncodeError> {
+                    let mut buffer = ryu::Buffer::new();
+
+                    match self.format_like_display(&mut buffer) {
+                        Some(formatted) => writer.write_number_str(formatted),
+                        None => writer.write_display(self),
+                    }
+                }
+            }
+
+            impl TagText for $rust_type {
+                fn push_tag_text(&self, output: &mut String) {
+                    if *self == <$rust_type>::INFINITY {
+                        output.push_str("INF");
+                    } else if *self == <$rust_type>::NEG_INFINITY {
+                        output.push_str("-INF");
+                    } else if self.is_nan() {
+                        output.push_str("NAN");
+                    } else {
+                        let mut buffer = ryu::Buffer::new();
+
+                        match self.format_like_display(&mut buffer) {
+                            Some(formatted) => output.push_str(formatted),
+                            None => write!(output, "{}", self).unwrap(),
+                        }
+                    }
+                }
+            }
+
+            impl FormatLikeDisplay for $rust_type {
+                fn format_like_display<'a>(&self, buffer: &'a mut ryu::Buffer) -> Option<&'a str> {
+                    // Past this point, neighboring values are a whole number
+                    // or more apart and the shortest digits can leave off
+                    // part of the integer.
+                    let integer_limit = (1u64 << $mantissa_bits) as $rust_type;
+
+                    if !self.is_finite() || self.abs() >= integer_limit {
+                        return None;
+                    }
+
+                    let bits = self.to_bits();
+                    let mantissa_mask = (1 << $mantissa_bits) - 1;
+                    let biased_exponent = (bits >> $mantissa_bits) as i32 & ((1 << $exponent_bits) - 1);
+
+                    let (significand, exponent) = if biased_exponent == 0 {
+                        (bits & mantissa_mask, 1 - $exponent_bias - $mantissa_bits)
+                    } else {
+                        (
+                            (bits & mantissa_mask) | (1 << $mantissa_bits),
+                            biased_exponent - $exponent_bias - $mantissa_bits,
+                        )
+                    };
+
+                    let fraction_digits = if significand == 0 {
+                        0
+                    } else {
+                        -(exponent + significand.trailing_zeros() as i32).min(0)
+                    };
+
+                    display_compatible_float(buffer.format_finite(*self), fraction_digits)
+                }
+            }
+        )*
+    };
+}
+
+float_characters!(f32 => 23, 8, 127, f64 => 52, 11, 1023);
+
+/// Formats a float with ryu, giving the same text as `Display` would.
+trait FormatLikeDisplay {
+    /// Returns `None` for values that ryu can't format the same way, which
+    /// need to be formatted with `Display` instead.
+    fn format_like_display<'a>(&self, buffer: &'a mut ryu::Buffer) -> Option<&'a str>;
+}
+
+/// Adjusts a float formatted by ryu to match the output of `Display`, given
+/// the number of digits after the decimal point in the float's exact value.
+///
+/// Both produce the shortest digits that round-trip, and pick the closest of
+/// those to the exact value. They differ in a few ways:
+///
+/// - ryu always includes a fractional part, which is trimmed off here.
+/// - ryu switches to exponent notation for very large and very small values,
+///   which `Display` never does.
+/// - When the exact value lies halfway between the two closest candidates,
+///   ryu rounds to even while `Display` rounds up. That can only happen when
+///   the exact value has one more fractional digit than the output.
+///
+/// The last two are rare enough that this returns `None` for them so the
+/// caller can use `Display` instead.
+fn display_compatible_float(formatted: &str, exact_fraction_digits: i32) -> Option<&str> {
+    if formatted.contains('e') {
+        return None;
+    }
+
+    let formatted = formatted.strip_suffix(".0").unwrap_or(formatted);
+
+    let output_fraction_digits = match formatted.find('.') {
+        Some(index) => (formatted.len() - index - 1) as i32,
+        None => 0,
+    };
+
+    if exact_fraction_digits == output_fraction_digits + 1 {
+        return None;
+    }
+
+    Some(formatted)
+}
+
+/// Copies base64 into `output`, starting a new line before any character that
+/// would go past `line_length`. `column` is how many characters are already on
+/// the current line, and is updated to match the end of the output. Since
+/// lines are only started when there's more to write, the output never ends
+/// with a line break.
+fn wrap_lines(encoded: &str, line_length: usize, column: &mut usize, output: &mut String) {
+    let mut rest = encoded;
+
+    while !rest.is_empty() {
+        if *column == line_length {
+            output.push('\n');
+            *column = 0;
+        }
+
+        // Base64 is always ASCII, so any byte index is a character boundary.
+        let len = (line_length - *column).min(rest.len());
+        output.push_str(&rest[..len]);
+        *column += len;
+        rest = &rest[len..];
+    }
+}
+
+/// Tells whether a character is allowed anywhere in an XML 1.0 document. Most
+/// control characters aren't, even as character references, and neither are
+/// the two noncharacters at the end of the Basic Multilingual Plane.
+pub(crate) fn is_xml_char(character: char) -> bool {
+    !matches!(
+        character,
+        '\u{0}'..='\u{8}' | '\u{B}' | '\u{C}' | '\u{E}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}'
+    )
+}
+
 /// Given a value, writes a `Characters` event or a `CData` event depending on
 /// whether the input string contains whitespace that needs to be explicitly
-/// preserved.
+/// preserved. Carriage returns are written as `&#13;`.
 ///
 /// This method is extracted so that it can be used inside both `write_string`
 /// and `write_characters` without borrowing issues.
@@ -133,10 +614,46 @@ fn write_characters_or_cdata<W: Write>(
         (None, None) => false,
     };
 
-    if has_outer_whitespace {
+    // Conforming XML parsers, including Roblox's, turn every carriage return
+    // in text into a line feed, which CDATA can't prevent. The only way to
+    // keep one is to write it as a character reference, and xml-rs has no
+    // event for that, so we write those straight to the output.
+    if value.contains('\r') {
+        for (i, piece) in value.split('\r').enumerate() {
+            if i > 0 {
+                writer
+                    .inner_mut()
+                    .write_all(b"&#13;")
+                    .map_err(|e| NewEncodeError::new_from_writer(e.into(), writer))?;
+            }
+
+            // This is written even when empty so that xml-rs closes the start
+            // tag before anything goes to the output behind its back.
+            writer
+                .write(XmlWriteEvent::characters(piece))
+                .map_err(|e| NewEncodeError::new_from_writer(e.into(), writer))?;
+        }
+    } else if has_outer_whitespace {
+        // A CDATA section ends at the first `]]>` in it, which Lua sources can
+        // contain inside of long comments. Some versions of xml-rs write those
+        // as-is, so we split the value into several sections that each end
+        // between the `]]` and the `>`. Parsers join adjacent sections back
+        // together.
+        let mut rest = value;
+
+        while let Some(index) = rest.find("]]>") {
+            let (piece, remaining) = rest.split_at(index + 2);
+
+            writer
+                .write(XmlWriteEvent::cdata(piece))
+                .map_err(|e| NewEncodeError::new_from_writer(e.into(), writer))?;
+
+            rest = remaining;
+        }
+
         writer
-            .write(XmlWriteEvent::cdata(value))
-            .map_err(|e| NewEncodeError::new_from_writer(e.into(), &writer))?;
+            .write(XmlWriteEvent::cdata(rest))
+            .map_err(|e| NewEncodeError::new_from_writer(e.into(), writer))?;
     } else {
         writer
             .write(XmlWriteEvent::characters(value))
@@ -145,3 +662,177 @@ fn write_characters_or_cdata<W: Write>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn written<T: XmlCharacters>(value: T) -> String {
+        let mut writer = XmlEventWriter::from_output(Vec::new(), Some("  ".to_owned()));
+        writer.write_characters(value).unwrap();
+        String::from_utf8(writer.output().clone()).unwrap()
+    }
+
+    /// Yields a spread of bit patterns covering every exponent, plus a few
+    /// values picked by hand.
+    fn sample_bits(step: u64, max: u64) -> impl Iterator<Item = u64> {
+        (0..=max / step).map(move |i| i * step)
+    }
+
+    #[test]
+    fn floats_match_display() {
+        let specials = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            0.1,
+            0.5,
+            1.5,
+            100.0,
+            1e7,
+            1e15,
+            1e16,
+            1e17,
+            1e-5,
+            1e-7,
+            123456789.0,
+            std::f64::consts::PI,
+        ];
+
+        for &value in &specials {
+            assert_eq!(written(value), format!("{}", value));
+            assert_eq!(written(value as f32), format!("{}", value as f32));
+        }
+
+        for value in [f64::MIN_POSITIVE, f64::MAX, f64::MIN, f64::EPSILON].iter() {
+            assert_eq!(written(*value), format!("{}", value));
+        }
+
+        for value in [f32::MIN_POSITIVE, f32::MAX, f32::MIN, f32::EPSILON].iter() {
+            assert_eq!(written(*value), format!("{}", value));
+        }
+
+        // These have two equally close shortest representations. The first
+        // is 2015.65625, which is written as 2015.6563.
+        let ties = [f32::from_bits(0x44fb_f500), 0.5625, 1.25, 0.125];
+
+        for &value in &ties {
+            assert_eq!(written(value), format!("{}", value));
+            assert_eq!(written(value as f64), format!("{}", value as f64));
+        }
+
+        for bits in sample_bits(65_521, u32::MAX as u64) {
+            let value = f32::from_bits(bits as u32);
+            assert_eq!(written(value), format!("{}", value), "bits {:#x}", bits);
+        }
+
+        for bits in sample_bits(0x0000_7ff0_0000_0001, u64::MAX) {
+            let value = f64::from_bits(bits);
+            assert_eq!(written(value), format!("{}", value), "bits {:#x}", bits);
+        }
+    }
+
+    #[test]
+    fn integers_match_display() {
+        for value in [0, 1, -1, 9, 10, 99, 100, i32::MAX, i32::MIN].iter() {
+            assert_eq!(written(*value), format!("{}", value));
+        }
+
+        for value in [0, 1, u32::MAX].iter() {
+            assert_eq!(written(*value), format!("{}", value));
+        }
+
+        for value in [i64::MAX, i64::MIN].iter() {
+            assert_eq!(written(*value), format!("{}", value));
+        }
+
+        assert_eq!(written(0b10_1101u8), "45");
+    }
+
+    #[test]
+    fn tag_arrays_match_separate_tags() {
+        let tags = ["A", "B", "C", "D", "E", "F", "G", "H"];
+        let values = [
+            -0.0,
+            f32::from_bits(1),
+            f32::MIN_POSITIVE / 2.0,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+            16777216.0,
+            0.1,
+        ];
+
+        let write = |indent: Option<&str>, batched: bool| {
+            let mut writer = XmlEventWriter::from_output(Vec::new(), indent.map(str::to_owned));
+            writer.write(XmlWriteEvent::start_element("Outer")).unwrap();
+            writer.write(XmlWriteEvent::start_element("Inner")).unwrap();
+
+            if batched {
+                writer.write_tag_array(&tags, &values).unwrap();
+                writer
+                    .write_tag_values(&[("S", &0.5f32), ("O", &-12i32)])
+                    .unwrap();
+            } else {
+                for (tag, value) in tags.iter().zip(&values) {
+                    writer.write_value_in_tag(value, tag).unwrap();
+                }
+                writer.write_value_in_tag(&0.5f32, "S").unwrap();
+                writer.write_value_in_tag(&-12i32, "O").unwrap();
+            }
+
+            writer.end_element().unwrap();
+            writer.write(XmlWriteEvent::start_element("After")).unwrap();
+            writer.end_element().unwrap();
+            writer.end_element().unwrap();
+
+            String::from_utf8(writer.output().clone()).unwrap()
+        };
+
+        for indent in &[Some("  "), Some("\t"), None] {
+            assert_eq!(write(*indent, true), write(*indent, false));
+        }
+
+        assert!(write(None, true)
+            .contains("<A>-0</A><B>0.000000000000000000000000000000000000000000001</B>"));
+    }
+
+    #[test]
+    fn strings_keep_cdata() {
+        assert_eq!(written("INF"), "INF");
+        assert_eq!(written(" padded "), "<![CDATA[ padded ]]>");
+    }
+
+    #[test]
+    fn cdata_terminators_are_split() {
+        assert_eq!(written(" a]]>b "), "<![CDATA[ a]]]]><![CDATA[>b ]]>");
+        assert_eq!(
+            written(" ]]>]]> "),
+            "<![CDATA[ ]]]]><![CDATA[>]]]]><![CDATA[> ]]>"
+        );
+        assert_eq!(written("a]]>b"), "a]]&gt;b");
+    }
+
+    #[test]
+    fn carriage_returns_are_escaped() {
+        assert_eq!(written("a\r\nb"), "a&#13;\nb");
+        assert_eq!(written(" \r"), " &#13;");
+        assert_eq!(written("\r\r"), "&#13;&#13;");
+    }
+
+    #[test]
+    fn wrap_base64() {
+        let writer = XmlEventWriter::from_output(Vec::new(), Some("  ".to_owned()))
+            .with_base64_line_length(Some(4));
+        assert_eq!(
+            writer.wrap_base64("abcdefghij".to_owned()),
+            "abcd\nefgh\nij"
+        );
+        assert_eq!(writer.wrap_base64("abcdefgh".to_owned()), "abcd\nefgh");
+        assert_eq!(writer.wrap_base64(String::new()), "");
+
+        let writer = writer.with_base64_line_length(Some(0));
+        assert_eq!(writer.wrap_base64("abcdefgh".to_owned()), "abcdefgh");
+    }
+}
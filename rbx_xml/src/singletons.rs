@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use rbx_dom_weak::{types::Ref, Instance, InstanceBuilder, WeakDom};
+use rbx_reflection::{ClassDescriptor, ClassTag};
+
+use crate::{
+    error::{EncodeError, EncodeErrorKind},
+    serializer::SingletonBehavior,
+    serializer_core::XmlEventWriter,
+};
+
+/// Where Roblox Studio puts the singletons that every place has. Which classes
+/// are singletons comes from the reflection database, which doesn't record
+/// where they go, so a singleton that isn't listed here is never synthesized.
+static SINGLETON_PARENTS: &[(&str, &str)] = &[
+    ("Terrain", "Workspace"),
+    ("StarterPlayerScripts", "StarterPlayer"),
+    ("StarterCharacterScripts", "StarterPlayer"),
+];
+
+/// Tells whether there can only be one instance of a class in each parent.
+///
+/// Services are singletons, and so are the instances that Roblox creates in
+/// every place but that can't be created with `Instance.new`, like Terrain.
+/// Those are the classes that are tagged `NotCreatable` and have default
+/// properties, which the database only has for classes that Studio could be
+/// made to save. Abstract classes like `BasePart` are `NotCreatable` too, but
+/// have no default properties.
+fn is_singleton(class: &ClassDescriptor) -> bool {
+    if class.tags.contains(&ClassTag::Service) {
+        return true;
+    }
+
+    class.tags.contains(&ClassTag::NotCreatable)
+        && !class.tags.contains(&ClassTag::Deprecated)
+        && !class.default_properties.is_empty()
+}
+
+/// Looks for two singletons of the same class with the same parent among the
+/// given instances and their descendants, and either logs each one or returns
+/// an error for the first one, depending on `behavior`.
+pub(crate) fn check_duplicate_singletons<W: Write>(
+    writer: &XmlEventWriter<W>,
+    tree: &WeakDom,
+    ids: &[Ref],
+    behavior: SingletonBehavior,
+) -> Result<(), EncodeError> {
+    if behavior == SingletonBehavior::Ignore {
+        return Ok(());
+    }
+
+    let database = rbx_reflection_database::get();
+
+    // The top-level instances are siblings in the document even if they
+    // aren't in the dom.
+    let mut groups: Vec<(Option<Ref>, &[Ref])> = vec![(None, ids)];
+    for id in ids {
+        groups.extend(
+            tree.descendants(*id)
+                .map(|instance| (Some(instance.referent()), instance.children())),
+        );
+    }
+
+    for (parent, children) in groups {
+        let mut seen = HashSet::new();
+
+        for child in children {
+            let class_name = &tree.get_by_ref(*child).unwrap().class;
+
+            let singleton = match database.classes.get(class_name.as_str()) {
+                Some(class) => is_singleton(class),
+                None => false,
+            };
+
+            if !singleton || seen.insert(class_name) {
+                continue;
+            }
+
+            let parent = match parent {
+                Some(parent) => tree.full_name(parent).unwrap(),
+                None => "the top level of the document".to_owned(),
+            };
+
+            if behavior == SingletonBehavior::ErrorOnDuplicates {
+                return Err(writer.error(EncodeErrorKind::DuplicateSingleton {
+                    class_name: class_name.clone(),
+                    parent,
+                }));
+            }
+
+            log::warn!(
+                "Found more than one {} in {}, which Roblox expects only one of",
+                class_name,
+                parent
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the singletons that Roblox Studio would put in `instance` but
+/// that it doesn't have, each in a dom of its own with its default
+/// properties.
+pub(crate) fn missing_singletons(tree: &WeakDom, instance: &Instance) -> Vec<WeakDom> {
+    let database = rbx_reflection_database::get();
+
+    SINGLETON_PARENTS
+        .iter()
+        .filter(|(_, parent)| *parent == instance.class)
+        .filter_map(|(class_name, _)| database.classes.get(*class_name))
+        .filter(|class| is_singleton(class))
+        .filter(|class| {
+            !instance
+                .children()
+                .iter()
+                .any(|child| tree.get_by_ref(*child).unwrap().class == class.name)
+        })
+        .map(|class| {
+            // The database's defaults are unordered, so they're sorted to
+            // keep the output the same from one run to the next.
+            let mut properties: Vec<_> = class
+                .default_properties
+                .iter()
+                .filter(|(name, _)| *name != "Name")
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect();
+            properties.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            WeakDom::new(
+                InstanceBuilder::new(class.name.as_ref())
+                    .with_name(class.name.as_ref())
+                    .with_properties(properties),
+            )
+        })
+        .collect()
+}
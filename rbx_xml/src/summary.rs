@@ -0,0 +1,473 @@
+use std::{collections::HashMap, fmt};
+
+use rbx_dom_weak::types::{Content, Ref, Variant};
+
+use crate::{
+    error::{DecodeError, DecodeErrorKind},
+    legacy_elements::LegacyElement,
+    processing_instruction::ProcessingInstruction,
+};
+
+/// Information about a model or place that was decoded by rbx_xml.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct DecodeSummary {
+    pub(crate) bytes_read: u64,
+    pub(crate) instance_count: usize,
+    pub(crate) property_count: usize,
+    pub(crate) recovered: bool,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) normalized_content: Vec<(Content, Content)>,
+    pub(crate) legacy_elements: Vec<LegacyElement>,
+    pub(crate) migrated_enum_values: Vec<MigratedEnumValue>,
+    pub(crate) source_locations: HashMap<Ref, SourceLocation>,
+    pub(crate) processing_instructions: Vec<ProcessingInstruction>,
+    pub(crate) version: u32,
+}
+
+impl DecodeSummary {
+    /// The total number of bytes that were read from the reader.
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// The number of `Item` elements that were decoded, not including the
+    /// `DataModel` instance that rbx_xml creates as the root of the dom.
+    #[inline]
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// The number of property values that were decoded, not including each
+    /// instance's name.
+    #[inline]
+    pub fn property_count(&self) -> usize {
+        self.property_count
+    }
+
+    /// Tells whether any part of the document was skipped because it could
+    /// not be read. This can only happen when decoding with
+    /// [`DecodeOptions::recover`][crate::DecodeOptions::recover] or
+    /// [`DecodeOptions::recover_from_property_errors`][crate::DecodeOptions::recover_from_property_errors]
+    /// enabled.
+    #[inline]
+    pub fn recovered(&self) -> bool {
+        self.recovered
+    }
+
+    /// All of the problems that rbx_xml worked around while decoding, in the
+    /// order that they were found.
+    #[inline]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Every distinct `Content` value that was rewritten because of
+    /// [`DecodeOptions::normalize_content`][crate::DecodeOptions::normalize_content],
+    /// paired with the value it was rewritten to, in the order they were first
+    /// found.
+    #[inline]
+    pub fn normalized_content(&self) -> &[(Content, Content)] {
+        &self.normalized_content
+    }
+
+    /// Every element that was kept because of
+    /// [`DecodeOptions::legacy_elements`][crate::DecodeOptions::legacy_elements],
+    /// in the order they were found.
+    #[inline]
+    pub fn legacy_elements(&self) -> &[LegacyElement] {
+        &self.legacy_elements
+    }
+
+    /// Every enum value that was replaced because of
+    /// [`DecodeOptions::migrate_enum_values`][crate::DecodeOptions::migrate_enum_values],
+    /// in the order they were found.
+    #[inline]
+    pub fn migrated_enum_values(&self) -> &[MigratedEnumValue] {
+        &self.migrated_enum_values
+    }
+
+    /// Where each decoded instance's `<Item>` tag starts in the document,
+    /// keyed by the instance's Ref. This is only filled in when decoding with
+    /// [`DecodeOptions::source_locations`][crate::DecodeOptions::source_locations]
+    /// enabled.
+    #[inline]
+    pub fn source_locations(&self) -> &HashMap<Ref, SourceLocation> {
+        &self.source_locations
+    }
+
+    /// The processing instructions that were found outside of every `<Item>`,
+    /// like `<?generator my-tool 1.2?>`, in the order they were found. Ones
+    /// inside of Items are dropped with a diagnostic.
+    #[inline]
+    pub fn processing_instructions(&self) -> &[ProcessingInstruction] {
+        &self.processing_instructions
+    }
+
+    /// The `version` attribute of the document's `<roblox>` tag. Passing this
+    /// to [`EncodeOptions::document_version`][crate::EncodeOptions::document_version]
+    /// writes the document back out with the version it was read with.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+/// An enum value from an older file that rbx_xml replaced with the value that
+/// means the same thing today.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MigratedEnumValue {
+    pub(crate) instance: Ref,
+    pub(crate) property_name: String,
+    pub(crate) enum_name: String,
+    pub(crate) original_value: u32,
+    pub(crate) migrated_value: u32,
+}
+
+impl MigratedEnumValue {
+    /// The instance the property is on.
+    #[inline]
+    pub fn instance(&self) -> Ref {
+        self.instance
+    }
+
+    /// The canonical name of the property.
+    #[inline]
+    pub fn property_name(&self) -> &str {
+        &self.property_name
+    }
+
+    /// The name of the property's enum, like `Technology`.
+    #[inline]
+    pub fn enum_name(&self) -> &str {
+        &self.enum_name
+    }
+
+    /// The value that was in the file.
+    #[inline]
+    pub fn original_value(&self) -> u32 {
+        self.original_value
+    }
+
+    /// The value that the property was given instead.
+    #[inline]
+    pub fn migrated_value(&self) -> u32 {
+        self.migrated_value
+    }
+}
+
+impl fmt::Display for MigratedEnumValue {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            output,
+            "{} value {} of property {} was migrated to {}",
+            self.enum_name, self.original_value, self.property_name, self.migrated_value
+        )
+    }
+}
+
+/// A position in a document that rbx_xml decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SourceLocation {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) byte_offset: u64,
+}
+
+impl SourceLocation {
+    /// 1-based line number in the document.
+    #[inline]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column number in the document, counted in characters rather
+    /// than bytes.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The number of bytes in the document before this position.
+    #[inline]
+    pub fn byte_offset(&self) -> u64 {
+        self.byte_offset
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(output, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Describes a problem that rbx_xml found and worked around while decoding.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub(crate) message: String,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) kind: DecodeErrorKind,
+    pub(crate) instance: Option<Ref>,
+    pub(crate) property_name: Option<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn from_error(error: &DecodeError) -> Self {
+        Diagnostic {
+            message: error.detail().to_string(),
+            line: error.line(),
+            column: error.column(),
+            kind: error.kind(),
+            instance: None,
+            property_name: None,
+        }
+    }
+
+    pub(crate) fn from_property_error(
+        error: &DecodeError,
+        instance: Ref,
+        property_name: String,
+    ) -> Self {
+        Diagnostic {
+            instance: Some(instance),
+            property_name: Some(property_name),
+            ..Diagnostic::from_error(error)
+        }
+    }
+
+    /// A human-readable description of the problem.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// 1-based line number in the document where the problem was found.
+    #[inline]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column number in the document where the problem was found.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The category of the error that rbx_xml would have returned if it
+    /// hadn't worked around the problem.
+    #[inline]
+    pub fn kind(&self) -> DecodeErrorKind {
+        self.kind
+    }
+
+    /// The instance the problem was found in, for properties that were
+    /// skipped because of
+    /// [`DecodeOptions::recover_from_property_errors`][crate::DecodeOptions::recover_from_property_errors].
+    #[inline]
+    pub fn instance(&self) -> Option<Ref> {
+        self.instance
+    }
+
+    /// The name of the property that was skipped, as it was written in the
+    /// document.
+    #[inline]
+    pub fn property_name(&self) -> Option<&str> {
+        self.property_name.as_deref()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            output,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Information about a model or place that was encoded by rbx_xml.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct EncodeSummary {
+    pub(crate) bytes_written: u64,
+    pub(crate) instance_count: usize,
+    pub(crate) property_count: usize,
+    pub(crate) skipped_properties: Vec<SkippedProperty>,
+    pub(crate) clamped_properties: Vec<ClampedProperty>,
+}
+
+impl EncodeSummary {
+    /// The total number of bytes that were written to the writer.
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// The number of instances that were encoded.
+    #[inline]
+    pub fn instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// The number of property values that were encoded, not including each
+    /// instance's name.
+    #[inline]
+    pub fn property_count(&self) -> usize {
+        self.property_count
+    }
+
+    /// Every property that was left out of the file, in the order they were
+    /// found. Instances' names only show up here when they're left out by
+    /// [`EncodeOptions::omit_default_names`][crate::EncodeOptions::omit_default_names].
+    #[inline]
+    pub fn skipped_properties(&self) -> &[SkippedProperty] {
+        &self.skipped_properties
+    }
+
+    /// Every property whose value didn't fit in the narrower type that it's
+    /// serialized as, in the order they were written. For example,
+    /// `BasePart.Color` is written as a Color3uint8, so a `Color3` with
+    /// components outside of 0 to 1 has them clamped.
+    #[inline]
+    pub fn clamped_properties(&self) -> &[ClampedProperty] {
+        &self.clamped_properties
+    }
+}
+
+/// How far along encoding is, given to the
+/// [`EncodeOptions::progress`][crate::EncodeOptions::progress] callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EncodeProgressReport {
+    pub(crate) instances_written: usize,
+    pub(crate) total_instances: usize,
+    pub(crate) bytes_written: u64,
+}
+
+impl EncodeProgressReport {
+    /// The number of instances whose properties have been written so far.
+    #[inline]
+    pub fn instances_written(&self) -> usize {
+        self.instances_written
+    }
+
+    /// The number of instances in the whole document, counted before
+    /// anything was written.
+    #[inline]
+    pub fn total_instances(&self) -> usize {
+        self.total_instances
+    }
+
+    /// The number of bytes written to the output so far.
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+/// Why a property was left out while encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The property isn't in the reflection database, and unknown properties
+    /// are being ignored.
+    NotInDatabase,
+
+    /// The property is in the reflection database, but Roblox doesn't
+    /// serialize it.
+    NotSerializable,
+
+    /// The property is read-only and isn't serialized by Roblox.
+    ReadOnly,
+
+    /// The property has a value that rbx_xml can leave out because it's the
+    /// value it would be decoded with anyway.
+    DefaultValue,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, output: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            SkipReason::NotInDatabase => "it isn't in the reflection database",
+            SkipReason::NotSerializable => "it isn't serialized by Roblox",
+            SkipReason::ReadOnly => "it's read-only",
+            SkipReason::DefaultValue => "it has its default value",
+        };
+
+        write!(output, "{}", reason)
+    }
+}
+
+/// A property that rbx_xml left out while encoding.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SkippedProperty {
+    pub(crate) instance: String,
+    pub(crate) property_name: String,
+    pub(crate) reason: SkipReason,
+}
+
+impl SkippedProperty {
+    /// The full name of the instance the property was on, as given by
+    /// [`WeakDom::full_name`][rbx_dom_weak::WeakDom::full_name].
+    #[inline]
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// The name of the property that was left out.
+    #[inline]
+    pub fn property_name(&self) -> &str {
+        &self.property_name
+    }
+
+    /// Why the property was left out.
+    #[inline]
+    pub fn reason(&self) -> SkipReason {
+        self.reason
+    }
+}
+
+/// A property whose value rbx_xml clamped to fit the type it's serialized as
+/// while encoding.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ClampedProperty {
+    pub(crate) instance: String,
+    pub(crate) property_name: String,
+    pub(crate) original_value: Variant,
+    pub(crate) written_value: Variant,
+}
+
+impl ClampedProperty {
+    /// The full name of the instance the property was on, as given by
+    /// [`WeakDom::full_name`][rbx_dom_weak::WeakDom::full_name].
+    #[inline]
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// The name of the property that was clamped.
+    #[inline]
+    pub fn property_name(&self) -> &str {
+        &self.property_name
+    }
+
+    /// The value as it was stored on the instance.
+    #[inline]
+    pub fn original_value(&self) -> &Variant {
+        &self.original_value
+    }
+
+    /// The value that was written instead.
+    #[inline]
+    pub fn written_value(&self) -> &Variant {
+        &self.written_value
+    }
+}
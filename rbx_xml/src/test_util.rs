@@ -9,7 +9,7 @@ where
     let _ = env_logger::try_init();
 
     let mut buffer = Vec::new();
-    let mut writer = XmlEventWriter::from_output(&mut buffer);
+    let mut writer = XmlEventWriter::from_output(&mut buffer, Some("  ".to_owned()));
 
     test_value.write_outer_xml("foo", &mut writer).unwrap();
 
@@ -30,40 +30,11 @@ where
     let _ = env_logger::try_init();
 
     let mut buffer = Vec::new();
-    let mut writer = XmlEventWriter::from_output(&mut buffer);
+    let mut writer = XmlEventWriter::from_output(&mut buffer, Some("  ".to_owned()));
 
     test_value.write_outer_xml("foo", &mut writer).unwrap();
 
-    let mut expected_events = XmlEventReader::from_source(expected_source.as_bytes());
-    let mut actual_events = XmlEventReader::from_source(buffer.as_slice());
-
-    let fail = || {
-        panic!(
-            "Expected XML:\n{}\n\nActual XML:\n{}",
-            expected_source,
-            std::str::from_utf8(&buffer).unwrap(),
-        )
-    };
-
-    loop {
-        let (expected, actual) = (expected_events.next(), actual_events.next());
-
-        match (expected.is_some(), actual.is_some()) {
-            (true, true) => {
-                if expected != actual {
-                    println!("Expected event: {:#?}", expected);
-                    println!("Actual event: {:#?}", actual);
-
-                    fail();
-                }
-            }
-            (true, false) | (false, true) => {
-                println!("Event streams were different lengths!");
-                fail()
-            }
-            (false, false) => break,
-        }
-    }
+    rbx_test_util::assert_xml_eq(expected_source, std::str::from_utf8(&buffer).unwrap());
 }
 
 pub fn test_xml_deserialize<T>(source: &str, expected_value: &T)
@@ -78,3 +49,16 @@ where
 
     assert_eq!(&value, expected_value);
 }
+
+/// Serializes the given value and checks that the output matches the expected
+/// source byte-for-byte, including whitespace and number formatting.
+pub fn test_xml_golden<T: XmlType>(expected_source: &str, test_value: &T) {
+    let _ = env_logger::try_init();
+
+    let mut buffer = Vec::new();
+    let mut writer = XmlEventWriter::from_output(&mut buffer, Some("  ".to_owned()));
+
+    test_value.write_outer_xml("foo", &mut writer).unwrap();
+
+    rbx_test_util::assert_text_eq(expected_source, std::str::from_utf8(&buffer).unwrap());
+}
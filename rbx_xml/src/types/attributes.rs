@@ -0,0 +1,163 @@
+//! Reads and writes the `AttributesSerialize` property, which holds a
+//! `BinaryString` in the format decoded by [`Attributes::from_binary`].
+
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::{Attributes, BinaryString, Variant};
+
+use crate::{
+    core::XmlType,
+    deserializer_core::XmlEventReader,
+    error::{DecodeError, EncodeError, EncodeErrorKind},
+    serializer_core::XmlEventWriter,
+};
+
+/// The name of the property that Roblox stores attributes in.
+pub const ATTRIBUTES_PROPERTY: &str = "AttributesSerialize";
+
+/// Reads an `AttributesSerialize` property, which is a `BinaryString`
+/// element. Its contents are only turned into `Attributes` if they can be
+/// written back exactly as they were; anything else, like a type that isn't
+/// supported yet, is kept as a `BinaryString`.
+pub fn read_attributes<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Variant, DecodeError> {
+    let value = BinaryString::read_outer_xml(reader)?;
+    Ok(attributes_from_binary(value))
+}
+
+/// Turns the contents of an `AttributesSerialize` property into `Attributes`
+/// if they can be written back exactly as they were, or leaves them alone.
+pub fn attributes_from_binary(value: BinaryString) -> Variant {
+    let data: &[u8] = value.as_ref();
+
+    match Attributes::from_binary(data) {
+        Some(attributes) if attributes.to_binary().as_deref() == Ok(data) => {
+            Variant::Attributes(attributes)
+        }
+        _ => Variant::BinaryString(value),
+    }
+}
+
+/// Writes attributes as the `BinaryString` element that Roblox reads them
+/// from.
+pub fn write_attributes<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    xml_property_name: &str,
+    value: &Attributes,
+) -> Result<(), EncodeError> {
+    attributes_to_binary(writer, value)?.write_outer_xml(xml_property_name, writer)
+}
+
+/// Encodes attributes into the contents of an `AttributesSerialize` property.
+pub fn attributes_to_binary<W: Write>(
+    writer: &XmlEventWriter<W>,
+    value: &Attributes,
+) -> Result<BinaryString, EncodeError> {
+    match value.to_binary() {
+        Ok(data) => Ok(BinaryString::from(data)),
+        Err(error) => Err(writer.error(EncodeErrorKind::UnsupportedAttributeType {
+            attribute: error.name().to_owned(),
+            ty: error.ty(),
+        })),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::types::{
+        BrickColor, Color3, NumberRange, NumberSequence, NumberSequenceKeypoint, UDim, UDim2,
+        Vector3,
+    };
+
+    fn sample() -> Attributes {
+        vec![
+            ("Team", Variant::from("Red")),
+            ("Alive", Variant::Bool(true)),
+            ("Speed", Variant::Float64(16.5)),
+            ("Scale", Variant::Float32(0.25)),
+            (
+                "Offset",
+                Variant::UDim2(UDim2::new(UDim::new(0.5, -10), UDim::new(1.0, 20))),
+            ),
+            ("Tint", Variant::Color3(Color3::new(1.0, 0.5, 0.0))),
+            ("Color", Variant::BrickColor(BrickColor::BrightRed)),
+            ("Spawn", Variant::Vector3(Vector3::new(1.0, 2.0, 3.0))),
+            ("Range", Variant::NumberRange(NumberRange::new(1.0, 5.0))),
+            (
+                "Fade",
+                Variant::NumberSequence(NumberSequence {
+                    keypoints: vec![
+                        NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+                        NumberSequenceKeypoint::new(1.0, 0.0, 0.5),
+                    ],
+                }),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let attributes = sample();
+        let data = attributes.to_binary().unwrap();
+
+        assert_eq!(
+            attributes_from_binary(BinaryString::from(data)),
+            Variant::Attributes(attributes)
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            attributes_from_binary(BinaryString::new()),
+            Variant::Attributes(Attributes::new())
+        );
+
+        // A count of zero is also no attributes, but it wouldn't be written
+        // back the same way, so it's left alone.
+        let zero = BinaryString::from(vec![0, 0, 0, 0]);
+        assert_eq!(
+            attributes_from_binary(zero.clone()),
+            Variant::BinaryString(zero)
+        );
+    }
+
+    #[test]
+    fn unknown_type_is_preserved() {
+        let mut data = sample().to_binary().unwrap();
+
+        // Roblox stores CFrames as type 0x14, which isn't supported yet.
+        data.extend_from_slice(b"\x01\x00\x00\x00C\x14\x00\x00\x00\x00");
+        data[0] += 1;
+
+        let value = BinaryString::from(data);
+        assert_eq!(
+            attributes_from_binary(value.clone()),
+            Variant::BinaryString(value)
+        );
+    }
+
+    #[test]
+    fn malformed_is_preserved() {
+        let data = sample().to_binary().unwrap();
+
+        for len in [1, 4, 10, data.len() - 1] {
+            let value = BinaryString::from(data[..len].to_vec());
+            assert_eq!(
+                attributes_from_binary(value.clone()),
+                Variant::BinaryString(value)
+            );
+        }
+
+        let mut extra = data;
+        extra.push(0);
+        let value = BinaryString::from(extra);
+        assert_eq!(
+            attributes_from_binary(value.clone()),
+            Variant::BinaryString(value)
+        );
+    }
+}
@@ -5,7 +5,7 @@ use rbx_dom_weak::types::Axes;
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::XmlEventWriter,
 };
 
@@ -24,8 +24,9 @@ impl XmlType for Axes {
             .parse::<u8>()
             .map_err(|e| reader.error(e))?;
 
-        Self::from_bits(value)
-            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("Axes value out of range")))
+        Self::from_bits(value).ok_or_else(|| {
+            reader.error(DecodeErrorDetail::InvalidContent("Axes value out of range"))
+        })
     }
 }
 
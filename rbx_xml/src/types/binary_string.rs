@@ -4,7 +4,8 @@ use rbx_dom_weak::types::BinaryString;
 
 use crate::{
     core::XmlType,
-    deserializer_core::XmlEventReader,
+    deserializer::ParseState,
+    deserializer_core::{Base64Contents, XmlEventReader},
     error::{DecodeError, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
 };
@@ -13,13 +14,22 @@ impl XmlType for BinaryString {
     const XML_TAG_NAME: &'static str = "BinaryString";
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-        // FIXME: BinaryString should have an is_empty method.
-        let contents: &[u8] = self.as_ref();
-        if !contents.is_empty() {
-            writer.write(XmlWriteEvent::cdata(&base64::encode(self)))?;
+        if let Some(blob) = self.blob() {
+            if !blob.is_empty() {
+                return writer.write_base64_blob(blob);
+            }
+        } else {
+            // FIXME: BinaryString should have an is_empty method.
+            let contents: &[u8] = self.as_ref();
+            if !contents.is_empty() {
+                let encoded = writer.wrap_base64(base64::encode(self));
+                return writer.write(XmlWriteEvent::cdata(&encoded));
+            }
         }
 
-        Ok(())
+        // Like Studio, empty values are closed on the same line they're
+        // opened on.
+        writer.write(XmlWriteEvent::characters(""))
     }
 
     fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<BinaryString, DecodeError> {
@@ -28,6 +38,22 @@ impl XmlType for BinaryString {
     }
 }
 
+/// Reads a BinaryString, keeping it in the blob store if the decode options
+/// have one and it's too long to keep in memory.
+pub fn read_binary_string<R: Read>(
+    reader: &mut XmlEventReader<R>,
+    state: &ParseState,
+) -> Result<BinaryString, DecodeError> {
+    reader.expect_start_with_name(BinaryString::XML_TAG_NAME)?;
+    let value = match state.read_binary_contents(reader)? {
+        Base64Contents::Buffer(buffer) => buffer.into(),
+        Base64Contents::Stored(blob) => BinaryString::from_blob(blob),
+    };
+    reader.expect_end_with_name(BinaryString::XML_TAG_NAME)?;
+
+    Ok(value)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -3,7 +3,7 @@ use std::io::{Read, Write};
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
 };
 
@@ -24,7 +24,9 @@ impl XmlType for bool {
             "true" => true,
             "false" => false,
             _ => {
-                return Err(reader.error(DecodeErrorKind::InvalidContent("expected true or false")))
+                return Err(
+                    reader.error(DecodeErrorDetail::InvalidContent("expected true or false"))
+                )
             }
         };
 
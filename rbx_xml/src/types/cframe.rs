@@ -1,71 +1,56 @@
-use std::io::{Read, Write};
+use std::{
+    fmt::Write as _,
+    io::{Read, Write},
+};
 
-use rbx_dom_weak::types::{CFrame, Matrix3, Vector3};
+use rbx_dom_weak::types::CFrame;
 
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
     error::{DecodeError, EncodeError},
-    serializer_core::XmlEventWriter,
+    serializer_core::{TagText, XmlEventWriter},
 };
 
 static TAG_NAMES: [&str; 12] = [
     "X", "Y", "Z", "R00", "R01", "R02", "R10", "R11", "R12", "R20", "R21", "R22",
 ];
 
+/// A component of a CFrame being written.
+///
+/// Unlike other floats, non-finite CFrame components have always been written
+/// the way `Display` formats them, as `inf`, `-inf`, and `NaN`. Files written
+/// before keep coming out the same.
+struct Component(f32);
+
+impl TagText for Component {
+    fn push_tag_text(&self, output: &mut String) {
+        if self.0.is_finite() {
+            self.0.push_tag_text(output);
+        } else {
+            write!(output, "{}", self.0).unwrap();
+        }
+    }
+}
+
 impl XmlType for CFrame {
     const XML_TAG_NAME: &'static str = "CoordinateFrame";
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-        // FIXME: Should this be built into rbx_types?
-        let as_slice = &[
-            self.position.x,
-            self.position.y,
-            self.position.z,
-            self.orientation.x.x,
-            self.orientation.x.y,
-            self.orientation.x.z,
-            self.orientation.y.x,
-            self.orientation.y.y,
-            self.orientation.y.z,
-            self.orientation.z.x,
-            self.orientation.z.y,
-            self.orientation.z.z,
-        ];
-
-        writer.write_tag_array(as_slice, &TAG_NAMES)?;
+        let components = self.to_components().map(Component);
+        writer.write_tag_array(&TAG_NAMES, &components)?;
 
         Ok(())
     }
 
     fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
-        let mut value = CFrame::new(Vector3::new(0.0, 0.0, 0.0), Matrix3::identity());
-
-        for &tag_name in &TAG_NAMES {
-            let component: f32 = reader.read_value_in_tag(tag_name)?;
+        let mut components = [0.0; 12];
 
-            match tag_name {
-                "X" => value.position.x = component,
-                "Y" => value.position.y = component,
-                "Z" => value.position.z = component,
-
-                "R00" => value.orientation.x.x = component,
-                "R01" => value.orientation.x.y = component,
-                "R02" => value.orientation.x.z = component,
-
-                "R10" => value.orientation.y.x = component,
-                "R11" => value.orientation.y.y = component,
-                "R12" => value.orientation.y.z = component,
-
-                "R20" => value.orientation.z.x = component,
-                "R21" => value.orientation.z.y = component,
-                "R22" => value.orientation.z.z = component,
-
-                _ => unreachable!(),
-            }
+        for (component, &tag_name) in components.iter_mut().zip(&TAG_NAMES) {
+            *component = reader.read_value_in_tag(tag_name)?;
         }
 
-        Ok(value)
+        Ok(CFrame::from_components(&components))
     }
 }
 
@@ -73,6 +58,8 @@ impl XmlType for CFrame {
 mod test {
     use super::*;
 
+    use rbx_dom_weak::types::{Matrix3, Vector3};
+
     use crate::test_util;
 
     #[test]
@@ -88,4 +75,64 @@ mod test {
 
         test_util::test_xml_round_trip(&test_input);
     }
+
+    #[test]
+    fn golden_cframe() {
+        test_util::test_xml_golden(
+            r#"<CoordinateFrame name="foo">
+  <X>1.5</X>
+  <Y>-20.25</Y>
+  <Z>300</Z>
+  <R00>1</R00>
+  <R01>0</R01>
+  <R02>-0</R02>
+  <R10>0.1</R10>
+  <R11>0.70710677</R11>
+  <R12>-0.0000001</R12>
+  <R20>123456.79</R20>
+  <R21>300000000000000000000000000000000000000</R21>
+  <R22>0.000000000000000000000000000000000000000000001</R22>
+</CoordinateFrame>"#,
+            &CFrame::new(
+                Vector3::new(1.5, -20.25, 300.0),
+                Matrix3 {
+                    x: Vector3::new(1.0, 0.0, -0.0),
+                    y: Vector3::new(0.1, 0.70710677, -1e-7),
+                    z: Vector3::new(123456.79, 3e38, 1e-45),
+                },
+            ),
+        );
+    }
+
+    #[test]
+    fn serialize_non_finite() {
+        let test_input = CFrame::new(
+            Vector3::new(f32::INFINITY, f32::NEG_INFINITY, f32::NAN),
+            Matrix3 {
+                x: Vector3::new(1.0, 0.0, 0.0),
+                y: Vector3::new(0.0, 1.0, 0.0),
+                z: Vector3::new(0.0, 0.0, f32::NAN),
+            },
+        );
+
+        test_util::test_xml_serialize(
+            r#"
+                <CoordinateFrame name="foo">
+                    <X>inf</X>
+                    <Y>-inf</Y>
+                    <Z>NaN</Z>
+                    <R00>1</R00>
+                    <R01>0</R01>
+                    <R02>0</R02>
+                    <R10>0</R10>
+                    <R11>1</R11>
+                    <R12>0</R12>
+                    <R20>0</R20>
+                    <R21>0</R21>
+                    <R22>NaN</R22>
+                </CoordinateFrame>
+            "#,
+            &test_input,
+        );
+    }
 }
@@ -5,7 +5,7 @@ use rbx_dom_weak::types::{Color3, ColorSequence, ColorSequenceKeypoint};
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
 };
 
@@ -41,7 +41,7 @@ impl XmlType for ColorSequence {
         let mut keypoints = Vec::new();
 
         let wrong_length = || {
-            reader.error(DecodeErrorKind::InvalidContent(
+            reader.error(DecodeErrorDetail::InvalidContent(
                 "incorrect number of values",
             ))
         };
@@ -70,7 +70,7 @@ impl XmlType for ColorSequence {
         }
 
         if keypoints.len() < 2 {
-            return Err(reader.error(DecodeErrorKind::InvalidContent(
+            return Err(reader.error(DecodeErrorDetail::InvalidContent(
                 "expected two or more keypoints",
             )));
         }
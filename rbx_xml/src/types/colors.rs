@@ -13,11 +13,7 @@ impl XmlType for Color3 {
     const XML_TAG_NAME: &'static str = "Color3";
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-        writer.write_value_in_tag(&self.r, "R")?;
-        writer.write_value_in_tag(&self.g, "G")?;
-        writer.write_value_in_tag(&self.b, "B")?;
-
-        Ok(())
+        writer.write_tag_array(&["R", "G", "B"], &[self.r, self.g, self.b])
     }
 
     fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
@@ -83,3 +79,22 @@ fn decode_packed_color3(packed_color: u32) -> Result<Color3uint8, DecodeError> {
 
     Ok(Color3uint8::new(r as u8, g as u8, b as u8))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn golden_color3() {
+        test_util::test_xml_golden(
+            r#"<Color3 name="foo">
+  <R>0.2</R>
+  <G>1</G>
+  <B>0.003921569</B>
+</Color3>"#,
+            &Color3::new(0.2, 1.0, 0.003921569),
+        );
+    }
+}
@@ -5,7 +5,7 @@ use rbx_dom_weak::types::Content;
 use crate::{
     core::XmlType,
     deserializer_core::{XmlEventReader, XmlReadEvent},
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
 };
 
@@ -56,10 +56,12 @@ impl XmlType for Content {
                         attributes,
                         namespace,
                     };
-                    return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(event)));
+                    return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
                 }
             },
-            unexpected => return Err(reader.error(DecodeErrorKind::UnexpectedXmlEvent(unexpected))),
+            unexpected => {
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(unexpected)))
+            }
         };
 
         Ok(Content::from(value))
@@ -5,7 +5,7 @@ use rbx_dom_weak::types::Faces;
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::XmlEventWriter,
 };
 
@@ -25,7 +25,9 @@ impl XmlType for Faces {
             .map_err(|e| reader.error(e))?;
 
         Self::from_bits(value).ok_or_else(|| {
-            reader.error(DecodeErrorKind::InvalidContent("Faces value out of range"))
+            reader.error(DecodeErrorDetail::InvalidContent(
+                "Faces value out of range",
+            ))
         })
     }
 }
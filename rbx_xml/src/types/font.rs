@@ -0,0 +1,176 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::{Content, Font, FontStyle, FontWeight};
+
+use crate::{
+    core::XmlType,
+    deserializer_core::{XmlEventReader, XmlReadEvent},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// A Font is serialized as:
+// <Family><url>rbxasset://fonts/families/SourceSansPro.json</url></Family>
+// <Weight>400</Weight>
+// <Style>Normal</Style>
+// <CachedFaceId><url>rbxasset://fonts/SourceSansPro-Regular.ttf</url></CachedFaceId>
+//
+// CachedFaceId is left out when Roblox hasn't loaded the font yet.
+impl XmlType for Font {
+    const XML_TAG_NAME: &'static str = "Font";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        write_url(writer, "Family", &self.family)?;
+        writer.write_tag_characters("Weight", self.weight.to_u16() as i32)?;
+
+        match self.style.name() {
+            Some(name) => writer.write_nested("Style", |writer| writer.write_string(name))?,
+            None => writer.write_tag_characters("Style", self.style.to_u8() as i32)?,
+        }
+
+        if let Some(cached_face_id) = &self.cached_face_id {
+            write_url(writer, "CachedFaceId", cached_face_id)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let family = reader.read_value_in_tag::<Content>("Family")?.into_string();
+
+        let weight = reader
+            .read_tag_contents("Weight")?
+            .parse::<u16>()
+            .map_err(|e| reader.error(e))?;
+
+        let style = reader.read_tag_contents("Style")?;
+        let style = match FontStyle::from_name(&style) {
+            Some(style) => style,
+            None => FontStyle::from_u8(style.parse::<u8>().map_err(|e| reader.error(e))?),
+        };
+
+        let cached_face_id = match reader.expect_peek()? {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "CachedFaceId" => Some(
+                reader
+                    .read_value_in_tag::<Content>("CachedFaceId")?
+                    .into_string(),
+            ),
+            XmlReadEvent::StartElement { .. } => {
+                let event = reader.expect_next()?;
+                return Err(reader.error(DecodeErrorDetail::UnexpectedXmlEvent(event)));
+            }
+            _ => None,
+        };
+
+        Ok(Font {
+            family,
+            weight: FontWeight::from_u16(weight),
+            style,
+            cached_face_id,
+        })
+    }
+}
+
+/// Writes a URL inside of a `<url>` tag, wrapped in a tag with the given name.
+/// Unlike a `Content` value, an empty URL is still written as `<url>`, so that
+/// it's read back the same way.
+fn write_url<W: Write>(
+    writer: &mut XmlEventWriter<W>,
+    tag: &str,
+    url: &str,
+) -> Result<(), EncodeError> {
+    writer.write_nested(tag, |writer| {
+        writer.write_nested("url", |writer| writer.write_string(url))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    fn arimo() -> Font {
+        Font {
+            cached_face_id: Some("rbxasset://fonts/Arimo-BoldItalic.ttf".to_owned()),
+            ..Font::new(
+                "rbxasset://fonts/families/Arimo.json",
+                FontWeight::Bold,
+                FontStyle::Italic,
+            )
+        }
+    }
+
+    #[test]
+    fn round_trip_font() {
+        test_util::test_xml_round_trip(&Font::default());
+        test_util::test_xml_round_trip(&arimo());
+    }
+
+    #[test]
+    fn round_trip_unknown_weight_and_style() {
+        test_util::test_xml_round_trip(&Font::new(
+            "rbxassetid://12187365364",
+            FontWeight::Other(450),
+            FontStyle::Other(7),
+        ));
+    }
+
+    #[test]
+    fn round_trip_family_needing_cdata() {
+        test_util::test_xml_round_trip(&Font {
+            cached_face_id: Some(String::new()),
+            ..Font::new(
+                " rbxasset://fonts/families/My <Font> & ]]> Co.json ",
+                FontWeight::Regular,
+                FontStyle::Normal,
+            )
+        });
+    }
+
+    #[test]
+    fn deserialize_font() {
+        test_util::test_xml_deserialize(
+            r#"
+                <Font name="FontFace">
+                    <Family><url>rbxasset://fonts/families/Arimo.json</url></Family>
+                    <Weight>700</Weight>
+                    <Style>Italic</Style>
+                    <CachedFaceId><url>rbxasset://fonts/Arimo-BoldItalic.ttf</url></CachedFaceId>
+                </Font>
+            "#,
+            &arimo(),
+        );
+    }
+
+    #[test]
+    fn deserialize_font_without_cached_face_id() {
+        test_util::test_xml_deserialize(
+            r#"
+                <Font name="FontFace">
+                    <Family><url>rbxasset://fonts/families/SourceSansPro.json</url></Family>
+                    <Weight>400</Weight>
+                    <Style>Normal</Style>
+                </Font>
+            "#,
+            &Font::default(),
+        );
+    }
+
+    #[test]
+    fn golden_font() {
+        test_util::test_xml_golden(
+            r#"<Font name="foo">
+  <Family>
+    <url>rbxasset://fonts/families/Arimo.json</url>
+  </Family>
+  <Weight>700</Weight>
+  <Style>Italic</Style>
+  <CachedFaceId>
+    <url>rbxasset://fonts/Arimo-BoldItalic.ttf</url>
+  </CachedFaceId>
+</Font>"#,
+            &arimo(),
+        );
+    }
+}
@@ -7,6 +7,7 @@
 //! 2. Add a 'mod' statement immediately below this comment
 //! 3. Add the type(s) to the declare_rbx_types! macro invocation
 
+mod attributes;
 mod axes;
 mod binary_string;
 mod bool;
@@ -16,9 +17,11 @@ mod colors;
 mod content;
 mod enumeration;
 mod faces;
+mod font;
 mod number_range;
 mod number_sequence;
 mod numbers;
+mod optional_cframe;
 mod physical_properties;
 mod ray;
 mod rect;
@@ -26,35 +29,51 @@ mod referent;
 mod shared_string;
 mod strings;
 mod udims;
+mod unique_id;
 mod vectors;
 
 use std::io::{Read, Write};
 
 use rbx_dom_weak::types::{
-    Axes, BinaryString, CFrame, Color3, Color3uint8, ColorSequence, Content, Enum, Faces,
-    NumberRange, NumberSequence, PhysicalProperties, Ray, Rect, Ref, UDim, UDim2, Variant, Vector2,
-    Vector2int16, Vector3, Vector3int16,
+    Axes, BinaryString, CFrame, Color3, Color3uint8, ColorSequence, Content, Enum, Faces, Font,
+    NumberRange, NumberSequence, PhysicalProperties, Ray, Rect, Ref, UDim, UDim2, UniqueId,
+    Variant, VariantType, Vector2, Vector2int16, Vector3, Vector3int16,
 };
 
 use crate::{
     core::XmlType,
     deserializer::ParseState,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError, EncodeErrorKind},
+    error::{DecodeError, DecodeErrorDetail, EncodeError, EncodeErrorKind},
     serializer::EmitState,
     serializer_core::XmlEventWriter,
 };
 
+pub use self::attributes::{attributes_from_binary, attributes_to_binary, ATTRIBUTES_PROPERTY};
+
 use self::{
+    attributes::{read_attributes, write_attributes},
+    binary_string::read_binary_string,
+    optional_cframe::OptionalCFrame,
     referent::{read_ref, write_ref},
     shared_string::{read_shared_string, write_shared_string},
+    strings::ArcStr,
 };
 
 /// The `declare_rbx_types` macro generates the two big match statements that
 /// rbx_xml uses to read/write values inside of `read_value_xml` and
-/// `write_value_xml`.
+/// `write_value_xml`, along with the list of types that `write_value_xml` can
+/// write.
 macro_rules! declare_rbx_types {
     { $($variant_name: ident : $inner_type: ident,)* } => {
+        /// Every type of value that `write_value_xml` can write: the ones
+        /// given to this macro, followed by the ones it handles specially.
+        pub const SUPPORTED_TYPES: &[VariantType] = &[
+            $(VariantType::$variant_name,)*
+            VariantType::Ref,
+            VariantType::SharedString,
+            VariantType::Attributes,
+        ];
 
         /// Reads a Roblox property value with the given type from the XML event
         /// stream.
@@ -66,19 +85,27 @@ macro_rules! declare_rbx_types {
             property_name: &str,
         ) -> Result<Variant, DecodeError> {
             match xml_type_name {
+                // Attributes are read into memory even when there's a blob
+                // store, since they need to be decoded.
+                <BinaryString>::XML_TAG_NAME if property_name == ATTRIBUTES_PROPERTY && state.reads_attributes() => read_attributes(reader),
+
+                // BinaryString values can be kept in a blob store, which
+                // needs the options from the parse state.
+                <BinaryString>::XML_TAG_NAME if state.uses_blob_store() => Ok(Variant::BinaryString(read_binary_string(reader, state)?)),
+
                 $(<$inner_type>::XML_TAG_NAME => Ok(Variant::$variant_name(<$inner_type>::read_outer_xml(reader)?)),)*
 
                 // Protected strings are only read, never written
                 self::strings::ProtectedStringDummy::XML_TAG_NAME => {
                     let value = self::strings::ProtectedStringDummy::read_outer_xml(reader)?;
-                    Ok(Variant::String(value.0))
+                    Ok(Variant::String(value.0.into()))
                 },
 
                 self::referent::XML_TAG_NAME => Ok(Variant::Ref(read_ref(reader, instance_id, property_name, state)?)),
                 self::shared_string::XML_TAG_NAME => read_shared_string(reader, instance_id, property_name, state),
 
                 _ => {
-                    Err(reader.error(DecodeErrorKind::UnknownPropertyType(xml_type_name.to_owned())))
+                    Err(reader.error(DecodeErrorDetail::UnknownPropertyType(xml_type_name.to_owned())))
                 },
             }
         }
@@ -101,6 +128,7 @@ macro_rules! declare_rbx_types {
 
                 Variant::Ref(value) => write_ref(writer, xml_property_name, *value, state),
                 Variant::SharedString(value) => write_shared_string(writer, xml_property_name, value, state),
+                Variant::Attributes(value) => write_attributes(writer, xml_property_name, value),
 
                 unknown => {
                     Err(writer.error(EncodeErrorKind::UnsupportedPropertyType(unknown.ty())))
@@ -123,16 +151,19 @@ declare_rbx_types! {
     Faces: Faces,
     Float32: f32,
     Float64: f64,
+    Font: Font,
     Int32: i32,
     Int64: i64,
     NumberRange: NumberRange,
     NumberSequence: NumberSequence,
+    OptionalCFrame: OptionalCFrame,
     PhysicalProperties: PhysicalProperties,
     Ray: Ray,
     Rect: Rect,
-    String: String,
+    String: ArcStr,
     UDim2: UDim2,
     UDim: UDim,
+    UniqueId: UniqueId,
     Vector2: Vector2,
     Vector2int16: Vector2int16,
     Vector3: Vector3,
@@ -5,7 +5,7 @@ use rbx_dom_weak::types::NumberRange;
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
 };
 
@@ -28,17 +28,19 @@ impl XmlType for NumberRange {
             .filter(|slice| !slice.is_empty())
             .map(|piece| piece.parse::<f32>().map_err(|e| reader.error(e)));
 
-        let min = pieces
-            .next()
-            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("missing min value")))??;
+        let min = pieces.next().ok_or_else(|| {
+            reader.error(DecodeErrorDetail::InvalidContent("missing min value"))
+        })??;
 
-        let max = pieces
-            .next()
-            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("missing max value")))??;
+        let max = pieces.next().ok_or_else(|| {
+            reader.error(DecodeErrorDetail::InvalidContent("missing max value"))
+        })??;
 
         match pieces.next() {
             None => {}
-            Some(_) => return Err(reader.error(DecodeErrorKind::InvalidContent("too many values"))),
+            Some(_) => {
+                return Err(reader.error(DecodeErrorDetail::InvalidContent("too many values")))
+            }
         }
 
         Ok(NumberRange { min, max })
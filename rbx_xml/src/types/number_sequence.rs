@@ -5,7 +5,7 @@ use rbx_dom_weak::types::{NumberSequence, NumberSequenceKeypoint};
 use crate::{
     core::XmlType,
     deserializer_core::XmlEventReader,
-    error::{DecodeError, DecodeErrorKind, EncodeError},
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
 };
 
@@ -34,7 +34,7 @@ impl XmlType for NumberSequence {
         let mut keypoints = Vec::new();
 
         let wrong_length = || {
-            reader.error(DecodeErrorKind::InvalidContent(
+            reader.error(DecodeErrorDetail::InvalidContent(
                 "incorrect number of values",
             ))
         };
@@ -59,7 +59,7 @@ impl XmlType for NumberSequence {
         }
 
         if keypoints.len() < 2 {
-            return Err(reader.error(DecodeErrorKind::InvalidContent(
+            return Err(reader.error(DecodeErrorDetail::InvalidContent(
                 "expected two or more keypoints",
             )));
         }
@@ -23,7 +23,7 @@ macro_rules! float_type {
                 } else if self.is_nan() {
                     writer.write_characters("NAN")
                 } else {
-                    writer.write_characters(self)
+                    writer.write_characters(*self)
                 }
             }
 
@@ -0,0 +1,125 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::CFrame;
+
+use crate::{
+    core::XmlType,
+    deserializer_core::{XmlEventReader, XmlReadEvent},
+    error::{DecodeError, EncodeError},
+    serializer_core::{XmlEventWriter, XmlWriteEvent},
+};
+
+/// The name of the element that holds the CFrame inside of an
+/// `OptionalCoordinateFrame`, when there is one.
+const CFRAME_TAG_NAME: &str = "CFrame";
+
+/// Lets `declare_rbx_types!`, which only takes identifiers, name the type held
+/// by `Variant::OptionalCFrame`.
+pub type OptionalCFrame = Option<CFrame>;
+
+impl XmlType for Option<CFrame> {
+    const XML_TAG_NAME: &'static str = "OptionalCoordinateFrame";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        match self {
+            Some(cframe) => writer.write_nested(CFRAME_TAG_NAME, |writer| cframe.write_xml(writer)),
+            // Studio writes the element with nothing in it, closed on the
+            // same line. An empty `Characters` event keeps xml-rs from
+            // putting a newline between the tags.
+            None => writer.write(XmlWriteEvent::characters("")),
+        }
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let has_cframe = match reader.expect_peek()? {
+            XmlReadEvent::StartElement { name, .. } => name.local_name == CFRAME_TAG_NAME,
+            _ => false,
+        };
+
+        if has_cframe {
+            reader.expect_start_with_name(CFRAME_TAG_NAME)?;
+            let cframe = CFrame::read_xml(reader)?;
+            reader.expect_end_with_name(CFRAME_TAG_NAME)?;
+
+            Ok(Some(cframe))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::types::{Matrix3, Vector3};
+
+    use crate::test_util;
+
+    fn pivot() -> CFrame {
+        CFrame::new(
+            Vector3::new(10.0, 2.5, -4.0),
+            Matrix3 {
+                x: Vector3::new(0.0, 0.0, 1.0),
+                y: Vector3::new(0.0, 1.0, 0.0),
+                z: Vector3::new(-1.0, 0.0, 0.0),
+            },
+        )
+    }
+
+    #[test]
+    fn round_trip_some() {
+        test_util::test_xml_round_trip(&Some(pivot()));
+    }
+
+    #[test]
+    fn round_trip_none() {
+        test_util::test_xml_round_trip(&None::<CFrame>);
+    }
+
+    #[test]
+    fn deserialize_some() {
+        test_util::test_xml_deserialize(
+            r#"
+                <OptionalCoordinateFrame name="WorldPivotData">
+                    <CFrame>
+                        <X>10</X>
+                        <Y>2.5</Y>
+                        <Z>-4</Z>
+                        <R00>0</R00>
+                        <R01>0</R01>
+                        <R02>1</R02>
+                        <R10>0</R10>
+                        <R11>1</R11>
+                        <R12>0</R12>
+                        <R20>-1</R20>
+                        <R21>0</R21>
+                        <R22>0</R22>
+                    </CFrame>
+                </OptionalCoordinateFrame>
+            "#,
+            &Some(pivot()),
+        );
+    }
+
+    #[test]
+    fn deserialize_none() {
+        test_util::test_xml_deserialize(
+            r#"<OptionalCoordinateFrame name="WorldPivotData"></OptionalCoordinateFrame>"#,
+            &None::<CFrame>,
+        );
+
+        test_util::test_xml_deserialize(
+            r#"<OptionalCoordinateFrame name="WorldPivotData" />"#,
+            &None::<CFrame>,
+        );
+    }
+
+    #[test]
+    fn golden_none() {
+        test_util::test_xml_golden(
+            r#"<OptionalCoordinateFrame name="foo"></OptionalCoordinateFrame>"#,
+            &None::<CFrame>,
+        );
+    }
+}
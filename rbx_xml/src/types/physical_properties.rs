@@ -16,11 +16,22 @@ impl XmlType for PhysicalProperties {
         match self {
             PhysicalProperties::Custom(properties) => {
                 writer.write_value_in_tag(&true, "CustomPhysics")?;
-                writer.write_value_in_tag(&properties.density, "Density")?;
-                writer.write_value_in_tag(&properties.friction, "Friction")?;
-                writer.write_value_in_tag(&properties.elasticity, "Elasticity")?;
-                writer.write_value_in_tag(&properties.friction_weight, "FrictionWeight")?;
-                writer.write_value_in_tag(&properties.elasticity_weight, "ElasticityWeight")?;
+                writer.write_tag_array(
+                    &[
+                        "Density",
+                        "Friction",
+                        "Elasticity",
+                        "FrictionWeight",
+                        "ElasticityWeight",
+                    ],
+                    &[
+                        properties.density,
+                        properties.friction,
+                        properties.elasticity,
+                        properties.friction_weight,
+                        properties.elasticity_weight,
+                    ],
+                )?;
             }
             PhysicalProperties::Default => {
                 writer.write_value_in_tag(&false, "CustomPhysics")?;
@@ -144,4 +155,35 @@ mod test {
             }),
         );
     }
+
+    #[test]
+    fn golden_physical_properties_custom() {
+        test_util::test_xml_golden(
+            r#"<PhysicalProperties name="foo">
+  <CustomPhysics>true</CustomPhysics>
+  <Density>0.7</Density>
+  <Friction>0.3</Friction>
+  <Elasticity>0.5</Elasticity>
+  <FrictionWeight>1</FrictionWeight>
+  <ElasticityWeight>1.25</ElasticityWeight>
+</PhysicalProperties>"#,
+            &PhysicalProperties::Custom(CustomPhysicalProperties {
+                density: 0.7,
+                friction: 0.3,
+                elasticity: 0.5,
+                friction_weight: 1.0,
+                elasticity_weight: 1.25,
+            }),
+        );
+    }
+
+    #[test]
+    fn golden_physical_properties_default() {
+        test_util::test_xml_golden(
+            r#"<PhysicalProperties name="foo">
+  <CustomPhysics>false</CustomPhysics>
+</PhysicalProperties>"#,
+            &PhysicalProperties::Default,
+        );
+    }
 }
@@ -90,4 +90,26 @@ mod test {
             },
         );
     }
+
+    #[test]
+    fn golden_ray() {
+        test_util::test_xml_golden(
+            r#"<Ray name="foo">
+  <origin>
+    <X>5</X>
+    <Y>10</Y>
+    <Z>6.5</Z>
+  </origin>
+  <direction>
+    <X>-0.001</X>
+    <Y>300</Y>
+    <Z>10000000000</Z>
+  </direction>
+</Ray>"#,
+            &Ray::new(
+                Vector3::new(5.0, 10.0, 6.5),
+                Vector3::new(-0.001, 300.0, 1e10),
+            ),
+        );
+    }
 }
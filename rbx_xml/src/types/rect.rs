@@ -86,4 +86,21 @@ mod test {
             },
         );
     }
+
+    #[test]
+    fn golden_rect() {
+        test_util::test_xml_golden(
+            r#"<Rect2D name="foo">
+  <min>
+    <X>0</X>
+    <Y>-1.5</Y>
+  </min>
+  <max>
+    <X>1920</X>
+    <Y>1080.25</Y>
+  </max>
+</Rect2D>"#,
+            &Rect::new(Vector2::new(0.0, -1.5), Vector2::new(1920.0, 1080.25)),
+        );
+    }
 }
@@ -14,6 +14,7 @@ use std::io::{Read, Write};
 use rbx_dom_weak::types::Ref;
 
 use crate::{
+    core::XmlReferent,
     deserializer::ParseState,
     deserializer_core::XmlEventReader,
     error::{DecodeError, EncodeError},
@@ -31,10 +32,13 @@ pub fn write_ref<W: Write>(
 ) -> Result<(), EncodeError> {
     writer.write(XmlWriteEvent::start_element(XML_TAG_NAME).attr("name", xml_property_name))?;
 
-    if value.is_none() {
+    // Refs to instances that aren't in the document, like ones that have
+    // been destroyed, would leave Studio looking for an instance that doesn't
+    // exist.
+    if value.is_none() || !state.writes_instance(value) {
         writer.write(XmlWriteEvent::characters("null"))?;
     } else {
-        writer.write_characters(state.map_id(value))?;
+        writer.write_characters(state.map_id(value).as_str())?;
     }
 
     writer.write(XmlWriteEvent::end_element())?;
@@ -56,7 +60,12 @@ pub fn read_ref<R: Read>(
         // We might not know which ID this referent points to yet, so instead of
         // trying to handle the case where we do here, we just let all referents
         // get written later.
-        state.add_referent_rewrite(id, property_name.to_owned(), ref_contents);
+        state.add_referent_rewrite(
+            id,
+            property_name.to_owned(),
+            XmlReferent::new(ref_contents),
+            reader.text_position(),
+        );
     }
 
     Ok(Ref::none())
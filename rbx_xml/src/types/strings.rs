@@ -1,4 +1,7 @@
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
 
 use crate::{
     core::XmlType,
@@ -19,6 +22,22 @@ impl XmlType for String {
     }
 }
 
+/// Lets `declare_rbx_types!`, which only takes identifiers, name the type held
+/// by `Variant::String`.
+pub type ArcStr = Arc<str>;
+
+impl XmlType for Arc<str> {
+    const XML_TAG_NAME: &'static str = "string";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_string(self)
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        reader.read_characters().map(Arc::from)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ProtectedStringDummy(pub String);
 
@@ -73,6 +92,20 @@ mod test {
         test_util::test_xml_round_trip(&"\n\t".to_owned());
     }
 
+    #[test]
+    fn de_plain_whitespace_string() {
+        test_util::test_xml_deserialize(r#"<string name="foo">  </string>"#, &"  ".to_owned());
+    }
+
+    #[test]
+    fn round_trip_carriage_returns() {
+        test_util::test_xml_round_trip(&"line\r\nline\r".to_owned());
+        test_util::test_xml_deserialize(
+            r#"<string name="foo">a&#13;b</string>"#,
+            &"a\rb".to_owned(),
+        );
+    }
+
     #[test]
     fn de_protected_string() {
         let test_value = "Hello,\n\tworld!\n";
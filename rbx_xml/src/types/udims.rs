@@ -13,8 +13,7 @@ impl XmlType for UDim {
     const XML_TAG_NAME: &'static str = "UDim";
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-        writer.write_value_in_tag(&self.scale, "S")?;
-        writer.write_value_in_tag(&self.offset, "O")?;
+        writer.write_tag_values(&[("S", &self.scale), ("O", &self.offset)])?;
 
         Ok(())
     }
@@ -31,10 +30,12 @@ impl XmlType for UDim2 {
     const XML_TAG_NAME: &'static str = "UDim2";
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-        writer.write_value_in_tag(&self.x.scale, "XS")?;
-        writer.write_value_in_tag(&self.x.offset, "XO")?;
-        writer.write_value_in_tag(&self.y.scale, "YS")?;
-        writer.write_value_in_tag(&self.y.offset, "YO")?;
+        writer.write_tag_values(&[
+            ("XS", &self.x.scale),
+            ("XO", &self.x.offset),
+            ("YS", &self.y.scale),
+            ("YO", &self.y.offset),
+        ])?;
 
         Ok(())
     }
@@ -95,4 +96,28 @@ mod test {
             &UDim2::new(UDim::new(0.5, 1), UDim::new(1.5, 2)),
         );
     }
+
+    #[test]
+    fn golden_udim() {
+        test_util::test_xml_golden(
+            r#"<UDim name="foo">
+  <S>0.25</S>
+  <O>-10</O>
+</UDim>"#,
+            &UDim::new(0.25, -10),
+        );
+    }
+
+    #[test]
+    fn golden_udim2() {
+        test_util::test_xml_golden(
+            r#"<UDim2 name="foo">
+  <XS>1</XS>
+  <XO>-5</XO>
+  <YS>0.333</YS>
+  <YO>200</YO>
+</UDim2>"#,
+            &UDim2::new(UDim::new(1.0, -5), UDim::new(0.333, 200)),
+        );
+    }
 }
@@ -0,0 +1,59 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::UniqueId;
+
+use crate::{
+    core::XmlType,
+    deserializer_core::XmlEventReader,
+    error::{DecodeError, DecodeErrorDetail, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// A UniqueId is serialized as its 16 bytes in hexadecimal:
+// <UniqueId name="UniqueId">44b188dace632b4702e9c68d004815fc</UniqueId>
+impl XmlType for UniqueId {
+    const XML_TAG_NAME: &'static str = "UniqueId";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_string(&self.to_string())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        reader.read_characters()?.trim().parse().map_err(|_| {
+            reader.error(DecodeErrorDetail::InvalidContent(
+                "UniqueId must be 32 hexadecimal digits",
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn round_trip_unique_id() {
+        test_util::test_xml_round_trip(&UniqueId::generate());
+        test_util::test_xml_round_trip(&UniqueId::default());
+    }
+
+    #[test]
+    fn deserialize_unique_id() {
+        test_util::test_xml_deserialize(
+            r#"<UniqueId name="UniqueId">44b188dace632b4702e9c68d004815fc</UniqueId>"#,
+            &"44b188dace632b4702e9c68d004815fc"
+                .parse::<UniqueId>()
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn serialize_unique_id() {
+        test_util::test_xml_serialize(
+            r#"<UniqueId name="foo">0000000000000000000000010000002a</UniqueId>"#,
+            &UniqueId::new(42, 1, 0),
+        );
+    }
+}
@@ -10,16 +10,16 @@ use crate::{
 };
 
 macro_rules! impl_vector {
-    ( $vector: ident, $component: ident, ( $( $axis: ident : $label: literal ),* ) ) => {
+    (
+        $vector: ident,
+        $component: ident,
+        ( $( $axis: ident : $label: literal ),* )
+    ) => {
         impl XmlType for $vector {
             const XML_TAG_NAME: &'static str = stringify!($vector);
 
             fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-                $(
-                    writer.write_value_in_tag(&self.$axis, $label)?;
-                )*
-
-                Ok(())
+                writer.write_tag_array(&[$( $label ),*], &[$( self.$axis ),*])
             }
 
             fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
@@ -66,4 +66,101 @@ mod test {
     fn round_trip_vector3int16() {
         test_util::test_xml_round_trip(&Vector3int16::new(1234, 4567, 8913));
     }
+
+    #[test]
+    fn round_trip_vector3_edge_floats() {
+        let subnormals = Vector3::new(
+            f32::from_bits(1),
+            f32::MIN_POSITIVE / 2.0,
+            -f32::MIN_POSITIVE,
+        );
+        test_util::test_xml_round_trip(&subnormals);
+
+        let extremes = Vector3::new(f32::MAX, f32::INFINITY, f32::NEG_INFINITY);
+        test_util::test_xml_round_trip(&extremes);
+
+        let mut buffer = Vec::new();
+        let mut writer = XmlEventWriter::from_output(&mut buffer, None);
+        Vector3::new(-0.0, f32::NAN, 0.0)
+            .write_outer_xml("foo", &mut writer)
+            .unwrap();
+
+        // Neither -0 nor NaN can be checked with `==`.
+        let mut reader = XmlEventReader::from_source(buffer.as_slice());
+        reader.next().unwrap().unwrap();
+        let value = Vector3::read_outer_xml(&mut reader).unwrap();
+
+        assert!(value.x == 0.0 && value.x.is_sign_negative());
+        assert!(value.y.is_nan());
+        assert!(value.z == 0.0 && value.z.is_sign_positive());
+    }
+
+    #[test]
+    fn golden_vector2() {
+        test_util::test_xml_golden(
+            r#"<Vector2 name="foo">
+  <X>-0.5</X>
+  <Y>100000000000000000000</Y>
+</Vector2>"#,
+            &Vector2::new(-0.5, 1e20),
+        );
+    }
+
+    #[test]
+    fn golden_vector2_infinite() {
+        test_util::test_xml_golden(
+            r#"<Vector2 name="foo">
+  <X>INF</X>
+  <Y>-INF</Y>
+</Vector2>"#,
+            &Vector2::new(f32::INFINITY, f32::NEG_INFINITY),
+        );
+    }
+
+    #[test]
+    fn golden_vector2int16() {
+        test_util::test_xml_golden(
+            r#"<Vector2int16 name="foo">
+  <X>-32768</X>
+  <Y>32767</Y>
+</Vector2int16>"#,
+            &Vector2int16::new(-32768, 32767),
+        );
+    }
+
+    #[test]
+    fn golden_vector3() {
+        test_util::test_xml_golden(
+            r#"<Vector3 name="foo">
+  <X>0.1</X>
+  <Y>-0</Y>
+  <Z>16777216</Z>
+</Vector3>"#,
+            &Vector3::new(0.1, -0.0, 16777216.0),
+        );
+    }
+
+    #[test]
+    fn golden_vector3_nan() {
+        test_util::test_xml_golden(
+            r#"<Vector3 name="foo">
+  <X>NAN</X>
+  <Y>1</Y>
+  <Z>2</Z>
+</Vector3>"#,
+            &Vector3::new(f32::NAN, 1.0, 2.0),
+        );
+    }
+
+    #[test]
+    fn golden_vector3int16() {
+        test_util::test_xml_golden(
+            r#"<Vector3int16 name="foo">
+  <X>-1</X>
+  <Y>0</Y>
+  <Z>1024</Z>
+</Vector3int16>"#,
+            &Vector3int16::new(-1, 0, 1024),
+        );
+    }
 }
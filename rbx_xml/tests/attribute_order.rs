@@ -0,0 +1,81 @@
+use rbx_dom_weak::{
+    types::{Variant, Vector3},
+    WeakDom,
+};
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, EncodeOptions, EncodePropertyBehavior};
+
+/// A model in the style of an external generator that writes single-quoted
+/// attributes and puts them in the reverse of the order Roblox does.
+static GENERATED: &str = include_str!("../test-files/single-quoted-reversed.rbxmx");
+
+/// The same model with its attributes written the way Roblox writes them.
+fn conventional() -> String {
+    GENERATED
+        .replace(
+            "referent='RBX1' class='Model'",
+            "class='Model' referent='RBX1'",
+        )
+        .replace(
+            "referent='RBX2' class='Part'",
+            "class='Part' referent='RBX2'",
+        )
+        .replace(
+            "referent='RBX3' class='Folder'",
+            "class='Folder' referent='RBX3'",
+        )
+        .replace('\'', "\"")
+}
+
+fn decode(source: &str) -> WeakDom {
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    rbx_xml::from_str(source, options).unwrap()
+}
+
+fn encode(dom: &WeakDom) -> String {
+    let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::WriteUnknown);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[test]
+fn generated_model() {
+    let _ = env_logger::try_init();
+
+    let dom = decode(GENERATED);
+
+    let model = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(model.class, "Model");
+    assert_eq!(model.name, "Generated");
+
+    let part_ref = model.children()[0];
+    let part = dom.get_by_ref(part_ref).unwrap();
+    assert_eq!(part.class, "Part");
+    assert_eq!(
+        part.properties.get("Size"),
+        Some(&Variant::Vector3(Vector3::new(4.0, 1.0, 2.0)))
+    );
+    assert_eq!(
+        model.properties.get("PrimaryPart"),
+        Some(&Variant::Ref(part_ref))
+    );
+
+    let folder = dom.get_by_ref(model.children()[1]).unwrap();
+    match folder.properties.get("Blob") {
+        Some(Variant::SharedString(value)) => assert_eq!(value.data(), b"Hello"),
+        other => panic!("expected a SharedString, got {:?}", other),
+    }
+}
+
+#[test]
+fn written_the_usual_way() {
+    let _ = env_logger::try_init();
+
+    let from_generated = encode(&decode(GENERATED));
+    let from_conventional = encode(&decode(&conventional()));
+
+    assert_eq!(from_generated, from_conventional);
+    assert!(from_generated.contains(r#"<Item class="Model" referent="0">"#));
+    assert!(!from_generated.contains('\''));
+}
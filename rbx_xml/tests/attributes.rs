@@ -0,0 +1,174 @@
+use rbx_dom_weak::{
+    types::{Attributes, BinaryString, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{DecodeOptions, EncodeOptions, RootPropertyBehavior};
+
+/// A Folder with a `Mode` attribute set to `"Hard"` and a `Lives` attribute
+/// set to 3.0.
+const ATTRIBUTES: &[u8] = b"\x02\x00\x00\x00\
+    \x04\x00\x00\x00Mode\x02\x04\x00\x00\x00Hard\
+    \x05\x00\x00\x00Lives\x06\x00\x00\x00\x00\x00\x00\x08\x40";
+
+fn document(attributes: &[u8]) -> String {
+    format!(
+        r#"<roblox version="4">
+            <Item class="Folder" referent="RBX0">
+                <Properties>
+                    <string name="Name">Settings</string>
+                    <BinaryString name="AttributesSerialize">{}</BinaryString>
+                </Properties>
+            </Item>
+        </roblox>"#,
+        base64::encode(attributes)
+    )
+}
+
+fn read_attributes() -> DecodeOptions {
+    DecodeOptions::new().read_attributes(true)
+}
+
+fn first_child(dom: &WeakDom) -> &rbx_dom_weak::Instance {
+    dom.get_by_ref(dom.root().children()[0]).unwrap()
+}
+
+fn encode(dom: &WeakDom, ids: &[rbx_dom_weak::types::Ref], options: EncodeOptions) -> String {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, ids, options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+/// Decodes the `AttributesSerialize` property written for the first child.
+fn encoded_attributes(dom: &WeakDom) -> Variant {
+    let document = encode(dom, dom.root().children(), EncodeOptions::new());
+    let decoded = rbx_xml::from_str_default(&document).unwrap();
+
+    first_child(&decoded).properties["AttributesSerialize"].clone()
+}
+
+#[test]
+fn read_and_write() {
+    let _ = env_logger::try_init();
+
+    let dom = rbx_xml::from_str(document(ATTRIBUTES), read_attributes()).unwrap();
+
+    let expected: Attributes = vec![("Mode", Variant::from("Hard")), ("Lives", 3.0f64.into())]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        first_child(&dom).properties.get("AttributesSerialize"),
+        Some(&Variant::Attributes(expected))
+    );
+
+    assert_eq!(
+        encoded_attributes(&dom),
+        Variant::BinaryString(BinaryString::from(ATTRIBUTES.to_vec()))
+    );
+}
+
+#[test]
+fn off_by_default() {
+    let _ = env_logger::try_init();
+
+    let dom = rbx_xml::from_str_default(document(ATTRIBUTES)).unwrap();
+
+    assert_eq!(
+        first_child(&dom).properties.get("AttributesSerialize"),
+        Some(&Variant::BinaryString(BinaryString::from(
+            ATTRIBUTES.to_vec()
+        )))
+    );
+}
+
+#[test]
+fn modify() {
+    let _ = env_logger::try_init();
+
+    let mut dom = rbx_xml::from_str(document(ATTRIBUTES), read_attributes()).unwrap();
+    let folder_ref = dom.root().children()[0];
+
+    let folder = dom.get_by_ref_mut(folder_ref).unwrap();
+    match folder.properties.get_mut("AttributesSerialize") {
+        Some(Variant::Attributes(attributes)) => {
+            attributes.insert("Lives", 5.0f64);
+            attributes.remove("Mode");
+        }
+        other => panic!("expected attributes, got {:?}", other),
+    }
+
+    assert_eq!(
+        encoded_attributes(&dom),
+        Variant::BinaryString(BinaryString::from(
+            b"\x01\x00\x00\x00\x05\x00\x00\x00Lives\x06\x00\x00\x00\x00\x00\x00\x14\x40".to_vec()
+        ))
+    );
+}
+
+#[test]
+fn empty() {
+    let _ = env_logger::try_init();
+
+    let dom = rbx_xml::from_str(document(b""), read_attributes()).unwrap();
+
+    assert_eq!(
+        first_child(&dom).properties.get("AttributesSerialize"),
+        Some(&Variant::Attributes(Attributes::new()))
+    );
+    assert_eq!(
+        encoded_attributes(&dom),
+        Variant::BinaryString(BinaryString::new())
+    );
+}
+
+#[test]
+fn unknown_type_is_preserved() {
+    let _ = env_logger::try_init();
+
+    // One attribute with the type 0x7f, which doesn't exist.
+    let data = b"\x01\x00\x00\x00\x04\x00\x00\x00Mode\x7f\x00";
+    let dom = rbx_xml::from_str(document(data), read_attributes()).unwrap();
+
+    assert_eq!(
+        first_child(&dom).properties.get("AttributesSerialize"),
+        Some(&Variant::BinaryString(BinaryString::from(data.to_vec())))
+    );
+}
+
+#[test]
+fn root_meta() {
+    let _ = env_logger::try_init();
+
+    let attributes: Attributes = vec![("Mode", "Hard")].into_iter().collect();
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_property("AttributesSerialize", attributes.clone())
+            .with_child(InstanceBuilder::new("Workspace")),
+    );
+
+    let options = EncodeOptions::new().root_property_behavior(RootPropertyBehavior::HoistIntoMeta);
+    let document = encode(&dom, dom.root().children(), options);
+
+    let decoded = rbx_xml::from_str(&document, read_attributes()).unwrap();
+    assert_eq!(
+        decoded.root().properties.get("AttributesSerialize"),
+        Some(&Variant::Attributes(attributes))
+    );
+}
+
+#[test]
+fn unsupported_type() {
+    let _ = env_logger::try_init();
+
+    let attributes: Attributes = vec![("Count", Variant::Int32(5))].into_iter().collect();
+    let dom = WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+        InstanceBuilder::new("Folder").with_property("AttributesSerialize", attributes),
+    ));
+
+    let mut buffer = Vec::new();
+    let error = rbx_xml::to_writer_default(&mut buffer, &dom, dom.root().children()).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Attribute Count has type Int32, which can't be stored in an attribute"
+    );
+}
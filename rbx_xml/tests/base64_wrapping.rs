@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use rbx_dom_weak::{
+    types::{BinaryString, Blob, BlobStore, SharedString, TempFileStore, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, EncodeOptions, EncodePropertyBehavior};
+
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 13 + i / 7) as u8).collect()
+}
+
+fn folder(properties: Vec<(&str, Variant)>) -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Folder").with_properties(properties)),
+    )
+}
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> String {
+    let options = options.property_behavior(EncodePropertyBehavior::NoReflection);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+fn decode(document: &str) -> WeakDom {
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::NoReflection);
+    rbx_xml::from_str(document, options).unwrap()
+}
+
+fn properties(dom: &WeakDom) -> Vec<(String, Variant)> {
+    let folder = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    folder
+        .properties
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// The lines of base64 in the first element whose start tag begins with
+/// `start`.
+fn base64_lines<'a>(document: &'a str, start: &str) -> Vec<&'a str> {
+    let offset = document.find(start).unwrap();
+    let rest = &document[offset..];
+    let rest = &rest[rest.find('>').unwrap() + 1..];
+    let contents = &rest[..rest.find("</").unwrap()];
+    let contents = contents
+        .trim_start_matches("<![CDATA[")
+        .trim_end_matches("]]>");
+
+    contents.split('\n').collect()
+}
+
+#[test]
+fn wrapped_at_72_by_default() {
+    let _ = env_logger::try_init();
+
+    let dom = folder(vec![
+        ("Data", BinaryString::from(payload(200)).into()),
+        ("Mesh", SharedString::new(payload(100)).into()),
+    ]);
+    let document = encode(&dom, EncodeOptions::new());
+
+    // 200 bytes is 268 characters of base64.
+    let lines = base64_lines(&document, r#"<BinaryString name="Data""#);
+    let lengths: Vec<_> = lines.iter().map(|line| line.len()).collect();
+    assert_eq!(lengths, [72, 72, 72, 52]);
+
+    // 100 bytes is 136 characters, with no line break left at the end.
+    let lines = base64_lines(&document, "<SharedString md5=");
+    let lengths: Vec<_> = lines.iter().map(|line| line.len()).collect();
+    assert_eq!(lengths, [72, 64]);
+
+    assert_eq!(properties(&decode(&document)), properties(&dom));
+}
+
+#[test]
+fn one_line_without_wrapping() {
+    let _ = env_logger::try_init();
+
+    let dom = folder(vec![("Data", BinaryString::from(payload(200)).into())]);
+    let wrapped = encode(&dom, EncodeOptions::new());
+    let unwrapped = encode(&dom, EncodeOptions::new().base64_line_length(None));
+
+    let lines = base64_lines(&unwrapped, r#"<BinaryString name="Data""#);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].len(), 268);
+
+    assert_eq!(wrapped.replace('\n', ""), unwrapped.replace('\n', ""));
+    assert_eq!(
+        properties(&decode(&unwrapped)),
+        properties(&decode(&wrapped))
+    );
+}
+
+#[test]
+fn stored_values_wrap_across_pieces() {
+    let _ = env_logger::try_init();
+
+    // Stored values are encoded 12288 bytes at a time, which is 16384
+    // characters and so doesn't end on a line boundary.
+    let store: Arc<dyn BlobStore> = Arc::new(TempFileStore::new().unwrap());
+    let data = payload(30_000);
+    let blob = Blob::store(&store, data.as_slice()).unwrap();
+
+    let dom = folder(vec![("Data", BinaryString::from_blob(blob).into())]);
+    let document = encode(&dom, EncodeOptions::new().base64_line_length(Some(76)));
+
+    let lines = base64_lines(&document, r#"<BinaryString name="Data""#);
+    let (last, full) = lines.split_last().unwrap();
+    assert!(full.iter().all(|line| line.len() == 76));
+    assert_eq!(full.len() * 76 + last.len(), 40_000);
+
+    assert_eq!(
+        properties(&decode(&document)),
+        [("Data".to_owned(), BinaryString::from(data).into())]
+    );
+}
+
+#[test]
+fn decode_crlf_and_interior_whitespace() {
+    let _ = env_logger::try_init();
+
+    let document = r#"<roblox version="4">
+    <Item class="Folder" referent="RBX0">
+        <Properties>
+            <BinaryString name="Data">SGVs&#13;
+bG8s IHdv&#13;
+cmxk	IQ==</BinaryString>
+            <SharedString name="Mesh">aGVsbG8=</SharedString>
+            <BinaryString name="Empty"></BinaryString>
+        </Properties>
+    </Item>
+    <SharedStrings>
+        <SharedString md5="aGVsbG8=">aGVs
+  bG8=&#13;
+</SharedString>
+    </SharedStrings>
+</roblox>"#;
+
+    let folder = decode(document);
+    let properties = properties(&folder);
+
+    assert_eq!(
+        properties,
+        [
+            (
+                "Data".to_owned(),
+                BinaryString::from(b"Hello, world!".to_vec()).into()
+            ),
+            (
+                "Mesh".to_owned(),
+                SharedString::new(b"hello".to_vec()).into()
+            ),
+            ("Empty".to_owned(), BinaryString::new().into()),
+        ]
+    );
+}
+
+#[test]
+fn empty_values_have_no_lines() {
+    let _ = env_logger::try_init();
+
+    let dom = folder(vec![("Empty", BinaryString::new().into())]);
+    let document = encode(&dom, EncodeOptions::new());
+
+    assert!(document.contains(r#"<BinaryString name="Empty"></BinaryString>"#));
+    assert_eq!(properties(&decode(&document)), properties(&dom));
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use rbx_dom_weak::{
+    types::{BinaryString, Blob, BlobStore, SharedString, TempFileStore, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{DecodeErrorKind, DecodeOptions, EncodeOptions, EncodePropertyBehavior};
+
+/// Bytes that don't repeat within a base64 line, so a piece decoded in the
+/// wrong place would be noticed.
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 + i / 251) as u8).collect()
+}
+
+fn temp_store() -> Arc<dyn BlobStore> {
+    Arc::new(TempFileStore::new().unwrap())
+}
+
+fn encode(dom: &WeakDom) -> String {
+    let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::NoReflection);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+fn decode_with_store(document: &str, store: &Arc<dyn BlobStore>) -> WeakDom {
+    let options = DecodeOptions::new()
+        .property_behavior(rbx_xml::DecodePropertyBehavior::NoReflection)
+        .blob_store(Arc::clone(store))
+        .blob_threshold(1024);
+
+    rbx_xml::from_str(document, options).unwrap()
+}
+
+fn folder(properties: Vec<(&str, Variant)>) -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Folder").with_properties(properties)),
+    )
+}
+
+fn first_child(dom: &WeakDom) -> &rbx_dom_weak::Instance {
+    dom.get_by_ref(dom.root().children()[0]).unwrap()
+}
+
+#[test]
+fn large_values_are_stored() {
+    let _ = env_logger::try_init();
+
+    let store = temp_store();
+    let large = payload(100_000);
+
+    let dom = folder(vec![
+        ("Small", BinaryString::from(b"small".to_vec()).into()),
+        ("Large", BinaryString::from(large.clone()).into()),
+        ("Shared", SharedString::new(large.clone()).into()),
+    ]);
+    let decoded = decode_with_store(&encode(&dom), &store);
+    let folder = first_child(&decoded);
+
+    match folder.properties.get("Small") {
+        Some(Variant::BinaryString(value)) => {
+            let contents: &[u8] = value.as_ref();
+            assert!(value.blob().is_none());
+            assert_eq!(contents, b"small");
+        }
+        other => panic!("expected a BinaryString, got {:?}", other),
+    }
+
+    match folder.properties.get("Large") {
+        Some(Variant::BinaryString(value)) => {
+            let contents: &[u8] = value.as_ref();
+            assert_eq!(value.blob().map(Blob::len), Some(large.len() as u64));
+            assert_eq!(contents, &large[..]);
+        }
+        other => panic!("expected a BinaryString, got {:?}", other),
+    }
+
+    match folder.properties.get("Shared") {
+        Some(Variant::SharedString(value)) => {
+            assert_eq!(value.blob().map(Blob::len), Some(large.len() as u64));
+            assert_eq!(value, &SharedString::new(large));
+        }
+        other => panic!("expected a SharedString, got {:?}", other),
+    }
+}
+
+/// Values kept in a store encode to the same document as values kept in
+/// memory, even though they're written a piece at a time.
+#[test]
+fn stored_values_encode_the_same() {
+    let _ = env_logger::try_init();
+
+    let store = temp_store();
+
+    for &len in &[0, 1, 2, 3, 12_288, 12_289, 100_000] {
+        let contents = payload(len);
+        let blob = Blob::store(&store, contents.as_slice()).unwrap();
+
+        let in_memory = folder(vec![
+            ("Data", BinaryString::from(contents.clone()).into()),
+            ("Shared", SharedString::new(contents.clone()).into()),
+        ]);
+        let stored = folder(vec![
+            ("Data", BinaryString::from_blob(blob.clone()).into()),
+            ("Shared", SharedString::from_blob(blob).unwrap().into()),
+        ]);
+
+        let document = encode(&stored);
+
+        let decoded = decode_with_store(&document, &store);
+        match first_child(&decoded).properties.get("Data") {
+            Some(Variant::BinaryString(value)) => {
+                let decoded_contents: &[u8] = value.as_ref();
+                assert_eq!(decoded_contents, &contents[..], "length {}", len);
+            }
+            other => panic!("expected a BinaryString, got {:?}", other),
+        }
+
+        // The in-memory encoding wraps its contents in CDATA, which the
+        // stored encoding doesn't bother with.
+        assert_eq!(
+            document,
+            encode(&in_memory)
+                .replace("<![CDATA[", "")
+                .replace("]]>", ""),
+            "length {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn invalid_stored_base64() {
+    let _ = env_logger::try_init();
+
+    let store = temp_store();
+    let contents = format!("{}!!!!", "AAAA".repeat(4096));
+    let document = format!(
+        r#"<roblox version="4">
+            <Item class="Folder" referent="RBX1">
+                <Properties>
+                    <BinaryString name="Data">{}</BinaryString>
+                </Properties>
+            </Item>
+        </roblox>"#,
+        contents
+    );
+
+    let options = DecodeOptions::new().blob_store(store).blob_threshold(1024);
+    let err = rbx_xml::from_str(&document, options).unwrap_err();
+
+    assert_eq!(err.kind(), DecodeErrorKind::MalformedValue);
+}
@@ -0,0 +1,143 @@
+use rbx_dom_weak::{types::Variant, WeakDom};
+use rbx_test_util::{dom_tree, encode_xml, first_child};
+use rbx_xml::{ChildOrder, DecodeOptions, EncodeOptions};
+
+fn canonical() -> EncodeOptions {
+    EncodeOptions::new()
+        .sort_properties(true)
+        .child_order(ChildOrder::SortByName)
+}
+
+/// Points the ObjectValue named `Pointer` in the first top-level instance at
+/// its sibling named `Target`.
+fn link_pointer(dom: &mut WeakDom) {
+    let model = first_child(dom);
+    let find = |name: &str| {
+        model
+            .children()
+            .iter()
+            .copied()
+            .find(|id| dom.get_by_ref(*id).unwrap().name == name)
+            .unwrap()
+    };
+    let (pointer, target) = (find("Pointer"), find("Target"));
+
+    dom.get_by_ref_mut(pointer)
+        .unwrap()
+        .properties
+        .insert("Value".to_owned(), Variant::Ref(target));
+}
+
+/// The same instances, with their properties and children added in a
+/// different order.
+fn shuffled_trees() -> (WeakDom, WeakDom) {
+    let mut first = dom_tree! {
+        DataModel {
+            Model "Car" {
+                ObjectValue "Pointer" {}
+                Part "Wheel" { Transparency = 0.5f32, Anchored = true }
+                Part "Target" { CanCollide = false, Anchored = true, Locked = true }
+                Seat "Wheel" {}
+            }
+        }
+    };
+    let mut second = dom_tree! {
+        DataModel {
+            Model "Car" {
+                Seat "Wheel" {}
+                Part "Target" { Locked = true, Anchored = true, CanCollide = false }
+                Part "Wheel" { Anchored = true, Transparency = 0.5f32 }
+                ObjectValue "Pointer" {}
+            }
+        }
+    };
+
+    link_pointer(&mut first);
+    link_pointer(&mut second);
+
+    (first, second)
+}
+
+#[test]
+fn canonical_output_ignores_insertion_order() {
+    let _ = env_logger::try_init();
+
+    let (first, second) = shuffled_trees();
+    let encoded = encode_xml(&first, canonical());
+
+    assert_eq!(encoded, encode_xml(&first, canonical()));
+    assert_eq!(encoded, encode_xml(&second, canonical()));
+
+    // Without the options, the order that instances were built in shows.
+    assert_ne!(
+        encode_xml(&first, EncodeOptions::new()),
+        encode_xml(&second, EncodeOptions::new())
+    );
+}
+
+#[test]
+fn canonical_output_survives_a_round_trip() {
+    let _ = env_logger::try_init();
+
+    let (first, _) = shuffled_trees();
+    let encoded = encode_xml(&first, canonical());
+
+    let decoded = rbx_xml::from_str(&encoded, DecodeOptions::new()).unwrap();
+    assert_eq!(encode_xml(&decoded, canonical()), encoded);
+
+    // Decoding keeps the order from the file, so writing the decoded tree
+    // without sorting it again gives the same document too.
+    assert_eq!(encode_xml(&decoded, EncodeOptions::new()), encoded);
+}
+
+#[test]
+fn canonical_order() {
+    let _ = env_logger::try_init();
+
+    let (first, _) = shuffled_trees();
+    let encoded = encode_xml(&first, canonical());
+
+    // Decoding keeps the order from the file, so the decoded children show
+    // what order they were written in: by Name, and then by ClassName.
+    let decoded = rbx_xml::from_str(&encoded, DecodeOptions::new()).unwrap();
+    let model = first_child(&decoded);
+    let children: Vec<_> = model
+        .children()
+        .iter()
+        .map(|id| {
+            let child = decoded.get_by_ref(*id).unwrap();
+            (child.name.as_str(), child.class.as_str())
+        })
+        .collect();
+    assert_eq!(
+        children,
+        [
+            ("Pointer", "ObjectValue"),
+            ("Target", "Part"),
+            ("Wheel", "Part"),
+            ("Wheel", "Seat"),
+        ]
+    );
+
+    // Name is first, and the rest of the properties are sorted.
+    let target = decoded.get_by_ref(model.children()[1]).unwrap();
+    let properties: Vec<_> = target.properties.keys().map(String::as_str).collect();
+    assert_eq!(properties, ["Anchored", "CanCollide", "Locked"]);
+    let name = encoded
+        .find(r#"<string name="Name">Target</string>"#)
+        .unwrap();
+    assert!(name < encoded.find(r#"name="Anchored""#).unwrap());
+
+    // Referents are handed out in the order that instances are first needed,
+    // which only depends on the order they're written in.
+    assert!(
+        encoded.contains(r#"<Item class="Part" referent="2">"#),
+        "{}",
+        encoded
+    );
+    assert!(
+        encoded.contains(r#"<Ref name="Value">2</Ref>"#),
+        "{}",
+        encoded
+    );
+}
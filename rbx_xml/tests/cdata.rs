@@ -0,0 +1,69 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+fn first_child(dom: &WeakDom) -> &rbx_dom_weak::Instance {
+    dom.get_by_ref(dom.root().children()[0]).unwrap()
+}
+
+/// Adjacent CDATA sections are one value, which is how values containing the
+/// CDATA terminator are written.
+#[test]
+fn split_cdata_sections() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="StringValue" referent="RBX1">
+                <Properties>
+                    <string name="Value"><![CDATA[ x[[ ]]]]><![CDATA[> ]]></string>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let tree = rbx_xml::from_str_default(document).unwrap();
+
+    assert_eq!(
+        first_child(&tree).properties.get("Value"),
+        Some(&Variant::from(" x[[ ]]> "))
+    );
+}
+
+#[test]
+fn source_containing_cdata_terminator() {
+    let _ = env_logger::try_init();
+
+    let sources = [
+        "\n--[[ a table like t[a[b]]>1 closes the comment ]]\nprint('hi')\n",
+        "\t]]>",
+        "  ]]>]]>  ",
+        "]]>",
+    ];
+
+    for source in &sources {
+        let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+            InstanceBuilder::new("Script").with_property("Source", source.to_string()),
+        ));
+
+        let options = rbx_xml::EncodeOptions::new()
+            .property_behavior(rbx_xml::EncodePropertyBehavior::NoReflection);
+
+        let mut buffer = Vec::new();
+        rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+
+        let document = String::from_utf8(buffer).unwrap();
+        let decoded = rbx_xml::from_str(
+            &document,
+            rbx_xml::DecodeOptions::new()
+                .property_behavior(rbx_xml::DecodePropertyBehavior::NoReflection),
+        )
+        .unwrap();
+
+        assert_eq!(
+            first_child(&decoded).properties.get("Source"),
+            Some(&Variant::from(*source)),
+            "{:?} was written as {}",
+            source,
+            document
+        );
+    }
+}
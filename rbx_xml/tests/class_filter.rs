@@ -0,0 +1,140 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    WeakDom,
+};
+use rbx_reflection::ClassFilter;
+use rbx_xml::DecodeOptions;
+
+/// A small place with geometry, scripts, and UI spread across the usual
+/// services, including scripts nested inside of models and UI.
+static PLACE: &str = include_str!("../test-files/extraction-place.rbxlx");
+
+fn decode(filter: ClassFilter) -> WeakDom {
+    rbx_xml::from_str(PLACE, DecodeOptions::new().class_filter(filter)).unwrap()
+}
+
+/// The full name of every instance in the dom other than the root, in the
+/// order they're found walking the tree depth first.
+fn full_names(dom: &WeakDom) -> Vec<String> {
+    dom.descendants(dom.root_ref())
+        .skip(1)
+        .map(|instance| dom.full_name(instance.referent()).unwrap())
+        .collect()
+}
+
+#[test]
+fn scripts() {
+    let _ = env_logger::try_init();
+
+    let dom = decode(ClassFilter::scripts());
+
+    assert_eq!(
+        full_names(&dom),
+        [
+            "DataModel.Drive",
+            "DataModel.Settings",
+            "DataModel.Main",
+            "DataModel.Update",
+            "DataModel.Controls",
+        ]
+    );
+}
+
+#[test]
+fn gui() {
+    let _ = env_logger::try_init();
+
+    let database = rbx_reflection_database::get();
+    let dom = decode(ClassFilter::gui(database));
+
+    assert_eq!(
+        full_names(&dom),
+        [
+            "DataModel.Hud",
+            "DataModel.Hud.Speedometer",
+            "DataModel.Hud.Speedometer.Rounded",
+            "DataModel.Hud.Speedometer.Speed",
+        ]
+    );
+}
+
+#[test]
+fn geometry() {
+    let _ = env_logger::try_init();
+
+    let database = rbx_reflection_database::get();
+    let dom = decode(ClassFilter::geometry(database));
+
+    assert_eq!(
+        full_names(&dom),
+        [
+            "DataModel.Terrain",
+            "DataModel.SpawnLocation",
+            "DataModel.Chassis",
+            "DataModel.Chassis.Hitch",
+            "DataModel.Body",
+            "DataModel.BodyWeld",
+            "DataModel.Tire",
+            "DataModel.Axle",
+        ]
+    );
+}
+
+#[test]
+fn combined_filters() {
+    let _ = env_logger::try_init();
+
+    let database = rbx_reflection_database::get();
+
+    let local_scripts = ClassFilter::scripts().intersection(&ClassFilter::new(Some("LocalScript")));
+    assert_eq!(
+        full_names(&decode(local_scripts)),
+        ["DataModel.Update", "DataModel.Controls"]
+    );
+
+    let scripts_and_ui = ClassFilter::scripts().union(&ClassFilter::gui(database));
+    assert_eq!(
+        full_names(&decode(scripts_and_ui)),
+        [
+            "DataModel.Drive",
+            "DataModel.Settings",
+            "DataModel.Main",
+            "DataModel.Hud",
+            "DataModel.Hud.Speedometer",
+            "DataModel.Hud.Speedometer.Rounded",
+            "DataModel.Hud.Speedometer.Speed",
+            "DataModel.Hud.Speedometer.Update",
+            "DataModel.Controls",
+        ]
+    );
+}
+
+#[test]
+fn refs_to_filtered_instances() {
+    let _ = env_logger::try_init();
+
+    let seat_value = |dom: &WeakDom| {
+        let seat = dom
+            .descendants(dom.root_ref())
+            .find(|instance| instance.name == "Seat")
+            .unwrap();
+
+        match seat.properties.get("Value") {
+            Some(Variant::Ref(target)) => *target,
+            other => panic!("expected a Ref, got {:?}", other),
+        }
+    };
+
+    let database = rbx_reflection_database::get();
+
+    let kept =
+        decode(ClassFilter::geometry(database).union(&ClassFilter::new(Some("ObjectValue"))));
+    assert_eq!(
+        kept.full_name(seat_value(&kept)),
+        Some("DataModel.Chassis".to_owned())
+    );
+
+    let dropped = decode(ClassFilter::new(Some("ObjectValue")));
+    assert_eq!(seat_value(&dropped), Ref::none());
+    assert_eq!(dropped.len(), 2);
+}
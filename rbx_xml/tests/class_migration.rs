@@ -0,0 +1,138 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    MigrationAction, WeakDom,
+};
+use rbx_test_util::{encode_xml, first_child};
+
+/// A model with one of each kind of legacy class: a HopperBin, which becomes a
+/// Tool, a Message holding a Hint, which are removed, and body movers, which
+/// can only be migrated by hand.
+static LEGACY_CLASSES: &str = include_str!("../test-files/legacy-classes.rbxmx");
+
+fn decode() -> WeakDom {
+    rbx_xml::from_str_default(LEGACY_CLASSES).unwrap()
+}
+
+#[test]
+fn plan() {
+    let _ = env_logger::try_init();
+
+    let dom = decode();
+    let database = rbx_reflection_database::get();
+    let report = dom.plan_class_migrations(first_child(&dom).referent(), database);
+
+    let messages: Vec<_> = report.migrations.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "HopperBin DataModel.Starter Kit.Hammer can become a Tool; dropped properties: \
+             Active, BinType",
+            "Message DataModel.Starter Kit.Welcome can be removed: messages are no longer shown; \
+             use a TextLabel in a ScreenGui instead",
+            "BodyVelocity DataModel.Starter Kit.Hoverboard.Thrust is deprecated and needs to be \
+             migrated by hand; consider a LinearVelocity constraint",
+            "BodyGyro DataModel.Starter Kit.Hoverboard.Balance is deprecated and needs to be \
+             migrated by hand; consider an AlignOrientation constraint",
+        ]
+    );
+
+    assert_eq!(report.applied().count(), 0);
+    assert!(report.nulled_refs.is_empty());
+
+    let unchanged = decode();
+    assert_eq!(dom.len(), unchanged.len());
+}
+
+#[test]
+fn apply() {
+    let _ = env_logger::try_init();
+
+    let mut dom = decode();
+    let database = rbx_reflection_database::get();
+    let model_ref = first_child(&dom).referent();
+    let report = dom.apply_class_migrations(model_ref, database);
+
+    let applied: Vec<_> = report
+        .applied()
+        .map(|migration| (migration.class.as_str(), &migration.action))
+        .collect();
+    assert_eq!(
+        applied,
+        [
+            (
+                "HopperBin",
+                &MigrationAction::Replace {
+                    class: "Tool".to_owned(),
+                    dropped_properties: vec!["Active".to_owned(), "BinType".to_owned()],
+                }
+            ),
+            (
+                "Message",
+                &MigrationAction::Remove {
+                    reason: "messages are no longer shown; use a TextLabel in a ScreenGui \
+                             instead"
+                        .to_owned(),
+                }
+            ),
+        ]
+    );
+
+    let unresolved: Vec<_> = report
+        .unresolved()
+        .map(|migration| migration.instance.as_str())
+        .collect();
+    assert_eq!(
+        unresolved,
+        [
+            "DataModel.Starter Kit.Hoverboard.Thrust",
+            "DataModel.Starter Kit.Hoverboard.Balance"
+        ]
+    );
+
+    let model = first_child(&dom);
+    let children: Vec<_> = model
+        .children()
+        .iter()
+        .map(|referent| dom.get_by_ref(*referent).unwrap())
+        .collect();
+
+    let classes: Vec<_> = children
+        .iter()
+        .map(|child| (child.class.as_str(), child.name.as_str()))
+        .collect();
+    assert_eq!(
+        classes,
+        [
+            ("Tool", "Hammer"),
+            ("ObjectValue", "CurrentMessage"),
+            ("Part", "Hoverboard"),
+        ]
+    );
+
+    let tool = children[0];
+    assert_eq!(
+        tool.properties.get("TextureId"),
+        Some(&Variant::Content("rbxasset://Textures/Hammer.png".into()))
+    );
+    assert_eq!(
+        tool.properties.get("RequiresHandle"),
+        Some(&Variant::Bool(false))
+    );
+    assert!(!tool.properties.contains_key("BinType"));
+
+    let object_value = children[1];
+    assert_eq!(
+        object_value.properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+    assert_eq!(report.nulled_refs.len(), 1);
+    assert_eq!(report.nulled_refs[0].referent, object_value.referent());
+    assert_eq!(report.nulled_refs[0].property, "Value");
+
+    // The body movers are left for a person to convert.
+    let hoverboard = children[2];
+    assert_eq!(hoverboard.children().len(), 2);
+
+    // Migrated documents still encode.
+    encode_xml(&dom, rbx_xml::EncodeOptions::new());
+}
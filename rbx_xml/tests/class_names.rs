@@ -0,0 +1,152 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, EncodeError};
+
+/// Characters that class names are built from in `arbitrary_class_names`:
+/// markup characters, whitespace, characters outside of ASCII, and characters
+/// that XML can't represent.
+static CHARACTERS: &str = "aZ0_ <>&\"';#=/\t\n\r\
+    éü日\u{85}\u{A0}\u{2028}\u{FEFF}\u{FFFD}\u{1F600}\u{10FFFF}\
+    \u{0}\u{1}\u{B}\u{1F}\u{7F}\u{FFFE}\u{FFFF}";
+
+fn is_xml_char(character: char) -> bool {
+    !matches!(
+        character,
+        '\u{0}'..='\u{8}' | '\u{B}' | '\u{C}' | '\u{E}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}'
+    )
+}
+
+/// Encodes an instance with the given ClassName and decodes it again, returning
+/// its ClassName as it was decoded.
+fn round_trip(class_name: &str) -> Result<String, EncodeError> {
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new(class_name).with_name("Instance")),
+    );
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, &dom, dom.root().children())?;
+
+    let decoded = rbx_xml::from_reader_default(buffer.as_slice()).unwrap_or_else(|err| {
+        panic!(
+            "encoding {:?} produced a document that can't be decoded: {}\n{}",
+            class_name,
+            err,
+            String::from_utf8_lossy(&buffer)
+        )
+    });
+
+    let instance = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    Ok(instance.class.clone())
+}
+
+#[test]
+fn escaped_class_names_round_trip() {
+    let _ = env_logger::try_init();
+
+    let class_names = [
+        "Custom Class",
+        "A \"quoted\" <tag> & 'apostrophes'",
+        "&amp; already escaped",
+        "Ünïcödé 日本語 \u{1F600}",
+        "tab\there",
+        "line\nbreak\r\nand carriage return",
+        "  padded  ",
+        "",
+    ];
+
+    for class_name in &class_names {
+        assert_eq!(round_trip(class_name).unwrap(), *class_name);
+    }
+}
+
+#[test]
+fn unrepresentable_characters_are_an_error() {
+    let _ = env_logger::try_init();
+
+    let error = round_trip("Bad\u{1}Class").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Can't write \"Bad\\u{1}Class\" as the class attribute of a <Item> element, because XML \
+         can't represent the character U+0001"
+    );
+
+    let error = round_trip("Not\u{FFFF}Allowed").unwrap_err();
+    assert!(error.to_string().contains("U+FFFF"), "{}", error);
+}
+
+#[test]
+fn arbitrary_class_names() {
+    let _ = env_logger::try_init();
+
+    // A fixed seed keeps failures reproducible.
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let characters: Vec<char> = CHARACTERS.chars().collect();
+
+    for _ in 0..2_000 {
+        let len = next() % 12;
+        let class_name: String = (0..len)
+            .map(|_| characters[next() as usize % characters.len()])
+            .collect();
+
+        match round_trip(&class_name) {
+            Ok(decoded) => assert_eq!(decoded, class_name),
+            Err(err) => {
+                assert!(
+                    class_name.chars().any(|c| !is_xml_char(c)),
+                    "{:?} should have been written, but got: {}",
+                    class_name,
+                    err
+                );
+                assert!(err.to_string().starts_with("Can't write"), "{}", err);
+            }
+        }
+    }
+}
+
+#[test]
+fn decoder_accepts_escaped_forms() {
+    let _ = env_logger::try_init();
+
+    let document = r#"<roblox version="4">
+    <Item class='&#80;art &amp; &quot;Co&quot;' referent="RBX0">
+        <Properties>
+            <string name="&#78;ame">First</string>
+        </Properties>
+    </Item>
+    <Item class="Tab&#9;Line&#xA;Return&#xD;" referent="RBX1">
+        <Properties>
+            <string name="Name">Second</string>
+        </Properties>
+    </Item>
+</roblox>"#;
+
+    let expected = [
+        ("Part & \"Co\"".to_owned(), "First".to_owned()),
+        ("Tab\tLine\nReturn\r".to_owned(), "Second".to_owned()),
+    ];
+
+    // None of these classes are in the reflection database, so their names
+    // are only kept when reading unknown properties.
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    let decoded = rbx_xml::from_str(document, options).unwrap();
+    let top_level: Vec<_> = decoded
+        .root()
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = decoded.get_by_ref(*referent).unwrap();
+            (instance.class.clone(), instance.name.clone())
+        })
+        .collect();
+    assert_eq!(top_level, expected);
+
+    let info = rbx_xml::peek(document.as_bytes()).unwrap();
+    assert_eq!(info.top_level(), expected);
+}
@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use rbx_dom_weak::{
+    types::{BlobStore, Ref, SharedString, TempFileStore, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{DecodeErrorKind, DecodeOptions, DecodePropertyBehavior, UnresolvedRefBehavior};
+
+/// Both documents use the same referents, so they can only be told apart if
+/// referents are scoped to a single call.
+static FIRST: &str = r#"
+<roblox version="4">
+    <Item class="Model" referent="RBX0">
+        <Properties>
+            <string name="Name">First</string>
+        </Properties>
+        <Item class="Part" referent="RBX1">
+            <Properties>
+                <string name="Name">FirstTarget</string>
+            </Properties>
+        </Item>
+        <Item class="ObjectValue" referent="RBX2">
+            <Properties>
+                <string name="Name">FirstValue</string>
+                <Ref name="Value">RBX1</Ref>
+            </Properties>
+        </Item>
+    </Item>
+</roblox>
+"#;
+
+static SECOND: &str = r#"
+<roblox version="4">
+    <Item class="Folder" referent="RBX0">
+        <Properties>
+            <string name="Name">Second</string>
+        </Properties>
+        <Item class="Part" referent="RBX1">
+            <Properties>
+                <string name="Name">SecondTarget</string>
+            </Properties>
+        </Item>
+        <Item class="ObjectValue" referent="RBX2">
+            <Properties>
+                <string name="Name">SecondValue</string>
+                <Ref name="Value">RBX1</Ref>
+            </Properties>
+        </Item>
+        <Item class="ObjectValue" referent="RBX3">
+            <Properties>
+                <string name="Name">Dangling</string>
+                <Ref name="Value">RBX9</Ref>
+            </Properties>
+        </Item>
+    </Item>
+    <Item class="Folder" referent="RBX4">
+        <Properties>
+            <string name="Name">Extra</string>
+        </Properties>
+    </Item>
+</roblox>
+"#;
+
+fn ref_value(dom: &WeakDom, referent: Ref) -> Ref {
+    match dom.get_by_ref(referent).unwrap().properties.get("Value") {
+        Some(Variant::Ref(target)) => *target,
+        other => panic!("expected a Ref, got {:?}", other),
+    }
+}
+
+#[test]
+fn two_documents_into_one_dom() {
+    let _ = env_logger::try_init();
+
+    let mut dom =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+    let folder = dom.root().children()[0];
+
+    let first_roots =
+        rbx_xml::decode_into(&mut dom, folder, FIRST.as_bytes(), DecodeOptions::new()).unwrap();
+    let second_roots =
+        rbx_xml::decode_into(&mut dom, folder, SECOND.as_bytes(), DecodeOptions::new()).unwrap();
+
+    assert_eq!(first_roots.len(), 1);
+    assert_eq!(second_roots.len(), 2);
+
+    let folder_children = dom.get_by_ref(folder).unwrap().children().to_vec();
+    assert_eq!(
+        folder_children,
+        [first_roots[0], second_roots[0], second_roots[1]]
+    );
+
+    for &model in &[first_roots[0], second_roots[0]] {
+        let children = dom.get_by_ref(model).unwrap().children().to_vec();
+        assert_eq!(ref_value(&dom, children[1]), children[0]);
+    }
+
+    // RBX9 isn't in the second document. It shouldn't be resolved against
+    // anything from the first one either.
+    let dangling = dom.get_by_ref(second_roots[0]).unwrap().children()[2];
+    assert_eq!(ref_value(&dom, dangling), Ref::none());
+
+    assert_eq!(
+        dom.full_name(ref_value(
+            &dom,
+            dom.get_by_ref(first_roots[0]).unwrap().children()[1]
+        )),
+        Some("DataModel.Folder.First.FirstTarget".to_owned())
+    );
+}
+
+#[test]
+fn failure_removes_inserted_instances() {
+    let _ = env_logger::try_init();
+
+    let mut dom =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+    let root = dom.root_ref();
+
+    let broken = SECOND.replace(r#"<string name="Name">Extra</string>"#, "<string>");
+
+    rbx_xml::decode_into(&mut dom, root, broken.as_bytes(), DecodeOptions::new()).unwrap_err();
+    assert_eq!(dom.root().children().len(), 1);
+    assert_eq!(dom.len(), 2);
+}
+
+#[test]
+fn unresolved_refs_can_be_an_error() {
+    let _ = env_logger::try_init();
+
+    let mut dom =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+    let root = dom.root_ref();
+
+    let options = DecodeOptions::new().unresolved_ref_behavior(UnresolvedRefBehavior::Error);
+    let error = rbx_xml::decode_into(&mut dom, root, SECOND.as_bytes(), options).unwrap_err();
+
+    assert_eq!(error.kind(), DecodeErrorKind::UnresolvedRef);
+    assert_eq!(error.line(), 21);
+    assert!(
+        error
+            .to_string()
+            .contains("ObjectValue.Value refers to 'RBX9'"),
+        "{}",
+        error
+    );
+
+    // Nothing from the document is kept.
+    assert_eq!(dom.root().children().len(), 1);
+    assert_eq!(dom.len(), 2);
+
+    // Refs between the document's own Items are still fine.
+    let options = DecodeOptions::new().unresolved_ref_behavior(UnresolvedRefBehavior::Error);
+    rbx_xml::decode_into(&mut dom, root, FIRST.as_bytes(), options).unwrap();
+}
+
+/// The base64-encoded MD5 hash of "Hello", which is the key Studio writes it
+/// with.
+const HELLO_KEY: &str = "ixqZU8RhEpaoJ6v4xHgE1w==";
+
+fn shared_string(dom: &WeakDom, referent: Ref) -> &SharedString {
+    match dom.get_by_ref(referent).unwrap().properties.get("Data") {
+        Some(Variant::SharedString(value)) => value,
+        other => panic!("expected a SharedString, got {:?}", other),
+    }
+}
+
+#[test]
+fn shared_strings_are_reused_from_the_tree() {
+    let _ = env_logger::try_init();
+
+    let hello = SharedString::new(b"Hello".to_vec());
+    let mut dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Folder").with_property("Data", hello.clone())),
+    );
+    let root = dom.root_ref();
+
+    // This model's SharedString dictionary was left out, so its value has to
+    // come from the tree.
+    let without_dictionary = format!(
+        r#"<roblox version="4">
+            <Item class="Folder" referent="RBX0">
+                <Properties>
+                    <SharedString name="Data">{}</SharedString>
+                </Properties>
+            </Item>
+        </roblox>"#,
+        HELLO_KEY
+    );
+
+    // This one has the same string in its dictionary, which would be kept in
+    // the blob store if it weren't already in the tree.
+    let with_dictionary = format!(
+        r#"<roblox version="4">
+            <Item class="Folder" referent="RBX0">
+                <Properties>
+                    <SharedString name="Data">{0}</SharedString>
+                </Properties>
+            </Item>
+            <SharedStrings>
+                <SharedString md5="{0}">SGVsbG8=</SharedString>
+            </SharedStrings>
+        </roblox>"#,
+        HELLO_KEY
+    );
+
+    let store: Arc<dyn BlobStore> = Arc::new(TempFileStore::new().unwrap());
+    let options = || {
+        DecodeOptions::new()
+            .property_behavior(DecodePropertyBehavior::NoReflection)
+            .blob_store(Arc::clone(&store))
+            .blob_threshold(1)
+    };
+
+    let first =
+        rbx_xml::decode_into(&mut dom, root, without_dictionary.as_bytes(), options()).unwrap()[0];
+    let second =
+        rbx_xml::decode_into(&mut dom, root, with_dictionary.as_bytes(), options()).unwrap()[0];
+
+    for &referent in &[first, second] {
+        let value = shared_string(&dom, referent);
+        assert_eq!(value.hash(), hello.hash());
+        assert!(value.blob().is_none());
+        assert_eq!(value.data().as_ptr(), hello.data().as_ptr());
+    }
+
+    // Without anything in the tree to reuse, the value is kept in the store.
+    let mut empty = WeakDom::new(InstanceBuilder::new("DataModel"));
+    let empty_root = empty.root_ref();
+    let decoded = rbx_xml::decode_into(
+        &mut empty,
+        empty_root,
+        with_dictionary.as_bytes(),
+        options(),
+    )
+    .unwrap()[0];
+    assert!(shared_string(&empty, decoded).blob().is_some());
+}
@@ -0,0 +1,96 @@
+use rbx_dom_weak::types::{Variant, Vector3};
+use rbx_xml::{DecodeErrorKind, DecodeOptions, EncodeOptions};
+
+/// A model saved when Roblox still wrote version 3 documents.
+static VERSION_3: &str = include_str!("../test-files/version-3.rbxmx");
+
+/// The same model, claiming to be from a version of the format that doesn't
+/// exist yet.
+fn version_9() -> String {
+    VERSION_3.replace(r#"version="3""#, r#"version="9""#)
+}
+
+#[test]
+fn version_3_is_decoded() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(VERSION_3.as_bytes(), DecodeOptions::new()).unwrap();
+
+    assert_eq!(summary.version(), 3);
+    assert!(summary.diagnostics().is_empty());
+
+    let model = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(model.name, "Tower");
+
+    let part = dom.get_by_ref(model.children()[0]).unwrap();
+    assert_eq!(part.name, "Base");
+    assert_eq!(
+        part.properties.get("Size"),
+        Some(&Variant::Vector3(Vector3::new(4.0, 1.2, 2.0)))
+    );
+
+    assert_eq!(rbx_xml::peek(VERSION_3.as_bytes()).unwrap().version(), 3);
+}
+
+#[test]
+fn version_is_kept_through_a_round_trip() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(VERSION_3.as_bytes(), DecodeOptions::new()).unwrap();
+
+    let mut output = Vec::new();
+    let options = EncodeOptions::new().document_version(summary.version());
+    rbx_xml::to_writer(&mut output, &dom, dom.root().children(), options).unwrap();
+    assert!(String::from_utf8_lossy(&output).contains(r#"version="3""#));
+
+    let (_, summary) =
+        rbx_xml::from_reader_with_summary(output.as_slice(), DecodeOptions::new()).unwrap();
+    assert_eq!(summary.version(), 3);
+
+    let mut output = Vec::new();
+    rbx_xml::to_writer(
+        &mut output,
+        &dom,
+        dom.root().children(),
+        EncodeOptions::new(),
+    )
+    .unwrap();
+    assert!(String::from_utf8_lossy(&output).contains(r#"version="4""#));
+}
+
+#[test]
+fn newer_version_is_an_error() {
+    let _ = env_logger::try_init();
+
+    let error = rbx_xml::from_reader(version_9().as_bytes(), DecodeOptions::new()).unwrap_err();
+
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedVersion);
+    assert_eq!(error.document_version(), Some(9));
+    assert!(error.to_string().contains("version 9"));
+
+    // Peeking still works, so that callers can check before decoding.
+    assert_eq!(rbx_xml::peek(version_9().as_bytes()).unwrap().version(), 9);
+}
+
+#[test]
+fn newer_version_can_be_accepted() {
+    let _ = env_logger::try_init();
+
+    let options = DecodeOptions::new().accept_newer_versions(true);
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(version_9().as_bytes(), options).unwrap();
+
+    assert_eq!(summary.version(), 9);
+    assert!(!summary.recovered());
+    assert_eq!(dom.descendants(dom.root_ref()).count(), 3);
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].kind(), DecodeErrorKind::UnsupportedVersion);
+    assert_eq!(
+        diagnostics[0].message(),
+        "The document is version 9, and was read as if it were version 4"
+    );
+}
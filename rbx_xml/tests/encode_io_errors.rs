@@ -0,0 +1,121 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use rbx_xml::{EncodeOptions, SkipReason};
+
+/// A writer that accepts a set number of bytes, then fails like a closed pipe.
+struct FailingWriter {
+    remaining: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"));
+        }
+
+        let len = self.remaining.min(buffer.len());
+        self.remaining -= len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn place() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(
+                InstanceBuilder::new("Workspace")
+                    .with_child(InstanceBuilder::new("Part").with_name("First")),
+            )
+            .with_child(
+                InstanceBuilder::new("ReplicatedStorage").with_child(
+                    InstanceBuilder::new("Model")
+                        .with_name("Second")
+                        .with_child(InstanceBuilder::new("Part").with_name("Third")),
+                ),
+            ),
+    )
+}
+
+fn encode_all(dom: &WeakDom) -> String {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, dom, dom.root().children()).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[test]
+fn reports_progress() {
+    let _ = env_logger::try_init();
+
+    let dom = place();
+    let offset = encode_all(&dom)
+        .find(r#"<string name="Name">Third</string>"#)
+        .unwrap();
+
+    let error = rbx_xml::to_writer_default(
+        FailingWriter { remaining: offset },
+        &dom,
+        dom.root().children(),
+    )
+    .unwrap_err();
+
+    assert!(error.is_io());
+    assert_eq!(error.to_string(), "broken pipe");
+    assert_eq!(error.bytes_written(), Some(offset as u64));
+    assert_eq!(error.instances_written(), Some(4));
+    assert_eq!(
+        error.failed_instance(),
+        Some("DataModel.ReplicatedStorage.Second.Third")
+    );
+
+    let source = error.source().unwrap().downcast_ref::<io::Error>().unwrap();
+    assert_eq!(source.kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn failure_after_items() {
+    let _ = env_logger::try_init();
+
+    let dom = place();
+    let document = encode_all(&dom);
+    let offset = document.len() - "</roblox>".len();
+
+    let error = rbx_xml::to_writer_default(
+        FailingWriter { remaining: offset },
+        &dom,
+        dom.root().children(),
+    )
+    .unwrap_err();
+
+    assert_eq!(error.bytes_written(), Some(offset as u64));
+    assert_eq!(error.instances_written(), Some(5));
+    assert_eq!(error.failed_instance(), None);
+}
+
+#[test]
+fn other_errors_have_no_progress() {
+    let _ = env_logger::try_init();
+
+    let dom = place();
+    let error = rbx_xml::to_writer(
+        Vec::new(),
+        &dom,
+        dom.root().children(),
+        EncodeOptions::new()
+            .omit_default_names(true)
+            .error_on_skip(vec![SkipReason::DefaultValue]),
+    )
+    .unwrap_err();
+
+    assert!(!error.is_io());
+    assert_eq!(error.bytes_written(), None);
+    assert_eq!(error.instances_written(), None);
+    assert_eq!(error.failed_instance(), None);
+}
@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use rbx_xml::{EncodeOptions, EncodeProgressReport, ProgressAction};
+
+/// A Folder of 25 Models with 9 Parts each, for 251 instances in all.
+fn place() -> WeakDom {
+    let models = (0..25).map(|i| {
+        InstanceBuilder::new("Model")
+            .with_name(format!("Model{}", i))
+            .with_children(
+                (0..9).map(|j| InstanceBuilder::new("Part").with_name(format!("Part{}", j))),
+            )
+    });
+
+    WeakDom::new(InstanceBuilder::new("Folder").with_children(models))
+}
+
+#[test]
+fn reports_are_monotonic() {
+    let _ = env_logger::try_init();
+
+    let dom = place();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let options = EncodeOptions::new().progress(50, {
+        let reports = Arc::clone(&reports);
+        move |report: &EncodeProgressReport| {
+            reports.lock().unwrap().push(*report);
+            ProgressAction::Continue
+        }
+    });
+
+    let mut output = Vec::new();
+    let summary =
+        rbx_xml::to_writer_with_summary(&mut output, &dom, &[dom.root_ref()], options).unwrap();
+
+    let reports = reports.lock().unwrap();
+    let written: Vec<_> = reports.iter().map(|r| r.instances_written()).collect();
+    assert_eq!(written, [50, 100, 150, 200, 250]);
+    assert!(reports.iter().all(|report| report.total_instances() == 251));
+
+    for pair in reports.windows(2) {
+        assert!(pair[0].bytes_written() < pair[1].bytes_written());
+    }
+    assert!(reports.last().unwrap().bytes_written() < summary.bytes_written());
+    assert_eq!(summary.instance_count(), 251);
+}
+
+#[test]
+fn cancelling_stops_partway() {
+    let _ = env_logger::try_init();
+
+    let dom = place();
+    let options = EncodeOptions::new().progress(50, |report: &EncodeProgressReport| {
+        if report.instances_written() == 100 {
+            ProgressAction::Cancel
+        } else {
+            ProgressAction::Continue
+        }
+    });
+
+    let mut output = Vec::new();
+    let error = rbx_xml::to_writer(&mut output, &dom, &[dom.root_ref()], options).unwrap_err();
+
+    assert!(error.is_cancelled());
+    assert!(!error.is_io());
+    assert_eq!(error.instances_written(), Some(100));
+    assert_eq!(error.bytes_written(), Some(output.len() as u64));
+    assert_eq!(
+        error.to_string(),
+        "Encoding was cancelled by the progress callback"
+    );
+
+    // The output is cut off after the 100th instance's properties.
+    let text = String::from_utf8(output).unwrap();
+    assert_eq!(text.matches("<Item ").count(), 100);
+    assert!(text.trim_end().ends_with("</Properties>"));
+    assert!(!text.contains("</roblox>"));
+}
@@ -0,0 +1,48 @@
+use rbx_dom_weak::types::{Enum, Variant};
+use rbx_xml::DecodeOptions;
+
+/// A place saved while Lighting.Technology still had its `Legacy` value, 0,
+/// which Roblox has since replaced with `Compatibility`, 2.
+static LIGHTING: &str = include_str!("../test-files/lighting-legacy.rbxlx");
+
+#[test]
+fn legacy_technology_is_migrated() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(LIGHTING.as_bytes(), DecodeOptions::new()).unwrap();
+
+    let lighting_ref = dom.root().children()[0];
+    let lighting = dom.get_by_ref(lighting_ref).unwrap();
+    assert_eq!(
+        lighting.properties.get("Technology"),
+        Some(&Variant::Enum(Enum::from_u32(2)))
+    );
+
+    let migrated = summary.migrated_enum_values();
+    assert_eq!(migrated.len(), 1);
+    assert_eq!(migrated[0].instance(), lighting_ref);
+    assert_eq!(migrated[0].property_name(), "Technology");
+    assert_eq!(migrated[0].enum_name(), "Technology");
+    assert_eq!(migrated[0].original_value(), 0);
+    assert_eq!(migrated[0].migrated_value(), 2);
+    assert_eq!(
+        migrated[0].to_string(),
+        "Technology value 0 of property Technology was migrated to 2"
+    );
+}
+
+#[test]
+fn migration_can_be_turned_off() {
+    let _ = env_logger::try_init();
+
+    let options = DecodeOptions::new().migrate_enum_values(false);
+    let (dom, summary) = rbx_xml::from_reader_with_summary(LIGHTING.as_bytes(), options).unwrap();
+
+    let lighting = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(
+        lighting.properties.get("Technology"),
+        Some(&Variant::Enum(Enum::from_u32(0)))
+    );
+    assert!(summary.migrated_enum_values().is_empty());
+}
@@ -0,0 +1,159 @@
+use std::io::{self, Read};
+
+use rbx_xml::{DecodeError, DecodeErrorKind, DecodeOptions, DecodePropertyBehavior};
+
+fn decode_error(document: &str, options: DecodeOptions) -> DecodeError {
+    let _ = env_logger::try_init();
+
+    rbx_xml::from_reader(document.as_bytes(), options).unwrap_err()
+}
+
+fn document_with(properties: &str) -> String {
+    format!(
+        r#"<roblox version="4"><Item class="Part" referent="RBX0"><Properties>{}</Properties></Item></roblox>"#,
+        properties
+    )
+}
+
+/// A reader that fails partway through the document.
+struct FailingReader<'a> {
+    contents: &'a [u8],
+}
+
+impl Read for FailingReader<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.contents.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "connection reset",
+            ));
+        }
+
+        let len = self.contents.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&self.contents[..len]);
+        self.contents = &self.contents[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn io() {
+    let _ = env_logger::try_init();
+
+    let reader = FailingReader {
+        contents: br#"<roblox version="4"><Item class="Part""#,
+    };
+    let error = rbx_xml::from_reader(reader, DecodeOptions::new()).unwrap_err();
+
+    assert_eq!(error.kind(), DecodeErrorKind::Io);
+    assert!(error.is_io());
+    assert!(!error.is_malformed());
+    assert!(!error.is_unsupported());
+}
+
+#[test]
+fn malformed_document() {
+    let documents = [
+        // Not XML at all
+        "<roblox version=\"4\"><Item</roblox>",
+        // Ends early
+        "<roblox version=\"4\"><Item class=\"Part\" referent=\"RBX0\">",
+        // Missing a required attribute
+        "<roblox version=\"4\"><Item referent=\"RBX0\"><Properties/></Item></roblox>",
+        // An element that doesn't belong
+        "<roblox version=\"4\"><Unexpected/></roblox>",
+    ];
+
+    for document in &documents {
+        let error = decode_error(document, DecodeOptions::new());
+
+        assert_eq!(
+            error.kind(),
+            DecodeErrorKind::MalformedDocument,
+            "{}",
+            document
+        );
+        assert!(error.is_malformed());
+        assert!(!error.is_io());
+        assert!(!error.is_unsupported());
+    }
+}
+
+/// XML documents have a declared encoding, so string values that aren't valid
+/// UTF-8 are always an error, unlike in rbx_binary.
+#[test]
+fn invalid_utf8() {
+    let _ = env_logger::try_init();
+
+    let mut document = document_with(r#"<string name="Name">Brick!</string>"#).into_bytes();
+    let index = document.iter().position(|&byte| byte == b'!').unwrap();
+    document[index] = 0xff;
+
+    let error = rbx_xml::from_reader(document.as_slice(), DecodeOptions::new()).unwrap_err();
+
+    assert!(error.is_malformed(), "{:?}", error);
+}
+
+#[test]
+fn malformed_value() {
+    let properties = [
+        r#"<float name="Transparency">half</float>"#,
+        r#"<int name="SomeNumber">1.5</int>"#,
+        r#"<BinaryString name="Tags">not base64!</BinaryString>"#,
+        r#"<bool name="Anchored">maybe</bool>"#,
+    ];
+
+    for property in &properties {
+        let error = decode_error(&document_with(property), DecodeOptions::new());
+
+        assert_eq!(
+            error.kind(),
+            DecodeErrorKind::MalformedValue,
+            "{}",
+            property
+        );
+        assert!(error.is_malformed());
+        assert!(!error.is_unsupported());
+    }
+}
+
+#[test]
+fn unsupported_version() {
+    let error = decode_error(r#"<roblox version="2"></roblox>"#, DecodeOptions::new());
+
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedVersion);
+    assert!(error.is_unsupported());
+    assert!(!error.is_malformed());
+
+    // Display output is the same as it's always been.
+    assert_eq!(
+        error.to_string(),
+        "line 1, column 19: Invalid version '2', expected version 4"
+    );
+}
+
+#[test]
+fn unsupported_type() {
+    let error = decode_error(
+        &document_with(r#"<Hologram name="Projection">3</Hologram>"#),
+        DecodeOptions::new(),
+    );
+
+    assert_eq!(error.kind(), DecodeErrorKind::UnsupportedType);
+    assert!(error.is_unsupported());
+}
+
+#[test]
+fn unknown_property() {
+    let document = document_with(r#"<bool name="DefinitelyNotAProperty">true</bool>"#);
+
+    // Unknown properties are only errors when asked for.
+    assert!(rbx_xml::from_reader(document.as_bytes(), DecodeOptions::new()).is_ok());
+
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ErrorOnUnknown);
+    let error = decode_error(&document, options);
+
+    assert_eq!(error.kind(), DecodeErrorKind::UnknownProperty);
+    assert!(error.is_unsupported());
+    assert!(!error.is_malformed());
+}
@@ -0,0 +1,142 @@
+use rbx_dom_weak::types::Variant;
+use rbx_xml::DecodeOptions;
+
+/// A model as written by exporters that put an extra `</Properties>` after
+/// instances with no properties.
+static BROKEN_EXPORT: &str = r#"
+<roblox version="4">
+    <Item class="Model" referent="RBX0">
+        <Properties>
+            <string name="Name">Tree</string>
+        </Properties>
+        <Item class="Folder" referent="RBX1">
+            <Properties/>
+            </Properties>
+            <Item class="StringValue" referent="RBX2">
+                <Properties>
+                    <string name="Value">leaf</string>
+                </Properties>
+            </Item>
+        </Item>
+        <Item class="Folder" referent="RBX3">
+            <Properties/></Properties>
+        </Item>
+    </Item>
+</roblox>
+"#;
+
+fn recover_options() -> DecodeOptions {
+    DecodeOptions::new().recover(true)
+}
+
+#[test]
+fn strict_names_the_pattern() {
+    let _ = env_logger::try_init();
+
+    let error = rbx_xml::from_str_default(BROKEN_EXPORT).unwrap_err();
+    let message = error.to_string();
+
+    assert!(message.contains("extra </Properties> tag"), "{}", message);
+    assert!(message.contains("third-party exporters"), "{}", message);
+    assert_eq!(error.line(), 8);
+}
+
+#[test]
+fn recover_ignores_extra_tags() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(BROKEN_EXPORT.as_bytes(), recover_options()).unwrap();
+
+    assert_eq!(summary.instance_count(), 4);
+    assert_eq!(summary.diagnostics().len(), 2);
+    assert!(summary.diagnostics()[0]
+        .message()
+        .contains("extra </Properties> tag"));
+
+    // Nothing had to be thrown away.
+    assert!(!summary.recovered());
+
+    let model = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    assert_eq!(model.name, "Tree");
+    assert_eq!(model.children().len(), 2);
+
+    let folder = dom.get_by_ref(model.children()[0]).unwrap();
+    assert_eq!(folder.class, "Folder");
+
+    let value = dom.get_by_ref(folder.children()[0]).unwrap();
+    assert_eq!(value.properties.get("Value"), Some(&Variant::from("leaf")));
+}
+
+/// Script source that happens to contain the pattern has to be left alone.
+#[test]
+fn cdata_is_untouched() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Script" referent="RBX0">
+                <Properties>
+                    <ProtectedString name="Source"><![CDATA[print("<Properties/></Properties>")]]></ProtectedString>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    for options in &[DecodeOptions::new(), recover_options()] {
+        let (dom, summary) =
+            rbx_xml::from_reader_with_summary(document.as_bytes(), options.clone()).unwrap();
+        assert!(summary.diagnostics().is_empty());
+
+        let script = dom.get_by_ref(dom.root().children()[0]).unwrap();
+        assert_eq!(
+            script.properties.get("Source"),
+            Some(&Variant::from(r#"print("<Properties/></Properties>")"#))
+        );
+    }
+}
+
+/// Comments and processing instructions aren't part of the document's elements
+/// either, so a commented-out copy of the pattern is left alone too.
+#[test]
+fn comments_are_untouched() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <?exporter <Properties/></Properties>?>
+            <Item class="Folder" referent="RBX0">
+                <Properties/>
+                <!-- <Properties/></Properties> -->
+            </Item>
+        </roblox>
+    "#;
+
+    for options in &[DecodeOptions::new(), recover_options()] {
+        let (dom, summary) =
+            rbx_xml::from_reader_with_summary(document.as_bytes(), options.clone()).unwrap();
+        assert!(summary.diagnostics().is_empty());
+
+        let folder = dom.get_by_ref(dom.root().children()[0]).unwrap();
+        assert_eq!(folder.class, "Folder");
+    }
+}
+
+/// Other mismatched end tags are still malformed XML, and nothing can be done
+/// about them.
+#[test]
+fn other_extra_tags_still_fail() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Folder" referent="RBX0">
+                <Properties/>
+            </Item>
+            </Item>
+        </roblox>
+    "#;
+
+    assert!(rbx_xml::from_str_default(document).is_err());
+    assert!(rbx_xml::from_str(document, recover_options()).is_err());
+}
@@ -0,0 +1,119 @@
+use std::{
+    io::{self, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use rbx_xml::EncodeOptions;
+
+enum Message {
+    /// Bytes that the writer passed along when it was flushed.
+    Chunk(Vec<u8>),
+
+    /// The encoder returned.
+    Finished,
+}
+
+/// A writer that holds onto everything written to it until it's flushed, like
+/// the buffered end of a pipe, then sends it over a channel.
+struct ChannelWriter {
+    buffer: Vec<u8>,
+    sender: Sender<Message>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let chunk = std::mem::take(&mut self.buffer);
+            self.sender.send(Message::Chunk(chunk)).unwrap();
+        }
+
+        Ok(())
+    }
+}
+
+fn place() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(
+                InstanceBuilder::new("Workspace")
+                    .with_child(InstanceBuilder::new("Part").with_name("First")),
+            )
+            .with_child(
+                InstanceBuilder::new("ReplicatedStorage")
+                    .with_child(InstanceBuilder::new("Model").with_name("Second")),
+            ),
+    )
+}
+
+/// Encodes the place on another thread, which sends `Finished` once the
+/// encoder returns and then flushes whatever the encoder didn't.
+fn encode_on_thread(options: EncodeOptions) -> Receiver<Message> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let dom = place();
+        let mut writer = ChannelWriter {
+            buffer: Vec::new(),
+            sender: sender.clone(),
+        };
+
+        rbx_xml::to_writer(&mut writer, &dom, dom.root().children(), options).unwrap();
+        sender.send(Message::Finished).unwrap();
+        writer.flush().unwrap();
+    });
+
+    receiver
+}
+
+/// Splits the output into the chunks that arrived before the encoder
+/// returned, and everything that arrived after.
+fn collect(receiver: Receiver<Message>) -> (Vec<String>, String) {
+    let mut before = Vec::new();
+    let mut after = String::new();
+    let mut finished = false;
+
+    for message in receiver {
+        match message {
+            Message::Chunk(chunk) => {
+                let chunk = String::from_utf8(chunk).unwrap();
+
+                if finished {
+                    after.push_str(&chunk);
+                } else {
+                    before.push(chunk);
+                }
+            }
+            Message::Finished => finished = true,
+        }
+    }
+
+    (before, after)
+}
+
+#[test]
+fn flush_per_item() {
+    let _ = env_logger::try_init();
+
+    let (before, after) = collect(encode_on_thread(EncodeOptions::new().flush_per_item(true)));
+
+    // One chunk for each top-level instance, and one for the end of the
+    // document.
+    assert_eq!(before.len(), 3);
+    assert!(before[0].contains("First"));
+    assert!(!before[0].contains("Second"));
+    assert!(before[1].contains("Second"));
+    assert_eq!(before[2].trim(), "</roblox>");
+    assert_eq!(after, "");
+
+    let (unflushed_before, unflushed) = collect(encode_on_thread(EncodeOptions::new()));
+
+    assert!(unflushed_before.is_empty());
+    assert_eq!(before.concat(), unflushed);
+}
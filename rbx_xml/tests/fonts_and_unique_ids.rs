@@ -0,0 +1,134 @@
+use rbx_dom_weak::{
+    types::{Font, FontStyle, FontWeight, Ref, UniqueId, Variant},
+    WeakDom,
+};
+use rbx_test_util::assert_dom_eq;
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, EncodeOptions, EncodePropertyBehavior};
+
+/// A ScreenGui saved by Studio, with text objects using custom fonts and a
+/// UniqueId on every instance.
+static GUI: &str = include_str!("../test-files/custom-fonts.rbxmx");
+
+fn decode(document: &[u8]) -> WeakDom {
+    // Neither FontFace nor UniqueId are in rbx_xml's reflection database yet.
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    rbx_xml::from_reader(document, options).unwrap()
+}
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> Vec<u8> {
+    let options = options.property_behavior(EncodePropertyBehavior::WriteUnknown);
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    buffer
+}
+
+fn find(dom: &WeakDom, name: &str) -> Ref {
+    dom.descendants(dom.root_ref())
+        .find(|instance| instance.name == name)
+        .unwrap_or_else(|| panic!("no instance named {}", name))
+        .referent()
+}
+
+fn property<'a>(dom: &'a WeakDom, name: &str, property: &str) -> &'a Variant {
+    &dom.get_by_ref(find(dom, name)).unwrap().properties[property]
+}
+
+#[test]
+fn decodes_fonts() {
+    let _ = env_logger::try_init();
+
+    // Both types used to be an error even when their properties were being
+    // ignored.
+    rbx_xml::from_str_default(GUI).unwrap();
+
+    let dom = decode(GUI.as_bytes());
+
+    assert_eq!(
+        property(&dom, "Title", "FontFace"),
+        &Variant::Font(Font {
+            cached_face_id: Some("rbxasset://fonts/Arimo-BoldItalic.ttf".to_owned()),
+            ..Font::new(
+                "rbxasset://fonts/families/Arimo.json",
+                FontWeight::Bold,
+                FontStyle::Italic
+            )
+        })
+    );
+
+    assert_eq!(
+        property(&dom, "Play", "FontFace"),
+        &Variant::Font(Font::new(
+            "rbxassetid://12187365364",
+            FontWeight::Other(450),
+            FontStyle::Normal
+        ))
+    );
+
+    assert_eq!(
+        property(&dom, "Search", "FontFace"),
+        &Variant::Font(Font {
+            cached_face_id: Some(String::new()),
+            ..Font::new(
+                " rbxasset://fonts/families/Source Sans & Serif.json ",
+                FontWeight::Thin,
+                FontStyle::Italic
+            )
+        })
+    );
+}
+
+#[test]
+fn round_trips_gui() {
+    let _ = env_logger::try_init();
+
+    let dom = decode(GUI.as_bytes());
+    let encoded = encode(&dom, EncodeOptions::new());
+    let round_tripped = decode(&encoded);
+
+    assert_dom_eq!(round_tripped, dom);
+
+    let document = String::from_utf8(encoded).unwrap();
+    assert!(document
+        .contains(r#"<UniqueId name="UniqueId">44b188dace632b4702e9c68d004815fc</UniqueId>"#));
+    assert!(document
+        .contains(r#"<UniqueId name="UniqueId">00000000000000000000000000000000</UniqueId>"#));
+}
+
+#[test]
+fn regenerates_unique_ids() {
+    let _ = env_logger::try_init();
+
+    let dom = decode(GUI.as_bytes());
+    let copy = decode(&encode(
+        &dom,
+        EncodeOptions::new().regenerate_unique_ids(true),
+    ));
+
+    let unique_ids = |dom: &WeakDom| -> Vec<UniqueId> {
+        ["Menu", "Title", "Play", "Search"]
+            .iter()
+            .map(|name| match property(dom, name, "UniqueId") {
+                Variant::UniqueId(id) => *id,
+                other => panic!("expected a UniqueId, got {:?}", other),
+            })
+            .collect()
+    };
+
+    let original = unique_ids(&dom);
+    let regenerated = unique_ids(&copy);
+
+    for id in &regenerated {
+        assert!(!original.contains(id));
+    }
+
+    let mut distinct = regenerated.clone();
+    distinct.sort_by_key(|id| id.to_bytes());
+    distinct.dedup();
+    assert_eq!(distinct.len(), regenerated.len());
+
+    // Everything else is written the same way.
+    assert_eq!(
+        property(&copy, "Title", "FontFace"),
+        property(&dom, "Title", "FontFace")
+    );
+}
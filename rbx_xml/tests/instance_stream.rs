@@ -0,0 +1,178 @@
+use rbx_dom_weak::types::{Ref, SharedString, Variant};
+use rbx_reflection::ClassFilter;
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, StreamEvent, StreamedInstance};
+
+static PLACE: &str = include_str!("../test-files/extraction-place.rbxlx");
+
+fn events(document: &str, options: DecodeOptions) -> Vec<StreamEvent> {
+    rbx_xml::decode_instances_iter(document.as_bytes(), options)
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn instances(document: &str, options: DecodeOptions) -> Vec<StreamedInstance> {
+    events(document, options)
+        .into_iter()
+        .filter_map(|event| match event {
+            StreamEvent::Instance(instance) => Some(instance),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn parents() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Model" referent="RBX1">
+                <Properties>
+                    <string name="Name">House</string>
+                </Properties>
+                <Item class="Part" referent="RBX2">
+                    <Properties>
+                        <string name="Name">Floor</string>
+                    </Properties>
+                </Item>
+                <Item class="Folder">
+                    <Item class="Part" referent="RBX3" />
+                </Item>
+            </Item>
+            <Item class="Folder" referent="RBX4" />
+        </roblox>
+    "#;
+
+    let instances = instances(document, DecodeOptions::new());
+    let summary: Vec<_> = instances
+        .iter()
+        .map(|instance| {
+            (
+                instance.class(),
+                instance.name(),
+                instance.referent(),
+                instance.parent(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        summary,
+        [
+            ("Model", "House", Some("RBX1"), None),
+            ("Part", "Floor", Some("RBX2"), Some("RBX1")),
+            ("Folder", "Folder", None, Some("RBX1")),
+            ("Part", "Part", Some("RBX3"), None),
+            ("Folder", "Folder", Some("RBX4"), None),
+        ]
+    );
+}
+
+#[test]
+fn raw_refs_and_shared_strings() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Weld" referent="RBX1">
+                <Properties>
+                    <Ref name="Part0">RBX2</Ref>
+                    <Ref name="Part1">null</Ref>
+                </Properties>
+            </Item>
+            <Item class="MeshPart" referent="RBX2">
+                <Properties>
+                    <SharedString name="PhysicalConfigData">aGVsbG8=</SharedString>
+                </Properties>
+            </Item>
+            <SharedStrings>
+                <SharedString md5="aGVsbG8=">d29ybGQ=</SharedString>
+            </SharedStrings>
+        </roblox>
+    "#;
+
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    let events = events(document, options);
+    assert_eq!(events.len(), 3);
+
+    let weld = match &events[0] {
+        StreamEvent::Instance(instance) => instance,
+        other => panic!("expected an instance, got {:?}", other),
+    };
+    assert_eq!(weld.refs(), [("Part0".to_owned(), "RBX2".to_owned())]);
+    assert!(weld.properties().get("Part0").is_none());
+    assert_eq!(
+        weld.properties().get("Part1"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+
+    let mesh = match &events[1] {
+        StreamEvent::Instance(instance) => instance,
+        other => panic!("expected an instance, got {:?}", other),
+    };
+    assert_eq!(
+        mesh.shared_strings(),
+        [("PhysicalConfigData".to_owned(), "aGVsbG8=".to_owned())]
+    );
+    assert!(mesh.properties().is_empty());
+
+    assert_eq!(
+        events[2],
+        StreamEvent::SharedString {
+            hash: "aGVsbG8=".to_owned(),
+            value: SharedString::new(b"world".to_vec()),
+        }
+    );
+}
+
+#[test]
+fn script_sources() {
+    let _ = env_logger::try_init();
+
+    let options = || DecodeOptions::new().class_filter(ClassFilter::scripts());
+
+    // Streaming a place should find the same scripts as decoding all of it.
+    let dom = rbx_xml::from_str(PLACE, options()).unwrap();
+    let expected: Vec<_> = dom
+        .descendants(dom.root_ref())
+        .skip(1)
+        .map(|instance| {
+            (
+                instance.name.clone(),
+                instance.properties.get("Source").cloned(),
+            )
+        })
+        .collect();
+
+    let streamed: Vec<_> = instances(PLACE, options())
+        .into_iter()
+        .map(|instance| {
+            let name = instance.name().to_owned();
+            (name, instance.into_properties().shift_remove("Source"))
+        })
+        .collect();
+
+    assert_eq!(streamed.len(), 5);
+    assert_eq!(streamed, expected);
+}
+
+#[test]
+fn stops_after_error() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Folder" />
+            <Item>
+            </Item>
+            <Item class="Folder" />
+        </roblox>
+    "#;
+
+    let mut stream = rbx_xml::decode_instances_iter(document.as_bytes(), DecodeOptions::new());
+
+    assert!(matches!(stream.next(), Some(Ok(StreamEvent::Instance(_)))));
+    assert!(matches!(stream.next(), Some(Err(_))));
+    assert!(stream.next().is_none());
+    assert!(stream.bytes_read() > 0);
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, Layer},
+    prelude::*,
+    registry::LookupSpan,
+};
+
+use rbx_xml::{from_reader, to_writer_default, DecodeOptions};
+
+/// Records the name of every span and event message along with the names of
+/// the spans that were open around it.
+#[derive(Clone, Default)]
+struct Recorder {
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl Recorder {
+    fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().clone()
+    }
+}
+
+fn scope_path<S>(span: Option<tracing_subscriber::registry::SpanRef<'_, S>>) -> Vec<&'static str>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    match span {
+        Some(span) => span.scope().from_root().map(|span| span.name()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Pulls the message out of an event.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let path = scope_path(ctx.span(id));
+        let line = path.join(" > ");
+
+        let mut lines = self.lines.lock().unwrap();
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let path = scope_path(ctx.event_span(event));
+        let line = format!("event: {} > {}", path.join(" > "), visitor.message);
+
+        let mut lines = self.lines.lock().unwrap();
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+}
+
+fn record<F: FnOnce()>(body: F) -> Vec<String> {
+    let recorder = Recorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+    tracing::subscriber::with_default(subscriber, body);
+
+    recorder.lines()
+}
+
+fn document() -> Vec<u8> {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "Hello")),
+    );
+
+    let mut document = Vec::new();
+    to_writer_default(&mut document, &tree, &[tree.root_ref()]).unwrap();
+    document
+}
+
+#[test]
+fn decode_spans() {
+    let document = document();
+
+    let lines = record(|| {
+        from_reader(document.as_slice(), DecodeOptions::new()).unwrap();
+    });
+
+    let expected = [
+        "decode",
+        "decode > read_items",
+        "decode > read_items > read_properties",
+        "decode > resolve_referents",
+        "decode > resolve_shared_strings",
+    ];
+    let spans: Vec<_> = lines
+        .iter()
+        .filter(|line| !line.starts_with("event"))
+        .collect();
+    assert_eq!(spans, expected);
+
+    assert!(lines
+        .iter()
+        .any(|line| line == "event: decode > decoded document"));
+}
+
+#[test]
+fn encode_spans() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder"));
+
+    let lines = record(|| {
+        to_writer_default(Vec::new(), &tree, &[tree.root_ref()]).unwrap();
+    });
+
+    let expected = [
+        "encode",
+        "encode > order_instances",
+        "encode > write_items",
+        "encode > write_shared_strings",
+    ];
+    let spans: Vec<_> = lines
+        .iter()
+        .filter(|line| !line.starts_with("event"))
+        .collect();
+    assert_eq!(spans, expected);
+
+    assert!(lines
+        .iter()
+        .any(|line| line == "event: encode > encoded document"));
+}
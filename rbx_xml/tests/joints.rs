@@ -0,0 +1,99 @@
+use rbx_dom_weak::{
+    builders::joints,
+    types::{CFrame, Matrix3, Ref, Variant, Vector3},
+    InstanceBuilder, WeakDom,
+};
+
+/// A rotation of 90 degrees around the Y axis.
+fn quarter_turn() -> Matrix3 {
+    Matrix3::new(
+        Vector3::new(0.0, 0.0, 1.0),
+        Vector3::new(0.0, 1.0, 0.0),
+        Vector3::new(-1.0, 0.0, 0.0),
+    )
+}
+
+fn cframe_property(dom: &WeakDom, referent: Ref, name: &str) -> CFrame {
+    match dom.get_by_ref(referent).unwrap().properties.get(name) {
+        Some(Variant::CFrame(cframe)) => *cframe,
+        other => panic!("expected {} to be a CFrame, got {:?}", name, other),
+    }
+}
+
+fn ref_property(dom: &WeakDom, referent: Ref, name: &str) -> Ref {
+    match dom.get_by_ref(referent).unwrap().properties.get(name) {
+        Some(Variant::Ref(value)) => *value,
+        other => panic!("expected {} to be a Ref, got {:?}", name, other),
+    }
+}
+
+fn find_child(dom: &WeakDom, parent: Ref, class: &str) -> Ref {
+    dom.get_by_ref(parent)
+        .unwrap()
+        .children()
+        .iter()
+        .copied()
+        .find(|child| dom.get_by_ref(*child).unwrap().class == class)
+        .unwrap_or_else(|| panic!("no child with class {}", class))
+}
+
+#[test]
+fn two_part_rig_round_trip() {
+    let _ = env_logger::try_init();
+
+    let torso = InstanceBuilder::new("Part")
+        .with_name("Torso")
+        .with_property(
+            "CFrame",
+            CFrame::new(Vector3::new(0.0, 3.0, 0.0), quarter_turn()),
+        );
+    let arm = InstanceBuilder::new("Part").with_name("Arm");
+    let torso_ref = torso.referent();
+    let arm_ref = arm.referent();
+
+    let mut dom = WeakDom::new(InstanceBuilder::new("Model").with_children(vec![torso, arm]));
+
+    let shoulder = CFrame::new(Vector3::new(1.5, 0.5, 0.0), quarter_turn());
+    let arm_top = CFrame::new(Vector3::new(-0.5, 1.0, 0.0), quarter_turn());
+
+    joints::motor6d(&mut dom, torso_ref, arm_ref, shoulder, arm_top);
+    joints::weld_constraint(&mut dom, torso_ref, arm_ref);
+    joints::attachment(&mut dom, arm_ref, arm_top);
+
+    // The reflection database doesn't list WeldConstraint's parts as
+    // serialized properties, so the weld needs unknown properties kept.
+    let encode_options = rbx_xml::EncodeOptions::new()
+        .property_behavior(rbx_xml::EncodePropertyBehavior::WriteUnknown);
+    let decode_options = rbx_xml::DecodeOptions::new()
+        .property_behavior(rbx_xml::DecodePropertyBehavior::ReadUnknown);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, &dom, &[dom.root_ref()], encode_options).unwrap();
+    let decoded = rbx_xml::from_reader(buffer.as_slice(), decode_options).unwrap();
+
+    let model = decoded.root().children()[0];
+    let children = decoded.get_by_ref(model).unwrap().children();
+    let (torso, arm) = (children[0], children[1]);
+
+    let motor = find_child(&decoded, torso, "Motor6D");
+    assert_eq!(ref_property(&decoded, motor, "Part0"), torso);
+    assert_eq!(ref_property(&decoded, motor, "Part1"), arm);
+
+    let c0 = cframe_property(&decoded, motor, "C0");
+    let c1 = cframe_property(&decoded, motor, "C1");
+    assert_eq!(c0, shoulder);
+    assert_eq!(c1, arm_top);
+
+    // The parts are still related by the joint after being decoded.
+    let torso_cframe = cframe_property(&decoded, torso, "CFrame");
+    let arm_cframe = cframe_property(&decoded, arm, "CFrame");
+    assert_eq!(torso_cframe * c0, arm_cframe * c1);
+    assert_eq!(arm_cframe, cframe_property(&dom, arm_ref, "CFrame"));
+
+    let weld = find_child(&decoded, torso, "WeldConstraint");
+    assert_eq!(ref_property(&decoded, weld, "Part0"), torso);
+    assert_eq!(ref_property(&decoded, weld, "Part1"), arm);
+
+    let attachment = find_child(&decoded, arm, "Attachment");
+    assert_eq!(cframe_property(&decoded, attachment, "CFrame"), arm_top);
+}
@@ -0,0 +1,108 @@
+use rbx_dom_weak::{
+    types::{Color3, Color3uint8, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+/// A Part as saved by Roblox Studio in 2013, before parts had a Color3 color.
+static PART_2013: &str = r#"
+<roblox xmlns:xmime="http://www.w3.org/2005/05/xmlmime" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:noNamespaceSchemaLocation="http://www.roblox.com/roblox.xsd" version="4">
+    <External>null</External>
+    <External>nil</External>
+    <Item class="Part" referent="RBX0">
+        <Properties>
+            <bool name="Anchored">true</bool>
+            <int name="BrickColor">21</int>
+            <bool name="CanCollide">true</bool>
+            <string name="Name">Part</string>
+            <float name="Transparency">0</float>
+        </Properties>
+    </Item>
+</roblox>
+"#;
+
+fn decode_color(document: &str) -> Option<Variant> {
+    let tree = rbx_xml::from_str_default(document).unwrap();
+    let part = tree.get_by_ref(tree.root().children()[0]).unwrap();
+
+    assert!(!part.properties.contains_key("BrickColor"));
+    part.properties.get("Color").cloned()
+}
+
+#[test]
+fn brick_color_becomes_color() {
+    let _ = env_logger::try_init();
+
+    // BrickColor 21 is Bright red.
+    assert_eq!(
+        decode_color(PART_2013),
+        Some(Variant::Color3(Color3uint8::new(196, 40, 28).into()))
+    );
+}
+
+#[test]
+fn unknown_brick_color_is_medium_stone_grey() {
+    let _ = env_logger::try_init();
+
+    let document = PART_2013.replace(
+        r#"<int name="BrickColor">21</int>"#,
+        r#"<int name="BrickColor">9999</int>"#,
+    );
+
+    assert_eq!(
+        decode_color(&document),
+        Some(Variant::Color3(Color3uint8::new(163, 162, 165).into()))
+    );
+}
+
+/// Files written during the transition contain both properties. The newer one
+/// should win regardless of the order they're written in.
+#[test]
+fn color3uint8_wins_over_brick_color() {
+    let _ = env_logger::try_init();
+
+    let color = r#"<Color3uint8 name="Color3uint8">4278190335</Color3uint8>"#;
+    let expected = Some(Variant::Color3(Color3uint8::new(0, 0, 255).into()));
+
+    let before = PART_2013.replace(
+        r#"<int name="BrickColor">21</int>"#,
+        &format!(r#"{}<int name="BrickColor">21</int>"#, color),
+    );
+    assert_eq!(decode_color(&before), expected);
+
+    let after = PART_2013.replace(
+        r#"<int name="BrickColor">21</int>"#,
+        &format!(r#"<int name="BrickColor">21</int>{}"#, color),
+    );
+    assert_eq!(decode_color(&after), expected);
+}
+
+#[test]
+fn legacy_brick_colors_on_encode() {
+    let _ = env_logger::try_init();
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+        InstanceBuilder::new("Part").with_property("Color", Color3::new(0.77, 0.16, 0.11)),
+    ));
+
+    let mut buffer = Vec::new();
+    let options = rbx_xml::EncodeOptions::new().legacy_brick_colors(true);
+    rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+
+    let document = String::from_utf8(buffer).unwrap();
+    assert!(document.contains(r#"<int name="BrickColor">21</int>"#));
+    assert!(!document.contains("brickColor"));
+
+    // We should still read back the color, not the BrickColor. It's written
+    // as a Color3uint8, so it's only as exact as that.
+    let written = Color3uint8::from(Color3::new(0.77, 0.16, 0.11));
+    assert_eq!(
+        decode_color(&document),
+        Some(Variant::Color3(written.into()))
+    );
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, &tree, tree.root().children()).unwrap();
+
+    let document = String::from_utf8(buffer).unwrap();
+    assert!(!document.contains("BrickColor"));
+}
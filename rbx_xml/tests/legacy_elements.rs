@@ -0,0 +1,213 @@
+use rbx_dom_weak::WeakDom;
+use rbx_xml::{DecodeErrorKind, DecodeOptions, DecodeSummary, EncodeOptions};
+
+/// A place in the shape of ones saved before 2015, with a `<HashMap>` of
+/// physics settings and a `<Custom>` element in Workspace, and another
+/// `<HashMap>` directly inside `<roblox>`.
+static LEGACY_PLACE: &str = include_str!("../test-files/legacy-workspace.rbxlx");
+
+fn decode_kept() -> (WeakDom, DecodeSummary) {
+    rbx_xml::from_reader_with_summary(
+        LEGACY_PLACE.as_bytes(),
+        DecodeOptions::new().legacy_elements(true),
+    )
+    .unwrap()
+}
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> String {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+fn child_names(dom: &WeakDom, parent: rbx_dom_weak::types::Ref) -> Vec<&str> {
+    dom.get_by_ref(parent)
+        .unwrap()
+        .children()
+        .iter()
+        .map(|child| dom.get_by_ref(*child).unwrap().name.as_str())
+        .collect()
+}
+
+#[test]
+fn strict_names_the_element() {
+    let _ = env_logger::try_init();
+
+    let error = rbx_xml::from_str_default(LEGACY_PLACE).unwrap_err();
+    let message = error.to_string();
+
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedDocument);
+    assert!(message.contains("<HashMap>"), "{}", message);
+    assert!(
+        message.contains("DecodeOptions::legacy_elements"),
+        "{}",
+        message
+    );
+}
+
+/// With recovery enabled, an Item holding one of these elements is dropped
+/// like any other Item that can't be read.
+#[test]
+fn recover_drops_items() {
+    let _ = env_logger::try_init();
+
+    let document = r#"<roblox version="4">
+        <Item class="Folder" referent="RBX0">
+            <Properties>
+                <string name="Name">Legacy</string>
+            </Properties>
+            <HashMap name="PhysicsSettings"></HashMap>
+        </Item>
+        <Item class="Folder" referent="RBX1">
+            <Properties>
+                <string name="Name">Intact</string>
+            </Properties>
+        </Item>
+    </roblox>"#;
+
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(document.as_bytes(), DecodeOptions::new().recover(true))
+            .unwrap();
+
+    assert!(summary.recovered());
+    assert_eq!(child_names(&dom, dom.root_ref()), ["Intact"]);
+    assert!(summary.diagnostics()[0].message().contains("<HashMap>"));
+    assert!(summary.legacy_elements().is_empty());
+}
+
+#[test]
+fn elements_are_kept() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = decode_kept();
+
+    let workspace = dom.root().children()[0];
+    assert_eq!(child_names(&dom, workspace), ["Camera", "Baseplate"]);
+    assert!(summary.diagnostics().is_empty());
+
+    let elements = summary.legacy_elements();
+    assert_eq!(elements.len(), 3);
+
+    assert_eq!(elements[0].name(), "HashMap");
+    assert_eq!(elements[0].parent(), Some(workspace));
+    assert_eq!(elements[0].index(), 1);
+    assert_eq!(
+        elements[0].xml(),
+        concat!(
+            r#"<HashMap name="PhysicsSettings">"#,
+            r#"<bool name="AllowSleep">true</bool>"#,
+            r#"<bool name="AreAnchorsShown">false</bool>"#,
+            r#"<float name="ThrottleAdjustTime">0</float>"#,
+            r#"<token name="PhysicsEnvironmentalThrottle">0</token>"#,
+            r#"</HashMap>"#,
+        )
+    );
+
+    assert_eq!(elements[1].name(), "Custom");
+    assert_eq!(elements[1].parent(), Some(workspace));
+    assert_eq!(elements[1].index(), 2);
+    assert_eq!(
+        elements[1].xml(),
+        r#"<Custom name="TerrainOptions"><![CDATA[  water & grass  ]]></Custom>"#
+    );
+
+    assert_eq!(elements[2].name(), "HashMap");
+    assert_eq!(elements[2].parent(), None);
+    assert_eq!(elements[2].index(), 2);
+}
+
+#[test]
+fn elements_are_written_back() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = decode_kept();
+    let options = EncodeOptions::new().legacy_elements(summary.legacy_elements().to_vec());
+    let document = encode(&dom, options);
+
+    // Each element ends up in the same spot among its parent's children.
+    let position = |needle: &str| {
+        document
+            .find(needle)
+            .unwrap_or_else(|| panic!("{} is missing from {}", needle, document))
+    };
+    assert!(position(r#"<string name="Name">Camera</string>"#) < position("PhysicsSettings"));
+    assert!(position("PhysicsSettings") < position("Baseplate"));
+    assert!(position("Baseplate") < position("TerrainOptions"));
+    assert!(position("TerrainOptions") < position(r#"<Item class="Lighting""#));
+    assert!(position(r#"<Item class="Lighting""#) < position("NetworkSettings"));
+
+    // Decoding what we wrote gives back the same elements.
+    let (redecoded, resummary) = rbx_xml::from_reader_with_summary(
+        document.as_bytes(),
+        DecodeOptions::new().legacy_elements(true),
+    )
+    .unwrap();
+
+    let summarize = |dom: &WeakDom, summary: &DecodeSummary| {
+        summary
+            .legacy_elements()
+            .iter()
+            .map(|element| {
+                let parent = element
+                    .parent()
+                    .map(|parent| dom.get_by_ref(parent).unwrap().name.clone());
+                (parent, element.index(), element.xml().to_owned())
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(summarize(&redecoded, &resummary), summarize(&dom, &summary));
+
+    // Writing it out again doesn't change anything.
+    let options = EncodeOptions::new().legacy_elements(resummary.legacy_elements().to_vec());
+    assert_eq!(encode(&redecoded, options), document);
+}
+
+#[test]
+fn elements_are_not_written_by_default() {
+    let _ = env_logger::try_init();
+
+    let (dom, _) = decode_kept();
+    let document = encode(&dom, EncodeOptions::new());
+
+    assert!(!document.contains("HashMap"));
+    assert!(!document.contains("Custom"));
+    rbx_xml::from_str_default(&document).unwrap();
+}
+
+/// Elements can use a namespace prefix that's declared on `<roblox>`. Writing
+/// one out again used to leave the prefix undeclared, which panicked.
+#[test]
+fn elements_keep_namespaces_they_use() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:a="urn:legacy" version="4">
+            <a:Settings a:kind="physics"><a:Gravity>196.2</a:Gravity></a:Settings>
+            <Item class="Folder" referent="RBX0"><Properties/></Item>
+        </roblox>
+    "#;
+
+    let (dom, summary) = rbx_xml::from_reader_with_summary(
+        document.as_bytes(),
+        DecodeOptions::new().legacy_elements(true),
+    )
+    .unwrap();
+
+    assert_eq!(
+        summary.legacy_elements()[0].xml(),
+        r#"<a:Settings xmlns:a="urn:legacy" a:kind="physics"><a:Gravity>196.2</a:Gravity></a:Settings>"#
+    );
+
+    let options = EncodeOptions::new().legacy_elements(summary.legacy_elements().to_vec());
+    let written = encode(&dom, options);
+
+    let (_, resummary) = rbx_xml::from_reader_with_summary(
+        written.as_bytes(),
+        DecodeOptions::new().legacy_elements(true),
+    )
+    .unwrap();
+    assert_eq!(
+        resummary.legacy_elements()[0].xml(),
+        summary.legacy_elements()[0].xml()
+    );
+}
@@ -0,0 +1,78 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+static SOURCES: &[&str] = &[
+    "print(1)\r\nprint(2)\r\n",
+    "print(1)\rprint(2)\r",
+    "-- mixed\r\n\nprint(1)\r\r\nprint(2)\n\r",
+    "\r\n\tindented()\r\n",
+    "\r",
+];
+
+fn string_property(dom: &WeakDom, index: usize, property: &str) -> String {
+    let instance = dom.get_by_ref(dom.root().children()[index]).unwrap();
+
+    match instance.properties.get(property) {
+        Some(Variant::String(value)) => value.to_string(),
+        other => panic!("expected a String, got {:?}", other),
+    }
+}
+
+/// Scripts and StringValues written with CRLF, lone CR, and mixed line endings
+/// read back with the same bytes, without the document having any raw carriage
+/// returns for a parser to normalize.
+#[test]
+fn round_trip() {
+    let _ = env_logger::try_init();
+
+    let instances = SOURCES.iter().flat_map(|source| {
+        vec![
+            InstanceBuilder::new("Script").with_property("Source", *source),
+            InstanceBuilder::new("StringValue").with_property("Value", *source),
+        ]
+    });
+    let dom = WeakDom::new(InstanceBuilder::new("DataModel").with_children(instances));
+
+    let mut encoded = Vec::new();
+    rbx_xml::to_writer_default(&mut encoded, &dom, dom.root().children()).unwrap();
+    assert!(!encoded.contains(&b'\r'));
+
+    let decoded = rbx_xml::from_reader_default(encoded.as_slice()).unwrap();
+
+    for (i, source) in SOURCES.iter().enumerate() {
+        assert_eq!(string_property(&decoded, i * 2, "Source"), *source);
+        assert_eq!(string_property(&decoded, i * 2 + 1, "Value"), *source);
+    }
+}
+
+/// Documents from other tools that write carriage returns as character
+/// references, including in ProtectedString values, keep them.
+#[test]
+fn character_references() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Script" referent="RBX1">
+                <Properties>
+                    <ProtectedString name="Source">print(1)&#13;
+print(2)&#13;print(3)
+</ProtectedString>
+                </Properties>
+            </Item>
+            <Item class="StringValue" referent="RBX2">
+                <Properties>
+                    <string name="Value">a&#13;
+b&#13;</string>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let dom = rbx_xml::from_str_default(document).unwrap();
+
+    assert_eq!(
+        string_property(&dom, 0, "Source"),
+        "print(1)\r\nprint(2)\rprint(3)\n"
+    );
+    assert_eq!(string_property(&dom, 1, "Value"), "a\r\nb\r");
+}
@@ -0,0 +1,85 @@
+//! rbx_xml promises not to panic on any document, no matter how it's damaged.
+//! These tests cut short and damage a few documents in every position to back
+//! that up, so that it doesn't depend on running a fuzzer.
+
+use std::panic;
+
+use rbx_xml::{DecodeOptions, EncodeOptions, StreamEvent};
+
+static DOCUMENTS: &[(&str, &str)] = &[
+    ("sound.rbxmx", include_str!("../test-files/sound.rbxmx")),
+    (
+        "legacy-workspace.rbxlx",
+        include_str!("../test-files/legacy-workspace.rbxlx"),
+    ),
+    (
+        "corrupted-properties.rbxmx",
+        include_str!("../test-files/corrupted-properties.rbxmx"),
+    ),
+];
+
+/// Decodes the document every way that rbx_xml can, and writes out again
+/// whatever could be decoded, making sure that none of it panics.
+fn decode_every_way(document: &[u8], description: &str) {
+    let result = panic::catch_unwind(|| {
+        let _ = rbx_xml::from_reader_default(document);
+        let _ = rbx_xml::peek(document);
+
+        for event in rbx_xml::decode_instances_iter(document, DecodeOptions::new()) {
+            if let Ok(StreamEvent::Instance(_)) = event {
+                continue;
+            }
+            break;
+        }
+
+        let options = DecodeOptions::new()
+            .recover(true)
+            .recover_from_property_errors(true)
+            .legacy_elements(true);
+
+        if let Ok((dom, summary)) = rbx_xml::from_reader_with_summary(document, options) {
+            let options = EncodeOptions::new()
+                .legacy_elements(summary.legacy_elements().to_vec())
+                .processing_instructions(summary.processing_instructions().to_vec());
+
+            let _ = rbx_xml::to_writer(Vec::new(), &dom, dom.root().children(), options);
+        }
+    });
+
+    if result.is_err() {
+        panic!("decoding {} panicked", description);
+    }
+}
+
+#[test]
+fn cut_off_documents() {
+    let _ = env_logger::try_init();
+
+    for (name, document) in DOCUMENTS {
+        for len in 0..document.len() {
+            decode_every_way(
+                &document.as_bytes()[..len],
+                &format!("{} cut off after {} bytes", name, len),
+            );
+        }
+    }
+}
+
+#[test]
+fn damaged_documents() {
+    let _ = env_logger::try_init();
+
+    for (name, document) in DOCUMENTS {
+        for offset in 0..document.len() {
+            for &byte in b"<\"9" {
+                let mut damaged = document.as_bytes().to_vec();
+                damaged[offset] = byte;
+
+                decode_every_way(
+                    &damaged,
+                    &format!("{} with byte {} set to {:?}", name, offset, byte as char),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use rbx_dom_weak::{
+    types::{Color3, UDim, UDim2},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::EncodeOptions;
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> Result<Vec<u8>, rbx_xml::EncodeError> {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options)?;
+    Ok(buffer)
+}
+
+fn frame_with(name: &str, value: impl Into<rbx_dom_weak::types::Variant>) -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("ScreenGui")
+            .with_name("Gui")
+            .with_child(
+                InstanceBuilder::new("Frame")
+                    .with_name("Panel")
+                    .with_property("Size", UDim2::new(UDim::new(0.5, 0), UDim::new(0.5, 0)))
+                    .with_property(name, value),
+            ),
+    )
+}
+
+#[test]
+fn misplaced_property() {
+    let _ = env_logger::try_init();
+
+    let dom = frame_with("Text", "Hello");
+    let options = EncodeOptions::new().error_on_misplaced_properties(true);
+
+    let mut buffer = Vec::new();
+    let error = rbx_xml::to_writer(&mut buffer, &dom, dom.root().children(), options).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Instance Gui.Panel has a property named Text, which its class does not have"
+    );
+    assert!(buffer.is_empty(), "nothing should be written");
+
+    // Without the option, the property is left out like any other unknown
+    // property.
+    assert!(encode(&dom, EncodeOptions::new()).is_ok());
+}
+
+#[test]
+fn misplaced_property_suggestion() {
+    let _ = env_logger::try_init();
+
+    let dom = frame_with("backgroundcolor3", Color3::new(1.0, 1.0, 1.0));
+    let options = EncodeOptions::new().error_on_misplaced_properties(true);
+
+    assert_eq!(
+        encode(&dom, options).unwrap_err().to_string(),
+        "Instance Gui.Panel has a property named backgroundcolor3, which its class does not \
+         have; rename it to BackgroundColor3"
+    );
+}
+
+#[test]
+fn other_lints_are_allowed() {
+    let _ = env_logger::try_init();
+
+    // A UDim2 in Part.Size has the wrong type, but the property exists.
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("Part")
+                .with_property("Size", UDim2::new(UDim::new(0.5, 0), UDim::new(0.5, 0))),
+        ),
+    );
+    let options = EncodeOptions::new().error_on_misplaced_properties(true);
+
+    assert!(encode(&dom, options).is_ok());
+}
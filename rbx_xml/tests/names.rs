@@ -0,0 +1,67 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+/// Items with no Name property should be named after their ClassName, just
+/// like instances with no Name in binary files.
+#[test]
+fn missing_name_uses_class_name() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="StringValue" referent="RBX1">
+                <Properties>
+                    <string name="Value">Hello</string>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let tree = rbx_xml::from_str_default(document).unwrap();
+    let instance = tree.get_by_ref(tree.root().children()[0]).unwrap();
+
+    assert_eq!(instance.name, "StringValue");
+    assert!(!instance.properties.contains_key("Name"));
+}
+
+#[test]
+fn omit_default_names() {
+    let _ = env_logger::try_init();
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_children(vec![
+        InstanceBuilder::new("Folder"),
+        InstanceBuilder::new("Folder").with_name("Custom"),
+    ]));
+
+    let options = rbx_xml::EncodeOptions::new().omit_default_names(true);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+
+    let document = String::from_utf8(buffer).unwrap();
+    assert_eq!(document.matches(r#"name="Name""#).count(), 1);
+    assert!(document.contains(r#"<string name="Name">Custom</string>"#));
+
+    let decoded = rbx_xml::from_str_default(&document).unwrap();
+    let names: Vec<_> = decoded
+        .root()
+        .children()
+        .iter()
+        .map(|id| decoded.get_by_ref(*id).unwrap().name.as_str())
+        .collect();
+
+    assert_eq!(names, ["Folder", "Custom"]);
+}
+
+#[test]
+fn names_are_written_by_default() {
+    let _ = env_logger::try_init();
+
+    let tree =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, &tree, tree.root().children()).unwrap();
+
+    let document = String::from_utf8(buffer).unwrap();
+    assert!(document.contains(r#"<string name="Name">Folder</string>"#));
+}
@@ -0,0 +1,122 @@
+use rbx_dom_weak::{
+    types::{Color3, Color3uint8, Variant, Vector3, Vector3int16},
+    InstanceBuilder, WeakDom,
+};
+use rbx_test_util::{assert_dom_eq, encode_xml, first_child};
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, EncodeOptions};
+
+/// A Part as saved by Roblox Studio around 2010, with its size stored as a
+/// Vector3int16 and its color as a Color3uint8.
+static PART_2010: &str = include_str!("../test-files/narrow-types-2010.rbxmx");
+
+#[test]
+fn narrow_values_are_widened() {
+    let _ = env_logger::try_init();
+
+    let dom = rbx_xml::from_str_default(PART_2010).unwrap();
+    let part = first_child(&dom);
+
+    assert_eq!(
+        part.properties.get("Size"),
+        Some(&Variant::Vector3(Vector3::new(4.0, 1.0, 2.0)))
+    );
+    assert_eq!(
+        part.properties.get("Color"),
+        Some(&Variant::Color3(Color3uint8::new(196, 40, 28).into()))
+    );
+}
+
+#[test]
+fn narrow_values_are_kept_without_reflection() {
+    let _ = env_logger::try_init();
+
+    let dom = rbx_xml::from_str(
+        PART_2010,
+        DecodeOptions::new().property_behavior(DecodePropertyBehavior::NoReflection),
+    )
+    .unwrap();
+    let part = first_child(&dom);
+
+    assert_eq!(
+        part.properties.get("size"),
+        Some(&Variant::Vector3int16(Vector3int16::new(4, 1, 2)))
+    );
+    assert_eq!(
+        part.properties.get("Color3uint8"),
+        Some(&Variant::Color3uint8(Color3uint8::new(196, 40, 28)))
+    );
+}
+
+#[test]
+fn widened_values_narrow_without_loss() {
+    let _ = env_logger::try_init();
+
+    let dom = rbx_xml::from_str_default(PART_2010).unwrap();
+    let (encoded, summary) = {
+        let mut buffer = Vec::new();
+        let summary = rbx_xml::to_writer_with_summary(
+            &mut buffer,
+            &dom,
+            dom.root().children(),
+            EncodeOptions::new(),
+        )
+        .unwrap();
+
+        (String::from_utf8(buffer).unwrap(), summary)
+    };
+
+    // BasePart.Color is still serialized as a Color3uint8, so it goes back to
+    // exactly the color it was read from. rbx_xml leaves out the alpha byte
+    // that Studio puts at the top, which Roblox ignores anyway.
+    assert!(
+        encoded.contains(r#"<Color3uint8 name="Color3uint8">12855324</Color3uint8>"#),
+        "{}",
+        encoded
+    );
+    assert!(summary.clamped_properties().is_empty());
+
+    let decoded = rbx_xml::from_str_default(&encoded).unwrap();
+    assert_dom_eq!(dom, decoded);
+    assert_eq!(encode_xml(&decoded, EncodeOptions::new()), encoded);
+}
+
+#[test]
+fn narrowing_reports_clamped_values() {
+    let _ = env_logger::try_init();
+
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel").with_child(
+            InstanceBuilder::new("Part")
+                .with_name("Glowing")
+                .with_property("Color", Color3::new(1.5, 0.5, -1.0)),
+        ),
+    );
+
+    let mut buffer = Vec::new();
+    let summary = rbx_xml::to_writer_with_summary(
+        &mut buffer,
+        &dom,
+        dom.root().children(),
+        EncodeOptions::new(),
+    )
+    .unwrap();
+
+    let clamped = summary.clamped_properties();
+    assert_eq!(clamped.len(), 1);
+    assert_eq!(clamped[0].instance(), "DataModel.Glowing");
+    assert_eq!(clamped[0].property_name(), "Color");
+    assert_eq!(
+        clamped[0].original_value(),
+        &Variant::Color3(Color3::new(1.5, 0.5, -1.0))
+    );
+    assert_eq!(
+        clamped[0].written_value(),
+        &Variant::Color3uint8(Color3uint8::new(255, 128, 0))
+    );
+
+    let decoded = rbx_xml::from_reader_default(buffer.as_slice()).unwrap();
+    assert_eq!(
+        first_child(&decoded).properties.get("Color"),
+        Some(&Variant::Color3(Color3uint8::new(255, 128, 0).into()))
+    );
+}
@@ -0,0 +1,89 @@
+use rbx_dom_weak::{types::Content, InstanceBuilder, WeakDom};
+use rbx_test_util::textures;
+use rbx_xml::DecodeOptions;
+
+static URLS: &[&str] = &[
+    "http://www.roblox.com/asset/?id=1818",
+    "rbxasset://textures/face.png",
+    "https://www.roblox.com/asset/?id=262470873",
+    "rbxassetid://1818",
+    "http://www.roblox.com/asset/?id=1818",
+];
+
+fn encode_decals() -> Vec<u8> {
+    let decals = URLS
+        .iter()
+        .map(|url| InstanceBuilder::new("Decal").with_property("Texture", Content::from(*url)));
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(decals));
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, &tree, &[tree.root_ref()]).unwrap();
+    buffer
+}
+
+fn urls<'a>(contents: &[&'a Content]) -> Vec<&'a str> {
+    contents
+        .iter()
+        .map(|content| AsRef::<str>::as_ref(*content))
+        .collect()
+}
+
+#[test]
+fn off_by_default() {
+    let _ = env_logger::try_init();
+
+    let file = encode_decals();
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(file.as_slice(), DecodeOptions::new()).unwrap();
+
+    assert_eq!(urls(&textures(&dom)), URLS);
+    assert!(summary.normalized_content().is_empty());
+}
+
+#[test]
+fn asset_urls_are_normalized() {
+    let _ = env_logger::try_init();
+
+    let file = encode_decals();
+    let options = DecodeOptions::new().normalize_content(true);
+    let (dom, summary) = rbx_xml::from_reader_with_summary(file.as_slice(), options).unwrap();
+
+    assert_eq!(
+        urls(&textures(&dom)),
+        [
+            "rbxassetid://1818",
+            "rbxasset://textures/face.png",
+            "rbxassetid://262470873",
+            "rbxassetid://1818",
+            "rbxassetid://1818",
+        ]
+    );
+
+    // Each distinct URL that changed is listed once.
+    assert_eq!(
+        summary.normalized_content(),
+        [
+            (Content::from(URLS[0]), Content::from("rbxassetid://1818")),
+            (
+                Content::from(URLS[2]),
+                Content::from("rbxassetid://262470873")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn normalized_values_are_interned() {
+    let _ = env_logger::try_init();
+
+    let file = encode_decals();
+    let options = DecodeOptions::new()
+        .normalize_content(true)
+        .intern_strings(true);
+    let dom = rbx_xml::from_reader(file.as_slice(), options).unwrap();
+    let textures = textures(&dom);
+
+    assert!(textures[0].ptr_eq(textures[3]));
+    assert!(textures[0].ptr_eq(textures[4]));
+}
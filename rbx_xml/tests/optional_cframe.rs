@@ -0,0 +1,71 @@
+use rbx_dom_weak::{
+    types::{CFrame, Matrix3, Variant, Vector3},
+    WeakDom,
+};
+use rbx_xml::{DecodeOptions, DecodePropertyBehavior, EncodeOptions, EncodePropertyBehavior};
+
+/// A model saved by Roblox Studio with its pivot set, holding a second model
+/// whose pivot has never been set.
+static MODEL_PIVOT: &str = include_str!("../test-files/model-pivot.rbxmx");
+
+fn decode(document: &str) -> WeakDom {
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    rbx_xml::from_str(document, options).unwrap()
+}
+
+fn encode(dom: &WeakDom) -> String {
+    let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::WriteUnknown);
+    let mut output = Vec::new();
+    rbx_xml::to_writer(&mut output, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// The `WorldPivotData` of the outer model and the inner one.
+fn pivots(dom: &WeakDom) -> (Option<Variant>, Option<Variant>) {
+    let lamp = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    let shade = dom.get_by_ref(lamp.children()[1]).unwrap();
+
+    (
+        lamp.properties.get("WorldPivotData").cloned(),
+        shade.properties.get("WorldPivotData").cloned(),
+    )
+}
+
+#[test]
+fn decode_pivots() {
+    let _ = env_logger::try_init();
+
+    let dom = decode(MODEL_PIVOT);
+    let pivot = CFrame::new(
+        Vector3::new(12.0, 3.5, -8.0),
+        Matrix3 {
+            x: Vector3::new(0.0, 0.0, 1.0),
+            y: Vector3::new(0.0, 1.0, 0.0),
+            z: Vector3::new(-1.0, 0.0, 0.0),
+        },
+    );
+
+    assert_eq!(
+        pivots(&dom),
+        (
+            Some(Variant::OptionalCFrame(Some(pivot))),
+            Some(Variant::OptionalCFrame(None)),
+        )
+    );
+}
+
+#[test]
+fn round_trip_pivots() {
+    let _ = env_logger::try_init();
+
+    let dom = decode(MODEL_PIVOT);
+    let encoded = encode(&dom);
+
+    assert!(encoded
+        .contains(r#"<OptionalCoordinateFrame name="WorldPivotData"></OptionalCoordinateFrame>"#));
+    assert!(encoded.contains("<OptionalCoordinateFrame name=\"WorldPivotData\">\n"));
+    assert_eq!(encoded.matches("<CFrame>").count(), 1);
+
+    let decoded = decode(&encoded);
+    assert_eq!(pivots(&decoded), pivots(&dom));
+}
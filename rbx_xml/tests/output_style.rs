@@ -0,0 +1,109 @@
+use rbx_dom_weak::{
+    types::{Color3, Variant, Vector3},
+    WeakDom,
+};
+use rbx_test_util::dom_tree;
+use rbx_xml::{EncodeOptions, EncodePropertyBehavior, OutputStyle, PropertyFilter, SkipReason};
+
+fn part() -> WeakDom {
+    dom_tree! {
+        DataModel {
+            Folder {
+                Part {
+                    Anchored = false,
+                    Transparency = 0.5f32,
+                    // Off from the default of 0 by less than the tolerance.
+                    Reflectance = 1e-7f32,
+                    size = Vector3::new(4.0, 1.200_001, 2.0),
+                    Color = Color3::new(0.2, 0.4, 0.6),
+                }
+            }
+        }
+    }
+}
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> String {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+/// The names of the properties written for the Part.
+fn property_names(document: &str) -> Vec<String> {
+    let decoded = rbx_xml::from_str_default(document).unwrap();
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let part = decoded.get_by_ref(folder.children()[0]).unwrap();
+
+    let mut names: Vec<_> = part.properties.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn compact() {
+    let _ = env_logger::try_init();
+
+    let document = encode(&part(), EncodeOptions::new().compact());
+
+    assert!(!document.contains('\n'));
+    assert!(document.starts_with(r#"<roblox version="4"><Item class="Folder""#));
+    assert_eq!(property_names(&document), ["Color", "Transparency"]);
+}
+
+#[test]
+fn pretty_indent() {
+    let _ = env_logger::try_init();
+
+    let document = encode(&part(), EncodeOptions::new());
+    assert!(document.contains("\n  <Item class=\"Folder\""));
+    assert!(document.contains("\n    <Item class=\"Part\""));
+
+    let options = EncodeOptions::new().output_style(OutputStyle::Pretty {
+        indent: "\t".to_owned(),
+    });
+    let tabbed = encode(&part(), options);
+    assert!(tabbed.contains("\n\t<Item class=\"Folder\""));
+    assert!(tabbed.contains("\n\t\t<Item class=\"Part\""));
+
+    // Everything is written unless defaults are being skipped.
+    assert_eq!(property_names(&tabbed), property_names(&document));
+    assert_eq!(
+        property_names(&document),
+        ["Anchored", "Color", "Reflectance", "Size", "Transparency"]
+    );
+}
+
+#[test]
+fn skipped_defaults_are_reported() {
+    let _ = env_logger::try_init();
+
+    let mut dom = part();
+    let folder = dom.root().children()[0];
+    let part_ref = dom.get_by_ref(folder).unwrap().children()[0];
+
+    // Properties that aren't in the database have no default to match.
+    dom.get_by_ref_mut(part_ref)
+        .unwrap()
+        .properties
+        .insert("Unknown".to_owned(), Variant::Bool(false));
+
+    let options = EncodeOptions::new()
+        .property_filter(PropertyFilter::SkipDefaults)
+        .property_behavior(EncodePropertyBehavior::WriteUnknown);
+
+    let mut buffer = Vec::new();
+    let summary =
+        rbx_xml::to_writer_with_summary(&mut buffer, &dom, dom.root().children(), options).unwrap();
+
+    let mut skipped: Vec<_> = summary
+        .skipped_properties()
+        .iter()
+        .filter(|skipped| skipped.reason() == SkipReason::DefaultValue)
+        .map(|skipped| skipped.property_name().to_owned())
+        .collect();
+    skipped.sort();
+    assert_eq!(skipped, ["Anchored", "Reflectance", "size"]);
+
+    let document = String::from_utf8(buffer).unwrap();
+    assert!(document.contains(r#"<bool name="Unknown">false</bool>"#));
+}
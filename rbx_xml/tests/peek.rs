@@ -0,0 +1,145 @@
+use std::time::{Duration, Instant};
+
+use rbx_dom_weak::{
+    types::{CFrame, Color3, Matrix3, Vector3},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::EncodeOptions;
+
+/// A place with a few services, one of which is full of parts.
+fn place(parts: usize) -> WeakDom {
+    let workspace = InstanceBuilder::new("Workspace").with_children((0..parts).map(|i| {
+        InstanceBuilder::new("Part")
+            .with_name(format!("Part{}", i))
+            .with_property(
+                "CFrame",
+                CFrame::new(Vector3::new(i as f32, 0.0, 0.0), Matrix3::identity()),
+            )
+            .with_property("Color", Color3::new(0.5, 0.25, i as f32 / parts as f32))
+            .with_property("size", Vector3::new(4.0, 1.0, 2.0))
+    }));
+
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(workspace)
+            .with_child(InstanceBuilder::new("Lighting"))
+            .with_child(
+                InstanceBuilder::new("ReplicatedStorage")
+                    .with_child(InstanceBuilder::new("Folder").with_name("Assets")),
+            ),
+    )
+}
+
+fn encode(dom: &WeakDom) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(
+        &mut buffer,
+        dom,
+        dom.root().children(),
+        EncodeOptions::new(),
+    )
+    .unwrap();
+    buffer
+}
+
+/// The fastest of a few runs of `f`, to keep one slow run from failing the
+/// test.
+fn fastest<F: FnMut()>(mut f: F) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn peek_matches_decode() {
+    let _ = env_logger::try_init();
+
+    let document = r#"<roblox version="4">
+    <Meta name="ExplicitAutoJoints">true</Meta>
+    <Meta name="Tags">SGVsbG8=</Meta>
+    <External>null</External>
+    <!-- Studio doesn't write comments, but they're allowed. <Item class="Fake"> -->
+    <Item class="Workspace" referent="RBX0">
+        <Properties>
+            <bool name="StreamingEnabled">true</bool>
+            <string name="Name">Workspace</string>
+        </Properties>
+        <Item class="Part" referent="RBX1">
+            <Properties>
+                <string name="Name">Baseplate</string>
+            </Properties>
+        </Item>
+    </Item>
+    <Item class="Script" referent="RBX2">
+        <Properties>
+            <ProtectedString name="Source"><![CDATA[print("</Item>")]]></ProtectedString>
+            <string name='Tag' note="a > b">Not the name</string>
+            <string name="Name">Fish &amp; Chips</string>
+        </Properties>
+    </Item>
+    <Item class="Lighting" referent="RBX3">
+    </Item>
+    <SharedStrings>
+        <SharedString md5="aGVsbG8=">aGVsbG8=</SharedString>
+    </SharedStrings>
+</roblox>"#;
+
+    let info = rbx_xml::peek(document.as_bytes()).unwrap();
+    let decoded = rbx_xml::from_str_default(document).unwrap();
+
+    let top_level: Vec<_> = decoded
+        .root()
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = decoded.get_by_ref(*referent).unwrap();
+            (instance.class.clone(), instance.name.clone())
+        })
+        .collect();
+
+    assert_eq!(info.top_level(), top_level.as_slice());
+    assert_eq!(
+        info.top_level()[1],
+        ("Script".to_owned(), "Fish & Chips".to_owned())
+    );
+    assert_eq!(
+        info.top_level()[2],
+        ("Lighting".to_owned(), "Lighting".to_owned())
+    );
+
+    // Tags is a property of the root that was hoisted into a Meta element, not
+    // metadata.
+    assert_eq!(info.metadata().len(), 1);
+    assert_eq!(
+        info.metadata()
+            .get("ExplicitAutoJoints")
+            .map(String::as_str),
+        Some("true")
+    );
+}
+
+#[test]
+fn peek_is_faster_than_decode() {
+    let _ = env_logger::try_init();
+
+    let document = encode(&place(5_000));
+
+    let peek_time = fastest(|| {
+        rbx_xml::peek(document.as_slice()).unwrap();
+    });
+    let decode_time = fastest(|| {
+        rbx_xml::from_reader_default(document.as_slice()).unwrap();
+    });
+
+    assert!(
+        peek_time * 5 < decode_time,
+        "peeking took {:?}, but decoding took {:?}",
+        peek_time,
+        decode_time
+    );
+}
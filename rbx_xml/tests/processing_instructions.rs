@@ -0,0 +1,112 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+use rbx_test_util::{encode_xml, first_child};
+use rbx_xml::{DecodeErrorKind, DecodeOptions, EncodeOptions, ProcessingInstruction};
+
+/// A model with a processing instruction before `<roblox>`, one directly
+/// inside it, and one in an unexpected spot among an Item's properties.
+static MODEL: &str = include_str!("../test-files/processing-instructions.rbxmx");
+
+#[test]
+fn top_level_instructions_are_kept() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(MODEL.as_bytes(), DecodeOptions::new()).unwrap();
+
+    assert_eq!(
+        summary.processing_instructions(),
+        [
+            ProcessingInstruction::new("generator").with_data("my-tool 1.2"),
+            ProcessingInstruction::new("build-id").with_data("4f2a9c"),
+        ]
+    );
+
+    let value = dom.get_by_ref(first_child(&dom).children()[0]).unwrap();
+    assert_eq!(value.name, "Version");
+    assert_eq!(value.properties.get("Value"), Some(&Variant::from("1.2")));
+}
+
+#[test]
+fn item_instructions_are_dropped_with_a_diagnostic() {
+    let _ = env_logger::try_init();
+
+    let (_dom, summary) =
+        rbx_xml::from_reader_with_summary(MODEL.as_bytes(), DecodeOptions::new()).unwrap();
+
+    let diagnostics = summary.diagnostics();
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].kind(), DecodeErrorKind::MalformedDocument);
+    assert_eq!(diagnostics[0].line(), 12);
+    assert!(
+        diagnostics[0].message().contains("<?checksum?>"),
+        "{}",
+        diagnostics[0].message()
+    );
+}
+
+#[test]
+fn provenance_round_trip() {
+    let _ = env_logger::try_init();
+
+    let dom =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+    let provenance = vec![
+        ProcessingInstruction::new("generator").with_data("my-tool 1.2"),
+        ProcessingInstruction::new("reviewed"),
+    ];
+
+    let encoded = encode_xml(
+        &dom,
+        EncodeOptions::new().processing_instructions(provenance.clone()),
+    );
+    assert!(
+        encoded
+            .starts_with("<roblox version=\"4\">\n  <?generator my-tool 1.2?>\n  <?reviewed?>\n"),
+        "{}",
+        encoded
+    );
+
+    let (decoded, summary) =
+        rbx_xml::from_reader_with_summary(encoded.as_bytes(), DecodeOptions::new()).unwrap();
+    assert_eq!(summary.processing_instructions(), provenance.as_slice());
+    assert!(summary.diagnostics().is_empty());
+    assert_eq!(decoded.root().children().len(), 1);
+
+    // Writing the decoded instructions back out gives the same document.
+    let reencoded = encode_xml(
+        &decoded,
+        EncodeOptions::new().processing_instructions(summary.processing_instructions().to_vec()),
+    );
+    assert_eq!(reencoded, encoded);
+}
+
+#[test]
+fn invalid_instructions_are_an_error() {
+    let _ = env_logger::try_init();
+
+    let dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+
+    for instruction in [
+        ProcessingInstruction::new("xml").with_data("version=\"1.0\""),
+        ProcessingInstruction::new("generator").with_data("one ?> two"),
+        ProcessingInstruction::new("1st"),
+        ProcessingInstruction::new(""),
+    ] {
+        let mut buffer = Vec::new();
+        let error = rbx_xml::to_writer(
+            &mut buffer,
+            &dom,
+            &[],
+            EncodeOptions::new().processing_instructions(vec![instruction.clone()]),
+        )
+        .unwrap_err();
+
+        assert!(
+            error.to_string().contains("processing instruction"),
+            "{:?}: {}",
+            instruction,
+            error
+        );
+        assert!(buffer.is_empty(), "{:?}", instruction);
+    }
+}
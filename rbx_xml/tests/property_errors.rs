@@ -0,0 +1,125 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    WeakDom,
+};
+use rbx_xml::{DecodeErrorKind, DecodeOptions};
+
+/// A model with three properties whose values can't be read: a BinaryString
+/// that isn't base64, a Vector3 with a component that isn't a number, and a
+/// value with a type that doesn't exist.
+static DOCUMENT: &str = include_str!("../test-files/corrupted-properties.rbxmx");
+
+fn options() -> DecodeOptions {
+    DecodeOptions::new().recover_from_property_errors(true)
+}
+
+fn find(dom: &WeakDom, name: &str) -> Ref {
+    dom.descendants(dom.root_ref())
+        .find(|instance| instance.name == name)
+        .unwrap_or_else(|| panic!("no instance named {}", name))
+        .referent()
+}
+
+#[test]
+fn strict_mode_rejects_corrupted_properties() {
+    let _ = env_logger::try_init();
+
+    let error = rbx_xml::from_str_default(DOCUMENT).unwrap_err();
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedValue);
+    assert_eq!(error.line(), 5);
+}
+
+#[test]
+fn keeps_the_whole_tree() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = rbx_xml::from_reader_with_summary(DOCUMENT.as_bytes(), options()).unwrap();
+
+    assert!(summary.recovered());
+    assert_eq!(summary.instance_count(), 4);
+
+    let model = find(&dom, "Damaged");
+    let part = find(&dom, "Body");
+    let link = find(&dom, "Link");
+    find(&dom, "Intact");
+
+    assert_eq!(dom.get_by_ref(part).unwrap().parent(), model);
+    assert_eq!(dom.get_by_ref(link).unwrap().parent(), part);
+
+    // The properties around the broken ones are all still there.
+    let model_props = &dom.get_by_ref(model).unwrap().properties;
+    assert!(!model_props.contains_key("Tags"));
+    assert_eq!(model_props.get("PrimaryPart"), Some(&Variant::Ref(part)));
+
+    let part_props = &dom.get_by_ref(part).unwrap().properties;
+    assert!(!part_props.contains_key("Size"));
+    assert_eq!(part_props.get("Anchored"), Some(&Variant::Bool(true)));
+    assert_eq!(part_props.get("Transparency"), Some(&Variant::Float32(0.5)));
+
+    let link_props = &dom.get_by_ref(link).unwrap().properties;
+    assert_eq!(link_props.get("Value"), Some(&Variant::Ref(model)));
+
+    let diagnostics: Vec<_> = summary
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.instance(),
+                diagnostic.property_name(),
+                diagnostic.kind(),
+                diagnostic.line(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        diagnostics,
+        [
+            (
+                Some(model),
+                Some("Tags"),
+                DecodeErrorKind::MalformedValue,
+                5
+            ),
+            (
+                Some(part),
+                Some("size"),
+                DecodeErrorKind::MalformedValue,
+                14
+            ),
+            (
+                Some(link),
+                Some("Value"),
+                DecodeErrorKind::UnsupportedType,
+                22
+            ),
+        ]
+    );
+}
+
+#[test]
+fn structural_errors_are_still_fatal() {
+    let _ = env_logger::try_init();
+
+    let document = r#"<roblox version="4">
+    <Item referent="RBX0">
+        <Properties>
+            <string name="Name">NoClass</string>
+        </Properties>
+    </Item>
+</roblox>"#;
+
+    let error = rbx_xml::from_str(document, options()).unwrap_err();
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedDocument);
+
+    let document = r#"<roblox version="4">
+    <Item class="Folder" referent="RBX0">
+        <Properties>
+            <string>Unnamed</string>
+        </Properties>
+    </Item>
+</roblox>"#;
+
+    let error = rbx_xml::from_str(document, options()).unwrap_err();
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedDocument);
+}
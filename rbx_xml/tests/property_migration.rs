@@ -0,0 +1,88 @@
+use rbx_dom_weak::{content_hash, types::Variant, ContentHashOptions, WeakDom};
+use rbx_xml::{DecodeErrorKind, DecodeOptions, DecodePropertyBehavior};
+
+/// A model shaped like one saved by Roblox Studio in 2014, with properties
+/// under their old names and types, and properties that aren't saved anymore.
+static MODEL_2014: &str = include_str!("../test-files/migration-2014.rbxmx");
+
+/// The same model as it's saved by Roblox Studio today.
+static MODEL_CURRENT: &str = include_str!("../test-files/migration-current.rbxmx");
+
+fn decode(document: &str, options: DecodeOptions) -> WeakDom {
+    rbx_xml::from_str(document, options).unwrap()
+}
+
+fn hash(dom: &WeakDom) -> [u8; 32] {
+    content_hash(dom, dom.root_ref(), ContentHashOptions::new())
+}
+
+/// The name of each property on the model's Part, in order.
+fn part_properties(dom: &WeakDom) -> Vec<String> {
+    let model = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    let part = dom.get_by_ref(model.children()[0]).unwrap();
+
+    let mut names: Vec<_> = part.properties.keys().cloned().collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn old_and_new_files_match() {
+    let _ = env_logger::try_init();
+
+    let options = || {
+        DecodeOptions::new()
+            .property_behavior(DecodePropertyBehavior::ReadUnknown)
+            .drop_unserialized_properties(true)
+    };
+
+    let old = decode(MODEL_2014, options());
+    let current = decode(MODEL_CURRENT, options());
+
+    assert_eq!(
+        part_properties(&old),
+        [
+            "Anchored",
+            "CFrame",
+            "Color",
+            "FormFactor",
+            "Shape",
+            "Size",
+            "Transparency"
+        ]
+    );
+    assert_eq!(part_properties(&old), part_properties(&current));
+    assert_eq!(hash(&old), hash(&current));
+}
+
+#[test]
+fn unserialized_properties_are_unknown_by_default() {
+    let _ = env_logger::try_init();
+
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    let old = decode(MODEL_2014, options);
+
+    let model = old.get_by_ref(old.root().children()[0]).unwrap();
+    let part = old.get_by_ref(model.children()[0]).unwrap();
+    assert_eq!(
+        part.properties.get("Elasticity"),
+        Some(&Variant::Float32(0.5))
+    );
+
+    let error = rbx_xml::from_str(
+        MODEL_2014,
+        DecodeOptions::new().property_behavior(DecodePropertyBehavior::ErrorOnUnknown),
+    )
+    .unwrap_err();
+    assert_eq!(error.kind(), DecodeErrorKind::UnknownProperty);
+
+    // Dropping them means the file decodes even when unknown properties are
+    // errors.
+    rbx_xml::from_str(
+        MODEL_2014,
+        DecodeOptions::new()
+            .property_behavior(DecodePropertyBehavior::ErrorOnUnknown)
+            .drop_unserialized_properties(true),
+    )
+    .unwrap();
+}
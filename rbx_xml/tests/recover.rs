@@ -0,0 +1,126 @@
+use rbx_dom_weak::types::{Ref, Variant};
+use rbx_xml::DecodeOptions;
+
+static DOCUMENT: &str = r#"<roblox version="4">
+    <Item class="Folder" referent="RBX1">
+        <Properties>
+            <string name="Name">Intact</string>
+        </Properties>
+    </Item>
+    <Item class="IntValue" referent="RBX2">
+        <Properties>
+            <string name="Name">Broken</string>
+            <int name="Value">not a number</int>
+        </Properties>
+        <Item class="Folder" referent="RBX3">
+            <Properties>
+                <string name="Name">ChildOfBroken</string>
+            </Properties>
+        </Item>
+    </Item>
+    <Item class="Folder" referent="RBX4">
+        <Properties>
+            <string name="Name">Parent</string>
+        </Properties>
+        <Item class="Folder" referent="RBX5">
+            <Properties>
+                <string name="Name">UnknownTag</string>
+            </Properties>
+            <Bogus />
+        </Item>
+        <Item class="ObjectValue" referent="RBX6">
+            <Properties>
+                <string name="Name">Pointer</string>
+                <Ref name="Value">RBX2</Ref>
+            </Properties>
+        </Item>
+    </Item>
+</roblox>"#;
+
+fn recover_options() -> DecodeOptions {
+    DecodeOptions::new().recover(true)
+}
+
+#[test]
+fn strict_mode_rejects_damage() {
+    let _ = env_logger::try_init();
+
+    let error = rbx_xml::from_str(DOCUMENT, DecodeOptions::new()).unwrap_err();
+    assert_eq!(error.line(), 10);
+}
+
+#[test]
+fn salvages_surviving_items() {
+    let _ = env_logger::try_init();
+
+    let (tree, summary) =
+        rbx_xml::from_reader_with_summary(DOCUMENT.as_bytes(), recover_options()).unwrap();
+
+    assert!(summary.recovered());
+    assert_eq!(summary.instance_count(), 3);
+
+    let root = tree.root();
+    let names: Vec<_> = root
+        .children()
+        .iter()
+        .map(|id| tree.get_by_ref(*id).unwrap().name.as_str())
+        .collect();
+    assert_eq!(names, ["Intact", "Parent"]);
+
+    let parent = tree.get_by_ref(root.children()[1]).unwrap();
+    assert_eq!(parent.children().len(), 1);
+
+    // The ObjectValue pointed at an instance that was dropped, so it should be
+    // left pointing at nothing.
+    let pointer = tree.get_by_ref(parent.children()[0]).unwrap();
+    assert_eq!(pointer.name, "Pointer");
+    assert_eq!(
+        pointer.properties.get("Value"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+
+    let lines: Vec<_> = summary
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| diagnostic.line())
+        .collect();
+    assert_eq!(lines, [10, 26]);
+}
+
+#[test]
+fn recovery_is_reported_as_absent_for_intact_files() {
+    let document = r#"<roblox version="4">
+        <Item class="Folder" referent="RBX1">
+            <Properties>
+                <string name="Name">Intact</string>
+            </Properties>
+        </Item>
+    </roblox>"#;
+
+    let (tree, summary) =
+        rbx_xml::from_reader_with_summary(document.as_bytes(), recover_options()).unwrap();
+
+    assert!(!summary.recovered());
+    assert!(summary.diagnostics().is_empty());
+    assert_eq!(tree.root().children().len(), 1);
+}
+
+#[test]
+fn malformed_xml_is_still_fatal() {
+    let _ = env_logger::try_init();
+
+    let document = r#"<roblox version="4">
+        <Item class="Folder" referent="RBX1">
+            <Properties>
+                <string name="Name">Intact</string>
+            </Properties>
+        </Item>
+        <Item class="Folder" referent="RBX2">
+            <Properties>
+                <string name="Name">Mismatched</strin>
+            </Properties>
+        </Item>
+    </roblox>"#;
+
+    assert!(rbx_xml::from_str(document, recover_options()).is_err());
+}
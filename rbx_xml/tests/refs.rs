@@ -0,0 +1,243 @@
+use rbx_dom_weak::{
+    types::{Ref, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{
+    DecodeOptions, DecodePropertyBehavior, EncodeOptions, EncodePropertyBehavior, StreamEvent,
+    UnresolvedRefBehavior,
+};
+
+fn find_child(dom: &WeakDom, parent: Ref, name: &str) -> Ref {
+    dom.get_by_ref(parent)
+        .unwrap()
+        .children()
+        .iter()
+        .copied()
+        .find(|child| dom.get_by_ref(*child).unwrap().name == name)
+        .unwrap()
+}
+
+fn encode(dom: &WeakDom, ids: &[Ref]) -> String {
+    let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::NoReflection);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, ids, options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+/// A Model whose PrimaryPart comes after it in the file, and a value that
+/// points to a sibling of the Model that comes after both of them.
+#[test]
+fn forward_refs() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="ObjectValue" referent="RBX1">
+                <Properties>
+                    <string name="Name">Target</string>
+                    <Ref name="Value">RBX4</Ref>
+                </Properties>
+            </Item>
+            <Item class="Model" referent="RBX2">
+                <Properties>
+                    <string name="Name">House</string>
+                    <Ref name="PrimaryPart">RBX3</Ref>
+                </Properties>
+                <Item class="Part" referent="RBX3">
+                    <Properties>
+                        <string name="Name">Floor</string>
+                    </Properties>
+                </Item>
+            </Item>
+            <Item class="Part" referent="RBX4">
+                <Properties>
+                    <string name="Name">Door</string>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let dom = rbx_xml::from_str_default(document).unwrap();
+    let root = dom.root_ref();
+
+    let house = find_child(&dom, root, "House");
+    let floor = find_child(&dom, house, "Floor");
+    let door = find_child(&dom, root, "Door");
+    let target = find_child(&dom, root, "Target");
+
+    assert_eq!(
+        dom.get_by_ref(house).unwrap().properties.get("PrimaryPart"),
+        Some(&Variant::Ref(floor))
+    );
+    assert_eq!(
+        dom.get_by_ref(target).unwrap().properties.get("Value"),
+        Some(&Variant::Ref(door))
+    );
+}
+
+#[test]
+fn shared_mesh_data() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="MeshPart" referent="RBX1">
+                <Properties>
+                    <string name="Name">Roof</string>
+                    <SharedString name="PhysicalConfigData">bWVzaA==</SharedString>
+                </Properties>
+            </Item>
+            <Item class="MeshPart" referent="RBX2">
+                <Properties>
+                    <string name="Name">Chimney</string>
+                    <SharedString name="PhysicalConfigData">bWVzaA==</SharedString>
+                </Properties>
+            </Item>
+            <SharedStrings>
+                <SharedString md5="bWVzaA==">bWVzaCBkYXRh</SharedString>
+            </SharedStrings>
+        </roblox>
+    "#;
+
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+    let dom = rbx_xml::from_str(document, options).unwrap();
+
+    let values: Vec<_> = dom
+        .root()
+        .children()
+        .iter()
+        .map(|child| {
+            match dom
+                .get_by_ref(*child)
+                .unwrap()
+                .properties
+                .get("PhysicalConfigData")
+            {
+                Some(Variant::SharedString(value)) => value.clone(),
+                other => panic!("expected a SharedString, got {:?}", other),
+            }
+        })
+        .collect();
+
+    assert_eq!(values[0].data(), b"mesh data");
+    assert_eq!(values[0].hash(), values[1].hash());
+
+    // Writing the parts back out keeps one copy of the data.
+    let encoded = encode(&dom, dom.root().children());
+    assert_eq!(encoded.matches("<SharedString md5=").count(), 1);
+    assert!(encoded.rfind("<SharedStrings>") > encoded.rfind("<Item"));
+}
+
+#[test]
+fn dangling_refs_are_null() {
+    let _ = env_logger::try_init();
+
+    let part = InstanceBuilder::new("Part").with_name("Floor");
+    let part_ref = part.referent();
+    let outside = InstanceBuilder::new("Part").with_name("Outside");
+    let outside_ref = outside.referent();
+
+    let mut dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(
+                InstanceBuilder::new("Model")
+                    .with_name("House")
+                    .with_property("PrimaryPart", part_ref)
+                    .with_child(part)
+                    .with_child(
+                        InstanceBuilder::new("ObjectValue")
+                            .with_name("Link")
+                            .with_property("Value", outside_ref),
+                    ),
+            )
+            .with_child(outside),
+    );
+
+    let house = find_child(&dom, dom.root_ref(), "House");
+    dom.destroy(part_ref);
+
+    // Neither the destroyed part nor the one outside of the model are in the
+    // document, so both Refs are written as null.
+    let encoded = encode(&dom, &[house]);
+    assert!(encoded.contains(r#"<Ref name="PrimaryPart">null</Ref>"#));
+    assert!(encoded.contains(r#"<Ref name="Value">null</Ref>"#));
+
+    let decoded = rbx_xml::from_str(
+        &encoded,
+        DecodeOptions::new().property_behavior(DecodePropertyBehavior::NoReflection),
+    )
+    .unwrap();
+    let house = decoded.root().children()[0];
+    assert_eq!(
+        decoded
+            .get_by_ref(house)
+            .unwrap()
+            .properties
+            .get("PrimaryPart"),
+        Some(&Variant::Ref(Ref::none()))
+    );
+}
+
+/// Referents are written as the order each instance was reached in, and read
+/// back exactly as they're written.
+#[test]
+fn encoded_referents() {
+    let _ = env_logger::try_init();
+
+    let part = InstanceBuilder::new("Part").with_name("Floor");
+    let part_ref = part.referent();
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Model")
+            .with_name("House")
+            .with_property("PrimaryPart", part_ref)
+            .with_child(part),
+    );
+
+    let encoded = encode(&dom, &[dom.root_ref()]);
+    assert!(encoded.contains(r#"<Item class="Model" referent="0">"#));
+    assert!(encoded.contains(r#"<Item class="Part" referent="1">"#));
+    assert!(encoded.contains(r#"<Ref name="PrimaryPart">1</Ref>"#));
+
+    let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::NoReflection);
+    let instances: Vec<_> = rbx_xml::decode_instances_iter(encoded.as_bytes(), options)
+        .filter_map(|event| match event.unwrap() {
+            StreamEvent::Instance(instance) => Some(instance),
+            _ => None,
+        })
+        .collect();
+
+    let model = &instances[0];
+    assert_eq!(model.referent(), Some("0"));
+    assert_eq!(model.parent(), None);
+    assert_eq!(model.refs(), [("PrimaryPart".to_owned(), "1".to_owned())]);
+
+    let floor = &instances[1];
+    assert_eq!(floor.referent(), Some("1"));
+    assert_eq!(floor.parent(), Some("0"));
+    assert!(floor.refs().is_empty());
+}
+
+#[test]
+fn unresolved_ref_message() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="ObjectValue" referent="RBX1">
+                <Properties>
+                    <string name="Name">Link</string>
+                    <Ref name="Value">RBX9</Ref>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let options = DecodeOptions::new().unresolved_ref_behavior(UnresolvedRefBehavior::Error);
+    let error = rbx_xml::from_str(document, options).unwrap_err();
+
+    assert!(error.to_string().contains(
+        "Property ObjectValue.Value refers to 'RBX9', which isn't the referent of any Item in \
+         the document"
+    ));
+}
@@ -0,0 +1,182 @@
+use rbx_dom_weak::{
+    types::{BinaryString, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{EncodeError, EncodeOptions, RootPropertyBehavior};
+
+/// A place whose DataModel has attributes and tags set on it.
+fn place_with_root_properties() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_property(
+                "AttributesSerialize",
+                BinaryString::from(
+                    b"\x01\x00\x00\x00\x04\x00\x00\x00Mode\x02\x04\x00\x00\x00Hard".to_vec(),
+                ),
+            )
+            .with_property("Tags", BinaryString::from(b"Level".to_vec()))
+            .with_child(InstanceBuilder::new("Workspace")),
+    )
+}
+
+fn encode(dom: &WeakDom, behavior: RootPropertyBehavior) -> Result<String, EncodeError> {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(
+        &mut buffer,
+        dom,
+        dom.root().children(),
+        EncodeOptions::new().root_property_behavior(behavior),
+    )?;
+
+    Ok(String::from_utf8(buffer).unwrap())
+}
+
+#[test]
+fn drop_by_default() {
+    let _ = env_logger::try_init();
+
+    let dom = place_with_root_properties();
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_default(&mut buffer, &dom, dom.root().children()).unwrap();
+    let encoded = String::from_utf8(buffer).unwrap();
+
+    assert_eq!(encoded, encode(&dom, RootPropertyBehavior::Drop).unwrap());
+    assert!(!encoded.contains("<Meta"), "{}", encoded);
+
+    let decoded = rbx_xml::from_str_default(&encoded).unwrap();
+    assert!(decoded.root().properties.is_empty());
+}
+
+#[test]
+fn error_if_present() {
+    let _ = env_logger::try_init();
+
+    let dom = place_with_root_properties();
+    let error = encode(&dom, RootPropertyBehavior::Error).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "The root instance has a property named AttributesSerialize, which can't be written to a place"
+    );
+
+    // Roots without properties are fine.
+    let empty = WeakDom::new(
+        InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Workspace")),
+    );
+    encode(&empty, RootPropertyBehavior::Error).unwrap();
+}
+
+#[test]
+fn error_ignores_roots_that_are_written() {
+    let _ = env_logger::try_init();
+
+    let mut dom = place_with_root_properties();
+
+    // When the root is written as an Item, its properties go with it. They
+    // also don't apply when writing a subtree that isn't directly under the
+    // root.
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(
+        &mut buffer,
+        &dom,
+        &[dom.root_ref()],
+        EncodeOptions::new().root_property_behavior(RootPropertyBehavior::Error),
+    )
+    .unwrap();
+
+    let workspace = dom.root().children()[0];
+    let model = InstanceBuilder::new("Model");
+    let model_ref = model.referent();
+    dom.insert(workspace, model);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(
+        &mut buffer,
+        &dom,
+        &[model_ref],
+        EncodeOptions::new().root_property_behavior(RootPropertyBehavior::Error),
+    )
+    .unwrap();
+}
+
+#[test]
+fn hoist_into_meta() {
+    let _ = env_logger::try_init();
+
+    let dom = place_with_root_properties();
+    let encoded = encode(&dom, RootPropertyBehavior::HoistIntoMeta).unwrap();
+
+    assert!(
+        encoded.contains(r#"<Meta name="AttributesSerialize">"#),
+        "{}",
+        encoded
+    );
+    assert!(
+        encoded.contains(r#"<Meta name="Tags">TGV2ZWw=</Meta>"#),
+        "{}",
+        encoded
+    );
+
+    let decoded = rbx_xml::from_str_default(&encoded).unwrap();
+    assert_eq!(decoded.root().properties, dom.root().properties);
+
+    let workspace = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    assert_eq!(workspace.class, "Workspace");
+}
+
+#[test]
+fn hoist_rejects_unmapped_properties() {
+    let _ = env_logger::try_init();
+
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_property("Tags", BinaryString::from(b"Level".to_vec()))
+            .with_property("Genre", "Adventure")
+            .with_child(InstanceBuilder::new("Workspace")),
+    );
+
+    let error = encode(&dom, RootPropertyBehavior::HoistIntoMeta).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The root instance has a property named Genre, which can't be written as a <Meta> element"
+    );
+
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_property("Tags", "Level")
+            .with_child(InstanceBuilder::new("Workspace")),
+    );
+
+    let error = encode(&dom, RootPropertyBehavior::HoistIntoMeta).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Property DataModel.Tags is expected to be of type BinaryString, but it was of type String"
+    );
+}
+
+#[test]
+fn other_meta_is_not_attached() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Meta name="ExplicitAutoJoints">true</Meta>
+            <Meta name="Tags">TGV2ZWw=</Meta>
+            <Item class="Workspace" referent="RBX0">
+                <Properties>
+                    <string name="Name">Workspace</string>
+                </Properties>
+            </Item>
+        </roblox>
+    "#;
+
+    let decoded = rbx_xml::from_str_default(document).unwrap();
+    let root_properties = &decoded.root().properties;
+
+    assert_eq!(root_properties.len(), 1);
+    assert_eq!(
+        root_properties.get("Tags"),
+        Some(&Variant::BinaryString(b"Level".to_vec().into()))
+    );
+}
@@ -0,0 +1,146 @@
+use rbx_dom_weak::{
+    types::{SharedString, Vector3},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{
+    DecodeOptions, EncodeOptions, EncodePropertyBehavior, SerializeProgress, SharedStringsPosition,
+    XmlSerializer,
+};
+
+static LEGACY_PLACE: &str = include_str!("../test-files/legacy-workspace.rbxlx");
+
+/// A model with nested instances, a Ref, and SharedStrings that are used more
+/// than once. It's encoded without reflection, which would leave out the
+/// SharedStrings.
+fn sample_model() -> WeakDom {
+    let mesh = SharedString::new(b"mesh data".to_vec());
+    let part = InstanceBuilder::new("Part")
+        .with_name("Base")
+        .with_property("Size", Vector3::new(4.0, 1.0, 2.0))
+        .with_property("Anchored", true);
+    let part_ref = part.referent();
+
+    WeakDom::new(
+        InstanceBuilder::new("DataModel").with_child(
+            InstanceBuilder::new("Model")
+                .with_name("House")
+                .with_property("PrimaryPart", part_ref)
+                .with_child(part)
+                .with_child(
+                    InstanceBuilder::new("Folder")
+                        .with_name("Meshes")
+                        .with_child(
+                            InstanceBuilder::new("MeshPart")
+                                .with_name("Roof")
+                                .with_property("PhysicalConfigData", mesh.clone()),
+                        )
+                        .with_child(
+                            InstanceBuilder::new("MeshPart")
+                                .with_name("Chimney")
+                                .with_property("PhysicalConfigData", mesh),
+                        )
+                        .with_child(InstanceBuilder::new("Folder").with_name("Empty")),
+                )
+                .with_child(
+                    InstanceBuilder::new("Script")
+                        .with_name("Door")
+                        .with_property("Source", "print('knock')"),
+                ),
+        ),
+    )
+}
+
+fn sample_options() -> EncodeOptions {
+    EncodeOptions::new().property_behavior(EncodePropertyBehavior::NoReflection)
+}
+
+fn encode_at_once(dom: &WeakDom, options: EncodeOptions) -> String {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+#[test]
+fn one_shot_output() {
+    let _ = env_logger::try_init();
+
+    let dom = sample_model();
+
+    insta::assert_snapshot!(encode_at_once(&dom, sample_options()));
+    insta::assert_snapshot!(
+        "one_shot_output_shared_strings_after",
+        encode_at_once(
+            &dom,
+            sample_options().shared_strings_position(SharedStringsPosition::AfterItems)
+        )
+    );
+}
+
+#[test]
+fn one_shot_legacy_output() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = rbx_xml::from_reader_with_summary(
+        LEGACY_PLACE.as_bytes(),
+        DecodeOptions::new().legacy_elements(true),
+    )
+    .unwrap();
+    let options = EncodeOptions::new().legacy_elements(summary.legacy_elements().to_vec());
+
+    insta::assert_snapshot!(encode_at_once(&dom, options));
+}
+
+/// Encodes with an `XmlSerializer` that writes one instance per step,
+/// returning the document and the number of steps it took.
+fn encode_in_steps(dom: &WeakDom, options: EncodeOptions) -> (String, usize) {
+    let mut serializer = XmlSerializer::new(Vec::new(), dom, dom.root().children(), options);
+    let mut document = Vec::new();
+    let mut steps = 0;
+
+    loop {
+        let progress = serializer.step(1).unwrap();
+        steps += 1;
+        document.append(serializer.output_mut());
+
+        if progress == SerializeProgress::Done {
+            break;
+        }
+    }
+
+    (String::from_utf8(document).unwrap(), steps)
+}
+
+#[test]
+fn steps_match_one_shot() {
+    let _ = env_logger::try_init();
+
+    let dom = sample_model();
+
+    for &position in &[
+        SharedStringsPosition::BeforeItems,
+        SharedStringsPosition::AfterItems,
+    ] {
+        let options = sample_options().shared_strings_position(position);
+        let (document, steps) = encode_in_steps(&dom, options.clone());
+
+        assert_eq!(document, encode_at_once(&dom, options));
+        assert_eq!(steps, 7);
+    }
+}
+
+#[test]
+fn steps_match_one_shot_legacy() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = rbx_xml::from_reader_with_summary(
+        LEGACY_PLACE.as_bytes(),
+        DecodeOptions::new().legacy_elements(true),
+    )
+    .unwrap();
+    let options = EncodeOptions::new().legacy_elements(summary.legacy_elements().to_vec());
+
+    let (document, steps) = encode_in_steps(&dom, options.clone());
+
+    assert_eq!(document, encode_at_once(&dom, options));
+    assert_eq!(steps, 4);
+}
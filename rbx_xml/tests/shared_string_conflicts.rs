@@ -0,0 +1,49 @@
+use rbx_dom_weak::types::Variant;
+use rbx_xml::{DecodeErrorKind, DecodeOptions};
+
+/// A union whose SharedString dictionary lists its key three times: twice
+/// with the same five bytes, then once with seven different ones.
+static CONFLICTING: &str = include_str!("../test-files/conflicting-shared-strings.rbxmx");
+
+#[test]
+fn conflicting_entries_are_an_error() {
+    let _ = env_logger::try_init();
+
+    let error = rbx_xml::from_str_default(CONFLICTING).unwrap_err();
+
+    assert_eq!(error.kind(), DecodeErrorKind::MalformedDocument);
+    assert_eq!(error.line(), 11);
+    assert_eq!(
+        error.to_string(),
+        "line 11, column 59: The SharedString dictionary has two different entries with the \
+         key 'X5R87GzoVqKYjBgqdQ1lNg==', of 5 and 7 bytes. Decode with DecodeOptions::recover \
+         enabled to keep the first one."
+    );
+}
+
+#[test]
+fn first_entry_is_kept_when_recovering() {
+    let _ = env_logger::try_init();
+
+    let options = DecodeOptions::new().recover(true);
+    let (dom, summary) =
+        rbx_xml::from_reader_with_summary(CONFLICTING.as_bytes(), options).unwrap();
+
+    let union = dom.get_by_ref(dom.root().children()[0]).unwrap();
+    match union.properties.get("PhysicalConfigData") {
+        Some(Variant::SharedString(value)) => assert_eq!(value.data(), b"first"),
+        other => panic!("expected a SharedString, got {:?}", other),
+    }
+
+    assert!(summary.recovered());
+    assert_eq!(summary.diagnostics().len(), 1);
+
+    let diagnostic = &summary.diagnostics()[0];
+    assert_eq!(diagnostic.kind(), DecodeErrorKind::MalformedDocument);
+    assert_eq!(diagnostic.line(), 11);
+    assert_eq!(
+        diagnostic.message(),
+        "Kept the first of two SharedStrings with the key 'X5R87GzoVqKYjBgqdQ1lNg==' and \
+         dropped the second"
+    );
+}
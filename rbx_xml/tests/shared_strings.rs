@@ -0,0 +1,213 @@
+use rbx_dom_weak::{
+    types::{SharedString, Variant},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{EncodeOptions, EncodePropertyBehavior, SharedStringsPosition};
+
+/// Base64-encoded MD5 hashes of "Hello" and "World", as written by Roblox
+/// Studio.
+const HELLO_KEY: &str = "ixqZU8RhEpaoJ6v4xHgE1w==";
+const WORLD_KEY: &str = "9aeSTmIehMkoCpon4by39g==";
+
+#[test]
+fn keys_are_md5_of_contents() {
+    let _ = env_logger::try_init();
+
+    let hello = SharedString::new(b"Hello".to_vec());
+    let world = SharedString::new(b"World".to_vec());
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_children(vec![
+        InstanceBuilder::new("Folder").with_property("Data", hello.clone()),
+        InstanceBuilder::new("Folder").with_property("Data", hello),
+        InstanceBuilder::new("Folder").with_property("Data", world),
+    ]));
+
+    let options = rbx_xml::EncodeOptions::new()
+        .property_behavior(rbx_xml::EncodePropertyBehavior::NoReflection);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+    let document = String::from_utf8(buffer).unwrap();
+
+    // Each key should show up once in the dictionary and once for every
+    // property that refers to it.
+    assert_eq!(
+        document.matches(&format!(r#"md5="{}""#, HELLO_KEY)).count(),
+        1
+    );
+    assert_eq!(
+        document.matches(&format!(r#"md5="{}""#, WORLD_KEY)).count(),
+        1
+    );
+    assert_eq!(document.matches(&format!(">{}<", HELLO_KEY)).count(), 2);
+    assert_eq!(document.matches(&format!(">{}<", WORLD_KEY)).count(), 1);
+}
+
+/// Keys are only used to look values up in the dictionary, so files with keys
+/// that aren't MD5 hashes should still decode.
+#[test]
+fn arbitrary_keys_are_accepted() {
+    let _ = env_logger::try_init();
+
+    let document = r#"
+        <roblox version="4">
+            <Item class="Folder" referent="RBX1">
+                <Properties>
+                    <SharedString name="Data">not-a-real-hash</SharedString>
+                </Properties>
+            </Item>
+            <SharedStrings>
+                <SharedString md5="not-a-real-hash">SGVsbG8=</SharedString>
+            </SharedStrings>
+        </roblox>
+    "#;
+
+    let options = rbx_xml::DecodeOptions::new()
+        .property_behavior(rbx_xml::DecodePropertyBehavior::NoReflection);
+    let tree = rbx_xml::from_str(document, options).unwrap();
+    let instance = tree.get_by_ref(tree.root().children()[0]).unwrap();
+
+    match instance.properties.get("Data") {
+        Some(Variant::SharedString(value)) => assert_eq!(value.data(), b"Hello"),
+        other => panic!("expected a SharedString, got {:?}", other),
+    }
+}
+
+/// A tree whose SharedStrings are first used in the order "Charlie", "Alpha",
+/// "Bravo", which isn't the order of their keys or their contents.
+fn ordering_tree() -> WeakDom {
+    let alpha = SharedString::new(b"Alpha".to_vec());
+    let bravo = SharedString::new(b"Bravo".to_vec());
+    let charlie = SharedString::new(b"Charlie".to_vec());
+
+    WeakDom::new(InstanceBuilder::new("DataModel").with_children(vec![
+        InstanceBuilder::new("Folder")
+            .with_property("Data", charlie.clone())
+            .with_child(InstanceBuilder::new("Folder").with_property("Data", alpha.clone())),
+        InstanceBuilder::new("Folder").with_property("Data", charlie),
+        InstanceBuilder::new("Folder")
+            .with_property("Data", bravo)
+            .with_property("Other", alpha),
+    ]))
+}
+
+fn encode_ordering_tree(position: SharedStringsPosition) -> String {
+    let tree = ordering_tree();
+    let options = EncodeOptions::new()
+        .property_behavior(EncodePropertyBehavior::NoReflection)
+        .shared_strings_position(position);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+/// The contents of each entry in the document's SharedString dictionary, in
+/// the order they're written.
+fn dictionary_contents(document: &str) -> Vec<Vec<u8>> {
+    let start = document.find("<SharedStrings>").unwrap();
+    let end = document.find("</SharedStrings>").unwrap();
+
+    document[start..end]
+        .split("</SharedString>")
+        .filter(|entry| entry.contains("md5="))
+        .filter_map(|entry| entry.rsplit('>').next())
+        .map(|contents| contents.trim())
+        .map(|contents| base64::decode(contents).unwrap())
+        .collect()
+}
+
+#[test]
+fn dictionary_keeps_first_use_order() {
+    let _ = env_logger::try_init();
+
+    let expected: Vec<Vec<u8>> = vec![b"Charlie".to_vec(), b"Alpha".to_vec(), b"Bravo".to_vec()];
+
+    for position in [
+        SharedStringsPosition::AfterItems,
+        SharedStringsPosition::BeforeItems,
+    ] {
+        let document = encode_ordering_tree(position);
+        assert_eq!(dictionary_contents(&document), expected, "{:?}", position);
+    }
+}
+
+#[test]
+fn dictionary_position() {
+    let _ = env_logger::try_init();
+
+    let after = encode_ordering_tree(SharedStringsPosition::AfterItems);
+    assert!(after.find("<SharedStrings>").unwrap() > after.rfind("</Item>").unwrap());
+
+    let before = encode_ordering_tree(SharedStringsPosition::BeforeItems);
+    assert!(before.find("</SharedStrings>").unwrap() < before.find("<Item").unwrap());
+
+    // Studio writes the dictionary last, so that's what we do by default.
+    let tree = ordering_tree();
+    let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::NoReflection);
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), after);
+}
+
+#[test]
+fn both_positions_decode_the_same() {
+    let _ = env_logger::try_init();
+
+    let decode = |document: &str| {
+        let options = rbx_xml::DecodeOptions::new()
+            .property_behavior(rbx_xml::DecodePropertyBehavior::NoReflection);
+        let tree = rbx_xml::from_str(document, options).unwrap();
+
+        let mut values = Vec::new();
+        let mut to_visit = tree.root().children().to_vec();
+
+        while let Some(referent) = to_visit.pop() {
+            let instance = tree.get_by_ref(referent).unwrap();
+
+            for (name, value) in &instance.properties {
+                if let Variant::SharedString(value) = value {
+                    values.push((name.clone(), value.data().to_vec()));
+                }
+            }
+
+            to_visit.extend_from_slice(instance.children());
+        }
+
+        values
+    };
+
+    let after = decode(&encode_ordering_tree(SharedStringsPosition::AfterItems));
+    let before = decode(&encode_ordering_tree(SharedStringsPosition::BeforeItems));
+
+    assert_eq!(after.len(), 5);
+    assert_eq!(after, before);
+}
+
+/// Collecting SharedStrings up front should only pick up the ones that
+/// actually end up in the file.
+#[test]
+fn before_items_skips_properties_that_are_not_written() {
+    let _ = env_logger::try_init();
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_child(
+        InstanceBuilder::new("Folder").with_property("Data", SharedString::new(b"Hello".to_vec())),
+    ));
+
+    let encode = |behavior| {
+        let options = EncodeOptions::new()
+            .property_behavior(behavior)
+            .shared_strings_position(SharedStringsPosition::BeforeItems);
+
+        let mut buffer = Vec::new();
+        rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+        String::from_utf8(buffer).unwrap()
+    };
+
+    // Folder.Data isn't a real property, so it's skipped by default.
+    let ignored = encode(EncodePropertyBehavior::IgnoreUnknown);
+    assert!(!ignored.contains("<SharedStrings>"));
+
+    let written = encode(EncodePropertyBehavior::WriteUnknown);
+    assert_eq!(dictionary_contents(&written), vec![b"Hello".to_vec()]);
+}
@@ -0,0 +1,130 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+use rbx_xml::{EncodeError, EncodeOptions, SingletonBehavior};
+
+/// A place whose Workspace has two Terrains, as can happen when places are
+/// merged by a tool.
+fn duplicate_terrain() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel").with_child(
+            InstanceBuilder::new("Workspace")
+                .with_child(InstanceBuilder::new("Terrain"))
+                .with_child(InstanceBuilder::new("Part"))
+                .with_child(InstanceBuilder::new("Part"))
+                .with_child(InstanceBuilder::new("Terrain")),
+        ),
+    )
+}
+
+/// A place built from scratch, without any of the instances that Studio
+/// creates in every place.
+fn bare_place() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Workspace").with_child(InstanceBuilder::new("Part")))
+            .with_child(
+                InstanceBuilder::new("StarterPlayer")
+                    .with_child(InstanceBuilder::new("StarterCharacterScripts")),
+            ),
+    )
+}
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> Result<String, EncodeError> {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, dom, dom.root().children(), options)?;
+    Ok(String::from_utf8(buffer).unwrap())
+}
+
+/// The classes of the children of each of the root's children.
+fn child_classes(dom: &WeakDom) -> Vec<(String, Vec<String>)> {
+    dom.root()
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = dom.get_by_ref(*referent).unwrap();
+            let children = instance
+                .children()
+                .iter()
+                .map(|child| dom.get_by_ref(*child).unwrap().class.clone())
+                .collect();
+
+            (instance.class.clone(), children)
+        })
+        .collect()
+}
+
+#[test]
+fn duplicate_singletons_are_errors() {
+    let _ = env_logger::try_init();
+
+    let options = EncodeOptions::new().ensure_singletons(SingletonBehavior::ErrorOnDuplicates);
+    let error = encode(&duplicate_terrain(), options.clone()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Found more than one Terrain in DataModel.Workspace, which Roblox expects only one of"
+    );
+
+    // The top-level instances are siblings in the document.
+    let dom = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Workspace"))
+            .with_child(InstanceBuilder::new("Workspace")),
+    );
+    let error = encode(&dom, options).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Found more than one Workspace in the top level of the document, which Roblox expects only one of"
+    );
+
+    // Warnings don't stop anything from being written.
+    let options = EncodeOptions::new().ensure_singletons(SingletonBehavior::WarnOnDuplicates);
+    let document = encode(&duplicate_terrain(), options).unwrap();
+    assert_eq!(document.matches(r#"class="Terrain""#).count(), 2);
+}
+
+#[test]
+fn missing_singletons_are_synthesized() {
+    let _ = env_logger::try_init();
+
+    let dom = bare_place();
+    let options = EncodeOptions::new().synthesize_singletons(true);
+    let document = encode(&dom, options).unwrap();
+
+    let decoded = rbx_xml::from_str_default(&document).unwrap();
+    assert_eq!(
+        child_classes(&decoded),
+        [
+            (
+                "Workspace".to_owned(),
+                vec!["Terrain".to_owned(), "Part".to_owned()]
+            ),
+            (
+                "StarterPlayer".to_owned(),
+                vec![
+                    "StarterPlayerScripts".to_owned(),
+                    "StarterCharacterScripts".to_owned()
+                ]
+            ),
+        ]
+    );
+
+    // Synthesized instances get their defaults from the reflection database.
+    let workspace = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+    let terrain = decoded.get_by_ref(workspace.children()[0]).unwrap();
+    assert_eq!(terrain.name, "Terrain");
+    assert!(terrain.properties.contains_key("WaterWaveSize"));
+
+    // The dom that was written doesn't change.
+    assert_eq!(dom.descendants(dom.root_ref()).count(), 5);
+}
+
+#[test]
+fn singletons_are_left_alone_by_default() {
+    let _ = env_logger::try_init();
+
+    let document = encode(&duplicate_terrain(), EncodeOptions::new()).unwrap();
+    assert_eq!(document.matches(r#"class="Terrain""#).count(), 2);
+
+    let document = encode(&bare_place(), EncodeOptions::new()).unwrap();
+    assert!(!document.contains("Terrain"));
+    assert!(!document.contains("StarterPlayerScripts"));
+}
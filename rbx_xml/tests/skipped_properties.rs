@@ -0,0 +1,123 @@
+use rbx_dom_weak::{
+    types::{Variant, Vector3},
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{EncodeError, EncodeOptions, EncodeSummary, SkipReason};
+
+/// A part with one property that's left out for each reason.
+fn part_with_skipped_properties() -> WeakDom {
+    WeakDom::new(
+        InstanceBuilder::new("Folder").with_name("Root").with_child(
+            InstanceBuilder::new("Part")
+                .with_property("Anchored", true)
+                .with_property("NotARealProperty", Variant::Int32(5))
+                .with_property("Position", Vector3::new(1.0, 2.0, 3.0))
+                .with_property("Mass", 10.0f32),
+        ),
+    )
+}
+
+fn encode(dom: &WeakDom, options: EncodeOptions) -> Result<EncodeSummary, EncodeError> {
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer_with_summary(&mut buffer, dom, dom.root().children(), options)
+}
+
+#[test]
+fn skipped_properties_are_listed() {
+    let _ = env_logger::try_init();
+
+    let dom = part_with_skipped_properties();
+    let summary = encode(&dom, EncodeOptions::new().omit_default_names(true)).unwrap();
+
+    let skipped: Vec<_> = summary
+        .skipped_properties()
+        .iter()
+        .map(|skipped| {
+            (
+                skipped.instance(),
+                skipped.property_name(),
+                skipped.reason(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        skipped,
+        [
+            ("Root.Part", "Name", SkipReason::DefaultValue),
+            ("Root.Part", "NotARealProperty", SkipReason::NotInDatabase),
+            ("Root.Part", "Position", SkipReason::NotSerializable),
+            ("Root.Part", "Mass", SkipReason::ReadOnly),
+        ]
+    );
+    assert_eq!(summary.property_count(), 1);
+}
+
+#[test]
+fn nothing_skipped() {
+    let _ = env_logger::try_init();
+
+    let dom = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Part").with_property("Anchored", true)),
+    );
+
+    let summary = encode(&dom, EncodeOptions::new()).unwrap();
+    assert!(summary.skipped_properties().is_empty());
+}
+
+#[test]
+fn error_on_chosen_reasons() {
+    let _ = env_logger::try_init();
+
+    let dom = part_with_skipped_properties();
+    let options = || EncodeOptions::new().omit_default_names(true);
+
+    let cases = [
+        (
+            SkipReason::DefaultValue,
+            "Property Name on instance Root.Part would be left out because it has its default value",
+        ),
+        (
+            SkipReason::NotInDatabase,
+            "Property NotARealProperty on instance Root.Part would be left out because it isn't in \
+             the reflection database",
+        ),
+        (
+            SkipReason::NotSerializable,
+            "Property Position on instance Root.Part would be left out because it isn't serialized \
+             by Roblox",
+        ),
+        (
+            SkipReason::ReadOnly,
+            "Property Mass on instance Root.Part would be left out because it's read-only",
+        ),
+    ];
+
+    for (reason, message) in &cases {
+        let error = encode(&dom, options().error_on_skip(vec![*reason])).unwrap_err();
+        assert_eq!(error.to_string(), *message);
+    }
+
+    // Reasons that aren't chosen are still only listed in the summary.
+    let summary = encode(
+        &dom,
+        EncodeOptions::new().error_on_skip(vec![SkipReason::DefaultValue]),
+    )
+    .unwrap();
+    assert_eq!(summary.skipped_properties().len(), 3);
+}
+
+#[test]
+fn shared_strings_before_items_are_not_listed_twice() {
+    let _ = env_logger::try_init();
+
+    let dom = part_with_skipped_properties();
+    let summary = encode(
+        &dom,
+        EncodeOptions::new().shared_strings_position(rbx_xml::SharedStringsPosition::BeforeItems),
+    )
+    .unwrap();
+
+    assert_eq!(summary.skipped_properties().len(), 3);
+}
@@ -0,0 +1,98 @@
+use rbx_dom_weak::WeakDom;
+use rbx_reflection::ClassFilter;
+use rbx_xml::{DecodeOptions, DecodeSummary};
+
+/// A model with most Items on their own lines, except for a folder with a
+/// non-ASCII name whose child is on the same line, after the name.
+static MODEL: &str = include_str!("../test-files/source-locations.rbxmx");
+
+fn decode(options: DecodeOptions) -> (WeakDom, DecodeSummary) {
+    rbx_xml::from_reader_with_summary(MODEL.as_bytes(), options).unwrap()
+}
+
+/// The name, line, and column of every decoded instance, in the order they're
+/// found walking the tree depth first.
+fn locations(dom: &WeakDom, summary: &DecodeSummary) -> Vec<(String, usize, usize)> {
+    dom.descendants(dom.root_ref())
+        .skip(1)
+        .map(|instance| {
+            let location = summary.source_locations()[&instance.referent()];
+            (instance.name.clone(), location.line(), location.column())
+        })
+        .collect()
+}
+
+#[test]
+fn lines_and_columns() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = decode(DecodeOptions::new().source_locations(true));
+
+    assert_eq!(
+        locations(&dom, &summary),
+        [
+            ("Vehicle".to_owned(), 2, 2),
+            ("Chassis".to_owned(), 6, 3),
+            ("Café ☕".to_owned(), 11, 3),
+            ("Label".to_owned(), 11, 100),
+            ("Main".to_owned(), 13, 2),
+        ]
+    );
+}
+
+#[test]
+fn byte_offsets() {
+    let _ = env_logger::try_init();
+
+    let (dom, summary) = decode(DecodeOptions::new().source_locations(true));
+    assert_eq!(summary.source_locations().len(), 5);
+
+    for instance in dom.descendants(dom.root_ref()).skip(1) {
+        let location = summary.source_locations()[&instance.referent()];
+        let rest = &MODEL[location.byte_offset() as usize..];
+        let tag = format!("<Item class=\"{}\"", instance.class);
+
+        assert!(
+            rest.starts_with(&tag),
+            "{} at {} should start with {}",
+            instance.name,
+            location,
+            tag
+        );
+    }
+
+    let label = dom
+        .descendants(dom.root_ref())
+        .find(|instance| instance.name == "Label")
+        .unwrap();
+
+    // The folder's name earlier on the line is 6 characters, but 9 bytes.
+    assert_eq!(
+        summary.source_locations()[&label.referent()].byte_offset(),
+        354
+    );
+}
+
+#[test]
+fn off_by_default() {
+    let _ = env_logger::try_init();
+
+    let (_dom, summary) = decode(DecodeOptions::new());
+    assert!(summary.source_locations().is_empty());
+}
+
+#[test]
+fn filtered_instances_have_no_location() {
+    let _ = env_logger::try_init();
+
+    let options = DecodeOptions::new()
+        .source_locations(true)
+        .class_filter(ClassFilter::new(["Part", "StringValue"].iter().copied()));
+    let (dom, summary) = decode(options);
+
+    assert_eq!(
+        locations(&dom, &summary),
+        [("Chassis".to_owned(), 6, 3), ("Label".to_owned(), 11, 100)]
+    );
+    assert_eq!(summary.source_locations().len(), 2);
+}
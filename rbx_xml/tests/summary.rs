@@ -0,0 +1,35 @@
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+/// Ensures that the byte counts reported for encoding and decoding the same
+/// document both match the size of that document.
+#[test]
+fn byte_counts_match_file_size() {
+    let _ = env_logger::try_init();
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+        InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("IntValue")),
+    ]));
+
+    let mut buffer = Vec::new();
+    let encode_summary = rbx_xml::to_writer_with_summary(
+        &mut buffer,
+        &tree,
+        tree.root().children(),
+        rbx_xml::EncodeOptions::new(),
+    )
+    .unwrap();
+
+    assert_eq!(encode_summary.bytes_written(), buffer.len() as u64);
+    assert_eq!(encode_summary.instance_count(), 3);
+    assert_eq!(encode_summary.property_count(), 1);
+
+    let (decoded, decode_summary) =
+        rbx_xml::from_reader_with_summary(buffer.as_slice(), rbx_xml::DecodeOptions::new())
+            .unwrap();
+
+    assert_eq!(decode_summary.bytes_read(), buffer.len() as u64);
+    assert_eq!(decode_summary.instance_count(), 3);
+    assert_eq!(decode_summary.property_count(), 1);
+    assert_eq!(decoded.root().children().len(), 2);
+}
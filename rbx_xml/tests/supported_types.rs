@@ -0,0 +1,100 @@
+use rbx_dom_weak::{
+    types::{
+        Attributes, Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
+        ColorSequenceKeypoint, Content, Enum, Faces, Font, Matrix3, NumberRange, NumberSequence,
+        NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref, Region3, Region3int16,
+        SharedString, UDim, UDim2, UniqueId, Variant, Vector2, Vector2int16, Vector3, Vector3int16,
+    },
+    InstanceBuilder, WeakDom,
+};
+use rbx_xml::{EncodeOptions, EncodePropertyBehavior};
+
+/// One value of every type that a `Variant` can hold.
+fn every_type() -> Vec<Variant> {
+    let origin = Vector3::new(0.0, 0.0, 0.0);
+
+    vec![
+        Axes::all().into(),
+        BinaryString::from(b"binary".to_vec()).into(),
+        true.into(),
+        BrickColor::BrightRed.into(),
+        CFrame::new(origin, Matrix3::identity()).into(),
+        Color3::new(1.0, 0.5, 0.25).into(),
+        Color3uint8::new(255, 128, 64).into(),
+        ColorSequence {
+            keypoints: vec![
+                ColorSequenceKeypoint::new(0.0, Color3::new(0.0, 0.0, 0.0)),
+                ColorSequenceKeypoint::new(1.0, Color3::new(1.0, 1.0, 1.0)),
+            ],
+        }
+        .into(),
+        Content::from("rbxassetid://1").into(),
+        Enum::from_u32(1).into(),
+        Faces::all().into(),
+        1.5f32.into(),
+        1.5f64.into(),
+        1i32.into(),
+        1i64.into(),
+        NumberRange::new(0.0, 1.0).into(),
+        NumberSequence {
+            keypoints: vec![
+                NumberSequenceKeypoint::new(0.0, 0.0, 0.0),
+                NumberSequenceKeypoint::new(1.0, 1.0, 0.0),
+            ],
+        }
+        .into(),
+        PhysicalProperties::Default.into(),
+        Ray::new(origin, Vector3::new(0.0, 1.0, 0.0)).into(),
+        Rect::new(Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0)).into(),
+        Ref::none().into(),
+        Region3::new(origin, Vector3::new(1.0, 1.0, 1.0)).into(),
+        Region3int16::new(Vector3int16::new(0, 0, 0), Vector3int16::new(1, 1, 1)).into(),
+        SharedString::new(b"shared".to_vec()).into(),
+        "string".into(),
+        UDim::new(0.5, 10).into(),
+        UDim2::new(UDim::new(0.5, 10), UDim::new(0.5, 10)).into(),
+        Vector2::new(1.0, 2.0).into(),
+        Vector2int16::new(1, 2).into(),
+        Vector3::new(1.0, 2.0, 3.0).into(),
+        Vector3int16::new(1, 2, 3).into(),
+        Attributes::new().into(),
+        Variant::OptionalCFrame(None),
+        Font::default().into(),
+        UniqueId::new(1, 2, 3).into(),
+    ]
+}
+
+/// `supported_types` comes from the same macro as the code that writes each
+/// type, so it should list exactly the types that rbx_xml can write.
+#[test]
+fn supported_types_match_encoding() {
+    let _ = env_logger::try_init();
+
+    for value in every_type() {
+        let ty = value.ty();
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_property("Value", value));
+
+        let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::NoReflection);
+        let result = rbx_xml::to_writer(Vec::new(), &dom, &[dom.root_ref()], options);
+
+        assert_eq!(
+            result.is_ok(),
+            rbx_xml::supported_types().contains(&ty),
+            "{:?}: {:?}",
+            ty,
+            result
+        );
+    }
+}
+
+#[test]
+fn unsupported_properties() {
+    let database = rbx_reflection_database::get();
+    let unsupported: Vec<_> = database
+        .unsupported_properties(rbx_xml::supported_types())
+        .into_iter()
+        .map(|(class, property)| format!("{}.{}", class, property))
+        .collect();
+
+    insta::assert_snapshot!(unsupported.join("\n"));
+}
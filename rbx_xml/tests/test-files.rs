@@ -1,6 +1,5 @@
-use std::{fs, path::Path};
-
 use rbx_dom_weak::DomViewer;
+use rbx_test_util::Fixture;
 
 macro_rules! test_models {
     ( $( $test_name: ident : $file_name: expr,)* ) => {
@@ -9,10 +8,7 @@ macro_rules! test_models {
             fn $test_name() {
                 let _ = env_logger::try_init();
 
-                let mut path = Path::new("../test-files/models").join($file_name);
-                path.push("xml.rbxmx");
-
-                let contents = fs::read_to_string(path).unwrap();
+                let contents = Fixture::model($file_name).read_xml();
                 let dom = rbx_xml::from_str_default(&contents).unwrap();
 
                 let mut viewer = DomViewer::new();
@@ -0,0 +1,104 @@
+//! Multi-threaded tools share doms between threads and decode many files at
+//! once. These tests make sure that's possible and doesn't misbehave. They're
+//! also useful to run under a thread sanitizer, like with:
+//!
+//! ```bash
+//! RUSTFLAGS="-Zsanitizer=thread" cargo +nightly test -Zbuild-std \
+//!     --target x86_64-unknown-linux-gnu -p rbx_xml --test threads
+//! ```
+
+use std::{sync::Arc, thread};
+
+use rbx_dom_weak::{
+    types::{SharedString, Variant},
+    InstanceBuilder, WeakDom,
+};
+
+#[allow(dead_code)]
+fn types_are_send_and_sync() {
+    fn trait_test<T: Send + Sync>() {}
+
+    trait_test::<rbx_xml::DecodeError>();
+    trait_test::<rbx_xml::EncodeError>();
+    trait_test::<rbx_xml::DecodeOptions>();
+    trait_test::<rbx_xml::EncodeOptions>();
+}
+
+/// Creates a document with the given number of instances, all of which share
+/// the same SharedString value.
+fn make_document(instance_count: usize) -> Vec<u8> {
+    let shared = SharedString::new(b"shared between threads".to_vec());
+
+    let children = (0..instance_count).map(|i| {
+        InstanceBuilder::new("Folder")
+            .with_name(format!("Folder {}", i))
+            .with_property("Data", shared.clone())
+    });
+
+    let tree = WeakDom::new(InstanceBuilder::new("DataModel").with_children(children));
+
+    let options = rbx_xml::EncodeOptions::new()
+        .property_behavior(rbx_xml::EncodePropertyBehavior::NoReflection);
+
+    let mut buffer = Vec::new();
+    rbx_xml::to_writer(&mut buffer, &tree, tree.root().children(), options).unwrap();
+    buffer
+}
+
+fn decode(document: &[u8]) -> WeakDom {
+    let options = rbx_xml::DecodeOptions::new()
+        .property_behavior(rbx_xml::DecodePropertyBehavior::NoReflection);
+
+    rbx_xml::from_reader(document, options).unwrap()
+}
+
+#[test]
+fn decode_concurrently() {
+    let _ = env_logger::try_init();
+
+    let documents: Vec<_> = (1..=4).map(|i| make_document(i * 25)).collect();
+
+    let handles: Vec<_> = documents
+        .into_iter()
+        .map(|document| thread::spawn(move || decode(&document).root().children().len()))
+        .collect();
+
+    let counts: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(counts, [25, 50, 75, 100]);
+}
+
+#[test]
+fn read_dom_from_many_threads() {
+    let _ = env_logger::try_init();
+
+    let dom = Arc::new(decode(&make_document(100)));
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let dom = Arc::clone(&dom);
+
+            thread::spawn(move || {
+                let mut total_len = 0;
+
+                for referent in dom.root().children() {
+                    let instance = dom.get_by_ref(*referent).unwrap();
+
+                    match instance.properties.get("Data") {
+                        Some(Variant::SharedString(value)) => total_len += value.data().len(),
+                        other => panic!("expected a SharedString, got {:?}", other),
+                    }
+                }
+
+                total_len
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 100 * "shared between threads".len());
+    }
+}